@@ -9,6 +9,7 @@
 
 use rat_logger::{LoggerBuilder, LevelFilter, Level, FileConfig, NetworkConfig, config::Record, Logger};
 use rat_logger::config::Metadata;
+use rat_logger::test_util::UdpCollector;
 use std::sync::Arc;
 use std::path::PathBuf;
 
@@ -30,10 +31,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             auth_token: None,
             app_id: Some("main".to_string()),
         }),
-        args: "应用程序启动".to_string(),
+        args: "应用程序启动".into(),
         module_path: Some("basic_usage".to_string()),
         file: Some("basic_usage.rs".to_string()),
         line: Some(29),
+        ..Default::default()
     };
     terminal_logger.log(&record);
 
@@ -50,6 +52,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     let file_logger = LoggerBuilder::new()
@@ -65,20 +68,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             auth_token: None,
             app_id: Some("file_app".to_string()),
         }),
-        args: "这是一条文件日志".to_string(),
+        args: "这是一条文件日志".into(),
         module_path: Some("basic_usage".to_string()),
         file: Some("basic_usage.rs".to_string()),
         line: Some(60),
+        ..Default::default()
     };
     file_logger.log(&file_record);
 
     // 3. 网络日志
     println!("\n3. 网络日志:");
+    // 本地收集器代替日志服务器，接收并解码发出的数据包
+    let collector = UdpCollector::bind().expect("无法绑定UDP收集器");
     let network_config = NetworkConfig {
-        server_addr: "127.0.0.1".to_string(),
-        server_port: 54321,
         auth_token: "example_token".to_string(),
         app_id: "network_app".to_string(),
+        ..collector.network_config()
     };
 
     let network_logger = LoggerBuilder::new()
@@ -94,12 +99,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             auth_token: Some("example_token".to_string()),
             app_id: Some("network_app".to_string()),
         }),
-        args: "这是一条网络日志".to_string(),
+        args: "这是一条网络日志".into(),
         module_path: Some("basic_usage".to_string()),
         file: Some("basic_usage.rs".to_string()),
         line: Some(90),
+        ..Default::default()
     };
     network_logger.log(&network_record);
+    if collector.wait_for(1, std::time::Duration::from_secs(2)) {
+        for record in collector.records() {
+            println!("收集器 {} 收到: [{}] {}", collector.addr(), record.app_id.unwrap_or_default(), record.message);
+        }
+    }
 
     // 4. 多输出日志
     println!("\n4. 多输出日志 (终端+文件):");
@@ -114,6 +125,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     let multi_logger = LoggerBuilder::new()
@@ -130,10 +142,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             auth_token: None,
             app_id: Some("multi_app".to_string()),
         }),
-        args: "这是一条多输出日志".to_string(),
+        args: "这是一条多输出日志".into(),
         module_path: Some("basic_usage".to_string()),
         file: Some("basic_usage.rs".to_string()),
         line: Some(126),
+        ..Default::default()
     };
     multi_logger.log(&multi_record);
 
@@ -161,10 +174,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 auth_token: None,
                 app_id: Some("level_app".to_string()),
             }),
-            args: message.to_string(),
+            args: message.into(),
             module_path: Some("basic_usage".to_string()),
             file: Some("basic_usage.rs".to_string()),
             line: Some(160),
+            ..Default::default()
         };
         level_logger.log(&record);
     }
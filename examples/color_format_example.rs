@@ -29,6 +29,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         target: "\x1b[94m".to_string(),      // 亮蓝色
         file: "\x1b[95m".to_string(),       // 亮紫色
         message: "\x1b[97m".to_string(),      // 亮白色
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     // 1.2 暗黑主题
@@ -42,6 +45,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         target: "\x1b[38;5;45m".to_string(),   // 青色
         file: "\x1b[38;5;201m".to_string(),   // 粉色
         message: "\x1b[38;5;252m".to_string(), // 浅灰色
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     // 1.3 高对比度主题
@@ -55,6 +61,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         target: "\x1b[1;34m".to_string(),     // 粗体蓝色
         file: "\x1b[1;35m".to_string(),      // 粗体紫色
         message: "\x1b[0m".to_string(),       // 重置
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     // 1.4 柔和主题
@@ -68,6 +77,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         target: "\x1b[38;5;104m".to_string(),  // 柔和蓝紫色
         file: "\x1b[38;5;133m".to_string(),   // 柔和品红
         message: "\x1b[38;5;251m".to_string(), // 极浅灰色
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     println!("   ✓ 已创建4种颜色主题\n");
@@ -87,6 +99,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "{{\"timestamp\":\"{timestamp}\",\"level\":\"{level}\",\"target\":\"{target}\",\"message\":\"{message}\"}}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 2.2 简洁风格格式
@@ -101,6 +118,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "{timestamp} [{level}] {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 2.3 详细风格格式
@@ -115,6 +137,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "[{timestamp}] {level} | {target} | {file}:{line} | {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 2.4 自定义分隔符格式
@@ -129,6 +156,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "┌─ {timestamp}\n├─ {level}\n├─ {target}\n├─ {file}:{line}\n└─ {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     println!("   ✓ 已创建4种格式配置\n");
@@ -222,6 +254,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         target: "\x1b[34m".to_string(),      // 蓝色（与默认相同）
         file: "\x1b[35m".to_string(),       // 紫色（与默认相同）
         message: "\x1b[0m".to_string(),      // 重置（与默认相同）
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     let term_config5 = rat_logger::handler::term::TermConfig {
@@ -242,7 +277,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 4.2 动态颜色配置（基于内容）
     println!("\n   4.2 动态颜色配置示例:");
-    for (i, level) in [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace].iter().enumerate() {
+    for (i, level) in Level::ALL.iter().enumerate() {
         let dynamic_theme = create_dynamic_theme(i);
         let term_config = rat_logger::handler::term::TermConfig {
             format: Some(minimal_format.clone()),
@@ -339,6 +374,9 @@ fn create_dynamic_theme(seed: usize) -> rat_logger::ColorConfig {
         target: "\x1b[94m".to_string(),
         file: "\x1b[95m".to_string(),
         message: "\x1b[0m".to_string(),
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     }
 }
 
@@ -355,9 +393,10 @@ fn create_test_record(
             auth_token: None,
             app_id: Some("color_format_example".to_string()),
         }),
-        args: message.to_string(),
+        args: message.into(),
         module_path: Some("color_format_example".to_string()),
         file: Some("color_format_example.rs".to_string()),
         line: Some(42),
+        ..Default::default()
     }
 }
\ No newline at end of file
@@ -25,6 +25,7 @@ fn main() {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     // 使用LoggerBuilder创建多输出日志器（终端 + 文件）
@@ -44,10 +45,11 @@ fn main() {
             auth_token: None,
             app_id: Some("main".to_string()),
         }),
-        args: "使用多输出处理器，日志将同时输出到终端和文件".to_string(),
+        args: "使用多输出处理器，日志将同时输出到终端和文件".into(),
         module_path: Some("composite_handler".to_string()),
         file: Some("composite_handler.rs".to_string()),
         line: Some(38),
+        ..Default::default()
     };
 
     logger.log(&record);
@@ -59,10 +61,11 @@ fn main() {
             auth_token: None,
             app_id: Some("main".to_string()),
         }),
-        args: "多输出处理器测试".to_string(),
+        args: "多输出处理器测试".into(),
         module_path: Some("composite_handler".to_string()),
         file: Some("composite_handler.rs".to_string()),
         line: Some(53),
+        ..Default::default()
     };
 
     logger.log(&warn_record);
@@ -74,10 +77,11 @@ fn main() {
             auth_token: None,
             app_id: Some("main".to_string()),
         }),
-        args: "错误日志将通过多个处理器并行处理".to_string(),
+        args: "错误日志将通过多个处理器并行处理".into(),
         module_path: Some("composite_handler".to_string()),
         file: Some("composite_handler.rs".to_string()),
         line: Some(66),
+        ..Default::default()
     };
 
     logger.log(&error_record);
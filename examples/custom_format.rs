@@ -0,0 +1,67 @@
+//! rat_logger 自定义格式化函数示例
+//!
+//! 演示如何通过构建器为终端和文件处理器注册自定义格式化函数
+//!
+//! ⚠️  重要提醒：
+//! - 自定义格式化函数负责写出完整的一行（包括换行符）
+//! - 自定义格式化函数不做控制字符处理，需要时请自行转义
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, FileConfig, Logger};
+use rat_logger::config::{Record, Metadata};
+use rat_logger::handler::term::TermConfig;
+use std::io::Write;
+use std::sync::Arc;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== rat_logger 自定义格式化函数示例 ===\n");
+
+    let log_dir = PathBuf::from("./custom_format_logs");
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        .add_terminal_with_formatter(TermConfig::default(), |buf: &mut dyn Write, record: &Record| {
+            writeln!(buf, ">> [{}] {}", record.metadata.level, record.args)
+        })
+        .add_file_with_formatter(
+            FileConfig {
+                log_dir: log_dir.clone(),
+                ..Default::default()
+            },
+            |buf: &mut dyn Write, record: &Record| {
+                writeln!(
+                    buf,
+                    "{{\"level\":\"{}\",\"target\":\"{}\",\"message\":{:?}}}",
+                    record.metadata.level, record.metadata.target, record.args
+                )
+            },
+        )
+        .build();
+
+    logger.log(&create_test_record(Level::Info, "custom_format", "服务启动"));
+    logger.log(&create_test_record(Level::Warn, "custom_format", "磁盘空间不足"));
+    logger.flush_sync(2000)?;
+
+    println!("\n文件内容:");
+    for entry in std::fs::read_dir(&log_dir)?.flatten() {
+        if entry.path().extension().and_then(|s| s.to_str()) == Some("log") {
+            print!("{}", std::fs::read_to_string(entry.path())?);
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+    Ok(())
+}
+
+fn create_test_record(level: Level, target: &str, message: &str) -> Record {
+    Record {
+        metadata: Arc::new(Metadata {
+            level,
+            target: target.to_string(),
+            auth_token: None,
+            app_id: None,
+        }),
+        args: message.into(),
+        ..Default::default()
+    }
+}
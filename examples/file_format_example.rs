@@ -28,6 +28,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "{level} {timestamp} {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 2. 创建详细格式配置
@@ -43,6 +48,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "[{level}] {timestamp} {target}:{line} - {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 3. 创建JSON格式配置
@@ -58,6 +68,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "{{\"timestamp\":\"{timestamp}\",\"level\":\"{level}\",\"target\":\"{target}\",\"message\":\"{message}\"}}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     println!("   ✓ 已创建配置\n");
@@ -78,6 +93,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: None, // 使用默认格式
+        ..Default::default()
     };
 
     let logger1 = LoggerBuilder::new()
@@ -102,6 +118,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: Some(simple_format.clone()),
+        ..Default::default()
     };
 
     let logger2 = LoggerBuilder::new()
@@ -126,6 +143,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: Some(detailed_format.clone()),
+        ..Default::default()
     };
 
     let logger3 = LoggerBuilder::new()
@@ -150,6 +168,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: Some(json_format.clone()),
+        ..Default::default()
     };
 
     let logger4 = LoggerBuilder::new()
@@ -161,8 +180,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     logger4.log(&create_test_record(Level::Error, "json_test", "错误消息"));
     logger4.log(&create_test_record(Level::Info, "json_test", "信息消息"));
 
-    // 等待文件写入完成
-    std::thread::sleep(std::time::Duration::from_millis(100));
+    // 同步点：返回后此前记录的日志已写入文件
+    for logger in [&logger1, &logger2, &logger3, &logger4] {
+        logger.sync_point(std::time::Duration::from_secs(2))?;
+    }
 
     println!("\n5. 查看生成的日志文件内容:");
 
@@ -248,9 +269,10 @@ fn create_test_record(
             auth_token: None,
             app_id: Some("file_format_example".to_string()),
         }),
-        args: message.to_string(),
+        args: message.into(),
         module_path: Some("file_format_example".to_string()),
         file: Some("file_format_example.rs".to_string()),
         line: Some(42),
+        ..Default::default()
     }
 }
\ No newline at end of file
@@ -27,6 +27,7 @@ fn main() {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     let logger = LoggerBuilder::new()
@@ -46,10 +47,11 @@ fn main() {
                 auth_token: None,
                 app_id: Some("main".to_string()),
             }),
-            args: format!("这是第 {} 条日志，用于测试文件轮转功能", i),
+            args: format!("这是第 {} 条日志，用于测试文件轮转功能", i).into(),
             module_path: Some("file_rotation".to_string()),
             file: Some("file_rotation.rs".to_string()),
             line: Some(42),
+            ..Default::default()
         };
         logger.log(&record);
 
@@ -60,10 +62,11 @@ fn main() {
                 auth_token: None,
                 app_id: Some("main".to_string()),
             }),
-            args: format!("警告日志 {}", i),
+            args: format!("警告日志 {}", i).into(),
             module_path: Some("file_rotation".to_string()),
             file: Some("file_rotation.rs".to_string()),
             line: Some(58),
+            ..Default::default()
         };
         logger.log(&warn_record);
 
@@ -74,10 +77,11 @@ fn main() {
                 auth_token: None,
                 app_id: Some("main".to_string()),
             }),
-            args: format!("错误日志 {}", i),
+            args: format!("错误日志 {}", i).into(),
             module_path: Some("file_rotation".to_string()),
             file: Some("file_rotation.rs".to_string()),
             line: Some(73),
+            ..Default::default()
         };
         logger.log(&error_record);
 
@@ -61,6 +61,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: true, // 同步写入，确保输出格式正确
         format: None,
+        ..Default::default()
     };
 
     let custom_logger = LoggerBuilder::new()
@@ -78,10 +79,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             auth_token: None,
             app_id: Some("macro_app".to_string()),
         }),
-        args: "自定义日志器记录的消息".to_string(),
+        args: "自定义日志器记录的消息".into(),
         module_path: Some("macro_example".to_string()),
         file: Some("macro_example.rs".to_string()),
         line: Some(71),
+        ..Default::default()
     };
     custom_logger.log(&record);
     println!();
@@ -32,6 +32,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "{level} {timestamp} {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 2. 创建详细格式配置
@@ -47,6 +52,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "[{level}] {timestamp} {target}:{line} - {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 3. 创建颜色配置
@@ -61,6 +71,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         target: "\x1b[94m".to_string(),      // 亮蓝色
         file: "\x1b[95m".to_string(),       // 亮紫色
         message: "\x1b[97m".to_string(),      // 亮白色
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     println!("   ✓ 已创建配置\n");
@@ -103,6 +116,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: true, // 同步写入，确保格式不错位
         format: Some(detailed_format.clone()),
+        ..Default::default()
     };
 
     // 重新初始化为文件输出（开发模式允许）
@@ -138,6 +152,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: true, // 同步写入，确保格式不错位
         format: Some(detailed_format.clone()),
+        ..Default::default()
     };
 
     LoggerBuilder::new()
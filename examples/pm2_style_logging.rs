@@ -28,6 +28,7 @@ fn main() {
         compress_on_drop: true, // 程序结束时强制压缩
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     // 2. 错误日志文件 - 程序结束时压缩
@@ -42,6 +43,7 @@ fn main() {
         compress_on_drop: true, // 程序结束时强制压缩
         force_sync: true, // 错误日志同步写入，确保不丢失
         format: None,
+        ..Default::default()
     };
 
     // 3. 访问日志文件 - 不在程序结束时压缩
@@ -56,6 +58,7 @@ fn main() {
         compress_on_drop: false, // 不在程序结束时压缩
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     // 4. 性能监控日志文件 - 不在程序结束时压缩
@@ -70,6 +73,7 @@ fn main() {
         compress_on_drop: false, // 不在程序结束时压缩
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     // 创建主日志器（包含终端输出）
@@ -157,10 +161,11 @@ fn create_app_record(message: String) -> Record {
             auth_token: None,
             app_id: Some("my_app".to_string()),
         }),
-        args: message,
+        args: message.into(),
         module_path: Some("main".to_string()),
         file: Some("main.rs".to_string()),
         line: Some(42),
+        ..Default::default()
     }
 }
 
@@ -173,10 +178,11 @@ fn create_error_record(message: String) -> Record {
             auth_token: None,
             app_id: Some("my_app".to_string()),
         }),
-        args: message,
+        args: message.into(),
         module_path: Some("main".to_string()),
         file: Some("main.rs".to_string()),
         line: Some(85),
+        ..Default::default()
     }
 }
 
@@ -189,10 +195,11 @@ fn create_access_record(message: String) -> Record {
             auth_token: None,
             app_id: Some("my_app".to_string()),
         }),
-        args: message,
+        args: message.into(),
         module_path: Some("middleware".to_string()),
         file: Some("access.rs".to_string()),
         line: Some(120),
+        ..Default::default()
     }
 }
 
@@ -205,9 +212,10 @@ fn create_perf_record(message: String) -> Record {
             auth_token: None,
             app_id: Some("my_app".to_string()),
         }),
-        args: message,
+        args: message.into(),
         module_path: Some("monitor".to_string()),
         file: Some("perf.rs".to_string()),
         line: Some(35),
+        ..Default::default()
     }
 }
\ No newline at end of file
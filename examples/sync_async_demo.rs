@@ -11,7 +11,6 @@ use rat_logger::config::Record;
 use rat_logger::config::Metadata;
 use std::sync::Arc;
 use std::path::PathBuf;
-use std::thread;
 use std::time::Duration;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -32,6 +31,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步写入，性能更好
         format: None,
+        ..Default::default()
     };
 
     let async_logger = LoggerBuilder::new()
@@ -51,10 +51,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 auth_token: None,
                 app_id: Some("async_demo".to_string()),
             }),
-            args: format!("异步日志消息 #{}", i),
+            args: format!("异步日志消息 #{}", i).into(),
             module_path: Some("sync_async_demo".to_string()),
             file: Some("sync_async_demo.rs".to_string()),
             line: Some(42),
+            ..Default::default()
         };
         async_logger.log(&record);
     }
@@ -62,8 +63,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let async_duration = start_time.elapsed();
     println!("   ✓ 异步模式写入100条日志耗时: {:?}", async_duration);
 
-    // 等待异步写入完成
-    thread::sleep(Duration::from_millis(100));
+    // 同步点：返回后此前记录的日志已写入文件
+    async_logger.sync_point(Duration::from_secs(2))?;
 
     // 测试2: 同步模式 (force_sync = true)
     println!("\n2. 同步模式测试 (force_sync = true):");
@@ -80,6 +81,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: true, // 同步写入，确保数据安全
         format: None,
+        ..Default::default()
     };
 
     let sync_logger = LoggerBuilder::new()
@@ -99,10 +101,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 auth_token: None,
                 app_id: Some("sync_demo".to_string()),
             }),
-            args: format!("同步日志消息 #{}", i),
+            args: format!("同步日志消息 #{}", i).into(),
             module_path: Some("sync_async_demo".to_string()),
             file: Some("sync_async_demo.rs".to_string()),
             line: Some(42),
+            ..Default::default()
         };
         sync_logger.log(&record);
     }
@@ -110,8 +113,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let sync_duration = start_time.elapsed();
     println!("   ✓ 同步模式写入100条日志耗时: {:?}", sync_duration);
 
-    // 等待同步写入完成
-    thread::sleep(Duration::from_millis(100));
+    // 同步点：返回后此前记录的日志已写入文件
+    sync_logger.sync_point(Duration::from_secs(2))?;
 
     // 性能对比
     println!("\n3. 性能对比:");
@@ -138,6 +141,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 业务日志异步写入，追求性能
         format: None,
+        ..Default::default()
     };
 
     // 关键错误日志 - 同步模式
@@ -152,6 +156,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: true, // 错误日志同步写入，确保不丢失
         format: None,
+        ..Default::default()
     };
 
     println!("   业务日志 (异步) 和 错误日志 (同步) 同时写入测试...");
@@ -166,10 +171,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 auth_token: None,
                 app_id: Some("business_app".to_string()),
             }),
-            args: format!("用户操作日志 #{}", i),
+            args: format!("用户操作日志 #{}", i).into(),
             module_path: Some("sync_async_demo".to_string()),
             file: Some("sync_async_demo.rs".to_string()),
             line: Some(42),
+            ..Default::default()
         };
 
         // 错误日志 (每10条业务日志产生1条错误日志)
@@ -181,10 +187,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     auth_token: None,
                     app_id: Some("error_app".to_string()),
                 }),
-                args: format!("严重错误！处理失败，ID: {}", i),
+                args: format!("严重错误！处理失败，ID: {}", i).into(),
                 module_path: Some("sync_async_demo.rs".to_string()),
                 file: Some("sync_async_demo.rs".to_string()),
                 line: Some(42),
+                ..Default::default()
             };
 
             // 使用不同的日志器
@@ -27,6 +27,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "{level} {timestamp} {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 2. 创建详细格式配置
@@ -42,6 +47,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         format_template: "[{level}] {timestamp} {target}:{line} - {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     // 3. 创建颜色配置
@@ -56,6 +66,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         target: "\x1b[94m".to_string(),      // 亮蓝色
         file: "\x1b[95m".to_string(),       // 亮紫色
         message: "\x1b[97m".to_string(),      // 亮白色
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     println!("   ✓ 已创建配置\n");
@@ -164,9 +177,10 @@ fn create_test_record(
             auth_token: None,
             app_id: Some("term_format_example".to_string()),
         }),
-        args: message.to_string(),
+        args: message.into(),
         module_path: Some("term_format_example".to_string()),
         file: Some("term_format_example.rs".to_string()),
         line: Some(42),
+        ..Default::default()
     }
 }
\ No newline at end of file
@@ -29,10 +29,11 @@ fn main() {
                 auth_token: None,
                 app_id: None,
             }),
-            args: format!("启动日志 #{}", i),
+            args: format!("启动日志 #{}", i).into(),
             module_path: Some("test_idle_cpu".to_string()),
             file: Some("test_idle_cpu.rs".to_string()),
             line: Some(i),
+            ..Default::default()
         };
         terminal_logger.log(&record);
     }
@@ -48,10 +49,11 @@ fn main() {
             auth_token: None,
             app_id: None,
         }),
-        args: "被过滤的 debug 日志".to_string(),
+        args: "被过滤的 debug 日志".into(),
         module_path: Some("test_idle_cpu".to_string()),
         file: Some("test_idle_cpu.rs".to_string()),
         line: Some(100),
+        ..Default::default()
     };
 
     // 空闲循环：只调用被过滤的日志
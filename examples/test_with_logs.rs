@@ -21,10 +21,11 @@ fn main() {
                 auth_token: None,
                 app_id: None,
             }),
-            args: format!("日志 #{}", counter),
+            args: format!("日志 #{}", counter).into(),
             module_path: None,
             file: None,
             line: None,
+            ..Default::default()
         };
         logger.log(&record);
         counter += 1;
@@ -0,0 +1,32 @@
+//! Windows 控制台输出示例（手动测试）
+//!
+//! 在代码页不是 UTF-8 的控制台（如 `chcp 936` 或 `chcp 437`）中直接运行：
+//!
+//! ```text
+//! cargo run --example windows_console_example
+//! ```
+//!
+//! 中文和 emoji 应正常显示，级别颜色生效。再运行 `cargo run --example windows_console_example > out.txt`，
+//! 重定向的文件应为 UTF-8 编码。其他平台上输出与普通终端处理器相同。
+
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{debug, error, info, warn, ColorConfig, LevelFilter, LoggerBuilder};
+
+fn main() {
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        .add_terminal_with_config(TermConfig {
+            enable_color: true,
+            color: Some(ColorConfig::default()),
+            ..Default::default()
+        })
+        .init_global_logger()
+        .unwrap();
+
+    info!("服务启动完成，监听端口 {}", 8080);
+    warn!("配置文件缺少字段「超时」，使用默认值 30 秒");
+    error!("连接数据库失败：拒绝访问 ❌");
+    debug!("混合内容：ASCII、中文、日本語、한국어、emoji 🚀🎉");
+
+    rat_logger::flush_logs!();
+}
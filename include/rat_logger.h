@@ -0,0 +1,100 @@
+#ifndef RAT_LOGGER_H
+#define RAT_LOGGER_H
+
+/* 由 cbindgen 生成，请勿手动修改 */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// 成功
+#define RAT_LOGGER_OK 0
+
+// 必需的指针参数为空
+#define RAT_LOGGER_ERR_NULL -1
+
+// 配置 JSON 不是合法的 UTF-8
+#define RAT_LOGGER_ERR_UTF8 -2
+
+// 配置 JSON 无法解析或配置无效
+#define RAT_LOGGER_ERR_CONFIG -3
+
+// 全局日志器已经初始化
+#define RAT_LOGGER_ERR_ALREADY_INITIALIZED -4
+
+// 全局日志器尚未初始化（或已关闭）
+#define RAT_LOGGER_ERR_NOT_INITIALIZED -5
+
+// 刷新未在超时时间内完成
+#define RAT_LOGGER_ERR_TIMEOUT -6
+
+// 日志级别无效
+#define RAT_LOGGER_ERR_LEVEL -7
+
+// 内部错误（捕获到 panic）
+#define RAT_LOGGER_ERR_INTERNAL -8
+
+// 日志级别：错误
+#define RAT_LOGGER_LEVEL_ERROR 1
+
+// 日志级别：警告
+#define RAT_LOGGER_LEVEL_WARN 2
+
+// 日志级别：信息
+#define RAT_LOGGER_LEVEL_INFO 3
+
+// 日志级别：调试
+#define RAT_LOGGER_LEVEL_DEBUG 4
+
+// 日志级别：跟踪
+#define RAT_LOGGER_LEVEL_TRACE 5
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// 按 JSON 配置初始化全局日志器
+//
+// 全局日志器已存在时返回 `RAT_LOGGER_ERR_ALREADY_INITIALIZED`，不会替换现有日志器。
+//
+// # Safety
+//
+// `config_json` 必须为空指针或指向以 NUL 结尾的字符串。
+int rat_logger_init_from_json(const char *config_json);
+
+// 写入一条日志
+//
+// `target` 为空指针时使用 `"ffi"`。被级别过滤的记录同样返回 `RAT_LOGGER_OK`。
+//
+// # Safety
+//
+// `target` 和 `message` 必须为空指针或指向以 NUL 结尾的字符串。
+int rat_logger_log(int level, const char *target, const char *message);
+
+// 写入一条带结构化字段的日志，`keys[i]` 与 `values[i]` 组成第 i 个字段
+//
+// `target` 为空指针时使用 `"ffi"`；`count` 为 0 时 `keys` 和 `values` 可以为空指针。
+//
+// # Safety
+//
+// `target` 和 `message` 必须为空指针或指向以 NUL 结尾的字符串；`count` 不为 0 时，
+// `keys` 和 `values` 必须指向至少 `count` 个元素的数组，每个元素指向以 NUL 结尾的字符串。
+int rat_logger_log_kv(int level,
+                      const char *target,
+                      const char *message,
+                      const char *const *keys,
+                      const char *const *values,
+                      uintptr_t count);
+
+// 刷新全局日志器，等待此前写入的日志处理完毕（最多等待 `timeout_ms` 毫秒）
+int rat_logger_flush(uint64_t timeout_ms);
+
+// 刷新并关闭全局日志器，之后可以重新初始化
+int rat_logger_shutdown(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RAT_LOGGER_H */
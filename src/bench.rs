@@ -0,0 +1,164 @@
+//! 基准测试工具 - 在应用内生成合成日志负载（需要 `bench-utils` 特性）
+//!
+//! `LoadGenerator` 按 `LoadProfile` 从多个线程驱动给定的日志器，记录每次 `log()` 调用的耗时，
+//! 返回吞吐量、延迟分位数和期间丢弃的记录数，便于在启动时或 CI 中调整 `BatchConfig`。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::{Level, Metadata, Record};
+use crate::core::Logger;
+
+/// 合成记录的目标
+///
+/// 负载记录模拟应用自身的日志，需要经过采样和熔断，因此不能使用 rat_logger 自身记录的
+/// `rat_logger::` 目标前缀（见 [`Metadata::is_internal`]）。
+pub const BENCH_TARGET: &str = "bench::load";
+
+/// 负载配置
+#[derive(Debug, Clone)]
+pub struct LoadProfile {
+    /// 产生日志的线程数
+    pub threads: usize,
+    /// 记录总数（平均分配到各线程）
+    pub records: usize,
+    /// 每条消息的字节数
+    pub message_size: usize,
+    /// 级别权重，按权重轮流产生各级别的记录（如 `[(Level::Info, 9), (Level::Error, 1)]`）
+    pub level_mix: Vec<(Level, u32)>,
+}
+
+impl LoadProfile {
+    /// 验证配置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.threads == 0 {
+            return Err("配置错误: 线程数不能为 0".to_string());
+        }
+        if self.records == 0 {
+            return Err("配置错误: 记录总数不能为 0".to_string());
+        }
+        if self.level_mix.iter().all(|(_, weight)| *weight == 0) {
+            return Err("配置错误: 级别权重不能全部为 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// 第 `index` 条记录的级别
+    fn level_at(&self, index: usize) -> Level {
+        let total: u64 = self.level_mix.iter().map(|(_, weight)| *weight as u64).sum();
+        let mut slot = index as u64 % total;
+        for (level, weight) in &self.level_mix {
+            if slot < *weight as u64 {
+                return *level;
+            }
+            slot -= *weight as u64;
+        }
+        Level::Info
+    }
+}
+
+impl Default for LoadProfile {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            records: 100_000,
+            message_size: 128,
+            level_mix: vec![(Level::Info, 1)],
+        }
+    }
+}
+
+/// 负载测试结果
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    /// 提交的记录数
+    pub records: u64,
+    /// 从开始提交到所有线程提交完毕的耗时
+    pub duration: Duration,
+    /// 吞吐量（条/秒）
+    pub throughput: f64,
+    /// `log()` 调用耗时的中位数（从记录捕获时间到调用返回）
+    pub p50_latency: Duration,
+    /// `log()` 调用耗时的 95 分位数
+    pub p95_latency: Duration,
+    /// 期间被采样或发送失败丢弃的记录数（日志器不提供统计时为 0）
+    pub dropped: u64,
+}
+
+/// 合成负载生成器
+pub struct LoadGenerator {
+    logger: Arc<dyn Logger>,
+    profile: LoadProfile,
+}
+
+impl LoadGenerator {
+    /// 创建负载生成器，配置无效时直接panic
+    pub fn new(logger: Arc<dyn Logger>, profile: LoadProfile) -> Self {
+        if let Err(e) = profile.validate() {
+            panic!("LoadProfile 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e);
+        }
+        Self { logger, profile }
+    }
+
+    /// 运行负载并返回结果（结束时广播一次刷新，不等待处理器写出）
+    pub fn run(&self) -> LoadReport {
+        let profile = &self.profile;
+        let stats = self.logger.runtime_stats();
+        let dropped_before = stats.as_ref().map_or(0, |s| s.dropped() + s.sampled_out());
+
+        let per_thread = profile.records.div_ceil(profile.threads);
+        let message = "x".repeat(profile.message_size);
+        let metadata: Vec<Arc<Metadata>> = Level::ALL
+            .into_iter()
+            .map(|level| Arc::new(Metadata {
+                level,
+                target: BENCH_TARGET.to_string(),
+                auth_token: None,
+                app_id: None,
+            }))
+            .collect();
+
+        let start = Instant::now();
+        let mut latencies: Vec<Duration> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..profile.threads)
+                .map(|thread| {
+                    let first = thread * per_thread;
+                    let last = (first + per_thread).min(profile.records);
+                    let (logger, message, metadata) = (&self.logger, &message, &metadata);
+                    scope.spawn(move || {
+                        let mut latencies = Vec::with_capacity(last.saturating_sub(first));
+                        for index in first..last {
+                            let level = profile.level_at(index);
+                            let record = Record {
+                                metadata: metadata[level as usize].clone(),
+                                args: message.clone().into(),
+                                timestamp: Some(SystemTime::now()),
+                                ..Default::default()
+                            };
+                            let captured = Instant::now();
+                            logger.log(&record);
+                            latencies.push(captured.elapsed());
+                        }
+                        latencies
+                    })
+                })
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        let duration = start.elapsed();
+        self.logger.flush();
+
+        latencies.sort_unstable();
+        let percentile = |p: usize| latencies.get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1))).copied().unwrap_or_default();
+        let dropped_after = stats.as_ref().map_or(0, |s| s.dropped() + s.sampled_out());
+
+        LoadReport {
+            records: latencies.len() as u64,
+            duration,
+            throughput: latencies.len() as f64 / duration.as_secs_f64().max(f64::EPSILON),
+            p50_latency: percentile(50),
+            p95_latency: percentile(95),
+            dropped: dropped_after - dropped_before,
+        }
+    }
+}
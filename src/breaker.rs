@@ -0,0 +1,142 @@
+//! 熔断模块 - 日志器整体的记录速率超过上限时丢弃 Error 以外的记录
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+
+use parking_lot::Mutex;
+
+use crate::config::{BreakerAction, BreakerConfig, Level};
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+
+/// 速率统计窗口（毫秒）
+const WINDOW_MS: u64 = 1000;
+
+/// 全局熔断器
+///
+/// 热路径只做原子计数，窗口切换和状态变化在锁内进行。
+pub(crate) struct CircuitBreaker {
+    config: BreakerConfig,
+    diagnostics: Diagnostics,
+    started: Instant,
+    /// 当前窗口的起点（自创建起的毫秒数）
+    window_start: AtomicU64,
+    /// 当前窗口内的记录数
+    window_count: AtomicU64,
+    /// 当前窗口内丢弃的记录数
+    window_dropped: AtomicU64,
+    open: AtomicBool,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    /// 熔断开始的时间
+    opened_at: Option<Instant>,
+    /// 速率开始低于恢复阈值的时间（自创建起的毫秒数）
+    calm_since: Option<u64>,
+    /// 本次熔断累计丢弃的记录数
+    dropped: u64,
+}
+
+/// 熔断判定结果
+pub(crate) struct Admission {
+    /// 是否放行该记录
+    pub pass: bool,
+    /// 需要输出的汇总：上一个窗口内丢弃的记录数（只在 `DropWithSummary` 下产生）
+    pub summary: Option<u64>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig, diagnostics: Diagnostics) -> Self {
+        Self {
+            config,
+            diagnostics,
+            started: Instant::now(),
+            window_start: AtomicU64::new(0),
+            window_count: AtomicU64::new(0),
+            window_dropped: AtomicU64::new(0),
+            open: AtomicBool::new(false),
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    pub fn config(&self) -> &BreakerConfig {
+        &self.config
+    }
+
+    /// 统计一条通过级别过滤的记录，熔断中时 Error 以外的记录不放行
+    pub fn admit(&self, level: Level) -> Admission {
+        let now = self.started.elapsed().as_millis() as u64;
+        let summary = if now.saturating_sub(self.window_start.load(Ordering::Relaxed)) >= WINDOW_MS {
+            self.roll_window(now)
+        } else {
+            None
+        };
+
+        let count = self.window_count.fetch_add(1, Ordering::Relaxed) + 1;
+        if count > self.config.max_records_per_sec && !self.open.load(Ordering::Relaxed) {
+            self.trip(count);
+        }
+
+        let pass = level == Level::Error || !self.open.load(Ordering::Relaxed);
+        if !pass {
+            self.window_dropped.fetch_add(1, Ordering::Relaxed);
+            self.diagnostics.stats().add_breaker_dropped();
+        }
+        Admission { pass, summary }
+    }
+
+    fn trip(&self, rate: u64) {
+        let mut state = self.state.lock();
+        if self.open.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        *state = BreakerState { opened_at: Some(Instant::now()), ..Default::default() };
+        drop(state);
+        self.diagnostics.emit(DiagnosticEvent::BreakerOpened { rate, limit: self.config.max_records_per_sec });
+    }
+
+    /// 切换到新窗口，熔断中时按上一个窗口的速率判断是否恢复
+    fn roll_window(&self, now: u64) -> Option<u64> {
+        let mut state = self.state.lock();
+        let start = self.window_start.load(Ordering::Relaxed);
+        if now.saturating_sub(start) < WINDOW_MS {
+            // 其他线程已经切换
+            return None;
+        }
+        let count = self.window_count.swap(0, Ordering::Relaxed);
+        let dropped = self.window_dropped.swap(0, Ordering::Relaxed);
+        self.window_start.store(now - (now - start) % WINDOW_MS, Ordering::Relaxed);
+        if !self.open.load(Ordering::Relaxed) {
+            return None;
+        }
+        state.dropped += dropped;
+
+        // 上一个窗口低于恢复阈值时从窗口起点开始计算冷却；之后还有空窗口时从空窗口起点开始
+        let recovery = self.config.recovery_records_per_sec;
+        if count < recovery {
+            state.calm_since.get_or_insert(start);
+        } else if now - start >= 2 * WINDOW_MS {
+            state.calm_since = Some(start + WINDOW_MS);
+        } else {
+            state.calm_since = None;
+        }
+
+        let summary = (self.config.action == BreakerAction::DropWithSummary && dropped > 0).then_some(dropped);
+        if state.calm_since.is_some_and(|calm_since| now - calm_since >= self.config.cooldown_ms) {
+            self.open.store(false, Ordering::Relaxed);
+            let event = DiagnosticEvent::BreakerClosed {
+                dropped: state.dropped,
+                duration: state.opened_at.map(|opened_at| opened_at.elapsed()).unwrap_or_default(),
+            };
+            drop(state);
+            self.diagnostics.emit(event);
+        }
+        summary
+    }
+}
+
+/// 熔断汇总记录的消息
+pub(crate) fn summary_message(dropped: u64, limit: u64) -> String {
+    format!("熔断中：上一秒丢弃 {} 条记录（上限 {} 条/秒）", dropped, limit)
+}
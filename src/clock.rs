@@ -0,0 +1,82 @@
+//! 时钟模块 - 记录时间的来源
+//!
+//! `LoggerCore` 在 `log()` 中通过时钟为没有时间的记录写入时间，格式化器和 `NetRecord` 都使用该时间。
+//! 测试中可通过 `LoggerBuilder::with_clock()` 换成 `FixedClock`/`StepClock`（需要 `test-util` 特性）得到确定的输出。
+
+use std::time::SystemTime;
+#[cfg(feature = "test-util")]
+use std::time::{Duration, UNIX_EPOCH};
+#[cfg(feature = "test-util")]
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// 时钟 trait
+pub trait Clock: Send + Sync {
+    /// 当前时间
+    fn now(&self) -> SystemTime;
+}
+
+/// 系统时钟（默认）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// 固定时钟 - 始终返回同一时间
+#[cfg(feature = "test-util")]
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    time: SystemTime,
+}
+
+#[cfg(feature = "test-util")]
+impl FixedClock {
+    /// 创建返回指定时间的时钟
+    pub fn new(time: SystemTime) -> Self {
+        Self { time }
+    }
+
+    /// 创建返回指定 Unix 毫秒时间戳的时钟
+    pub fn from_unix_millis(millis: u64) -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_millis(millis))
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.time
+    }
+}
+
+/// 步进时钟 - 第一次返回起始时间，之后每次调用前进固定步长
+#[cfg(feature = "test-util")]
+#[derive(Debug)]
+pub struct StepClock {
+    start: SystemTime,
+    step: Duration,
+    ticks: AtomicU32,
+}
+
+#[cfg(feature = "test-util")]
+impl StepClock {
+    /// 创建从 `start` 开始、每次前进 `step` 的时钟
+    pub fn new(start: SystemTime, step: Duration) -> Self {
+        Self { start, step, ticks: AtomicU32::new(0) }
+    }
+
+    /// 创建从指定 Unix 毫秒时间戳开始的时钟
+    pub fn from_unix_millis(millis: u64, step: Duration) -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_millis(millis), step)
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Clock for StepClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.step * self.ticks.fetch_add(1, Ordering::Relaxed)
+    }
+}
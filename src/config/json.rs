@@ -0,0 +1,121 @@
+//! 记录的 JSON 结构 - 结构化输出（`Record` 的 serde 序列化、ndjson）统一使用的字段定义
+
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use super::Record;
+
+/// 认证令牌在结构化输出中的替代文本
+pub const REDACTED: &str = "***";
+
+/// 记录的 JSON 结构，借用 `Record` 的内容
+///
+/// 字段按以下顺序输出，值为空的可选字段不输出：
+///
+/// | 键 | 说明 |
+/// |----|------|
+/// | `timestamp` | 记录时间，RFC 3339 UTC 字符串（如 `2026-01-02T03:04:05.678Z`） |
+/// | `level` | 级别（`ERROR`/`WARN`/`INFO`/`DEBUG`/`TRACE`） |
+/// | `label` | 级别别名（如 `FATAL`） |
+/// | `target` | 日志目标 |
+/// | `message` | 消息 |
+/// | `module`、`file`、`line` | 产生记录的位置 |
+/// | `app_id` | 应用ID |
+/// | `auth_token` | 认证令牌，设置时始终输出为 `***` |
+/// | `seq` | 日志器序号 |
+/// | `tid`、`tseq` | 线程编号和线程内序号 |
+/// | `record_id` | 记录标识，16 位十六进制字符串（与 `{record_id}` 占位符相同） |
+/// | `hostname`、`pid` | 主机名和进程号（通过 [`JsonRecord::with_process_info`] 启用） |
+/// | `fields` | 结构化字段，按添加顺序输出为对象 |
+///
+/// 离线工具的 ndjson 输入（`tools::InputKind::Ndjson`）按相同的键读取。
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonRecord<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub level: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<&'a str>,
+    pub target: &'a str,
+    pub message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_token: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tid: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tseq: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(skip_serializing_if = "JsonFields::is_empty")]
+    pub fields: JsonFields<'a>,
+}
+
+impl<'a> JsonRecord<'a> {
+    /// 输出本进程的主机名和进程号
+    pub fn with_process_info(mut self) -> Self {
+        self.hostname = crate::process_info::hostname();
+        self.pid = Some(crate::process_info::pid());
+        self
+    }
+}
+
+impl<'a> From<&'a Record> for JsonRecord<'a> {
+    fn from(record: &'a Record) -> Self {
+        let metadata = &record.metadata;
+        Self {
+            timestamp: record.timestamp.map(|time| {
+                chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            }),
+            level: metadata.level.as_str(),
+            label: record.level_label.as_deref(),
+            target: &metadata.target,
+            message: &record.args,
+            module: record.module_path.as_deref(),
+            file: record.file.as_deref(),
+            line: record.line,
+            app_id: metadata.app_id.as_deref(),
+            auth_token: metadata.auth_token.as_ref().map(|_| REDACTED),
+            seq: record.seq,
+            tid: record.thread.map(|thread| thread.id),
+            tseq: record.thread.map(|thread| thread.seq),
+            record_id: record.record_id.map(super::record_id_text),
+            hostname: None,
+            pid: None,
+            fields: JsonFields(&record.fields),
+        }
+    }
+}
+
+/// 结构化字段，序列化为保持添加顺序的对象
+#[derive(Debug, Clone, Copy)]
+pub struct JsonFields<'a>(pub &'a [(String, String)]);
+
+impl JsonFields<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Serialize for JsonFields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
@@ -1,11 +1,17 @@
 //! 配置模块
 
 use serde::{Serialize, Deserialize};
-use bincode::{Encode, Decode};
 use std::path::PathBuf;
 
+pub mod units;
+pub use units::{ByteSize, DurationMs};
+pub mod json;
+pub use json::JsonRecord;
+pub mod template;
+pub use template::{FormatConfigBuilder, TemplateError};
+
 /// 日志级别
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Level {
     Error,
     Warn,
@@ -15,6 +21,30 @@ pub enum Level {
 }
 
 impl Level {
+    /// 所有级别，按严重程度从高到低排列
+    pub const ALL: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+    /// 按严重程度从高到低遍历所有级别
+    pub fn iter() -> impl Iterator<Item = Level> {
+        Self::ALL.into_iter()
+    }
+
+    /// 级别名称（与 `Display` 相同）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+
+    /// 从数值还原级别（1 为 Error 到 5 为 Trace，与 `LevelFilter as usize` 对应）
+    pub fn from_usize(value: usize) -> Option<Level> {
+        value.checked_sub(1).and_then(|index| Self::ALL.get(index).copied())
+    }
+
     pub fn to_level_filter(&self) -> LevelFilter {
         match self {
             Level::Error => LevelFilter::Error,
@@ -26,9 +56,9 @@ impl Level {
     }
 
     /// 检查在给定的过滤级别下，该日志是否应该被记录
-    /// 返回true表示该级别的日志应该被发送
+    /// 返回true表示该级别的日志应该被发送（过滤级别为 `Off` 时任何级别都不发送）
     pub fn should_log_at(&self, filter_level: LevelFilter) -> bool {
-        self.to_level_filter() as u8 <= filter_level as u8
+        filter_level != LevelFilter::Off && self.to_level_filter() as u8 <= filter_level as u8
     }
 
     /// 检查在给定的日志级别下，该日志是否应该被记录
@@ -40,24 +70,36 @@ impl Level {
 
 impl std::fmt::Display for Level {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Level {
+    /// 线格式编码值（与 `Display` 无关；新增级别只能追加新值，不能修改已有值）
+    pub fn wire_value(self) -> u8 {
         match self {
-            Level::Error => write!(f, "ERROR"),
-            Level::Warn => write!(f, "WARN"),
-            Level::Info => write!(f, "INFO"),
-            Level::Debug => write!(f, "DEBUG"),
-            Level::Trace => write!(f, "TRACE"),
+            Level::Error => 1,
+            Level::Warn => 2,
+            Level::Info => 3,
+            Level::Debug => 4,
+            Level::Trace => 5,
         }
     }
-}
 
-impl bincode::Encode for Level {
-    fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
-        bincode::Encode::encode(&self.to_string(), encoder)
+    /// 从线格式编码值还原级别
+    pub fn from_wire_value(value: u8) -> Option<Level> {
+        match value {
+            1 => Some(Level::Error),
+            2 => Some(Level::Warn),
+            3 => Some(Level::Info),
+            4 => Some(Level::Debug),
+            5 => Some(Level::Trace),
+            _ => None,
+        }
     }
-}
 
-impl bincode::Decode<()> for Level {
-    fn decode<D: bincode::de::Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+    /// 解码旧线格式（v1 数据包、v1 记录）中以字符串编码的级别
+    pub(crate) fn decode_legacy<D: bincode::de::Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
         let level_str: String = bincode::Decode::decode(decoder)?;
         match level_str.as_str() {
             "ERROR" => Ok(Level::Error),
@@ -70,8 +112,22 @@ impl bincode::Decode<()> for Level {
     }
 }
 
+impl bincode::Encode for Level {
+    fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+        bincode::Encode::encode(&self.wire_value(), encoder)
+    }
+}
+
+impl bincode::Decode<()> for Level {
+    fn decode<D: bincode::de::Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        let value: u8 = bincode::Decode::decode(decoder)?;
+        Level::from_wire_value(value)
+            .ok_or_else(|| bincode::error::DecodeError::OtherString(format!("Invalid level value: {}", value)))
+    }
+}
+
 /// 日志级别过滤器
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum LevelFilter {
     Off,
     Error,
@@ -81,6 +137,101 @@ pub enum LevelFilter {
     Trace,
 }
 
+impl LevelFilter {
+    /// 所有过滤级别，从 `Off` 到 `Trace` 按放行范围从小到大排列
+    pub const ALL: [LevelFilter; 6] = [
+        LevelFilter::Off,
+        LevelFilter::Error,
+        LevelFilter::Warn,
+        LevelFilter::Info,
+        LevelFilter::Debug,
+        LevelFilter::Trace,
+    ];
+
+    /// 从 `Off` 到 `Trace` 遍历所有过滤级别
+    pub fn iter() -> impl Iterator<Item = LevelFilter> {
+        Self::ALL.into_iter()
+    }
+}
+
+/// 级别集合：五个级别上的位集，用于让处理器只接收指定的级别（见 `HandlerOptions::levels`）
+///
+/// 与阈值不同，集合可以排除中间的级别，例如只接收 Info 而不接收 Warn/Error。
+/// 序列化为级别列表（如 `["Warn", "Error"]`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "Vec<Level>", into = "Vec<Level>")]
+pub struct LevelMask(u8);
+
+impl LevelMask {
+    /// 所有级别
+    pub const ALL: LevelMask = LevelMask(0b1_1111);
+    /// 空集合
+    pub const NONE: LevelMask = LevelMask(0);
+
+    fn bit(level: Level) -> u8 {
+        1 << level as u8
+    }
+
+    /// 只包含给定级别的集合
+    pub fn only(levels: &[Level]) -> Self {
+        levels.iter().fold(Self::NONE, |mask, &level| mask.with(level))
+    }
+
+    /// 包含 `level` 及更严重级别的集合，如 `at_least(Level::Warn)` 为 Warn 和 Error
+    pub fn at_least(level: Level) -> Self {
+        Level::iter().filter(|other| other.should_log_at_level(level)).fold(Self::NONE, Self::with)
+    }
+
+    /// 加入一个级别
+    pub fn with(self, level: Level) -> Self {
+        LevelMask(self.0 | Self::bit(level))
+    }
+
+    /// 去掉一个级别
+    pub fn without(self, level: Level) -> Self {
+        LevelMask(self.0 & !Self::bit(level))
+    }
+
+    /// 是否包含该级别
+    pub fn contains(self, level: Level) -> bool {
+        self.0 & Self::bit(level) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 集合中的级别，按严重程度从高到低排列
+    pub fn levels(self) -> impl Iterator<Item = Level> {
+        Level::iter().filter(move |&level| self.contains(level))
+    }
+}
+
+impl Default for LevelMask {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl From<Vec<Level>> for LevelMask {
+    fn from(levels: Vec<Level>) -> Self {
+        Self::only(&levels)
+    }
+}
+
+impl From<LevelMask> for Vec<Level> {
+    fn from(mask: LevelMask) -> Self {
+        mask.levels().collect()
+    }
+}
+
+impl std::fmt::Display for LevelMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self.levels().map(|level| level.as_str()).collect();
+        write!(f, "[{}]", names.join(", "))
+    }
+}
+
 /// 应用ID
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct AppId(String);
@@ -118,6 +269,34 @@ pub struct Metadata {
     pub app_id: Option<String>,
 }
 
+impl Metadata {
+    /// 日志级别
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// 日志目标
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// 应用ID
+    pub fn app_id(&self) -> Option<&str> {
+        self.app_id.as_deref()
+    }
+
+    /// 是否为 rat_logger 自身产生的记录（目标为 [`INTERNAL_TARGET`] 或以 `rat_logger::` 开头）
+    pub fn is_internal(&self) -> bool {
+        self.target.strip_prefix(INTERNAL_TARGET).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+    }
+}
+
+/// rat_logger 自身产生的记录的目标（及目标前缀 `rat_logger::`）
+pub const INTERNAL_TARGET: &str = "rat_logger";
+
+/// 管道探测标记记录的目标（见 [`ProbeMarker::Written`]）
+pub const PROBE_TARGET: &str = "rat_logger::probe";
+
 impl Default for Metadata {
     fn default() -> Self {
         Metadata {
@@ -154,74 +333,573 @@ impl bincode::Decode<()> for Metadata {
 }
 
 /// 日志记录
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Record {
     pub metadata: std::sync::Arc<Metadata>,
-    pub args: String,
+    /// 消息（见 [`RecordArgs`]）
+    pub args: RecordArgs,
     pub module_path: Option<String>,
     pub file: Option<String>,
     pub line: Option<u32>,
+    /// 结构化字段（键值对，按添加顺序输出）
+    pub fields: Vec<(String, String)>,
+    /// 记录时间（为空时由 `LoggerCore::log()` 写入当前时间，保留完整精度）
+    pub timestamp: Option<std::time::SystemTime>,
+    /// 日志器序号（启用 `LoggerBuilder::with_sequence()` 后由 `LoggerCore::log()` 写入）
+    pub seq: Option<u64>,
+    /// 产生记录的线程及线程内序号（为空时由 `LoggerCore::log()` 在调用线程上写入）
+    pub thread: Option<ThreadStamp>,
+    /// 级别别名（如 `fatal!` 的 `FATAL`），为空时输出级别本身的文本；过滤和颜色仍按 `metadata.level`
+    pub level_label: Option<String>,
+    /// 记录标识（见 [`next_record_id`]，为空时由 `LoggerCore::log()` 写入），同一条记录在各处理器的输出中相同，用于下游去重
+    pub record_id: Option<u64>,
+}
+
+/// 记录的消息：已生成的文本，或第一次读取时才生成的延迟消息
+///
+/// `LoggerCore` 收到延迟消息的记录时，在调用线程上完成过滤和时间、序号等标记，
+/// 由日志器的后台线程生成消息后再编码交给处理器（见 `info_lazy!`）；被过滤的记录不会生成消息。
+/// 其他情况下（自定义 `Logger`、紧急日志）在第一次读取消息的线程上生成。
+#[derive(Clone)]
+pub enum RecordArgs {
+    /// 已生成的消息
+    Eager(String),
+    /// 延迟生成的消息，克隆的记录共享同一个闭包和生成结果
+    Lazy(std::sync::Arc<LazyMessage>),
+}
+
+impl RecordArgs {
+    /// 由闭包延迟生成的消息
+    pub fn lazy<F>(message: F) -> Self
+    where
+        F: FnOnce() -> String + Send + 'static,
+    {
+        RecordArgs::Lazy(std::sync::Arc::new(LazyMessage::new(message)))
+    }
+
+    /// 消息文本，延迟消息在第一次调用时生成
+    pub fn as_str(&self) -> &str {
+        match self {
+            RecordArgs::Eager(message) => message,
+            RecordArgs::Lazy(message) => message.get(),
+        }
+    }
+
+    /// 可修改的消息文本（延迟消息先生成后转为已生成的消息）
+    pub fn to_mut(&mut self) -> &mut String {
+        if let RecordArgs::Lazy(message) = self {
+            *self = RecordArgs::Eager(message.get().to_string());
+        }
+        match self {
+            RecordArgs::Eager(message) => message,
+            RecordArgs::Lazy(_) => unreachable!(),
+        }
+    }
+
+    /// 是否为尚未生成的延迟消息
+    pub fn is_pending(&self) -> bool {
+        matches!(self, RecordArgs::Lazy(message) if !message.is_built())
+    }
+}
+
+impl Default for RecordArgs {
+    fn default() -> Self {
+        RecordArgs::Eager(String::new())
+    }
+}
+
+impl std::ops::Deref for RecordArgs {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for RecordArgs {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for RecordArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Debug for RecordArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordArgs::Lazy(message) if !message.is_built() => f.write_str("<延迟消息>"),
+            _ => std::fmt::Debug::fmt(self.as_str(), f),
+        }
+    }
+}
+
+impl From<String> for RecordArgs {
+    fn from(message: String) -> Self {
+        RecordArgs::Eager(message)
+    }
+}
+
+impl From<&str> for RecordArgs {
+    fn from(message: &str) -> Self {
+        RecordArgs::Eager(message.to_string())
+    }
+}
+
+impl From<RecordArgs> for String {
+    fn from(message: RecordArgs) -> Self {
+        match message {
+            RecordArgs::Eager(message) => message,
+            RecordArgs::Lazy(message) => message.get().to_string(),
+        }
+    }
+}
+
+impl PartialEq for RecordArgs {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl PartialEq<str> for RecordArgs {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for RecordArgs {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for RecordArgs {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+/// 延迟生成的消息：闭包最多调用一次，结果保存供之后读取
+pub struct LazyMessage {
+    build: std::sync::Mutex<Option<Box<dyn FnOnce() -> String + Send>>>,
+    message: std::sync::OnceLock<String>,
+}
+
+impl LazyMessage {
+    fn new<F>(message: F) -> Self
+    where
+        F: FnOnce() -> String + Send + 'static,
+    {
+        Self { build: std::sync::Mutex::new(Some(Box::new(message))), message: std::sync::OnceLock::new() }
+    }
+
+    /// 消息文本，第一次调用时调用闭包（闭包 panic 后消息为空）
+    pub fn get(&self) -> &str {
+        self.message.get_or_init(|| {
+            let build = self.build.lock().unwrap_or_else(std::sync::PoisonError::into_inner).take();
+            build.map(|build| build()).unwrap_or_default()
+        })
+    }
+
+    /// 消息是否已经生成
+    pub fn is_built(&self) -> bool {
+        self.message.get().is_some()
+    }
+}
+
+/// 生成进程内唯一的记录标识
+///
+/// 高 16 位为进程启动时生成的随机数，中间 16 位为进程号的低 16 位，低 32 位为进程内递增的序号
+/// （每个进程 2^32 条记录后回绕）。生成只需一次原子加法，不同进程的标识重复的概率很低。
+pub fn next_record_id() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static PREFIX: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+    static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+    let prefix = PREFIX.get_or_init(|| {
+        (crate::sampling::random_seed() >> 48) << 48 | u64::from(crate::process_info::pid() & 0xffff) << 32
+    });
+    prefix | (NEXT_SEQ.fetch_add(1, Ordering::Relaxed) & 0xffff_ffff)
+}
+
+/// 记录标识的文本形式（16 位小写十六进制，`{record_id}` 占位符和 JSON 输出使用）
+pub fn record_id_text(id: u64) -> String {
+    format!("{:016x}", id)
+}
+
+/// 产生记录的线程编号和线程内序号
+///
+/// 同一线程的记录按线程内序号的顺序到达处理器；不同线程的记录按进入通道的顺序到达，
+/// 与记录时间不一定一致，需要时可在处理器上启用 `order_by_timestamp` 按批次排序。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadStamp {
+    /// 线程编号（进程内从 1 开始，按线程第一次记录日志的顺序分配）
+    pub id: u64,
+    /// 线程内序号（每个线程从 0 开始递增）
+    pub seq: u64,
 }
 
+impl ThreadStamp {
+    /// 为当前线程生成下一个标记
+    pub fn next() -> Self {
+        use std::cell::Cell;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+        thread_local! {
+            static STAMP: Cell<Option<ThreadStamp>> = const { Cell::new(None) };
+        }
+
+        STAMP.with(|stamp| {
+            let current = stamp.get().map_or_else(
+                || ThreadStamp { id: NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed), seq: 0 },
+                |last| ThreadStamp { seq: last.seq + 1, ..last },
+            );
+            stamp.set(Some(current));
+            current
+        })
+    }
+}
+
+impl Record {
+    /// 按键查找结构化字段
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// 级别的显示名称：级别别名，没有别名时为级别本身（`ERROR`、`WARN` 等）
+    pub fn level_name(&self) -> &str {
+        self.level_label.as_deref().unwrap_or(self.metadata.level.as_str())
+    }
+
+    /// 按级别样式得到的级别显示文本，级别别名优先
+    pub fn level_text<'a>(&'a self, style: &'a LevelStyle) -> &'a str {
+        self.level_label.as_deref().unwrap_or_else(|| style.get(self.metadata.level))
+    }
+
+    /// 将结构化字段渲染为 `key=value key=value` 形式
+    pub fn fields_text(&self) -> String {
+        self.fields.iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// 按 [`JsonRecord`] 的结构序列化（认证令牌输出为 `***`）
 impl Serialize for Record {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("Record", 6)?;
-        state.serialize_field("metadata", &*self.metadata)?;
-        state.serialize_field("args", &self.args)?;
-        state.serialize_field("module_path", &self.module_path)?;
-        state.serialize_field("file", &self.file)?;
-        state.serialize_field("line", &self.line)?;
-        state.end()
+        JsonRecord::from(self).serialize(serializer)
+    }
+}
+
+/// 将 `SystemTime` 拆分为 UNIX 纪元以来的秒数和亚秒纳秒数（纪元之前的时间记为 0）
+pub(crate) fn split_system_time(time: std::time::SystemTime) -> (u64, u32) {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map(|d| (d.as_secs(), d.subsec_nanos()))
+        .unwrap_or((0, 0))
+}
+
+/// 带有覆盖时间、序号和记录标识的记录编码视图，编码结果与 `Record` 相同，无需克隆记录
+pub(crate) struct StampedRecord<'a> {
+    pub record: &'a Record,
+    pub timestamp: Option<std::time::SystemTime>,
+    pub seq: Option<u64>,
+    pub thread: Option<ThreadStamp>,
+    pub record_id: Option<u64>,
+}
+
+impl bincode::Encode for StampedRecord<'_> {
+    fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+        let record = self.record;
+        bincode::Encode::encode(&*record.metadata, encoder)?;
+        bincode::Encode::encode(record.args.as_str(), encoder)?;
+        bincode::Encode::encode(&record.module_path, encoder)?;
+        bincode::Encode::encode(&record.file, encoder)?;
+        bincode::Encode::encode(&record.line, encoder)?;
+        bincode::Encode::encode(&record.fields, encoder)?;
+        bincode::Encode::encode(&self.timestamp.map(split_system_time), encoder)?;
+        bincode::Encode::encode(&self.seq, encoder)?;
+        bincode::Encode::encode(&self.thread.map(|t| (t.id, t.seq)), encoder)?;
+        bincode::Encode::encode(&record.level_label, encoder)?;
+        bincode::Encode::encode(&self.record_id, encoder)
     }
 }
 
 impl bincode::Encode for Record {
     fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
-        bincode::Encode::encode(&(*self.metadata).clone(), encoder)?;
-        bincode::Encode::encode(&self.args, encoder)?;
-        bincode::Encode::encode(&self.module_path, encoder)?;
-        bincode::Encode::encode(&self.file, encoder)?;
-        bincode::Encode::encode(&self.line, encoder)
+        let stamped = StampedRecord { record: self, timestamp: self.timestamp, seq: self.seq, thread: self.thread, record_id: self.record_id };
+        bincode::Encode::encode(&stamped, encoder)
     }
 }
 
 impl bincode::Decode<()> for Record {
+    fn decode<D: bincode::de::Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        let RecordV3(record) = bincode::Decode::decode(decoder)?;
+        Ok(Record { record_id: bincode::Decode::decode(decoder)?, ..record })
+    }
+}
+
+/// v3 线格式的记录（没有记录标识）
+struct RecordV3(Record);
+
+impl bincode::Decode<()> for RecordV3 {
+    fn decode<D: bincode::de::Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        let RecordV2(record) = bincode::Decode::decode(decoder)?;
+        Ok(RecordV3(Record { level_label: bincode::Decode::decode(decoder)?, ..record }))
+    }
+}
+
+/// v2 线格式的记录（没有级别别名）
+struct RecordV2(Record);
+
+impl bincode::Decode<()> for RecordV2 {
     fn decode<D: bincode::de::Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
         let metadata = bincode::Decode::decode(decoder)?;
-        let args = bincode::Decode::decode(decoder)?;
+        let args: String = bincode::Decode::decode(decoder)?;
         let module_path = bincode::Decode::decode(decoder)?;
         let file = bincode::Decode::decode(decoder)?;
         let line = bincode::Decode::decode(decoder)?;
-        Ok(Record {
+        let fields = bincode::Decode::decode(decoder)?;
+        let timestamp: Option<(u64, u32)> = bincode::Decode::decode(decoder)?;
+        let seq = bincode::Decode::decode(decoder)?;
+        let thread: Option<(u64, u64)> = bincode::Decode::decode(decoder)?;
+        Ok(RecordV2(Record {
             metadata: std::sync::Arc::new(metadata),
-            args,
+            args: RecordArgs::Eager(args),
             module_path,
             file,
             line,
-        })
+            fields,
+            timestamp: timestamp.map(|(secs, nanos)| std::time::UNIX_EPOCH + std::time::Duration::new(secs, nanos)),
+            seq,
+            thread: thread.map(|(id, seq)| ThreadStamp { id, seq }),
+            level_label: None,
+            record_id: None,
+        }))
     }
 }
 
+/// 当前记录线格式版本（[`encode_record_v2`] 写在记录之前的首字节）
+///
+/// - v1: 元数据（级别为字符串）、消息、模块路径、文件、行号
+/// - v2: 级别改为单字节编码值（`Level::wire_value`），增加结构化字段、记录时间、日志器序号和线程标记
+/// - v3: 增加级别别名
+/// - v4: 增加记录标识
+///
+/// 新增字段时追加新版本，旧版本的解码保留在 [`decode_record_versioned`] 中，缺少的字段取默认值。
+pub const RECORD_VERSION: u8 = 4;
+
+/// 将记录编码为带版本号的当前线格式：首字节为 [`RECORD_VERSION`]，之后是记录本身
+pub fn encode_record_v2(record: &Record) -> Result<Vec<u8>, bincode::error::EncodeError> {
+    encode_versioned(record)
+}
+
+/// 写入版本号后编码记录（或编码结果与 `Record` 相同的视图）
+pub(crate) fn encode_versioned(record: &impl bincode::Encode) -> Result<Vec<u8>, bincode::error::EncodeError> {
+    let mut data = Vec::with_capacity(128);
+    data.push(RECORD_VERSION);
+    bincode::encode_into_std_write(record, &mut data, bincode::config::standard())?;
+    Ok(data)
+}
+
+/// 按首字节的版本号解码记录，返回记录和消耗的字节数（含版本号字节）
+///
+/// 旧版本记录中不存在的字段（v1 的结构化字段、记录时间、日志器序号、线程标记，v2 的级别别名，v3 的记录标识）取默认值。
+pub fn decode_record_versioned(data: &[u8]) -> Result<(Record, usize), bincode::error::DecodeError> {
+    let (&version, body) = data.split_first().ok_or(bincode::error::DecodeError::UnexpectedEnd { additional: 1 })?;
+    let (record, consumed) = match version {
+        1 => decode_record_legacy(body)?,
+        2 => bincode::decode_from_slice::<RecordV2, _>(body, bincode::config::standard()).map(|(record, consumed)| (record.0, consumed))?,
+        3 => bincode::decode_from_slice::<RecordV3, _>(body, bincode::config::standard()).map(|(record, consumed)| (record.0, consumed))?,
+        RECORD_VERSION => bincode::decode_from_slice(body, bincode::config::standard())?,
+        version => {
+            return Err(bincode::error::DecodeError::OtherString(format!("不支持的记录版本: {}", version)));
+        }
+    };
+    Ok((record, consumed + 1))
+}
+
+/// 解码没有版本号字节的 v1 记录（引入版本号之前写入的数据），返回记录和消耗的字节数
+pub fn decode_record_legacy(data: &[u8]) -> Result<(Record, usize), bincode::error::DecodeError> {
+    bincode::decode_from_slice::<RecordV1, _>(data, bincode::config::standard()).map(|(record, consumed)| (record.0, consumed))
+}
+
+/// v1 线格式的记录
+struct RecordV1(Record);
+
+impl bincode::Decode<()> for RecordV1 {
+    fn decode<D: bincode::de::Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        let metadata = Metadata {
+            level: Level::decode_legacy(decoder)?,
+            target: bincode::Decode::decode(decoder)?,
+            auth_token: bincode::Decode::decode(decoder)?,
+            app_id: bincode::Decode::decode(decoder)?,
+        };
+        Ok(RecordV1(Record {
+            metadata: std::sync::Arc::new(metadata),
+            args: RecordArgs::Eager(bincode::Decode::decode(decoder)?),
+            module_path: bincode::Decode::decode(decoder)?,
+            file: bincode::Decode::decode(decoder)?,
+            line: bincode::Decode::decode(decoder)?,
+            ..Default::default()
+        }))
+    }
+}
+
+/// 处理器默认接收 rat_logger 自身产生的记录
+pub(crate) fn default_include_internal() -> bool {
+    true
+}
+
+fn default_post_rotate_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_atomic_chunk_size() -> u64 {
+    64 * 1024
+}
+
 /// 文件日志配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileConfig {
     pub log_dir: PathBuf,
+    /// 单个日志文件的大小上限（字节，配置文件中也可写作 `"10MiB"`）
+    #[serde(with = "units::bytes")]
     pub max_file_size: u64,
+    /// 保留的压缩文件数量上限，0 表示禁用压缩（轮转后的文件保留为 `.log`，不按数量删除）
     pub max_compressed_files: usize,
     pub compression_level: u8,
+    /// 压缩线程数（全局压缩线程池在第一次压缩时创建，取所有文件处理器中的最大值，不超过 CPU 核数）
     pub min_compress_threads: usize,
     pub skip_server_logs: bool,
     pub is_raw: bool,
     pub compress_on_drop: bool, // 是否在Drop时强制压缩
     pub force_sync: bool,     // 是否强制同步写入磁盘
     pub format: Option<FormatConfig>, // 格式配置
+    /// 定期检查当前日志文件路径是否仍然存在的间隔（毫秒，0 表示不检查；配置文件中也可写作 `"5s"`）
+    ///
+    /// Unix 上日志目录被删除后写入会进入已删除的 inode 而不会报错，只能通过检查路径发现。
+    #[serde(with = "units::millis")]
+    pub check_path_interval_ms: u64,
+    /// 日志目录中日志文件（含压缩文件）的总大小上限（字节，0 表示不限制）
+    ///
+    /// 超出时从最旧的文件开始删除，当前活动文件不会被删除。禁用压缩时这是唯一的清理策略。
+    /// 配置文件中也可写作 `"1GiB"`。
+    #[serde(with = "units::bytes")]
+    pub max_total_size: u64,
+    /// 是否接收强制写入（紧急日志和 Error 级别日志），为 false 时这些记录改走普通批量路径
+    pub accept_emergency: bool,
+    /// 是否接收 rat_logger 自身产生的记录（目标为 `rat_logger` 或以 `rat_logger::` 开头，如熔断汇总和启动横幅）
+    #[serde(default = "default_include_internal")]
+    pub include_internal: bool,
+    /// 是否在每个新日志文件（启动或轮转时创建）的第一行写入头部
+    ///
+    /// 头部以 `# rat_logger ` 开头，包含版本、可执行文件名、进程号、主机名、开始时间、格式模板哈希和应用ID。
+    /// 头部在文件写入第一条日志前写入，不计入 `max_file_size`；追加到已有内容的文件时不写入。
+    pub write_header: bool,
+    /// 每条记录结尾的换行符（消息内部的换行符不受影响）
+    pub line_ending: LineEnding,
+    /// 是否在写入前将每个批次按记录时间排序
+    ///
+    /// 多个线程同时记录日志时，记录进入通道的顺序可能与记录时间不一致；排序只在同一批次内进行，
+    /// 批次之间仍按到达顺序写入。强制写入（紧急日志和 Error 级别日志）不参与排序。
+    pub order_by_timestamp: bool,
+    /// 排队和正在执行的压缩任务数上限（0 表示不限制）
+    ///
+    /// 轮转快于压缩时，超出上限的文件按 `compression_overflow` 处理。
+    pub max_pending_compressions: usize,
+    /// 压缩任务数达到 `max_pending_compressions` 时的处理方式
+    pub compression_overflow: CompressionOverflow,
+    /// 压缩归档的保留期限（按修改时间，None 表示不按期限清理；配置文件中也可写作 `"30d"`）
+    ///
+    /// 每次轮转和压缩完成后删除超过期限的 `.lz4` 归档，与 `max_compressed_files`、`max_total_size`
+    /// 同时配置时满足任一条件的文件都会被删除。未压缩的 `.log` 文件（包括当前活动文件）不受影响。
+    #[serde(default, with = "units::opt_duration")]
+    pub max_archive_age: Option<std::time::Duration>,
+    /// 日志目录已被另一个文件处理器使用时的处理方式
+    pub shared_dir_policy: SharedDirPolicy,
+    /// 日志文件名模板（不含 `.log` 扩展名，None 时为 `{prefix}{timestamp}`，即 `app_20240501_120000.log`）
+    ///
+    /// 支持的占位符：`{prefix}`（文件名前缀，见 `shared_dir_policy`）、`{timestamp}`（创建文件的本地时间，
+    /// `%Y%m%d_%H%M%S`）、`{app_id}`、`{hostname}`（主机名）和 `{seq}`（本处理器创建的第几个文件，从 1 开始）。
+    /// 模板必须包含 `{timestamp}` 或 `{seq}`。占位符在创建文件（启动和轮转）时解析；保留策略只清理与模板
+    /// 推导出的模式匹配的文件，同一目录中按其他模板命名的文件不受影响。
+    #[serde(default)]
+    pub file_name_template: Option<String>,
+    /// 文件名模板中 `{app_id}` 的值，None 时使用 `LoggerBuilder::with_app_id` 设置的应用ID
+    ///
+    /// 模板包含 `{app_id}` 而两者都未设置时配置无效。设置后也用作文件头部中的应用ID。
+    #[serde(default)]
+    pub app_id: Option<String>,
+    /// 轮转后执行的外部命令（程序和参数，None 表示不执行），如 `["/usr/local/bin/upload-log", "--bucket", "logs"]`
+    ///
+    /// 轮转出的文件路径作为最后一个参数传入；启用压缩时在压缩完成后执行，传入 `.log.lz4` 路径。
+    /// 命令在后台线程中依次执行，每个处理器同时最多执行一个；启动失败、超时或退出状态不为 0 时
+    /// 上报 `PostRotateCommandFailed`，不影响日志写入。
+    #[serde(default)]
+    pub post_rotate_command: Option<Vec<String>>,
+    /// 轮转后命令的超时时间（毫秒，配置文件中也可写作 `"30s"`），超时后终止命令
+    #[serde(default = "default_post_rotate_timeout_ms", with = "units::millis")]
+    pub post_rotate_timeout_ms: u64,
+    /// 是否按记录边界整块写入每个批次，进程被强制终止时文件末尾只会缺少完整的记录，不会留下半行
+    ///
+    /// 启用后批次不经过写缓冲，按记录边界切分为不超过 `atomic_chunk_size` 的块，每块用一次写入调用
+    /// 追加到以追加模式（`O_APPEND`）打开的文件；超过上限的单条记录单独成块。这依赖文件系统对单次追加
+    /// 写入的保证，在 POSIX 本地文件系统上有效，在网络文件系统和非 POSIX 文件系统上只是尽力而为。
+    #[serde(default)]
+    pub atomic_batches: bool,
+    /// 启用 `atomic_batches` 时每次写入调用的大小上限（字节，配置文件中也可写作 `"64KiB"`）
+    #[serde(default = "default_atomic_chunk_size", with = "units::bytes")]
+    pub atomic_chunk_size: u64,
 }
 
 impl FileConfig {
+    /// 设置单个日志文件的大小上限，如 `"10MiB".parse()?` 或 `ByteSize::mib(10)`
+    pub fn with_max_file_size(mut self, size: ByteSize) -> Self {
+        self.max_file_size = size.as_u64();
+        self
+    }
+
+    /// 设置日志目录中日志文件的总大小上限
+    pub fn with_max_total_size(mut self, size: ByteSize) -> Self {
+        self.max_total_size = size.as_u64();
+        self
+    }
+
+    /// 设置压缩归档的保留期限，如 `DurationMs::days(30)`
+    pub fn with_max_archive_age(mut self, age: DurationMs) -> Self {
+        self.max_archive_age = Some(age.as_duration());
+        self
+    }
+
+    /// 设置轮转后执行的外部命令和超时时间
+    pub fn with_post_rotate_command<S: Into<String>>(mut self, command: impl IntoIterator<Item = S>, timeout: DurationMs) -> Self {
+        self.post_rotate_command = Some(command.into_iter().map(Into::into).collect());
+        self.post_rotate_timeout_ms = timeout.as_millis();
+        self
+    }
+
+    /// 启用按记录边界整块写入批次，每次写入调用不超过 `chunk_size`
+    pub fn with_atomic_batches(mut self, chunk_size: ByteSize) -> Self {
+        self.atomic_batches = true;
+        self.atomic_chunk_size = chunk_size.as_u64();
+        self
+    }
+
+    /// 设置检查日志文件路径是否仍然存在的间隔
+    pub fn with_check_path_interval(mut self, interval: DurationMs) -> Self {
+        self.check_path_interval_ms = interval.as_millis();
+        self
+    }
+
     /// 验证配置的有效性
     pub fn validate(&self) -> Result<(), String> {
         // 验证文件大小
@@ -247,24 +925,34 @@ impl FileConfig {
             return Err("配置错误: 最小压缩线程数过多 (最大 32)".to_string());
         }
 
-        // 如果启用压缩，则验证相关参数
-        if self.compression_level > 0 || self.max_compressed_files > 0 {
-            if self.max_compressed_files == 0 {
-                return Err("配置错误: 启用压缩时最大压缩文件数量不能为 0".to_string());
+        // max_compressed_files 为 0 表示禁用压缩，不能同时指定压缩相关参数
+        if self.max_compressed_files == 0 {
+            if self.compression_level > 0 {
+                return Err("配置冲突: max_compressed_files 为 0 表示禁用压缩，此时 compression_level 必须为 0".to_string());
             }
-            if self.min_compress_threads == 0 {
-                return Err("配置错误: 启用压缩时最小压缩线程数不能为 0".to_string());
+            if self.compress_on_drop {
+                return Err("配置冲突: max_compressed_files 为 0 表示禁用压缩，此时不能启用 compress_on_drop".to_string());
             }
+        } else if self.min_compress_threads == 0 {
+            return Err("配置错误: 启用压缩时最小压缩线程数不能为 0".to_string());
+        }
+
+        if self.max_pending_compressions > 0 && self.max_compressed_files == 0 {
+            return Err("配置冲突: max_compressed_files 为 0 表示禁用压缩，此时 max_pending_compressions 必须为 0".to_string());
+        }
+        if self.max_archive_age.is_some() && self.max_compressed_files == 0 {
+            return Err("配置冲突: max_compressed_files 为 0 表示禁用压缩，不会产生归档，此时不能设置 max_archive_age".to_string());
+        }
+        if self.max_archive_age.is_some_and(|age| age.is_zero()) {
+            return Err("配置错误: max_archive_age 不能为 0".to_string());
+        }
+        if self.compression_overflow == CompressionOverflow::WaitMs(0) {
+            return Err("配置错误: compression_overflow 的等待时间不能为 0".to_string());
         }
 
         // 验证格式配置（如果提供）
         if let Some(format_config) = &self.format {
-            if format_config.format_template.is_empty() {
-                return Err("配置错误: 格式模板不能为空".to_string());
-            }
-            if format_config.timestamp_format.is_empty() {
-                return Err("配置错误: 时间戳格式不能为空".to_string());
-            }
+            format_config.validate()?;
         }
 
         // 验证原始模式冲突
@@ -272,6 +960,23 @@ impl FileConfig {
             return Err("配置冲突: 原始模式 (is_raw) 下不能指定格式配置。原始模式将直接输出原始日志内容。".to_string());
         }
 
+        if let Some(template) = &self.file_name_template {
+            crate::handler::file::validate_file_name_template(template, self.app_id.as_deref())?;
+        }
+
+        if let Some(command) = &self.post_rotate_command {
+            if command.first().is_none_or(|program| program.is_empty()) {
+                return Err("配置错误: post_rotate_command 必须指定要执行的程序".to_string());
+            }
+            if self.post_rotate_timeout_ms == 0 {
+                return Err("配置错误: post_rotate_timeout_ms 不能为 0".to_string());
+            }
+        }
+
+        if self.atomic_batches && self.atomic_chunk_size == 0 {
+            return Err("配置错误: 启用 atomic_batches 时 atomic_chunk_size 不能为 0".to_string());
+        }
+
         Ok(())
     }
 }
@@ -289,25 +994,215 @@ impl Default for FileConfig {
             compress_on_drop: false, // 默认不在Drop时压缩
             force_sync: false,      // 默认异步写入
             format: None,
+            check_path_interval_ms: 0,
+            max_total_size: 0,
+            accept_emergency: true,
+            include_internal: true,
+            write_header: false,
+            line_ending: LineEnding::Lf,
+            order_by_timestamp: false,
+            max_pending_compressions: 0,
+            compression_overflow: CompressionOverflow::Skip,
+            max_archive_age: None,
+            shared_dir_policy: SharedDirPolicy::Error,
+            file_name_template: None,
+            app_id: None,
+            post_rotate_command: None,
+            post_rotate_timeout_ms: default_post_rotate_timeout_ms(),
+            atomic_batches: false,
+            atomic_chunk_size: default_atomic_chunk_size(),
         }
     }
 }
 
 /// 日志格式配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FormatConfig {
     /// 时间戳格式
     pub timestamp_format: String,
     /// 日志级别显示样式
     pub level_style: LevelStyle,
     /// 通用输出模板
+    ///
+    /// 支持条件段 `{?file}...{/file}`，字段不存在或为空时整段省略。
     pub format_template: String,
     /// 各级别专用模板（为空时使用通用模板）
     pub level_templates: Option<LevelTemplates>,
+    /// 控制字符处理方式（为空时使用输出默认值：文件为 `Escape`，终端为 `Strip`）
+    pub sanitize: Option<SanitizeMode>,
+    /// 时间戳是否按 UTC 渲染（默认为本地时间）
+    #[serde(default)]
+    pub timestamp_utc: bool,
+    /// 模板中允许使用的自定义占位符（结构化字段名），内置占位符见 [`template::BUILTIN_PLACEHOLDERS`]
+    #[serde(default)]
+    pub custom_placeholders: Vec<String>,
+    /// `{uptime}` 的小数位数（单位为秒，如 `+12.345s`）
+    #[serde(default = "default_uptime_precision")]
+    pub uptime_precision: usize,
+    /// `{delta}` 的小数位数（不足 1 秒时单位为毫秒，如 `+0.8ms`，否则为秒）
+    #[serde(default = "default_delta_precision")]
+    pub delta_precision: usize,
+}
+
+fn default_uptime_precision() -> usize {
+    3
+}
+
+fn default_delta_precision() -> usize {
+    1
+}
+
+impl FormatConfig {
+    /// 带检查的构建器：`build()` 时检查占位符名称、花括号配对和时间戳格式
+    ///
+    /// ```rust
+    /// use rat_logger::FormatConfig;
+    ///
+    /// let format = FormatConfig::builder()
+    ///     .template("{timestamp} [{level}] {request_id} {message}")
+    ///     .timestamp("%H:%M:%S%.3f")
+    ///     .placeholder("request_id")
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(FormatConfig::builder().template("{mesage}").build().is_err());
+    /// ```
+    pub fn builder() -> FormatConfigBuilder {
+        FormatConfigBuilder::new()
+    }
+
+    /// 验证配置的有效性（与 [`builder`](Self::builder) 的检查相同）
+    pub fn validate(&self) -> Result<(), String> {
+        template::check(self).map_err(|e| format!("配置错误: {}", e))
+    }
+
+    /// env_logger 默认格式：`[2024-05-01T12:00:00Z INFO  my_crate] message`
+    ///
+    /// 时间戳为精确到秒的 UTC RFC3339，级别左对齐到 5 个字符，后跟目标。
+    pub fn env_logger_style() -> Self {
+        Self {
+            timestamp_format: "%Y-%m-%dT%H:%M:%SZ".to_string(),
+            level_style: LevelStyle {
+                error: "ERROR".to_string(),
+                warn: "WARN ".to_string(),
+                info: "INFO ".to_string(),
+                debug: "DEBUG".to_string(),
+                trace: "TRACE".to_string(),
+            },
+            format_template: "[{timestamp} {level} {target}] {message}".to_string(),
+            level_templates: None,
+            sanitize: None,
+            timestamp_utc: true,
+            custom_placeholders: Vec::new(),
+            uptime_precision: 3,
+            delta_precision: 1,
+        }
+    }
+}
+
+/// 文件输出中记录结尾的换行符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`（Windows 记事本等工具需要）
+    CrLf,
+    /// 当前平台的换行符（Windows 上为 `\r\n`，其他平台为 `\n`）
+    Native,
+}
+
+impl LineEnding {
+    /// 换行符文本
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Native if cfg!(windows) => "\r\n",
+            LineEnding::Native => "\n",
+        }
+    }
+}
+
+/// 压缩任务数达到上限时轮转出的文件的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CompressionOverflow {
+    /// 不压缩该文件，保留为 `.log`，由 `max_total_size` 清理
+    #[default]
+    Skip,
+    /// 阻塞轮转最多指定毫秒数等待空位，超时后按 `Skip` 处理
+    WaitMs(u64),
+}
+
+/// 日志目录已被另一个文件处理器使用时的处理方式
+///
+/// 文件处理器启动时对日志目录中的 `.rat_logger.lock` 加建议锁，持有锁的处理器使用默认文件名
+/// （`app_<时间>.log`）。同一目录的多个处理器各自轮转和清理，会删除对方的活动文件或轮转到同名文件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SharedDirPolicy {
+    /// 创建处理器失败（`FileProcessorError::DirInUse`）
+    #[default]
+    Error,
+    /// 使用带进程号和实例序号的文件名前缀（`app-<pid>-<n>_<时间>.log`），轮转和清理只涉及自己的文件
+    ///
+    /// 进程退出后这些文件不会被之后的实例清理。
+    Prefix,
+}
+
+/// 控制字符处理方式
+///
+/// 作用于 `{message}`、`{target}` 和结构化字段值，防止用户输入中的 `\r`、ANSI 转义序列
+/// 伪造日志行或干扰终端。换行符不在处理范围内。原始模式 (`is_raw`) 不做处理。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SanitizeMode {
+    /// 原样输出
+    Off,
+    /// 将控制字符转义为可见文本（如 `\r`、`\x1b`）
+    Escape,
+    /// 删除控制字符和 ANSI CSI 序列
+    Strip,
+}
+
+impl SanitizeMode {
+    /// 处理字符串，不含控制字符时不分配内存
+    pub fn apply<'a>(&self, text: &'a str) -> std::borrow::Cow<'a, str> {
+        let needs_sanitize = |c: char| c.is_control() && c != '\n';
+        if *self == SanitizeMode::Off || !text.contains(needs_sanitize) {
+            return std::borrow::Cow::Borrowed(text);
+        }
+
+        let mut out = String::with_capacity(text.len() + 8);
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if !needs_sanitize(c) {
+                out.push(c);
+                continue;
+            }
+            match self {
+                SanitizeMode::Escape => match c {
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    _ => out.push_str(&format!("\\x{:02x}", c as u32)),
+                },
+                SanitizeMode::Strip => {
+                    // CSI 序列：ESC [ 参数字节 中间字节 结束字节(0x40-0x7E)
+                    if c == '\x1b' && chars.peek() == Some(&'[') {
+                        chars.next();
+                        for next in chars.by_ref() {
+                            if ('\x40'..='\x7e').contains(&next) {
+                                break;
+                            }
+                        }
+                    }
+                }
+                SanitizeMode::Off => unreachable!(),
+            }
+        }
+        std::borrow::Cow::Owned(out)
+    }
 }
 
 /// 各级别专用模板
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LevelTemplates {
     /// ERROR 级别模板（支持继承，设为 "+" 则继承通用模板）
     pub error: Option<String>,
@@ -321,6 +1216,19 @@ pub struct LevelTemplates {
     pub trace: Option<String>,
 }
 
+impl LevelTemplates {
+    /// 指定级别的专用模板
+    pub fn get(&self, level: Level) -> Option<&str> {
+        match level {
+            Level::Error => self.error.as_deref(),
+            Level::Warn => self.warn.as_deref(),
+            Level::Info => self.info.as_deref(),
+            Level::Debug => self.debug.as_deref(),
+            Level::Trace => self.trace.as_deref(),
+        }
+    }
+}
+
 impl Default for LevelTemplates {
     fn default() -> Self {
         Self {
@@ -334,7 +1242,7 @@ impl Default for LevelTemplates {
 }
 
 /// 日志级别样式配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LevelStyle {
     /// 错误级别显示
     pub error: String,
@@ -348,8 +1256,21 @@ pub struct LevelStyle {
     pub trace: String,
 }
 
+impl LevelStyle {
+    /// 指定级别的显示文本
+    pub fn get(&self, level: Level) -> &str {
+        match level {
+            Level::Error => &self.error,
+            Level::Warn => &self.warn,
+            Level::Info => &self.info,
+            Level::Debug => &self.debug,
+            Level::Trace => &self.trace,
+        }
+    }
+}
+
 /// 终端颜色配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ColorConfig {
     /// 错误级别颜色 (ANSI颜色代码)
     pub error: String,
@@ -367,26 +1288,338 @@ pub struct ColorConfig {
     pub target: String,
     /// 文件名颜色
     pub file: String,
-    /// 消息颜色
+    /// 消息颜色（`message_follows_level` 为 true 时不使用）
     pub message: String,
+    /// 消息是否使用所在级别的颜色（如 Error 级别的消息显示为 `error` 颜色），为 false 时使用 `message`
+    #[serde(default)]
+    pub message_follows_level: bool,
+    /// 级别别名的颜色（别名, ANSI颜色代码），未配置的别名使用所在级别的颜色
+    #[serde(default)]
+    pub label_colors: Vec<(String, String)>,
+    /// 按目标前缀覆盖 `{target}` 的颜色（前缀, ANSI颜色代码），最长前缀优先，没有匹配时使用 `target`
+    #[serde(default)]
+    pub target_colors: Vec<(String, String)>,
+}
+
+impl ColorConfig {
+    /// 指定级别的颜色
+    pub fn get(&self, level: Level) -> &str {
+        match level {
+            Level::Error => &self.error,
+            Level::Warn => &self.warn,
+            Level::Info => &self.info,
+            Level::Debug => &self.debug,
+            Level::Trace => &self.trace,
+        }
+    }
+
+    /// 记录的级别颜色：级别别名配置了颜色时使用别名的颜色，否则使用所在级别的颜色
+    pub fn for_record(&self, record: &Record) -> &str {
+        record.level_label.as_deref()
+            .and_then(|label| self.label_colors.iter().find(|(l, _)| l == label))
+            .map_or_else(|| self.get(record.metadata.level), |(_, color)| color.as_str())
+    }
+
+    /// 目标的颜色：按最长前缀匹配 `target_colors`，没有匹配时使用 `target`
+    pub fn for_target(&self, target: &str) -> &str {
+        longest_prefix_match(&self.target_colors, target).map_or(&self.target, |(_, color)| color.as_str())
+    }
+
+    /// env_logger 默认配色：只为级别着色，时间戳、目标和消息保持终端默认颜色
+    pub fn env_logger_style() -> Self {
+        Self {
+            error: "\x1b[31m".to_string(),      // 红色
+            warn: "\x1b[33m".to_string(),       // 黄色
+            info: "\x1b[32m".to_string(),       // 绿色
+            debug: "\x1b[34m".to_string(),      // 蓝色
+            trace: "\x1b[36m".to_string(),      // 青色
+            timestamp: String::new(),
+            target: String::new(),
+            file: String::new(),
+            message: String::new(),
+            message_follows_level: false,
+            label_colors: Vec::new(),
+            target_colors: Vec::new(),
+        }
+    }
+}
+
+/// 按目标前缀配置的规则中与目标匹配的最长前缀规则（采样比例、目标颜色等共用）
+pub(crate) fn longest_prefix_match<'a, T>(rules: &'a [(String, T)], target: &str) -> Option<&'a (String, T)> {
+    rules.iter()
+        .filter(|(prefix, _)| target.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+}
+
+/// 采样配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    /// 默认采样比例（0.0 - 1.0）
+    pub default_ratio: f32,
+    /// 按目标前缀覆盖采样比例（最长前缀优先）
+    pub per_target: Vec<(String, f32)>,
+    /// 采样作用的级别阈值：只对该级别及更详细的记录采样（如 Debug 表示 Debug 和 Trace）
+    pub levels: LevelFilter,
+    /// 是否为通过采样的记录添加 `sampled=<比例>` 字段，便于下游按比例还原
+    pub annotate: bool,
+    /// 固定随机种子（用于测试中的确定性采样）
+    pub seed: Option<u64>,
+}
+
+impl SamplingConfig {
+    /// 验证配置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        let valid = |ratio: f32| (0.0..=1.0).contains(&ratio);
+        if !valid(self.default_ratio) {
+            return Err(format!("配置错误: 默认采样比例必须在 0.0-1.0 之间，当前为 {}", self.default_ratio));
+        }
+        for (target, ratio) in &self.per_target {
+            if !valid(*ratio) {
+                return Err(format!("配置错误: 目标 {} 的采样比例必须在 0.0-1.0 之间，当前为 {}", target, ratio));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            default_ratio: 1.0,
+            per_target: Vec::new(),
+            levels: LevelFilter::Debug,
+            annotate: false,
+            seed: None,
+        }
+    }
+}
+
+/// 熔断后的处理方式（见 [`BreakerConfig`]）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BreakerAction {
+    /// 丢弃 Error 以外的记录，熔断期间每秒输出一条 Warn 级别的汇总记录（上一秒丢弃的记录数）
+    DropWithSummary,
+    /// 只保留 Error 级别的记录，不输出汇总（丢弃数只计入统计）
+    DegradeToErrorOnly,
+}
+
+/// 全局熔断配置：整个日志器的记录速率超过上限时丢弃 Error 以外的记录，速率持续低于恢复阈值后恢复
+///
+/// 速率按 1 秒的固定窗口统计，只统计通过级别过滤的记录。与采样不同，熔断不区分目标。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakerConfig {
+    /// 每秒记录数上限，当前窗口内的记录数超过该值时熔断
+    pub max_records_per_sec: u64,
+    /// 熔断后的处理方式
+    pub action: BreakerAction,
+    /// 恢复阈值（每秒记录数），速率低于该值持续 `cooldown_ms` 后恢复
+    pub recovery_records_per_sec: u64,
+    /// 恢复前速率需持续低于恢复阈值的时间（毫秒，配置文件中也可写作 `"5s"`）
+    #[serde(with = "units::millis")]
+    pub cooldown_ms: u64,
+}
+
+impl BreakerConfig {
+    /// 创建熔断配置：恢复阈值为上限的一半，冷却时间 5 秒
+    pub fn new(max_records_per_sec: u64, action: BreakerAction) -> Self {
+        Self {
+            max_records_per_sec,
+            action,
+            recovery_records_per_sec: (max_records_per_sec / 2).max(1),
+            cooldown_ms: 5000,
+        }
+    }
+
+    /// 设置恢复阈值和冷却时间
+    pub fn with_recovery(mut self, records_per_sec: u64, cooldown: DurationMs) -> Self {
+        self.recovery_records_per_sec = records_per_sec;
+        self.cooldown_ms = cooldown.as_millis();
+        self
+    }
+
+    /// 验证配置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_records_per_sec == 0 {
+            return Err("配置错误: 每秒记录数上限不能为 0".to_string());
+        }
+        if self.recovery_records_per_sec == 0 || self.recovery_records_per_sec > self.max_records_per_sec {
+            return Err(format!(
+                "配置错误: 恢复阈值必须在 1 到每秒记录数上限（{}）之间，当前为 {}",
+                self.max_records_per_sec, self.recovery_records_per_sec
+            ));
+        }
+        if self.cooldown_ms == 0 {
+            return Err("配置错误: 冷却时间不能为 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 超出内存预算时的处理方式（见 [`MemoryBudget`]）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemoryAction {
+    /// 丢弃新提交的记录（丢弃数只计入统计）
+    DropNewest,
+    /// 阻塞提交记录的线程，直到处理器处理完足够的数据
+    BlockProducers,
+    /// 只保留 Error 级别的记录，管道中的数据降到预算的一半以下后恢复
+    DegradeToErrorOnly,
+}
+
+/// 管道探测标记的输出方式（见 `LoggerBuilder::with_pipeline_probe`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ProbeMarker {
+    /// 标记只用于计时，不写入任何输出
+    #[default]
+    Suppressed,
+    /// 同时写入一条目标为 [`PROBE_TARGET`] 的 Info 记录（接收 rat_logger 自身记录的处理器会输出）
+    Written,
+}
+
+/// 内存预算：限制已提交但处理器尚未处理完的数据总量
+///
+/// 每条记录按编码后的大小乘以接收它的处理器数计入 `LoggerStats::in_flight_bytes()`，处理器处理完后扣除。
+/// Error 级别和紧急日志可以额外使用 `emergency_reserve` 的预留；管道为空时任何记录都会放行。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryBudget {
+    /// 预算（字节，配置文件中也可写作 `"64MiB"`）
+    #[serde(with = "units::bytes")]
+    pub max_bytes: u64,
+    /// 超出预算时的处理方式
+    pub action: MemoryAction,
+    /// Error 级别和紧急日志可额外使用的预留（字节）
+    #[serde(with = "units::bytes")]
+    pub emergency_reserve: u64,
+}
+
+impl MemoryBudget {
+    /// 创建内存预算：预留为预算的 1/16
+    pub fn new(max_bytes: ByteSize, action: MemoryAction) -> Self {
+        Self {
+            max_bytes: max_bytes.as_u64(),
+            action,
+            emergency_reserve: max_bytes.as_u64() / 16,
+        }
+    }
+
+    /// 设置 Error 级别和紧急日志可额外使用的预留
+    pub fn with_emergency_reserve(mut self, reserve: ByteSize) -> Self {
+        self.emergency_reserve = reserve.as_u64();
+        self
+    }
+
+    /// 验证配置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_bytes == 0 {
+            return Err("配置错误: 内存预算不能为 0".to_string());
+        }
+        Ok(())
+    }
 }
 
 /// 网络日志配置
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NetworkConfig {
+    /// 服务器地址：IPv4 地址、IPv6 地址（可以不加方括号）或主机名
     pub server_addr: String,
     pub server_port: u16,
     pub auth_token: String,
+    /// 数据包的应用ID，为空时使用记录的应用ID，记录也没有时使用可执行文件名
     pub app_id: String,
 }
 
+impl NetworkConfig {
+    /// 由 `host:port` 形式的地址创建配置（如服务发现返回的 `collector.internal:5140`）
+    ///
+    /// IPv6 地址需要加方括号（`[::1]:5140`），否则无法区分地址和端口。返回的配置已通过 [`validate`](Self::validate)。
+    pub fn from_endpoint(endpoint: &str, auth_token: impl Into<String>, app_id: impl Into<String>) -> Result<Self, String> {
+        let (host, port) = split_endpoint(endpoint)?;
+        let config = Self {
+            server_addr: host.to_string(),
+            server_port: port,
+            auth_token: auth_token.into(),
+            app_id: app_id.into(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 去掉方括号的服务器主机（`[::1]` 返回 `::1`）
+    pub fn host(&self) -> &str {
+        self.server_addr.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(&self.server_addr)
+    }
+
+    /// 用于连接的 `host:port` 地址，IPv6 地址自动加方括号（`[::1]:5140`）
+    pub fn endpoint(&self) -> String {
+        format_endpoint(self.host(), self.server_port)
+    }
+
+    /// 验证配置的有效性（服务器地址为 IP 或合法主机名，端口不为 0）
+    ///
+    /// 不检查地址能否解析，需要时使用 `UdpConfig::verify_connectivity`；应用ID为空时使用记录的应用ID或可执行文件名。
+    pub fn validate(&self) -> Result<(), String> {
+        if self.server_addr.is_empty() {
+            return Err("配置错误: 服务器地址不能为空".to_string());
+        }
+        if self.host().parse::<std::net::IpAddr>().is_err() && !is_plausible_hostname(&self.server_addr) {
+            return Err(format!("配置错误: 服务器地址既不是 IP 地址也不是合法的主机名: {}", self.server_addr));
+        }
+        if self.server_port == 0 {
+            return Err("配置错误: 服务器端口不能为 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 拆分 `host:port` 形式的地址，IPv6 地址需要加方括号
+fn split_endpoint(endpoint: &str) -> Result<(&str, u16), String> {
+    let (host, port) = match endpoint.strip_prefix('[') {
+        Some(rest) => rest.split_once("]:"),
+        None => endpoint.rsplit_once(':'),
+    }
+    .ok_or_else(|| format!("配置错误: 地址缺少端口: {}", endpoint))?;
+    if !endpoint.starts_with('[') && host.contains(':') {
+        return Err(format!("配置错误: IPv6 地址需要加方括号（如 [::1]:5140）: {}", endpoint));
+    }
+    let port = port.parse().map_err(|_| format!("配置错误: 端口无效: {}", endpoint))?;
+    Ok((host, port))
+}
+
+/// 规范的 `host:port` 地址：IP 地址使用标准写法（IPv6 加方括号），主机名转为小写
+pub(crate) fn format_endpoint(host: &str, port: u16) -> String {
+    match host.parse::<std::net::IpAddr>() {
+        Ok(ip) => std::net::SocketAddr::new(ip, port).to_string(),
+        Err(_) => format!("{}:{}", host.to_ascii_lowercase(), port),
+    }
+}
+
+/// 把 `host:port` 地址转为规范写法，无法拆分的地址原样返回
+pub(crate) fn normalize_endpoint(endpoint: &str) -> String {
+    match split_endpoint(endpoint) {
+        Ok((host, port)) => format_endpoint(host, port),
+        Err(_) => endpoint.to_string(),
+    }
+}
+
+/// 主机名是否合法：总长不超过 253，每段 1-63 个字母、数字或连字符，且不以连字符开头或结尾
+fn is_plausible_hostname(host: &str) -> bool {
+    host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
 impl Default for NetworkConfig {
     fn default() -> Self {
         Self {
             server_addr: "127.0.0.1".to_string(),
             server_port: 5140,
             auth_token: "default_token".to_string(),
-            app_id: "default_app".to_string(),
+            app_id: String::new(),
         }
     }
 }
@@ -398,6 +1631,11 @@ impl Default for FormatConfig {
             level_style: LevelStyle::default(),
             format_template: "{timestamp} [{level}] {target}:{line} - {message}".to_string(),
             level_templates: None,
+            sanitize: None,
+            timestamp_utc: false,
+            custom_placeholders: Vec::new(),
+            uptime_precision: default_uptime_precision(),
+            delta_precision: default_delta_precision(),
         }
     }
 }
@@ -414,6 +1652,9 @@ impl Default for ColorConfig {
             target: "\x1b[34m".to_string(),      // 蓝色
             file: "\x1b[35m".to_string(),       // 紫色
             message: "\x1b[0m".to_string(),      // 重置颜色
+            message_follows_level: false,
+            label_colors: Vec::new(),
+            target_colors: Vec::new(),
         }
     }
 }
@@ -432,7 +1673,7 @@ impl Default for LevelStyle {
 
 
 /// 用于网络传输的日志记录
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetRecord {
     pub level: Level,
     pub target: String,
@@ -443,22 +1684,51 @@ pub struct NetRecord {
     pub timestamp: u64,
     pub auth_token: Option<String>,
     pub app_id: Option<String>,
+    /// 发送端主机名
+    pub hostname: Option<String>,
+    /// 发送端进程号
+    pub pid: u32,
+    /// 发送端数据包序号（进程内递增，v1 数据包为 0）
+    pub seq: u64,
+    /// 时间戳的亚秒部分（纳秒，v1 数据包为 0）
+    pub timestamp_nanos: u32,
+    /// 日志器序号（发送端启用 `LoggerBuilder::with_sequence()` 时存在，v1 数据包为 None）
+    pub log_seq: Option<u64>,
+    /// 发送端可执行文件名（v1 数据包为 None）
+    pub binary: Option<String>,
+    /// 级别别名（v1 数据包为 None）
+    #[serde(default)]
+    pub level_label: Option<String>,
+    /// 记录标识（与同一条记录在其他处理器中的 `{record_id}` 相同，v1 数据包为 None）
+    #[serde(default)]
+    pub record_id: Option<u64>,
 }
 
+/// 线格式说明（v2）：头部字段在前，服务端无需解码消息体即可过滤；级别以单字节编码值表示
+///
+/// `level, app_id, target, timestamp, timestamp_nanos, seq, log_seq, hostname, pid, binary, message, module_path, file, line, auth_token, level_label, record_id`
 impl bincode::Encode for NetRecord {
     fn encode<E: bincode::enc::Encoder>(
         &self,
         encoder: &mut E,
     ) -> Result<(), bincode::error::EncodeError> {
         bincode::Encode::encode(&self.level, encoder)?;
+        bincode::Encode::encode(&self.app_id, encoder)?;
         bincode::Encode::encode(&self.target, encoder)?;
+        bincode::Encode::encode(&self.timestamp, encoder)?;
+        bincode::Encode::encode(&self.timestamp_nanos, encoder)?;
+        bincode::Encode::encode(&self.seq, encoder)?;
+        bincode::Encode::encode(&self.log_seq, encoder)?;
+        bincode::Encode::encode(&self.hostname, encoder)?;
+        bincode::Encode::encode(&self.pid, encoder)?;
+        bincode::Encode::encode(&self.binary, encoder)?;
         bincode::Encode::encode(&self.message, encoder)?;
         bincode::Encode::encode(&self.module_path, encoder)?;
         bincode::Encode::encode(&self.file, encoder)?;
         bincode::Encode::encode(&self.line, encoder)?;
-        bincode::Encode::encode(&self.timestamp, encoder)?;
         bincode::Encode::encode(&self.auth_token, encoder)?;
-        bincode::Encode::encode(&self.app_id, encoder)?;
+        bincode::Encode::encode(&self.level_label, encoder)?;
+        bincode::Encode::encode(&self.record_id, encoder)?;
         Ok(())
     }
 }
@@ -467,8 +1737,45 @@ impl bincode::Decode<()> for NetRecord {
     fn decode<D: bincode::de::Decoder<Context = ()>>(
         decoder: &mut D,
     ) -> Result<Self, bincode::error::DecodeError> {
+        let level = bincode::Decode::decode(decoder)?;
+        let app_id = bincode::Decode::decode(decoder)?;
+        let target = bincode::Decode::decode(decoder)?;
+        let timestamp = bincode::Decode::decode(decoder)?;
+        let timestamp_nanos = bincode::Decode::decode(decoder)?;
+        let seq = bincode::Decode::decode(decoder)?;
+        let log_seq = bincode::Decode::decode(decoder)?;
+        let hostname = bincode::Decode::decode(decoder)?;
+        let pid = bincode::Decode::decode(decoder)?;
+        let binary = bincode::Decode::decode(decoder)?;
         Ok(Self {
-            level: bincode::Decode::decode(decoder)?,
+            level,
+            target,
+            message: bincode::Decode::decode(decoder)?,
+            module_path: bincode::Decode::decode(decoder)?,
+            file: bincode::Decode::decode(decoder)?,
+            line: bincode::Decode::decode(decoder)?,
+            timestamp,
+            auth_token: bincode::Decode::decode(decoder)?,
+            app_id,
+            hostname,
+            pid,
+            seq,
+            timestamp_nanos,
+            log_seq,
+            binary,
+            level_label: bincode::Decode::decode(decoder)?,
+            record_id: bincode::Decode::decode(decoder)?,
+        })
+    }
+}
+
+impl NetRecord {
+    /// 解码 v1 线格式（无头部数据包，级别以字符串编码，没有主机信息等字段）
+    pub(crate) fn decode_v1<D: bincode::de::Decoder<Context = ()>>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        Ok(Self {
+            level: Level::decode_legacy(decoder)?,
             target: bincode::Decode::decode(decoder)?,
             message: bincode::Decode::decode(decoder)?,
             module_path: bincode::Decode::decode(decoder)?,
@@ -477,25 +1784,48 @@ impl bincode::Decode<()> for NetRecord {
             timestamp: bincode::Decode::decode(decoder)?,
             auth_token: bincode::Decode::decode(decoder)?,
             app_id: bincode::Decode::decode(decoder)?,
+            hostname: None,
+            pid: 0,
+            seq: 0,
+            timestamp_nanos: 0,
+            log_seq: None,
+            binary: None,
+            level_label: None,
+            record_id: None,
         })
     }
 }
 
 impl From<&Record> for NetRecord {
     fn from(record: &Record) -> Self {
+        let timestamp = record.timestamp.unwrap_or_else(std::time::SystemTime::now);
         NetRecord {
             level: record.metadata.level,
             target: record.metadata.target.clone(),
-            message: record.args.clone(),
+            message: record.args.to_string(),
             module_path: record.module_path.clone(),
             file: record.file.clone(),
             line: record.line,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: split_system_time(timestamp).0,
             auth_token: record.metadata.auth_token.clone(),
             app_id: record.metadata.app_id.clone(),
+            hostname: crate::process_info::hostname().map(|h| h.to_string()),
+            pid: crate::process_info::pid(),
+            seq: 0,
+            timestamp_nanos: split_system_time(timestamp).1,
+            log_seq: record.seq,
+            binary: Some(crate::process_info::binary_name())
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+            level_label: record.level_label.clone(),
+            record_id: record.record_id,
         }
     }
 }
+
+impl NetRecord {
+    /// 记录时间（包含亚秒部分）
+    pub fn system_time(&self) -> std::time::SystemTime {
+        std::time::UNIX_EPOCH + std::time::Duration::new(self.timestamp, self.timestamp_nanos)
+    }
+}
@@ -0,0 +1,229 @@
+//! 格式模板检查 - 占位符名称、花括号配对和时间戳格式，以及带检查的 [`FormatConfig`] 构建器
+
+use chrono::format::{Item, StrftimeItems};
+
+use super::{FormatConfig, LevelStyle, LevelTemplates, SanitizeMode};
+
+/// 内置占位符（也可用作条件段 `{?name}...{/name}` 的名称）
+///
+/// `hostname`/`pid` 是 UDP 发送端写入的结构化字段，服务端模板可以直接使用。
+pub const BUILTIN_PLACEHOLDERS: &[&str] = &[
+    "timestamp", "level", "target", "file", "line", "message", "seq", "tid", "tseq", "app_id", "module", "binary",
+    "fields", "hostname", "pid", "uptime", "delta", "record_id",
+];
+
+/// 格式模板错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TemplateError {
+    /// 模板为空
+    EmptyTemplate,
+    /// 未知的占位符（既不是内置占位符也没有注册为自定义占位符），按出现顺序列出，不重复
+    UnknownPlaceholders {
+        unknown: Vec<String>,
+    },
+    /// 花括号不配对，`position` 为第一个不配对的花括号的字节位置
+    UnbalancedBrace {
+        template: String,
+        position: usize,
+    },
+    /// 时间戳格式无法解析
+    InvalidTimestamp {
+        format: String,
+    },
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateError::EmptyTemplate => write!(f, "格式模板不能为空"),
+            TemplateError::UnknownPlaceholders { unknown } => {
+                let names: Vec<String> = unknown.iter().map(|name| format!("{{{}}}", name)).collect();
+                write!(f, "格式模板包含未知的占位符: {}（自定义占位符需通过 FormatConfigBuilder::placeholder 注册）", names.join(", "))
+            }
+            TemplateError::UnbalancedBrace { template, position } => {
+                write!(f, "格式模板的花括号不配对（位置 {}）: {}", position, template)
+            }
+            TemplateError::InvalidTimestamp { format } => write!(f, "时间戳格式无法解析: {}", format),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// 检查格式配置：通用模板和各级别专用模板的占位符与花括号、时间戳格式
+pub(crate) fn check(config: &FormatConfig) -> Result<(), TemplateError> {
+    if config.format_template.is_empty() {
+        return Err(TemplateError::EmptyTemplate);
+    }
+
+    let mut unknown: Vec<String> = Vec::new();
+    for template in templates(config) {
+        for name in placeholders(template)? {
+            let known = BUILTIN_PLACEHOLDERS.contains(&name) || config.custom_placeholders.iter().any(|custom| custom == name);
+            if !known && !unknown.iter().any(|seen| seen == name) {
+                unknown.push(name.to_string());
+            }
+        }
+    }
+    if !unknown.is_empty() {
+        return Err(TemplateError::UnknownPlaceholders { unknown });
+    }
+
+    if config.timestamp_format.is_empty() || StrftimeItems::new(&config.timestamp_format).any(|item| item == Item::Error) {
+        return Err(TemplateError::InvalidTimestamp { format: config.timestamp_format.clone() });
+    }
+    Ok(())
+}
+
+/// 模板是否使用了占位符 `name`（包括条件段），花括号不配对的模板视为未使用
+pub(crate) fn uses_placeholder(config: &FormatConfig, name: &str) -> bool {
+    templates(config).any(|template| placeholders(template).is_ok_and(|names| names.contains(&name)))
+}
+
+/// 通用模板和各级别专用模板（不包括继承通用模板的 `"+"`）
+fn templates(config: &FormatConfig) -> impl Iterator<Item = &str> {
+    let level_templates = config.level_templates.iter().flat_map(|templates| {
+        [&templates.error, &templates.warn, &templates.info, &templates.debug, &templates.trace]
+    });
+    std::iter::once(config.format_template.as_str())
+        .chain(level_templates.flatten().map(String::as_str).filter(|template| *template != "+"))
+}
+
+/// 模板中的占位符名称（条件段 `{?name}`/`{/name}` 取段名），花括号不配对时返回错误
+///
+/// 连续的 `{{` 和 `}}` 不视为占位符；`{` 后面不是占位符名称时（如 JSON 模板 `{"msg":"{message}"}`）
+/// 按字面输出，需要有配对的 `}`。
+fn placeholders(template: &str) -> Result<Vec<&str>, TemplateError> {
+    let unbalanced = |position| TemplateError::UnbalancedBrace { template: template.to_string(), position };
+    let bytes = template.as_bytes();
+    let mut names = Vec::new();
+    // 尚未配对的字面 `{` 的位置
+    let mut literal_open = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let brace = bytes[i];
+        if brace != b'{' && brace != b'}' {
+            i += 1;
+            continue;
+        }
+        // 字面 `{` 未配对时 `}` 总是与之配对（JSON 对象嵌套结尾的 `}}`）
+        if brace == b'}' && literal_open.pop().is_some() {
+            i += 1;
+            continue;
+        }
+        if bytes.get(i + 1) == Some(&brace) {
+            i += 2;
+            continue;
+        }
+        if brace == b'}' {
+            return Err(unbalanced(i));
+        }
+        let starts_name = bytes.get(i + 1).is_some_and(|&b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'?' | b'/'));
+        if !starts_name {
+            literal_open.push(i);
+            i += 1;
+            continue;
+        }
+        let tag = &template[i + 1..];
+        let end = tag.find(['{', '}']).filter(|&end| tag.as_bytes()[end] == b'}').ok_or(unbalanced(i))?;
+        let name = &tag[..end];
+        names.push(name.strip_prefix(['?', '/']).unwrap_or(name));
+        i += end + 2;
+    }
+    match literal_open.first() {
+        Some(&position) => Err(unbalanced(position)),
+        None => Ok(names),
+    }
+}
+
+/// [`FormatConfig`] 构建器，`build()` 时检查模板（见 [`FormatConfig::builder`]）
+#[derive(Debug, Clone, Default)]
+pub struct FormatConfigBuilder {
+    config: FormatConfig,
+}
+
+impl FormatConfigBuilder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// 通用输出模板
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.config.format_template = template.into();
+        self
+    }
+
+    /// 时间戳格式（chrono `strftime` 语法）
+    pub fn timestamp(mut self, format: impl Into<String>) -> Self {
+        self.config.timestamp_format = format.into();
+        self
+    }
+
+    /// 时间戳按 UTC 渲染
+    pub fn utc(mut self, utc: bool) -> Self {
+        self.config.timestamp_utc = utc;
+        self
+    }
+
+    /// 日志级别显示样式
+    pub fn level_style(mut self, style: LevelStyle) -> Self {
+        self.config.level_style = style;
+        self
+    }
+
+    /// 各级别专用模板
+    pub fn level_templates(mut self, templates: LevelTemplates) -> Self {
+        self.config.level_templates = Some(templates);
+        self
+    }
+
+    /// 控制字符处理方式
+    pub fn sanitize(mut self, mode: SanitizeMode) -> Self {
+        self.config.sanitize = Some(mode);
+        self
+    }
+
+    /// 注册自定义占位符（结构化字段名，如 `request_id`），模板中的 `{request_id}` 由同名字段替换
+    pub fn placeholder(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.config.custom_placeholders.contains(&name) {
+            self.config.custom_placeholders.push(name);
+        }
+        self
+    }
+
+    /// 检查模板并生成格式配置
+    pub fn build(self) -> Result<FormatConfig, TemplateError> {
+        check(&self.config)?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_and_braces() {
+        assert_eq!(placeholders("{?file}{file}:{line}{/file} {message}").unwrap(), ["file", "file", "line", "file", "message"]);
+        assert_eq!(placeholders("plain text").unwrap(), Vec::<&str>::new());
+        assert_eq!(placeholders("{message").unwrap_err(), TemplateError::UnbalancedBrace { template: "{message".to_string(), position: 0 });
+        assert!(matches!(placeholders("a } b"), Err(TemplateError::UnbalancedBrace { position: 2, .. })));
+        assert!(matches!(placeholders("{a{b}"), Err(TemplateError::UnbalancedBrace { position: 0, .. })));
+        assert_eq!(placeholders(r#"{{"msg":"{message}"}}"#).unwrap(), ["message"]);
+        assert_eq!(placeholders(r#"{"msg":"{message}","ctx":{"tid":"{tid}"}}"#).unwrap(), ["message", "tid"]);
+        assert!(matches!(placeholders(r#"{"msg":"{message}""#), Err(TemplateError::UnbalancedBrace { position: 0, .. })));
+    }
+
+    #[test]
+    fn test_level_templates_checked() {
+        let config = FormatConfig {
+            level_templates: Some(LevelTemplates { error: Some("{levle} {message}".to_string()), debug: Some("+".to_string()), ..Default::default() }),
+            ..Default::default()
+        };
+        assert_eq!(check(&config).unwrap_err(), TemplateError::UnknownPlaceholders { unknown: vec!["levle".to_string()] });
+        assert!(check(&FormatConfig::default()).is_ok());
+        assert!(check(&FormatConfig::env_logger_style()).is_ok());
+    }
+}
@@ -0,0 +1,369 @@
+//! 配置中的大小和时间 - 接受字节数/毫秒数，也接受带单位的字符串（如 `"10MB"`、`"100ms"`）
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 字节大小
+///
+/// 解析时接受字节数或带单位的字符串：`B`、`KB`/`MB`/`GB`/`TB`（1000 进制）、
+/// `KiB`/`MiB`/`GiB`/`TiB`（1024 进制），单位不区分大小写，数值可以带小数（如 `"1.5GiB"`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    /// 字节
+    pub const fn b(n: u64) -> Self {
+        Self(n)
+    }
+
+    /// 千字节（1000 字节）
+    pub const fn kb(n: u64) -> Self {
+        Self(n * 1000)
+    }
+
+    /// 兆字节（1000² 字节）
+    pub const fn mb(n: u64) -> Self {
+        Self(n * 1000 * 1000)
+    }
+
+    /// 吉字节（1000³ 字节）
+    pub const fn gb(n: u64) -> Self {
+        Self(n * 1000 * 1000 * 1000)
+    }
+
+    /// KiB（1024 字节）
+    pub const fn kib(n: u64) -> Self {
+        Self(n * 1024)
+    }
+
+    /// MiB（1024² 字节）
+    pub const fn mib(n: u64) -> Self {
+        Self(n * 1024 * 1024)
+    }
+
+    /// GiB（1024³ 字节）
+    pub const fn gib(n: u64) -> Self {
+        Self(n * 1024 * 1024 * 1024)
+    }
+
+    /// 字节数
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> Self {
+        size.0
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("无效的大小 {:?}：应为字节数或带单位的大小（如 \"10MB\"、\"512KiB\"）", s);
+        let (number, unit) = split_unit(s).ok_or_else(invalid)?;
+        let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "kb" => 1000,
+            "mb" => 1000_u64.pow(2),
+            "gb" => 1000_u64.pow(3),
+            "tb" => 1000_u64.pow(4),
+            "kib" => 1024,
+            "mib" => 1024_u64.pow(2),
+            "gib" => 1024_u64.pow(3),
+            "tib" => 1024_u64.pow(4),
+            _ => return Err(invalid()),
+        };
+        scale(number, multiplier).map(Self).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    /// 能整除时使用最大的 1024 进制单位，否则输出字节数
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [(&str, u64); 4] = [("TiB", 1 << 40), ("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)];
+        match UNITS.iter().find(|&&(_, size)| self.0 != 0 && self.0.is_multiple_of(size)) {
+            Some(&(unit, size)) => write!(f, "{}{}", self.0 / size, unit),
+            None => write!(f, "{}B", self.0),
+        }
+    }
+}
+
+/// 以毫秒计的时间
+///
+/// 解析时接受毫秒数或带单位的字符串：`ms`、`s`、`m`（分钟）、`h`、`d`（天），单位不区分大小写，
+/// 数值可以带小数（如 `"1.5s"`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DurationMs(pub u64);
+
+impl DurationMs {
+    /// 毫秒
+    pub const fn ms(n: u64) -> Self {
+        Self(n)
+    }
+
+    /// 秒
+    pub const fn secs(n: u64) -> Self {
+        Self(n * 1000)
+    }
+
+    /// 分钟
+    pub const fn mins(n: u64) -> Self {
+        Self(n * 60 * 1000)
+    }
+
+    /// 天
+    pub const fn days(n: u64) -> Self {
+        Self(n * 24 * 60 * 60 * 1000)
+    }
+
+    /// 毫秒数
+    pub const fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    /// 转换为 `Duration`
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+}
+
+impl From<u64> for DurationMs {
+    fn from(millis: u64) -> Self {
+        Self(millis)
+    }
+}
+
+impl From<DurationMs> for u64 {
+    fn from(duration: DurationMs) -> Self {
+        duration.0
+    }
+}
+
+impl From<DurationMs> for Duration {
+    fn from(duration: DurationMs) -> Self {
+        duration.as_duration()
+    }
+}
+
+impl FromStr for DurationMs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("无效的时间 {:?}：应为毫秒数或带单位的时间（如 \"100ms\"、\"2s\"）", s);
+        let (number, unit) = split_unit(s).ok_or_else(invalid)?;
+        let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+            "" | "ms" => 1,
+            "s" => 1000,
+            "m" => 60 * 1000,
+            "h" => 60 * 60 * 1000,
+            "d" => 24 * 60 * 60 * 1000,
+            _ => return Err(invalid()),
+        };
+        scale(number, multiplier).map(Self).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for DurationMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 != 0 && self.0.is_multiple_of(1000) {
+            write!(f, "{}s", self.0 / 1000)
+        } else {
+            write!(f, "{}ms", self.0)
+        }
+    }
+}
+
+/// 拆分数值和单位（允许中间有空格），数值部分为空时返回 `None`
+fn split_unit(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim();
+    let end = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = s.split_at(end);
+    (!number.is_empty()).then(|| (number, unit.trim_start()))
+}
+
+/// 数值乘以单位，溢出或格式错误时返回 `None`
+fn scale(number: &str, multiplier: u64) -> Option<u64> {
+    if let Ok(n) = number.parse::<u64>() {
+        return n.checked_mul(multiplier);
+    }
+    let n: f64 = number.parse().ok()?;
+    let scaled = (n * multiplier as f64).round();
+    (scaled.is_finite() && scaled <= u64::MAX as f64).then_some(scaled as u64)
+}
+
+/// 接受整数或字符串的反序列化访问器
+struct UnitVisitor<T>(std::marker::PhantomData<T>);
+
+impl<T> Visitor<'_> for UnitVisitor<T>
+where
+    T: From<u64> + FromStr<Err = String>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("非负整数或带单位的字符串")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        Ok(T::from(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+        u64::try_from(v).map(T::from).map_err(|_| E::custom(format!("无效的数值 {}：不能为负数", v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UnitVisitor(std::marker::PhantomData))
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationMs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UnitVisitor(std::marker::PhantomData))
+    }
+}
+
+/// 序列化为整数，与旧版本的配置文件保持兼容
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl Serialize for DurationMs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+/// 用于 `u64` 字节数字段：`#[serde(with = "crate::config::units::bytes")]`
+pub(crate) mod bytes {
+    use super::ByteSize;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        ByteSize::deserialize(deserializer).map(u64::from)
+    }
+}
+
+/// 用于 `u64` 毫秒数字段：`#[serde(with = "crate::config::units::millis")]`
+pub(crate) mod millis {
+    use super::DurationMs;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(millis: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*millis)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        DurationMs::deserialize(deserializer).map(u64::from)
+    }
+}
+
+/// 用于 `Duration` 字段（配置文件中以毫秒表示）：`#[serde(with = "crate::config::units::duration")]`
+pub(crate) mod duration {
+    use super::DurationMs;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        DurationMs::deserialize(deserializer).map(Duration::from)
+    }
+}
+
+/// 用于 `Option<Duration>` 字段（配置文件中以毫秒表示，`null` 表示未设置）：
+/// `#[serde(default, with = "crate::config::units::opt_duration")]`
+pub(crate) mod opt_duration {
+    use super::DurationMs;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&(duration.as_millis() as u64)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<DurationMs>::deserialize(deserializer).map(|duration| duration.map(Duration::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sizes() {
+        assert_eq!("10MB".parse::<ByteSize>(), Ok(ByteSize::mb(10)));
+        assert_eq!("512KiB".parse::<ByteSize>(), Ok(ByteSize(512 * 1024)));
+        assert_eq!("1.5 gib".parse::<ByteSize>(), Ok(ByteSize(3 * 512 * 1024 * 1024)));
+        assert_eq!("4096".parse::<ByteSize>(), Ok(ByteSize(4096)));
+        assert_eq!(ByteSize::mib(10).to_string(), "10MiB");
+
+        let err = "10XB".parse::<ByteSize>().unwrap_err();
+        assert!(err.contains("\"10XB\""), "{}", err);
+        assert!("MB".parse::<ByteSize>().is_err());
+        assert!("99999999TiB".parse::<ByteSize>().is_err());
+    }
+
+    #[test]
+    fn test_parse_durations() {
+        assert_eq!("100ms".parse::<DurationMs>(), Ok(DurationMs(100)));
+        assert_eq!("2s".parse::<DurationMs>(), Ok(DurationMs::secs(2)));
+        assert_eq!("1.5s".parse::<DurationMs>(), Ok(DurationMs(1500)));
+        assert_eq!("250".parse::<DurationMs>(), Ok(DurationMs(250)));
+        assert_eq!("30d".parse::<DurationMs>(), Ok(DurationMs::days(30)));
+        assert_eq!(DurationMs(2000).to_string(), "2s");
+
+        let err = "2 fortnights".parse::<DurationMs>().unwrap_err();
+        assert!(err.contains("\"2 fortnights\""), "{}", err);
+    }
+
+    #[test]
+    fn test_deserialize_integers_and_strings() {
+        #[derive(Deserialize)]
+        struct Limits {
+            #[serde(with = "bytes")]
+            size: u64,
+            #[serde(with = "millis")]
+            interval: u64,
+        }
+
+        let limits: Limits = serde_json::from_str(r#"{"size": "10MB", "interval": 100}"#).unwrap();
+        assert_eq!((limits.size, limits.interval), (10_000_000, 100));
+        let limits: Limits = serde_json::from_str(r#"{"size": 10485760, "interval": "2s"}"#).unwrap();
+        assert_eq!((limits.size, limits.interval), (10_485_760, 2000));
+
+        let err = serde_json::from_str::<Limits>(r#"{"size": "10XB", "interval": 1}"#).err().unwrap();
+        assert!(err.to_string().contains("\"10XB\""), "{}", err);
+        assert!(serde_json::from_str::<Limits>(r#"{"size": -1, "interval": 1}"#).is_err());
+    }
+}
@@ -2,22 +2,51 @@
 
 use std::sync::Arc;
 use once_cell::sync::Lazy;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::SystemTime;
 use crossbeam_channel::Sender;
 
-use crate::config::{LevelFilter, Record};
-use crate::producer_consumer::{ProcessorManager, BatchConfig};
+use crate::config::{BreakerAction, BreakerConfig, ByteSize, MemoryAction, MemoryBudget, Level, LevelFilter, Record, SamplingConfig, FormatConfig, ColorConfig, StampedRecord, ThreadStamp};
+use crate::handler::{HandlerOptions, LineAffixes};
+use crate::breaker::CircuitBreaker;
+use crate::producer_consumer::{ProcessorManager, BatchConfig, FlushError, HandlerHandle, LogProcessor, RemoveError};
+use crate::sampling::{Sampler, SampleDecision};
+use crate::stats::LoggerStats;
+use crate::clock::{Clock, SystemClock};
+use crate::diagnostics::{FilterGate, FilterTracer};
+use crate::report::{ConfigReport, HandlerReport};
+use crate::lazy::LazyDispatcher;
 
 /// 全局日志器实例
 pub static LOGGER: Lazy<Mutex<Option<Arc<dyn Logger>>>> = Lazy::new(|| Mutex::new(None));
 
+/// 锁定全局日志器
+///
+/// 持有锁的线程 panic（如日志参数的 `Display` 实现 panic）后锁仍可继续使用，
+/// 不会让之后每次记录日志都因锁中毒而 panic。
+pub fn lock_global_logger() -> MutexGuard<'static, Option<Arc<dyn Logger>>> {
+    LOGGER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 /// 全局日志器锁（用于开发模式重新初始化）
 static LOGGER_LOCK: std::sync::RwLock<()> = std::sync::RwLock::new(());
 
 /// 全局最大日志级别
 static MAX_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
 
+/// 是否构建过启用过滤追踪的日志器（为 false 时日志宏的快速路径不查询全局日志器）
+static FILTER_TRACING: AtomicBool = AtomicBool::new(false);
+
+/// 构建时等待工作线程就绪的默认超时时间
+const DEFAULT_READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 开发模式下每条记录等待处理完成的超时时间
+const DEV_MODE_SYNC_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// `flush`/`force_flush` 等待延迟消息队列中的记录交给处理器的最长时间
+const LAZY_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
 /// 处理器类型名称常量
 pub mod processor_types {
     /// 终端处理器类型名称
@@ -35,22 +64,61 @@ pub enum LogCommand {
     Write(Vec<u8>),
     /// 强制写入日志数据（忽略批量限制）
     WriteForce(Vec<u8>),
+    /// 写入等待富化的记录（所有处理器共享，第一个处理它的工作线程执行富化函数），`bool` 为是否强制写入
+    Enrich(Arc<crate::producer_consumer::PendingEnrichment>, bool),
+    /// 写入原始数据（一行不含格式的文本，不经过记录编码和格式化）
+    WriteRaw(Vec<u8>),
+    /// 转发已编码的网络数据包（只有 UDP 处理器处理，不解码也不重新编码）
+    ForwardPacket(Vec<u8>),
     /// 文件轮转
     Rotate,
     /// 文件压缩
     Compress(std::path::PathBuf),
     /// 强制刷新
     Flush,
+    /// 带确认的强制刷新（刷新完成后回复结果）
+    FlushAck(Sender<bool>),
     /// 停止工作线程
     Shutdown(&'static str),
     /// 健康检查（用于初始化时验证工作线程状态）
     HealthCheck(Sender<bool>),
+    /// 管道探测标记（携带产生时间），处理器处理完此前缓冲的记录后记录延迟
+    Probe(std::time::Instant),
+    /// 更新批量配置（经控制通道，工作线程在下一次循环时生效）
+    UpdateBatch(BatchConfig),
+}
+
+/// 记录的投递优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    /// 普通记录：经过级别过滤和采样，按批量配置写入
+    #[default]
+    Normal,
+    /// 紧急记录：不受级别过滤和采样限制，强制写入并立即刷新（级别为 `Off` 时见 [`Logger::emergency_enabled`]）
+    Emergency,
 }
 
 /// 日志器 trait - 极简接口
 pub trait Logger: Send + Sync {
     fn log(&self, record: &Record);
+
     fn flush(&self);
+
+    /// 带确认的刷新：等待处理器处理完此前提交的记录，超时返回错误
+    ///
+    /// 默认实现调用 `flush` 后立即返回。
+    fn flush_sync(&self, _timeout_ms: u64) -> Result<(), String> {
+        self.flush();
+        Ok(())
+    }
+
+    /// 同步点：调用前记录的日志全部被每个处理器处理并刷新后返回，用于替代日志后的 `sleep`
+    ///
+    /// 默认实现调用 `flush_sync`。
+    fn sync_point(&self, timeout: std::time::Duration) -> Result<(), FlushError> {
+        self.flush_sync(timeout.as_millis() as u64).map_err(|_| FlushError::Failed)
+    }
+
     fn set_level(&self, level: LevelFilter);
     fn level(&self) -> LevelFilter;
 
@@ -59,26 +127,130 @@ pub trait Logger: Send + Sync {
 
     /// 紧急日志 - 无视所有限制立即输出，适用于启动日志和关键错误
     fn emergency_log(&self, record: &Record);
+
+    /// 按优先级投递记录：日志宏（包括 `emergency!`、`startup_log!`）的统一入口
+    ///
+    /// 默认实现按优先级转发给 `log` 或 `emergency_log`。
+    fn log_with_priority(&self, record: &Record, priority: Priority) {
+        match priority {
+            Priority::Normal => self.log(record),
+            Priority::Emergency => self.emergency_log(record),
+        }
+    }
+
+    /// 紧急日志是否会被输出（级别为 `Off` 时默认不输出，见 [`LoggerBuilder::with_emergency_when_off`]）
+    fn emergency_enabled(&self) -> bool {
+        true
+    }
+
+    /// 已注册的处理器类型名称（见 [`processor_types`]）
+    fn handler_types(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// 日志宏产生的记录使用的应用ID
+    fn app_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// 构建时添加的处理器使用的配置（见 [`LoggerCore::processor_configs`]），不提供配置的日志器返回空列表
+    fn processor_configs(&self) -> Vec<ProcessorConfig> {
+        Vec::new()
+    }
+
+    /// 上报一条在日志器之外（如全局最大级别）被过滤的记录，未启用过滤追踪时不做任何事
+    fn trace_filtered(&self, _level: Level, _target: &str, _gate: FilterGate) {}
+
+    /// 运行统计（采样丢弃数、发送失败丢弃数），不提供统计的日志器返回 `None`
+    fn runtime_stats(&self) -> Option<Arc<LoggerStats>> {
+        None
+    }
+
+    /// 是否注册了指定类型的处理器，`kind` 可以是完整名称（`"file_processor"`）或简称（`"file"`）
+    fn has_handler(&self, kind: &str) -> bool {
+        self.handler_types()
+            .iter()
+            .any(|t| *t == kind || t.strip_suffix("_processor") == Some(kind))
+    }
+
+    /// 在运行中移除指定类型的处理器（见 [`ProcessorManager::remove_processor`]），默认实现没有可移除的处理器
+    fn detach_handler(&self, kind: &str) -> Result<(), RemoveError> {
+        Err(RemoveError::NotFound(kind.to_string()))
+    }
+}
+
+/// 构建器添加的处理器使用的配置（见 [`LoggerCore::processor_configs`]）
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ProcessorConfig {
+    /// 终端处理器
+    Terminal(crate::handler::term::TermConfig),
+    /// 文件处理器
+    File(crate::config::FileConfig),
+    /// UDP处理器
+    Udp(crate::handler::udp::UdpConfig),
 }
 
 /// 日志核心实现 - 极简设计
 #[derive(Clone)]
 pub struct LoggerCore {
-    level: LevelFilter,
+    /// 当前日志级别（克隆的日志器共享，`set_level` 立即生效）
+    level: Arc<AtomicUsize>,
+    /// 延迟消息队列（在 `processor_manager` 之前释放：等待生成线程发出剩余记录后才关闭处理器）
+    lazy: Arc<LazyDispatcher>,
     processor_manager: Arc<ProcessorManager>,
     dev_mode: bool, // 开发模式：同步等待日志处理完成
     /// 需要等待的处理器类型集合
-    expected_processor_types: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    expected_processor_types: Arc<parking_lot::Mutex<std::collections::HashSet<String>>>,
+    /// 运行统计
+    stats: Arc<LoggerStats>,
+    /// 采样器（未配置采样时为 None）
+    sampler: Option<Arc<Sampler>>,
+    /// 日志器序号计数器（未启用序号时为 None，克隆的日志器共享同一计数器）
+    seq: Option<Arc<AtomicU64>>,
+    /// 日志宏产生的记录使用的应用ID（未设置时为可执行文件名）
+    app_id: Option<String>,
+    /// 级别为 `Off` 时是否仍输出紧急日志
+    emergency_when_off: bool,
+    /// 记录时间的来源
+    clock: Arc<dyn Clock>,
+    /// 构建时添加的处理器使用的配置
+    processor_configs: Arc<Vec<ProcessorConfig>>,
+    /// 过滤追踪（未启用时为 None）
+    filter_tracer: Option<Arc<FilterTracer>>,
+    /// 全局熔断器（未配置时为 None）
+    breaker: Option<Arc<CircuitBreaker>>,
+    /// 受保护的目标前缀（见 [`LoggerBuilder::with_protected_targets`]）
+    protected_targets: Arc<Vec<String>>,
+    /// 用户回调中产生的记录的处理方式（见 [`ReentrantPolicy`]）
+    reentrant_policy: ReentrantPolicy,
+    /// 处理器使用的批量配置
+    batch_config: BatchConfig,
 }
 
 impl LoggerCore {
     /// 创建新的日志核心
     pub fn new(level: LevelFilter, processor_manager: ProcessorManager, batch_config: BatchConfig, dev_mode: bool) -> Self {
+        let stats = processor_manager.diagnostics().stats().clone();
+        let processor_manager = Arc::new(processor_manager);
         Self {
-            level,
-            processor_manager: Arc::new(processor_manager),
+            level: Arc::new(AtomicUsize::new(level as usize)),
+            lazy: lazy_dispatcher(&processor_manager, &stats),
+            stats,
+            processor_manager,
             dev_mode,
-            expected_processor_types: Arc::new(std::sync::Mutex::new(std::collections::HashSet::new())),
+            expected_processor_types: Arc::new(parking_lot::Mutex::new(std::collections::HashSet::new())),
+            sampler: None,
+            seq: None,
+            app_id: None,
+            emergency_when_off: false,
+            clock: Arc::new(SystemClock),
+            processor_configs: Arc::new(Vec::new()),
+            filter_tracer: None,
+            breaker: None,
+            protected_targets: Arc::new(Vec::new()),
+            reentrant_policy: ReentrantPolicy::default(),
+            batch_config,
         }
     }
 
@@ -90,22 +262,144 @@ impl LoggerCore {
         dev_mode: bool,
         expected_types: std::collections::HashSet<String>
     ) -> Self {
+        let stats = processor_manager.diagnostics().stats().clone();
+        let processor_manager = Arc::new(processor_manager);
         Self {
-            level,
-            processor_manager: Arc::new(processor_manager),
+            level: Arc::new(AtomicUsize::new(level as usize)),
+            lazy: lazy_dispatcher(&processor_manager, &stats),
+            stats,
+            processor_manager,
             dev_mode,
-            expected_processor_types: Arc::new(std::sync::Mutex::new(expected_types)),
+            expected_processor_types: Arc::new(parking_lot::Mutex::new(expected_types)),
+            sampler: None,
+            seq: None,
+            app_id: None,
+            emergency_when_off: false,
+            clock: Arc::new(SystemClock),
+            processor_configs: Arc::new(Vec::new()),
+            filter_tracer: None,
+            breaker: None,
+            protected_targets: Arc::new(Vec::new()),
+            reentrant_policy: ReentrantPolicy::default(),
+            batch_config,
         }
     }
 
     /// 获取当前日志级别
     pub fn level(&self) -> LevelFilter {
-        self.level
+        level_filter_from(self.level.load(Ordering::Relaxed))
     }
 
-    /// 检查是否应该记录该级别的日志
+    /// 检查是否应该记录该级别的日志（级别为 `Off` 时任何记录都不记录）
     pub fn should_log(&self, level: &crate::config::Level) -> bool {
-        (level.to_level_filter() as u8) <= (self.level as u8)
+        level.should_log_at(self.level())
+    }
+
+    /// 获取运行统计
+    pub fn stats(&self) -> &Arc<LoggerStats> {
+        &self.stats
+    }
+
+    /// 单调递增计数器的快照（可序列化，见 [`StatsSnapshot`](crate::stats::StatsSnapshot)）
+    pub fn stats_snapshot(&self) -> crate::stats::StatsSnapshot {
+        self.stats.snapshot()
+    }
+
+    /// 各处理器的管道探测延迟（按添加顺序，见 [`LoggerBuilder::with_pipeline_probe`]）
+    pub fn probe_reports(&self) -> Vec<crate::stats::ProbeReport> {
+        self.processor_manager.probe_reports()
+    }
+
+    /// 已注册的处理器类型名称（按添加顺序，见 [`processor_types`]）
+    pub fn handler_types(&self) -> Vec<&'static str> {
+        self.processor_manager.processor_types()
+    }
+
+    /// 处理器名称（按添加顺序）：`HandlerOptions::name` 指定的名称，或自动生成的 `file_processor#0` 形式
+    pub fn handler_names(&self) -> Vec<String> {
+        self.processor_manager.processor_names()
+    }
+
+    /// 名为 `name` 的处理器的句柄，可单独刷新、查看队列长度、修改批量配置或移除该处理器
+    pub fn handler(&self, name: &str) -> Option<HandlerHandle> {
+        self.processor_manager.worker(name).map(|_| HandlerHandle::new(&self.processor_manager, name))
+    }
+
+    /// 是否注册了指定类型的处理器，`kind` 可以是完整名称（`"file_processor"`）或简称（`"file"`）
+    pub fn has_handler(&self, kind: &str) -> bool {
+        Logger::has_handler(self, kind)
+    }
+
+    /// 在运行中移除指定类型的处理器，`kind` 可以是完整名称（`"udp_processor"`）或简称（`"udp"`）
+    ///
+    /// 返回后该处理器已处理完此前收到的记录并退出，之后的记录只发送给其余处理器。
+    pub fn detach_handler(&self, kind: &str) -> Result<(), RemoveError> {
+        self.processor_manager.remove_processor(kind)
+    }
+
+    /// 原始数据快速路径：把一行已格式化的文本直接交给 `target` 类型的处理器（如 `"file"`）
+    ///
+    /// 不构造记录、不做级别过滤以外的任何处理，也不经过格式化，数据照常参与批量写入、轮转和压缩。
+    /// 处理器在行尾补充换行符（文件处理器使用 `line_ending`）；不支持原始数据的处理器（UDP）忽略这些数据。
+    /// 级别为 `Off` 时不输出。
+    pub fn log_raw(&self, target: &str, line: &[u8]) -> Result<(), String> {
+        if self.level() == LevelFilter::Off {
+            return Ok(());
+        }
+        self.processor_manager.broadcast_raw(target, line.to_vec())
+    }
+
+    /// 中继快速路径：把收到的已编码 `NetRecord` 数据包原样交给 UDP 处理器转发
+    ///
+    /// 只按 [`PacketMetadata::parse_header`](crate::udp_helper::PacketMetadata::parse_header) 检查数据包头部，
+    /// 不解码消息体，也不做级别过滤、采样和格式化；终端和文件处理器不会收到这些数据包。
+    /// 数据包照常参与 UDP 处理器的批量发送。级别为 `Off` 时不转发。
+    pub fn forward_net_packet(&self, packet: &[u8]) -> Result<(), String> {
+        if self.level() == LevelFilter::Off {
+            return Ok(());
+        }
+        crate::udp_helper::PacketMetadata::parse_header(packet).map_err(|e| format!("无效的数据包: {}", e))?;
+        self.processor_manager.forward_packet(packet.to_vec())
+    }
+
+    /// 构建时添加的处理器使用的配置（按添加顺序，已补充构建器默认格式、颜色和刷新策略）
+    ///
+    /// 运行中通过 `attach_processor()`/`detach_handler()` 增减的处理器不反映在这里。
+    pub fn processor_configs(&self) -> &[ProcessorConfig] {
+        &self.processor_configs
+    }
+
+    /// 实际生效的配置快照（当前级别、批量、采样、熔断和内存预算配置、构建时添加的处理器和启用的特性）
+    ///
+    /// 报告可以序列化，UDP 认证令牌只报告是否设置。
+    pub fn config_report(&self) -> ConfigReport {
+        ConfigReport {
+            version: env!("CARGO_PKG_VERSION"),
+            level: self.level(),
+            batch: self.batch_config.clone(),
+            sampling: self.sampler.as_ref().map(|sampler| sampler.config().clone()),
+            breaker: self.breaker.as_ref().map(|breaker| breaker.config().clone()),
+            memory_budget: self.processor_manager.memory_budget().cloned(),
+            handlers: self.processor_configs.iter().map(HandlerReport::from).collect(),
+            features: crate::report::enabled_features(),
+        }
+    }
+
+    /// 在运行中添加处理器，之后的记录会发送给它
+    pub fn attach_processor<P>(&self, processor: P, config: BatchConfig) -> Result<(), String>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        self.processor_manager.attach_processor(processor, config)
+    }
+
+    /// 在运行中用新的处理器替换 `kind` 类型的处理器，切换期间的每条记录恰好由其中一个写入
+    /// （见 [`ProcessorManager::replace_processor`]）
+    pub fn replace_processor<P>(&self, kind: &str, processor: P, config: BatchConfig) -> Result<(), RemoveError>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        self.processor_manager.replace_processor(kind, processor, config)
     }
 
     /// 获取ProcessorManager的引用
@@ -113,97 +407,468 @@ impl LoggerCore {
         &self.processor_manager
     }
 
-    /// 智能等待所有工作线程启动就绪
+    /// 等待本日志器的所有工作线程启动就绪
+    ///
+    /// 只检查本日志器自己的工作线程，不依赖全局就绪计数器，可用于未设为全局日志器的实例。
     pub fn wait_for_workers_ready(&self, timeout_ms: u64) -> Result<(), String> {
-        // 获取预期的处理器类型
-        let expected_types: Vec<String> = {
-            let guard = self.expected_processor_types.lock().unwrap();
-            guard.iter().cloned().collect()
-        };
+        self.processor_manager.wait_ready(timeout_ms).map_err(|e| e.to_string())
+    }
 
-        if expected_types.is_empty() {
-            // 如果没有指定预期的处理器类型，直接检查所有处理器
-            self.processor_manager.smart_health_check(timeout_ms)?;
-        } else {
-            // 检查指定的处理器类型
-            self.processor_manager.check_specific_types(&expected_types, timeout_ms)?;
+    /// 带确认的刷新：等待所有处理器处理完此前提交的日志并完成刷新
+    pub fn flush_sync(&self, timeout_ms: u64) -> Result<(), String> {
+        let timeout = std::time::Duration::from_millis(timeout_ms);
+        let deadline = std::time::Instant::now() + timeout;
+        if !self.drain_lazy(timeout) {
+            return Err(FlushError::Timeout(timeout).to_string());
         }
+        self.processor_manager.broadcast_flush_ack(deadline.saturating_duration_since(std::time::Instant::now()).as_millis() as u64)
+    }
 
-        Ok(())
+    /// 同步点：调用前记录的日志全部被每个处理器处理后返回
+    ///
+    /// 返回时文件已同步到磁盘、终端已刷新、UDP 待发记录已发送（或达到重试上限），
+    /// 之后读取日志文件或终端输出不需要再 `sleep`。
+    pub fn sync_point(&self, timeout: std::time::Duration) -> Result<(), FlushError> {
+        let deadline = std::time::Instant::now() + timeout;
+        if !self.drain_lazy(timeout) {
+            return Err(FlushError::Timeout(timeout));
+        }
+        self.processor_manager.sync_point(deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+
+    /// 关闭日志器：之后的记录被拒绝并计入 `LoggerStats::shutdown_rejected`（见
+    /// [`LoggerBuilder::with_stderr_on_shutdown`]），此前提交的记录全部写入后所有工作线程退出
+    ///
+    /// 关闭对所有克隆的日志器生效；关闭期间和之后调用 `log` 不会阻塞或 panic。
+    pub fn shutdown(&self, timeout: std::time::Duration) -> Result<(), String> {
+        let deadline = std::time::Instant::now() + timeout;
+        self.drain_lazy(timeout);
+        self.processor_manager.shutdown(deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+
+    /// 立即刷新：不等待队列中积压的记录，每个处理器最多再处理一个批次（至少 1024 条命令）后刷新并确认
+    ///
+    /// 与 [`sync_point`](Self::sync_point) 不同，返回时积压的记录可能尚未写入；积压的记录之后仍按原顺序写入。
+    pub fn flush_now(&self, timeout: std::time::Duration) -> Result<(), FlushError> {
+        let deadline = std::time::Instant::now() + timeout;
+        if !self.drain_lazy(timeout) {
+            return Err(FlushError::Timeout(timeout));
+        }
+        self.processor_manager.flush_now(deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+
+    /// 等待延迟消息队列中的记录交给处理器，超时返回 false
+    ///
+    /// 处理器的工作线程中不等待：生成线程可能正阻塞在内存预算上等待该工作线程。
+    fn drain_lazy(&self, timeout: std::time::Duration) -> bool {
+        crate::producer_consumer::on_worker_thread() || self.lazy.wait_idle(timeout)
     }
 
     /// 添加预期的处理器类型
     pub fn add_expected_type(&self, processor_type: String) {
-        let mut guard = self.expected_processor_types.lock().unwrap();
+        let mut guard = self.expected_processor_types.lock();
         guard.insert(processor_type);
     }
 }
 
-impl Logger for LoggerCore {
-    fn log(&self, record: &Record) {
-        if self.should_log(&record.metadata.level) {
-            // 优化：使用快速路径处理非Error级别日志
-            let is_error = record.metadata.level == crate::config::Level::Error;
-
-            // 序列化日志数据
-            if let Ok(data) = bincode::encode_to_vec(record, bincode::config::standard()) {
-                if is_error {
-                    // Error级别日志自动使用紧急模式
-                    let _ = self.processor_manager.broadcast_write_force(data);
-                } else {
-                    // 普通日志使用正常路径
-                    let _ = self.processor_manager.broadcast_write(data);
+thread_local! {
+    /// 当前线程是否正在处理日志器自身的事件（熔断判定中上报的诊断事件、发送熔断汇总），
+    /// 期间回调产生的记录不再经过熔断和采样，也不会再引发新的事件
+    static IN_INTERNAL: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// 内部事件处理的作用域，结束（包括回调 panic 展开）时恢复外层状态
+struct InternalScope(bool);
+
+impl InternalScope {
+    fn enter() -> Self {
+        Self(IN_INTERNAL.with(|flag| flag.replace(true)))
+    }
+
+    fn active() -> bool {
+        IN_INTERNAL.with(std::cell::Cell::get)
+    }
+}
+
+impl Drop for InternalScope {
+    fn drop(&mut self) {
+        IN_INTERNAL.with(|flag| flag.set(self.0));
+    }
+}
+
+thread_local! {
+    /// 当前线程是否正在执行用户回调（格式化函数、富化函数、终端的 `on_record` 钩子、工作线程中的错误回调）
+    static IN_CALLBACK: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// 用户回调的作用域：期间日志宏产生的记录不进入管道，按 [`ReentrantPolicy`] 处理；
+/// 结束（包括回调 panic 展开）时恢复外层状态
+pub(crate) struct CallbackScope(bool);
+
+impl CallbackScope {
+    pub(crate) fn enter() -> Self {
+        Self(IN_CALLBACK.with(|flag| flag.replace(true)))
+    }
+
+    fn active() -> bool {
+        IN_CALLBACK.with(std::cell::Cell::get)
+    }
+}
+
+impl Drop for CallbackScope {
+    fn drop(&mut self) {
+        IN_CALLBACK.with(|flag| flag.set(self.0));
+    }
+}
+
+impl LoggerCore {
+    /// 级别过滤和采样：被过滤的记录返回 `None`，通过的记录返回需要附加的采样比例字段
+    ///
+    /// rat_logger 自身产生的记录、内部事件处理中产生的记录和受保护目标的记录只按级别过滤。
+    fn admit(&self, record: &Record) -> Option<Option<(String, String)>> {
+        let (level, target) = (record.metadata.level, record.metadata.target.as_str());
+        let internal = record.metadata.is_internal() || InternalScope::active();
+        if !self.should_log(&level) {
+            if let Some(tracer) = self.filter_tracer.as_ref().filter(|_| !internal) {
+                tracer.trace(level, target, FilterGate::LoggerLevel(self.level()));
+            }
+            return None;
+        }
+        if internal || self.is_protected(target) {
+            return Some(None);
+        }
+
+        // 熔断：只放行 Error 级别的记录，汇总记录直接发送
+        if let Some(breaker) = &self.breaker {
+            let _scope = InternalScope::enter();
+            let admission = breaker.admit(level);
+            if let Some(dropped) = admission.summary {
+                let limit = breaker.config().max_records_per_sec;
+                let message = crate::breaker::summary_message(dropped, limit);
+                self.dispatch(&crate::macro_record(self, Level::Warn, message, module_path!(), file!(), line!()));
+            }
+            if !admission.pass {
+                if let Some(tracer) = &self.filter_tracer {
+                    tracer.trace(level, target, FilterGate::CircuitBreaker { limit: breaker.config().max_records_per_sec });
                 }
+                return None;
+            }
+        }
 
-                // 开发模式：同步等待日志处理完成
-                if self.dev_mode {
-                    self.flush();
-                    std::thread::sleep(std::time::Duration::from_millis(10));
+        // 采样：被丢弃的记录只计数，通过的记录按需附加采样比例字段
+        if let Some(sampler) = &self.sampler {
+            match sampler.decide(level, target) {
+                SampleDecision::Drop => {
+                    self.stats.sampled_out.fetch_add(1, Ordering::Relaxed);
+                    if let Some(tracer) = &self.filter_tracer {
+                        let gate = match sampler.matching_rule(target) {
+                            Some((rule, ratio)) => FilterGate::Sampling { rule: Some(rule.clone()), ratio: *ratio },
+                            None => FilterGate::Sampling { rule: None, ratio: sampler.config().default_ratio },
+                        };
+                        tracer.trace(level, target, gate);
+                    }
+                    return None;
+                }
+                SampleDecision::Keep(ratio) if ratio < 1.0 && sampler.config().annotate => {
+                    return Some(Some(("sampled".to_string(), ratio.to_string())));
                 }
+                _ => {}
+            }
+        }
+        Some(None)
+    }
+
+    /// 处理用户回调中产生的记录：通过级别过滤的计入 `stats().reentrant_records()`，
+    /// 按策略丢弃或写入标准错误，不进入管道（工作线程不会等待自己的队列，也不会递归格式化）
+    fn divert_reentrant(&self, record: &Record) {
+        let level = record.metadata.level;
+        if !self.should_log(&level) {
+            return;
+        }
+        self.stats.reentrant_records.fetch_add(1, Ordering::Relaxed);
+        if self.reentrant_policy == ReentrantPolicy::Stderr {
+            let location = record.module_path.as_deref().unwrap_or(&record.metadata.target);
+            let file = record.file.as_deref().unwrap_or("");
+            write_stderr_line(level, record.level_label.as_deref(), &record.args, location, file, record.line.unwrap_or(0));
+        }
+    }
+
+    /// 编码通过过滤的记录并发送给处理器
+    ///
+    /// 延迟消息的记录写入标记后交给延迟消息队列，由生成线程生成消息后编码发送；
+    /// 队列中还有记录时其他记录也排队，保持同一线程内记录的顺序。
+    fn dispatch(&self, record: &Record) {
+        let protected = self.is_protected(&record.metadata.target);
+        if record.args.is_pending() || self.lazy.busy() {
+            self.lazy.submit(self.stamp(record), protected);
+        } else if let Ok(data) = self.encode_record(record) {
+            send_encoded(&self.processor_manager, &self.stats, data, record.metadata.level, protected);
+        } else {
+            return;
+        }
+
+        // 开发模式：同步等待日志处理完成（工作线程中的诊断回调记录日志时不能等待自己的队列，只发送刷新命令）
+        if self.dev_mode {
+            if crate::producer_consumer::on_worker_thread() {
+                self.flush();
+            } else {
+                let _ = self.sync_point(DEV_MODE_SYNC_TIMEOUT);
+            }
+        }
+    }
+
+    /// 目标是否以受保护的前缀开头
+    fn is_protected(&self, target: &str) -> bool {
+        self.protected_targets.iter().any(|prefix| target.starts_with(prefix.as_str()))
+    }
+
+    /// 编码记录，同时写入标记（见 [`stamped`](Self::stamped)）
+    fn encode_record(&self, record: &Record) -> Result<Vec<u8>, bincode::error::EncodeError> {
+        crate::config::encode_versioned(&self.stamped(record))
+    }
+
+    /// 写入标记后的记录副本，用于在其他线程中编码
+    fn stamp(&self, record: &Record) -> Record {
+        let stamped = self.stamped(record);
+        Record {
+            timestamp: stamped.timestamp,
+            seq: stamped.seq,
+            thread: stamped.thread,
+            record_id: stamped.record_id,
+            ..record.clone()
+        }
+    }
+
+    /// 记录时间（来自时钟）、日志器序号、线程标记和记录标识（记录自带的值优先），在调用线程上写入
+    fn stamped<'a>(&self, record: &'a Record) -> StampedRecord<'a> {
+        StampedRecord {
+            record,
+            timestamp: record.timestamp.or_else(|| Some(self.clock.now())),
+            seq: record.seq.or_else(|| self.seq.as_ref().map(|seq| seq.fetch_add(1, Ordering::Relaxed))),
+            thread: record.thread.or_else(|| Some(ThreadStamp::next())),
+            record_id: record.record_id.or_else(|| Some(crate::config::next_record_id())),
+        }
+    }
+}
+
+/// 延迟消息队列：生成线程编码记录后直接交给处理器
+fn lazy_dispatcher(processor_manager: &Arc<ProcessorManager>, stats: &Arc<LoggerStats>) -> Arc<LazyDispatcher> {
+    let (processor_manager, stats) = (processor_manager.clone(), stats.clone());
+    Arc::new(LazyDispatcher::new(Arc::new(move |record: &Record, protected: bool| {
+        if let Ok(data) = crate::config::encode_versioned(record) {
+            send_encoded(&processor_manager, &stats, data, record.metadata.level, protected);
+        }
+    })))
+}
+
+/// 把编码后的记录只发送给接收该级别的处理器（Error级别日志自动使用紧急模式）
+fn send_encoded(processor_manager: &ProcessorManager, stats: &LoggerStats, data: Vec<u8>, level: Level, protected: bool) {
+    stats.add_submitted();
+    if protected {
+        stats.protected_records.fetch_add(1, Ordering::Relaxed);
+        let _ = processor_manager.broadcast_protected_record(data, level);
+    } else {
+        let _ = processor_manager.broadcast_record(data, level);
+    }
+}
+
+impl Logger for LoggerCore {
+    fn log(&self, record: &Record) {
+        if CallbackScope::active() {
+            return self.divert_reentrant(record);
+        }
+        let Some(annotation) = self.admit(record) else {
+            return;
+        };
+        match annotation {
+            Some(field) => {
+                let mut sampled = record.clone();
+                sampled.fields.push(field);
+                self.dispatch(&sampled);
             }
+            None => self.dispatch(record),
         }
     }
 
     fn flush(&self) {
-        // 广播刷新命令给所有处理器
+        // 广播刷新命令给所有处理器（先等待延迟消息队列中的记录交给处理器）
+        self.drain_lazy(LAZY_FLUSH_TIMEOUT);
         let _ = self.processor_manager.broadcast_flush();
     }
 
+    fn flush_sync(&self, timeout_ms: u64) -> Result<(), String> {
+        LoggerCore::flush_sync(self, timeout_ms)
+    }
+
+    fn sync_point(&self, timeout: std::time::Duration) -> Result<(), FlushError> {
+        LoggerCore::sync_point(self, timeout)
+    }
+
     fn set_level(&self, level: LevelFilter) {
-        // 更新全局最大级别
+        // 更新日志器级别和全局最大级别，之后的记录立即按新级别过滤
+        self.level.store(level as usize, Ordering::Relaxed);
         MAX_LEVEL.store(level as usize, Ordering::Relaxed);
     }
 
     fn level(&self) -> LevelFilter {
-        self.level
+        LoggerCore::level(self)
     }
 
     fn force_flush(&self) {
         // 强制刷新所有处理器，无视批量配置
+        self.drain_lazy(LAZY_FLUSH_TIMEOUT);
         let _ = self.processor_manager.broadcast_flush();
         // 给处理器一些时间来完成刷新
         std::thread::sleep(std::time::Duration::from_millis(50));
     }
 
     fn emergency_log(&self, record: &Record) {
-        // 紧急日志：直接发送并立即刷新，无视级别检查和批量配置
-        if let Ok(data) = bincode::encode_to_vec(record, bincode::config::standard()) {
-            // 直接发送给所有处理器，使用强制写入命令（忽略批量限制）
-            let _ = self.processor_manager.broadcast_write_force(data);
+        // 紧急日志：直接发送并立即刷新，无视级别检查和批量配置（级别为 Off 时除外）
+        if !self.emergency_enabled() {
+            return;
+        }
+        if CallbackScope::active() {
+            return self.divert_reentrant(record);
+        }
+        self.stats.emergency.fetch_add(1, Ordering::Relaxed);
+        if let Ok(data) = self.encode_record(record) {
+            self.stats.add_submitted();
+            // 直接发送给所有处理器，使用强制写入命令（忽略批量限制）；
+            // 不接收紧急日志的处理器只在记录通过级别过滤时走普通路径
+            let level_allowed = self.should_log(&record.metadata.level);
+            let _ = self.processor_manager.broadcast_emergency_record(data, record.metadata.level, level_allowed);
+        }
+    }
+
+    fn handler_types(&self) -> Vec<&'static str> {
+        LoggerCore::handler_types(self)
+    }
+
+    fn processor_configs(&self) -> Vec<ProcessorConfig> {
+        LoggerCore::processor_configs(self).to_vec()
+    }
+
+    fn trace_filtered(&self, level: Level, target: &str, gate: FilterGate) {
+        if let Some(tracer) = &self.filter_tracer {
+            tracer.trace(level, target, gate);
+        }
+    }
+
+    fn detach_handler(&self, kind: &str) -> Result<(), RemoveError> {
+        LoggerCore::detach_handler(self, kind)
+    }
+
+    fn runtime_stats(&self) -> Option<Arc<LoggerStats>> {
+        Some(self.stats.clone())
+    }
+
+    fn app_id(&self) -> Option<&str> {
+        self.app_id.as_deref()
+    }
+
+    fn emergency_enabled(&self) -> bool {
+        self.emergency_when_off || self.level() != LevelFilter::Off
+    }
+}
+
+/// 已添加的处理器：配置和可选的自定义格式化函数，构建时才创建处理器并启动工作线程
+#[derive(Clone)]
+enum HandlerSpec {
+    Terminal(crate::handler::term::TermConfig, Option<crate::handler::Formatter>),
+    File(crate::config::FileConfig, Option<crate::handler::Formatter>),
+    Udp(crate::handler::udp::UdpConfig),
+}
+
+impl HandlerSpec {
+    /// 处理器简称（用于错误信息）
+    fn kind(&self) -> &'static str {
+        match self {
+            HandlerSpec::Terminal(..) => "terminal",
+            HandlerSpec::File(..) => "file",
+            HandlerSpec::Udp(_) => "udp",
+        }
+    }
+
+    /// 格式模板是否使用 `{uptime}`
+    fn uses_uptime(&self) -> bool {
+        let format = match self {
+            HandlerSpec::Terminal(config, None) => config.format.as_ref(),
+            HandlerSpec::File(config, None) => config.format.as_ref(),
+            _ => None,
+        };
+        format.is_some_and(|format| crate::config::template::uses_placeholder(format, "uptime"))
+    }
+
+    /// 处理器类型名称（见 [`processor_types`]）
+    fn processor_type(&self) -> &'static str {
+        match self {
+            HandlerSpec::Terminal(..) => processor_types::TERMINAL,
+            HandlerSpec::File(..) => processor_types::FILE,
+            HandlerSpec::Udp(_) => processor_types::UDP,
         }
     }
 }
 
 /// 日志构建器 - 极简设计
+///
+/// `add_*` 只记录处理器配置，处理器在 `try_build()` 中才创建，因此构建器可以廉价地克隆，
+/// 未构建的构建器不持有任何文件或线程。
+#[derive(Clone)]
 pub struct LoggerBuilder {
     level: LevelFilter,
-    processor_manager: ProcessorManager,
     batch_config: Option<BatchConfig>,
     dev_mode: bool, // 开发模式：同步等待日志处理完成
     /// 是否启用异步模式
     enable_async: bool,
-    /// 预期的处理器类型集合
-    expected_processor_types: std::collections::HashSet<String>,
+    /// 需要拦截并刷新日志的终止信号
+    #[cfg(feature = "signal-flush")]
+    signal_flush: Vec<crate::signal::Signal>,
+    /// 收到信号刷新后是否完整关闭工作线程
+    #[cfg(feature = "signal-flush")]
+    signal_shutdown: bool,
+    /// 采样配置
+    sampling: Option<SamplingConfig>,
+    /// 全局熔断配置
+    breaker: Option<BreakerConfig>,
+    /// 内存预算
+    memory_budget: Option<MemoryBudget>,
+    /// 是否为记录写入日志器序号
+    sequence: bool,
+    /// 日志宏产生的记录使用的应用ID
+    app_id: Option<String>,
+    /// 级别为 `Off` 时是否仍输出紧急日志
+    emergency_when_off: bool,
+    /// 记录时间的来源
+    clock: Arc<dyn Clock>,
+    /// 预设的终端处理器配置（构建时添加，显式添加终端处理器后失效）
+    preset_terminal: Option<crate::handler::term::TermConfig>,
+    /// 处理器未指定格式时使用的默认格式
+    default_format: Option<FormatConfig>,
+    /// 终端处理器启用颜色但未指定颜色时使用的默认颜色
+    default_color: Option<ColorConfig>,
+    /// 设置选项时发现的配置错误（构建时报告）
+    config_errors: Vec<String>,
+    /// 已添加的处理器（按添加顺序）
+    handlers: Vec<(HandlerSpec, HandlerOptions)>,
+    /// 错误回调
+    error_callback: Option<crate::diagnostics::ErrorCallback>,
+    /// 卡住阈值
+    stall_threshold: Option<std::time::Duration>,
+    /// 关闭后被拒绝的记录是否写入标准错误
+    stderr_on_shutdown: bool,
+    /// 构建时等待工作线程就绪的超时时间（为零时不等待）
+    ready_timeout: std::time::Duration,
+    /// 是否上报被过滤的记录
+    filter_tracing: bool,
+    /// 记录富化函数
+    enricher: Option<crate::producer_consumer::Enricher>,
+    /// 受保护的目标前缀
+    protected_targets: Vec<String>,
+    /// 统计导出间隔和回调
+    stats_exporter: Option<(std::time::Duration, crate::stats::StatsExporterFn)>,
+    pipeline_probe: Option<(std::time::Duration, crate::config::ProbeMarker)>,
+    /// 用户回调中产生的记录的处理方式
+    reentrant_policy: ReentrantPolicy,
 }
 
 impl LoggerBuilder {
@@ -211,14 +876,80 @@ impl LoggerBuilder {
     pub fn new() -> Self {
         Self {
             level: LevelFilter::Info,
-            processor_manager: ProcessorManager::new(),
             batch_config: None,
             dev_mode: false,
             enable_async: false,
-            expected_processor_types: std::collections::HashSet::new(),
+            #[cfg(feature = "signal-flush")]
+            signal_flush: Vec::new(),
+            #[cfg(feature = "signal-flush")]
+            signal_shutdown: false,
+            sampling: None,
+            breaker: None,
+            memory_budget: None,
+            sequence: false,
+            app_id: None,
+            emergency_when_off: false,
+            clock: Arc::new(SystemClock),
+            preset_terminal: None,
+            default_format: None,
+            default_color: None,
+            config_errors: Vec::new(),
+            handlers: Vec::new(),
+            error_callback: None,
+            stall_threshold: None,
+            stderr_on_shutdown: false,
+            ready_timeout: DEFAULT_READY_TIMEOUT,
+            filter_tracing: false,
+            enricher: None,
+            protected_targets: Vec::new(),
+            stats_exporter: None,
+            pipeline_probe: None,
+            reentrant_policy: ReentrantPolicy::default(),
         }
     }
 
+    /// 开发环境预设：带颜色的终端输出、Debug 级别、同步等待每条日志处理完成
+    ///
+    /// 预设只是对已有选项的组合，之后的 `with_*`/`add_*` 调用会覆盖预设值。
+    pub fn development() -> Self {
+        let mut builder = Self::new()
+            .with_level(LevelFilter::Debug)
+            .with_async_mode(false)
+            .with_dev_mode(true);
+        builder.preset_terminal = Some(crate::handler::term::TermConfig {
+            enable_color: true,
+            ..Default::default()
+        });
+        builder
+    }
+
+    /// 生产环境预设：终端输出（仅在终端中启用颜色）、Info 级别、异步批量处理
+    pub fn production() -> Self {
+        use std::io::IsTerminal;
+        let mut builder = Self::new()
+            .with_level(LevelFilter::Info)
+            .with_async_mode(true)
+            .with_batch_config(BatchConfig::default());
+        builder.preset_terminal = Some(crate::handler::term::TermConfig {
+            enable_color: std::io::stdout().is_terminal(),
+            ..Default::default()
+        });
+        builder
+    }
+
+    /// 高吞吐预设：大批量、较长刷新间隔的异步处理，Info 级别，终端输出不带颜色
+    pub fn high_throughput() -> Self {
+        let mut builder = Self::new()
+            .with_level(LevelFilter::Info)
+            .with_async_mode(true)
+            .with_batch_config(BatchConfig::throughput());
+        builder.preset_terminal = Some(crate::handler::term::TermConfig {
+            enable_color: false,
+            ..Default::default()
+        });
+        builder
+    }
+
     /// 设置是否启用异步模式
     pub fn with_async_mode(mut self, enable_async: bool) -> Self {
         self.enable_async = enable_async;
@@ -237,172 +968,554 @@ impl LoggerBuilder {
         self
     }
 
+    /// 设置采样配置（只对 `levels` 阈值及更详细的级别生效）
+    pub fn with_sampling(mut self, config: SamplingConfig) -> Self {
+        self.sampling = Some(config);
+        self
+    }
+
+    /// 设置全局熔断：记录速率超过 `records_per_sec` 时按 `action` 丢弃 Error 以外的记录
+    ///
+    /// 恢复阈值为上限的一半，速率持续低于恢复阈值 5 秒后恢复；需要调整时使用 [`with_circuit_breaker`](Self::with_circuit_breaker)。
+    /// 熔断和恢复时上报 `BreakerOpened`/`BreakerClosed` 诊断事件，状态可通过 `stats().breaker_open()` 查看。
+    pub fn with_max_record_rate(self, records_per_sec: u64, action: BreakerAction) -> Self {
+        self.with_circuit_breaker(BreakerConfig::new(records_per_sec, action))
+    }
+
+    /// 设置全局熔断配置
+    pub fn with_circuit_breaker(mut self, config: BreakerConfig) -> Self {
+        self.breaker = Some(config);
+        self
+    }
+
+    /// 设置内存预算：已提交但处理器尚未处理完的数据超过 `bytes` 时按 `on_exceed` 处理新记录
+    ///
+    /// Error 级别和紧急日志可以额外使用预算 1/16 的预留，需要调整时使用 [`with_memory_budget_config`](Self::with_memory_budget_config)。
+    /// 超出和恢复时上报 `MemoryBudgetExceeded`/`MemoryBudgetRecovered` 诊断事件，
+    /// 当前的待处理字节数可通过 `stats().in_flight_bytes()` 查看。
+    pub fn with_memory_budget(self, bytes: ByteSize, on_exceed: MemoryAction) -> Self {
+        self.with_memory_budget_config(MemoryBudget::new(bytes, on_exceed))
+    }
+
+    /// 设置内存预算配置
+    pub fn with_memory_budget_config(mut self, config: MemoryBudget) -> Self {
+        self.memory_budget = Some(config);
+        self
+    }
+
+    /// 为每条记录写入单调递增的日志器序号（从 0 开始），格式模板可使用 `{seq}`
+    ///
+    /// 序号在 `log()` 中分配，即使时间戳相同也能对记录完整排序。
+    /// 多线程并发记录时，输出中的先后顺序可能与序号略有不同，应按序号排序。
+    pub fn with_sequence(mut self, enabled: bool) -> Self {
+        self.sequence = enabled;
+        self
+    }
+
+    /// 设置日志宏产生的记录使用的应用ID
+    ///
+    /// 未设置时使用当前可执行文件名（见 [`crate::process_info::binary_name`]），文件名无法解析时不设置应用ID。
+    /// 手动构造并传给 `log()` 的记录保留自身的应用ID。
+    pub fn with_app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.app_id = Some(app_id.into());
+        self
+    }
+
+    /// 设置记录时间的来源（默认 `SystemClock`）
+    ///
+    /// 没有时间的记录在 `log()` 中由时钟写入时间，所有格式化器和 UDP 数据包都使用该时间，
+    /// 测试中可使用 `FixedClock`/`StepClock`（需要 `test-util` 特性）得到确定的输出。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置级别为 `Off` 时是否仍输出紧急日志（`emergency!`、`startup_log!`、`emergency_log()`）
+    ///
+    /// 默认不输出：级别为 `Off` 时日志器完全静默。其他级别下紧急日志始终无视级别过滤。
+    pub fn with_emergency_when_off(mut self, enabled: bool) -> Self {
+        self.emergency_when_off = enabled;
+        self
+    }
+
+    /// 设置所有处理器的默认格式
+    ///
+    /// 格式解析顺序：处理器自身的 `format: Some(..)` > 构建器默认格式 > 处理器内置格式。
+    /// 默认格式在添加处理器时解析，因此只作用于之后添加的处理器（以及构建时添加的预设终端）。
+    pub fn with_format(mut self, format: FormatConfig) -> Self {
+        if let Err(e) = format.validate() {
+            panic!("FormatConfig 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e);
+        }
+        self.default_format = Some(format);
+        self
+    }
+
+    /// 设置终端处理器的默认颜色
+    ///
+    /// 只作用于启用颜色（`enable_color: true`）且未指定 `color` 的终端处理器，解析时机同 `with_format()`。
+    pub fn with_color(mut self, color: ColorConfig) -> Self {
+        self.default_color = Some(color);
+        self
+    }
+
+    /// 按解析顺序为终端配置补充构建器默认格式和颜色
+    fn resolve_term_config(&self, mut config: crate::handler::term::TermConfig) -> crate::handler::term::TermConfig {
+        if config.format.is_none() {
+            config.format = self.default_format.clone();
+        }
+        if config.enable_color && config.color.is_none() {
+            config.color = self.default_color.clone();
+        }
+        if config.flush_policy.is_none() {
+            // 同步模式保持交互式输出，异步模式每个批次刷新一次
+            use crate::handler::term::FlushPolicy;
+            config.flush_policy = Some(if self.enable_async { FlushPolicy::EveryBatch } else { FlushPolicy::EveryRecord });
+        }
+        config
+    }
+
+    /// 按解析顺序为文件配置补充构建器默认格式
+    fn resolve_file_config(&self, mut config: crate::config::FileConfig) -> crate::config::FileConfig {
+        if config.format.is_none() {
+            config.format = self.default_format.clone();
+        }
+        config
+    }
+
     /// 启用开发模式（同步等待日志处理完成）
     pub fn with_dev_mode(mut self, enabled: bool) -> Self {
         self.dev_mode = enabled;
         self
     }
 
-    
+    /// 收到指定终止信号时执行带确认的刷新，然后按原语义退出（需要 `signal-flush` 特性）
+    ///
+    /// 之前处理方式为默认的信号会在刷新后重新触发；已有处理器的信号则交由原处理器处理。
+    #[cfg(feature = "signal-flush")]
+    pub fn with_signal_flush(mut self, signals: &[crate::signal::Signal]) -> Self {
+        self.signal_flush = signals.to_vec();
+        self
+    }
+
+    /// 收到信号刷新后是否完整关闭所有工作线程（需要 `signal-flush` 特性）
+    #[cfg(feature = "signal-flush")]
+    pub fn with_signal_shutdown(mut self, enabled: bool) -> Self {
+        self.signal_shutdown = enabled;
+        self
+    }
+
     /// 添加带配置的终端处理器
     pub fn add_terminal_with_config(mut self, config: crate::handler::term::TermConfig) -> Self {
-        use crate::handler::term::TermProcessor;
-        let processor = TermProcessor::with_config(config);
+        self.preset_terminal = None;
+        let config = self.resolve_term_config(config);
+        self.handlers.push((HandlerSpec::Terminal(config, None), HandlerOptions::default()));
+        self
+    }
 
-        // 如果还没有设置batch_config，使用默认的同步配置
-        let batch_config = self.batch_config.clone().unwrap_or_else(|| {
-            if self.enable_async {
-                panic!("配置错误: 异步模式必须先配置BatchConfig，请使用with_batch_config()方法设置。");
-            } else {
-                BatchConfig {
-                    batch_size: 1,
-                    batch_interval_ms: 1,
-                    buffer_size: 1024,
-                }
-            }
-        });
+    /// 添加带选项的终端处理器（如只接收部分级别，见 [`HandlerOptions`]）
+    pub fn add_terminal_with_options(self, config: crate::handler::term::TermConfig, options: HandlerOptions) -> Self {
+        self.add_terminal_with_config(config).with_last_handler_options(options)
+    }
 
-        if let Err(e) = self.processor_manager.add_processor(processor, batch_config) {
-            eprintln!("添加终端处理器失败: {}", e);
-        } else {
-            self.expected_processor_types.insert(processor_types::TERMINAL.to_string());
-        }
+    /// 添加使用自定义格式化函数的终端处理器
+    ///
+    /// 格式化函数负责写出完整的一行（包括换行符），配置中的格式和颜色不再生效。
+    pub fn add_terminal_with_formatter<F>(mut self, config: crate::handler::term::TermConfig, formatter: F) -> Self
+    where
+        F: Fn(&mut dyn std::io::Write, &Record) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        self.preset_terminal = None;
+        let config = self.resolve_term_config(config);
+        self.handlers.push((HandlerSpec::Terminal(config, Some(Arc::new(formatter))), HandlerOptions::default()));
         self
     }
 
     /// 添加文件处理器
+    ///
+    /// 配置无效或日志目录已被另一个文件处理器使用（见 `SharedDirPolicy`）时，错误在构建时报告。
     pub fn add_file(mut self, config: crate::config::FileConfig) -> Self {
+        let config = self.resolve_file_config(config);
+        self.handlers.push((HandlerSpec::File(config, None), HandlerOptions::default()));
+        self
+    }
+
+    /// 添加带选项的文件处理器（如只接收部分级别，见 [`HandlerOptions`]）
+    pub fn add_file_with_options(self, config: crate::config::FileConfig, options: HandlerOptions) -> Self {
+        self.add_file(config).with_last_handler_options(options)
+    }
+
+    /// 添加使用自定义格式化函数的文件处理器
+    ///
+    /// 格式化函数负责写出完整的一行（包括换行符），配置中的格式不再生效，轮转和压缩照常进行。
+    pub fn add_file_with_formatter<F>(mut self, config: crate::config::FileConfig, formatter: F) -> Self
+    where
+        F: Fn(&mut dyn std::io::Write, &Record) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        let config = self.resolve_file_config(config);
+        self.handlers.push((HandlerSpec::File(config, Some(Arc::new(formatter))), HandlerOptions::default()));
+        self
+    }
+
+    /// 创建处理器并启动其工作线程，返回处理器使用的配置
+    fn create_processor(
+        &self,
+        manager: &mut ProcessorManager,
+        spec: HandlerSpec,
+        options: HandlerOptions,
+        batch_config: BatchConfig,
+        started: Option<SystemTime>,
+    ) -> Result<ProcessorConfig, String> {
         use crate::handler::file::FileProcessor;
-        let processor = FileProcessor::new(config);
+        use crate::handler::term::TermProcessor;
+        use crate::handler::udp::UdpProcessor;
 
-        // 如果还没有设置batch_config，使用默认的同步配置
-        let batch_config = self.batch_config.clone().unwrap_or_else(|| {
-            if self.enable_async {
-                panic!("配置错误: 异步模式必须先配置BatchConfig，请使用with_batch_config()方法设置。");
-            } else {
-                BatchConfig {
-                    batch_size: 1,
-                    batch_interval_ms: 1,
-                    buffer_size: 1024,
+        options.validate()?;
+        let levels = options.effective_levels();
+        let started = started.unwrap_or_else(SystemTime::now);
+
+        match spec {
+            HandlerSpec::Terminal(config, formatter) => {
+                config.validate().map_err(|e| format!("TermConfig 验证失败: {}", e))?;
+                let mut processor = TermProcessor::with_config(config).with_start_time(started);
+                processor.set_line_affixes(LineAffixes::new(&options));
+                if let Some(formatter) = formatter {
+                    processor.set_formatter(move |buf: &mut dyn std::io::Write, record: &Record| formatter(buf, record));
                 }
+                let config = ProcessorConfig::Terminal(processor.config().clone());
+                manager.add_named_processor(processor, batch_config, levels, options.name.clone())?;
+                Ok(config)
             }
-        });
+            HandlerSpec::File(mut config, formatter) => {
+                // 文件名模板中的 `{app_id}` 未单独设置时使用日志器的应用ID
+                let uses_app_id = config.file_name_template.as_ref().is_some_and(|template| template.contains("{app_id}"));
+                if uses_app_id && config.app_id.is_none() {
+                    config.app_id = self.app_id.clone();
+                }
+                let mut processor = FileProcessor::try_new(config).map_err(|e| e.to_string())?.with_start_time(started);
+                processor.set_line_affixes(LineAffixes::new(&options));
+                if let Some(formatter) = formatter {
+                    processor.set_formatter(move |buf: &mut dyn std::io::Write, record: &Record| formatter(buf, record));
+                }
+                if let Some(app_id) = &self.app_id {
+                    processor = processor.with_header_app_id(app_id.clone());
+                }
+                let config = ProcessorConfig::File(processor.config().clone());
+                manager.add_named_processor(processor, batch_config, levels, options.name.clone())?;
+                Ok(config)
+            }
+            HandlerSpec::Udp(config) => {
+                if options.has_line_affixes() {
+                    return Err("UDP 处理器不支持行前缀和后缀（记录以结构化数据包发送）".to_string());
+                }
+                let checked = config.validate().and_then(|_| {
+                    if config.verify_connectivity { config.check_connectivity() } else { Ok(()) }
+                });
+                checked.map_err(|e| format!("UdpConfig 验证失败: {}", e))?;
+                manager.add_named_processor(UdpProcessor::with_config(config.clone()), batch_config, levels, options.name.clone())?;
+                Ok(ProcessorConfig::Udp(config))
+            }
+        }
+    }
 
-        if let Err(e) = self.processor_manager.add_processor(processor, batch_config) {
-            eprintln!("添加文件处理器失败: {}", e);
+    /// 设置错误回调，处理器无法恢复的错误（如发送重试耗尽）通过它上报
+    pub fn with_error_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&crate::diagnostics::DiagnosticEvent) + Send + Sync + 'static,
+    {
+        self.error_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// 设置卡住阈值：处理器的单次操作（批量写入、刷新等）超过该时间仍未完成时，
+    /// 通过错误回调上报 `DiagnosticEvent::ProcessorStalled` 并增加 `LoggerStats::stalled()`；
+    /// 操作完成后上报 `ProcessorRecovered`（附带实际耗时）。检测不会中断卡住的工作线程。
+    pub fn with_stall_threshold(mut self, threshold: std::time::Duration) -> Self {
+        if threshold.is_zero() {
+            self.config_errors.push("配置错误: 卡住阈值不能为 0".to_string());
         } else {
-            self.expected_processor_types.insert(processor_types::FILE.to_string());
+            self.stall_threshold = Some(threshold);
         }
         self
     }
 
     /// 添加UDP处理器
-    pub fn add_udp(mut self, config: crate::config::NetworkConfig) -> Self {
-        use crate::handler::udp::UdpProcessor;
-        let processor = UdpProcessor::new(config);
+    pub fn add_udp(self, config: crate::config::NetworkConfig) -> Self {
+        self.add_udp_with_config(crate::handler::udp::UdpConfig {
+            network_config: config,
+            ..Default::default()
+        })
+    }
 
-        // 如果还没有设置batch_config，使用默认的同步配置
-        let batch_config = self.batch_config.clone().unwrap_or_else(|| {
-            if self.enable_async {
-                panic!("配置错误: 异步模式必须先配置BatchConfig，请使用with_batch_config()方法设置。");
-            } else {
-                BatchConfig {
-                    batch_size: 1,
-                    batch_interval_ms: 1,
-                    buffer_size: 1024,
-                }
-            }
-        });
+    /// 使用完整的UDP配置添加UDP处理器（重试、批量发送等）
+    ///
+    /// 配置无效（或启用 `verify_connectivity` 时无法连接）时，错误在构建时报告。
+    pub fn add_udp_with_config(mut self, config: crate::handler::udp::UdpConfig) -> Self {
+        self.handlers.push((HandlerSpec::Udp(config), HandlerOptions::default()));
+        self
+    }
+
+    /// 添加带选项的UDP处理器（如只接收部分级别，见 [`HandlerOptions`]）
+    pub fn add_udp_with_options(self, config: crate::handler::udp::UdpConfig, options: HandlerOptions) -> Self {
+        self.add_udp_with_config(config).with_last_handler_options(options)
+    }
+
+    /// 设置最近添加的处理器的选项
+    fn with_last_handler_options(mut self, options: HandlerOptions) -> Self {
+        if let Some((_, last)) = self.handlers.last_mut() {
+            *last = options;
+        }
+        self
+    }
+
+    /// 日志器关闭（见 [`LoggerCore::shutdown`]）后被拒绝的记录写入标准错误，作为最后的输出
+    ///
+    /// 默认只计入 `LoggerStats::shutdown_rejected`。
+    pub fn with_stderr_on_shutdown(mut self, enabled: bool) -> Self {
+        self.stderr_on_shutdown = enabled;
+        self
+    }
+
+    /// 设置构建时等待工作线程就绪的超时时间（默认 5 秒）
+    ///
+    /// `build()` 返回前所有工作线程已进入处理循环，之后立即记录的日志不会因线程尚未启动而延迟；
+    /// 超时视为构建失败。设为 `Duration::ZERO` 时不等待，之后可调用
+    /// [`LoggerCore::wait_for_workers_ready`] 自行等待。
+    pub fn with_ready_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.ready_timeout = timeout;
+        self
+    }
+
+    /// 启用过滤追踪：每条被过滤的记录通过错误回调上报一个 `DiagnosticEvent::RecordFiltered`，
+    /// 指明过滤它的环节（全局最大级别、日志器级别或采样规则）及相应的配置值，用于排查“日志为什么没有输出”
+    ///
+    /// 事件不会作为日志记录输出，每秒最多上报 [`FILTER_TRACE_LIMIT`](crate::diagnostics::FILTER_TRACE_LIMIT) 个。
+    /// 也可以通过环境变量 `RAT_LOGGER_TRACE_FILTER=1` 启用。追踪会让被过滤的日志宏调用变慢，只用于调试。
+    pub fn with_filter_tracing(mut self, enabled: bool) -> Self {
+        self.filter_tracing = enabled;
+        self
+    }
+
+    /// 设置记录富化函数，用于附加代价较高的信息（如从本地缓存把用户ID解析为用户名）而不拖慢调用方
+    ///
+    /// 富化函数在工作线程中执行：记录解码之后、终端和文件处理器格式化之前，UDP 处理器编码网络记录之前。
+    /// 每条记录只富化一次，第一个处理它的工作线程执行，结果由所有处理器共享。富化函数 panic 时
+    /// 上报 `DiagnosticEvent::EnricherPanicked`，记录按原样输出，工作线程不受影响。原始数据
+    /// （`log_raw`）和转发的数据包不经过富化。
+    pub fn with_enricher(mut self, enricher: impl Fn(&mut Record) + Send + Sync + 'static) -> Self {
+        self.enricher = Some(Arc::new(enricher));
+        self
+    }
+
+    /// 设置受保护的目标前缀（如 `"audit::"`），目标以其中任一前缀开头的记录不会因负载而被丢弃
+    ///
+    /// 受保护的记录只按级别过滤，不经过采样和熔断；内存预算下使用 Error 级别的预留，预留也用完时
+    /// `BlockProducers` 等待处理器腾出空间，其他处理方式直接放行（超出预算也不丢弃）。
+    /// 这类记录计入 `stats().protected_records()`。保护针对合规审计这类必须完整保留的少量记录，
+    /// 大量记录使用受保护的目标会让熔断和内存预算失去作用。
+    pub fn with_protected_targets(mut self, prefixes: Vec<String>) -> Self {
+        self.protected_targets = prefixes;
+        self
+    }
+
+    /// 设置用户回调（格式化函数、富化函数、终端的 `on_record` 钩子、工作线程中的错误回调）中产生的日志记录的处理方式
+    ///
+    /// 回调在工作线程中执行，其中的日志宏记录如果进入管道，同步写出的处理器可能在共享的终端输出上死锁，
+    /// 格式化函数记录日志也会无限递归。这类记录不进入管道：默认计数后丢弃，
+    /// `ReentrantPolicy::Stderr` 时直接写入标准错误；均计入 `stats().reentrant_records()`。
+    /// 应用线程中触发的错误回调（如熔断、过滤追踪）不受影响，其中记录的日志照常写入（见 [`Metadata::is_internal`](crate::config::Metadata::is_internal)）。
+    pub fn with_reentrant_policy(mut self, policy: ReentrantPolicy) -> Self {
+        self.reentrant_policy = policy;
+        self
+    }
+
+    /// 设置统计导出：每隔 `interval` 在独立线程中以当前的 [`StatsSnapshot`](crate::stats::StatsSnapshot) 调用一次 `exporter`
+    ///
+    /// 用于把计数器推送到应用选择的指标库（如 prometheus、metrics），rat_logger 本身不依赖这些库。
+    /// 回调应当很快返回，执行期间不会开始下一次导出；日志器释放时导出线程停止。
+    pub fn with_stats_exporter(
+        mut self,
+        interval: std::time::Duration,
+        exporter: impl Fn(crate::stats::StatsSnapshot) + Send + Sync + 'static,
+    ) -> Self {
+        if interval.is_zero() {
+            self.config_errors.push("配置错误: 统计导出间隔不能为 0".to_string());
+        } else {
+            self.stats_exporter = Some((interval, Arc::new(exporter)));
+        }
+        self
+    }
 
-        if let Err(e) = self.processor_manager.add_processor(processor, batch_config) {
-            eprintln!("添加UDP处理器失败: {}", e);
+    /// 设置管道探测：每隔 `interval` 向每个处理器发送一个探测标记，测量记录从产生到被该处理器处理完的延迟
+    ///
+    /// 标记与记录经同一队列按顺序处理，延迟包括排队和凑满批次的时间，可用于检验批量配置；
+    /// 结果通过 [`LoggerCore::probe_reports`] 读取。`marker` 为 [`ProbeMarker::Written`](crate::config::ProbeMarker::Written)
+    /// 时同时写入一条可见的标记记录。
+    pub fn with_pipeline_probe(mut self, interval: std::time::Duration, marker: crate::config::ProbeMarker) -> Self {
+        if interval.is_zero() {
+            self.config_errors.push("配置错误: 管道探测间隔不能为 0".to_string());
         } else {
-            self.expected_processor_types.insert(processor_types::UDP.to_string());
+            self.pipeline_probe = Some((interval, marker));
         }
         self
     }
 
     /// 构建日志器
+    ///
+    /// 配置错误时 panic，需要处理错误时使用 [`try_build`](Self::try_build)。
     pub fn build(self) -> LoggerCore {
+        self.try_build().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 构建日志器，配置错误（包括添加处理器时发现的错误）通过返回值报告
+    pub fn try_build(mut self) -> Result<LoggerCore, String> {
+        if let Some(e) = self.config_errors.first() {
+            return Err(format!("{}\n请检查您的配置并修复上述问题后再重试。", e));
+        }
+
+        // 预设的终端处理器在构建时才添加，使之后设置的批量配置同样作用于它
+        if let Some(config) = self.preset_terminal.take() {
+            self = self.add_terminal_with_config(config);
+        }
+
         // 验证批量配置
-        let batch_config = match self.batch_config {
+        let batch_config = match self.batch_config.clone() {
             Some(config) => config,
             None => {
                 if self.enable_async {
-                    panic!("配置错误: 异步模式必须配置BatchConfig，请使用with_batch_config()方法设置。");
+                    return Err("配置错误: 异步模式必须配置BatchConfig，请使用with_batch_config()方法设置。".to_string());
                 } else {
                     // 同步模式使用默认配置
-                    BatchConfig {
-                        batch_size: 1,
-                        batch_interval_ms: 1,
-                        buffer_size: 1024,
-                    }
+                    BatchConfig::low_latency()
                 }
             }
         };
 
         // 验证批量配置
         if let Err(e) = batch_config.validate() {
-            panic!("LoggerBuilder 批量配置验证失败: {}\n请检查您的批量配置并修复上述问题后再重试。", e);
+            return Err(format!("LoggerBuilder 批量配置验证失败: {}\n请检查您的批量配置并修复上述问题后再重试。", e));
         }
 
         // 验证是否有处理器
-        if self.processor_manager.is_empty() {
-            panic!("配置错误: 必须至少添加一个处理器（终端、文件或UDP）");
+        if self.handlers.is_empty() {
+            return Err("配置错误: 必须至少添加一个处理器（终端、文件或UDP）".to_string());
         }
 
-        LoggerCore::with_expected_types(
+        // 验证采样配置
+        if let Some(Err(e)) = self.sampling.as_ref().map(|sampling| sampling.validate()) {
+            return Err(format!("SamplingConfig 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e));
+        }
+
+        // 验证熔断配置
+        if let Some(Err(e)) = self.breaker.as_ref().map(|breaker| breaker.validate()) {
+            return Err(format!("BreakerConfig 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e));
+        }
+
+        // 验证内存预算
+        if let Some(Err(e)) = self.memory_budget.as_ref().map(|budget| budget.validate()) {
+            return Err(format!("MemoryBudget 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e));
+        }
+
+        // 配置检查通过后才创建处理器并启动工作线程，第一个失败的处理器使构建失败
+        let mut processor_manager = ProcessorManager::new();
+        if let Some(callback) = self.error_callback.clone() {
+            processor_manager.diagnostics().set_callback(Some(callback));
+        }
+        if let Some(threshold) = self.stall_threshold {
+            processor_manager.set_stall_threshold(threshold);
+        }
+        processor_manager.set_stderr_on_shutdown(self.stderr_on_shutdown);
+        processor_manager.set_enricher(self.enricher.clone());
+        if let Some((interval, exporter)) = self.stats_exporter.take() {
+            processor_manager.set_stats_exporter(interval, exporter);
+        }
+        if let Some((interval, marker)) = self.pipeline_probe.take() {
+            processor_manager.set_pipeline_probe(interval, marker);
+        }
+        if let Some(budget) = self.memory_budget.take() {
+            processor_manager.set_memory_budget(budget);
+        }
+        // `{uptime}` 从日志器时钟的当前时间开始计算（只在模板使用时读取时钟）
+        let started = self.handlers.iter().any(|(spec, _)| spec.uses_uptime()).then(|| self.clock.now());
+        let mut expected_processor_types = std::collections::HashSet::new();
+        let mut processor_configs = Vec::with_capacity(self.handlers.len());
+        for (index, (spec, options)) in std::mem::take(&mut self.handlers).into_iter().enumerate() {
+            let (kind, processor_type) = (spec.kind(), spec.processor_type());
+            let config = self.create_processor(&mut processor_manager, spec, options, batch_config.clone(), started).map_err(|e| {
+                format!("处理器 #{}（{}）创建失败: {}\n请检查您的配置并修复上述问题后再重试。", index, kind, e)
+            })?;
+            expected_processor_types.insert(processor_type.to_string());
+            processor_configs.push(config);
+        }
+
+        let mut logger = LoggerCore::with_expected_types(
             self.level,
-            self.processor_manager,
+            processor_manager,
             batch_config,
             self.dev_mode,
-            self.expected_processor_types
-        )
+            expected_processor_types
+        );
+        logger.sampler = self.sampling.map(|config| Arc::new(Sampler::new(config)));
+        let diagnostics = logger.processor_manager.diagnostics().clone();
+        logger.breaker = self.breaker.map(|config| Arc::new(CircuitBreaker::new(config, diagnostics)));
+        logger.protected_targets = Arc::new(std::mem::take(&mut self.protected_targets));
+        logger.reentrant_policy = self.reentrant_policy;
+        logger.seq = self.sequence.then(|| Arc::new(AtomicU64::new(0)));
+        logger.app_id = self.app_id
+            .or_else(|| Some(crate::process_info::binary_name().to_string()))
+            .filter(|app_id| !app_id.is_empty());
+        logger.emergency_when_off = self.emergency_when_off;
+        logger.clock = self.clock;
+        logger.processor_configs = Arc::new(processor_configs);
+        if self.filter_tracing || std::env::var("RAT_LOGGER_TRACE_FILTER").is_ok_and(|value| value == "1") {
+            logger.filter_tracer = Some(Arc::new(FilterTracer::new(logger.processor_manager.diagnostics().clone())));
+            FILTER_TRACING.store(true, Ordering::Relaxed);
+        }
+
+        #[cfg(feature = "signal-flush")]
+        if !self.signal_flush.is_empty() {
+            let manager = Arc::downgrade(&logger.processor_manager);
+            if let Err(e) = crate::signal::install(manager, &self.signal_flush, self.signal_shutdown) {
+                eprintln!("安装信号处理器失败: {}", e);
+            }
+        }
+
+        if !self.ready_timeout.is_zero() && let Err(e) = logger.wait_for_workers_ready(self.ready_timeout.as_millis() as u64) {
+            return Err(format!("日志器初始化失败：工作线程就绪检查失败: {}\n请检查处理器配置或系统资源", e));
+        }
+
+        Ok(logger)
     }
 
     /// 构建并初始化全局日志器
+    ///
+    /// 多个线程同时初始化时只有一个成功，其余返回 [`SetLoggerError::AlreadyInitialized`]；
+    /// 失败的一方不会创建处理器或启动工作线程。开发模式（debug 构建）下允许替换已有的日志器。
+    /// 构建失败（配置错误、工作线程就绪超时）时 panic，需要处理错误时使用
+    /// [`try_init_global_logger`](Self::try_init_global_logger)。
     pub fn init_global_logger(self) -> Result<(), SetLoggerError> {
+        match self.try_init_global_logger() {
+            Err(SetLoggerError::Build(e)) => panic!("{}", e),
+            result => result,
+        }
+    }
+
+    /// 构建并初始化全局日志器，构建失败时返回 [`SetLoggerError::Build`]
+    pub fn try_init_global_logger(self) -> Result<(), SetLoggerError> {
         let level = self.level;
-        let is_dev_mode = self.dev_mode;
-        let logger = Arc::new(self.build());
 
         // 开发模式下允许重新初始化
-        if is_dev_mode && cfg!(debug_assertions) {
-            set_logger_dev(logger)?;
+        if self.dev_mode && cfg!(debug_assertions) {
+            let logger = self.try_build().map_err(SetLoggerError::Build)?;
+            set_logger_dev(Arc::new(logger))?;
         } else {
-            // 生产模式：允许重新初始化以应对程序多次运行的情况
-            let _lock = LOGGER_LOCK.write().unwrap();
-            let mut guard = LOGGER.lock().unwrap();
-
-            // 检查是否已经初始化过
-            if guard.is_some() {
-                // 如果已经初始化过，直接使用现有的日志器
-                // 注意：这里我们放弃新创建的logger，保持现有配置
-                eprintln!("⚠️  警告：全局日志器已经初始化，跳过重复初始化");
-                eprintln!("⚠️  这将导致新创建的LoggerCore被丢弃，ProcessorWorker的Drop trait会被调用！");
-            } else {
-                // 如果没有初始化过，正常设置
-                *guard = Some(logger);
-            }
-
-            // 智能等待所有工作线程启动就绪
-            // 替换原来的固定延时，提供更可靠的等待机制
-            if let Some(logger) = guard.as_ref() {
-                // 使用更安全的方式检查类型
-                let logger_ptr = logger.as_ref() as *const dyn Logger;
-                let logger_core_ptr = logger_ptr as *const LoggerCore;
-
-                // 检查是否确实是LoggerCore类型
-                if !logger_ptr.is_null() && !logger_core_ptr.is_null() {
-                    // 安全转换，因为我们已经检查了类型
-                    let logger_core = unsafe { &*logger_core_ptr };
-
-                    // 智能等待所有工作线程启动就绪，超时时间5秒
-                    if let Err(e) = logger_core.wait_for_workers_ready(5000) {
-                        panic!("❌ 日志器初始化失败：工作线程健康检查失败: {}\n请检查处理器配置或系统资源", e);
-                    }
-                }
+            // 持有写锁直到日志器安装完成，检查和安装之间不会有其他线程插入
+            let _lock = LOGGER_LOCK.write().unwrap_or_else(PoisonError::into_inner);
+            if lock_global_logger().is_some() {
+                return Err(SetLoggerError::AlreadyInitialized);
             }
+            // 构建时等待工作线程就绪（见 `with_ready_timeout`）
+            let logger = self.try_build().map_err(SetLoggerError::Build)?;
+            *lock_global_logger() = Some(Arc::new(logger));
         }
 
         set_max_level(level);
@@ -424,9 +1537,10 @@ impl Default for LoggerBuilder {
 
 /// 设置全局日志器
 pub fn set_logger(logger: Arc<dyn Logger>) -> Result<(), SetLoggerError> {
-    let mut guard = LOGGER.lock().unwrap();
+    let _lock = LOGGER_LOCK.write().unwrap_or_else(PoisonError::into_inner);
+    let mut guard = lock_global_logger();
     if guard.is_some() {
-        return Err(SetLoggerError(()));
+        return Err(SetLoggerError::AlreadyInitialized);
     }
     *guard = Some(logger);
     Ok(())
@@ -435,9 +1549,9 @@ pub fn set_logger(logger: Arc<dyn Logger>) -> Result<(), SetLoggerError> {
 /// 开发模式友好的日志器设置（允许重新初始化）
 pub fn set_logger_dev(logger: Arc<dyn Logger>) -> Result<(), SetLoggerError> {
     // 开发模式下：使用写锁来保证安全
-    let _lock = LOGGER_LOCK.write().unwrap();
+    let _lock = LOGGER_LOCK.write().unwrap_or_else(PoisonError::into_inner);
 
-    let mut guard = LOGGER.lock().unwrap();
+    let mut guard = lock_global_logger();
     if guard.is_some() {
         eprintln!("⚠️  警告：重新初始化全局日志器（开发模式）");
         eprintln!("⚠️  此功能仅供开发使用，生产环境请确保只初始化一次日志器");
@@ -471,7 +1585,87 @@ pub fn set_max_level(level: LevelFilter) {
 
 /// 获取全局最大日志级别
 pub fn max_level() -> LevelFilter {
-    match MAX_LEVEL.load(Ordering::Relaxed) {
+    level_filter_from(MAX_LEVEL.load(Ordering::Relaxed))
+}
+
+/// 全局日志器安装之前日志宏记录的处理方式（见 [`set_preinit_policy`]）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreInit {
+    /// 丢弃（默认）
+    #[default]
+    Silent,
+    /// Warn 及以上级别的记录直接写入标准错误（不启动线程，不带颜色）
+    StderrWarnAndAbove,
+}
+
+/// 全局日志器安装之前的处理方式（`PreInit` 的判别值）
+static PREINIT_POLICY: AtomicUsize = AtomicUsize::new(PreInit::Silent as usize);
+
+/// 设置全局日志器安装之前日志宏记录的处理方式
+///
+/// 参数解析、加载配置等初始化之前的阶段也能报告问题：设置为 `PreInit::StderrWarnAndAbove` 后，
+/// 没有全局日志器时 Warn 及以上级别的日志宏记录以 `{时间} [{级别}] {目标} {文件}:{行号} - {消息}`
+/// 格式直接写入标准错误。全局日志器安装之后不再使用此路径；作用域日志器不受影响。
+pub fn set_preinit_policy(policy: PreInit) {
+    PREINIT_POLICY.store(policy as usize, Ordering::Relaxed);
+}
+
+/// 当前的全局日志器安装之前的处理方式
+pub fn preinit_policy() -> PreInit {
+    match PREINIT_POLICY.load(Ordering::Relaxed) {
+        1 => PreInit::StderrWarnAndAbove,
+        _ => PreInit::Silent,
+    }
+}
+
+/// 用户回调中产生的日志记录的处理方式（见 [`LoggerBuilder::with_reentrant_policy`]）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReentrantPolicy {
+    /// 丢弃（默认），只计数
+    #[default]
+    Drop,
+    /// 以初始化之前的格式直接写入标准错误（见 [`set_preinit_policy`]）
+    Stderr,
+}
+
+/// 处理全局日志器安装之前的日志宏记录，`message` 只在需要输出时调用
+pub(crate) fn log_preinit(
+    level: Level,
+    label: Option<&str>,
+    message: impl FnOnce() -> String,
+    module_path: &str,
+    file: &str,
+    line: u32,
+) {
+    if preinit_policy() == PreInit::Silent || !level.should_log_at(LevelFilter::Warn) {
+        return;
+    }
+    write_stderr_line(level, label, &message(), module_path, file, line);
+}
+
+/// 以 `{时间} [{级别}] {目标} {文件}:{行号} - {消息}` 格式向标准错误写入一行（不经过处理器）
+fn write_stderr_line(level: Level, label: Option<&str>, message: &str, module_path: &str, file: &str, line: u32) {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+    let line = format!("{} [{}] {} {}:{} - {}\n", timestamp, label.unwrap_or(level.as_str()), module_path, file, line, message);
+    // 一次写入整行，标准错误不可写时丢弃
+    let _ = std::io::Write::write_all(&mut std::io::stderr().lock(), line.as_bytes());
+}
+
+/// 上报被全局最大级别过滤的日志宏记录（只在构建过启用过滤追踪的日志器时查询全局日志器）
+#[doc(hidden)]
+pub fn trace_max_level_filtered(level: Level, target: &str) {
+    if !FILTER_TRACING.load(Ordering::Relaxed) {
+        return;
+    }
+    let logger = lock_global_logger().clone();
+    if let Some(logger) = logger {
+        logger.trace_filtered(level, target, FilterGate::MaxLevel(max_level()));
+    }
+}
+
+/// 从 `LevelFilter as usize` 的值还原级别过滤器
+fn level_filter_from(value: usize) -> LevelFilter {
+    match value {
         0 => LevelFilter::Off,
         1 => LevelFilter::Error,
         2 => LevelFilter::Warn,
@@ -496,10 +1690,51 @@ pub fn max_level() -> LevelFilter {
 /// }
 /// ```
 pub fn is_initialized() -> bool {
-    let guard = LOGGER.lock().unwrap();
+    let guard = lock_global_logger();
     guard.is_some()
 }
 
+/// 全局日志器已注册的处理器类型名称（未初始化时为空）
+///
+/// # 示例
+///
+/// ```rust
+/// if rat_logger::global_handler_types().contains(&"file_processor") {
+///     println!("日志保存在 ./logs 目录");
+/// }
+/// ```
+pub fn global_handler_types() -> Vec<&'static str> {
+    let guard = lock_global_logger();
+    guard.as_ref().map(|logger| logger.handler_types()).unwrap_or_default()
+}
+
+/// 全局日志器的同步点：调用前记录的日志全部被每个处理器处理并刷新后返回
+///
+/// # 示例
+///
+/// ```rust
+/// rat_logger::info!("服务已启动");
+/// if rat_logger::is_initialized() {
+///     rat_logger::sync_point(std::time::Duration::from_secs(2)).unwrap();
+/// }
+/// ```
+pub fn sync_point(timeout: std::time::Duration) -> Result<(), FlushError> {
+    let logger = lock_global_logger().clone();
+    match logger {
+        Some(logger) => logger.sync_point(timeout),
+        None => Err(FlushError::NotInitialized),
+    }
+}
+
+/// 在运行中移除全局日志器的指定类型处理器（如操作员关闭远程日志时移除 `"udp"`）
+pub fn detach_global_handler(kind: &str) -> Result<(), RemoveError> {
+    let logger = lock_global_logger().clone();
+    match logger {
+        Some(logger) => logger.detach_handler(kind),
+        None => Err(RemoveError::NotFound(kind.to_string())),
+    }
+}
+
 /// 从环境变量解析日志级别
 pub fn parse_log_level_from_env() -> Option<LevelFilter> {
     std::env::var("RUST_LOG").ok().and_then(|s| {
@@ -530,6 +1765,11 @@ fn create_default_logger_from_env() -> Option<LoggerCore> {
         },
         format_template: "{level} {timestamp} {message}".to_string(),
         level_templates: None,
+        sanitize: None,
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
     };
 
     let color_config = crate::config::ColorConfig {
@@ -542,6 +1782,9 @@ fn create_default_logger_from_env() -> Option<LoggerCore> {
         target: "\x1b[94m".to_string(),      // 亮蓝色
         file: "\x1b[95m".to_string(),       // 亮紫色
         message: "\x1b[97m".to_string(),      // 亮白色
+        message_follows_level: false,
+        label_colors: Vec::new(),
+        target_colors: Vec::new(),
     };
 
     let term_config = crate::handler::term::TermConfig {
@@ -554,6 +1797,7 @@ fn create_default_logger_from_env() -> Option<LoggerCore> {
         batch_size: 1,
         batch_interval_ms: 1,
         buffer_size: 1024,
+        ..Default::default()
     };
 
     let mut processor_manager = crate::producer_consumer::ProcessorManager::new();
@@ -585,7 +1829,7 @@ fn create_default_logger_from_env() -> Option<LoggerCore> {
 pub fn try_init_from_env() -> Result<(), SetLoggerError> {
     // 检查是否已经初始化
     {
-        let guard = LOGGER.lock().unwrap();
+        let guard = lock_global_logger();
         if guard.is_some() {
             return Ok(()); // 已经初始化，直接返回
         }
@@ -596,8 +1840,8 @@ pub fn try_init_from_env() -> Result<(), SetLoggerError> {
         let logger = Arc::new(logger);
 
         // 初始化全局日志器
-        let _lock = LOGGER_LOCK.write().unwrap();
-        let mut guard = LOGGER.lock().unwrap();
+        let _lock = LOGGER_LOCK.write().unwrap_or_else(PoisonError::into_inner);
+        let mut guard = lock_global_logger();
 
         // 双重检查，防止并发初始化
         if guard.is_some() {
@@ -627,13 +1871,152 @@ pub fn try_init_from_env() -> Result<(), SetLoggerError> {
 }
 
 /// 日志器设置错误
-#[derive(Debug)]
-pub struct SetLoggerError(());
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SetLoggerError {
+    /// 全局日志器已经初始化（包括与其他线程同时初始化而落后的一方）
+    AlreadyInitialized,
+    /// 构建日志器失败（配置错误、处理器创建失败或工作线程就绪超时）
+    Build(String),
+}
 
 impl std::fmt::Display for SetLoggerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("failed to set logger")
+        match self {
+            SetLoggerError::AlreadyInitialized => f.write_str("全局日志器已经初始化"),
+            SetLoggerError::Build(e) => f.write_str(e),
+        }
     }
 }
 
-impl std::error::Error for SetLoggerError {}
\ No newline at end of file
+impl std::error::Error for SetLoggerError {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_development_preset() {
+        let builder = LoggerBuilder::development();
+        assert_eq!(builder.level, LevelFilter::Debug);
+        assert!(builder.dev_mode);
+        assert!(!builder.enable_async);
+        assert!(builder.batch_config.is_none());
+        assert!(builder.preset_terminal.as_ref().unwrap().enable_color);
+
+        let logger = builder.build();
+        assert_eq!(logger.level(), LevelFilter::Debug);
+        assert!(logger.expected_processor_types.lock().contains(processor_types::TERMINAL));
+    }
+
+    #[test]
+    fn test_production_preset() {
+        let builder = LoggerBuilder::production();
+        assert_eq!(builder.level, LevelFilter::Info);
+        assert!(!builder.dev_mode);
+        assert!(builder.enable_async);
+        let batch = builder.batch_config.clone().unwrap();
+        assert_eq!((batch.batch_size, batch.batch_interval_ms, batch.buffer_size), (2048, 25, 16 * 1024));
+        assert!(builder.preset_terminal.is_some());
+    }
+
+    #[test]
+    fn test_high_throughput_preset() {
+        let builder = LoggerBuilder::high_throughput();
+        assert_eq!(builder.level, LevelFilter::Info);
+        assert!(builder.enable_async);
+        let batch = builder.batch_config.clone().unwrap();
+        assert!(batch.validate().is_ok());
+        assert_eq!((batch.batch_size, batch.batch_interval_ms, batch.buffer_size), (64 * 1024, 100, 1024 * 1024));
+        assert!(!builder.preset_terminal.as_ref().unwrap().enable_color);
+    }
+
+    #[test]
+    fn test_preset_overrides_win() {
+        let builder = LoggerBuilder::production()
+            .with_level(LevelFilter::Warn)
+            .with_dev_mode(true)
+            .with_batch_config(BatchConfig {
+                batch_size: 1,
+                batch_interval_ms: 1,
+                buffer_size: 1024,
+                ..Default::default()
+            });
+        assert_eq!(builder.level, LevelFilter::Warn);
+        assert!(builder.dev_mode);
+        assert_eq!(builder.batch_config.as_ref().unwrap().batch_size, 1);
+
+        // 显式添加终端处理器后不再重复添加预设终端
+        let builder = builder.add_terminal_with_config(crate::handler::term::TermConfig {
+            enable_color: false,
+            ..Default::default()
+        });
+        assert!(builder.preset_terminal.is_none());
+        let logger = builder.build();
+        assert_eq!(logger.level(), LevelFilter::Warn);
+        assert_eq!(logger.processor_manager().len(), 1);
+    }
+
+    #[test]
+    fn test_format_and_color_resolution_order() {
+        use crate::handler::term::TermConfig;
+
+        let builder = LoggerBuilder::new()
+            .with_format(FormatConfig { format_template: "{message}".to_string(), ..Default::default() })
+            .with_color(ColorConfig::default());
+
+        // 未指定时使用构建器默认值
+        let resolved = builder.resolve_term_config(TermConfig::default());
+        assert_eq!(resolved.format.unwrap().format_template, "{message}");
+        assert!(resolved.color.is_some());
+
+        // 处理器自身的格式优先；未启用颜色时不补充颜色（避免配置冲突）
+        let resolved = builder.resolve_term_config(TermConfig {
+            enable_color: false,
+            format: Some(FormatConfig { format_template: "[{level}] {message}".to_string(), ..Default::default() }),
+            ..Default::default()
+        });
+        assert_eq!(resolved.format.unwrap().format_template, "[{level}] {message}");
+        assert!(resolved.color.is_none());
+    }
+
+    #[test]
+    fn test_term_flush_policy_follows_mode() {
+        use crate::handler::term::{FlushPolicy, TermConfig};
+
+        let sync = LoggerBuilder::new().resolve_term_config(TermConfig::default());
+        assert_eq!(sync.flush_policy, Some(FlushPolicy::EveryRecord));
+        let batched = LoggerBuilder::new().with_async_mode(true).resolve_term_config(TermConfig::default());
+        assert_eq!(batched.flush_policy, Some(FlushPolicy::EveryBatch));
+
+        // 显式指定的策略优先
+        let explicit = LoggerBuilder::new().resolve_term_config(TermConfig {
+            flush_policy: Some(FlushPolicy::IntervalMs(50)),
+            ..Default::default()
+        });
+        assert_eq!(explicit.flush_policy, Some(FlushPolicy::IntervalMs(50)));
+    }
+
+    #[test]
+    fn test_handler_presence_queries() {
+        let log_dir = std::env::temp_dir().join(format!("rat_logger_handler_types_{}", std::process::id()));
+        let logger = LoggerBuilder::new()
+            .add_terminal_with_config(crate::handler::term::TermConfig::default())
+            .add_file(crate::config::FileConfig {
+                log_dir: log_dir.clone(),
+                ..Default::default()
+            })
+            .build();
+
+        assert_eq!(logger.handler_types(), vec![processor_types::TERMINAL, processor_types::FILE]);
+        assert!(logger.has_handler("file"));
+        assert!(logger.has_handler(processor_types::TERMINAL));
+        assert!(!logger.has_handler("udp"));
+        assert!(!logger.has_handler(processor_types::UDP));
+
+        // 通过 trait 对象（全局日志器的形式）同样可以查询
+        let logger: Arc<dyn Logger> = Arc::new(logger);
+        assert!(logger.has_handler("term"));
+        drop(logger);
+        let _ = std::fs::remove_dir_all(&log_dir);
+    }
+}
@@ -0,0 +1,415 @@
+//! 诊断模块 - 处理器运行期间的异常事件上报
+//!
+//! 每个 `ProcessorManager` 持有一个 `Diagnostics`，添加处理器时交给处理器。
+//! 处理器通过它上报事件（调用用户注册的错误回调）并累加共享的统计计数器。
+
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use parking_lot::RwLock;
+
+use crate::config::{Level, LevelFilter, MemoryAction};
+use crate::producer_consumer::ErrorKind;
+use crate::stats::LoggerStats;
+
+/// 诊断事件
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DiagnosticEvent {
+    /// 发送重试耗尽，数据被丢弃
+    SendFailed {
+        /// 处理器名称
+        processor: &'static str,
+        /// 已尝试次数
+        attempts: u32,
+        /// 被丢弃的记录数
+        dropped: usize,
+        /// 最后一次错误
+        error: String,
+    },
+    /// 日志目录或当前日志文件丢失，已重新创建
+    LogFileRecreated {
+        /// 新的日志文件路径
+        path: std::path::PathBuf,
+        /// 发现丢失的原因
+        reason: String,
+    },
+    /// 处理器操作失败（不可重试，或重试耗尽）
+    ProcessorFailed {
+        /// 处理器名称
+        processor: &'static str,
+        /// 错误类别
+        kind: ErrorKind,
+        /// 已尝试次数
+        attempts: u32,
+        /// 错误描述
+        error: String,
+    },
+    /// 输出流的读取端已关闭（如管道另一端的进程已退出），之后写往该处的记录被丢弃并计入丢弃数
+    OutputClosed {
+        /// 处理器名称
+        processor: &'static str,
+    },
+    /// 处理器当前操作超过卡住阈值仍未完成（见 `LoggerBuilder::with_stall_threshold`）
+    ProcessorStalled {
+        /// 处理器名称
+        processor: &'static str,
+        /// 发现时操作已执行的时间
+        elapsed: Duration,
+    },
+    /// 曾被上报卡住的操作已完成
+    ProcessorRecovered {
+        /// 处理器名称
+        processor: &'static str,
+        /// 操作的总耗时
+        duration: Duration,
+    },
+    /// 连续多次连通性探测失败，处理器进入降级状态（见 `UdpConfig::probe_interval_ms`）
+    ProcessorDegraded {
+        /// 处理器名称
+        processor: &'static str,
+        /// 连续失败的探测次数
+        failed_probes: u32,
+    },
+    /// 降级的处理器探测成功，恢复健康状态
+    ProcessorHealthy {
+        /// 处理器名称
+        processor: &'static str,
+        /// 降级状态持续的时间
+        degraded_for: Duration,
+    },
+    /// 轮转出的日志文件已压缩（原文件已删除）
+    LogFileCompressed {
+        /// 压缩文件路径
+        path: std::path::PathBuf,
+    },
+    /// 压缩任务数达到 `FileConfig::max_pending_compressions`，轮转出的文件未压缩，保留为 `.log`
+    CompressionSkipped {
+        /// 未压缩的文件路径
+        path: std::path::PathBuf,
+        /// 当时排队和正在执行的压缩任务数
+        pending: usize,
+    },
+    /// 超过 `FileConfig::max_archive_age` 的压缩归档已删除
+    ArchivesExpired {
+        /// 日志目录
+        dir: std::path::PathBuf,
+        /// 本次删除的归档数
+        count: usize,
+    },
+    /// `FileConfig::post_rotate_command` 执行失败（无法启动、超时或退出状态不为 0）
+    PostRotateCommandFailed {
+        /// 传给命令的文件路径
+        path: std::path::PathBuf,
+        /// 退出码（无法启动、超时或被信号终止时为 None）
+        code: Option<i32>,
+        /// 失败原因，退出状态不为 0 时附带标准错误输出的末尾部分
+        error: String,
+    },
+    /// 记录速率超过 `BreakerConfig::max_records_per_sec`，熔断开始
+    BreakerOpened {
+        /// 熔断时当前窗口内的记录数
+        rate: u64,
+        /// 每秒记录数上限
+        limit: u64,
+    },
+    /// 速率持续低于恢复阈值，熔断结束
+    BreakerClosed {
+        /// 熔断期间丢弃的记录数
+        dropped: u64,
+        /// 熔断持续的时间
+        duration: Duration,
+    },
+    /// 已提交但尚未处理完的数据超过 `MemoryBudget::max_bytes`，开始按 `action` 处理新记录
+    MemoryBudgetExceeded {
+        /// 超出时已提交但尚未处理完的字节数
+        in_flight: u64,
+        /// 预算（字节）
+        budget: u64,
+        /// 超出预算时的处理方式
+        action: MemoryAction,
+    },
+    /// 已提交但尚未处理完的数据回到预算以内
+    MemoryBudgetRecovered {
+        /// 超出期间丢弃的记录数
+        dropped: u64,
+        /// 超出预算持续的时间
+        duration: Duration,
+    },
+    /// UDP 离线缓存超出 `SpoolConfig::max_bytes`，最旧的分段已删除
+    SpoolOverflow {
+        /// 处理器名称
+        processor: &'static str,
+        /// 删除的分段数
+        segments: usize,
+        /// 删除的分段中的记录数
+        dropped: usize,
+    },
+    /// UDP 离线缓存的分段末尾损坏（如写入时进程崩溃），损坏处之后的数据被跳过
+    SpoolCorrupted {
+        /// 分段文件路径
+        path: std::path::PathBuf,
+        /// 跳过的字节数
+        discarded_bytes: u64,
+    },
+    /// 记录富化函数（`LoggerBuilder::with_enricher`）panic，记录按未富化的原样输出
+    EnricherPanicked {
+        /// 记录的目标
+        target: String,
+        /// panic 信息
+        message: String,
+    },
+    /// 记录被过滤（只在启用 `LoggerBuilder::with_filter_tracing` 时上报，每秒最多 [`FILTER_TRACE_LIMIT`] 个）
+    RecordFiltered {
+        /// 记录的级别
+        level: Level,
+        /// 记录的目标
+        target: String,
+        /// 过滤记录的环节
+        gate: FilterGate,
+        /// 上一个事件之后因限流未上报的事件数
+        suppressed: u64,
+    },
+}
+
+/// 过滤记录的环节及其配置值
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum FilterGate {
+    /// 全局最大级别（`core::max_level()`），日志宏在查询日志器之前检查
+    MaxLevel(LevelFilter),
+    /// 日志器自身的级别
+    LoggerLevel(LevelFilter),
+    /// 采样丢弃
+    Sampling {
+        /// 匹配的目标前缀规则（没有匹配时使用默认比例）
+        rule: Option<String>,
+        /// 生效的采样比例
+        ratio: f32,
+    },
+    /// 全局熔断（见 `LoggerBuilder::with_max_record_rate`）
+    CircuitBreaker {
+        /// 每秒记录数上限
+        limit: u64,
+    },
+}
+
+impl fmt::Display for FilterGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterGate::MaxLevel(level) => write!(f, "全局最大级别 {:?}", level),
+            FilterGate::LoggerLevel(level) => write!(f, "日志器级别 {:?}", level),
+            FilterGate::Sampling { rule: Some(rule), ratio } => write!(f, "采样规则 {}={}", rule, ratio),
+            FilterGate::Sampling { rule: None, ratio } => write!(f, "默认采样比例 {}", ratio),
+            FilterGate::CircuitBreaker { limit } => write!(f, "熔断（上限 {} 条/秒）", limit),
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticEvent::SendFailed { processor, attempts, dropped, error } => {
+                write!(f, "[{}] 发送失败，重试{}次后丢弃{}条记录: {}", processor, attempts, dropped, error)
+            }
+            DiagnosticEvent::LogFileRecreated { path, reason } => {
+                write!(f, "[file_processor] 日志文件丢失（{}），已重新创建: {}", reason, path.display())
+            }
+            DiagnosticEvent::ProcessorFailed { processor, kind, attempts, error } => {
+                write!(f, "[{}] 处理失败（{:?}，尝试{}次）: {}", processor, kind, attempts, error)
+            }
+            DiagnosticEvent::OutputClosed { processor } => {
+                write!(f, "[{}] 输出流的读取端已关闭，之后的输出将被丢弃", processor)
+            }
+            DiagnosticEvent::ProcessorStalled { processor, elapsed } => {
+                write!(f, "[{}] 操作已执行 {:?} 仍未完成，可能卡住", processor, elapsed)
+            }
+            DiagnosticEvent::ProcessorRecovered { processor, duration } => {
+                write!(f, "[{}] 卡住的操作已完成，耗时 {:?}", processor, duration)
+            }
+            DiagnosticEvent::ProcessorDegraded { processor, failed_probes } => {
+                write!(f, "[{}] 连续{}次连通性探测失败，处理器已降级", processor, failed_probes)
+            }
+            DiagnosticEvent::ProcessorHealthy { processor, degraded_for } => {
+                write!(f, "[{}] 连通性探测成功，降级状态持续 {:?} 后恢复", processor, degraded_for)
+            }
+            DiagnosticEvent::LogFileCompressed { path } => {
+                write!(f, "[file_processor] 日志文件已压缩: {}", path.display())
+            }
+            DiagnosticEvent::CompressionSkipped { path, pending } => {
+                write!(f, "[file_processor] 已有{}个压缩任务，跳过压缩: {}", pending, path.display())
+            }
+            DiagnosticEvent::ArchivesExpired { dir, count } => {
+                write!(f, "[file_processor] 已删除{}个超过保留期限的归档: {}", count, dir.display())
+            }
+            DiagnosticEvent::PostRotateCommandFailed { path, error, .. } => {
+                write!(f, "[file_processor] 轮转后命令执行失败（{}）: {}", path.display(), error)
+            }
+            DiagnosticEvent::BreakerOpened { rate, limit } => {
+                write!(f, "[breaker] 记录速率达到 {} 条/秒（上限 {}），开始丢弃 Error 以外的记录", rate, limit)
+            }
+            DiagnosticEvent::BreakerClosed { dropped, duration } => {
+                write!(f, "[breaker] 记录速率已恢复，熔断持续 {:?}，共丢弃 {} 条记录", duration, dropped)
+            }
+            DiagnosticEvent::MemoryBudgetExceeded { in_flight, budget, action } => {
+                write!(f, "[memory] 待处理数据 {} 字节超过预算 {} 字节，按 {:?} 处理新记录", in_flight, budget, action)
+            }
+            DiagnosticEvent::MemoryBudgetRecovered { dropped, duration } => {
+                write!(f, "[memory] 待处理数据已回到预算以内，超出持续 {:?}，共丢弃 {} 条记录", duration, dropped)
+            }
+            DiagnosticEvent::SpoolOverflow { processor, segments, dropped } => {
+                write!(f, "[{}] 离线缓存超出上限，删除最旧的{}个分段（{}条记录）", processor, segments, dropped)
+            }
+            DiagnosticEvent::SpoolCorrupted { path, discarded_bytes } => {
+                write!(f, "[udp_processor] 离线缓存分段末尾损坏，跳过{}字节: {}", discarded_bytes, path.display())
+            }
+            DiagnosticEvent::EnricherPanicked { target, message } => {
+                write!(f, "[enricher] 富化目标为 {} 的记录时 panic，记录按原样输出: {}", target, message)
+            }
+            DiagnosticEvent::RecordFiltered { level, target, gate, suppressed } => {
+                write!(f, "[filter] {:?} 级别的记录（目标 {}）被{}过滤", level, target, gate)?;
+                if *suppressed > 0 {
+                    write!(f, "（另有{}个事件因限流未上报）", suppressed)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 错误回调
+pub type ErrorCallback = Arc<dyn Fn(&DiagnosticEvent) + Send + Sync>;
+
+/// 诊断上报句柄（可廉价克隆，所有克隆共享回调和统计）
+#[derive(Clone)]
+pub struct Diagnostics {
+    stats: Arc<LoggerStats>,
+    callback: Arc<RwLock<Option<ErrorCallback>>>,
+}
+
+impl Diagnostics {
+    /// 创建新的诊断句柄
+    pub fn new() -> Self {
+        Self {
+            stats: Arc::new(LoggerStats::new()),
+            callback: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 共享的运行统计
+    pub fn stats(&self) -> &Arc<LoggerStats> {
+        &self.stats
+    }
+
+    /// 设置错误回调（对已添加的处理器同样生效）
+    pub fn set_callback(&self, callback: Option<ErrorCallback>) {
+        *self.callback.write() = callback;
+    }
+
+    /// 上报事件：按事件类型累加计数器，并调用错误回调
+    pub fn emit(&self, event: DiagnosticEvent) {
+        match &event {
+            DiagnosticEvent::SendFailed { dropped, .. } => self.stats.add_dropped(*dropped as u64),
+            DiagnosticEvent::LogFileRecreated { .. }
+            | DiagnosticEvent::OutputClosed { .. }
+            | DiagnosticEvent::LogFileCompressed { .. }
+            | DiagnosticEvent::PostRotateCommandFailed { .. }
+            | DiagnosticEvent::EnricherPanicked { .. }
+            | DiagnosticEvent::SpoolCorrupted { .. }
+            | DiagnosticEvent::RecordFiltered { .. } => {}
+            DiagnosticEvent::SpoolOverflow { dropped, .. } => self.stats.add_spool_dropped(*dropped as u64),
+            DiagnosticEvent::ProcessorFailed { .. } => self.stats.add_processor_error(),
+            DiagnosticEvent::ProcessorStalled { .. } => self.stats.add_stalled(),
+            DiagnosticEvent::ProcessorRecovered { .. } => self.stats.remove_stalled(),
+            DiagnosticEvent::ProcessorDegraded { .. } => self.stats.add_degraded(),
+            DiagnosticEvent::ProcessorHealthy { .. } => self.stats.remove_degraded(),
+            DiagnosticEvent::CompressionSkipped { .. } => self.stats.add_compression_skipped(),
+            DiagnosticEvent::ArchivesExpired { count, .. } => self.stats.add_archives_expired(*count as u64),
+            DiagnosticEvent::BreakerOpened { .. } => self.stats.set_breaker_open(true),
+            DiagnosticEvent::BreakerClosed { .. } => self.stats.set_breaker_open(false),
+            DiagnosticEvent::MemoryBudgetExceeded { .. } => self.stats.set_memory_exceeded(true),
+            DiagnosticEvent::MemoryBudgetRecovered { .. } => self.stats.set_memory_exceeded(false),
+        }
+
+        let callback = self.callback.read().clone();
+        if let Some(callback) = callback {
+            // 工作线程中触发的事件（如格式化函数 panic）按用户回调处理，应用线程中的照常记录
+            let _scope = crate::producer_consumer::on_worker_thread().then(crate::core::CallbackScope::enter);
+            callback(&event);
+        }
+    }
+}
+
+/// 过滤追踪每秒最多上报的事件数
+pub const FILTER_TRACE_LIMIT: u32 = 20;
+
+thread_local! {
+    /// 当前线程是否正在上报过滤事件（回调中被过滤的日志不再上报，避免反馈循环）
+    static IN_FILTER_TRACE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 过滤追踪：把被过滤的记录作为 `RecordFiltered` 事件上报，按秒限流
+#[derive(Debug)]
+pub(crate) struct FilterTracer {
+    diagnostics: Diagnostics,
+    started: Instant,
+    /// 当前限流窗口（自创建起的秒数）
+    window: AtomicU64,
+    /// 当前窗口内已上报的事件数
+    emitted: AtomicU32,
+    /// 因限流未上报的事件数
+    suppressed: AtomicU64,
+}
+
+impl FilterTracer {
+    pub(crate) fn new(diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            started: Instant::now(),
+            window: AtomicU64::new(0),
+            emitted: AtomicU32::new(0),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// 上报一条被过滤的记录
+    pub(crate) fn trace(&self, level: Level, target: &str, gate: FilterGate) {
+        if IN_FILTER_TRACE.with(Cell::get) {
+            return;
+        }
+        let window = self.started.elapsed().as_secs();
+        if self.window.swap(window, Ordering::Relaxed) != window {
+            self.emitted.store(0, Ordering::Relaxed);
+        }
+        if self.emitted.fetch_add(1, Ordering::Relaxed) >= FILTER_TRACE_LIMIT {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        IN_FILTER_TRACE.with(|flag| flag.set(true));
+        self.diagnostics.emit(DiagnosticEvent::RecordFiltered {
+            level,
+            target: target.to_string(),
+            gate,
+            suppressed: self.suppressed.swap(0, Ordering::Relaxed),
+        });
+        IN_FILTER_TRACE.with(|flag| flag.set(false));
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Diagnostics")
+            .field("stats", &self.stats)
+            .field("has_callback", &self.callback.read().is_some())
+            .finish()
+    }
+}
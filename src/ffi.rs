@@ -0,0 +1,330 @@
+//! C FFI 接口 - 供 C/C++ 代码写入同一个全局日志器（需要启用 `ffi` 特性）
+//!
+//! 头文件见 `include/rat_logger.h`（可用 `cbindgen --config cbindgen.toml` 重新生成）。
+//! 所有函数返回错误码而不是 panic；传入的字符串在函数返回前复制完毕，调用方随后即可释放。
+//! 配置 JSON 必须是合法的 UTF-8；目标、消息和结构化字段中的非法 UTF-8 按替换字符（U+FFFD）处理。
+
+use std::ffi::{c_char, c_int, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{FileConfig, FormatConfig, Level, LevelFilter, Metadata, NetworkConfig, Record};
+use crate::core::{self, lock_global_logger, Logger, LoggerBuilder};
+use crate::handler::term::TermConfig;
+use crate::producer_consumer::BatchConfig;
+
+/// 成功
+pub const RAT_LOGGER_OK: c_int = 0;
+/// 必需的指针参数为空
+pub const RAT_LOGGER_ERR_NULL: c_int = -1;
+/// 配置 JSON 不是合法的 UTF-8
+pub const RAT_LOGGER_ERR_UTF8: c_int = -2;
+/// 配置 JSON 无法解析或配置无效
+pub const RAT_LOGGER_ERR_CONFIG: c_int = -3;
+/// 全局日志器已经初始化
+pub const RAT_LOGGER_ERR_ALREADY_INITIALIZED: c_int = -4;
+/// 全局日志器尚未初始化（或已关闭）
+pub const RAT_LOGGER_ERR_NOT_INITIALIZED: c_int = -5;
+/// 刷新未在超时时间内完成
+pub const RAT_LOGGER_ERR_TIMEOUT: c_int = -6;
+/// 日志级别无效
+pub const RAT_LOGGER_ERR_LEVEL: c_int = -7;
+/// 内部错误（捕获到 panic）
+pub const RAT_LOGGER_ERR_INTERNAL: c_int = -8;
+
+/// 日志级别：错误
+pub const RAT_LOGGER_LEVEL_ERROR: c_int = 1;
+/// 日志级别：警告
+pub const RAT_LOGGER_LEVEL_WARN: c_int = 2;
+/// 日志级别：信息
+pub const RAT_LOGGER_LEVEL_INFO: c_int = 3;
+/// 日志级别：调试
+pub const RAT_LOGGER_LEVEL_DEBUG: c_int = 4;
+/// 日志级别：跟踪
+pub const RAT_LOGGER_LEVEL_TRACE: c_int = 5;
+
+/// 目标为空指针时使用的目标
+const DEFAULT_TARGET: &str = "ffi";
+
+/// 初始化配置（JSON）
+///
+/// ```json
+/// {
+///   "level": "info",
+///   "async": false,
+///   "app_id": "billing",
+///   "terminal": { "enable_color": false },
+///   "file": { "log_dir": "/var/log/billing", "max_file_size": 10485760 },
+///   "udp": { "server_addr": "127.0.0.1", "server_port": 5140, "auth_token": "token" }
+/// }
+/// ```
+///
+/// `file`、`udp` 以及其中的 `format` 只需列出与默认值不同的字段。
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FfiConfig {
+    #[serde(default)]
+    level: Option<String>,
+    #[serde(default, rename = "async")]
+    async_mode: bool,
+    #[serde(default)]
+    app_id: Option<String>,
+    #[serde(default)]
+    terminal: Option<FfiTermConfig>,
+    #[serde(default)]
+    file: Option<Value>,
+    #[serde(default)]
+    udp: Option<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FfiTermConfig {
+    #[serde(default = "default_enable_color")]
+    enable_color: bool,
+    #[serde(default)]
+    format: Option<Value>,
+}
+
+fn default_enable_color() -> bool {
+    TermConfig::default().enable_color
+}
+
+/// 用 JSON 对象中的字段覆盖默认配置
+fn with_defaults<T: Serialize + DeserializeOwned>(defaults: T, overrides: Value) -> Result<T, String> {
+    let Value::Object(overrides) = overrides else {
+        return Err("配置错误: 应为 JSON 对象".to_string());
+    };
+    let mut merged = serde_json::to_value(defaults).map_err(|e| e.to_string())?;
+    if let Value::Object(fields) = &mut merged {
+        fields.extend(overrides);
+    }
+    serde_json::from_value(merged).map_err(|e| format!("配置错误: {}", e))
+}
+
+/// 解析可选的格式配置（只需列出与默认值不同的字段）
+fn format_with_defaults(format: Option<Value>) -> Result<Option<FormatConfig>, String> {
+    format.map(|format| with_defaults(FormatConfig::default(), format)).transpose()
+}
+
+fn parse_level_filter(level: &str) -> Result<LevelFilter, String> {
+    match level.to_lowercase().as_str() {
+        "off" => Ok(LevelFilter::Off),
+        "error" => Ok(LevelFilter::Error),
+        "warn" | "warning" => Ok(LevelFilter::Warn),
+        "info" => Ok(LevelFilter::Info),
+        "debug" => Ok(LevelFilter::Debug),
+        "trace" => Ok(LevelFilter::Trace),
+        _ => Err(format!("配置错误: 无效的日志级别 {:?}", level)),
+    }
+}
+
+fn level_from_c(level: c_int) -> Option<Level> {
+    match level {
+        RAT_LOGGER_LEVEL_ERROR => Some(Level::Error),
+        RAT_LOGGER_LEVEL_WARN => Some(Level::Warn),
+        RAT_LOGGER_LEVEL_INFO => Some(Level::Info),
+        RAT_LOGGER_LEVEL_DEBUG => Some(Level::Debug),
+        RAT_LOGGER_LEVEL_TRACE => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+/// 按 JSON 配置创建构建器
+fn builder_from_json(json: &str) -> Result<LoggerBuilder, String> {
+    let config: FfiConfig = serde_json::from_str(json).map_err(|e| format!("配置错误: {}", e))?;
+
+    let level = config.level.as_deref().map(parse_level_filter).transpose()?.unwrap_or(LevelFilter::Info);
+    let mut builder = LoggerBuilder::new()
+        .with_level(level)
+        .with_async_mode(config.async_mode);
+    if config.async_mode {
+        builder = builder.with_batch_config(BatchConfig::default());
+    }
+    if let Some(app_id) = config.app_id {
+        builder = builder.with_app_id(app_id);
+    }
+    if let Some(terminal) = config.terminal {
+        builder = builder.add_terminal_with_config(TermConfig {
+            enable_color: terminal.enable_color,
+            format: format_with_defaults(terminal.format)?,
+            ..Default::default()
+        });
+    }
+    if let Some(mut file) = config.file {
+        // 格式配置同样只需列出与默认值不同的字段
+        let format = file.as_object_mut().and_then(|fields| fields.remove("format"));
+        let mut file_config = with_defaults(FileConfig::default(), file)?;
+        file_config.format = format_with_defaults(format)?;
+        file_config.validate().map_err(|e| format!("FileConfig 验证失败: {}", e))?;
+        builder = builder.add_file(file_config);
+    }
+    if let Some(udp) = config.udp {
+        builder = builder.add_udp(with_defaults(NetworkConfig::default(), udp)?);
+    }
+    Ok(builder)
+}
+
+/// 执行函数体，panic 转换为 `RAT_LOGGER_ERR_INTERNAL`，不跨越 FFI 边界展开
+fn guarded(body: impl FnOnce() -> c_int) -> c_int {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or(RAT_LOGGER_ERR_INTERNAL)
+}
+
+/// 复制可能含有非法 UTF-8 的 C 字符串
+///
+/// # Safety
+///
+/// `ptr` 必须指向以 NUL 结尾的字符串。
+unsafe fn lossy_string(ptr: *const c_char) -> String {
+    unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+}
+
+/// 当前的全局日志器
+fn global_logger() -> Option<Arc<dyn Logger>> {
+    lock_global_logger().clone()
+}
+
+/// 按 JSON 配置初始化全局日志器
+///
+/// 全局日志器已存在时返回 `RAT_LOGGER_ERR_ALREADY_INITIALIZED`，不会替换现有日志器。
+///
+/// # Safety
+///
+/// `config_json` 必须为空指针或指向以 NUL 结尾的字符串。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rat_logger_init_from_json(config_json: *const c_char) -> c_int {
+    if config_json.is_null() {
+        return RAT_LOGGER_ERR_NULL;
+    }
+    let Ok(json) = unsafe { CStr::from_ptr(config_json) }.to_str() else {
+        return RAT_LOGGER_ERR_UTF8;
+    };
+    let json = json.to_string();
+
+    guarded(move || {
+        if core::is_initialized() {
+            return RAT_LOGGER_ERR_ALREADY_INITIALIZED;
+        }
+        let logger = match builder_from_json(&json).and_then(LoggerBuilder::try_build) {
+            Ok(logger) => logger,
+            Err(e) => {
+                eprintln!("[ffi] 初始化失败: {}", e);
+                return RAT_LOGGER_ERR_CONFIG;
+            }
+        };
+        if let Err(e) = logger.wait_for_workers_ready(5000) {
+            eprintln!("[ffi] 初始化失败: {}", e);
+            return RAT_LOGGER_ERR_CONFIG;
+        }
+        let level = logger.level();
+        if core::set_logger(Arc::new(logger)).is_err() {
+            return RAT_LOGGER_ERR_ALREADY_INITIALIZED;
+        }
+        core::set_max_level(level);
+        RAT_LOGGER_OK
+    })
+}
+
+/// 写入一条日志
+///
+/// `target` 为空指针时使用 `"ffi"`。被级别过滤的记录同样返回 `RAT_LOGGER_OK`。
+///
+/// # Safety
+///
+/// `target` 和 `message` 必须为空指针或指向以 NUL 结尾的字符串。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rat_logger_log(level: c_int, target: *const c_char, message: *const c_char) -> c_int {
+    unsafe { rat_logger_log_kv(level, target, message, std::ptr::null(), std::ptr::null(), 0) }
+}
+
+/// 写入一条带结构化字段的日志，`keys[i]` 与 `values[i]` 组成第 i 个字段
+///
+/// `target` 为空指针时使用 `"ffi"`；`count` 为 0 时 `keys` 和 `values` 可以为空指针。
+///
+/// # Safety
+///
+/// `target` 和 `message` 必须为空指针或指向以 NUL 结尾的字符串；`count` 不为 0 时，
+/// `keys` 和 `values` 必须指向至少 `count` 个元素的数组，每个元素指向以 NUL 结尾的字符串。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rat_logger_log_kv(
+    level: c_int,
+    target: *const c_char,
+    message: *const c_char,
+    keys: *const *const c_char,
+    values: *const *const c_char,
+    count: usize,
+) -> c_int {
+    let Some(level) = level_from_c(level) else {
+        return RAT_LOGGER_ERR_LEVEL;
+    };
+    if message.is_null() || (count > 0 && (keys.is_null() || values.is_null())) {
+        return RAT_LOGGER_ERR_NULL;
+    }
+
+    // 快速路径：被过滤的日志不复制字符串
+    if !level.should_log_at(core::max_level()) {
+        return if core::is_initialized() { RAT_LOGGER_OK } else { RAT_LOGGER_ERR_NOT_INITIALIZED };
+    }
+
+    // 返回前复制所有字符串
+    let mut fields = Vec::with_capacity(count);
+    for i in 0..count {
+        let (key, value) = unsafe { (*keys.add(i), *values.add(i)) };
+        if key.is_null() || value.is_null() {
+            return RAT_LOGGER_ERR_NULL;
+        }
+        fields.push(unsafe { (lossy_string(key), lossy_string(value)) });
+    }
+    let target = if target.is_null() { DEFAULT_TARGET.to_string() } else { unsafe { lossy_string(target) } };
+    let message = unsafe { lossy_string(message) };
+
+    guarded(move || {
+        let Some(logger) = global_logger() else {
+            return RAT_LOGGER_ERR_NOT_INITIALIZED;
+        };
+        logger.log(&Record {
+            metadata: Arc::new(Metadata {
+                level,
+                target,
+                auth_token: None,
+                app_id: logger.app_id().map(str::to_string),
+            }),
+            args: message.into(),
+            fields,
+            ..Default::default()
+        });
+        RAT_LOGGER_OK
+    })
+}
+
+/// 刷新全局日志器，等待此前写入的日志处理完毕（最多等待 `timeout_ms` 毫秒）
+#[unsafe(no_mangle)]
+pub extern "C" fn rat_logger_flush(timeout_ms: u64) -> c_int {
+    guarded(|| {
+        let Some(logger) = global_logger() else {
+            return RAT_LOGGER_ERR_NOT_INITIALIZED;
+        };
+        match logger.flush_sync(timeout_ms) {
+            Ok(()) => RAT_LOGGER_OK,
+            Err(_) => RAT_LOGGER_ERR_TIMEOUT,
+        }
+    })
+}
+
+/// 刷新并关闭全局日志器，之后可以重新初始化
+#[unsafe(no_mangle)]
+pub extern "C" fn rat_logger_shutdown() -> c_int {
+    guarded(|| {
+        let Some(logger) = lock_global_logger().take() else {
+            return RAT_LOGGER_ERR_NOT_INITIALIZED;
+        };
+        let _ = logger.flush_sync(5000);
+        // 丢弃最后一个引用时停止工作线程
+        drop(logger);
+        RAT_LOGGER_OK
+    })
+}
@@ -13,11 +13,15 @@
 //!     .with_max_level(rat_logger::LevelFilter::Debug)
 //!     .init();
 //!
+//! // env_logger 风格的输出格式，级别由 RUST_LOG 决定
+//! // rat_logger::fmt().env_style().init();
+//!
 //! error!("这是一条错误日志");
 //! info!("这是一条信息日志");
 //! ```
 
-use crate::{LevelFilter, LoggerBuilder};
+use crate::handler::term::TermConfig;
+use crate::{ColorConfig, FormatConfig, LevelFilter, LoggerBuilder};
 
 /// 快速初始化器 - 类似 tracing_subscriber::fmt()
 ///
@@ -35,12 +39,14 @@ use crate::{LevelFilter, LoggerBuilder};
 #[derive(Debug, Clone)]
 pub struct FmtInitializer {
     max_level: LevelFilter,
+    env_style: bool,
 }
 
 impl Default for FmtInitializer {
     fn default() -> Self {
         Self {
             max_level: LevelFilter::Info,  // 默认 Info 级别
+            env_style: false,
         }
     }
 }
@@ -65,6 +71,42 @@ impl FmtInitializer {
         self
     }
 
+    /// 使用 env_logger 风格的输出：`[2024-05-01T12:00:00Z INFO  my_crate] message`
+    ///
+    /// 同时从 `RUST_LOG` 读取日志级别；未设置或无法解析时使用 `with_max_level` 设置的级别。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// rat_logger::fmt().env_style().init();
+    /// ```
+    pub fn env_style(mut self) -> Self {
+        self.env_style = true;
+        self
+    }
+
+    /// 生效的日志级别
+    fn level(&self) -> LevelFilter {
+        if self.env_style {
+            crate::core::parse_log_level_from_env().unwrap_or(self.max_level)
+        } else {
+            self.max_level
+        }
+    }
+
+    /// 终端处理器配置
+    fn term_config(&self) -> TermConfig {
+        if self.env_style {
+            TermConfig {
+                format: Some(FormatConfig::env_logger_style()),
+                color: Some(ColorConfig::env_logger_style()),
+                ..Default::default()
+            }
+        } else {
+            TermConfig::default()
+        }
+    }
+
     /// 初始化全局日志器
     ///
     /// # 示例
@@ -74,8 +116,8 @@ impl FmtInitializer {
     /// ```
     pub fn init(self) -> Result<(), crate::core::SetLoggerError> {
         LoggerBuilder::new()
-            .add_terminal_with_config(crate::handler::term::TermConfig::default())
-            .with_level(self.max_level)
+            .add_terminal_with_config(self.term_config())
+            .with_level(self.level())
             .init()
     }
 }
@@ -114,4 +156,13 @@ mod tests {
 
         assert_eq!(initializer.max_level, LevelFilter::Debug);
     }
+
+    #[test]
+    fn test_fmt_initializer_env_style() {
+        assert_eq!(fmt().term_config(), TermConfig::default());
+
+        let config = fmt().env_style().term_config();
+        assert_eq!(config.format, Some(FormatConfig::env_logger_style()));
+        assert_eq!(config.color, Some(ColorConfig::env_logger_style()));
+    }
 }
@@ -2,14 +2,82 @@
 
 use std::any::Any;
 use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{bounded, Receiver, Sender};
 
 use crate::handler::{LogHandler, HandlerType};
 use crate::config::Record;
 
+/// 并行模式下每个处理器队列的容量（记录数），队列满时 `handle()` 阻塞等待
+const QUEUE_CAPACITY: usize = 1024;
+
+/// 并行模式下发送给处理器工作线程的命令
+enum Command {
+    /// 处理一条记录
+    Handle(Arc<Record>),
+    /// 处理完此前的记录后刷新处理器，完成后回复
+    Flush(Sender<()>),
+}
+
+/// 并行模式下一个处理器独占的工作线程
+struct HandlerWorker {
+    sender: Option<Sender<Command>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HandlerWorker {
+    fn spawn(handler: Arc<dyn LogHandler>) -> Self {
+        let (sender, receiver) = bounded(QUEUE_CAPACITY);
+        let thread = std::thread::Builder::new()
+            .name("rat-logger-composite".to_string())
+            .spawn(move || Self::run(handler, receiver))
+            .map_err(|e| eprintln!("创建组合处理器工作线程失败: {}", e))
+            .ok();
+        Self {
+            sender: Some(sender),
+            thread,
+        }
+    }
+
+    fn run(handler: Arc<dyn LogHandler>, receiver: Receiver<Command>) {
+        // 发送端全部关闭后退出
+        for command in receiver {
+            match command {
+                Command::Handle(record) => handler.handle(&record),
+                Command::Flush(ack) => {
+                    handler.flush();
+                    let _ = ack.send(());
+                }
+            }
+        }
+    }
+
+    /// 工作线程已退出（例如处理器 panic）时命令被丢弃
+    fn send(&self, command: Command) -> bool {
+        self.sender.as_ref().is_some_and(|sender| sender.send(command).is_ok())
+    }
+}
+
+impl Drop for HandlerWorker {
+    fn drop(&mut self) {
+        // 先关闭队列，工作线程处理完剩余记录后退出
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
 /// 组合多个日志处理器的实现
+///
+/// 并行模式下每个处理器由一个独占的工作线程驱动，`handle()` 只把记录放入各处理器的有界队列，
+/// 每个处理器按提交顺序处理记录；`flush()` 等待所有队列处理完毕并刷新处理器后返回。
 pub struct CompositeHandler {
     handlers: Vec<Arc<dyn LogHandler>>,
     parallel: bool,
+    /// 并行模式下与 `handlers` 一一对应的工作线程
+    workers: Vec<HandlerWorker>,
 }
 
 impl CompositeHandler {
@@ -18,17 +86,24 @@ impl CompositeHandler {
         Self {
             handlers: Vec::new(),
             parallel: false,
+            workers: Vec::new(),
         }
     }
 
     /// 启用并行处理
     pub fn with_parallel(mut self) -> Self {
-        self.parallel = true;
+        if !self.parallel {
+            self.parallel = true;
+            self.workers = self.handlers.iter().cloned().map(HandlerWorker::spawn).collect();
+        }
         self
     }
 
     /// 添加日志处理器
     pub fn add_handler(&mut self, handler: Arc<dyn LogHandler>) {
+        if self.parallel {
+            self.workers.push(HandlerWorker::spawn(handler.clone()));
+        }
         self.handlers.push(handler);
     }
 }
@@ -41,28 +116,12 @@ impl Default for CompositeHandler {
 
 impl LogHandler for CompositeHandler {
     fn handle(&self, record: &Record) {
-        if self.parallel && self.handlers.len() > 1 {
-            // 并行处理：为每个处理器创建独立的任务
-            let handlers: Vec<Arc<dyn LogHandler>> = self.handlers.iter().cloned().collect();
-            let record = record.clone();
-
-            // 使用tokio进行并行处理
-            tokio::spawn(async move {
-                let join_handles: Vec<_> = handlers
-                    .into_iter()
-                    .map(|handler| {
-                        let record = record.clone();
-                        tokio::spawn(async move {
-                            handler.handle(&record);
-                        })
-                    })
-                    .collect();
-
-                // 等待所有任务完成
-                for handle in join_handles {
-                    let _ = handle.await;
-                }
-            });
+        if self.parallel {
+            // 并行处理：记录只复制一次，由各处理器的工作线程共享
+            let record = Arc::new(record.clone());
+            for worker in &self.workers {
+                worker.send(Command::Handle(record.clone()));
+            }
         } else {
             // 串行处理
             for handler in &self.handlers {
@@ -72,8 +131,21 @@ impl LogHandler for CompositeHandler {
     }
 
     fn flush(&self) {
-        for handler in &self.handlers {
-            handler.flush();
+        if self.parallel {
+            // 先向所有队列发送刷新命令，再逐个等待完成，各处理器并行排空
+            let acks: Vec<Receiver<()>> = self.workers.iter()
+                .filter_map(|worker| {
+                    let (ack, done) = bounded(1);
+                    worker.send(Command::Flush(ack)).then_some(done)
+                })
+                .collect();
+            for done in acks {
+                let _ = done.recv();
+            }
+        } else {
+            for handler in &self.handlers {
+                handler.flush();
+            }
         }
     }
 
@@ -85,3 +157,66 @@ impl LogHandler for CompositeHandler {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 记录收到的消息和刷新次数的处理器
+    #[derive(Default)]
+    struct CountingHandler {
+        messages: Mutex<Vec<String>>,
+        flushes: Mutex<usize>,
+    }
+
+    impl LogHandler for CountingHandler {
+        fn handle(&self, record: &Record) {
+            self.messages.lock().unwrap().push(record.args.to_string());
+        }
+
+        fn flush(&self) {
+            *self.flushes.lock().unwrap() += 1;
+        }
+
+        fn handler_type(&self) -> HandlerType {
+            HandlerType::Terminal
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_parallel_delivers_all_records_in_order() {
+        let counters: Vec<Arc<CountingHandler>> = (0..3).map(|_| Arc::new(CountingHandler::default())).collect();
+        let mut composite = CompositeHandler::new().with_parallel();
+        for counter in &counters {
+            composite.add_handler(counter.clone());
+        }
+
+        for i in 0..10_000 {
+            composite.handle(&Record { args: i.to_string().into(), ..Default::default() });
+        }
+        composite.flush();
+
+        let expected: Vec<String> = (0..10_000).map(|i| i.to_string()).collect();
+        for counter in &counters {
+            assert_eq!(*counter.messages.lock().unwrap(), expected);
+            assert_eq!(*counter.flushes.lock().unwrap(), 1);
+        }
+    }
+
+    #[test]
+    fn test_parallel_enabled_after_adding_handlers() {
+        let counter = Arc::new(CountingHandler::default());
+        let mut composite = CompositeHandler::new();
+        composite.add_handler(counter.clone());
+        let composite = composite.with_parallel();
+
+        composite.handle(&Record { args: "late".into(), ..Default::default() });
+        composite.flush();
+        assert_eq!(*counter.messages.lock().unwrap(), ["late"]);
+    }
+}
@@ -0,0 +1,115 @@
+//! Windows 控制台输出 - 标准输出为控制台时将 UTF-8 输出转换为 UTF-16，通过 `WriteConsoleW` 写入
+//!
+//! 控制台按当前代码页解释写入的字节，代码页不是 UTF-8 时中文会显示为乱码；`WriteConsoleW` 不受代码页影响。
+//! 标准输出被重定向到文件或管道时仍写入原始 UTF-8 字节。
+
+#![cfg_attr(not(windows), allow(dead_code))]
+
+/// 单次 `WriteConsoleW` 写入的 UTF-16 单元数上限（过大的写入在旧版控制台上会失败）
+pub(crate) const MAX_CHUNK_UNITS: usize = 8192;
+
+/// 将 UTF-8 输出转换为 UTF-16，无效的字节序列替换为 U+FFFD
+pub(crate) fn to_utf16(data: &[u8]) -> Vec<u16> {
+    String::from_utf8_lossy(data).encode_utf16().collect()
+}
+
+/// 按不超过 `max_units` 个单元切分 UTF-16 数据，切分位置不会落在代理对中间
+pub(crate) fn chunks(mut units: &[u16], max_units: usize) -> impl Iterator<Item = &[u16]> {
+    std::iter::from_fn(move || {
+        if units.is_empty() {
+            return None;
+        }
+        let mut len = units.len().min(max_units);
+        if len < units.len() && len > 1 && (0xD800..0xDC00).contains(&units[len - 1]) {
+            len -= 1;
+        }
+        let (chunk, rest) = units.split_at(len);
+        units = rest;
+        Some(chunk)
+    })
+}
+
+/// 标准输出是否为控制台（未重定向）；是控制台且 `enable_color` 为 true 时开启虚拟终端处理，使 ANSI 颜色序列生效
+#[cfg(windows)]
+pub(crate) fn detect(enable_color: bool) -> bool {
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_OUTPUT_HANDLE,
+    };
+
+    // SAFETY: 只查询和设置标准输出句柄的模式，句柄无效时调用失败
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return false;
+        }
+        if enable_color && mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING == 0 {
+            // 旧版控制台不支持时保持原模式，颜色序列原样显示
+            SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING);
+        }
+        true
+    }
+}
+
+/// 将 UTF-8 输出写入控制台
+#[cfg(windows)]
+pub(crate) fn write(data: &[u8]) -> std::io::Result<()> {
+    use windows_sys::Win32::System::Console::{GetStdHandle, WriteConsoleW, STD_OUTPUT_HANDLE};
+
+    let units = to_utf16(data);
+    for mut chunk in chunks(&units, MAX_CHUNK_UNITS) {
+        while !chunk.is_empty() {
+            let mut written = 0u32;
+            // SAFETY: 缓冲区指针和长度来自同一个切片，`written` 为有效的输出参数
+            let ok = unsafe {
+                WriteConsoleW(
+                    GetStdHandle(STD_OUTPUT_HANDLE),
+                    chunk.as_ptr().cast(),
+                    chunk.len() as u32,
+                    &mut written,
+                    std::ptr::null(),
+                )
+            };
+            if ok == 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if written == 0 {
+                return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "控制台未写入任何字符"));
+            }
+            chunk = &chunk[(written as usize).min(chunk.len())..];
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_conversion_of_mixed_content() {
+        let text = "INFO 启动完成 ok 🚀 done\n";
+        let units = to_utf16(text.as_bytes());
+        assert_eq!(String::from_utf16(&units).unwrap(), text);
+        // ASCII 和中文各占一个单元，emoji 占一个代理对
+        assert_eq!(units.len(), text.chars().count() + 1);
+        assert_eq!(&units[..5], "INFO ".encode_utf16().collect::<Vec<_>>().as_slice());
+        assert_eq!(units[5], '启' as u16);
+
+        // 无效的字节序列替换为 U+FFFD
+        assert_eq!(to_utf16(b"a\xffb"), [b'a' as u16, 0xFFFD, b'b' as u16]);
+    }
+
+    #[test]
+    fn test_chunks_keep_surrogate_pairs() {
+        let units = to_utf16("ab🚀🚀c".as_bytes());
+        // 第 3 个单元是代理对的高位，切分点前移
+        let split: Vec<&[u16]> = chunks(&units, 3).collect();
+        assert_eq!(split.iter().map(|c| c.len()).collect::<Vec<_>>(), [2, 2, 3]);
+        let joined: Vec<u16> = split.concat();
+        assert_eq!(String::from_utf16(&joined).unwrap(), "ab🚀🚀c");
+
+        assert_eq!(chunks(&[], 3).count(), 0);
+        assert_eq!(chunks(&units, MAX_CHUNK_UNITS).count(), 1);
+    }
+}
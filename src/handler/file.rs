@@ -4,22 +4,368 @@ use std::io::{self, Write, BufWriter};
 use std::fs::{File, OpenOptions};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use parking_lot::Mutex;
-use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use parking_lot::{Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use crossbeam_channel::{Sender, Receiver, unbounded};
+use serde::{Deserialize, Serialize};
 use std::thread;
 
-use crate::producer_consumer::LogProcessor;
-use crate::config::{Record, FileConfig, FormatConfig, Level};
+use crate::producer_consumer::{ErrorKind, LogProcessor, ProcessorError};
+use crate::config::{Record, CompressionOverflow, FileConfig, FormatConfig, LineEnding, SanitizeMode, SharedDirPolicy};
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+use crate::handler::{decode_batch, decode_record, sanitize_record, ElapsedTracker, Formatter, FormatterHandle, LineAffixes};
+use crate::handler::post_rotate::PostRotateHook;
+
+/// 文件输出默认转义控制字符，保留原始内容的同时避免伪造日志行
+const DEFAULT_SANITIZE: SanitizeMode = SanitizeMode::Escape;
+
+/// 默认格式化函数对应的格式模板（用于计算文件头部中的格式哈希）
+const DEFAULT_TEMPLATE: &str = "{timestamp} [{level}] {target} {file}:{line} - {message}";
+
+/// 日志文件头部行前缀
+pub const HEADER_PREFIX: &str = "# rat_logger ";
+
+/// 日志目录中的建议锁文件名（见 `SharedDirPolicy`）
+pub const LOCK_FILE_NAME: &str = ".rat_logger.lock";
+
+/// 持有目录锁的处理器使用的日志文件名前缀
+const OWNER_PREFIX: &str = "app_";
+
+/// 未设置 `file_name_template` 时的日志文件名模板
+pub const DEFAULT_FILE_NAME_TEMPLATE: &str = "{prefix}{timestamp}";
+
+/// 日志文件名中 `{timestamp}` 的格式
+const FILE_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// `FILE_TIMESTAMP_FORMAT` 生成的时间戳长度
+const FILE_TIMESTAMP_LEN: usize = 15;
+
+/// 本进程中按 `SharedDirPolicy::Prefix` 使用独立前缀的处理器序号
+static INSTANCE_SEQ: AtomicU64 = AtomicU64::new(0);
 
 /// 全局压缩线程池
-lazy_static::lazy_static! {
-    static ref COMPRESSION_POOL: threadpool::ThreadPool = {
-        let num_threads = std::thread::available_parallelism()
-            .map(|n| n.get())
-            .unwrap_or(4);
-        threadpool::ThreadPool::new(num_threads.max(1))
-    };
+///
+/// 第一次压缩时才创建，线程数取所有存活文件处理器中最大的 `min_compress_threads`（不超过 CPU 核数）；
+/// 最后一个文件处理器释放后关闭，已排队的压缩任务仍会执行完。
+static COMPRESSION_POOL: Mutex<CompressionPool> = Mutex::new(CompressionPool { pool: None, requested: Vec::new() });
+
+/// 压缩线程名
+const COMPRESSION_THREAD_NAME: &str = "rat-compress";
+
+/// 清理时等待已提交的压缩任务结束的最长时间
+const CLEANUP_COMPRESSION_WAIT: Duration = Duration::from_secs(10);
+
+struct CompressionPool {
+    pool: Option<threadpool::ThreadPool>,
+    /// 各存活文件处理器请求的线程数（禁用压缩的处理器为 0）
+    requested: Vec<usize>,
+}
+
+impl CompressionPool {
+    /// 按当前请求应有的线程数
+    fn wanted_threads(&self) -> usize {
+        let cap = std::thread::available_parallelism().map_or(4, |n| n.get());
+        self.requested.iter().copied().max().unwrap_or(0).clamp(1, cap)
+    }
+}
+
+/// 文件处理器对压缩线程池的占用，释放时撤回请求的线程数
+struct CompressionLease {
+    threads: usize,
+}
+
+impl CompressionLease {
+    fn acquire(threads: usize) -> Self {
+        COMPRESSION_POOL.lock().requested.push(threads);
+        Self { threads }
+    }
+
+    /// 在压缩线程池中执行任务，线程池不存在时创建，线程数不足时扩充
+    fn execute(&self, job: impl FnOnce() + Send + 'static) {
+        let mut state = COMPRESSION_POOL.lock();
+        let threads = state.wanted_threads();
+        let pool = state.pool.get_or_insert_with(|| threadpool::ThreadPool::with_name(COMPRESSION_THREAD_NAME.to_string(), threads));
+        if pool.max_count() < threads {
+            pool.set_num_threads(threads);
+        }
+        pool.execute(job);
+    }
+}
+
+impl Drop for CompressionLease {
+    fn drop(&mut self) {
+        let mut state = COMPRESSION_POOL.lock();
+        if let Some(index) = state.requested.iter().position(|&threads| threads == self.threads) {
+            state.requested.swap_remove(index);
+        }
+        if state.requested.is_empty() {
+            // 丢弃线程池句柄后，线程执行完已排队的任务即退出
+            state.pool = None;
+        } else if state.pool.is_some() {
+            let threads = state.wanted_threads();
+            state.pool.as_mut().unwrap().set_num_threads(threads);
+        }
+    }
+}
+
+/// 压缩线程池当前的线程数（尚未发生压缩或所有文件处理器都已释放时为 0）
+pub fn compression_threads() -> usize {
+    COMPRESSION_POOL.lock().pool.as_ref().map_or(0, |pool| pool.max_count())
+}
+
+/// 等待已提交到压缩线程池的任务全部完成
+#[cfg(test)]
+fn join_compressions() {
+    let pool = COMPRESSION_POOL.lock().pool.clone();
+    if let Some(pool) = pool {
+        pool.join();
+    }
+}
+
+/// 以追加模式打开日志文件
+///
+/// Windows 上显式允许其他句柄读取、写入和删除/重命名，外部工具可以查看正在写入的日志，
+/// 外部轮转工具也可以重命名活动文件。
+fn open_log_file(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        const FILE_SHARE_READ: u32 = 0x0000_0001;
+        const FILE_SHARE_WRITE: u32 = 0x0000_0002;
+        const FILE_SHARE_DELETE: u32 = 0x0000_0004;
+        options.share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE);
+    }
+    options.open(path)
+}
+
+/// 按记录边界把数据切分为不超过 `limit` 字节的块，返回各块的结束位置
+///
+/// `boundaries` 为各条记录的结束位置（递增）；超过上限的单条记录单独成块，不会被拆开。
+fn record_chunks(boundaries: &[usize], limit: usize) -> Vec<usize> {
+    let mut chunks = Vec::new();
+    let (mut chunk_start, mut previous) = (0, 0);
+    for &end in boundaries {
+        if end - chunk_start > limit && previous > chunk_start {
+            chunks.push(previous);
+            chunk_start = previous;
+        }
+        previous = end;
+    }
+    if previous > chunk_start {
+        chunks.push(previous);
+    }
+    chunks
+}
+
+/// 打开的文件是否为空（新建的文件需要写入头部）
+fn is_empty_file(file: &File) -> bool {
+    file.metadata().is_ok_and(|metadata| metadata.len() == 0)
+}
+
+/// 文件是否因被其他进程打开而无法操作（Windows 共享冲突/锁冲突）
+fn is_sharing_violation(error: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    cfg!(windows) && matches!(error.raw_os_error(), Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION))
+}
+
+/// 删除失败后的重试间隔，不应重试时返回 None
+///
+/// 共享冲突通常是其他进程短暂打开文件（查看器、备份工具），快速多次重试；
+/// 权限错误（如杀毒软件扫描）恢复较慢，少量慢速重试。
+fn remove_retry_delay(error: &io::Error, attempt: u64) -> Option<Duration> {
+    if is_sharing_violation(error) {
+        (attempt < 10).then(|| Duration::from_millis(50 * (attempt + 1)))
+    } else if error.kind() == io::ErrorKind::PermissionDenied {
+        let delay = if cfg!(windows) { 200 } else { 100 };
+        (attempt < 5).then(|| Duration::from_millis(delay * (attempt + 1)))
+    } else {
+        None
+    }
+}
+
+/// 删除文件，遇到共享冲突或权限错误时按 `remove_retry_delay` 重试
+fn remove_file_with_retry(path: &Path) {
+    let mut attempt = 0;
+    loop {
+        match std::fs::remove_file(path) {
+            Ok(_) => return,
+            Err(e) => match remove_retry_delay(&e, attempt) {
+                Some(delay) => {
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => {
+                    eprintln!("[file] 删除原文件失败 {}: {}", path.display(), e);
+                    return;
+                }
+            },
+        }
+    }
+}
+
+/// 日志文件头部信息（启用 `write_header` 时写入每个日志文件的第一行）
+struct FileHeader {
+    /// 应用ID
+    app_id: String,
+    /// 格式模板哈希（自定义格式化函数为 `custom`）
+    format_hash: String,
+}
+
+impl FileHeader {
+    /// 生成头部行，`started` 为写入头部的时间
+    fn line(&self) -> String {
+        format!(
+            "{}version={} binary={} pid={} hostname={} started={} format={} app_id={}\n",
+            HEADER_PREFIX,
+            env!("CARGO_PKG_VERSION"),
+            crate::process_info::binary_name(),
+            crate::process_info::pid(),
+            crate::process_info::hostname().unwrap_or("unknown"),
+            chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, false),
+            self.format_hash,
+            self.app_id,
+        )
+    }
+}
+
+/// 格式模板的 FNV-1a 哈希（16 位十六进制）
+fn template_hash(template: &str) -> String {
+    let hash = template.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// 日志文件名模板的片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NameSegment {
+    /// 固定文本（包括已解析的 `{prefix}`、`{app_id}` 和 `{hostname}`）
+    Literal(String),
+    /// 创建文件的时间
+    Timestamp,
+    /// 文件序号
+    Seq,
+}
+
+/// 解析后的日志文件名模板
+///
+/// 除生成文件名外，还按模板推导出的模式识别本处理器的文件：`{timestamp}` 匹配 `[0-9]{8}_[0-9]{6}`，
+/// `{seq}` 匹配若干数字，文件名末尾允许同一秒内轮转追加的 `_1`、`_2`……
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileNameTemplate {
+    segments: Vec<NameSegment>,
+}
+
+impl FileNameTemplate {
+    /// 解析模板，`{prefix}`、`{app_id}` 和 `{hostname}` 替换为固定文本；包含无法解析的占位符时返回错误
+    fn parse(template: &str, prefix: &str, app_id: Option<&str>) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            literal.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or_else(|| format!("配置错误: 文件名模板中的 '{{' 没有对应的 '}}': {}", template))?;
+            match &rest[start + 1..end] {
+                "prefix" => literal.push_str(prefix),
+                "app_id" => literal.push_str(app_id.ok_or(
+                    "配置错误: 文件名模板包含 {app_id}，但未设置 FileConfig::app_id 或 LoggerBuilder::with_app_id",
+                )?),
+                "hostname" => literal.push_str(
+                    crate::process_info::hostname().ok_or("配置错误: 文件名模板包含 {hostname}，但无法获取主机名")?,
+                ),
+                name @ ("timestamp" | "seq") => {
+                    if !literal.is_empty() {
+                        segments.push(NameSegment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(if name == "seq" { NameSegment::Seq } else { NameSegment::Timestamp });
+                }
+                name => return Err(format!("配置错误: 文件名模板包含未知的占位符 {{{}}}", name)),
+            }
+            rest = &rest[end + 1..];
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(NameSegment::Literal(literal));
+        }
+
+        if !segments.iter().any(|segment| matches!(segment, NameSegment::Timestamp | NameSegment::Seq)) {
+            return Err(format!("配置错误: 文件名模板必须包含 {{timestamp}} 或 {{seq}}，否则轮转后的文件会重名: {}", template));
+        }
+        let has_separator = segments.iter().any(|segment| {
+            matches!(segment, NameSegment::Literal(text) if text.contains(['/', '\\']) || text == "..")
+        });
+        if has_separator {
+            return Err(format!("配置错误: 解析后的文件名不能包含路径分隔符: {}", template));
+        }
+        Ok(Self { segments })
+    }
+
+    fn has_seq(&self) -> bool {
+        self.segments.contains(&NameSegment::Seq)
+    }
+
+    /// 生成文件名（不含扩展名）
+    fn render(&self, timestamp: &str, seq: u64) -> String {
+        let mut name = String::new();
+        for segment in &self.segments {
+            match segment {
+                NameSegment::Literal(text) => name.push_str(text),
+                NameSegment::Timestamp => name.push_str(timestamp),
+                NameSegment::Seq => name.push_str(&seq.to_string()),
+            }
+        }
+        name
+    }
+
+    /// 文件名（不含 `.log` 或 `.log.lz4` 扩展名）是否由本模板生成
+    fn matches(&self, stem: &str) -> bool {
+        self.match_stem(stem).is_some()
+    }
+
+    /// 匹配文件名（不含扩展名），匹配时返回其中的 `{seq}`（模板不含 `{seq}` 时为 None）
+    fn match_stem(&self, stem: &str) -> Option<Option<u64>> {
+        fn digits(text: &str) -> usize {
+            text.bytes().take_while(u8::is_ascii_digit).count()
+        }
+        fn match_from(segments: &[NameSegment], text: &str) -> Option<Option<u64>> {
+            let Some((segment, rest)) = segments.split_first() else {
+                // 同一秒内轮转时追加的序号
+                let suffix = text.is_empty() || text.strip_prefix('_').is_some_and(|n| !n.is_empty() && digits(n) == n.len());
+                return suffix.then_some(None);
+            };
+            match segment {
+                NameSegment::Literal(literal) => match_from(rest, text.strip_prefix(literal.as_str())?),
+                NameSegment::Timestamp => {
+                    let is_timestamp = text.len() >= FILE_TIMESTAMP_LEN
+                        && text.as_bytes()[..FILE_TIMESTAMP_LEN]
+                            .iter()
+                            .enumerate()
+                            .all(|(i, byte)| if i == 8 { *byte == b'_' } else { byte.is_ascii_digit() });
+                    if !is_timestamp {
+                        return None;
+                    }
+                    match_from(rest, &text[FILE_TIMESTAMP_LEN..])
+                }
+                NameSegment::Seq => (1..=digits(text)).find_map(|len| {
+                    let seq = match_from(rest, &text[len..])?;
+                    Some(seq.or_else(|| text[..len].parse().ok()))
+                }),
+            }
+        }
+        match_from(&self.segments, stem)
+    }
+}
+
+/// 检查文件名模板能否解析（`{prefix}` 总能解析，按持有目录锁时的前缀检查）
+pub(crate) fn validate_file_name_template(template: &str, app_id: Option<&str>) -> Result<(), String> {
+    FileNameTemplate::parse(template, OWNER_PREFIX, app_id).map(|_| ())
 }
 
 /// 日志文件写入器
@@ -31,22 +377,83 @@ struct LogWriter {
     last_flush: Instant,
     flush_interval: Duration,
     aggressive_sync: bool,
+    /// 文件头部（未启用 `write_header` 时为 None）
+    header: Option<FileHeader>,
+    /// 当前文件是新建的空文件，尚未写入头部
+    header_pending: bool,
+    /// 按记录边界整块写入时每次写入调用的大小上限（未启用 `atomic_batches` 时为 None，数据经过写缓冲）
+    atomic_chunk: Option<usize>,
 }
 
 /// 日志轮转器
 struct LogRotator {
     base_path: PathBuf,
+    /// 压缩文件数量上限（0 表示禁用压缩，不按数量清理）
     max_files: usize,
+    /// 日志文件总大小上限（0 表示不限制）
+    max_total_size: u64,
+    /// 压缩归档的保留期限（None 表示不按期限清理）
+    max_archive_age: Option<Duration>,
+    /// 日志文件名模板，轮转和清理只涉及与其匹配的文件
+    file_name: FileNameTemplate,
+    /// 下一个文件的序号（`{seq}`）
+    next_seq: AtomicU64,
+}
+
+/// 文件处理器排队和正在执行的压缩任务数
+#[derive(Default)]
+struct CompressionSlots {
+    pending: Mutex<usize>,
+    released: Condvar,
+    /// 处理器已清理，仍在执行的任务完成后不再执行轮转后命令或上报事件
+    closed: AtomicBool,
+}
+
+impl CompressionSlots {
+    /// 占用一个任务空位（`limit` 为 0 表示不限制）；已满时按 `overflow` 等待，仍然已满时返回当前任务数
+    fn acquire(&self, limit: usize, overflow: CompressionOverflow) -> Result<(), usize> {
+        let mut pending = self.pending.lock();
+        if limit > 0 && *pending >= limit {
+            if let CompressionOverflow::WaitMs(ms) = overflow {
+                let deadline = Instant::now() + Duration::from_millis(ms);
+                while *pending >= limit && !self.released.wait_until(&mut pending, deadline).timed_out() {}
+            }
+            if *pending >= limit {
+                return Err(*pending);
+            }
+        }
+        *pending += 1;
+        Ok(())
+    }
+
+    /// 压缩任务结束后释放空位
+    fn release(&self) {
+        *self.pending.lock() -= 1;
+        self.released.notify_all();
+    }
+
+    /// 等待所有任务结束（最多到 `deadline`），之后结束的任务不再执行回调，返回是否全部结束
+    fn close(&self, deadline: Instant) -> bool {
+        let mut pending = self.pending.lock();
+        while *pending > 0 && !self.released.wait_until(&mut pending, deadline).timed_out() {}
+        self.closed.store(true, Ordering::SeqCst);
+        *pending == 0
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
 }
 
 /// 文件处理器配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileProcessorConfig {
     /// 文件配置
     pub file_config: FileConfig,
     /// 批量大小
     pub batch_size: usize,
-    /// 刷新间隔（毫秒）
+    /// 刷新间隔（毫秒，配置文件中也可写作 `"100ms"`）
+    #[serde(with = "crate::config::units::millis")]
     pub flush_interval_ms: u64,
 }
 
@@ -60,56 +467,286 @@ impl Default for FileProcessorConfig {
     }
 }
 
+/// 创建文件处理器失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FileProcessorError {
+    /// 配置无效
+    InvalidConfig(String),
+    /// 日志目录已被另一个文件处理器使用（`SharedDirPolicy::Error`）
+    DirInUse(PathBuf),
+}
+
+impl std::fmt::Display for FileProcessorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileProcessorError::InvalidConfig(e) => write!(f, "FileConfig 验证失败: {}", e),
+            FileProcessorError::DirInUse(dir) => write!(
+                f,
+                "日志目录已被另一个文件处理器使用: {}（使用不同的 log_dir，或设置 shared_dir_policy 为 Prefix）",
+                dir.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FileProcessorError {}
+
+/// 对日志目录加建议锁，返回锁文件（处理器存活期间持有）和日志文件名前缀
+///
+/// 无法创建锁文件或文件系统不支持加锁时不做检查，按持有锁处理。
+fn lock_log_dir(config: &FileConfig) -> Result<(Option<File>, String), FileProcessorError> {
+    let _ = std::fs::create_dir_all(&config.log_dir);
+    let lock_path = config.log_dir.join(LOCK_FILE_NAME);
+    let lock_file = match OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("[file] 无法创建目录锁文件 {}，不检查日志目录是否共享: {}", lock_path.display(), e);
+            return Ok((None, OWNER_PREFIX.to_string()));
+        }
+    };
+    match lock_file.try_lock() {
+        Ok(()) => Ok((Some(lock_file), OWNER_PREFIX.to_string())),
+        Err(std::fs::TryLockError::WouldBlock) => match config.shared_dir_policy {
+            SharedDirPolicy::Error => Err(FileProcessorError::DirInUse(config.log_dir.clone())),
+            SharedDirPolicy::Prefix => {
+                let instance = INSTANCE_SEQ.fetch_add(1, Ordering::Relaxed) + 1;
+                Ok((None, format!("app-{}-{}_", crate::process_info::pid(), instance)))
+            }
+        },
+        Err(std::fs::TryLockError::Error(e)) => {
+            eprintln!("[file] 无法锁定目录锁文件 {}，不检查日志目录是否共享: {}", lock_path.display(), e);
+            Ok((None, OWNER_PREFIX.to_string()))
+        }
+    }
+}
+
 /// 文件日志处理器 - 实现LogProcessor trait
 pub struct FileProcessor {
     file_config: FileConfig,
     writer: Arc<Mutex<LogWriter>>,
     rotator: Arc<LogRotator>,
-    formatter: Box<dyn Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync>,
+    formatter: FormatterHandle,
+    diagnostics: Diagnostics,
+    /// 上次检查当前日志文件路径的时间
+    last_path_check: Instant,
+    /// 排队和正在执行的压缩任务（任务结束时释放，处理器销毁后仍可能有任务在执行）
+    compressions: Arc<CompressionSlots>,
+    /// 对全局压缩线程池的占用
+    compression: CompressionLease,
+    /// 日志目录的建议锁（持有期间其他处理器不能以默认文件名使用该目录）
+    _dir_lock: Option<File>,
+    /// 输出缓冲（记录直接格式化到其中，在批次之间复用）
+    output: Vec<u8>,
+    /// 输出缓冲中各条记录的结束位置（`atomic_batches` 按其切分写入）
+    boundaries: Vec<usize>,
+    /// 轮转后命令的执行队列（配置了 `post_rotate_command` 时存在）
+    post_rotate: Option<Arc<PostRotateHook>>,
+    /// `{uptime}`/`{delta}` 的时间状态
+    elapsed: ElapsedTracker,
+    /// 已清理（见 [`LogProcessor::cleanup`]），之后的写入、轮转和压缩命令被忽略
+    cleaned: AtomicBool,
+    /// 每行输出前后附加的文本
+    affixes: LineAffixes,
 }
 
 impl FileProcessor {
     /// 创建新的文件处理器
+    ///
+    /// 配置无效或日志目录已被另一个文件处理器使用（见 `SharedDirPolicy`）时 panic，
+    /// 需要处理错误时使用 [`try_new`](Self::try_new)。
     pub fn new(config: FileConfig) -> Self {
-        // 验证配置，如果失败则直接panic，让用户明确知道配置问题
-        if let Err(e) = config.validate() {
-            panic!("FileConfig 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e);
-        }
+        // 失败时直接panic，让用户明确知道配置问题
+        Self::try_new(config).unwrap_or_else(|e| panic!("{}\n请检查您的配置并修复上述问题后再重试。", e))
+    }
 
-        let writer = Arc::new(Mutex::new(
-            LogWriter::new(&config.log_dir, config.max_file_size as usize, config.force_sync)
-                .unwrap_or_else(|_| LogWriter::create_default(&config.log_dir, config.max_file_size as usize, config.force_sync))
-        ));
+    /// 创建新的文件处理器，配置无效或日志目录已被使用时返回错误
+    pub fn try_new(config: FileConfig) -> Result<Self, FileProcessorError> {
+        config.validate().map_err(FileProcessorError::InvalidConfig)?;
+        let (dir_lock, file_prefix) = lock_log_dir(&config)?;
+        let template = config.file_name_template.as_deref().unwrap_or(DEFAULT_FILE_NAME_TEMPLATE);
+        let file_name = FileNameTemplate::parse(template, &file_prefix, config.app_id.as_deref())
+            .map_err(FileProcessorError::InvalidConfig)?;
+
+        let rotator = Arc::new(LogRotator {
+            file_name,
+            ..LogRotator::new(
+                config.log_dir.clone(),
+                config.max_compressed_files,
+                config.max_total_size,
+                config.max_archive_age,
+            )
+        });
 
-        let rotator = Arc::new(LogRotator::new(config.log_dir.clone(), config.max_compressed_files));
+        let path = rotator.first_path();
+        let mut writer = LogWriter::new(&config.log_dir, &path, config.max_file_size as usize, config.force_sync)
+            .unwrap_or_else(|_| LogWriter::create_default(&config.log_dir, &path, config.max_file_size as usize, config.force_sync));
+        if config.write_header {
+            let template = match &config.format {
+                _ if config.is_raw => "{message}",
+                Some(format_config) => format_config.format_template.as_str(),
+                None => DEFAULT_TEMPLATE,
+            };
+            writer.header = Some(FileHeader {
+                app_id: config.app_id.clone().unwrap_or_else(|| crate::process_info::binary_name().to_string()),
+                format_hash: template_hash(template),
+            });
+        }
+        if config.atomic_batches {
+            writer.atomic_chunk = Some(config.atomic_chunk_size as usize);
+        }
+        let writer = Arc::new(Mutex::new(writer));
 
         // 根据配置设置格式化器，原始模式下使用原始格式
-        let formatter: Box<dyn Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync> =
+        let line_ending = config.line_ending;
+        let formatter: Formatter =
             if config.is_raw {
-                Box::new(Self::raw_format)
+                Arc::new(move |buf, record| Self::raw_format(buf, record, line_ending))
             } else if let Some(format_config) = &config.format {
                 let format_config = format_config.clone();
-                Box::new(move |buf, record| {
-                    Self::format_with_config(buf, record, &format_config)
+                let sanitize = format_config.sanitize.unwrap_or(DEFAULT_SANITIZE);
+                Arc::new(move |buf, record| {
+                    Self::format_with_config(buf, &sanitize_record(record, sanitize), &format_config, line_ending)
                 })
             } else {
-                Box::new(Self::default_format)
+                Arc::new(move |buf, record| {
+                    Self::default_format(buf, &sanitize_record(record, DEFAULT_SANITIZE), line_ending)
+                })
             };
 
-        Self {
+        let compression = CompressionLease::acquire(if config.max_compressed_files > 0 { config.min_compress_threads } else { 0 });
+        let post_rotate = config.post_rotate_command.clone().map(|command| {
+            Arc::new(PostRotateHook::spawn(command, Duration::from_millis(config.post_rotate_timeout_ms)))
+        });
+
+        Ok(Self {
             file_config: config,
             writer,
             rotator,
-            formatter,
+            formatter: FormatterHandle::new(formatter),
+            diagnostics: Diagnostics::new(),
+            last_path_check: Instant::now(),
+            compressions: Arc::new(CompressionSlots::default()),
+            compression,
+            _dir_lock: dir_lock,
+            output: Vec::new(),
+            boundaries: Vec::new(),
+            post_rotate,
+            elapsed: ElapsedTracker::default(),
+            cleaned: AtomicBool::new(false),
+            affixes: LineAffixes::default(),
+        })
+    }
+
+    /// 按 `check_path_interval_ms` 定期检查当前日志文件是否仍然存在，丢失时重新创建
+    fn check_active_file(&mut self) {
+        let interval = self.file_config.check_path_interval_ms;
+        if interval == 0 || self.last_path_check.elapsed() < Duration::from_millis(interval) {
+            return;
+        }
+        self.last_path_check = Instant::now();
+
+        let missing = {
+            let writer_guard = self.writer.lock();
+            writer_guard.current_file.is_some() && !writer_guard.current_path.exists()
+        };
+        if !missing {
+            return;
+        }
+        if let Err(e) = self.recreate_active_file("当前日志文件已被删除".to_string()) {
+            eprintln!("[file] 重新创建日志文件失败: {}", e);
+        }
+    }
+
+    /// 重新创建日志目录和活动日志文件，尚未写出的缓冲数据转移到新文件
+    fn recreate_active_file(&self, reason: String) -> io::Result<()> {
+        std::fs::create_dir_all(&self.file_config.log_dir)?;
+        let new_path = self.rotator.next_path(Path::new(""));
+        let new_file = BufWriter::new(
+            open_log_file(&new_path)?
+        );
+
+        let mut writer_guard = self.writer.lock();
+        let buffered = match writer_guard.current_file.take().map(BufWriter::into_parts) {
+            Some((_, Ok(buffered))) => buffered,
+            _ => Vec::new(),
+        };
+        // 转移的缓冲数据以头部开头时不再重复写入头部
+        writer_guard.header_pending = is_empty_file(new_file.get_ref()) && !buffered.starts_with(HEADER_PREFIX.as_bytes());
+        writer_guard.current_file = Some(new_file);
+        writer_guard.current_path = new_path.clone();
+        if !buffered.is_empty() {
+            writer_guard.write_pending_header()?;
+            if let Some(file) = &mut writer_guard.current_file {
+                file.write_all(&buffered)?;
+            }
+        }
+        writer_guard.current_size = buffered.len();
+        drop(writer_guard);
+
+        self.diagnostics.emit(DiagnosticEvent::LogFileRecreated { path: new_path, reason });
+        Ok(())
+    }
+
+    /// 写入数据，日志目录丢失（NotFound）时重新创建后重试一次
+    fn write_with_recovery(&self, data: &[u8]) -> io::Result<()> {
+        let result = self.writer.lock().write_records(data, &self.boundaries);
+        match result {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.recreate_active_file(format!("写入失败: {}", e))?;
+                self.writer.lock().write_records(data, &self.boundaries)
+            }
+            other => other,
         }
     }
 
-    
-    
-    
-    
+    /// 写入一行原始文本（不经过记录编码和格式化），行尾补充配置的换行符，照常检查轮转
+    pub fn write_raw_line(&mut self, line: &str) -> Result<(), ProcessorError> {
+        let mut data = Vec::with_capacity(line.len() + 2);
+        self.push_raw_line(&mut data, line.as_bytes());
+        self.boundaries.clear();
+        self.boundaries.push(data.len());
+        self.write_raw(&data)
+    }
+
+    /// 追加一行原始数据（附加行前后缀），已有换行符结尾时不再补充
+    fn push_raw_line(&self, buf: &mut Vec<u8>, line: &[u8]) {
+        let start = buf.len();
+        buf.extend_from_slice(line);
+        if !line.ends_with(b"\n") {
+            buf.extend_from_slice(self.file_config.line_ending.as_str().as_bytes());
+        }
+        self.affixes.apply(buf, start);
+    }
+
+    /// 写入已拼接好的原始数据并检查轮转
+    fn write_raw(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
+        self.write_output(data, "原始数据写入失败")
+    }
+
+    /// 写入数据并检查是否需要轮转，写入失败时使用 `message` 描述错误；处理器清理后的写入被忽略
+    ///
+    /// `self.boundaries` 需要记录 `data` 中各条记录的结束位置。
+    fn write_output(&mut self, data: &[u8], message: &str) -> Result<(), ProcessorError> {
+        if self.is_cleaned() {
+            return Ok(());
+        }
+        self.check_active_file();
+        self.write_with_recovery(data)
+            .map_err(|e| ProcessorError::io(message, e))?;
+
+        let needs_rotation = {
+            let writer_guard = self.writer.lock();
+            writer_guard.current_size >= writer_guard.max_size
+        };
+        if needs_rotation {
+            self.perform_rotation()?;
+        }
+        Ok(())
+    }
+
     /// 执行日志轮转
-    fn perform_rotation(&self) -> Result<(), String> {
+    fn perform_rotation(&self) -> Result<(), ProcessorError> {
         let old_path = {
             let writer_guard = self.writer.lock();
             writer_guard.current_path.clone()
@@ -127,62 +764,94 @@ impl FileProcessor {
                 }
             }
 
-            let new_path = self.rotator.next_path();
-            let new_file = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&new_path)
-                .unwrap_or_else(|_| {
-                    eprintln!("[file] 无法创建新日志文件: {}", new_path.display());
-                    OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(&new_path)
-                        .expect("无法恢复日志文件创建")
-                });
+            let new_path = self.rotator.next_path(&old_path);
+            let new_file = match open_log_file(&new_path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    // 日志目录在轮转期间被删除：重新创建目录后再打开
+                    std::fs::create_dir_all(&self.file_config.log_dir)
+                        .and_then(|_| open_log_file(&new_path))
+                        .map_err(|e| ProcessorError::io(format!("无法创建新日志文件 {}", new_path.display()), e))
+                        .inspect(|_| self.diagnostics.emit(DiagnosticEvent::LogFileRecreated {
+                            path: new_path.clone(),
+                            reason: format!("轮转时日志目录丢失: {}", e),
+                        }))?
+                }
+                Err(e) => return Err(ProcessorError::io(format!("无法创建新日志文件 {}", new_path.display()), e)),
+            };
 
             {
                 let mut writer_guard = self.writer.lock();
+                writer_guard.header_pending = is_empty_file(&new_file);
                 writer_guard.current_file = Some(BufWriter::new(new_file));
                 writer_guard.current_path = new_path;
                 writer_guard.current_size = 0;
             }
 
-            // 异步压缩旧文件
+            // 异步压缩旧文件（max_compressed_files 为 0 时禁用压缩，旧文件保留为 .log），
+            // 轮转后命令在压缩完成后执行；未压缩时直接执行
             if old_path.exists() {
-                let log_dir = self.file_config.log_dir.clone();
-                let max_compressed_files = self.file_config.max_compressed_files;
-                COMPRESSION_POOL.execute(move || {
-                    if let Err(e) = Self::compress_file(&old_path, &log_dir, max_compressed_files) {
-                        eprintln!("[file] 压缩失败 {}: {}", old_path.display(), e);
-                    } else {
-                        // 重试删除原文件
-                        for attempt in 0..5 {
-                            match std::fs::remove_file(&old_path) {
-                                Ok(_) => break,
-                                Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
-                                    let delay = if cfg!(windows) { 200 } else { 100 };
-                                    thread::sleep(Duration::from_millis(delay * (attempt + 1)));
-                                    continue;
-                                }
-                                Err(e) => {
-                                    eprintln!("[file] 删除原文件失败 {}: {}", old_path.display(), e);
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                });
+                let compressing = self.rotator.compression_enabled() && self.schedule_compression(old_path.clone(), true);
+                if let Some(hook) = self.post_rotate.as_ref().filter(|_| !compressing) {
+                    hook.submit(old_path, &self.diagnostics);
+                }
             }
 
-            self.rotator.cleanup_old_files();
+            Self::cleanup_old_files(&self.rotator, &self.diagnostics);
         }
 
         Ok(())
     }
 
-    /// 压缩文件
-    fn compress_file(src: &Path, base_path: &Path, max_files: usize) -> io::Result<()> {
+    /// 在压缩线程池中压缩文件，`remove_source` 为 true（轮转）时压缩成功后删除原文件，并执行轮转后命令
+    ///
+    /// 压缩任务数达到 `max_pending_compressions` 时按 `compression_overflow` 等待或跳过，
+    /// 跳过时上报 `CompressionSkipped` 并返回 false，文件保留为 `.log`。
+    fn schedule_compression(&self, path: PathBuf, remove_source: bool) -> bool {
+        let limit = self.file_config.max_pending_compressions;
+        if let Err(pending) = self.compressions.acquire(limit, self.file_config.compression_overflow) {
+            self.diagnostics.emit(DiagnosticEvent::CompressionSkipped { path, pending });
+            return false;
+        }
+
+        self.diagnostics.stats().add_pending_compression();
+        let rotator = Arc::clone(&self.rotator);
+        let compressions = Arc::clone(&self.compressions);
+        let diagnostics = self.diagnostics.clone();
+        let post_rotate = self.post_rotate.clone().filter(|_| remove_source);
+        self.compression.execute(move || {
+            let compressed = Self::compress_file(&path);
+            // 处理器清理后完成的任务只保留压缩结果，不再执行回调
+            let callbacks = !compressions.is_closed();
+            match compressed {
+                Ok(compressed_path) => {
+                    if remove_source {
+                        remove_file_with_retry(&path);
+                    }
+                    if callbacks {
+                        if let Some(hook) = &post_rotate {
+                            hook.submit(compressed_path.clone(), &diagnostics);
+                        }
+                        diagnostics.emit(DiagnosticEvent::LogFileCompressed { path: compressed_path });
+                        Self::cleanup_old_files(&rotator, &diagnostics);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[file] 压缩失败 {}: {}", path.display(), e);
+                    // 压缩失败时原文件仍在
+                    if let Some(hook) = post_rotate.as_ref().filter(|_| callbacks) {
+                        hook.submit(path.clone(), &diagnostics);
+                    }
+                }
+            }
+            compressions.release();
+            diagnostics.stats().remove_pending_compression();
+        });
+        true
+    }
+
+    /// 压缩文件，返回压缩文件路径
+    fn compress_file(src: &Path) -> io::Result<PathBuf> {
         let mut input = std::fs::File::open(src)?;
         let compressed_path = src.with_extension("log.lz4");
         let output = std::fs::File::create(&compressed_path)?;
@@ -194,14 +863,15 @@ impl FileProcessor {
         std::io::copy(&mut input, &mut encoder)?;
         encoder.finish().1?;
 
-        // 清理旧文件
-        let rotator = LogRotator {
-            base_path: base_path.to_path_buf(),
-            max_files,
-        };
-        rotator.cleanup_old_files();
+        Ok(compressed_path)
+    }
 
-        Ok(())
+    /// 按保留策略清理旧文件，上报因超过保留期限而删除的归档数
+    fn cleanup_old_files(rotator: &LogRotator, diagnostics: &Diagnostics) {
+        let count = rotator.cleanup_old_files();
+        if count > 0 {
+            diagnostics.emit(DiagnosticEvent::ArchivesExpired { dir: rotator.base_path.clone(), count });
+        }
     }
 }
 
@@ -210,165 +880,192 @@ impl LogProcessor for FileProcessor {
         "file_processor"
     }
 
-    fn process(&mut self, data: &[u8]) -> Result<(), String> {
+    fn accepts_emergency(&self) -> bool {
+        self.file_config.accept_emergency
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
         // 反序列化日志记录
-        let record = bincode::decode_from_slice::<Record, _>(data, bincode::config::standard())
-            .map_err(|e| format!("反序列化失败: {}", e))?.0;
+        let record = decode_record(data)?;
 
   
-        // 根据配置决定是否跳过服务端自身日志
+        // 根据配置决定是否跳过服务端自身日志和 rat_logger 自身产生的记录
         if self.file_config.skip_server_logs && record.metadata.app_id.is_none() {
             return Ok(());
         }
-
-        // 格式化日志记录
-        let formatted_data = self.format_record(&record)?;
-
-        // 直接写入文件并检查轮转
-        {
-            let mut writer_guard = self.writer.lock();
-            if let Err(e) = writer_guard.write_direct(&formatted_data) {
-                return Err(format!("文件写入失败: {}", e));
-            }
-
-            // 检查是否需要轮转
-            if writer_guard.current_size >= writer_guard.max_size {
-                drop(writer_guard);
-                self.perform_rotation()?;
-            }
+        if !self.file_config.include_internal && record.metadata.is_internal() {
+            return Ok(());
         }
 
-        Ok(())
+        // 格式化到输出缓冲，直接写入文件并检查轮转
+        let mut output = self.take_output();
+        let result = match self.format_into(&record, &mut output) {
+            Ok(()) => {
+                self.boundaries.push(output.len());
+                self.write_output(&output, "文件写入失败")
+            }
+            Err(e) => Err(e.into()),
+        };
+        self.output = output;
+        result
     }
 
-    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), String> {
-        let mut all_data = Vec::new();
-
-        // 批量反序列化和格式化
-        for data in batch {
-            let record = bincode::decode_from_slice::<Record, _>(data, bincode::config::standard())
-                .map_err(|e| format!("批量反序列化失败: {}", e))?.0;
+    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        let mut output = self.take_output();
 
-            // 根据配置决定是否跳过服务端自身日志
+        // 批量反序列化，各记录直接格式化到输出缓冲，无法反序列化的记录跳过，不影响同批次的其他记录
+        let (records, decode_error) = decode_batch(batch, self.file_config.order_by_timestamp);
+        let mut result = Ok(());
+        for record in records {
+            // 根据配置决定是否跳过服务端自身日志和 rat_logger 自身产生的记录
             if self.file_config.skip_server_logs && record.metadata.app_id.is_none() {
                 continue;
             }
+            if !self.file_config.include_internal && record.metadata.is_internal() {
+                continue;
+            }
 
-            let formatted_data = self.format_record(&record)?;
-            all_data.extend_from_slice(&formatted_data);
+            if let Err(e) = self.format_into(&record, &mut output) {
+                result = Err(e.into());
+                break;
+            }
+            self.boundaries.push(output.len());
         }
 
-        if all_data.is_empty() {
-            return Ok(());
+        // 批量写入文件
+        if result.is_ok() && !output.is_empty() {
+            result = self.write_output(&output, "批量写入文件失败");
         }
+        self.output = output;
+        result?;
 
-        // 批量写入文件
-        {
-            let mut writer_guard = self.writer.lock();
-            if let Err(e) = writer_guard.write_direct(&all_data) {
-                return Err(format!("批量写入文件失败: {}", e));
-            }
+        decode_error.map_or(Ok(()), Err)
+    }
 
-            // 检查是否需要轮转
-            if writer_guard.current_size >= writer_guard.max_size {
-                drop(writer_guard);
-                self.perform_rotation()?;
-            }
+    fn process_raw_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        let mut output = self.take_output();
+        for line in batch {
+            self.push_raw_line(&mut output, line);
+            self.boundaries.push(output.len());
         }
-
-        Ok(())
+        let result = self.write_raw(&output);
+        self.output = output;
+        result
     }
 
-    fn handle_rotate(&mut self) -> Result<(), String> {
+    fn handle_rotate(&mut self) -> Result<(), ProcessorError> {
+        if self.is_cleaned() {
+            return Ok(());
+        }
         self.perform_rotation()
     }
 
-    fn handle_compress(&mut self, path: &Path) -> Result<(), String> {
+    fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        self.diagnostics = diagnostics;
+    }
+
+    fn handle_compress(&mut self, path: &Path) -> Result<(), ProcessorError> {
+        if self.is_cleaned() {
+            return Ok(());
+        }
         // 直接执行压缩
-        let path = path.to_path_buf();
-        let log_dir = self.file_config.log_dir.clone();
-        let max_compressed_files = self.file_config.max_compressed_files;
-        COMPRESSION_POOL.execute(move || {
-            if let Err(e) = Self::compress_file(&path, &log_dir, max_compressed_files) {
-                eprintln!("[file] 压缩失败 {}: {}", path.display(), e);
-            }
-        });
+        if !self.rotator.compression_enabled() {
+            return Err(ProcessorError::new(ErrorKind::Other, "压缩已禁用 (max_compressed_files 为 0)"));
+        }
+        if !self.schedule_compression(path.to_path_buf(), false) {
+            return Err(ProcessorError::new(ErrorKind::Other, format!("压缩任务已满，未压缩 {}", path.display())));
+        }
         Ok(())
     }
 
-    fn flush(&mut self) -> Result<(), String> {
-        let mut writer_guard = self.writer.lock();
-        if let Err(e) = writer_guard.sync_all() {
-            return Err(format!("文件同步失败: {}", e));
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        if self.is_cleaned() {
+            return Ok(());
         }
-        Ok(())
+        let mut writer_guard = self.writer.lock();
+        writer_guard.sync_all()
+            .map_err(|e| ProcessorError::io("文件同步失败", e))
     }
 
-    fn cleanup(&mut self) -> Result<(), String> {
-        // 先刷新剩余数据
-        self.flush()?;
-        Ok(())
+    /// 刷新剩余数据并等待已提交的压缩任务结束；可重复调用（工作线程停止时和销毁时各调用一次），
+    /// 只有第一次生效
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        if self.cleaned.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let flushed = self.writer.lock().sync_all().map_err(|e| ProcessorError::io("文件同步失败", e));
+        if !self.compressions.close(Instant::now() + CLEANUP_COMPRESSION_WAIT) {
+            eprintln!("[file] 清理时仍有压缩任务未完成，其完成后不再执行轮转后命令");
+        }
+        flushed
     }
 }
 
 impl Drop for FileProcessor {
     fn drop(&mut self) {
-        // 清理时会自动调用cleanup
+        // 工作线程停止时已经清理过的处理器不再重复清理
         let _ = self.cleanup();
     }
 }
 
 impl LogWriter {
-    fn new(base_path: &Path, max_size: usize, force_sync: bool) -> io::Result<Self> {
+    fn new(base_path: &Path, path: &Path, max_size: usize, force_sync: bool) -> io::Result<Self> {
         if let Some(parent) = base_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
-        let path = LogRotator::new_path(base_path);
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)?;
+        let file = open_log_file(path)?;
 
         Ok(Self {
+            header_pending: is_empty_file(&file),
             current_file: Some(BufWriter::new(file)),
-            current_path: path,
+            current_path: path.to_path_buf(),
             max_size,
             current_size: 0,
             last_flush: Instant::now(),
             flush_interval: Duration::from_millis(100),
             aggressive_sync: force_sync, // 严格使用用户配置
+            header: None,
+            atomic_chunk: None,
         })
     }
 
-    fn create_default(base_path: &Path, max_size: usize, force_sync: bool) -> Self {
-        let path = LogRotator::new_path(base_path);
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)
+    fn create_default(base_path: &Path, path: &Path, max_size: usize, force_sync: bool) -> Self {
+        let file = open_log_file(path)
             .unwrap_or_else(|_| {
                 std::fs::create_dir_all(base_path.parent().unwrap_or(Path::new("."))).unwrap();
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&path)
-                    .unwrap()
+                open_log_file(path).unwrap()
             });
 
         Self {
+            header_pending: is_empty_file(&file),
             current_file: Some(BufWriter::new(file)),
-            current_path: path,
+            current_path: path.to_path_buf(),
             max_size,
             current_size: 0,
             last_flush: Instant::now(),
             flush_interval: Duration::from_millis(100),
             aggressive_sync: force_sync, // 严格使用用户配置
+            header: None,
+            atomic_chunk: None,
+        }
+    }
+
+    /// 新文件写入第一批数据前先写入头部（头部不计入 `max_size`）
+    fn write_pending_header(&mut self) -> io::Result<()> {
+        if !self.header_pending {
+            return Ok(());
+        }
+        if let (Some(header), Some(file)) = (&self.header, &mut self.current_file) {
+            file.write_all(header.line().as_bytes())?;
         }
+        self.header_pending = false;
+        Ok(())
     }
 
     /// 批量写入数据
     fn write_batch(&mut self, data: &[u8]) -> io::Result<()> {
+        self.write_pending_header()?;
         if let Some(file) = &mut self.current_file {
             file.write_all(data)?;
             self.current_size += data.len();
@@ -384,6 +1081,7 @@ impl LogWriter {
 
     /// 直接写入数据（不批量处理）
     fn write_direct(&mut self, data: &[u8]) -> io::Result<()> {
+        self.write_pending_header()?;
         if let Some(file) = &mut self.current_file {
             file.write_all(data)?;
             self.current_size += data.len();
@@ -406,6 +1104,34 @@ impl LogWriter {
         Ok(())
     }
 
+    /// 写入按记录拼接的数据（`boundaries` 为各条记录在 `data` 中的结束位置）
+    ///
+    /// 启用 `atomic_batches` 时绕过写缓冲，按记录边界切分后每块用一次写入调用追加到文件，
+    /// 写到一半被终止时文件末尾只会缺少完整的记录；否则同 [`write_direct`](Self::write_direct)。
+    fn write_records(&mut self, data: &[u8], boundaries: &[usize]) -> io::Result<()> {
+        let Some(limit) = self.atomic_chunk else {
+            return self.write_direct(data);
+        };
+        self.write_pending_header()?;
+        if let Some(file) = &mut self.current_file {
+            // 写缓冲中只可能有头部，先整行写出
+            file.flush()?;
+            let mut start = 0;
+            for end in record_chunks(boundaries, limit) {
+                file.get_mut().write_all(&data[start..end])?;
+                start = end;
+            }
+            if start < data.len() {
+                file.get_mut().write_all(&data[start..])?;
+            }
+            self.current_size += data.len();
+            if self.aggressive_sync {
+                self.sync_all()?;
+            }
+        }
+        Ok(())
+    }
+
     /// 立即刷新并同步到磁盘
     fn sync_all(&mut self) -> io::Result<()> {
         if let Some(file) = &mut self.current_file {
@@ -432,132 +1158,713 @@ impl LogWriter {
 }
 
 impl LogRotator {
-    fn new(base_path: PathBuf, max_files: usize) -> Self {
-        Self { base_path, max_files }
+    fn new(base_path: PathBuf, max_files: usize, max_total_size: u64, max_archive_age: Option<Duration>) -> Self {
+        let file_name = FileNameTemplate::parse(DEFAULT_FILE_NAME_TEMPLATE, OWNER_PREFIX, None).expect("默认文件名模板有效");
+        Self { base_path, max_files, max_total_size, max_archive_age, file_name, next_seq: AtomicU64::new(1) }
     }
 
-    fn next_path(&self) -> PathBuf {
-        Self::new_path(&self.base_path)
+    fn compression_enabled(&self) -> bool {
+        self.max_files > 0
     }
 
-    fn new_path(base_path: &Path) -> PathBuf {
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let dir = base_path;
+    /// 启动时的日志文件路径
+    ///
+    /// 模板包含 `{seq}` 时从目录中已有文件（含已压缩的）的最大序号之后开始，已清理的序号不会重复使用；
+    /// 否则同一秒内重启时追加到已有文件。
+    fn first_path(&self) -> PathBuf {
+        if self.file_name.has_seq() {
+            let last_seq = std::fs::read_dir(&self.base_path)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let stem = name.strip_suffix(".log.lz4").or_else(|| name.strip_suffix(".log"))?;
+                    self.file_name.match_stem(stem).flatten()
+                })
+                .max();
+            if let Some(last_seq) = last_seq {
+                self.next_seq.store(last_seq + 1, Ordering::Relaxed);
+            }
+            return self.next_path(Path::new(""));
+        }
+        self.new_path(self.next_seq.load(Ordering::Relaxed))
+    }
+
+    /// 轮转后的新文件路径
+    ///
+    /// 模板包含 `{seq}` 时使用下一个未被占用的序号。否则同一秒内多次轮转时按时间生成的路径会与当前文件或
+    /// 尚未压缩完成的文件重名，此时追加序号 `_1`、`_2`……
+    fn next_path(&self, current: &Path) -> PathBuf {
+        let taken = |path: &Path| path == current || path.exists() || path.with_extension("log.lz4").exists();
+        if self.file_name.has_seq() {
+            return std::iter::repeat_with(|| self.new_path(self.next_seq.fetch_add(1, Ordering::Relaxed)))
+                .find(|path| !taken(path))
+                .unwrap();
+        }
+        let path = self.new_path(self.next_seq.load(Ordering::Relaxed));
+        if !taken(&path) {
+            return path;
+        }
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        (1..)
+            .map(|n| path.with_file_name(format!("{}_{}.log", stem, n)))
+            .find(|path| !taken(path))
+            .unwrap()
+    }
+
+    /// 按模板和当前时间生成文件路径，`seq` 为 `{seq}` 的值
+    fn new_path(&self, seq: u64) -> PathBuf {
+        let timestamp = chrono::Local::now().format(FILE_TIMESTAMP_FORMAT).to_string();
+        let dir = &self.base_path;
         std::fs::create_dir_all(dir).unwrap_or(());
-        dir.join(format!("app_{}.log", timestamp))
+        dir.join(format!("{}.log", self.file_name.render(&timestamp, seq)))
     }
 
-    fn cleanup_old_files(&self) {
-        let dir_path = self.base_path.parent().unwrap_or_else(|| Path::new("."));
+    /// 清理旧日志文件
+    ///
+    /// 删除修改时间超过 `max_archive_age` 的压缩文件；压缩文件超过 `max_files` 时删除最旧的压缩文件；
+    /// 总大小超过 `max_total_size` 时从最旧的文件开始删除。最新的 .log 文件是当前活动文件，始终保留。
+    /// 只处理与文件名模板匹配的文件，同一目录中其他处理器的文件不受影响。
+    ///
+    /// 返回因超过保留期限而删除的压缩文件数（只统计本次实际删除的文件，并发的清理不会重复计数）。
+    fn cleanup_old_files(&self) -> usize {
+        let dir_path = &self.base_path;
         if !dir_path.exists() {
-            return;
+            return 0;
         }
 
-        if let Ok(entries) = std::fs::read_dir(dir_path) {
-            let mut files: Vec<_> = entries
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    let path = e.path();
-                    path.extension().map_or(false, |ext|
-                        ext == "log" || ext == "lz4"
-                    )
-                })
-                .collect();
-
-            files.sort_by(|a, b| {
-                let a_time = a.metadata().ok()
-                    .and_then(|m| m.modified().ok());
-                let b_time = b.metadata().ok()
-                    .and_then(|m| m.modified().ok());
-                a_time.cmp(&b_time)
-            });
+        let Ok(entries) = std::fs::read_dir(dir_path) else {
+            return 0;
+        };
+        let mut files: Vec<(PathBuf, u64, Option<std::time::SystemTime>)> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let path = e.path();
+                let name = e.file_name().to_string_lossy().into_owned();
+                let stem = name.strip_suffix(".log.lz4").or_else(|| name.strip_suffix(".log"));
+                let is_log = stem.is_some_and(|stem| self.file_name.matches(stem));
+                let metadata = e.metadata().ok()?;
+                is_log.then(|| (path, metadata.len(), metadata.modified().ok()))
+            })
+            .collect();
+        files.sort_by_key(|(_, _, modified)| *modified);
+
+        let mut total_size: u64 = files.iter().map(|(_, size, _)| size).sum();
+        if let Some(active) = files.iter().rposition(|(path, _, _)| path.extension().is_some_and(|ext| ext == "log")) {
+            files.remove(active);
+        }
 
-            while files.len() > self.max_files {
-                if let Some(oldest) = files.first() {
-                    if let Err(e) = std::fs::remove_file(oldest.path()) {
-                        eprintln!("[file] 删除旧日志文件失败: {}", e);
+        let mut removable = vec![false; files.len()];
+        let mut expired_files = vec![false; files.len()];
+        if self.compression_enabled() {
+            let mut compressed: Vec<usize> = files.iter()
+                .enumerate()
+                .filter(|(_, (path, _, _))| path.extension().is_some_and(|ext| ext == "lz4"))
+                .map(|(i, _)| i)
+                .collect();
+            if let Some(max_age) = self.max_archive_age {
+                let now = std::time::SystemTime::now();
+                let is_expired = |modified: Option<std::time::SystemTime>| {
+                    modified.and_then(|modified| now.duration_since(modified).ok()).is_some_and(|age| age > max_age)
+                };
+                compressed.retain(|&i| {
+                    if is_expired(files[i].2) {
+                        removable[i] = true;
+                        expired_files[i] = true;
+                        total_size -= files[i].1;
+                        false
+                    } else {
+                        true
                     }
-                    files.remove(0);
+                });
+            }
+            let excess = compressed.len().saturating_sub(self.max_files);
+            for &i in &compressed[..excess] {
+                removable[i] = true;
+                total_size -= files[i].1;
+            }
+        }
+        if self.max_total_size > 0 {
+            for (i, (_, size, _)) in files.iter().enumerate() {
+                if total_size <= self.max_total_size {
+                    break;
+                }
+                if !removable[i] {
+                    removable[i] = true;
+                    total_size -= size;
                 }
             }
         }
+
+        let mut expired = 0;
+        let removed = files.iter().zip(removable).zip(expired_files).filter(|((_, remove), _)| *remove);
+        for (((path, _, _), _), is_expired) in removed {
+            match std::fs::remove_file(path) {
+                Ok(()) => expired += usize::from(is_expired),
+                // 同时进行的另一次清理已经删除
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => eprintln!("[file] 删除旧日志文件失败: {}", e),
+            }
+        }
+        expired
     }
 }
 
 impl FileProcessor {
-    /// 格式化日志记录
-    fn format_record(&self, record: &Record) -> Result<Vec<u8>, String> {
-        let mut buf = Vec::new();
-        (self.formatter)(&mut buf, record)
+    /// 是否已清理
+    fn is_cleaned(&self) -> bool {
+        self.cleaned.load(Ordering::SeqCst)
+    }
+
+    /// 设置每行输出前后附加的文本
+    pub(crate) fn set_line_affixes(&mut self, affixes: LineAffixes) {
+        self.affixes = affixes;
+    }
+
+    /// 格式化日志记录并追加到 `buf`，附加行前后缀
+    fn format_into(&self, record: &Record, buf: &mut Vec<u8>) -> Result<(), String> {
+        let start = buf.len();
+        self.elapsed.format(record, || self.formatter.format(buf, record))
             .map_err(|e| format!("格式化失败: {}", e))?;
-        Ok(buf)
+        self.affixes.apply(buf, start);
+        Ok(())
     }
 
-    /// 默认格式化函数
-    fn default_format(buf: &mut dyn Write, record: &Record) -> io::Result<()> {
-        use chrono::Local;
+    /// 取出清空的输出缓冲（同时清空记录边界），写出后通过 `self.output = output` 放回以复用其容量
+    fn take_output(&mut self) -> Vec<u8> {
+        let mut output = std::mem::take(&mut self.output);
+        output.clear();
+        self.boundaries.clear();
+        output
+    }
 
-        let now = Local::now();
+    /// 默认格式化函数
+    fn default_format(buf: &mut dyn Write, record: &Record, line_ending: LineEnding) -> io::Result<()> {
+        let now = crate::handler::record_time(record);
         let timestamp = now.format("%Y-%m-%d %H:%M:%S%.3f");
 
-        writeln!(
+        write!(
             buf,
-            "{} [{}] {} {}:{} - {}",
+            "{} [{}] {} {}:{} - {}{}",
             timestamp,
-            record.metadata.level,
+            record.level_name(),
             record.metadata.target,
             record.file.as_deref().unwrap_or("unknown"),
             record.line.unwrap_or(0),
-            record.args
+            record.args,
+            line_ending.as_str()
         )
     }
 
     /// 原始格式化函数 - 直接输出日志消息，不添加任何格式
-    fn raw_format(buf: &mut dyn Write, record: &Record) -> io::Result<()> {
-        writeln!(buf, "{}", record.args)
+    fn raw_format(buf: &mut dyn Write, record: &Record, line_ending: LineEnding) -> io::Result<()> {
+        write!(buf, "{}{}", record.args, line_ending.as_str())
+    }
+
+    /// 处理器使用的配置（通过构建器添加时已补充构建器默认格式）
+    pub fn config(&self) -> &FileConfig {
+        &self.file_config
     }
 
-    /// 设置自定义格式化函数
-    pub fn with_formatter<F>(mut self, formatter: F) -> Self
+    /// 设置自定义格式化函数（自定义格式化函数不做控制字符处理）
+    pub fn with_formatter<F>(self, formatter: F) -> Self
     where
         F: Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync + 'static,
     {
-        self.formatter = Box::new(formatter);
+        self.set_formatter(formatter);
         self
     }
 
     /// 使用格式配置
-    pub fn with_format(mut self, format_config: FormatConfig) -> Self {
-        let format_config = format_config.clone();
-        self.formatter = Box::new(move |buf, record| Self::format_with_config(buf, record, &format_config));
+    pub fn with_format(self, format_config: FormatConfig) -> Self {
+        self.set_format(format_config);
+        self
+    }
+
+    /// 替换格式化函数（自定义格式化函数不做控制字符处理，自行写出换行符，`line_ending` 不生效）
+    pub fn set_formatter<F>(&self, formatter: F)
+    where
+        F: Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync + 'static,
+    {
+        self.formatter.set(Arc::new(formatter));
+        self.set_header_format("custom".to_string());
+    }
+
+    /// 按格式配置替换格式化函数
+    pub fn set_format(&self, format_config: FormatConfig) {
+        self.set_header_format(template_hash(&format_config.format_template));
+        let sanitize = format_config.sanitize.unwrap_or(DEFAULT_SANITIZE);
+        let line_ending = self.file_config.line_ending;
+        self.formatter.set(Arc::new(move |buf, record| {
+            Self::format_with_config(buf, &sanitize_record(record, sanitize), &format_config, line_ending)
+        }));
+    }
+
+    /// 格式化函数句柄，处理器注册后仍可通过它替换格式化函数
+    ///
+    /// 通过句柄替换格式化函数不会更新文件头部中的格式哈希。
+    pub fn formatter_handle(&self) -> FormatterHandle {
+        self.formatter.clone()
+    }
+
+    /// 设置 `{uptime}` 的起始时间（默认为处理器创建时间）
+    pub fn with_start_time(mut self, start: SystemTime) -> Self {
+        self.elapsed.set_start(start);
         self
     }
 
+    /// 设置文件头部中的应用ID（默认为可执行文件名，未启用 `write_header` 时无效）
+    pub fn with_header_app_id(self, app_id: impl Into<String>) -> Self {
+        if let Some(header) = &mut self.writer.lock().header {
+            header.app_id = app_id.into();
+        }
+        self
+    }
+
+    /// 更新之后写入的文件头部中的格式哈希
+    fn set_header_format(&self, format_hash: String) {
+        if let Some(header) = &mut self.writer.lock().header {
+            header.format_hash = format_hash;
+        }
+    }
+
     /// 使用格式配置的格式化函数
-    fn format_with_config(buf: &mut dyn Write, record: &Record, format_config: &FormatConfig) -> io::Result<()> {
-        use chrono::Local;
-
-        let now = Local::now();
-        let timestamp = now.format(&format_config.timestamp_format);
-
-        // 获取级别显示文本
-        let level_text = match record.metadata.level {
-            Level::Error => &format_config.level_style.error,
-            Level::Warn => &format_config.level_style.warn,
-            Level::Info => &format_config.level_style.info,
-            Level::Debug => &format_config.level_style.debug,
-            Level::Trace => &format_config.level_style.trace,
+    pub(crate) fn format_with_config(buf: &mut dyn Write, record: &Record, format_config: &FormatConfig, line_ending: LineEnding) -> io::Result<()> {
+        // 展开条件段后单遍替换占位符
+        let template = crate::handler::render_conditional_sections(&format_config.format_template, record);
+        crate::handler::expand_template(buf, &template, record, |out, name| {
+            crate::handler::write_builtin_placeholder(out, name, record, format_config)
+        })?;
+        buf.write_all(line_ending.as_str().as_bytes())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rat_logger_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 格式化一条记录到新的缓冲
+    fn formatted(processor: &FileProcessor, record: &Record) -> Vec<u8> {
+        let mut buf = Vec::new();
+        processor.format_into(record, &mut buf).unwrap();
+        buf
+    }
+
+    /// 创建修改时间为 `age_secs` 秒之前的日志文件
+    fn old_file(dir: &Path, name: &str, size: usize, age_secs: u64) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![b'x'; size]).unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age_secs)).unwrap();
+        path
+    }
+
+    /// 目录中的日志文件名（不含目录锁文件）
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name != LOCK_FILE_NAME)
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn uncompressed_config(dir: &Path) -> FileConfig {
+        FileConfig {
+            log_dir: dir.to_path_buf(),
+            max_compressed_files: 0,
+            compression_level: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_zero_compressed_files_disables_compression() {
+        let dir = test_dir("no_compress");
+        for i in 0..3 {
+            old_file(&dir, &format!("app_2000010{}_000000.log", i + 1), 16, 300 - i * 10);
+        }
+
+        let mut processor = FileProcessor::new(uncompressed_config(&dir));
+        processor.handle_rotate().unwrap();
+        assert!(processor.handle_compress(&dir.join("app_20000101_000000.log")).is_err());
+        join_compressions();
+
+        let names = names(&dir);
+        assert!(names.iter().all(|n| n.ends_with(".log")), "不应出现压缩文件: {:?}", names);
+        for i in 0..3 {
+            assert!(names.contains(&format!("app_2000010{}_000000.log", i + 1)), "旧日志不应被删除: {:?}", names);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_total_size_limit_keeps_active_file() {
+        let dir = test_dir("total_size");
+        let oldest = old_file(&dir, "app_20000101_000000.log", 100, 300);
+        let older = old_file(&dir, "app_20000102_000000.log", 100, 200);
+        let newer = old_file(&dir, "app_20000103_000000.log", 100, 100);
+        let active = old_file(&dir, "app_20000104_000000.log", 100, 0);
+
+        LogRotator::new(dir.clone(), 0, 250, None).cleanup_old_files();
+
+        assert!(!oldest.exists());
+        assert!(!older.exists());
+        assert!(newer.exists());
+        assert!(active.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_compressed_file_limit_counts_only_compressed_files() {
+        let dir = test_dir("compressed_limit");
+        for i in 0..5 {
+            old_file(&dir, &format!("app_2000010{}_000000.log.lz4", i + 1), 16, 500 - i * 10);
+        }
+        let active = old_file(&dir, "app_20000106_000000.log", 16, 0);
+
+        LogRotator::new(dir.clone(), 2, 0, None).cleanup_old_files();
+
+        assert_eq!(names(&dir), vec![
+            "app_20000104_000000.log.lz4".to_string(),
+            "app_20000105_000000.log.lz4".to_string(),
+            "app_20000106_000000.log".to_string(),
+        ]);
+        assert!(active.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_archive_age_limit_removes_expired_archives() {
+        let dir = test_dir("archive_age");
+        let expired = old_file(&dir, "app_20000101_000000.log.lz4", 16, 3 * 86400);
+        let recent = old_file(&dir, "app_20000102_000000.log.lz4", 16, 3600);
+        let old_log = old_file(&dir, "app_20000103_000000.log", 16, 5 * 86400);
+        let active = old_file(&dir, "app_20000104_000000.log", 16, 0);
+
+        let rotator = LogRotator::new(dir.clone(), 10, 0, Some(Duration::from_secs(86400)));
+        assert_eq!(rotator.cleanup_old_files(), 1);
+
+        // 只删除过期的归档，未压缩的文件不受期限影响
+        assert!(!expired.exists());
+        assert!(recent.exists());
+        assert!(old_log.exists());
+        assert!(active.exists());
+
+        // 与数量上限同时配置时取更严格的结果
+        old_file(&dir, "app_20000105_000000.log.lz4", 16, 60);
+        assert_eq!(LogRotator::new(dir.clone(), 1, 0, Some(Duration::from_secs(86400))).cleanup_old_files(), 0);
+        assert!(!recent.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disabled_compression_rejects_compression_options() {
+        let dir = std::env::temp_dir();
+        assert!(uncompressed_config(&dir).validate().is_ok());
+        assert!(FileConfig { compression_level: 4, ..uncompressed_config(&dir) }.validate().is_err());
+        assert!(FileConfig { compress_on_drop: true, ..uncompressed_config(&dir) }.validate().is_err());
+        assert!(FileConfig { min_compress_threads: 0, ..Default::default() }.validate().is_err());
+        assert!(FileConfig { max_pending_compressions: 2, ..uncompressed_config(&dir) }.validate().is_err());
+        assert!(FileConfig { compression_overflow: CompressionOverflow::WaitMs(0), ..Default::default() }.validate().is_err());
+        assert!(FileConfig { max_archive_age: Some(Duration::from_secs(60)), ..uncompressed_config(&dir) }.validate().is_err());
+        assert!(FileConfig { max_archive_age: Some(Duration::ZERO), ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_rotation_within_same_second_uses_new_file() {
+        let dir = test_dir("same_second");
+        let rotator = LogRotator::new(dir.clone(), 10, 0, None);
+        let first = rotator.next_path(Path::new(""));
+        std::fs::write(&first, b"x").unwrap();
+        let second = rotator.next_path(&first);
+        std::fs::write(second.with_extension("log.lz4"), b"x").unwrap();
+        let third = rotator.next_path(&first);
+
+        // 除非跨秒，否则依次追加序号
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+        assert!(!third.exists() && third.extension().is_some_and(|ext| ext == "log"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pending_compression_limit() {
+        let dir = test_dir("pending_compressions");
+        let config = FileConfig {
+            log_dir: dir.clone(),
+            max_pending_compressions: 1,
+            ..Default::default()
         };
 
-        // 使用格式模板
-        let formatted = format_config.format_template
-            .replace("{timestamp}", &timestamp.to_string())
-            .replace("{level}", level_text)
-            .replace("{target}", &record.metadata.target)
-            .replace("{file}", record.file.as_deref().unwrap_or("unknown"))
-            .replace("{line}", &record.line.unwrap_or(0).to_string())
-            .replace("{message}", &record.args);
+        // 占满空位后轮转：跳过压缩，文件保留为 .log
+        let mut processor = FileProcessor::new(config.clone());
+        processor.write_with_recovery(b"skipped\n").unwrap();
+        let skipped = processor.writer.lock().current_path.clone();
+        processor.compressions.acquire(1, CompressionOverflow::Skip).unwrap();
+        processor.handle_rotate().unwrap();
+        assert_eq!(processor.diagnostics.stats().compressions_skipped(), 1);
+        assert_eq!(processor.diagnostics.stats().pending_compressions(), 0);
+
+        // 等待模式：空位释放后继续压缩
+        let wait_dir = test_dir("pending_compressions_wait");
+        let mut processor = FileProcessor::new(FileConfig {
+            log_dir: wait_dir.clone(),
+            compression_overflow: CompressionOverflow::WaitMs(5000),
+            ..config
+        });
+        processor.write_with_recovery(b"compressed\n").unwrap();
+        let compressed = processor.writer.lock().current_path.clone();
+        processor.compressions.acquire(1, CompressionOverflow::Skip).unwrap();
+        let compressions = Arc::clone(&processor.compressions);
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            compressions.release();
+        });
+        processor.handle_rotate().unwrap();
+        releaser.join().unwrap();
+        join_compressions();
+
+        assert_eq!(processor.diagnostics.stats().compressions_skipped(), 0);
+        assert_eq!(processor.diagnostics.stats().pending_compressions(), 0);
+        assert_eq!(std::fs::read_to_string(&skipped).unwrap(), "skipped\n");
+        assert!(!compressed.exists() && compressed.with_extension("log.lz4").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&wait_dir);
+    }
+
+    #[test]
+    fn test_remove_retry_distinguishes_errors() {
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(remove_retry_delay(&denied, 0).is_some());
+        assert!(remove_retry_delay(&denied, 5).is_none());
+        assert!(remove_retry_delay(&io::Error::from(io::ErrorKind::NotFound), 0).is_none());
+
+        // 共享冲突只在 Windows 上出现，使用更短的间隔和更多的重试次数
+        let sharing = io::Error::from_raw_os_error(32);
+        if cfg!(windows) {
+            assert!(remove_retry_delay(&sharing, 0).unwrap() < remove_retry_delay(&denied, 0).unwrap());
+            assert!(remove_retry_delay(&sharing, 9).is_some());
+        } else {
+            assert!(!is_sharing_violation(&sharing));
+        }
+    }
 
-        writeln!(buf, "{}", formatted)
+    #[test]
+    fn test_header_written_once_per_rotated_file() {
+        let dir = test_dir("header");
+        let mut processor = FileProcessor::new(FileConfig {
+            write_header: true,
+            ..uncompressed_config(&dir)
+        }).with_header_app_id("billing");
+
+        for round in 0..3 {
+            if round > 0 {
+                // 日志文件名精确到秒，间隔一秒以上才会产生新文件
+                thread::sleep(Duration::from_millis(1100));
+                processor.handle_rotate().unwrap();
+            }
+            processor.write_with_recovery(format!("round {} a\n", round).as_bytes()).unwrap();
+            processor.write_with_recovery(format!("round {} b\n", round).as_bytes()).unwrap();
+            // 头部不计入文件大小
+            assert_eq!(processor.writer.lock().current_size, 20);
+        }
+        processor.flush().unwrap();
+
+        let started: Vec<String> = names(&dir)
+            .iter()
+            .map(|name| {
+                let content = std::fs::read_to_string(dir.join(name)).unwrap();
+                let lines: Vec<&str> = content.lines().collect();
+                assert_eq!(lines.len(), 3, "{}: {:?}", name, content);
+                assert_eq!(content.matches(HEADER_PREFIX).count(), 1);
+
+                let header = lines[0].strip_prefix(HEADER_PREFIX).expect("文件应当以头部开头");
+                let fields: std::collections::HashMap<&str, &str> = header
+                    .split(' ')
+                    .filter_map(|field| field.split_once('='))
+                    .collect();
+                assert_eq!(fields["version"], env!("CARGO_PKG_VERSION"));
+                assert_eq!(fields["binary"], crate::process_info::binary_name());
+                assert_eq!(fields["pid"], std::process::id().to_string());
+                assert_eq!(fields["format"], template_hash(DEFAULT_TEMPLATE));
+                assert_eq!(fields["app_id"], "billing");
+                fields["started"].to_string()
+            })
+            .collect();
+        assert_eq!(started.len(), 3);
+
+        let started: Vec<_> = started
+            .iter()
+            .map(|time| chrono::DateTime::parse_from_rfc3339(time).unwrap())
+            .collect();
+        assert!(started[0] < started[1] && started[1] < started[2], "{:?}", started);
+        let _ = std::fs::remove_dir_all(&dir);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_line_ending_terminates_each_record() {
+        let dir = test_dir("line_ending");
+        let native = if cfg!(windows) { "\r\n" } else { "\n" };
+        let record = Record {
+            args: "first\nsecond".into(),
+            ..Default::default()
+        };
+
+        for (line_ending, suffix) in [(LineEnding::Lf, "\n"), (LineEnding::CrLf, "\r\n"), (LineEnding::Native, native)] {
+            // 三个处理器同时使用同一目录
+            let config = FileConfig { line_ending, shared_dir_policy: SharedDirPolicy::Prefix, ..uncompressed_config(&dir) };
+
+            // 消息内部的换行符不受 line_ending 影响，只有记录结尾使用配置的换行符
+            let templated = FileProcessor::new(FileConfig {
+                format: Some(FormatConfig {
+                    format_template: "{message}".to_string(),
+                    ..Default::default()
+                }),
+                ..config.clone()
+            });
+            let output = formatted(&templated, &record);
+            assert_eq!(output, format!("first\nsecond{}", suffix).as_bytes(), "{:?}", line_ending);
+
+            let raw = FileProcessor::new(FileConfig { is_raw: true, ..config.clone() });
+            assert_eq!(formatted(&raw, &record), format!("first\nsecond{}", suffix).as_bytes());
+
+            let default = FileProcessor::new(config);
+            let output = formatted(&default, &record);
+            assert!(output.ends_with(format!(" - first\nsecond{}", suffix).as_bytes()), "{:?}", String::from_utf8_lossy(&output));
+            assert_eq!(output.iter().filter(|&&b| b == b'\r').count(), suffix.len() - 1);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_write_raw_line_appends_line_ending() {
+        let dir = test_dir("raw_line");
+        let mut processor = FileProcessor::new(FileConfig {
+            is_raw: true,
+            line_ending: LineEnding::CrLf,
+            ..uncompressed_config(&dir)
+        });
+        processor.write_raw_line("pre-formatted line").unwrap();
+        processor.write_raw_line("already terminated\n").unwrap();
+        processor.flush().unwrap();
+
+        let active = processor.writer.lock().current_path.clone();
+        assert_eq!(std::fs::read_to_string(active).unwrap(), "pre-formatted line\r\nalready terminated\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_active_file_can_be_read_and_renamed_while_logging() {
+        use std::io::Read;
+
+        let dir = test_dir("share_mode");
+        let mut processor = FileProcessor::new(uncompressed_config(&dir));
+        processor.write_with_recovery(b"first\n").unwrap();
+        processor.flush().unwrap();
+
+        // 其他句柄可以在写入期间读取活动文件
+        let active = processor.writer.lock().current_path.clone();
+        let mut reader = File::open(&active).unwrap();
+        processor.write_with_recovery(b"second\n").unwrap();
+        processor.flush().unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "first\nsecond\n");
+
+        // 外部轮转工具可以重命名仍被打开的活动文件，之后的轮转正常进行
+        let renamed = active.with_extension("renamed");
+        std::fs::rename(&active, &renamed).unwrap();
+        processor.write_with_recovery(b"third\n").unwrap();
+        processor.handle_rotate().unwrap();
+        drop(reader);
+        drop(processor);
+
+        let renamed_content = std::fs::read_to_string(&renamed).unwrap();
+        assert!(renamed_content.ends_with("third\n"), "{:?}", renamed_content);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_record_chunks_end_on_record_boundaries() {
+        assert_eq!(record_chunks(&[10, 20, 30, 40], 25), [20, 40]);
+        assert_eq!(record_chunks(&[10, 20, 30, 40], 40), [40]);
+        // 超过上限的记录单独成块，不会被拆开
+        assert_eq!(record_chunks(&[10, 100, 110, 120], 25), [10, 100, 120]);
+        assert_eq!(record_chunks(&[100], 25), [100]);
+        assert!(record_chunks(&[], 25).is_empty());
+    }
+
+    #[test]
+    fn test_atomic_batch_truncation_loses_only_whole_records() {
+        let dir = test_dir("atomic_batches");
+        let config = FileConfig { is_raw: true, ..uncompressed_config(&dir) }.with_atomic_batches(crate::ByteSize::b(256));
+        let mut processor = FileProcessor::new(config);
+        // 包含多行记录和超过块大小上限的记录
+        let lines: Vec<Vec<u8>> = (0..200)
+            .map(|i| match i % 7 {
+                3 => format!("record {} first\n  continued", i),
+                5 => format!("record {} {}", i, "x".repeat(300 + i)),
+                _ => format!("record {} {}", i, "y".repeat(i % 40)),
+            }.into_bytes())
+            .collect();
+        processor.process_raw_batch(&lines).unwrap();
+        processor.cleanup().unwrap();
+
+        let path = std::fs::read_dir(&dir).unwrap().flatten().map(|e| e.path())
+            .find(|p| p.extension().is_some_and(|e| e == "log")).unwrap();
+        let content = std::fs::read(&path).unwrap();
+        let (mut records, mut boundaries) = (Vec::new(), Vec::new());
+        for line in &lines {
+            records.extend_from_slice(line);
+            records.push(b'\n');
+            boundaries.push(records.len());
+        }
+        assert_eq!(content, records);
+
+        // 每次写入调用整体生效：在任意字节处终止时文件保留到之前最后一个写入块的结尾
+        let writes = record_chunks(&boundaries, 256);
+        assert!(writes.len() > 1);
+        for offset in 0..=content.len() {
+            let kept = writes.iter().rev().find(|end| **end <= offset).copied().unwrap_or(0);
+            assert!(kept == 0 || boundaries.contains(&kept), "截断在 {} 处留下半条记录", offset);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_name_template_matching() {
+        let template = FileNameTemplate::parse("{app_id}-{timestamp}", "app_", Some("billing")).unwrap();
+        assert_eq!(template.render("20240501_120000", 1), "billing-20240501_120000");
+        assert!(template.matches("billing-20240501_120000"));
+        assert!(template.matches("billing-20240501_120000_2"));
+        // 前缀相同的其他应用的文件不匹配
+        assert!(!template.matches("billing-eu-20240501_120000"));
+        assert!(!template.matches("billing-2024050_120000"));
+
+        let template = FileNameTemplate::parse("{prefix}{seq}.{hostname}", "app-1-2_", None).unwrap();
+        let hostname = crate::process_info::hostname().unwrap();
+        assert_eq!(template.render("", 12), format!("app-1-2_12.{}", hostname));
+        assert_eq!(template.match_stem(&format!("app-1-2_7.{}", hostname)), Some(Some(7)));
+        assert!(!template.matches(&format!("app-1-2_.{}", hostname)));
+
+        // 默认模板生成原有的文件名
+        let default = FileNameTemplate::parse(DEFAULT_FILE_NAME_TEMPLATE, OWNER_PREFIX, None).unwrap();
+        assert_eq!(default.render("20240501_120000", 1), "app_20240501_120000");
+
+        assert!(validate_file_name_template("{app_id}-{timestamp}", None).unwrap_err().contains("{app_id}"));
+        assert!(validate_file_name_template("{host}-{timestamp}", None).unwrap_err().contains("{host}"));
+        assert!(validate_file_name_template("{prefix}{app_id}", Some("a")).unwrap_err().contains("{seq}"));
+        assert!(validate_file_name_template("{app_id}-{timestamp}", Some("a/b")).is_err());
+        assert!(validate_file_name_template("{timestamp", None).is_err());
+    }
+}
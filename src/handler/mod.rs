@@ -1,7 +1,15 @@
 //! 日志处理器模块
 
 use std::any::Any;
-use crate::config::Record;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Serialize};
+use crate::config::{Level, LevelMask, Record, SanitizeMode};
+use crate::producer_consumer::ProcessorError;
 
 /// 日志处理器 trait
 pub trait LogHandler: Send + Sync + Any {
@@ -18,6 +26,367 @@ pub trait LogHandler: Send + Sync + Any {
     fn as_any(&self) -> &dyn Any;
 }
 
+/// 格式化函数：将一条记录写入缓冲区（包括行尾换行符）
+pub type Formatter = Arc<dyn Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync>;
+
+/// 处理器的格式化函数句柄
+///
+/// 克隆的句柄共享同一个格式化函数，处理器注册到工作线程之后仍可通过句柄替换格式化函数，
+/// 替换对之后格式化的记录生效。
+#[derive(Clone)]
+pub struct FormatterHandle(Arc<ArcSwap<Formatter>>);
+
+impl FormatterHandle {
+    /// 创建格式化函数句柄
+    pub fn new(formatter: Formatter) -> Self {
+        Self(Arc::new(ArcSwap::from_pointee(formatter)))
+    }
+
+    /// 替换格式化函数
+    pub fn set(&self, formatter: Formatter) {
+        self.0.store(Arc::new(formatter));
+    }
+
+    /// 获取当前的格式化函数
+    pub fn get(&self) -> Formatter {
+        Formatter::clone(&self.0.load())
+    }
+
+    /// 使用当前的格式化函数格式化记录
+    pub fn format(&self, buf: &mut dyn Write, record: &Record) -> io::Result<()> {
+        let formatter = self.0.load();
+        let _scope = crate::core::CallbackScope::enter();
+        (formatter.as_ref())(buf, record)
+    }
+}
+
+impl std::fmt::Debug for FormatterHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FormatterHandle")
+    }
+}
+
+/// 单遍展开模板中的占位符，写入 `out`
+///
+/// `{name}` 先交给 `builtin` 写出内置占位符的值（返回 false 表示不是内置占位符），其次按同名的结构化字段替换
+/// （如 UDP 发送端写入的 `{hostname}`、`{pid}`），都不是时原样输出。替换进来的文本不会被再次扫描：
+/// 消息或字段值中的 `{level}` 等按字面输出，与内置占位符同名的字段也不会覆盖内置值。
+pub(crate) fn expand_template(
+    out: &mut dyn Write,
+    template: &str,
+    record: &Record,
+    mut builtin: impl FnMut(&mut dyn Write, &str) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.write_all(&rest.as_bytes()[..open])?;
+        let tag = &rest[open + 1..];
+        // 与模板检查相同：`{` 后面不是占位符名称时（如 JSON 模板的 `{"msg":...}`）按字面输出
+        let name = tag.find(['{', '}'])
+            .filter(|&end| tag.as_bytes()[end] == b'}')
+            .map(|end| &tag[..end])
+            .filter(|name| name.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_'));
+        let Some(name) = name else {
+            out.write_all(b"{")?;
+            rest = tag;
+            continue;
+        };
+        if !builtin(out, name)? {
+            match record.fields.iter().find(|(key, _)| key == name) {
+                Some((_, value)) => out.write_all(value.as_bytes())?,
+                None => write!(out, "{{{}}}", name)?,
+            }
+        }
+        rest = &tag[name.len() + 1..];
+    }
+    out.write_all(rest.as_bytes())
+}
+
+/// 写出内置占位符 `name` 的值（不带颜色），不是内置占位符时返回 false
+pub(crate) fn write_builtin_placeholder(
+    out: &mut dyn Write,
+    name: &str,
+    record: &Record,
+    format_config: &crate::config::FormatConfig,
+) -> io::Result<bool> {
+    match name {
+        "timestamp" => write_timestamp(out, record, format_config)?,
+        "level" => out.write_all(record.level_text(&format_config.level_style).as_bytes())?,
+        "target" => out.write_all(record.metadata.target.as_bytes())?,
+        "file" => out.write_all(record.file.as_deref().unwrap_or("unknown").as_bytes())?,
+        "line" => write!(out, "{}", record.line.unwrap_or(0))?,
+        "message" => out.write_all(record.args.as_bytes())?,
+        "seq" => {
+            if let Some(seq) = record.seq {
+                write!(out, "{}", seq)?;
+            }
+        }
+        "record_id" => {
+            if let Some(id) = record.record_id {
+                write!(out, "{:016x}", id)?;
+            }
+        }
+        "tid" => {
+            if let Some(thread) = record.thread {
+                write!(out, "{}", thread.id)?;
+            }
+        }
+        "tseq" => {
+            if let Some(thread) = record.thread {
+                write!(out, "{}", thread.seq)?;
+            }
+        }
+        "uptime" => write_uptime(out, format_config)?,
+        "delta" => write_delta(out, format_config)?,
+        "app_id" => out.write_all(record.metadata.app_id.as_deref().unwrap_or("").as_bytes())?,
+        "module" => out.write_all(record.module_path.as_deref().unwrap_or("").as_bytes())?,
+        "binary" => out.write_all(crate::process_info::binary_name().as_bytes())?,
+        "fields" => out.write_all(record.fields_text().as_bytes())?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// 展开模板中的条件段 `{?name}...{/name}`：字段存在且非空时保留段内内容，否则整段删除
+///
+/// 支持的字段：`file`、`line`、`app_id`、`module`、`fields`、`tid`、`tseq`、`uptime`、`delta`，其他名称（如 `thread`）按同名结构化字段判断。
+/// 条件段可以嵌套；未闭合的条件段延续到模板末尾，不匹配的结束标记原样保留。
+pub(crate) fn render_conditional_sections<'a>(template: &'a str, record: &Record) -> Cow<'a, str> {
+    if !template.contains("{?") {
+        return Cow::Borrowed(template);
+    }
+
+    let mut output = String::with_capacity(template.len());
+    // 当前打开的条件段及其是否输出
+    let mut sections: Vec<(&str, bool)> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let emitting = sections.last().is_none_or(|&(_, on)| on);
+        if emitting {
+            output.push_str(&rest[..start]);
+        }
+        let tag = &rest[start..];
+
+        if let Some((marker, name, len)) = parse_section_tag(tag) {
+            match marker {
+                '?' => sections.push((name, emitting && section_present(name, record))),
+                _ if sections.last().is_some_and(|&(open, _)| open == name) => {
+                    sections.pop();
+                }
+                _ if emitting => output.push_str(&tag[..len]),
+                _ => {}
+            }
+            rest = &tag[len..];
+        } else {
+            if emitting {
+                output.push('{');
+            }
+            rest = &tag[1..];
+        }
+    }
+    if sections.last().is_none_or(|&(_, on)| on) {
+        output.push_str(rest);
+    }
+    Cow::Owned(output)
+}
+
+/// 解析 `{?name}` 或 `{/name}` 标记，返回标记符、字段名和标记长度
+fn parse_section_tag(tag: &str) -> Option<(char, &str, usize)> {
+    let marker = match tag.as_bytes().get(1) {
+        Some(b'?') => '?',
+        Some(b'/') => '/',
+        _ => return None,
+    };
+    let end = tag.find('}')?;
+    let name = &tag[2..end];
+    let valid = !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+    valid.then_some((marker, name, end + 1))
+}
+
+/// 条件段对应的字段是否存在且非空
+fn section_present(name: &str, record: &Record) -> bool {
+    match name {
+        "file" => record.file.as_deref().is_some_and(|f| !f.is_empty()),
+        "line" => record.line.is_some(),
+        "app_id" => record.metadata.app_id.as_deref().is_some_and(|a| !a.is_empty()),
+        "module" => record.module_path.as_deref().is_some_and(|m| !m.is_empty()),
+        "fields" => !record.fields.is_empty(),
+        "tid" | "tseq" => record.thread.is_some(),
+        "uptime" | "delta" => CURRENT_ELAPSED.with(Cell::get).is_some(),
+        _ => record.fields.iter().any(|(key, value)| key == name && !value.is_empty()),
+    }
+}
+
+/// 反序列化工作线程收到的记录
+pub(crate) fn decode_record(data: &[u8]) -> Result<Record, ProcessorError> {
+    crate::config::decode_record_versioned(data)
+        .map(|(record, _)| record)
+        .map_err(ProcessorError::decode)
+}
+
+/// 反序列化一个批次，无法反序列化的记录跳过，返回其余记录和第一个错误
+///
+/// `order_by_timestamp` 为 true 时按记录时间稳定排序（时间相同的记录保持到达顺序），排序范围限于本批次。
+pub(crate) fn decode_batch(batch: &[Vec<u8>], order_by_timestamp: bool) -> (Vec<Record>, Option<ProcessorError>) {
+    let mut records = Vec::with_capacity(batch.len());
+    let mut decode_error = None;
+    for data in batch {
+        match decode_record(data) {
+            Ok(record) => records.push(record),
+            Err(e) => {
+                decode_error.get_or_insert(e);
+            }
+        }
+    }
+    if order_by_timestamp {
+        records.sort_by_key(|record| record.timestamp);
+    }
+    (records, decode_error)
+}
+
+/// 记录时间（没有写入时间的记录使用当前时间）
+pub(crate) fn record_time(record: &Record) -> chrono::DateTime<chrono::Local> {
+    record.timestamp.map(chrono::DateTime::from).unwrap_or_else(chrono::Local::now)
+}
+
+/// `{timestamp}` 占位符的替换文本（按格式配置选择本地时间或 UTC）
+pub(crate) fn timestamp_text(record: &Record, format_config: &crate::config::FormatConfig) -> String {
+    let mut text = Vec::new();
+    let _ = write_timestamp(&mut text, record, format_config);
+    String::from_utf8(text).unwrap_or_default()
+}
+
+/// 把 `{timestamp}` 直接写入输出，不经过临时字符串
+fn write_timestamp(out: &mut dyn Write, record: &Record, format_config: &crate::config::FormatConfig) -> io::Result<()> {
+    let now = record_time(record);
+    let format = format_config.timestamp_format.as_str();
+    // 带时区的格式项（%z、%Z、%+、%s）之外按无时区时间格式化，输出相同，且 chrono 不必为时区名称分配字符串
+    let naive = !format.contains(['z', 'Z', '+', 's']);
+    let mut writer = FmtWriter { out, error: None };
+    let result = match (format_config.timestamp_utc, naive) {
+        (true, true) => now.naive_utc().format(format).write_to(&mut writer),
+        (true, false) => now.with_timezone(&chrono::Utc).format(format).write_to(&mut writer),
+        (false, true) => now.naive_local().format(format).write_to(&mut writer),
+        (false, false) => now.format(format).write_to(&mut writer),
+    };
+    result.map_err(|_| writer.error.take().unwrap_or_else(|| io::Error::other("时间戳格式无效")))
+}
+
+/// 把 `fmt::Write` 的写入转发到 `io::Write`，保留底层的 IO 错误
+struct FmtWriter<'a> {
+    out: &'a mut dyn Write,
+    error: Option<io::Error>,
+}
+
+impl std::fmt::Write for FmtWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            std::fmt::Error
+        })
+    }
+}
+
+/// 记录相对日志器启动和同一处理器上一条记录的时间间隔（均按记录时间计算，不受批量处理影响）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Elapsed {
+    pub(crate) uptime: Duration,
+    pub(crate) delta: Duration,
+}
+
+thread_local! {
+    /// 工作线程当前正在格式化的记录的时间间隔（见 [`ElapsedTracker::format`]）
+    static CURRENT_ELAPSED: Cell<Option<Elapsed>> = const { Cell::new(None) };
+}
+
+/// 处理器的时间间隔状态：起始时间和上一条记录的时间
+///
+/// 只在处理器的工作线程中使用，上一条记录的时间保存在 `Cell` 中，格式化时不需要加锁。
+#[derive(Debug)]
+pub(crate) struct ElapsedTracker {
+    start: SystemTime,
+    previous: Cell<Option<SystemTime>>,
+}
+
+impl ElapsedTracker {
+    /// 从 `start` 开始计算 `{uptime}`
+    pub(crate) fn new(start: SystemTime) -> Self {
+        Self { start, previous: Cell::new(None) }
+    }
+
+    /// 重新设置起始时间（构建器使用日志器时钟的启动时间）
+    pub(crate) fn set_start(&mut self, start: SystemTime) {
+        self.start = start;
+    }
+
+    /// 计算记录的时间间隔并更新上一条记录的时间，第一条记录的 `delta` 为 0
+    fn next(&self, record: &Record) -> Elapsed {
+        let at = record.timestamp.unwrap_or_else(SystemTime::now);
+        let since = |earlier: SystemTime| at.duration_since(earlier).unwrap_or_default();
+        let delta = self.previous.replace(Some(at)).map(since).unwrap_or_default();
+        Elapsed { uptime: since(self.start), delta }
+    }
+
+    /// 调用格式化函数，期间记录的时间间隔可用于 `{uptime}`/`{delta}`
+    pub(crate) fn format<R>(&self, record: &Record, format: impl FnOnce() -> R) -> R {
+        let elapsed = self.next(record);
+        CURRENT_ELAPSED.with(|current| current.set(Some(elapsed)));
+        let result = format();
+        CURRENT_ELAPSED.with(|current| current.set(None));
+        result
+    }
+}
+
+impl Default for ElapsedTracker {
+    fn default() -> Self {
+        Self::new(SystemTime::now())
+    }
+}
+
+/// `{uptime}` 占位符（不在处理器的格式化过程中时为空，`{delta}` 同样）
+fn write_uptime(out: &mut dyn Write, format_config: &crate::config::FormatConfig) -> io::Result<()> {
+    match CURRENT_ELAPSED.with(Cell::get) {
+        Some(elapsed) => write!(out, "+{:.*}s", format_config.uptime_precision, elapsed.uptime.as_secs_f64()),
+        None => Ok(()),
+    }
+}
+
+/// `{delta}` 占位符：不足一秒时以毫秒显示
+fn write_delta(out: &mut dyn Write, format_config: &crate::config::FormatConfig) -> io::Result<()> {
+    match CURRENT_ELAPSED.with(Cell::get) {
+        Some(elapsed) if elapsed.delta < Duration::from_secs(1) => {
+            write!(out, "+{:.*}ms", format_config.delta_precision, elapsed.delta.as_secs_f64() * 1000.0)
+        }
+        Some(elapsed) => write!(out, "+{:.*}s", format_config.delta_precision, elapsed.delta.as_secs_f64()),
+        None => Ok(()),
+    }
+}
+
+/// 按处理方式清理记录中的用户内容（消息、目标和结构化字段值），无需处理时不复制记录
+pub(crate) fn sanitize_record(record: &Record, mode: SanitizeMode) -> Cow<'_, Record> {
+    let dirty = |text: &str| matches!(mode.apply(text), Cow::Owned(_));
+    if mode == SanitizeMode::Off
+        || !(dirty(&record.args)
+            || dirty(&record.metadata.target)
+            || record.fields.iter().any(|(_, v)| dirty(v)))
+    {
+        return Cow::Borrowed(record);
+    }
+
+    let mut sanitized = record.clone();
+    sanitized.args = mode.apply(&record.args).into_owned().into();
+    if dirty(&record.metadata.target) {
+        let mut metadata = (*record.metadata).clone();
+        metadata.target = mode.apply(&record.metadata.target).into_owned();
+        sanitized.metadata = std::sync::Arc::new(metadata);
+    }
+    for (_, value) in sanitized.fields.iter_mut() {
+        *value = mode.apply(value).into_owned();
+    }
+    Cow::Owned(sanitized)
+}
+
 /// 处理器类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HandlerType {
@@ -27,12 +396,263 @@ pub enum HandlerType {
     Composite,
 }
 
+/// 处理器选项：与处理器配置分开设置的分发和输出规则（见 `LoggerBuilder::add_file_with_options` 等）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandlerOptions {
+    /// 处理器接收的级别集合，`None` 表示接收所有通过日志器级别过滤的记录
+    ///
+    /// 集合在日志器的级别、熔断和采样过滤之后生效，只能进一步缩小处理器收到的记录。
+    pub levels: Option<LevelMask>,
+    /// 每行输出前附加的文本（如供下游按行路由的 `[svc:payments] `），在格式化之后添加，
+    /// 原始模式和原始行同样添加，计入文件大小和轮转；终端和文件处理器支持，UDP 处理器不支持
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_prefix: Option<String>,
+    /// 每行输出末尾（换行符之前）附加的文本，规则与 `line_prefix` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_suffix: Option<String>,
+    /// 处理器名称（见 `LoggerCore::handler`），在同一日志器中必须唯一；
+    /// `None` 时按类型和添加顺序自动命名（`file_processor#0`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl HandlerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只接收给定级别的记录
+    pub fn levels(self, levels: &[Level]) -> Self {
+        self.level_mask(LevelMask::only(levels))
+    }
+
+    /// 只接收集合中级别的记录
+    pub fn level_mask(mut self, mask: LevelMask) -> Self {
+        self.levels = Some(mask);
+        self
+    }
+
+    /// 每行输出前附加的文本
+    pub fn line_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.line_prefix = Some(prefix.into());
+        self
+    }
+
+    /// 每行输出末尾附加的文本
+    pub fn line_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.line_suffix = Some(suffix.into());
+        self
+    }
+
+    /// 处理器名称
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// 是否设置了行前缀或后缀
+    pub fn has_line_affixes(&self) -> bool {
+        self.line_prefix.is_some() || self.line_suffix.is_some()
+    }
+
+    /// 处理器实际接收的级别集合
+    pub fn effective_levels(&self) -> LevelMask {
+        self.levels.unwrap_or(LevelMask::ALL)
+    }
+
+    /// 验证选项
+    pub fn validate(&self) -> Result<(), String> {
+        if self.levels.is_some_and(LevelMask::is_empty) {
+            return Err("配置错误: 级别集合为空，处理器不会收到任何记录".to_string());
+        }
+        let multiline = |text: &Option<String>| text.as_deref().is_some_and(|text| text.contains(['\n', '\r']));
+        if multiline(&self.line_prefix) || multiline(&self.line_suffix) {
+            return Err("配置错误: 行前缀和后缀不能包含换行符".to_string());
+        }
+        if self.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+            return Err("配置错误: 处理器名称不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 处理器在每行输出前后附加的文本（见 [`HandlerOptions::line_prefix`]）
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LineAffixes {
+    prefix: Option<String>,
+    suffix: Option<String>,
+}
+
+impl LineAffixes {
+    pub(crate) fn new(options: &HandlerOptions) -> Self {
+        Self { prefix: options.line_prefix.clone(), suffix: options.line_suffix.clone() }
+    }
+
+    /// 给 `buf[start..]` 中刚写入的一行加上前缀和后缀（后缀在行尾换行符之前）
+    pub(crate) fn apply(&self, buf: &mut Vec<u8>, start: usize) {
+        if let Some(suffix) = &self.suffix {
+            let line = &buf[start..];
+            let ending = if line.ends_with(b"\r\n") { 2 } else if line.ends_with(b"\n") { 1 } else { 0 };
+            let at = buf.len() - ending;
+            buf.splice(at..at, suffix.bytes());
+        }
+        if let Some(prefix) = &self.prefix {
+            buf.splice(start..start, prefix.bytes());
+        }
+    }
+}
+
 pub mod term;
 pub mod file;
 pub mod udp;
+mod udp_runtime;
+pub mod udp_spool;
 pub mod composite;
+mod console;
+mod post_rotate;
 
 pub use term::TermProcessor;
 pub use file::FileProcessor;
 pub use udp::UdpProcessor;
 pub use composite::CompositeHandler;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Metadata;
+    use std::sync::Arc;
+
+    const INJECTION: &str = "login ok\r\x1b[2Jfake line";
+
+    #[test]
+    fn test_sanitize_modes() {
+        assert_eq!(SanitizeMode::Off.apply(INJECTION), INJECTION);
+        assert_eq!(SanitizeMode::Escape.apply(INJECTION), "login ok\\r\\x1b[2Jfake line");
+        assert_eq!(SanitizeMode::Strip.apply(INJECTION), "login okfake line");
+        assert_eq!(SanitizeMode::Strip.apply("a\tb\x07c\u{9b}d"), "abcd");
+        // 换行符不在处理范围内
+        assert_eq!(SanitizeMode::Escape.apply("a\nb"), "a\nb");
+        assert!(matches!(SanitizeMode::Escape.apply("plain text"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_sanitize_record_covers_target_and_fields() {
+        let record = Record {
+            metadata: Arc::new(Metadata {
+                target: "app\r".to_string(),
+                ..Default::default()
+            }),
+            args: INJECTION.into(),
+            fields: vec![("user".to_string(), "bob\x1b[31m".to_string())],
+            ..Default::default()
+        };
+
+        let sanitized = sanitize_record(&record, SanitizeMode::Escape);
+        assert_eq!(sanitized.metadata.target, "app\\r");
+        assert_eq!(sanitized.args, "login ok\\r\\x1b[2Jfake line");
+        assert_eq!(sanitized.field("user"), Some("bob\\x1b[31m"));
+
+        let clean = Record { args: "clean".into(), ..Default::default() };
+        assert!(matches!(sanitize_record(&clean, SanitizeMode::Escape), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_conditional_sections_drop_absent_fields() {
+        let template = "{?app_id}<{app_id}> {/app_id}{?file}[{file}:{line}] {/file}{message}{?fields} {{fields}}{/fields}";
+        let bare = Record { args: "hi".into(), ..Default::default() };
+        let full = Record {
+            metadata: Arc::new(Metadata {
+                app_id: Some("billing".to_string()),
+                ..Default::default()
+            }),
+            args: "hi".into(),
+            file: Some("src/main.rs".to_string()),
+            line: Some(7),
+            fields: vec![("user".to_string(), "bob".to_string())],
+            ..Default::default()
+        };
+
+        assert_eq!(render_conditional_sections(template, &bare), "{message}");
+        assert_eq!(
+            render_conditional_sections(template, &full),
+            "<{app_id}> [{file}:{line}] {message} {{fields}}"
+        );
+        // 嵌套条件段、同名结构化字段和不匹配的结束标记
+        let nested = "{?file}{file}{?thread}@{thread}{/thread}{/file}|{/line}";
+        assert_eq!(render_conditional_sections(nested, &bare), "|{/line}");
+        assert_eq!(render_conditional_sections(nested, &full), "{file}|{/line}");
+    }
+
+    #[test]
+    fn test_formatters_render_conditional_sections() {
+        let format_config = crate::config::FormatConfig {
+            format_template: "{?app_id}<{app_id}> {/app_id}{?file}[{file}:{line}] {/file}- {message}".to_string(),
+            ..Default::default()
+        };
+        let bare = Record { args: "hi".into(), ..Default::default() };
+        let located = Record {
+            metadata: Arc::new(Metadata {
+                app_id: Some("billing".to_string()),
+                ..Default::default()
+            }),
+            file: Some("src/main.rs".to_string()),
+            line: Some(7),
+            ..bare.clone()
+        };
+
+        for (record, expected) in [(&bare, "- hi\n"), (&located, "<billing> [src/main.rs:7] - hi\n")] {
+            let mut buf = Vec::new();
+            term::format_with_config(&mut buf, record, &format_config).unwrap();
+            assert_eq!(String::from_utf8(buf).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_substituted_text_is_not_expanded_again() {
+        let format_config = crate::config::FormatConfig {
+            format_template: "{level} {{message}} {message} {user} {hostname} {\"k\":{level}}".to_string(),
+            ..Default::default()
+        };
+        let record = Record {
+            metadata: Arc::new(Metadata { level: crate::Level::Warn, ..Default::default() }),
+            args: "msg {level} {user}".into(),
+            fields: vec![
+                ("user".to_string(), "{message}".to_string()),
+                ("level".to_string(), "spoofed".to_string()),
+            ],
+            ..Default::default()
+        };
+
+        // 消息和字段值中的占位符按字面输出，同名字段不覆盖内置占位符，未知占位符保留原样
+        let mut buf = Vec::new();
+        term::format_with_config(&mut buf, &record, &format_config).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "WARN {msg {level} {user}} msg {level} {user} {message} {hostname} {\"k\":WARN}\n"
+        );
+    }
+
+    #[test]
+    fn test_timestamp_with_and_without_offset_items() {
+        let record = Record {
+            timestamp: Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_123)),
+            ..Default::default()
+        };
+        for (timestamp_format, expected) in [
+            ("%Y-%m-%d %H:%M:%S%.3f", "2023-11-14 22:13:20.123"),
+            ("%s %z", "1700000000 +0000"),
+            ("%+", "2023-11-14T22:13:20.123+00:00"),
+        ] {
+            let format_config = crate::config::FormatConfig {
+                timestamp_format: timestamp_format.to_string(),
+                timestamp_utc: true,
+                ..Default::default()
+            };
+            assert_eq!(timestamp_text(&record, &format_config), expected);
+        }
+        // 本地时间的 %s 不受时区影响
+        let format_config = crate::config::FormatConfig { timestamp_format: "%s".to_string(), ..Default::default() };
+        assert_eq!(timestamp_text(&record, &format_config), "1700000000");
+    }
+}
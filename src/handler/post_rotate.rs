@@ -0,0 +1,103 @@
+//! 轮转后命令 - 在后台线程中依次执行 `FileConfig::post_rotate_command`，失败只通过诊断事件上报
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Sender};
+
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+
+/// 执行命令的线程名
+const THREAD_NAME: &str = "rat-post-rotate";
+
+/// 检查命令是否退出的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// 命令退出后等待读取标准错误输出的时间（命令启动的子进程仍持有管道时不再等待）
+const STDERR_GRACE: Duration = Duration::from_millis(100);
+
+/// 诊断事件中保留的标准错误输出末尾字节数
+const STDERR_TAIL: usize = 4096;
+
+/// 轮转后命令的执行队列（每个文件处理器一个，同时最多执行一个命令）
+///
+/// 执行线程在队列的所有持有者（处理器和尚未完成的压缩任务）释放后退出。
+pub(crate) struct PostRotateHook {
+    queue: Sender<(PathBuf, Diagnostics)>,
+}
+
+impl PostRotateHook {
+    /// 启动执行线程，`command` 为程序和参数
+    pub fn spawn(command: Vec<String>, timeout: Duration) -> Self {
+        let (queue, receiver) = unbounded::<(PathBuf, Diagnostics)>();
+        let spawned = thread::Builder::new().name(THREAD_NAME.to_string()).spawn(move || {
+            for (path, diagnostics) in receiver {
+                if let Err((code, error)) = run(&command, timeout, &path) {
+                    diagnostics.emit(DiagnosticEvent::PostRotateCommandFailed { path, code, error });
+                }
+            }
+        });
+        if let Err(e) = spawned {
+            eprintln!("[file] 无法启动轮转后命令线程: {}", e);
+        }
+        Self { queue }
+    }
+
+    /// 把文件路径加入执行队列（不等待命令执行）
+    pub fn submit(&self, path: PathBuf, diagnostics: &Diagnostics) {
+        let _ = self.queue.send((path, diagnostics.clone()));
+    }
+}
+
+/// 执行一次命令，失败时返回退出码和原因
+fn run(command: &[String], timeout: Duration, path: &Path) -> Result<(), (Option<i32>, String)> {
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| (None, format!("无法启动 {}: {}", command[0], e)))?;
+
+    // 在单独的线程中读取标准错误输出，避免输出过多时命令阻塞在管道上
+    let (stderr_tx, stderr_rx) = mpsc::channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf);
+            let _ = stderr_tx.send(buf);
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err((None, format!("执行超时（{}ms），已终止", timeout.as_millis())));
+            }
+            Err(e) => return Err((None, format!("等待命令退出失败: {}", e))),
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    let stderr = stderr_rx.recv_timeout(STDERR_GRACE).unwrap_or_default();
+    let tail = &stderr[stderr.len().saturating_sub(STDERR_TAIL)..];
+    let tail = String::from_utf8_lossy(tail);
+    let mut error = format!("退出状态 {}", status);
+    if !tail.trim().is_empty() {
+        error.push_str(": ");
+        error.push_str(tail.trim());
+    }
+    Err((status.code(), error))
+}
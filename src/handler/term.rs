@@ -1,15 +1,167 @@
 //! 终端日志处理器 - 高性能异步架构
 
-use std::io::{self, Write, BufWriter};
+use std::io::{self, IsTerminal, Write, BufWriter};
 use std::any::Any;
 use parking_lot::Mutex;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::producer_consumer::LogProcessor;
-use crate::config::{Record, FormatConfig, ColorConfig, Level};
+use crate::producer_consumer::{LogProcessor, ProcessorError};
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+use crate::config::{Record, FormatConfig, ColorConfig, Level, LevelFilter, SanitizeMode};
+use crate::handler::{decode_batch, decode_record, sanitize_record, ElapsedTracker, Formatter, FormatterHandle, LineAffixes};
+
+/// 终端输出默认删除控制字符，避免用户输入中的转义序列操纵终端
+const DEFAULT_SANITIZE: SanitizeMode = SanitizeMode::Strip;
+
+/// 记录写入终端后调用的钩子
+pub type RecordHook = Arc<dyn Fn(&Record) + Send + Sync>;
+
+/// 回到行首并清除当前行（见 [`TermConfig::progress_compat`]）
+const CLEAR_LINE: &[u8] = b"\r\x1b[2K";
+
+/// 终端处理器的输出流（测试中可以替换为内存缓冲）
+type SharedOutput = Arc<Mutex<BufWriter<Box<dyn Write + Send>>>>;
+
+/// 暂停输出期间保留的数据，按写入顺序记录目标输出流
+struct Suspended {
+    /// 当前持有的 [`SuspendGuard`] 数
+    depth: usize,
+    held: Vec<(SharedOutput, Vec<u8>)>,
+}
+
+static SUSPENDED: Mutex<Suspended> = Mutex::new(Suspended { depth: 0, held: Vec::new() });
+
+/// 暂停终端输出的守卫（见 [`suspend_guard`]），释放时按顺序写出暂停期间的输出
+#[must_use = "守卫释放后立即恢复输出"]
+pub struct SuspendGuard {
+    _private: (),
+}
+
+/// 暂停终端处理器的输出，直到返回的守卫被释放，用于交互式提示等需要独占终端的场景
+///
+/// 暂停期间写入终端的记录全部保留在内存中，最后一个守卫释放时按原顺序写出并刷新；
+/// 守卫可以嵌套或在多个线程中同时持有。只影响标准输出是终端的处理器，重定向的输出照常写入。
+pub fn suspend_guard() -> SuspendGuard {
+    SUSPENDED.lock().depth += 1;
+    SuspendGuard { _private: () }
+}
+
+impl Drop for SuspendGuard {
+    fn drop(&mut self) {
+        let mut suspended = SUSPENDED.lock();
+        suspended.depth -= 1;
+        if suspended.depth > 0 {
+            return;
+        }
+        // 写出期间保持锁，之后的输出排在保留的数据之后
+        for (output, data) in std::mem::take(&mut suspended.held) {
+            let mut output = output.lock();
+            let _ = output.write_all(&data).and_then(|_| output.flush());
+        }
+    }
+}
+
+/// 输出被暂停时保留 `parts`，返回是否已保留
+fn hold_if_suspended(output: &SharedOutput, parts: &[&[u8]]) -> bool {
+    let mut suspended = SUSPENDED.lock();
+    if suspended.depth == 0 {
+        return false;
+    }
+    match suspended.held.last_mut() {
+        Some((last, data)) if Arc::ptr_eq(last, output) => parts.iter().for_each(|part| data.extend_from_slice(part)),
+        _ => {
+            // 先写出暂停之前缓冲中的输出，保持顺序
+            let _ = output.lock().flush();
+            suspended.held.push((output.clone(), parts.concat()));
+        }
+    }
+    true
+}
+
+/// 终端输出的刷新策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// 每条记录写入后刷新（交互式输出，同步模式的默认值）
+    EveryRecord,
+    /// 每个批次写入后刷新一次，单条写入（强制写入）不单独刷新（异步模式的默认值）
+    EveryBatch,
+    /// 最早未刷新的数据写入超过指定毫秒数后刷新，到期的数据由工作线程在空闲时刷新
+    IntervalMs(u64),
+}
+
+/// 分组键中时间的精度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePrecision {
+    /// 同一秒内的记录时间视为相同
+    Second,
+    /// 同一分钟内的记录时间视为相同
+    Minute,
+}
+
+/// 分组续行的样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupStyle {
+    /// 树形标记：`  ├─ message`
+    #[default]
+    Tree,
+    /// 只缩进：`    message`
+    Indent,
+}
+
+impl GroupStyle {
+    /// 续行在消息之前的标记
+    fn marker(self) -> &'static str {
+        match self {
+            GroupStyle::Tree => "  ├─ ",
+            GroupStyle::Indent => "    ",
+        }
+    }
+}
+
+/// 分组显示：同一批次内分组键相同的连续记录只输出一次完整的行头，之后的记录输出为只含消息的续行
+///
+/// 分组只在一个批次内进行，批次之间不保留状态；单条写入（强制写入）和 `FlushPolicy::EveryRecord` 不分组。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupBy {
+    /// 分组键是否包含记录时间及其精度（按精度截断后比较），为 None 时不比较时间
+    pub timestamp: Option<TimePrecision>,
+    /// 分组键是否包含日志目标
+    pub target: bool,
+    /// 续行样式
+    pub style: GroupStyle,
+}
+
+impl Default for GroupBy {
+    /// 按记录时间（秒）和目标分组，续行使用树形标记
+    fn default() -> Self {
+        Self {
+            timestamp: Some(TimePrecision::Second),
+            target: true,
+            style: GroupStyle::Tree,
+        }
+    }
+}
+
+/// 分组键：未包含的字段为 `None`
+type GroupKey = (Option<i64>, Option<String>);
+
+impl GroupBy {
+    fn key(&self, record: &Record) -> GroupKey {
+        let timestamp = self.timestamp.map(|precision| {
+            let secs = crate::handler::record_time(record).timestamp();
+            match precision {
+                TimePrecision::Second => secs,
+                TimePrecision::Minute => secs.div_euclid(60),
+            }
+        });
+        let target = self.target.then(|| record.metadata.target.clone());
+        (timestamp, target)
+    }
+}
 
 /// 终端输出配置
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TermConfig {
     /// 是否启用颜色输出
     pub enable_color: bool,
@@ -17,8 +169,78 @@ pub struct TermConfig {
     pub format: Option<FormatConfig>,
     /// 颜色配置
     pub color: Option<ColorConfig>,
+    /// 记录写入终端后在工作线程中调用的钩子（如发出提示音、触发桌面通知）
+    ///
+    /// 钩子只接收记录，不能访问输出流。
+    pub on_record: Option<RecordHook>,
+    /// 达到该级别（含更严重级别）的记录写入后发出终端提示音 (`\x07`)
+    pub bell_on: Option<LevelFilter>,
+    /// 是否接收强制写入（紧急日志和 Error 级别日志），为 false 时这些记录改走普通批量路径
+    pub accept_emergency: bool,
+    /// 是否接收 rat_logger 自身产生的记录（目标为 `rat_logger` 或以 `rat_logger::` 开头，如熔断汇总和启动横幅）
+    pub include_internal: bool,
+    /// 单条输出的字节上限（不含行尾换行符），超出部分截断并附加 `… (+N bytes truncated)`
+    pub max_line_bytes: Option<usize>,
+    /// 是否在输出前将每个批次按记录时间排序（只在同一批次内排序，强制写入不参与）
+    pub order_by_timestamp: bool,
+    /// 刷新策略，未指定时由构建器按模式选择（同步模式 `EveryRecord`，异步模式 `EveryBatch`），
+    /// 直接创建的处理器使用 `EveryRecord`
+    pub flush_policy: Option<FlushPolicy>,
+    /// 标准输出的读取端关闭（如管道另一端的 `head` 已退出）时是否结束进程（退出码 141，与被 SIGPIPE
+    /// 终止一致）；为 false 时之后的终端输出被静默丢弃，其他处理器不受影响
+    pub exit_on_broken_pipe: bool,
+    /// 分组显示（见 [`GroupBy`]），为 None 时每条记录输出完整的一行
+    pub group_by: Option<GroupBy>,
+    /// 进度条兼容：每次写入前先回到行首并清除当前行（`\r\x1b[2K`），避免记录与同一行上的进度条混在一起，
+    /// 进度条由其所在的库在下次更新时重绘；只在标准输出是终端时生效
+    pub progress_compat: bool,
+}
+
+impl std::fmt::Debug for TermConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TermConfig")
+            .field("enable_color", &self.enable_color)
+            .field("format", &self.format)
+            .field("color", &self.color)
+            .field("on_record", &self.on_record.as_ref().map(|_| "<hook>"))
+            .field("bell_on", &self.bell_on)
+            .field("accept_emergency", &self.accept_emergency)
+            .field("include_internal", &self.include_internal)
+            .field("max_line_bytes", &self.max_line_bytes)
+            .field("order_by_timestamp", &self.order_by_timestamp)
+            .field("flush_policy", &self.flush_policy)
+            .field("exit_on_broken_pipe", &self.exit_on_broken_pipe)
+            .field("group_by", &self.group_by)
+            .field("progress_compat", &self.progress_compat)
+            .finish()
+    }
 }
 
+/// 钩子按同一个 `Arc` 比较（`Arc::ptr_eq`），其余字段按值比较
+impl PartialEq for TermConfig {
+    fn eq(&self, other: &Self) -> bool {
+        let same_hook = match (&self.on_record, &other.on_record) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        same_hook
+            && self.enable_color == other.enable_color
+            && self.format == other.format
+            && self.color == other.color
+            && self.bell_on == other.bell_on
+            && self.accept_emergency == other.accept_emergency
+            && self.include_internal == other.include_internal
+            && self.max_line_bytes == other.max_line_bytes
+            && self.order_by_timestamp == other.order_by_timestamp
+            && self.flush_policy == other.flush_policy
+            && self.exit_on_broken_pipe == other.exit_on_broken_pipe
+            && self.group_by == other.group_by
+            && self.progress_compat == other.progress_compat
+    }
+}
+
+impl Eq for TermConfig {}
+
 impl TermConfig {
     /// 验证配置的有效性
     pub fn validate(&self) -> Result<(), String> {
@@ -29,12 +251,19 @@ impl TermConfig {
 
         // 验证格式配置（如果提供）
         if let Some(format_config) = &self.format {
-            if format_config.format_template.is_empty() {
-                return Err("配置错误: 格式模板不能为空".to_string());
-            }
-            if format_config.timestamp_format.is_empty() {
-                return Err("配置错误: 时间戳格式不能为空".to_string());
-            }
+            format_config.validate()?;
+        }
+
+        if self.max_line_bytes == Some(0) {
+            return Err("配置错误: 单条输出字节上限不能为 0".to_string());
+        }
+
+        if self.flush_policy == Some(FlushPolicy::IntervalMs(0)) {
+            return Err("配置错误: 刷新间隔不能为 0".to_string());
+        }
+
+        if self.group_by.is_some_and(|group_by| group_by.timestamp.is_none() && !group_by.target) {
+            return Err("配置错误: 分组键至少需要包含时间或目标".to_string());
         }
 
         Ok(())
@@ -47,6 +276,16 @@ impl Default for TermConfig {
             enable_color: true,
             format: None,
             color: None,
+            on_record: None,
+            bell_on: None,
+            accept_emergency: true,
+            include_internal: true,
+            max_line_bytes: None,
+            order_by_timestamp: false,
+            flush_policy: None,
+            exit_on_broken_pipe: false,
+            group_by: None,
+            progress_compat: false,
         }
     }
 }
@@ -54,8 +293,24 @@ impl Default for TermConfig {
 /// 终端日志处理器 - 实现LogProcessor trait
 pub struct TermProcessor {
     config: TermConfig,
-    formatter: Box<dyn Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync>,
-    stdout: Arc<Mutex<BufWriter<io::Stdout>>>,
+    formatter: FormatterHandle,
+    stdout: SharedOutput,
+    /// 标准输出是否为终端（进度条兼容和暂停输出只在终端上生效）
+    tty: bool,
+    /// 最早未刷新的数据的写入时间
+    unflushed_since: Option<Instant>,
+    /// 标准输出的读取端已关闭，之后的输出被丢弃
+    stdout_closed: bool,
+    diagnostics: Diagnostics,
+    /// 输出缓冲（记录直接格式化到其中，在批次之间复用）
+    output: Vec<u8>,
+    /// `{uptime}`/`{delta}` 的时间状态
+    elapsed: ElapsedTracker,
+    /// 每行输出前后附加的文本
+    affixes: LineAffixes,
+    /// 标准输出为 Windows 控制台（未重定向），输出转换为 UTF-16 后通过 `WriteConsoleW` 写入
+    #[cfg(windows)]
+    console: bool,
 }
 
 impl TermProcessor {
@@ -72,89 +327,300 @@ impl TermProcessor {
             panic!("TermConfig 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e);
         }
 
-        let formatter: Box<dyn Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync> = {
+        let formatter: Formatter = {
             // 检查是否启用颜色且有颜色配置
             let use_color = config.enable_color && config.color.is_some();
+            let sanitize = sanitize_mode(config.format.as_ref());
 
             match (&config.format, use_color) {
                 (Some(format_config), true) => {
                     // 有格式配置且启用颜色
                     let format_config = format_config.clone();
                     let color_config = config.color.as_ref().unwrap().clone();
-                    Box::new(move |buf, record| {
-                        format_with_color(buf, record, &format_config, &color_config)
+                    Arc::new(move |buf, record| {
+                        format_with_color(buf, &sanitize_record(record, sanitize), &format_config, &color_config)
                     })
                 }
                 (Some(format_config), false) => {
                     // 有格式配置但不启用颜色
                     let format_config = format_config.clone();
-                    Box::new(move |buf, record| {
-                        format_with_config(buf, record, &format_config)
+                    Arc::new(move |buf, record| {
+                        format_with_config(buf, &sanitize_record(record, sanitize), &format_config)
                     })
                 }
                 (None, true) => {
                     // 无格式配置但启用颜色
                     let default_format_config = FormatConfig::default();
                     let color_config = config.color.as_ref().unwrap().clone();
-                    Box::new(move |buf, record| {
-                        format_with_color(buf, record, &default_format_config, &color_config)
+                    Arc::new(move |buf, record| {
+                        format_with_color(buf, &sanitize_record(record, sanitize), &default_format_config, &color_config)
                     })
                 }
-                (None, false) => Box::new(default_format),
+                (None, false) => Arc::new(move |buf, record| {
+                    default_format(buf, &sanitize_record(record, sanitize))
+                }),
             }
         };
 
+        #[cfg(windows)]
+        let console = crate::handler::console::detect(config.enable_color);
         let processor = Self {
             config,
-            formatter,
-            stdout: Arc::new(Mutex::new(BufWriter::new(io::stdout()))),
+            formatter: FormatterHandle::new(formatter),
+            stdout: Arc::new(Mutex::new(BufWriter::new(Box::new(io::stdout())))),
+            tty: io::stdout().is_terminal(),
+            unflushed_since: None,
+            stdout_closed: false,
+            diagnostics: Diagnostics::new(),
+            output: Vec::new(),
+            elapsed: ElapsedTracker::default(),
+            affixes: LineAffixes::default(),
+            #[cfg(windows)]
+            console,
         };
 
         processor
     }
 
-    
-    /// 设置自定义格式化函数
-    pub fn with_formatter<F>(mut self, formatter: F) -> Self
+    /// 处理器使用的配置（通过构建器添加时已补充构建器默认格式、颜色和刷新策略）
+    ///
+    /// 未指定 `flush_policy` 时按 `EveryRecord` 刷新。
+    pub fn config(&self) -> &TermConfig {
+        &self.config
+    }
+
+    /// 设置自定义格式化函数（自定义格式化函数不做控制字符处理）
+    pub fn with_formatter<F>(self, formatter: F) -> Self
     where
         F: Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync + 'static,
     {
-        self.formatter = Box::new(formatter);
+        self.set_formatter(formatter);
         self
     }
 
     /// 使用格式配置
-    pub fn with_format(mut self, format_config: FormatConfig) -> Self {
-        let format_config = format_config.clone();
-        self.formatter = Box::new(move |buf, record| format_with_config(buf, record, &format_config));
+    pub fn with_format(self, format_config: FormatConfig) -> Self {
+        self.set_format(format_config);
         self
     }
 
     /// 使用格式配置和颜色配置
-    pub fn with_format_and_color(mut self, format_config: FormatConfig, color_config: ColorConfig) -> Self {
-        let format_config = format_config.clone();
-        let color_config = color_config.clone();
-        self.formatter = Box::new(move |buf, record| format_with_color(buf, record, &format_config, &color_config));
+    pub fn with_format_and_color(self, format_config: FormatConfig, color_config: ColorConfig) -> Self {
+        let sanitize = sanitize_mode(Some(&format_config));
+        self.formatter.set(Arc::new(move |buf, record| {
+            format_with_color(buf, &sanitize_record(record, sanitize), &format_config, &color_config)
+        }));
         self
     }
 
-    /// 格式化日志记录
-    fn format_record(&self, record: &Record) -> Result<Vec<u8>, String> {
-        let mut buf = Vec::new();
-        (self.formatter)(&mut buf, record)
+    /// 替换格式化函数（自定义格式化函数不做控制字符处理）
+    pub fn set_formatter<F>(&self, formatter: F)
+    where
+        F: Fn(&mut dyn Write, &Record) -> io::Result<()> + Send + Sync + 'static,
+    {
+        self.formatter.set(Arc::new(formatter));
+    }
+
+    /// 按格式配置替换格式化函数
+    pub fn set_format(&self, format_config: FormatConfig) {
+        let sanitize = sanitize_mode(Some(&format_config));
+        self.formatter.set(Arc::new(move |buf, record| {
+            format_with_config(buf, &sanitize_record(record, sanitize), &format_config)
+        }));
+    }
+
+    /// 格式化函数句柄，处理器注册后仍可通过它替换格式化函数
+    pub fn formatter_handle(&self) -> FormatterHandle {
+        self.formatter.clone()
+    }
+
+    /// 设置 `{uptime}` 的起始时间（默认为处理器创建时间）
+    pub fn with_start_time(mut self, start: SystemTime) -> Self {
+        self.elapsed.set_start(start);
+        self
+    }
+
+    /// 设置每行输出前后附加的文本
+    pub(crate) fn set_line_affixes(&mut self, affixes: LineAffixes) {
+        self.affixes = affixes;
+    }
+
+    /// 格式化日志记录并追加到 `buf`（超过单条输出字节上限时截断，行前后缀在截断后添加）
+    fn format_into(&self, record: &Record, buf: &mut Vec<u8>) -> Result<(), String> {
+        let start = buf.len();
+        self.elapsed.format(record, || self.formatter.format(buf, record))
             .map_err(|e| format!("格式化失败: {}", e))?;
-        Ok(buf)
+        if let Some(max_bytes) = self.config.max_line_bytes {
+            truncate_line(buf, start, max_bytes);
+        }
+        self.affixes.apply(buf, start);
+        Ok(())
+    }
+
+    /// 把记录的终端输出（格式化内容，需要时附加提示音）追加到 `buf`
+    fn render_into(&self, record: &Record, buf: &mut Vec<u8>) -> Result<(), String> {
+        self.format_into(record, buf)?;
+        self.ring_bell(record, buf);
+        Ok(())
     }
 
-    /// 写入到终端
-    fn write_to_terminal(&self, data: &[u8]) -> Result<(), String> {
-        let mut stdout_guard = self.stdout.lock();
-        stdout_guard.write_all(data)
-            .map_err(|e| format!("终端写入失败: {}", e))?;
-        stdout_guard.flush()
-            .map_err(|e| format!("终端刷新失败: {}", e))?;
+    /// 把记录作为分组续行（标记加消息）追加到 `buf`，启用颜色时标记使用时间戳颜色
+    fn render_continuation(&self, record: &Record, style: GroupStyle, buf: &mut Vec<u8>) -> Result<(), String> {
+        let start = buf.len();
+        let record = sanitize_record(record, sanitize_mode(self.config.format.as_ref()));
+        let written = match self.config.color.as_ref().filter(|_| self.config.enable_color) {
+            Some(color) => {
+                let message_color = if color.message_follows_level { color.for_record(&record) } else { &color.message };
+                writeln!(buf, "{}{}{}{}{}{}", color.timestamp, style.marker(), RESET_COLOR, message_color, record.args, RESET_COLOR)
+            }
+            None => writeln!(buf, "{}{}", style.marker(), record.args),
+        };
+        written.map_err(|e| format!("格式化失败: {}", e))?;
+        if let Some(max_bytes) = self.config.max_line_bytes {
+            truncate_line(buf, start, max_bytes);
+        }
+        self.affixes.apply(buf, start);
+        self.ring_bell(&record, buf);
         Ok(())
     }
+
+    /// 记录达到提示音级别时在 `buf` 末尾附加提示音
+    fn ring_bell(&self, record: &Record, buf: &mut Vec<u8>) {
+        if self.config.bell_on.is_some_and(|level| record.metadata.level.should_log_at(level)) {
+            buf.push(b'\x07');
+        }
+    }
+
+    /// 把一个批次的记录追加到 `buf`，需要调用钩子时把写入的记录移入 `written`
+    ///
+    /// 启用分组时，与上一条记录分组键相同的记录输出为续行；分组状态不跨批次。
+    fn render_batch(&self, records: Vec<Record>, buf: &mut Vec<u8>, written: &mut Vec<Record>) -> Result<(), String> {
+        let mut previous_key = None;
+        for record in records {
+            if !self.config.include_internal && record.metadata.is_internal() {
+                continue;
+            }
+            match self.grouping() {
+                Some(group_by) => {
+                    let key = Some(group_by.key(&record));
+                    if key == previous_key {
+                        self.render_continuation(&record, group_by.style, buf)?;
+                    } else {
+                        self.render_into(&record, buf)?;
+                    }
+                    previous_key = key;
+                }
+                None => self.render_into(&record, buf)?,
+            }
+            if self.config.on_record.is_some() {
+                written.push(record);
+            }
+        }
+        Ok(())
+    }
+
+    /// 生效的分组配置（`EveryRecord` 刷新策略下不分组）
+    fn grouping(&self) -> Option<&GroupBy> {
+        let every_record = self.config.flush_policy.unwrap_or(FlushPolicy::EveryRecord) == FlushPolicy::EveryRecord;
+        self.config.group_by.as_ref().filter(|_| !every_record)
+    }
+
+    /// 取出清空的输出缓冲，写出后通过 `self.output = output` 放回以复用其容量
+    fn take_output(&mut self) -> Vec<u8> {
+        let mut output = std::mem::take(&mut self.output);
+        output.clear();
+        output
+    }
+
+    /// 记录写入后调用钩子
+    fn notify(&self, record: &Record) {
+        if let Some(hook) = &self.config.on_record {
+            let _scope = crate::core::CallbackScope::enter();
+            hook(record);
+        }
+    }
+
+    /// 写入到终端，按刷新策略决定是否立即刷新（`end_of_batch` 表示一个批次写入完毕）
+    ///
+    /// `records` 是 `data` 包含的记录数，标准输出已关闭时计入丢弃数。
+    fn write_to_terminal(&mut self, data: &[u8], end_of_batch: bool, records: usize) -> Result<(), ProcessorError> {
+        if self.discard_if_closed(records) {
+            return Ok(());
+        }
+        let written = self.write_output(data);
+        if let Err(e) = written {
+            return self.write_failed(e, "终端写入失败", records);
+        }
+        if self.should_flush(end_of_batch) {
+            self.flush_stdout(records)?;
+        }
+        Ok(())
+    }
+
+    /// 写入格式化后的输出：Windows 控制台转换为 UTF-16 写入，其他情况（包括重定向）写入原始 UTF-8 字节
+    ///
+    /// 标准输出是终端时，按配置在输出前清除当前行，输出被暂停时保留到守卫释放。
+    fn write_output(&self, data: &[u8]) -> io::Result<()> {
+        let clear = if self.tty && self.config.progress_compat { CLEAR_LINE } else { &[] };
+        if self.tty && hold_if_suspended(&self.stdout, &[clear, data]) {
+            return Ok(());
+        }
+        let mut stdout = self.stdout.lock();
+        #[cfg(windows)]
+        if self.console {
+            // 先写出缓冲中已有的字节，保持输出顺序
+            stdout.flush()?;
+            crate::handler::console::write(clear)?;
+            return crate::handler::console::write(data);
+        }
+        stdout.write_all(clear)?;
+        stdout.write_all(data)
+    }
+
+    /// 刷新标准输出，`records` 是刷新失败时计入丢弃数的记录数
+    fn flush_stdout(&mut self, records: usize) -> Result<(), ProcessorError> {
+        self.unflushed_since = None;
+        if self.discard_if_closed(records) {
+            return Ok(());
+        }
+        let flushed = self.stdout.lock().flush();
+        flushed.or_else(|e| self.write_failed(e, "终端刷新失败", records))
+    }
+
+    /// 标准输出已关闭时丢弃记录（计入丢弃数），返回是否已丢弃
+    fn discard_if_closed(&self, records: usize) -> bool {
+        if self.stdout_closed {
+            self.diagnostics.stats().add_dropped(records as u64);
+        }
+        self.stdout_closed
+    }
+
+    /// 处理写入或刷新错误：读取端关闭时按配置结束进程，或者标记关闭并上报一次，之后的输出被丢弃
+    fn write_failed(&mut self, e: io::Error, message: &str, records: usize) -> Result<(), ProcessorError> {
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            return Err(ProcessorError::io(message, e));
+        }
+        if self.config.exit_on_broken_pipe {
+            std::process::exit(141);
+        }
+        self.stdout_closed = true;
+        self.unflushed_since = None;
+        self.diagnostics.emit(DiagnosticEvent::OutputClosed { processor: self.name() });
+        self.diagnostics.stats().add_dropped(records as u64);
+        Ok(())
+    }
+
+    /// 写入数据后是否需要立即刷新
+    fn should_flush(&mut self, end_of_batch: bool) -> bool {
+        match self.config.flush_policy.unwrap_or(FlushPolicy::EveryRecord) {
+            FlushPolicy::EveryRecord => true,
+            FlushPolicy::EveryBatch => end_of_batch,
+            FlushPolicy::IntervalMs(interval) => {
+                let since = *self.unflushed_since.get_or_insert_with(Instant::now);
+                since.elapsed() >= Duration::from_millis(interval)
+            }
+        }
+    }
 }
 
 impl LogProcessor for TermProcessor {
@@ -162,43 +628,85 @@ impl LogProcessor for TermProcessor {
         "term_processor"
     }
 
-    fn process(&mut self, data: &[u8]) -> Result<(), String> {
-        // 反序列化日志记录
-        let record = bincode::decode_from_slice::<Record, _>(data, bincode::config::standard())
-            .map_err(|e| format!("反序列化失败: {}", e))?.0;
+    fn accepts_emergency(&self) -> bool {
+        self.config.accept_emergency
+    }
 
-        // 格式化日志记录
-        let formatted_data = self.format_record(&record)?;
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
+        // 反序列化日志记录
+        let record = decode_record(data)?;
+        if !self.config.include_internal && record.metadata.is_internal() {
+            return Ok(());
+        }
 
-        // 写入到终端
-        self.write_to_terminal(&formatted_data)
+        // 格式化到输出缓冲并写入到终端
+        let mut output = self.take_output();
+        let result = match self.render_into(&record, &mut output) {
+            Ok(()) => self.write_to_terminal(&output, false, 1),
+            Err(e) => Err(e.into()),
+        };
+        self.output = output;
+        result?;
+        if !self.stdout_closed {
+            self.notify(&record);
+        }
+        Ok(())
     }
 
-    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), String> {
-        let mut all_data = Vec::new();
+    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        let mut output = self.take_output();
+        let mut written = Vec::new();
 
-        // 批量反序列化和格式化
-        for data in batch {
-            let record = bincode::decode_from_slice::<Record, _>(data, bincode::config::standard())
-                .map_err(|e| format!("批量反序列化失败: {}", e))?.0;
+        // 批量反序列化，各记录直接格式化到输出缓冲，无法反序列化的记录跳过，不影响同批次的其他记录
+        let (records, decode_error) = decode_batch(batch, self.config.order_by_timestamp);
+        let rendered = self.render_batch(records, &mut output, &mut written);
 
-            let formatted_data = self.format_record(&record)?;
-            all_data.extend_from_slice(&formatted_data);
+        // 批量写入
+        let result = rendered
+            .map_err(ProcessorError::from)
+            .and_then(|_| self.write_to_terminal(&output, true, batch.len()));
+        self.output = output;
+        result?;
+        for record in written.iter().take_while(|_| !self.stdout_closed) {
+            self.notify(record);
         }
+        decode_error.map_or(Ok(()), Err)
+    }
 
-        // 批量写入
-        self.write_to_terminal(&all_data)
+    fn process_raw_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        let mut output = self.take_output();
+        for line in batch {
+            let start = output.len();
+            output.extend_from_slice(line);
+            if !line.ends_with(b"\n") {
+                output.push(b'\n');
+            }
+            self.affixes.apply(&mut output, start);
+        }
+        let result = self.write_to_terminal(&output, true, batch.len());
+        self.output = output;
+        result
     }
 
-    fn flush(&mut self) -> Result<(), String> {
-        // 直接刷新终端
-        let mut stdout_guard = self.stdout.lock();
-        stdout_guard.flush()
-            .map_err(|e| format!("终端刷新失败: {}", e))?;
-        Ok(())
+    fn pending_deadline(&self) -> Option<Instant> {
+        match self.config.flush_policy {
+            Some(FlushPolicy::IntervalMs(interval)) => {
+                self.unflushed_since.map(|since| since + Duration::from_millis(interval))
+            }
+            _ => None,
+        }
+    }
+
+    fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        self.diagnostics = diagnostics;
     }
 
-    fn cleanup(&mut self) -> Result<(), String> {
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        // 直接刷新终端，无视刷新策略（缓冲中的记录数未知，刷新失败时不计入丢弃数）
+        self.flush_stdout(0)
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
         // 刷新所有剩余数据
         self.flush()
     }
@@ -211,18 +719,56 @@ impl Drop for TermProcessor {
     }
 }
 
+/// 颜色重置序列
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// 将 `buf` 中从 `start` 开始的一条格式化输出截断到 `max_bytes` 字节（不含行尾换行符）并附加截断提示
+///
+/// 截断位置落在 UTF-8 字符边界上，且不会切开 ANSI 转义序列；保留部分包含转义序列时
+/// 在提示前重置颜色，避免终端停留在彩色状态。
+fn truncate_line(buf: &mut Vec<u8>, start: usize, max_bytes: usize) {
+    let line = &buf[start..];
+    let line_len = line.len() - line.ends_with(b"\n") as usize;
+    if line_len <= max_bytes {
+        return;
+    }
+
+    let mut cut = max_bytes;
+    while cut > 0 && (line[cut] & 0xC0) == 0x80 {
+        cut -= 1;
+    }
+    // 不切开转义序列：ESC '[' 之后还没有出现结束字节时，从 ESC 处截断
+    if let Some(esc) = line[..cut].iter().rposition(|&b| b == 0x1b) {
+        let complete = line.get(esc + 2..cut).is_some_and(|rest| rest.iter().any(|b| (0x40..=0x7e).contains(b)));
+        if !complete {
+            cut = esc;
+        }
+    }
+
+    let truncated = line_len - cut;
+    let colored = line[..cut].contains(&0x1b);
+    buf.truncate(start + cut);
+    if colored {
+        buf.extend_from_slice(RESET_COLOR.as_bytes());
+    }
+    let _ = writeln!(buf, "… (+{} bytes truncated)", truncated);
+}
+
+/// 终端输出使用的控制字符处理方式
+fn sanitize_mode(format_config: Option<&FormatConfig>) -> SanitizeMode {
+    format_config.and_then(|f| f.sanitize).unwrap_or(DEFAULT_SANITIZE)
+}
+
 /// 默认格式化函数
 pub fn default_format(buf: &mut dyn Write, record: &Record) -> io::Result<()> {
-    use chrono::Local;
-
-    let now = Local::now();
+    let now = crate::handler::record_time(record);
     let timestamp = now.format("%Y-%m-%d %H:%M:%S%.3f");
 
     writeln!(
         buf,
         "{} [{}] {} {}:{} - {}",
         timestamp,
-        record.metadata.level,
+        record.level_name(),
         record.metadata.target,
         record.file.as_deref().unwrap_or("unknown"),
         record.line.unwrap_or(0),
@@ -232,108 +778,456 @@ pub fn default_format(buf: &mut dyn Write, record: &Record) -> io::Result<()> {
 
 /// 格式化函数
 pub fn format_with_config(buf: &mut dyn Write, record: &Record, format_config: &FormatConfig) -> io::Result<()> {
-    use chrono::Local;
-
-    let now = Local::now();
-    let timestamp = now.format(&format_config.timestamp_format);
-
-    // 获取级别显示文本
-    let level_text = match record.metadata.level {
-        Level::Error => &format_config.level_style.error,
-        Level::Warn => &format_config.level_style.warn,
-        Level::Info => &format_config.level_style.info,
-        Level::Debug => &format_config.level_style.debug,
-        Level::Trace => &format_config.level_style.trace,
-    };
-
-    // 获取模板（支持级别专用模板和继承）
+    // 获取模板（支持级别专用模板和继承），展开条件段后单遍替换占位符
     let template = get_level_template(record.metadata.level, format_config);
-
-    // 使用格式模板
-    let formatted = template
-        .replace("{timestamp}", &timestamp.to_string())
-        .replace("{level}", level_text)
-        .replace("{target}", &record.metadata.target)
-        .replace("{file}", record.file.as_deref().unwrap_or("unknown"))
-        .replace("{line}", &record.line.unwrap_or(0).to_string())
-        .replace("{message}", &record.args);
-
-    writeln!(buf, "{}", formatted)
+    let template = crate::handler::render_conditional_sections(template, record);
+    crate::handler::expand_template(buf, &template, record, |out, name| {
+        crate::handler::write_builtin_placeholder(out, name, record, format_config)
+    })?;
+    buf.write_all(b"\n")
 }
 
 /// 根据级别获取模板（支持继承，模板设为 "+" 则继承通用模板）
-fn get_level_template(level: Level, format_config: &FormatConfig) -> String {
+fn get_level_template(level: Level, format_config: &FormatConfig) -> &str {
     let default_tpl = &format_config.format_template;
 
     if let Some(ref templates) = format_config.level_templates {
-        let tpl = match level {
-            Level::Error => templates.error.as_deref(),
-            Level::Warn => templates.warn.as_deref(),
-            Level::Info => templates.info.as_deref(),
-            Level::Debug => templates.debug.as_deref(),
-            Level::Trace => templates.trace.as_deref(),
-        };
-
         // 如果模板为空或为 "+"，使用通用模板
-        match tpl {
-            Some("+") | None => default_tpl.clone(),
-            Some(t) => t.to_string(),
+        match templates.get(level) {
+            Some("+") | None => default_tpl,
+            Some(t) => t,
         }
     } else {
-        default_tpl.clone()
+        default_tpl
     }
 }
 
 /// 带颜色的格式化函数
 pub fn format_with_color(buf: &mut dyn Write, record: &Record, format_config: &FormatConfig, color_config: &ColorConfig) -> io::Result<()> {
-    use chrono::Local;
-
-    let now = Local::now();
-    let timestamp = now.format(&format_config.timestamp_format);
+    let timestamp = crate::handler::timestamp_text(record, format_config);
 
     // 获取级别显示文本
-    let level_text = match record.metadata.level {
-        Level::Error => &format_config.level_style.error,
-        Level::Warn => &format_config.level_style.warn,
-        Level::Info => &format_config.level_style.info,
-        Level::Debug => &format_config.level_style.debug,
-        Level::Trace => &format_config.level_style.trace,
-    };
+    let level_text = record.level_text(&format_config.level_style);
 
     // 获取级别颜色
-    let level_color = match record.metadata.level {
-        Level::Error => &color_config.error,
-        Level::Warn => &color_config.warn,
-        Level::Info => &color_config.info,
-        Level::Debug => &color_config.debug,
-        Level::Trace => &color_config.trace,
-    };
+    let level_color = color_config.for_record(record);
 
     // 重置颜色
     let reset_color = "\x1b[0m";
 
-    // 获取模板（支持级别专用模板和继承）
+    // 获取模板（支持级别专用模板和继承），展开条件段
     let template = get_level_template(record.metadata.level, format_config);
+    let template = crate::handler::render_conditional_sections(template, record);
 
-    // 使用格式模板并应用颜色
+    // 应用颜色
     let colored_timestamp = format!("{}{}{}", color_config.timestamp, timestamp, reset_color);
     let colored_level = format!("{}{}{}", level_color, level_text, reset_color);
-    let colored_target = format!("{}{}{}", color_config.target, record.metadata.target, reset_color);
+    let colored_target = format!("{}{}{}", color_config.for_target(&record.metadata.target), record.metadata.target, reset_color);
     let colored_file = format!("{}{}{}", color_config.file, record.file.as_deref().unwrap_or("unknown"), reset_color);
     let colored_line = format!("{}{}{}", color_config.file, record.line.unwrap_or(0), reset_color);
-    let colored_message = format!("{}{}{}", color_config.message, record.args, reset_color);
+    let message_color = if color_config.message_follows_level { level_color } else { &color_config.message };
+    let colored_message = format!("{}{}{}", message_color, record.args, reset_color);
 
-    // 使用格式模板进行格式化
-    let mut formatted = template
-        .replace("{timestamp}", &colored_timestamp)
-        .replace("{level}", &colored_level)
-        .replace("{target}", &colored_target)
-        .replace("{file}", &colored_file)
-        .replace("{line}", &colored_line)
-        .replace("{message}", &colored_message);
+    // 单遍替换占位符，带颜色的部分优先
+    let mut expanded = Vec::new();
+    crate::handler::expand_template(&mut expanded, &template, record, |out, name| {
+        let colored = match name {
+            "timestamp" => &colored_timestamp,
+            "level" => &colored_level,
+            "target" => &colored_target,
+            "file" => &colored_file,
+            "line" => &colored_line,
+            "message" => &colored_message,
+            _ => return crate::handler::write_builtin_placeholder(out, name, record, format_config),
+        };
+        out.write_all(colored.as_bytes())?;
+        Ok(true)
+    })?;
+    let mut formatted = String::from_utf8_lossy(&expanded).into_owned();
 
     // 处理格式模板中可能包含的冒号和分隔符
     formatted = formatted.replace("}:", format!("{}:{}", reset_color, color_config.file).as_str());
 
     writeln!(buf, "{}", formatted)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_strips_control_characters_by_default() {
+        let processor = TermProcessor::with_config(TermConfig {
+            enable_color: false,
+            format: Some(FormatConfig {
+                format_template: "{level} {message}".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let record = Record {
+            args: "login ok\r\x1b[2Jfake line".into(),
+            ..Default::default()
+        };
+
+        let output = formatted(&processor, &record);
+        assert_eq!(String::from_utf8(output).unwrap(), "INFO login okfake line\n");
+    }
+
+    /// 格式化一条记录到新的缓冲
+    fn formatted(processor: &TermProcessor, record: &Record) -> Vec<u8> {
+        let mut buf = Vec::new();
+        processor.format_into(record, &mut buf).unwrap();
+        buf
+    }
+
+    /// 生成一条记录的终端输出到新的缓冲
+    fn rendered(processor: &TermProcessor, record: &Record) -> Vec<u8> {
+        let mut buf = Vec::new();
+        processor.render_into(record, &mut buf).unwrap();
+        buf
+    }
+
+    fn record(level: Level, message: &str) -> Record {
+        Record {
+            metadata: Arc::new(crate::config::Metadata {
+                level,
+                target: "term_test".to_string(),
+                auth_token: None,
+                app_id: None,
+            }),
+            args: message.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bell_follows_records_at_or_above_level() {
+        let processor = TermProcessor::with_config(TermConfig {
+            enable_color: false,
+            format: Some(FormatConfig {
+                format_template: "{message}".to_string(),
+                ..Default::default()
+            }),
+            bell_on: Some(LevelFilter::Error),
+            ..Default::default()
+        });
+
+        assert_eq!(rendered(&processor, &record(Level::Error, "boom")), b"boom\n\x07");
+        assert_eq!(rendered(&processor, &record(Level::Warn, "careful")), b"careful\n");
+    }
+
+    #[test]
+    fn test_record_hook_fires_once_per_written_record() {
+        use crate::core::{Logger, LoggerBuilder};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let hook_calls = calls.clone();
+        let logger = LoggerBuilder::new()
+            .with_level(LevelFilter::Warn)
+            .add_terminal_with_config(TermConfig {
+                enable_color: false,
+                on_record: Some(Arc::new(move |record: &Record| {
+                    assert!(record.metadata.level.should_log_at(LevelFilter::Warn));
+                    hook_calls.fetch_add(1, Ordering::SeqCst);
+                })),
+                ..Default::default()
+            })
+            .build();
+
+        for (level, message) in [
+            (Level::Error, "critical 1"),
+            (Level::Info, "ignored 1"),
+            (Level::Warn, "warning"),
+            (Level::Debug, "ignored 2"),
+            (Level::Error, "critical 2"),
+        ] {
+            logger.log(&record(level, message));
+        }
+        logger.flush_sync(2000).unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_flush_policy_decisions() {
+        let with_policy = |policy| TermProcessor::with_config(TermConfig {
+            enable_color: false,
+            flush_policy: policy,
+            ..Default::default()
+        });
+
+        // 直接创建的处理器与同步模式一致：单条写入也立即刷新
+        let mut interactive = with_policy(None);
+        assert!(interactive.should_flush(false));
+        assert!(interactive.should_flush(true));
+
+        let mut batched = with_policy(Some(FlushPolicy::EveryBatch));
+        assert!(!batched.should_flush(false));
+        assert!(batched.should_flush(true));
+        assert_eq!(batched.pending_deadline(), None);
+
+        // 间隔策略：未到期时不刷新，但报告截止时间让工作线程按时刷新
+        let mut interval = with_policy(Some(FlushPolicy::IntervalMs(50)));
+        assert!(!interval.should_flush(true));
+        assert!(interval.pending_deadline().is_some());
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(interval.should_flush(true));
+        interval.flush().unwrap();
+        assert_eq!(interval.pending_deadline(), None);
+    }
+
+    #[test]
+    fn test_long_line_truncated_at_budget() {
+        let processor = TermProcessor::with_config(TermConfig {
+            enable_color: false,
+            format: Some(FormatConfig {
+                format_template: "{message}".to_string(),
+                ..Default::default()
+            }),
+            max_line_bytes: Some(4096),
+            ..Default::default()
+        });
+        let record = Record { args: "x".repeat(1024 * 1024).into(), ..Default::default() };
+
+        let output = String::from_utf8(formatted(&processor, &record)).unwrap();
+        let indicator = format!("… (+{} bytes truncated)\n", 1024 * 1024 - 4096);
+        assert!(output.ends_with(&indicator));
+        assert_eq!(output.len(), 4096 + indicator.len());
+
+        // 截断位置落在字符边界上，未超出上限的记录不受影响
+        let wide = Record { args: "é".repeat(4096).into(), ..Default::default() };
+        let output = String::from_utf8(formatted(&processor, &wide)).unwrap();
+        assert!(output.starts_with(&"é".repeat(2048)));
+        assert!(output.ends_with("… (+4096 bytes truncated)\n"));
+        let short = Record { args: "short".into(), ..Default::default() };
+        assert_eq!(formatted(&processor, &short), b"short\n");
+    }
+
+    #[test]
+    fn test_truncated_colored_line_resets_color() {
+        let processor = TermProcessor::with_config(TermConfig {
+            enable_color: true,
+            format: Some(FormatConfig {
+                format_template: "{level} {message}".to_string(),
+                ..Default::default()
+            }),
+            color: Some(ColorConfig::default()),
+            max_line_bytes: Some(4096),
+            ..Default::default()
+        });
+        let record = Record { args: "x".repeat(1024 * 1024).into(), ..Default::default() };
+
+        let output = String::from_utf8(formatted(&processor, &record)).unwrap();
+        let indicator = output.rfind("… (+").unwrap();
+        assert!(output[..indicator].ends_with(RESET_COLOR));
+        assert!(indicator <= 4096 + RESET_COLOR.len());
+
+        // 截断位置不切开转义序列
+        let mut buf = b"ab\x1b[31mcolored\n".to_vec();
+        truncate_line(&mut buf, 0, 5);
+        assert_eq!(buf, "ab… (+12 bytes truncated)\n".as_bytes());
+
+        // 只截断缓冲中从 start 开始的记录
+        let mut buf = b"previous\nab\x1b[31mcolored\n".to_vec();
+        truncate_line(&mut buf, 9, 5);
+        assert_eq!(buf, "previous\nab… (+12 bytes truncated)\n".as_bytes());
+    }
+
+    #[test]
+    fn test_message_color_follows_level() {
+        let format_config = FormatConfig {
+            format_template: "{level} {message} {target}".to_string(),
+            ..Default::default()
+        };
+        let render = |level: Level, message_follows_level: bool| {
+            let color_config = ColorConfig { message_follows_level, ..Default::default() };
+            let record = Record {
+                metadata: Arc::new(crate::config::Metadata {
+                    level,
+                    target: "app".to_string(),
+                    auth_token: None,
+                    app_id: None,
+                }),
+                args: "text".into(),
+                ..Default::default()
+            };
+            let mut buf = Vec::new();
+            format_with_color(&mut buf, &record, &format_config, &color_config).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        // 默认使用固定的消息颜色，消息之后的目标颜色不受影响
+        assert_eq!(render(Level::Error, false), "\x1b[31mERROR\x1b[0m \x1b[0mtext\x1b[0m \x1b[34mapp\x1b[0m\n");
+        assert_eq!(render(Level::Info, false), "\x1b[32mINFO\x1b[0m \x1b[0mtext\x1b[0m \x1b[34mapp\x1b[0m\n");
+        assert_eq!(render(Level::Error, true), "\x1b[31mERROR\x1b[0m \x1b[31mtext\x1b[0m \x1b[34mapp\x1b[0m\n");
+        assert_eq!(render(Level::Info, true), "\x1b[32mINFO\x1b[0m \x1b[32mtext\x1b[0m \x1b[34mapp\x1b[0m\n");
+    }
+
+    /// 五条记录：前三条同一秒、同一目标，第四条进入下一秒，第五条换了目标
+    fn grouped_batch() -> Vec<Record> {
+        let base = std::time::UNIX_EPOCH + std::time::Duration::new(1_767_323_045, 0);
+        [("a", 0, "one"), ("a", 200, "two"), ("a", 900, "three"), ("a", 1000, "four"), ("b", 1000, "five")]
+            .into_iter()
+            .map(|(target, millis, message)| Record {
+                metadata: Arc::new(crate::config::Metadata {
+                    level: Level::Info,
+                    target: target.to_string(),
+                    auth_token: None,
+                    app_id: None,
+                }),
+                args: message.into(),
+                timestamp: Some(base + std::time::Duration::from_millis(millis)),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn grouping_processor(flush_policy: FlushPolicy, color: Option<ColorConfig>) -> TermProcessor {
+        TermProcessor::with_config(TermConfig {
+            enable_color: color.is_some(),
+            color,
+            format: Some(FormatConfig {
+                format_template: "{timestamp} {target} {message}".to_string(),
+                timestamp_format: "%H:%M:%S".to_string(),
+                timestamp_utc: true,
+                custom_placeholders: Vec::new(),
+                uptime_precision: 3,
+                delta_precision: 1,
+                ..Default::default()
+            }),
+            flush_policy: Some(flush_policy),
+            group_by: Some(GroupBy::default()),
+            ..Default::default()
+        })
+    }
+
+    fn render_batch(processor: &TermProcessor, records: Vec<Record>) -> String {
+        let mut buf = Vec::new();
+        processor.render_batch(records, &mut buf, &mut Vec::new()).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_group_by_prints_continuations_within_batch() {
+        let processor = grouping_processor(FlushPolicy::EveryBatch, None);
+        let expected = "03:04:05 a one\n  ├─ two\n  ├─ three\n03:04:06 a four\n03:04:06 b five\n";
+        assert_eq!(render_batch(&processor, grouped_batch()), expected);
+
+        // 分组状态不跨批次：下一批次的第一条记录输出完整的行头
+        let next = render_batch(&processor, grouped_batch().drain(1..2).collect());
+        assert_eq!(next, "03:04:05 a two\n");
+    }
+
+    #[test]
+    fn test_group_continuation_marker_uses_timestamp_color() {
+        let color = ColorConfig::default();
+        let processor = grouping_processor(FlushPolicy::EveryBatch, Some(color.clone()));
+        let output = render_batch(&processor, grouped_batch());
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], format!("{}  ├─ {}{}two{}", color.timestamp, RESET_COLOR, color.message, RESET_COLOR));
+        assert!(lines[3].contains("four"));
+    }
+
+    #[test]
+    fn test_target_colors_use_longest_prefix() {
+        let colors = ColorConfig {
+            target_colors: vec![
+                ("db::".to_string(), "\x1b[34m".to_string()),
+                ("db::pool::slow".to_string(), "\x1b[31m".to_string()),
+                ("net::".to_string(), "\x1b[35m".to_string()),
+            ],
+            ..Default::default()
+        };
+        let format = FormatConfig { format_template: "{target}".to_string(), ..Default::default() };
+        let render = |target: &str| {
+            let mut record = record(Level::Info, "m");
+            Arc::make_mut(&mut record.metadata).target = target.to_string();
+            let mut buf = Vec::new();
+            format_with_color(&mut buf, &record, &format, &colors).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        assert_eq!(render("db::pool"), format!("\x1b[34mdb::pool{}\n", RESET_COLOR));
+        assert_eq!(render("net::http"), format!("\x1b[35mnet::http{}\n", RESET_COLOR));
+        assert_eq!(render("db::pool::slow::query"), format!("\x1b[31mdb::pool::slow::query{}\n", RESET_COLOR));
+        assert_eq!(render("app::main"), format!("{}app::main{}\n", colors.target, RESET_COLOR));
+    }
+
+    #[test]
+    fn test_every_record_flush_disables_grouping() {
+        let processor = grouping_processor(FlushPolicy::EveryRecord, None);
+        let expected = "03:04:05 a one\n03:04:05 a two\n03:04:05 a three\n03:04:06 a four\n03:04:06 b five\n";
+        assert_eq!(render_batch(&processor, grouped_batch()), expected);
+
+        let empty = TermConfig { group_by: Some(GroupBy { timestamp: None, target: false, style: GroupStyle::Tree }), ..Default::default() };
+        assert!(empty.validate().is_err());
+    }
+
+    /// 多个处理器共享的内存输出
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 模拟终端上的处理器，输出写入 `buf`
+    fn tty_processor(progress_compat: bool, buf: &SharedBuf) -> TermProcessor {
+        let mut processor = TermProcessor::with_config(TermConfig { enable_color: false, progress_compat, ..Default::default() });
+        processor.stdout = Arc::new(Mutex::new(BufWriter::new(Box::new(buf.clone()))));
+        processor.tty = true;
+        #[cfg(windows)]
+        {
+            processor.console = false;
+        }
+        processor
+    }
+
+    // 暂停状态是全局的，清行和暂停在同一个测试中检查
+    #[test]
+    fn test_progress_compat_and_suspend_guard() {
+        let buf = SharedBuf::default();
+        let mut compat = tty_processor(true, &buf);
+        compat.write_to_terminal(b"one\n", true, 1).unwrap();
+        assert_eq!(&buf.0.lock()[..], b"\r\x1b[2Kone\n");
+
+        // 重定向的输出不清行
+        let mut redirected = tty_processor(true, &buf);
+        redirected.tty = false;
+        redirected.write_to_terminal(b"two\n", true, 1).unwrap();
+        assert_eq!(&buf.0.lock()[..], b"\r\x1b[2Kone\ntwo\n");
+
+        let plain_buf = SharedBuf::default();
+        let mut plain = tty_processor(false, &plain_buf);
+        buf.0.lock().clear();
+        {
+            let _outer = suspend_guard();
+            compat.write_to_terminal(b"a\n", true, 1).unwrap();
+            plain.write_to_terminal(b"b\n", true, 1).unwrap();
+            {
+                let _inner = suspend_guard();
+                compat.write_to_terminal(b"c\n", false, 1).unwrap();
+            }
+            compat.write_to_terminal(b"d\n", true, 1).unwrap();
+            // 暂停期间没有任何输出，非终端输出不受影响
+            redirected.write_to_terminal(b"e\n", true, 1).unwrap();
+            assert_eq!(&buf.0.lock()[..], b"e\n");
+            assert!(plain_buf.0.lock().is_empty());
+        }
+        assert_eq!(&buf.0.lock()[..], b"e\n\r\x1b[2Ka\n\r\x1b[2Kc\n\r\x1b[2Kd\n");
+        assert_eq!(&plain_buf.0.lock()[..], b"b\n");
+
+        // 守卫释放后直接输出
+        plain.write_to_terminal(b"f\n", true, 1).unwrap();
+        assert_eq!(&plain_buf.0.lock()[..], b"b\nf\n");
+    }
+}
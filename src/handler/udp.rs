@@ -1,76 +1,216 @@
 //! UDP日志处理器 - 高性能异步架构
 
-use std::any::Any;
+use std::future::Future;
 use std::sync::Arc;
-use std::thread;
-use std::time::Instant;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use dashmap::DashMap;
-use parking_lot::Mutex;
-use tokio::net::UdpSocket;
-use tokio::runtime::Runtime;
+use rand::Rng;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use futures::channel::oneshot;
+use futures::future::{select, Either};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 
-use crate::producer_consumer::LogProcessor;
+use crate::producer_consumer::{ErrorKind, LogProcessor, ProcessorError};
 use crate::config::{Record, NetworkConfig};
-use crate::udp_helper::UdpPacketHelper;
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+use crate::handler::decode_record;
+use crate::handler::udp_runtime::{self, ActiveRuntime as Runtime, UdpRuntime};
+use crate::handler::udp_spool::{Spool, SpoolConfig};
+use crate::udp_helper::{PacketMetadata, PayloadFields, ProbeKind, UdpPacketHelper, UdpBatchProcessor, MAX_DATAGRAM_SIZE, PROBE_LEN};
+
+/// 当前运行时的 UDP 套接字
+type UdpSocket = <Runtime as UdpRuntime>::Socket;
+
+/// 连接池中的单个连接
+struct PooledConnection {
+    socket: Arc<UdpSocket>,
+    /// 创建（解析地址并连接）的时间
+    created_at: Instant,
+    /// 尚未被连续成功抵消的发送错误数
+    consecutive_errors: u32,
+    /// 连续成功发送次数
+    success_streak: u32,
+}
+
+/// 连接池运行统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// 当前缓存的连接数
+    pub connections: usize,
+    /// 累计建立的连接数
+    pub created: u64,
+    /// 因超过存活时间而重建的连接数
+    pub expired: u64,
+    /// 因连续发送错误而移除的连接数
+    pub evicted: u64,
+}
+
+/// 连接池共享状态（发送任务与处理器共同持有）
+struct PoolInner {
+    connections: DashMap<String, PooledConnection>,
+    /// 连接存活时间，到期后重新解析地址并重新连接
+    ttl: Option<Duration>,
+    /// 连续发送错误达到该次数后移除连接（0 表示不移除）
+    max_consecutive_errors: u32,
+    created: AtomicU64,
+    expired: AtomicU64,
+    evicted: AtomicU64,
+}
+
+impl PoolInner {
+    /// 获取或创建UDP连接，过期的连接会重新解析地址
+    async fn get_connection(&self, addr: &str) -> std::io::Result<Arc<UdpSocket>> {
+        if let Some(entry) = self.connections.get(addr) {
+            let expired = self.ttl.is_some_and(|ttl| entry.created_at.elapsed() >= ttl);
+            if !expired {
+                return Ok(entry.socket.clone());
+            }
+            drop(entry);
+            if self.connections.remove(addr).is_some() {
+                self.expired.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let socket = Arc::new(Self::connect(addr).await?);
+        self.connections.insert(addr.to_string(), PooledConnection {
+            socket: socket.clone(),
+            created_at: Instant::now(),
+            consecutive_errors: 0,
+            success_streak: 0,
+        });
+        self.created.fetch_add(1, Ordering::Relaxed);
+        Ok(socket)
+    }
+
+    /// 解析地址（支持主机名）并连接到第一个可用地址
+    async fn connect(addr: &str) -> std::io::Result<UdpSocket> {
+        let mut last_error = None;
+        for target in Runtime::resolve(addr).await? {
+            let bind_addr = if target.is_ipv4() {
+                std::net::SocketAddr::from(([0, 0, 0, 0], 0))
+            } else {
+                std::net::SocketAddr::from(([0u16; 8], 0))
+            };
+            let result = async {
+                let socket = Runtime::bind(bind_addr).await?;
+                Runtime::connect(&socket, target).await?;
+                Ok::<_, std::io::Error>(socket)
+            }.await;
+            match result {
+                Ok(socket) => return Ok(socket),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("无法解析UDP地址: {}", addr),
+        )))
+    }
+
+    /// 发送数据，并记录结果用于错误移除
+    async fn send_data(&self, addr: &str, data: &[u8]) -> std::io::Result<()> {
+        let socket = self.get_connection(addr).await?;
+        let result = Runtime::send(&socket, data).await.map(|_| ());
+        self.record_result(addr, &socket, result.is_ok());
+        result
+    }
+
+    /// 记录发送结果
+    ///
+    /// 已连接的UDP套接字会在下一次发送时报告上一次发送引发的 ICMP 错误，
+    /// 服务端不可达时成功与失败交替出现，因此需要连续两次成功才清零错误计数。
+    fn record_result(&self, addr: &str, socket: &Arc<UdpSocket>, ok: bool) {
+        let evict = match self.connections.get_mut(addr) {
+            // 只处理本次发送所用的连接，避免误伤已经重建的新连接
+            Some(mut entry) if Arc::ptr_eq(&entry.socket, socket) => {
+                if ok {
+                    entry.success_streak += 1;
+                    if entry.success_streak >= 2 {
+                        entry.consecutive_errors = 0;
+                    }
+                    false
+                } else {
+                    entry.success_streak = 0;
+                    entry.consecutive_errors += 1;
+                    self.max_consecutive_errors > 0 && entry.consecutive_errors >= self.max_consecutive_errors
+                }
+            }
+            _ => false,
+        };
+
+        if evict && self.connections.remove_if(addr, |_, entry| Arc::ptr_eq(&entry.socket, socket)).is_some() {
+            self.evicted.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
 
 /// UDP连接池
 pub struct UdpConnectionPool {
-    connections: DashMap<String, Arc<UdpSocket>>,
+    inner: Arc<PoolInner>,
     runtime: Arc<Runtime>,
 }
 
 impl UdpConnectionPool {
-    /// 创建新的连接池
+    /// 创建新的连接池（连接永不过期，不因错误移除）
     pub fn new() -> Self {
-        let runtime = match Runtime::new() {
+        Self::with_settings(None, 0)
+    }
+
+    /// 使用连接存活时间和错误移除阈值创建连接池
+    pub fn with_settings(ttl: Option<Duration>, max_consecutive_errors: u32) -> Self {
+        let runtime = match Runtime::start() {
             Ok(rt) => Arc::new(rt),
             Err(e) => {
-                panic!("Failed to create tokio runtime: {}", e);
+                panic!("Failed to create async runtime: {}", e);
             }
         };
 
         Self {
-            connections: DashMap::new(),
+            inner: Arc::new(PoolInner {
+                connections: DashMap::new(),
+                ttl,
+                max_consecutive_errors,
+                created: AtomicU64::new(0),
+                expired: AtomicU64::new(0),
+                evicted: AtomicU64::new(0),
+            }),
             runtime,
         }
     }
 
-    /// 获取或创建UDP连接
-    async fn get_connection(&self, addr: &str) -> Option<Arc<UdpSocket>> {
-        if let Some(socket) = self.connections.get(addr) {
-            return Some(socket.clone());
-        }
+    /// 同步发送数据（阻塞当前线程，不能在异步上下文中调用）
+    ///
+    /// 地址按规范写法作为连接的键，`[::1]:5140` 和 `[0:0::1]:5140` 共用同一个连接。
+    pub fn send_blocking(&self, addr: &str, data: &[u8]) -> std::io::Result<()> {
+        let addr = crate::config::normalize_endpoint(addr);
+        self.runtime.block_on(self.inner.send_data(&addr, data))
+    }
 
-        match UdpSocket::bind("0.0.0.0:0").await {
-            Ok(socket) => {
-                if let Ok(()) = socket.connect(addr).await {
-                    let socket = Arc::new(socket);
-                    self.connections.insert(addr.to_string(), socket.clone());
-                    Some(socket)
-                } else {
-                    None
-                }
-            }
-            Err(_) => None,
-        }
+    /// 当前缓存的连接数
+    pub fn len(&self) -> usize {
+        self.inner.connections.len()
     }
 
-    /// 发送数据
-    async fn send_data(&self, addr: &str, data: &[u8]) -> std::io::Result<()> {
-        if let Some(socket) = self.get_connection(addr).await {
-            socket.send(data).await?;
-            Ok(())
-        } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::ConnectionRefused,
-                "Failed to establish UDP connection",
-            ))
+    /// 连接池是否为空
+    pub fn is_empty(&self) -> bool {
+        self.inner.connections.is_empty()
+    }
+
+    /// 连接池运行统计
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            connections: self.len(),
+            created: self.inner.created.load(Ordering::Relaxed),
+            expired: self.inner.expired.load(Ordering::Relaxed),
+            evicted: self.inner.evicted.load(Ordering::Relaxed),
         }
     }
 
     /// 清理连接
     fn cleanup(&self) {
-        self.connections.clear();
+        self.inner.connections.clear();
     }
 }
 
@@ -86,24 +226,345 @@ impl Drop for UdpConnectionPool {
     }
 }
 
+/// UDP发送重试策略 - 指数退避加随机抖动
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// 最多尝试次数（含首次发送）
+    pub max_attempts: u32,
+    /// 首次重试前的等待时间（配置文件中以毫秒表示，也可写作 `"100ms"`、`"2s"`）
+    #[serde(with = "crate::config::units::duration")]
+    pub initial_backoff: Duration,
+    /// 单次等待时间上限（配置文件中以毫秒表示，也可写作 `"100ms"`、`"2s"`）
+    #[serde(with = "crate::config::units::duration")]
+    pub max_backoff: Duration,
+    /// 抖动比例（0.0 - 1.0），实际等待时间在 `[(1 - jitter) * d, d]` 内随机，避免多个客户端同步重试
+    pub jitter: f32,
+}
+
+impl RetryPolicy {
+    /// 验证策略的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 {
+            return Err("配置错误: 重试次数不能为 0".to_string());
+        }
+        if self.max_attempts > 10 {
+            return Err("配置错误: 重试次数过多 (最大 10次)".to_string());
+        }
+        if self.initial_backoff > self.max_backoff {
+            return Err("配置错误: 初始退避时间不能大于最大退避时间".to_string());
+        }
+        if self.max_backoff > Duration::from_secs(60) {
+            return Err("配置错误: 最大退避时间过长 (最大 60秒)".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(format!("配置错误: 抖动比例必须在 0.0 到 1.0 之间，当前为 {}", self.jitter));
+        }
+        Ok(())
+    }
+
+    /// 第 `retry` 次重试（从 1 开始）前的基础等待时间，不含抖动
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    /// 第 `retry` 次重试前的实际等待时间（含抖动）
+    pub fn jittered_backoff(&self, retry: u32) -> Duration {
+        let base = self.backoff(retry);
+        if self.jitter <= 0.0 {
+            return base;
+        }
+        let scale = 1.0 - self.jitter as f64 * rand::thread_rng().r#gen::<f64>();
+        base.mul_f64(scale)
+    }
+
+    /// 一条数据用尽所有重试的最长等待时间
+    pub fn max_total_backoff(&self) -> Duration {
+        (1..self.max_attempts).map(|retry| self.backoff(retry)).sum()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.2,
+        }
+    }
+}
+
+/// 按重试策略执行发送，成功返回尝试次数，失败返回尝试次数和最后一次错误
+async fn send_with_retry<F, Fut>(policy: &RetryPolicy, mut send: F) -> Result<u32, (u32, std::io::Error)>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::io::Result<()>>,
+{
+    let mut attempt = 1;
+    loop {
+        match send().await {
+            Ok(()) => return Ok(attempt),
+            Err(e) if attempt >= policy.max_attempts => return Err((attempt, e)),
+            Err(_) => {
+                Runtime::sleep(policy.jittered_backoff(attempt)).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// 发送任务命令
+enum SendCommand {
+    /// 发送一个数据报（`records` 为其中的记录数，用于丢弃计数）
+    Datagram { data: Vec<u8>, records: usize },
+    /// 此前的数据报全部处理完后回复
+    Flush(std::sync::mpsc::Sender<()>),
+}
+
+/// 发送任务：按顺序发送数据报，失败时按策略退避重试，重试耗尽后上报诊断事件
+///
+/// 启用离线缓存时，重试耗尽的数据报写入缓存而不是丢弃；缓存不为空时新数据报也写入缓存以保持顺序，
+/// 启动、每次发送和刷新时先尝试重放缓存。
+async fn sender_task(
+    mut receiver: UnboundedReceiver<SendCommand>,
+    pool: Arc<PoolInner>,
+    addr: String,
+    policy: RetryPolicy,
+    diagnostics: Diagnostics,
+    mut spool: Option<Spool>,
+) {
+    if let Some(spool) = spool.as_mut() {
+        replay_spool(spool, &pool, &addr).await;
+    }
+
+    while let Some(command) = receiver.next().await {
+        match command {
+            SendCommand::Datagram { data, records } => {
+                if let Some(spool) = spool.as_mut().filter(|spool| !spool.is_empty())
+                    && !replay_spool(spool, &pool, &addr).await
+                {
+                    spool_datagram(spool, &data, records, 0, "离线缓存中仍有未发送的数据", &diagnostics);
+                    continue;
+                }
+
+                let result = send_with_retry(&policy, || pool.send_data(&addr, &data)).await;
+
+                if let Err((attempts, e)) = result {
+                    match spool.as_mut() {
+                        Some(spool) => spool_datagram(spool, &data, records, attempts, &e.to_string(), &diagnostics),
+                        None => diagnostics.emit(DiagnosticEvent::SendFailed {
+                            processor: "udp_processor",
+                            attempts,
+                            dropped: records,
+                            error: e.to_string(),
+                        }),
+                    }
+                }
+            }
+            SendCommand::Flush(ack) => {
+                if let Some(spool) = spool.as_mut() {
+                    replay_spool(spool, &pool, &addr).await;
+                }
+                let _ = ack.send(());
+            }
+        }
+    }
+}
+
+/// 按顺序重放离线缓存（不重试），全部发送成功时返回 true
+async fn replay_spool(spool: &mut Spool, pool: &PoolInner, addr: &str) -> bool {
+    while let Some((index, frames)) = spool.pending_frames() {
+        for frame in frames {
+            if pool.send_data(addr, &frame).await.is_err() {
+                return false;
+            }
+            spool.mark_sent(index);
+        }
+        spool.finish_segment(index);
+    }
+    true
+}
+
+/// 把数据报写入离线缓存，写入失败时按发送失败上报
+fn spool_datagram(spool: &mut Spool, data: &[u8], records: usize, attempts: u32, reason: &str, diagnostics: &Diagnostics) {
+    if let Err(e) = spool.append(data) {
+        diagnostics.emit(DiagnosticEvent::SendFailed {
+            processor: "udp_processor",
+            attempts,
+            dropped: records,
+            error: format!("{}，写入离线缓存失败: {}", reason, e),
+        });
+    }
+}
+
+/// 连通性探测设置（取自 `UdpConfig`）
+#[derive(Debug, Clone, Copy)]
+struct ProbeSettings {
+    interval: Duration,
+    timeout: Duration,
+    expect_ack: bool,
+    max_failed: u32,
+}
+
+/// 探测任务：按间隔发送探测数据报，连续失败达到阈值时上报降级，之后首次成功时上报恢复
+///
+/// 停止通道的发送端被释放时退出。
+async fn probe_task(
+    pool: Arc<PoolInner>,
+    addr: String,
+    settings: ProbeSettings,
+    diagnostics: Diagnostics,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let mut next_tick = Instant::now();
+    let mut next_id = 0u64;
+    let mut failures = 0u32;
+    let mut degraded_since: Option<Instant> = None;
+
+    loop {
+        let wait = next_tick.saturating_duration_since(Instant::now());
+        if let Either::Left(_) = select(&mut stop, std::pin::pin!(Runtime::sleep(wait))).await {
+            break;
+        }
+        // 探测耗时超过间隔时立即开始下一次，之后重新按间隔计时
+        next_tick = (next_tick + settings.interval).max(Instant::now());
+
+        next_id += 1;
+        if probe_once(&pool, &addr, next_id, &settings).await {
+            failures = 0;
+            if let Some(since) = degraded_since.take() {
+                diagnostics.emit(DiagnosticEvent::ProcessorHealthy {
+                    processor: "udp_processor",
+                    degraded_for: since.elapsed(),
+                });
+            }
+        } else {
+            failures += 1;
+            if degraded_since.is_none() && failures >= settings.max_failed {
+                degraded_since = Some(Instant::now());
+                diagnostics.emit(DiagnosticEvent::ProcessorDegraded {
+                    processor: "udp_processor",
+                    failed_probes: failures,
+                });
+            }
+        }
+    }
+
+    // 处理器已停止，不再计入降级的处理器数
+    if degraded_since.is_some() {
+        diagnostics.stats().remove_degraded();
+    }
+}
+
+/// 发送一次探测；要求应答时等待编号匹配的应答，超时或收到错误视为失败
+async fn probe_once(pool: &PoolInner, addr: &str, id: u64, settings: &ProbeSettings) -> bool {
+    let Ok(socket) = pool.get_connection(addr).await else {
+        return false;
+    };
+    let sent = Runtime::send(&socket, &UdpPacketHelper::encode_probe(ProbeKind::Probe, id)).await.is_ok();
+    pool.record_result(addr, &socket, sent);
+    if !sent || !settings.expect_ack {
+        return sent;
+    }
+
+    let ack = async {
+        let mut buf = [0u8; PROBE_LEN];
+        loop {
+            match Runtime::recv(&socket, &mut buf).await {
+                // 忽略已超时的探测迟到的应答
+                Ok(len) => {
+                    if UdpPacketHelper::parse_probe(&buf[..len]).is_ok_and(|reply| reply == (ProbeKind::Ack, id)) {
+                        return true;
+                    }
+                }
+                // 已连接的套接字收到 ICMP 端口不可达时报告错误
+                Err(_) => return false,
+            }
+        }
+    };
+    udp_runtime::timeout::<Runtime, _>(settings.timeout, ack).await.unwrap_or(false)
+}
+
 /// UDP处理器配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UdpConfig {
     /// 网络配置
     pub network_config: NetworkConfig,
-    /// 重试次数
-    pub retry_count: u32,
+    /// 发送重试策略
+    pub retry: RetryPolicy,
+    /// 每个数据报最多合并的记录数（1 表示每条记录单独发送）
+    pub batch_size: usize,
+    /// 单个批量数据报的字节上限
+    pub max_batch_bytes: usize,
+    /// 记录在发送端最长等待时间（毫秒），到时即使未满也会发送
+    pub max_wait_time_ms: u64,
+    /// 连接存活时间（毫秒），到期后重新解析地址并重新连接（0 表示永不过期）
+    pub connection_ttl_ms: u64,
+    /// 连续发送错误达到该次数后丢弃连接并重新建立（0 表示不丢弃）
+    pub max_consecutive_errors: u32,
+    /// 是否接收强制写入（紧急日志和 Error 级别日志），为 false 时这些记录改走普通批量路径
+    pub accept_emergency: bool,
+    /// 是否接收 rat_logger 自身产生的记录（目标为 `rat_logger` 或以 `rat_logger::` 开头，如熔断汇总和启动横幅）
+    pub include_internal: bool,
+    /// 是否要求配置认证令牌（为 true 时 `auth_token` 不能为空）
+    pub require_auth_token: bool,
+    /// 通过 `LoggerBuilder` 构建时是否先解析服务器地址并建立连接，失败时构建失败而不是在发送时才报错
+    pub verify_connectivity: bool,
+    /// 连通性探测间隔（毫秒，0 表示不探测）：按间隔向服务器发送探测数据报，
+    /// 连续 `max_failed_probes` 次失败时处理器降级（上报 `ProcessorDegraded`，计入 `LoggerStats::degraded`），首次成功时恢复
+    pub probe_interval_ms: u64,
+    /// 探测是否要求服务器应答（`UdpPacketHelper::probe_ack`）；为 false 时只有发送错误才算失败
+    pub expect_ack: bool,
+    /// 等待探测应答的超时时间（毫秒）
+    pub probe_timeout_ms: u64,
+    /// 连续失败多少次探测后降级
+    pub max_failed_probes: u32,
+    /// 数据包中发送的字段（默认全部发送），按流量计费的链路上可以省略目标、文件路径等
+    pub payload_fields: PayloadFields,
+    /// 离线缓存（默认不启用）：重试耗尽的数据报写入磁盘，连接恢复后按顺序重放，状态见 `LoggerStats::spool`
+    pub spool: Option<SpoolConfig>,
 }
 
 impl UdpConfig {
     /// 验证配置的有效性
     pub fn validate(&self) -> Result<(), String> {
-        // 验证重试次数
-        if self.retry_count == 0 {
-            return Err("配置错误: 重试次数不能为 0".to_string());
+        // 验证网络配置
+        self.network_config.validate()?;
+        if self.require_auth_token && self.network_config.auth_token.is_empty() {
+            return Err("配置错误: 认证令牌不能为空".to_string());
         }
-        if self.retry_count > 10 {
-            return Err("配置错误: 重试次数过多 (最大 10次)".to_string());
+
+        // 验证重试策略
+        self.retry.validate()?;
+
+        // 验证批量发送参数
+        if self.batch_size == 0 {
+            return Err("配置错误: 批量记录数不能为 0".to_string());
+        }
+        if self.batch_size > u16::MAX as usize {
+            return Err(format!("配置错误: 批量记录数过大 (最大 {})", u16::MAX));
+        }
+        if self.max_batch_bytes < 512 || self.max_batch_bytes > MAX_DATAGRAM_SIZE {
+            return Err(format!("配置错误: 批量数据报字节上限必须在 512 到 {} 之间", MAX_DATAGRAM_SIZE));
+        }
+        if self.max_wait_time_ms > 60_000 {
+            return Err("配置错误: 最长等待时间过长 (最大 60秒)".to_string());
+        }
+
+        // 验证连通性探测参数
+        if self.probe_interval_ms > 0 {
+            if self.probe_timeout_ms == 0 || self.probe_timeout_ms > 60_000 {
+                return Err("配置错误: 探测应答超时时间必须在 1 毫秒到 60秒之间".to_string());
+            }
+            if self.max_failed_probes == 0 {
+                return Err("配置错误: 降级前的探测失败次数不能为 0".to_string());
+            }
+        }
+
+        if let Some(spool) = &self.spool {
+            spool.validate()?;
         }
 
         Ok(())
@@ -114,15 +575,55 @@ impl Default for UdpConfig {
     fn default() -> Self {
         Self {
             network_config: NetworkConfig::default(),
-            retry_count: 3,
+            retry: RetryPolicy::default(),
+            batch_size: 1,
+            max_batch_bytes: 1400, // 不超过常见 MTU，避免 IP 分片
+            max_wait_time_ms: 100,
+            connection_ttl_ms: 300_000,
+            max_consecutive_errors: 5,
+            accept_emergency: true,
+            include_internal: true,
+            require_auth_token: false,
+            verify_connectivity: false,
+            probe_interval_ms: 0,
+            expect_ack: false,
+            probe_timeout_ms: 1000,
+            max_failed_probes: 3,
+            payload_fields: PayloadFields::ALL,
+            spool: None,
         }
     }
 }
 
+impl UdpConfig {
+    /// 解析服务器地址并建立连接（UDP 连接不发送数据），检查地址能否解析和路由
+    pub fn check_connectivity(&self) -> Result<(), String> {
+        use std::net::ToSocketAddrs;
+
+        let network = &self.network_config;
+        let addr = (network.host(), network.server_port)
+            .to_socket_addrs()
+            .map_err(|e| format!("无法解析服务器地址 {}: {}", network.server_addr, e))?
+            .next()
+            .ok_or_else(|| format!("服务器地址 {} 没有可用的解析结果", network.server_addr))?;
+
+        let local = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        std::net::UdpSocket::bind(local)
+            .and_then(|socket| socket.connect(addr))
+            .map_err(|e| format!("无法连接服务器 {}: {}", addr, e))
+    }
+}
+
 /// UDP日志处理器 - 实现LogProcessor trait
 pub struct UdpProcessor {
     config: UdpConfig,
     pool: Arc<UdpConnectionPool>,
+    batcher: UdpBatchProcessor,
+    diagnostics: Diagnostics,
+    /// 发送任务的命令通道（首次发送时启动任务）
+    sender: Option<UnboundedSender<SendCommand>>,
+    /// 探测任务的停止通道（释放时探测任务退出）
+    probe_stop: Option<oneshot::Sender<()>>,
 }
 
 impl UdpProcessor {
@@ -142,51 +643,149 @@ impl UdpProcessor {
             panic!("UdpConfig 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e);
         }
 
+        let batcher = UdpBatchProcessor::new(config.batch_size, config.max_wait_time_ms)
+            .with_max_batch_bytes(config.max_batch_bytes);
+
+        let ttl = (config.connection_ttl_ms > 0).then(|| Duration::from_millis(config.connection_ttl_ms));
+        let pool = UdpConnectionPool::with_settings(ttl, config.max_consecutive_errors);
+
         Self {
             config,
-            pool: Arc::new(UdpConnectionPool::new()),
+            pool: Arc::new(pool),
+            batcher,
+            diagnostics: Diagnostics::new(),
+            sender: None,
+            probe_stop: None,
         }
     }
 
     /// 设置重试次数
     pub fn with_retry_count(mut self, retry_count: u32) -> Self {
-        self.config.retry_count = retry_count;
+        self.config.retry.max_attempts = retry_count;
         self
     }
 
+    /// 设置重试策略
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.config.retry = retry;
+        self
+    }
+
+    /// 处理器使用的配置
+    pub fn config(&self) -> &UdpConfig {
+        &self.config
+    }
+
+    /// 获取连接池（用于查看连接数和统计）
+    pub fn pool(&self) -> &Arc<UdpConnectionPool> {
+        &self.pool
+    }
+
+    /// 原样转发已编码的 `NetRecord` 数据包（中继场景）
+    ///
+    /// 只检查数据包头部（见 [`PacketMetadata::parse_header`]），不解码消息体，也不改写认证令牌和应用ID；
+    /// 数据包与本处理器编码的记录一样参与批量发送。
+    pub fn forward_packet(&mut self, bytes: &[u8]) -> Result<(), ProcessorError> {
+        PacketMetadata::parse_header(bytes)
+            .map_err(|e| ProcessorError::new(ErrorKind::Decode, format!("无效的转发数据包: {}", e)))?;
+        self.enqueue_packet(bytes.to_vec())
+    }
+
     /// 编码日志记录
+    ///
+    /// 应用ID优先使用网络配置，其次是记录自带的应用ID，最后是可执行文件名。
     fn encode_record(&self, record: &Record) -> Result<Vec<u8>, String> {
-        UdpPacketHelper::encode_record(
+        let app_id = [
+            self.config.network_config.app_id.as_str(),
+            record.metadata.app_id.as_deref().unwrap_or(""),
+            crate::process_info::binary_name(),
+        ]
+        .into_iter()
+        .find(|app_id| !app_id.is_empty())
+        .map(str::to_string);
+        UdpPacketHelper::encode_record_fields(
             record,
             Some(self.config.network_config.auth_token.clone()),
-            Some(self.config.network_config.app_id.clone()),
+            app_id,
+            self.config.payload_fields,
         ).map_err(|e| format!("UDP编码失败: {}", e))
     }
 
-    /// 直接发送UDP数据
-    fn send_udp_data(&self, data: &[u8]) -> Result<(), String> {
-        let addr = format!("{}:{}", self.config.network_config.server_addr, self.config.network_config.server_port);
-        let pool = Arc::clone(&self.pool);
-        let retry_count = self.config.retry_count;
-
-        // 在当前线程的运行时中异步发送
-        pool.runtime.block_on(async {
-            for attempt in 0..retry_count {
-                match pool.send_data(&addr, data).await {
-                    Ok(_) => break,
-                    Err(e) => {
-                        if attempt == retry_count - 1 {
-                            eprintln!("[udp] 发送失败，重试{}次后放弃: {}", retry_count, e);
-                        } else {
-                            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                        }
-                    }
-                }
-            }
-        });
+    /// 将编码后的数据包交给批处理器，发送已就绪的数据报
+    fn enqueue_packet(&mut self, packet: Vec<u8>) -> Result<(), ProcessorError> {
+        if self.config.batch_size <= 1 {
+            return self.send_udp_data(packet);
+        }
 
+        for datagram in self.batcher.push(packet) {
+            self.send_udp_data(datagram)?;
+        }
         Ok(())
     }
+
+    /// 发送批处理器中所有待发送的数据包
+    fn ship_pending(&mut self) -> Result<(), ProcessorError> {
+        match self.batcher.take_datagram() {
+            Some(datagram) => self.send_udp_data(datagram),
+            None => Ok(()),
+        }
+    }
+
+    /// 获取发送任务的命令通道，首次调用时启动发送任务
+    fn sender(&mut self) -> &UnboundedSender<SendCommand> {
+        self.sender.get_or_insert_with(|| {
+            let (sender, receiver) = unbounded();
+            let addr = self.config.network_config.endpoint();
+            // 缓存目录无法打开时上报错误，之后按未启用缓存发送
+            let spool = self.config.spool.clone().and_then(|config| {
+                let dir = config.dir.clone();
+                Spool::open(config, self.diagnostics.clone())
+                    .map_err(|e| {
+                        self.diagnostics.emit(DiagnosticEvent::ProcessorFailed {
+                            processor: "udp_processor",
+                            kind: ErrorKind::Io,
+                            attempts: 1,
+                            error: format!("无法打开离线缓存目录 {}: {}", dir.display(), e),
+                        })
+                    })
+                    .ok()
+            });
+            self.pool.runtime.spawn(sender_task(
+                receiver,
+                Arc::clone(&self.pool.inner),
+                addr,
+                self.config.retry.clone(),
+                self.diagnostics.clone(),
+                spool,
+            ));
+            sender
+        })
+    }
+
+    /// 将数据报交给发送任务（不阻塞工作线程）
+    fn send_udp_data(&mut self, data: Vec<u8>) -> Result<(), ProcessorError> {
+        let records = UdpPacketHelper::record_count(&data);
+        self.sender()
+            .unbounded_send(SendCommand::Datagram { data, records })
+            .map_err(|_| ProcessorError::new(ErrorKind::Shutdown, "UDP发送任务已退出"))
+    }
+
+    /// 等待发送任务处理完此前的所有数据报
+    fn wait_sender_idle(&mut self) -> Result<(), ProcessorError> {
+        let Some(sender) = self.sender.as_ref() else {
+            return Ok(());
+        };
+
+        let (ack_tx, ack_rx) = std::sync::mpsc::channel();
+        sender
+            .unbounded_send(SendCommand::Flush(ack_tx))
+            .map_err(|_| ProcessorError::new(ErrorKind::Shutdown, "UDP发送任务已退出"))?;
+
+        let timeout = self.config.retry.max_total_backoff() + Duration::from_secs(1);
+        ack_rx
+            .recv_timeout(timeout)
+            .map_err(|_| ProcessorError::new(ErrorKind::Backpressure, format!("等待UDP发送任务超时 ({:?})", timeout)))
+    }
 }
 
 impl LogProcessor for UdpProcessor {
@@ -194,43 +793,108 @@ impl LogProcessor for UdpProcessor {
         "udp_processor"
     }
 
-    fn process(&mut self, data: &[u8]) -> Result<(), String> {
+    fn accepts_emergency(&self) -> bool {
+        self.config.accept_emergency
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
         // 反序列化日志记录
-        let record = bincode::decode_from_slice::<Record, _>(data, bincode::config::standard())
-            .map_err(|e| format!("反序列化失败: {}", e))?.0;
+        let record = decode_record(data)?;
+        if !self.config.include_internal && record.metadata.is_internal() {
+            return Ok(());
+        }
 
         // 编码为UDP包
         let encoded_data = self.encode_record(&record)?;
 
-        // 直接发送UDP数据（不使用内部缓冲）
-        self.send_udp_data(&encoded_data)
+        self.enqueue_packet(encoded_data)
     }
 
-    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), String> {
-        let mut all_data = Vec::new();
-
-        // 批量反序列化和编码
+    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        // 逐条编码后交给批处理器，按记录数和字节上限封装为批量数据报；无法反序列化的记录跳过
+        let mut decode_error = None;
         for data in batch {
-            let record = bincode::decode_from_slice::<Record, _>(data, bincode::config::standard())
-                .map_err(|e| format!("批量反序列化失败: {}", e))?.0;
+            let record = match decode_record(data) {
+                Ok(record) => record,
+                Err(e) => {
+                    decode_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            if !self.config.include_internal && record.metadata.is_internal() {
+                continue;
+            }
 
             let encoded_data = self.encode_record(&record)?;
-            all_data.extend_from_slice(&encoded_data);
+            self.enqueue_packet(encoded_data)?;
         }
 
-        // 批量发送
-        self.send_udp_data(&all_data)
+        decode_error.map_or(Ok(()), Err)
     }
 
-    fn flush(&mut self) -> Result<(), String> {
-        // UDP处理器没有内部缓冲，直接返回成功
-        Ok(())
+    fn process_packet_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        // 无效的数据包跳过，不影响同批次的其他数据包
+        let mut invalid = None;
+        for packet in batch {
+            if let Err(e) = self.forward_packet(packet) {
+                if e.kind != ErrorKind::Decode {
+                    return Err(e);
+                }
+                invalid.get_or_insert(e);
+            }
+        }
+        invalid.map_or(Ok(()), Err)
+    }
+
+    fn pending_deadline(&self) -> Option<Instant> {
+        self.batcher.deadline()
     }
 
-    fn cleanup(&mut self) -> Result<(), String> {
-        // 清理连接池
+    fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        self.diagnostics = diagnostics;
+    }
+
+    fn on_worker_start(&mut self) {
+        // 启用离线缓存时立即启动发送任务，重放上次运行留下的数据
+        if self.config.spool.is_some() {
+            self.sender();
+        }
+
+        // 启用连通性探测时启动探测任务
+        if self.config.probe_interval_ms == 0 || self.probe_stop.is_some() {
+            return;
+        }
+        let (stop, stopped) = oneshot::channel();
+        let addr = self.config.network_config.endpoint();
+        let settings = ProbeSettings {
+            interval: Duration::from_millis(self.config.probe_interval_ms),
+            timeout: Duration::from_millis(self.config.probe_timeout_ms),
+            expect_ack: self.config.expect_ack,
+            max_failed: self.config.max_failed_probes,
+        };
+        self.pool.runtime.spawn(probe_task(
+            Arc::clone(&self.pool.inner),
+            addr,
+            settings,
+            self.diagnostics.clone(),
+            stopped,
+        ));
+        self.probe_stop = Some(stop);
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        // 发送批处理器中剩余的记录，并等待发送任务处理完毕
+        self.ship_pending()?;
+        self.wait_sender_idle()
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        // 先发送剩余记录，再停止发送任务并清理连接池
+        let result = self.flush();
+        self.sender = None;
+        self.probe_stop = None;
         self.pool.cleanup();
-        Ok(())
+        result
     }
 }
 
@@ -239,4 +903,137 @@ impl Drop for UdpProcessor {
         // 清理时会自动调用cleanup
         let _ = self.cleanup();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 重试间隔由 `Runtime::sleep` 计时，只有 Tokio 运行时能用暂停的时钟验证
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_follows_exponential_schedule() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            jitter: 0.0,
+        };
+
+        let start = tokio::time::Instant::now();
+        let mut attempts_at = Vec::new();
+        let result = send_with_retry(&policy, || {
+            attempts_at.push(start.elapsed());
+            async { Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "port unreachable")) }
+        }).await;
+
+        let (attempts, _) = result.unwrap_err();
+        assert_eq!(attempts, 5);
+        // 等待间隔 100ms, 200ms, 400ms, 500ms（封顶）
+        let expected: Vec<Duration> = [0, 100, 300, 700, 1200].iter().map(|ms| Duration::from_millis(*ms)).collect();
+        assert_eq!(attempts_at, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_jitter_stays_within_bounds() {
+        let policy = RetryPolicy { jitter: 0.5, ..Default::default() };
+        for retry in 1..=4 {
+            let base = policy.backoff(retry);
+            let jittered = policy.jittered_backoff(retry);
+            assert!(jittered <= base && jittered >= base / 2, "retry {}: {:?} 不在 {:?} 的抖动范围内", retry, jittered, base);
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_from_config_file() {
+        let policy: RetryPolicy = serde_json::from_str(r#"{"max_attempts": 4, "initial_backoff": 50}"#).unwrap();
+        assert_eq!(policy.max_attempts, 4);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(50));
+        assert_eq!(policy.max_backoff, RetryPolicy::default().max_backoff);
+        assert!(policy.validate().is_ok());
+    }
+
+    #[test]
+    fn test_pool_evicts_after_errors_and_reconnects() {
+        let listener = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let addr = format!("127.0.0.1:{}", port);
+        let pool = UdpConnectionPool::with_settings(None, 3);
+
+        pool.send_blocking(&addr, b"before").unwrap();
+        assert_eq!(pool.len(), 1);
+
+        // 监听端关闭后，端口不可达错误会在后续发送时报告
+        drop(listener);
+        let mut errors = 0;
+        for _ in 0..20 {
+            if pool.send_blocking(&addr, b"lost").is_err() {
+                errors += 1;
+            }
+            if pool.stats().evicted > 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(errors, 3, "应当在第 3 次错误时移除连接");
+        assert_eq!(pool.stats().evicted, 1);
+        assert!(pool.is_empty());
+
+        // 服务端恢复后重新建立连接
+        let listener = std::net::UdpSocket::bind(("127.0.0.1", port)).unwrap();
+        listener.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        pool.send_blocking(&addr, b"after").unwrap();
+        let mut buf = [0u8; 16];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"after");
+        assert_eq!(pool.stats().created, 2);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_pool_reconnects_after_ttl_with_hostname_target() {
+        // 监听 localhost 解析出的第一个地址（可能是 IPv4 或 IPv6）
+        use std::net::ToSocketAddrs;
+        let local = ("localhost", 0).to_socket_addrs().unwrap().next().unwrap();
+        let listener = std::net::UdpSocket::bind(local).unwrap();
+        listener.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+        let pool = UdpConnectionPool::with_settings(Some(Duration::from_millis(50)), 0);
+
+        let mut buf = [0u8; 16];
+        pool.send_blocking(&addr, b"first").unwrap();
+        pool.send_blocking(&addr, b"second").unwrap();
+        assert_eq!(pool.stats().created, 1);
+
+        std::thread::sleep(Duration::from_millis(80));
+        pool.send_blocking(&addr, b"third").unwrap();
+        let stats = pool.stats();
+        assert_eq!((stats.created, stats.expired, stats.connections), (2, 1, 1));
+
+        for expected in [&b"first"[..], b"second", b"third"] {
+            let len = listener.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..len], expected);
+        }
+    }
+    #[test]
+    fn test_pool_keys_are_normalized() {
+        use std::net::ToSocketAddrs;
+        let local = ("localhost", 0).to_socket_addrs().unwrap().next().unwrap();
+        let listener = std::net::UdpSocket::bind(local).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let pool = UdpConnectionPool::new();
+
+        // 同一地址的不同写法共用一个连接
+        pool.send_blocking(&format!("LOCALHOST:{}", port), b"first").unwrap();
+        pool.send_blocking(&format!("localhost:{}", port), b"second").unwrap();
+        assert_eq!(pool.stats().created, 1);
+        // IPv6 地址的完整写法与简写相同
+        let expanded = match local.ip() {
+            std::net::IpAddr::V4(ip) => format!("{}:{}", ip, port),
+            std::net::IpAddr::V6(ip) => format!("[{}]:{}", ip.segments().map(|s| format!("{:04x}", s)).join(":"), port),
+        };
+        pool.send_blocking(&expanded, b"third").unwrap();
+        pool.send_blocking(&std::net::SocketAddr::new(local.ip(), port).to_string(), b"fourth").unwrap();
+        assert_eq!(pool.stats().created, 2);
+    }
+}
@@ -0,0 +1,251 @@
+//! UDP 发送任务的异步运行时 - 发送和探测任务只通过 [`UdpRuntime`] 使用运行时
+//!
+//! `UdpConnectionPool` 创建时启动运行时：`tokio-runtime` 特性（默认）使用独立的 Tokio 多线程运行时
+//! （[`TokioRuntime`]），`smol-runtime` 特性使用 async-io 反应器和单线程的 smol 执行器（[`SmolRuntime`]），
+//! 两个特性必须且只能启用一个。任务之间的通道使用 `futures` 提供的与运行时无关的实现。
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::pin;
+use std::time::Duration;
+
+use futures::future::{select, Either};
+
+#[cfg(all(feature = "tokio-runtime", feature = "smol-runtime"))]
+compile_error!("rat_logger: `tokio-runtime` 和 `smol-runtime` 特性只能启用一个（使用 smol 时需设置 default-features = false）");
+
+#[cfg(not(any(feature = "tokio-runtime", feature = "smol-runtime")))]
+compile_error!("rat_logger: 需要启用 `tokio-runtime`（默认）或 `smol-runtime` 特性之一，作为 UDP 发送任务的运行时");
+
+/// 发送任务使用的运行时接口
+pub(crate) trait UdpRuntime: Send + Sync + Sized + 'static {
+    /// 运行时的 UDP 套接字
+    type Socket: Send + Sync + 'static;
+
+    /// 启动运行时
+    fn start() -> io::Result<Self>;
+
+    /// 在当前线程上运行 `future` 直到完成（不能在异步上下文中调用）
+    fn block_on<F: Future>(&self, future: F) -> F::Output;
+
+    /// 在运行时中启动后台任务
+    fn spawn(&self, future: impl Future<Output = ()> + Send + 'static);
+
+    /// 绑定本地地址
+    fn bind(addr: SocketAddr) -> impl Future<Output = io::Result<Self::Socket>> + Send;
+
+    /// 连接到远端地址，之后的 `send`/`recv` 只与该地址通信
+    fn connect(socket: &Self::Socket, addr: SocketAddr) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// 发送一个数据报
+    fn send(socket: &Self::Socket, data: &[u8]) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// 接收一个数据报
+    fn recv(socket: &Self::Socket, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// 解析地址（支持主机名）
+    fn resolve(addr: &str) -> impl Future<Output = io::Result<Vec<SocketAddr>>> + Send;
+
+    /// 等待 `duration`
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+}
+
+/// 当前使用的运行时
+#[cfg(feature = "tokio-runtime")]
+pub(crate) type ActiveRuntime = TokioRuntime;
+
+/// 当前使用的运行时
+#[cfg(all(feature = "smol-runtime", not(feature = "tokio-runtime")))]
+pub(crate) type ActiveRuntime = SmolRuntime;
+
+/// 在 `duration` 内等待 `future` 完成，超时返回 `None`
+pub(crate) async fn timeout<R: UdpRuntime, F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    match select(pin!(future), pin!(R::sleep(duration))).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+/// Tokio 运行时
+#[cfg(feature = "tokio-runtime")]
+pub(crate) struct TokioRuntime(tokio::runtime::Runtime);
+
+#[cfg(feature = "tokio-runtime")]
+impl UdpRuntime for TokioRuntime {
+    type Socket = tokio::net::UdpSocket;
+
+    fn start() -> io::Result<Self> {
+        tokio::runtime::Runtime::new().map(Self)
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0.block_on(future)
+    }
+
+    fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        self.0.spawn(future);
+    }
+
+    fn bind(addr: SocketAddr) -> impl Future<Output = io::Result<Self::Socket>> + Send {
+        tokio::net::UdpSocket::bind(addr)
+    }
+
+    fn connect(socket: &Self::Socket, addr: SocketAddr) -> impl Future<Output = io::Result<()>> + Send {
+        socket.connect(addr)
+    }
+
+    fn send(socket: &Self::Socket, data: &[u8]) -> impl Future<Output = io::Result<usize>> + Send {
+        socket.send(data)
+    }
+
+    fn recv(socket: &Self::Socket, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send {
+        socket.recv(buf)
+    }
+
+    async fn resolve(addr: &str) -> io::Result<Vec<SocketAddr>> {
+        Ok(tokio::net::lookup_host(addr).await?.collect())
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+}
+
+/// smol 执行器线程名
+#[cfg(feature = "smol-runtime")]
+const SMOL_THREAD_NAME: &str = "rat-udp-smol";
+
+/// smol 运行时：在单独的线程上运行执行器，套接字和定时器由 async-io 反应器驱动
+///
+/// 释放时执行器线程退出，尚未完成的任务被取消（与 Tokio 运行时相同）。
+#[cfg(feature = "smol-runtime")]
+pub(crate) struct SmolRuntime {
+    executor: std::sync::Arc<smol::Executor<'static>>,
+    /// 释放时关闭，执行器线程随之退出
+    _stop: smol::channel::Sender<()>,
+}
+
+#[cfg(feature = "smol-runtime")]
+impl UdpRuntime for SmolRuntime {
+    type Socket = smol::net::UdpSocket;
+
+    fn start() -> io::Result<Self> {
+        let executor = std::sync::Arc::new(smol::Executor::new());
+        let (stop, stopped) = smol::channel::bounded::<()>(1);
+        let runner = executor.clone();
+        std::thread::Builder::new().name(SMOL_THREAD_NAME.to_string()).spawn(move || {
+            smol::block_on(runner.run(async move {
+                let _ = stopped.recv().await;
+            }))
+        })?;
+        Ok(Self { executor, _stop: stop })
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        smol::block_on(future)
+    }
+
+    fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        self.executor.spawn(future).detach();
+    }
+
+    fn bind(addr: SocketAddr) -> impl Future<Output = io::Result<Self::Socket>> + Send {
+        smol::net::UdpSocket::bind(addr)
+    }
+
+    fn connect(socket: &Self::Socket, addr: SocketAddr) -> impl Future<Output = io::Result<()>> + Send {
+        socket.connect(addr)
+    }
+
+    fn send(socket: &Self::Socket, data: &[u8]) -> impl Future<Output = io::Result<usize>> + Send {
+        socket.send(data)
+    }
+
+    fn recv(socket: &Self::Socket, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send {
+        socket.recv(buf)
+    }
+
+    fn resolve(addr: &str) -> impl Future<Output = io::Result<Vec<SocketAddr>>> + Send {
+        smol::net::resolve(addr.to_string())
+    }
+
+    async fn sleep(duration: Duration) {
+        smol::Timer::after(duration).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_send_and_timeout() {
+        let runtime = ActiveRuntime::start().unwrap();
+        runtime.block_on(async {
+            let listener = ActiveRuntime::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await.unwrap();
+            let target = ActiveRuntime::resolve(&format!("localhost:{}", local_port(&listener))).await.unwrap();
+            assert!(!target.is_empty());
+
+            let socket = ActiveRuntime::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await.unwrap();
+            ActiveRuntime::connect(&socket, SocketAddr::from(([127, 0, 0, 1], local_port(&listener)))).await.unwrap();
+            assert_eq!(ActiveRuntime::send(&socket, b"ping").await.unwrap(), 4);
+            let mut buf = [0u8; 8];
+            let len = timeout::<ActiveRuntime, _>(Duration::from_secs(5), ActiveRuntime::recv(&listener, &mut buf)).await;
+            assert_eq!(&buf[..len.unwrap().unwrap()], b"ping");
+
+            // 没有数据报时超时
+            let mut buf = [0u8; 8];
+            assert!(timeout::<ActiveRuntime, _>(Duration::from_millis(20), ActiveRuntime::recv(&listener, &mut buf)).await.is_none());
+        });
+    }
+
+    fn local_port(socket: &<ActiveRuntime as UdpRuntime>::Socket) -> u16 {
+        socket.local_addr().unwrap().port()
+    }
+
+    /// 启动运行时并在其中运行一个后台任务，返回 `probe` 在任务中的结果
+    fn run_in_spawned_task<T: Send + 'static>(probe: fn() -> T) -> T {
+        let runtime = ActiveRuntime::start().unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        runtime.spawn(async move {
+            ActiveRuntime::sleep(Duration::from_millis(1)).await;
+            let _ = sender.send(probe());
+        });
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap()
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    mod tokio_runtime {
+        use super::*;
+
+        #[test]
+        fn test_spawn_runs_inside_tokio_runtime() {
+            assert!(run_in_spawned_task(|| tokio::runtime::Handle::try_current().is_ok()));
+        }
+    }
+
+    #[cfg(feature = "smol-runtime")]
+    mod smol_runtime {
+        use super::*;
+
+        #[test]
+        fn test_spawn_runs_on_executor_thread() {
+            let thread = run_in_spawned_task(|| std::thread::current().name().map(str::to_string));
+            assert_eq!(thread.as_deref(), Some(SMOL_THREAD_NAME));
+        }
+
+        #[test]
+        fn test_drop_cancels_pending_tasks() {
+            let runtime = ActiveRuntime::start().unwrap();
+            let (sender, receiver) = std::sync::mpsc::channel::<()>();
+            // 任务持有发送端，运行时释放后任务被取消，发送端随之释放
+            runtime.spawn(async move {
+                let _sender = sender;
+                std::future::pending::<()>().await;
+            });
+            drop(runtime);
+            assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Err(std::sync::mpsc::RecvTimeoutError::Disconnected));
+        }
+    }
+}
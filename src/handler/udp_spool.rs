@@ -0,0 +1,437 @@
+//! UDP 离线缓存 - 发送失败的数据报按顺序写入磁盘上的分段文件，连接恢复后按序重放
+//!
+//! 缓存目录中是编号递增的分段文件（`spool-000001.bin`、`spool-000002.bin` …），每个分段不超过
+//! `SpoolConfig::segment_bytes`。分段由若干帧组成，每帧为：
+//!
+//! ```text
+//! 长度 (u32 LE) | CRC-32 (u32 LE) | 写入时间 (u64 LE，Unix 毫秒) | 数据报
+//! ```
+//!
+//! CRC 覆盖写入时间和数据报。进程崩溃时最多留下一个写了一半的帧：打开缓存时从该帧起的分段末尾被跳过，
+//! 之前的帧和之后的分段照常重放。分段中的帧全部发送成功后删除分段文件；重放到一半时进程退出，
+//! 下次启动会重新发送该分段中已发送的帧（至少一次）。
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ByteSize;
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+use crate::stats::SpoolGauge;
+use crate::udp_helper::UdpPacketHelper;
+
+/// 帧头长度：长度、CRC 和写入时间
+const FRAME_HEADER_LEN: usize = 16;
+
+/// UDP 离线缓存配置（见 `UdpConfig::spool`）
+///
+/// 同一目录只能由一个 UDP 处理器使用。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpoolConfig {
+    /// 缓存目录（不存在时自动创建）
+    pub dir: PathBuf,
+    /// 单个分段文件的大小上限
+    pub segment_bytes: ByteSize,
+    /// 缓存总大小上限，超出时删除最旧的分段（计入 `LoggerStats::spool_dropped`）
+    pub max_bytes: ByteSize,
+}
+
+impl SpoolConfig {
+    /// 验证配置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.dir.as_os_str().is_empty() {
+            return Err("配置错误: 离线缓存目录不能为空".to_string());
+        }
+        if self.segment_bytes.0 < FRAME_HEADER_LEN as u64 * 2 {
+            return Err("配置错误: 离线缓存分段大小过小".to_string());
+        }
+        if self.max_bytes < self.segment_bytes {
+            return Err("配置错误: 离线缓存总大小上限不能小于分段大小".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for SpoolConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("./logs/udp_spool"),
+            segment_bytes: ByteSize::mib(1),
+            max_bytes: ByteSize::mib(64),
+        }
+    }
+}
+
+/// 单个分段文件
+#[derive(Debug)]
+struct Segment {
+    index: u64,
+    path: PathBuf,
+    /// 有效帧的总字节数（不含损坏的末尾）
+    bytes: u64,
+    /// 有效帧数
+    frames: usize,
+    /// 有效帧中的记录数
+    records: usize,
+    /// 第一帧的写入时间（Unix 毫秒）
+    oldest_ms: u64,
+    /// 已重放成功的帧数
+    replayed: usize,
+}
+
+/// 离线缓存（由 UDP 发送任务独占）
+pub(crate) struct Spool {
+    config: SpoolConfig,
+    /// 按编号排列的分段，最后一个可能正在写入
+    segments: VecDeque<Segment>,
+    /// 当前写入的分段文件（对应 `segments` 的最后一个）
+    writer: Option<File>,
+    next_index: u64,
+    gauge: Arc<SpoolGauge>,
+    diagnostics: Diagnostics,
+}
+
+impl Spool {
+    /// 打开缓存目录，扫描已有的分段；新数据总是写入新的分段
+    pub(crate) fn open(config: SpoolConfig, diagnostics: Diagnostics) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&config.dir)?;
+        let mut indexes: Vec<u64> = std::fs::read_dir(&config.dir)?
+            .flatten()
+            .filter_map(|entry| segment_index(&entry.file_name().to_string_lossy()))
+            .collect();
+        indexes.sort_unstable();
+
+        let mut segments = VecDeque::new();
+        for index in indexes {
+            let path = config.dir.join(segment_name(index));
+            let data = std::fs::read(&path)?;
+            let (frames, valid) = parse_frames(&data);
+            if valid < data.len() {
+                diagnostics.emit(DiagnosticEvent::SpoolCorrupted {
+                    path: path.clone(),
+                    discarded_bytes: (data.len() - valid) as u64,
+                });
+            }
+            if frames.is_empty() {
+                let _ = std::fs::remove_file(&path);
+                continue;
+            }
+            segments.push_back(Segment {
+                index,
+                path,
+                bytes: valid as u64,
+                frames: frames.len(),
+                records: frames.iter().map(|(_, payload)| UdpPacketHelper::record_count(payload)).sum(),
+                oldest_ms: frames[0].0,
+                replayed: 0,
+            });
+        }
+
+        let next_index = segments.back().map_or(1, |segment| segment.index + 1);
+        let gauge = diagnostics.stats().register_spool();
+        let spool = Self { config, segments, writer: None, next_index, gauge, diagnostics };
+        spool.publish();
+        Ok(spool)
+    }
+
+    /// 是否还有待重放的数据
+    pub(crate) fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// 追加一个数据报，超出总大小上限时删除最旧的分段
+    pub(crate) fn append(&mut self, datagram: &[u8]) -> std::io::Result<()> {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let frame = encode_frame(now_ms, datagram);
+        let full = self
+            .segments
+            .back()
+            .is_none_or(|segment| segment.bytes > 0 && segment.bytes + frame.len() as u64 > self.config.segment_bytes.0);
+        if self.writer.is_none() || full {
+            self.roll()?;
+        }
+
+        self.writer.as_mut().expect("已打开写入分段").write_all(&frame)?;
+        let segment = self.segments.back_mut().expect("已打开写入分段");
+        if segment.frames == 0 {
+            segment.oldest_ms = now_ms;
+        }
+        segment.bytes += frame.len() as u64;
+        segment.frames += 1;
+        segment.records += UdpPacketHelper::record_count(datagram);
+
+        self.enforce_limit();
+        self.publish();
+        Ok(())
+    }
+
+    /// 最旧分段中尚未重放的帧；最旧分段正在写入时先封存它，之后的数据写入新分段
+    pub(crate) fn pending_frames(&mut self) -> Option<(u64, Vec<Vec<u8>>)> {
+        loop {
+            let segment = self.segments.front()?;
+            if self.segments.len() == 1 {
+                self.writer = None;
+            }
+            match std::fs::read(&segment.path) {
+                Ok(data) => {
+                    let frames = parse_frames(&data[..(segment.bytes as usize).min(data.len())]).0;
+                    let pending = frames.into_iter().skip(segment.replayed).map(|(_, payload)| payload.to_vec()).collect();
+                    return Some((segment.index, pending));
+                }
+                // 分段文件已被外部删除，跳过
+                Err(_) => {
+                    self.segments.pop_front();
+                    self.publish();
+                }
+            }
+        }
+    }
+
+    /// 记录 `index` 分段又有一帧重放成功
+    pub(crate) fn mark_sent(&mut self, index: u64) {
+        if let Some(segment) = self.segments.front_mut().filter(|segment| segment.index == index) {
+            segment.replayed += 1;
+        }
+    }
+
+    /// `index` 分段已全部重放，删除分段文件
+    pub(crate) fn finish_segment(&mut self, index: u64) {
+        if self.segments.front().is_some_and(|segment| segment.index == index) {
+            let segment = self.segments.pop_front().expect("分段存在");
+            let _ = std::fs::remove_file(&segment.path);
+            self.publish();
+        }
+    }
+
+    /// 关闭当前分段，开始写入新的分段
+    fn roll(&mut self) -> std::io::Result<()> {
+        let index = self.next_index;
+        let path = self.config.dir.join(segment_name(index));
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        self.next_index += 1;
+        self.segments.push_back(Segment { index, path, bytes: 0, frames: 0, records: 0, oldest_ms: 0, replayed: 0 });
+        self.writer = Some(file);
+        Ok(())
+    }
+
+    /// 删除最旧的分段直到总大小不超过上限（正在写入的分段保留）
+    fn enforce_limit(&mut self) {
+        let mut total: u64 = self.segments.iter().map(|segment| segment.bytes).sum();
+        let (mut segments, mut records) = (0, 0);
+        while total > self.config.max_bytes.0 && self.segments.len() > 1 {
+            let oldest = self.segments.pop_front().expect("分段存在");
+            let _ = std::fs::remove_file(&oldest.path);
+            total -= oldest.bytes;
+            segments += 1;
+            records += oldest.records;
+        }
+        if segments > 0 {
+            self.diagnostics.emit(DiagnosticEvent::SpoolOverflow { processor: "udp_processor", segments, dropped: records });
+        }
+    }
+
+    /// 把当前状态写入共享统计
+    fn publish(&self) {
+        let bytes = self.segments.iter().map(|segment| segment.bytes).sum();
+        let oldest_ms = self.segments.iter().find(|segment| segment.frames > 0).map_or(0, |segment| segment.oldest_ms);
+        self.gauge.set(self.segments.len() as u64, bytes, oldest_ms);
+    }
+}
+
+/// 分段文件名
+fn segment_name(index: u64) -> String {
+    format!("spool-{:06}.bin", index)
+}
+
+/// 从分段文件名解析编号
+fn segment_index(name: &str) -> Option<u64> {
+    name.strip_prefix("spool-")?.strip_suffix(".bin")?.parse().ok()
+}
+
+/// 编码一帧
+fn encode_frame(timestamp_ms: u64, datagram: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + datagram.len());
+    frame.extend_from_slice(&(datagram.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&[0; 4]);
+    frame.extend_from_slice(&timestamp_ms.to_le_bytes());
+    frame.extend_from_slice(datagram);
+    let crc = crc32(&frame[8..]);
+    frame[4..8].copy_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/// 解析分段中的帧（写入时间和数据报），遇到截断或校验失败的帧时停止；同时返回有效部分的字节数
+fn parse_frames(data: &[u8]) -> (Vec<(u64, &[u8])>, usize) {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while let Some(header) = data.get(offset..offset + FRAME_HEADER_LEN) {
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let Some(body) = data.get(offset + 8..offset + FRAME_HEADER_LEN + len) else {
+            break;
+        };
+        if crc32(body) != crc {
+            break;
+        }
+        let timestamp_ms = u64::from_le_bytes(body[0..8].try_into().unwrap());
+        frames.push((timestamp_ms, &body[8..]));
+        offset += FRAME_HEADER_LEN + len;
+    }
+    (frames, offset)
+}
+
+/// CRC-32（IEEE 802.3）
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+    !data.iter().fold(!0u32, |crc, &byte| TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rat_logger_spool_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config(dir: &Path, segment_bytes: u64, max_bytes: u64) -> SpoolConfig {
+        SpoolConfig { dir: dir.to_path_buf(), segment_bytes: ByteSize(segment_bytes), max_bytes: ByteSize(max_bytes) }
+    }
+
+    /// 重放全部帧（全部发送成功）
+    fn drain(spool: &mut Spool) -> Vec<Vec<u8>> {
+        let mut sent = Vec::new();
+        while let Some((index, frames)) = spool.pending_frames() {
+            for frame in frames {
+                sent.push(frame);
+                spool.mark_sent(index);
+            }
+            spool.finish_segment(index);
+        }
+        sent
+    }
+
+    fn datagram(i: usize) -> Vec<u8> {
+        format!("datagram {:03}", i).into_bytes()
+    }
+
+    #[test]
+    fn test_crc32_matches_reference() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_replay_in_order_across_segments_and_restart() {
+        let dir = test_dir("order");
+        let diagnostics = Diagnostics::new();
+        let mut spool = Spool::open(config(&dir, 100, 1 << 20), diagnostics.clone()).unwrap();
+        for i in 0..20 {
+            spool.append(&datagram(i)).unwrap();
+        }
+        let stats = diagnostics.stats().spool().unwrap();
+        assert!(stats.segments > 5, "{:?}", stats);
+        assert!(stats.oldest_timestamp_ms.is_some());
+
+        // 重放到一半时退出：已发送的帧在重启后重新发送，之后的顺序不变
+        let (index, frames) = spool.pending_frames().unwrap();
+        spool.mark_sent(index);
+        assert_eq!(frames[0], datagram(0));
+        drop(spool);
+
+        let mut spool = Spool::open(config(&dir, 100, 1 << 20), diagnostics.clone()).unwrap();
+        spool.append(&datagram(20)).unwrap();
+        assert_eq!(drain(&mut spool), (0..=20).map(datagram).collect::<Vec<_>>());
+        assert!(spool.is_empty());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        assert_eq!(diagnostics.stats().spool().unwrap().bytes, 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_truncated_segment_recovers_up_to_corruption() {
+        let dir = test_dir("truncated");
+        let diagnostics = Diagnostics::new();
+        let events = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        diagnostics.set_callback(Some(Arc::new(move |event: &DiagnosticEvent| sink.lock().push(event.clone()))));
+
+        let mut spool = Spool::open(config(&dir, 100, 1 << 20), diagnostics.clone()).unwrap();
+        for i in 0..12 {
+            spool.append(&datagram(i)).unwrap();
+        }
+        let first = spool.segments[0].frames;
+        let second = spool.segments[1].frames;
+        let path = spool.segments[1].path.clone();
+        drop(spool);
+
+        // 模拟崩溃：第二个分段的最后一帧只写了一半
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 5).unwrap();
+
+        let mut spool = Spool::open(config(&dir, 100, 1 << 20), diagnostics.clone()).unwrap();
+        let lost = first + second - 1;
+        let expected: Vec<_> = (0..12).filter(|&i| i != lost).map(datagram).collect();
+        assert_eq!(drain(&mut spool), expected);
+        assert!(matches!(
+            events.lock().as_slice(),
+            [DiagnosticEvent::SpoolCorrupted { discarded_bytes, .. }] if *discarded_bytes == FRAME_HEADER_LEN as u64 + 7
+        ));
+
+        // 校验失败（而不是截断）的帧同样被跳过
+        let mut spool = Spool::open(config(&dir, 1 << 10, 1 << 20), diagnostics.clone()).unwrap();
+        for i in 0..3 {
+            spool.append(&datagram(i)).unwrap();
+        }
+        let path = spool.segments[0].path.clone();
+        drop(spool);
+        let mut data = std::fs::read(&path).unwrap();
+        let second_frame = FRAME_HEADER_LEN + datagram(0).len();
+        data[second_frame + FRAME_HEADER_LEN] ^= 0xff;
+        std::fs::write(&path, data).unwrap();
+        let mut spool = Spool::open(config(&dir, 1 << 10, 1 << 20), diagnostics).unwrap();
+        assert_eq!(drain(&mut spool), [datagram(0)]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_max_bytes_drops_oldest_segments() {
+        let dir = test_dir("overflow");
+        let diagnostics = Diagnostics::new();
+        let mut spool = Spool::open(config(&dir, 100, 300), diagnostics.clone()).unwrap();
+        for i in 0..40 {
+            spool.append(&datagram(i)).unwrap();
+        }
+        let stats = diagnostics.stats().spool().unwrap();
+        assert!(stats.bytes <= 300 && stats.segments <= 4, "{:?}", stats);
+
+        let replayed = drain(&mut spool);
+        let dropped = diagnostics.stats().spool_dropped();
+        assert!(dropped > 0);
+        // 丢弃的是最旧的记录，保留的记录连续到最后一条
+        assert_eq!(replayed, (dropped as usize..40).map(datagram).collect::<Vec<_>>());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,140 @@
+//! 延迟消息的生成线程 - 在日志器的后台线程中生成记录的延迟消息，再编码并交给处理器
+//!
+//! 调用线程完成过滤并写入记录时间、序号等标记后把记录放入队列，消息闭包不在调用线程中执行。
+//! 队列中有记录尚未发出时，同一日志器的其他记录也经过队列，保持同一线程内记录的顺序。
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Sender};
+
+use crate::config::Record;
+use crate::core::CallbackScope;
+
+/// 生成线程名
+const THREAD_NAME: &str = "rat-lazy-message";
+
+/// 编码并发送已生成消息的记录，第二个参数为记录是否属于受保护的目标
+pub(crate) type Sink = Arc<dyn Fn(&Record, bool) + Send + Sync>;
+
+/// 延迟消息队列（每个日志器一个，克隆的日志器共享），第一条需要排队的记录到达时才启动生成线程
+///
+/// 日志器的所有克隆释放后，生成线程发出队列中剩余的记录后退出。
+pub(crate) struct LazyDispatcher {
+    sink: Sink,
+    queue: OnceLock<Sender<(Record, bool)>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+    pending: Arc<Pending>,
+}
+
+/// 已入队但尚未发出的记录数，降为 0 时唤醒等待者
+#[derive(Default)]
+struct Pending {
+    count: AtomicUsize,
+    lock: Mutex<()>,
+    idle: Condvar,
+}
+
+impl Pending {
+    fn busy(&self) -> bool {
+        self.count.load(Ordering::SeqCst) > 0
+    }
+
+    fn finish_one(&self) {
+        if self.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+            self.idle.notify_all();
+        }
+    }
+}
+
+impl LazyDispatcher {
+    pub fn new(sink: Sink) -> Self {
+        Self {
+            sink,
+            queue: OnceLock::new(),
+            thread: Mutex::new(None),
+            pending: Arc::new(Pending::default()),
+        }
+    }
+
+    /// 是否有已入队但尚未发出的记录（此时其他记录也需要排队以保持顺序）
+    pub fn busy(&self) -> bool {
+        self.pending.busy()
+    }
+
+    /// 把已写入标记的记录放入队列（无法启动生成线程时在调用线程中直接发送）
+    pub fn submit(&self, record: Record, protected: bool) {
+        let Some(queue) = self.queue() else {
+            return (self.sink)(&record, protected);
+        };
+        self.pending.count.fetch_add(1, Ordering::SeqCst);
+        if let Err(e) = queue.send((record, protected)) {
+            self.pending.finish_one();
+            let (record, protected) = e.into_inner();
+            (self.sink)(&record, protected);
+        }
+    }
+
+    /// 等待队列中的记录全部发出，超时返回 false
+    pub fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.pending.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        while self.pending.busy() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            guard = self.pending.idle.wait_timeout(guard, deadline - now).unwrap_or_else(PoisonError::into_inner).0;
+        }
+        true
+    }
+
+    /// 队列的发送端，第一次调用时启动生成线程
+    fn queue(&self) -> Option<&Sender<(Record, bool)>> {
+        if let Some(queue) = self.queue.get() {
+            return Some(queue);
+        }
+        let mut thread = self.thread.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(queue) = self.queue.get() {
+            return Some(queue);
+        }
+        let (queue, receiver) = unbounded::<(Record, bool)>();
+        let (sink, pending) = (self.sink.clone(), self.pending.clone());
+        let spawned = thread::Builder::new().name(THREAD_NAME.to_string()).spawn(move || {
+            for (record, protected) in receiver {
+                // 消息闭包是用户代码：其中产生的记录按 `ReentrantPolicy` 处理，panic 时消息为空
+                {
+                    let _scope = CallbackScope::enter();
+                    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| record.args.as_str().len())).is_err() {
+                        eprintln!("[rat_logger] 延迟消息的闭包 panic，记录以空消息写入");
+                    }
+                }
+                sink(&record, protected);
+                drop(record);
+                pending.finish_one();
+            }
+        });
+        match spawned {
+            Ok(handle) => *thread = Some(handle),
+            Err(e) => {
+                eprintln!("[rat_logger] 无法启动延迟消息线程: {}", e);
+                return None;
+            }
+        }
+        Some(self.queue.get_or_init(|| queue))
+    }
+}
+
+impl Drop for LazyDispatcher {
+    fn drop(&mut self) {
+        // 关闭队列后等待生成线程发出剩余记录（在生成线程自身中释放时不等待）
+        drop(self.queue.take());
+        let thread = self.thread.get_mut().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(thread) = thread.filter(|thread| thread.thread().id() != thread::current().id()) {
+            let _ = thread.join();
+        }
+    }
+}
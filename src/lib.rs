@@ -7,16 +7,46 @@ pub mod handler;
 pub mod config;
 pub mod udp_helper;
 pub mod producer_consumer;
+pub mod process_info;
+pub mod sampling;
+mod breaker;
+mod memory;
+mod lazy;
+pub mod stats;
+pub mod diagnostics;
+pub mod clock;
+pub mod scoped;
+pub mod startup;
+pub mod report;
+mod stall;
+#[cfg(feature = "signal-flush")]
+pub mod signal;
+#[cfg(feature = "bench-utils")]
+pub mod bench;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "tools")]
+pub mod tools;
 
 use core::LoggerCore;
 use handler::{LogHandler, HandlerType};
-use config::{Record, Metadata, AppId};
+use config::{Record, RecordArgs, Metadata, AppId};
 use std::any::Any;
 
 // 重新导出主要类型
-pub use core::{Logger, LoggerBuilder, parse_log_level_from_env, try_init_from_env, is_initialized, set_max_level};
-pub use handler::{composite::CompositeHandler, term::TermProcessor, file::FileProcessor, udp::UdpProcessor};
-pub use config::{Level, LevelFilter, FileConfig, NetworkConfig, FormatConfig, LevelStyle, LevelTemplates, ColorConfig};
+pub use core::{Logger, LoggerBuilder, Priority, ProcessorConfig, parse_log_level_from_env, try_init_from_env, is_initialized, global_handler_types, detach_global_handler, set_max_level, sync_point, PreInit, ReentrantPolicy, set_preinit_policy};
+pub use producer_consumer::{FlushError, HandlerHandle};
+pub use handler::{HandlerOptions, composite::CompositeHandler, term::TermProcessor, file::{FileProcessor, FileProcessorError}, udp::{UdpProcessor, UdpConfig, RetryPolicy}, udp_spool::SpoolConfig};
+pub use diagnostics::{DiagnosticEvent, FilterGate};
+pub use clock::{Clock, SystemClock};
+pub use report::{ConfigReport, HandlerReport};
+pub use config::{Level, LevelFilter, LevelMask, FileConfig, NetworkConfig, FormatConfig, LevelStyle, LevelTemplates, ColorConfig, SamplingConfig, BreakerConfig, BreakerAction, MemoryBudget, TemplateError, FormatConfigBuilder, MemoryAction, ProbeMarker, SanitizeMode, LineEnding, CompressionOverflow, SharedDirPolicy, ByteSize, DurationMs};
+pub use udp_helper::PayloadFields;
+pub use handler::term;
+#[cfg(feature = "signal-flush")]
+pub use signal::Signal;
 
 mod fmt_impl;
 pub use fmt_impl::{fmt, FmtInitializer};
@@ -27,6 +57,7 @@ macro_rules! error {
     ($($arg:tt)*) => {
         $crate::__private_log_impl(
             $crate::Level::Error,
+            $crate::Priority::Normal,
             format_args!($($arg)*),
             module_path!(),
             file!(),
@@ -40,6 +71,7 @@ macro_rules! warn {
     ($($arg:tt)*) => {
         $crate::__private_log_impl(
             $crate::Level::Warn,
+            $crate::Priority::Normal,
             format_args!($($arg)*),
             module_path!(),
             file!(),
@@ -53,6 +85,7 @@ macro_rules! info {
     ($($arg:tt)*) => {
         $crate::__private_log_impl(
             $crate::Level::Info,
+            $crate::Priority::Normal,
             format_args!($($arg)*),
             module_path!(),
             file!(),
@@ -66,6 +99,7 @@ macro_rules! debug {
     ($($arg:tt)*) => {
         $crate::__private_log_impl(
             $crate::Level::Debug,
+            $crate::Priority::Normal,
             format_args!($($arg)*),
             module_path!(),
             file!(),
@@ -79,6 +113,7 @@ macro_rules! trace {
     ($($arg:tt)*) => {
         $crate::__private_log_impl(
             $crate::Level::Trace,
+            $crate::Priority::Normal,
             format_args!($($arg)*),
             module_path!(),
             file!(),
@@ -87,35 +122,128 @@ macro_rules! trace {
     };
 }
 
+// 延迟生成消息的日志宏：参数是返回消息的 `Send + 'static` 闭包，例如 `info_lazy!(move || format!("{:?}", state))`，
+// 记录被级别过滤或采样丢弃时闭包不会被调用。通过过滤的记录由日志器的后台线程调用闭包（只调用一次）后再编码，
+// 调用线程只做过滤和时间、序号等标记（见 `RecordArgs`）
+#[macro_export]
+macro_rules! error_lazy {
+    ($message:expr) => {
+        $crate::__private_log_lazy_impl(
+            $crate::Level::Error,
+            $message,
+            module_path!(),
+            file!(),
+            line!(),
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! warn_lazy {
+    ($message:expr) => {
+        $crate::__private_log_lazy_impl(
+            $crate::Level::Warn,
+            $message,
+            module_path!(),
+            file!(),
+            line!(),
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! info_lazy {
+    ($message:expr) => {
+        $crate::__private_log_lazy_impl(
+            $crate::Level::Info,
+            $message,
+            module_path!(),
+            file!(),
+            line!(),
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! debug_lazy {
+    ($message:expr) => {
+        $crate::__private_log_lazy_impl(
+            $crate::Level::Debug,
+            $message,
+            module_path!(),
+            file!(),
+            line!(),
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! trace_lazy {
+    ($message:expr) => {
+        $crate::__private_log_lazy_impl(
+            $crate::Level::Trace,
+            $message,
+            module_path!(),
+            file!(),
+            line!(),
+        )
+    };
+}
+
 /// 强制刷新全局日志器 - 立即输出所有缓冲的日志
 #[macro_export]
 macro_rules! flush_logs {
     () => {
-        if let Some(logger) = $crate::core::LOGGER.lock().unwrap().as_ref() {
+        if let Some(logger) = $crate::core::lock_global_logger().as_ref() {
             logger.force_flush();
         }
     };
 }
 
 /// 紧急日志宏 - 无视所有限制立即输出，适用于启动日志和关键错误
+///
+/// 日志器级别为 `Off` 时默认不输出，也不格式化消息（见 `LoggerBuilder::with_emergency_when_off`）
 #[macro_export]
 macro_rules! emergency {
     ($($arg:tt)*) => {
-        if let Some(logger) = $crate::core::LOGGER.lock().unwrap().as_ref() {
-            let record = $crate::config::Record {
-                metadata: std::sync::Arc::new($crate::config::Metadata {
-                    level: $crate::Level::Error,
-                    target: module_path!().to_string(),
-                    auth_token: None,
-                    app_id: None,
-                }),
-                args: format_args!($($arg)*).to_string(),
-                module_path: Some(module_path!().to_string()),
-                file: Some(file!().to_string()),
-                line: Some(line!()),
-            };
-            logger.emergency_log(&record);
-        }
+        $crate::__private_log_impl(
+            $crate::Level::Error,
+            $crate::Priority::Emergency,
+            format_args!($($arg)*),
+            module_path!(),
+            file!(),
+            line!(),
+        )
+    };
+}
+
+/// 致命错误日志宏 - 按 Error 级别过滤，输出时级别显示为 `FATAL`
+#[macro_export]
+macro_rules! fatal {
+    ($($arg:tt)*) => {
+        $crate::__private_log_labeled_impl(
+            $crate::Level::Error,
+            "FATAL",
+            format_args!($($arg)*),
+            module_path!(),
+            file!(),
+            line!(),
+        )
+    };
+}
+
+/// 审计日志宏 - 按 Info 级别过滤，输出时级别显示为 `AUDIT`
+#[macro_export]
+macro_rules! audit {
+    ($($arg:tt)*) => {
+        $crate::__private_log_labeled_impl(
+            $crate::Level::Info,
+            "AUDIT",
+            format_args!($($arg)*),
+            module_path!(),
+            file!(),
+            line!(),
+        )
     };
 }
 
@@ -123,56 +251,151 @@ macro_rules! emergency {
 #[macro_export]
 macro_rules! startup_log {
     ($($arg:tt)*) => {
-        if let Some(logger) = $crate::core::LOGGER.lock().unwrap().as_ref() {
-            let record = $crate::config::Record {
-                metadata: std::sync::Arc::new($crate::config::Metadata {
-                    level: $crate::Level::Info,
-                    target: module_path!().to_string(),
-                    auth_token: None,
-                    app_id: None,
-                }),
-                args: format_args!($($arg)*).to_string(),
-                module_path: Some(module_path!().to_string()),
-                file: Some(file!().to_string()),
-                line: Some(line!()),
-            };
-            logger.emergency_log(&record);
-        }
+        $crate::__private_log_impl(
+            $crate::Level::Info,
+            $crate::Priority::Emergency,
+            format_args!($($arg)*),
+            module_path!(),
+            file!(),
+            line!(),
+        )
     };
 }
 
 #[doc(hidden)]
 pub fn __private_log_impl(
     level: Level,
+    priority: Priority,
+    args: std::fmt::Arguments<'_>,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+) {
+    log_impl(level, None, priority, args, module_path, file, line)
+}
+
+#[doc(hidden)]
+pub fn __private_log_labeled_impl(
+    level: Level,
+    label: &'static str,
     args: std::fmt::Arguments<'_>,
     module_path: &'static str,
     file: &'static str,
     line: u32,
 ) {
-    // 快速路径：首先检查级别，避免被过滤的日志产生任何开销
+    log_impl(level, Some(label), Priority::Normal, args, module_path, file, line)
+}
+
+fn log_impl(
+    level: Level,
+    label: Option<&'static str>,
+    priority: Priority,
+    args: std::fmt::Arguments<'_>,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+) {
+    // 作用域日志器按自己的级别过滤，不使用全局快速路径
+    if let Some(logger) = scoped::current() {
+        if priority == Priority::Normal && !level.should_log_at(logger.level()) {
+            logger.trace_filtered(level, module_path, FilterGate::LoggerLevel(logger.level()));
+            return;
+        }
+        if priority == Priority::Normal || logger.emergency_enabled() {
+            let mut record = macro_record(logger.as_ref(), level, args.to_string(), module_path, file, line);
+            record.level_label = label.map(str::to_string);
+            logger.log_with_priority(&record, priority);
+        }
+        return;
+    }
+
+    if priority == Priority::Normal {
+        // 快速路径：首先检查级别，避免被过滤的日志产生任何开销
+        let max_level = core::max_level();
+        if !level.should_log_at(max_level) {
+            core::trace_max_level_filtered(level, module_path);
+            return;
+        }
+
+        // 首先尝试从环境变量初始化（如果还未初始化且存在RUST_LOG）
+        let _ = core::try_init_from_env();
+    }
+
+    // 检查全局日志器的配置；紧急日志不输出时不格式化消息。
+    // 格式化消息前释放锁，参数的 `Display` 实现 panic 时不会使锁中毒
+    let logger = core::lock_global_logger().clone();
+    let Some(logger) = logger else {
+        core::log_preinit(level, label, || args.to_string(), module_path, file, line);
+        return;
+    };
+    if priority == Priority::Normal || logger.emergency_enabled() {
+        let mut record = macro_record(logger.as_ref(), level, args.to_string(), module_path, file, line);
+        record.level_label = label.map(str::to_string);
+        logger.log_with_priority(&record, priority);
+    }
+}
+
+/// 构造日志宏产生的记录
+fn macro_record(
+    logger: &dyn core::Logger,
+    level: Level,
+    args: impl Into<RecordArgs>,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+) -> Record {
+    Record {
+        metadata: std::sync::Arc::new(Metadata {
+            level,
+            target: module_path.to_string(),
+            auth_token: None,
+            app_id: logger.app_id().map(str::to_string),
+        }),
+        args: args.into(),
+        module_path: Some(module_path.to_string()),
+        file: Some(file.to_string()),
+        line: Some(line),
+        fields: Vec::new(),
+        timestamp: None,
+        seq: None,
+        thread: None,
+        level_label: None,
+        record_id: None,
+    }
+}
+
+#[doc(hidden)]
+pub fn __private_log_lazy_impl<F>(
+    level: Level,
+    message: F,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+) where
+    F: FnOnce() -> String + Send + 'static,
+{
+    if let Some(logger) = scoped::current() {
+        if level.should_log_at(logger.level()) {
+            logger.log(&macro_record(logger.as_ref(), level, RecordArgs::lazy(message), module_path, file, line));
+        } else {
+            logger.trace_filtered(level, module_path, FilterGate::LoggerLevel(logger.level()));
+        }
+        return;
+    }
+
+    // 快速路径：被过滤的日志不构造记录，也不调用闭包
     let max_level = core::max_level();
     if !level.should_log_at(max_level) {
+        core::trace_max_level_filtered(level, module_path);
         return;
     }
 
-    // 首先尝试从环境变量初始化（如果还未初始化且存在RUST_LOG）
     let _ = core::try_init_from_env();
 
-    // 检查全局日志器的配置
-    if let Some(logger) = core::LOGGER.lock().unwrap().as_ref() {
-        let record = Record {
-            metadata: std::sync::Arc::new(Metadata {
-                level,
-                target: module_path.to_string(),
-                auth_token: None,
-                app_id: None,
-            }),
-            args: args.to_string(),
-            module_path: Some(module_path.to_string()),
-            file: Some(file.to_string()),
-            line: Some(line),
-        };
-        logger.log(&record);
+    let logger = core::lock_global_logger().clone();
+    match logger {
+        Some(logger) => logger.log(&macro_record(logger.as_ref(), level, RecordArgs::lazy(message), module_path, file, line)),
+        None => core::log_preinit(level, None, message, module_path, file, line),
     }
 }
 
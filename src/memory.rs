@@ -0,0 +1,154 @@
+//! 内存预算模块 - 限制已提交但处理器尚未处理完的数据总量
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use parking_lot::Mutex;
+
+use crate::config::{MemoryAction, MemoryBudget};
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+use crate::producer_consumer::on_worker_thread;
+use crate::stats::LoggerStats;
+
+/// 内存预算控制器
+///
+/// 已提交的字节数由工作线程在 `LoggerStats::in_flight_bytes` 中增减，这里只判定新记录能否进入管道。
+pub(crate) struct MemoryGovernor {
+    config: MemoryBudget,
+    diagnostics: Diagnostics,
+    exceeded: AtomicBool,
+    state: Mutex<ExceededState>,
+}
+
+/// 在 `in_flight_bytes` 上预留的字节，释放时扣除（并唤醒等待的生产者）
+pub(crate) struct Reservation<'a> {
+    stats: Option<&'a LoggerStats>,
+    bytes: u64,
+}
+
+impl Reservation<'_> {
+    /// 没有设置内存预算时的空预留
+    pub fn none() -> Self {
+        Self { stats: None, bytes: 0 }
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if let Some(stats) = self.stats {
+            stats.remove_in_flight(self.bytes);
+        }
+    }
+}
+
+#[derive(Default)]
+struct ExceededState {
+    /// 超出预算的时间
+    since: Option<Instant>,
+    /// 本次超出期间丢弃的记录数
+    dropped: u64,
+}
+
+impl MemoryGovernor {
+    pub fn new(config: MemoryBudget, diagnostics: Diagnostics) -> Self {
+        Self {
+            config,
+            diagnostics,
+            exceeded: AtomicBool::new(false),
+            state: Mutex::new(ExceededState::default()),
+        }
+    }
+
+    /// 内存预算配置
+    pub fn config(&self) -> &MemoryBudget {
+        &self.config
+    }
+
+    /// 判定 `bytes`（发给各处理器的副本总大小）能否进入管道，`forced` 的记录可以使用预留
+    ///
+    /// 放行时在 `in_flight_bytes` 上原子地预留这些字节，并发的生产者不会一起越过预算；
+    /// 预留在发送完成后释放，此时各处理器的副本已经计入。
+    /// `BlockProducers` 下等到有足够的空间后放行（处理器工作线程中不等待），其余方式超出预算时丢弃并返回 None。
+    pub fn admit(&self, bytes: u64, forced: bool) -> Option<Reservation<'_>> {
+        let stats = self.diagnostics.stats();
+        let limit = if forced { self.config.max_bytes + self.config.emergency_reserve } else { self.config.max_bytes };
+        // 管道为空时总是放行，超过预算的单条记录不会被永久拒绝
+        let fits = |in_flight: u64| in_flight == 0 || in_flight + bytes <= limit;
+
+        if self.exceeded.load(Ordering::Relaxed) {
+            let degrade = self.config.action == MemoryAction::DegradeToErrorOnly;
+            let in_flight = stats.in_flight_bytes();
+            let recovered = if degrade { in_flight <= self.config.max_bytes / 2 } else { fits(in_flight) };
+            if recovered {
+                self.recover();
+            } else if degrade && !forced {
+                self.drop_record();
+                return None;
+            }
+        }
+        if stats.try_reserve_in_flight(bytes, fits) {
+            return Some(Reservation { stats: Some(stats), bytes });
+        }
+
+        self.exceed(stats.in_flight_bytes());
+        // 工作线程中（诊断回调）等待会阻塞自身的处理，改为丢弃
+        if self.config.action == MemoryAction::BlockProducers && !on_worker_thread() {
+            stats.reserve_in_flight_blocking(bytes, fits);
+            return Some(Reservation { stats: Some(stats), bytes });
+        }
+        self.drop_record();
+        None
+    }
+
+    /// 受保护的记录：可以使用预留，超出预留时 `BlockProducers` 等待处理器腾出空间，其他处理方式直接放行
+    ///
+    /// 受保护的记录从不被丢弃，也不计入 `memory_dropped`。
+    pub fn admit_protected(&self, bytes: u64) -> Reservation<'_> {
+        let stats = self.diagnostics.stats();
+        let limit = self.config.max_bytes + self.config.emergency_reserve;
+        let fits = |in_flight: u64| in_flight == 0 || in_flight + bytes <= limit;
+        if stats.try_reserve_in_flight(bytes, fits) {
+            return Reservation { stats: Some(stats), bytes };
+        }
+
+        self.exceed(stats.in_flight_bytes());
+        if self.config.action == MemoryAction::BlockProducers && !on_worker_thread() {
+            stats.reserve_in_flight_blocking(bytes, fits);
+        } else {
+            stats.add_in_flight(bytes);
+        }
+        Reservation { stats: Some(stats), bytes }
+    }
+
+    fn exceed(&self, in_flight: u64) {
+        let mut state = self.state.lock();
+        if self.exceeded.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        *state = ExceededState { since: Some(Instant::now()), dropped: 0 };
+        drop(state);
+        self.diagnostics.emit(DiagnosticEvent::MemoryBudgetExceeded {
+            in_flight,
+            budget: self.config.max_bytes,
+            action: self.config.action,
+        });
+    }
+
+    fn recover(&self) {
+        let mut guard = self.state.lock();
+        if !self.exceeded.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let state = std::mem::take(&mut *guard);
+        drop(guard);
+        self.diagnostics.emit(DiagnosticEvent::MemoryBudgetRecovered {
+            dropped: state.dropped,
+            duration: state.since.map(|since| since.elapsed()).unwrap_or_default(),
+        });
+    }
+
+    fn drop_record(&self) {
+        self.state.lock().dropped += 1;
+        self.diagnostics.stats().add_memory_dropped();
+    }
+}
@@ -0,0 +1,34 @@
+//! 进程信息模块 - 主机名、进程号等只需解析一次的信息
+
+use once_cell::sync::OnceCell;
+
+static HOSTNAME: OnceCell<Option<String>> = OnceCell::new();
+
+/// 本机主机名（首次调用时解析并缓存，解析失败返回 None）
+pub fn hostname() -> Option<&'static str> {
+    HOSTNAME
+        .get_or_init(|| {
+            gethostname::gethostname()
+                .into_string()
+                .ok()
+                .filter(|name| !name.is_empty())
+        })
+        .as_deref()
+}
+
+/// 当前进程号
+pub fn pid() -> u32 {
+    std::process::id()
+}
+
+static BINARY_NAME: OnceCell<String> = OnceCell::new();
+
+/// 当前可执行文件名（不含扩展名，首次调用时解析并缓存，解析失败返回空字符串）
+pub fn binary_name() -> &'static str {
+    BINARY_NAME.get_or_init(|| {
+        std::env::current_exe()
+            .ok()
+            .and_then(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .unwrap_or_default()
+    })
+}
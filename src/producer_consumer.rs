@@ -1,16 +1,83 @@
 //! 广播式生产者消费者模式实现
 //! 主线程广播消息给所有处理器，每个处理器自己决定是否处理
 
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
-use crossbeam_channel::{Sender, Receiver, unbounded};
-use std::sync::Mutex;
+use crossbeam_channel::{bounded, unbounded, Receiver, Select, Sender, TrySendError};
+use parking_lot::{Condvar, Mutex};
 use once_cell::sync::Lazy;
+use arc_swap::ArcSwap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{DurationMs, Level, LevelMask, MemoryBudget, ProbeMarker};
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+use crate::memory::{MemoryGovernor, Reservation};
+use crate::stats::{LoggerStats, PeriodicTask, ProbeLatency, ProbeReport, StatsExporterFn};
+use crate::stall::{OpTracker, StallSupervisor, TrackerRegistry};
 
 // 重新导出core模块中的LogCommand
 pub use crate::core::LogCommand;
 
+/// 记录富化函数（见 [`LoggerBuilder::with_enricher`](crate::LoggerBuilder::with_enricher)）
+pub type Enricher = Arc<dyn Fn(&mut crate::config::Record) + Send + Sync>;
+
+/// 等待富化的已编码记录，同一条记录的所有处理器共享
+///
+/// 第一个处理它的工作线程解码记录、执行富化函数并重新编码，结果缓存在这里，其余处理器直接使用。
+pub struct PendingEnrichment {
+    data: Vec<u8>,
+    enricher: Enricher,
+    /// 富化后的编码（富化函数 panic 或数据无法解码时为 None，使用原始编码）
+    enriched: std::sync::OnceLock<Option<Vec<u8>>>,
+}
+
+impl PendingEnrichment {
+    fn new(data: Vec<u8>, enricher: Enricher) -> Self {
+        Self { data, enricher, enriched: std::sync::OnceLock::new() }
+    }
+
+    /// 原始编码的大小
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 富化后的编码，只有第一次调用执行富化函数；富化函数 panic 时上报 `EnricherPanicked` 并使用原始记录
+    fn resolve(&self, diagnostics: &Diagnostics) -> Vec<u8> {
+        let enriched = self.enriched.get_or_init(|| {
+            // 无法解码的数据原样交给处理器，由处理器报告解码错误
+            let (mut record, _) = crate::config::decode_record_versioned(&self.data).ok()?;
+            let target = record.metadata.target.clone();
+            let enrich = || {
+                let _scope = crate::core::CallbackScope::enter();
+                (self.enricher)(&mut record)
+            };
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(enrich)) {
+                Ok(()) => crate::config::encode_record_v2(&record).ok(),
+                Err(payload) => {
+                    let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "未知 panic".to_string());
+                    diagnostics.emit(DiagnosticEvent::EnricherPanicked { target, message });
+                    None
+                }
+            }
+        });
+        enriched.clone().unwrap_or_else(|| self.data.clone())
+    }
+}
+
+impl std::fmt::Debug for PendingEnrichment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingEnrichment")
+            .field("len", &self.data.len())
+            .field("resolved", &self.enriched.get().is_some())
+            .finish()
+    }
+}
+
 /// 全局工作线程就绪计数器
 static WORKER_READY_COUNT: Lazy<std::sync::atomic::AtomicUsize> = Lazy::new(|| {
     std::sync::atomic::AtomicUsize::new(0)
@@ -21,9 +88,15 @@ static EXPECTED_WORKER_COUNT: Lazy<std::sync::atomic::AtomicUsize> = Lazy::new(|
     std::sync::atomic::AtomicUsize::new(0)
 });
 
+/// 就绪计数器变化的通知（配合 `READY_COUNT_LOCK` 使用，避免等待方错过通知）
+static READY_COUNT_CHANGED: Condvar = Condvar::new();
+static READY_COUNT_LOCK: Mutex<()> = Mutex::new(());
+
 /// 增加就绪计数器
 pub fn increment_ready_count() {
     WORKER_READY_COUNT.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let _guard = READY_COUNT_LOCK.lock();
+    READY_COUNT_CHANGED.notify_all();
 }
 
 /// 设置预期工作线程数量
@@ -41,6 +114,76 @@ pub fn reset_ready_count() {
     WORKER_READY_COUNT.store(0, std::sync::atomic::Ordering::SeqCst);
 }
 
+/// 单个工作线程的就绪通知（工作线程进入处理循环时发出，等待方使用各自的截止时间）
+struct WorkerReady {
+    /// 创建工作线程的时间
+    spawned: Instant,
+    /// 工作线程就绪的时间
+    ready_at: Mutex<Option<Instant>>,
+    changed: Condvar,
+}
+
+impl WorkerReady {
+    fn new() -> Self {
+        Self {
+            spawned: Instant::now(),
+            ready_at: Mutex::new(None),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn mark_ready(&self) {
+        *self.ready_at.lock() = Some(Instant::now());
+        self.changed.notify_all();
+    }
+
+    /// 等待工作线程就绪，截止时间前就绪返回 true
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let mut ready_at = self.ready_at.lock();
+        while ready_at.is_none() && !self.changed.wait_until(&mut ready_at, deadline).timed_out() {}
+        ready_at.is_some()
+    }
+
+    /// 从创建到就绪的耗时，尚未就绪时为 None
+    fn startup(&self) -> Option<Duration> {
+        self.ready_at.lock().map(|ready_at| ready_at - self.spawned)
+    }
+}
+
+/// 工作线程从创建到就绪的耗时（见 [`ProcessorManager::readiness_report`]）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerReadiness {
+    /// 处理器名称
+    pub processor: &'static str,
+    /// 从创建工作线程到进入处理循环的耗时，尚未就绪时为 None
+    pub startup: Option<Duration>,
+}
+
+/// 工作线程未在超时时间内就绪
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerStartupTimeout {
+    /// 未就绪的工作线程中创建最早（等待最久）的处理器名称
+    pub slowest: &'static str,
+    /// 已等待的毫秒数
+    pub waited_ms: u64,
+    /// 已就绪的工作线程数
+    pub ready: usize,
+    /// 工作线程总数
+    pub total: usize,
+}
+
+impl std::fmt::Display for WorkerStartupTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "工作线程就绪超时（{}/{}个）：{} 等待 {}ms 仍未就绪",
+            self.ready, self.total, self.slowest, self.waited_ms
+        )
+    }
+}
+
+impl std::error::Error for WorkerStartupTimeout {}
+
 /// 等待所有工作线程就绪
 pub fn wait_for_all_ready(timeout_ms: u64) -> Result<(), String> {
     let expected = EXPECTED_WORKER_COUNT.load(std::sync::atomic::Ordering::SeqCst);
@@ -48,41 +191,98 @@ pub fn wait_for_all_ready(timeout_ms: u64) -> Result<(), String> {
         return Ok(());
     }
 
-    let start = std::time::Instant::now();
-    let timeout = std::time::Duration::from_millis(timeout_ms);
-
-    while start.elapsed() < timeout {
-        let ready = get_ready_count();
-        if ready >= expected {
-            return Ok(());
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let mut guard = READY_COUNT_LOCK.lock();
+    while get_ready_count() < expected {
+        if READY_COUNT_CHANGED.wait_until(&mut guard, deadline).timed_out() {
+            let ready = get_ready_count();
+            if ready >= expected {
+                break;
+            }
+            return Err(format!("工作线程就绪超时（{}/{}个）", ready, expected));
         }
-        std::thread::sleep(std::time::Duration::from_millis(10));
     }
-
-    let ready = get_ready_count();
-    Err(format!("工作线程就绪超时（{}/{}个）", ready, expected))
+    Ok(())
 }
 
 /// 批量处理配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BatchConfig {
-    /// 批量大小阈值（字节）
+    /// 批量记录数阈值：缓冲的记录数达到该值时立即批量处理
     pub batch_size: usize,
-    /// 批量时间间隔（毫秒）
+    /// 批量时间间隔（毫秒，配置文件中也可写作 `"100ms"`、`"2s"`）
+    #[serde(with = "crate::config::units::millis")]
     pub batch_interval_ms: u64,
-    /// 缓冲区大小
+    /// 缓冲区容量（记录数），不能小于批量记录数
     pub buffer_size: usize,
+    /// 强制写入合并窗口（毫秒）：强制写入后在该窗口内到达的强制写入合并为一批处理，窗口结束时只刷新一次
+    ///
+    /// 突发的错误日志不再逐条刷新；单独的强制写入最多延迟一个窗口。0 表示每条强制写入立即处理并刷新。
+    #[serde(default = "default_emergency_coalesce_ms", with = "crate::config::units::millis")]
+    pub emergency_coalesce_ms: u64,
+}
+
+fn default_emergency_coalesce_ms() -> u64 {
+    BatchConfig::default().emergency_coalesce_ms
 }
 
 impl BatchConfig {
+    /// 批量记录数的建议上限，超过时创建工作线程会输出警告
+    pub const SOFT_MAX_BATCH_SIZE: usize = 1024 * 1024;
+    /// 批量记录数的硬上限
+    pub const MAX_BATCH_SIZE: usize = 16 * 1024 * 1024;
+    /// 缓冲区容量的建议上限，超过时创建工作线程会输出警告
+    pub const SOFT_MAX_BUFFER_SIZE: usize = 10 * 1024 * 1024;
+    /// 缓冲区容量的硬上限
+    pub const MAX_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+    /// 强制写入合并窗口的上限（毫秒）
+    pub const MAX_EMERGENCY_COALESCE_MS: u64 = 1000;
+
+    /// 低延迟预设：每条记录立即处理（同步模式的默认配置）
+    pub fn low_latency() -> Self {
+        Self {
+            batch_size: 1,
+            batch_interval_ms: 1,
+            buffer_size: 1024,
+            emergency_coalesce_ms: 0,
+        }
+    }
+
+    /// 设置批量时间间隔，如 `"100ms".parse()?` 或 `DurationMs::ms(100)`
+    pub fn with_batch_interval(mut self, interval: DurationMs) -> Self {
+        self.batch_interval_ms = interval.as_millis();
+        self
+    }
+
+    /// 设置强制写入合并窗口，`DurationMs::ms(0)` 表示不合并
+    pub fn with_emergency_coalesce(mut self, window: DurationMs) -> Self {
+        self.emergency_coalesce_ms = window.as_millis();
+        self
+    }
+
+    /// 均衡预设：与 `BatchConfig::default()` 相同
+    pub fn balanced() -> Self {
+        Self::default()
+    }
+
+    /// 高吞吐预设：大批量、较长间隔，适合高速磁盘上的大量日志写入
+    pub fn throughput() -> Self {
+        Self {
+            batch_size: 64 * 1024,
+            batch_interval_ms: 100,
+            buffer_size: 1024 * 1024,
+            emergency_coalesce_ms: 2,
+        }
+    }
+
     /// 验证配置的有效性
     pub fn validate(&self) -> Result<(), String> {
-        // 验证批量大小
+        // 验证批量记录数
         if self.batch_size == 0 {
-            return Err("配置错误: 批量大小不能为 0".to_string());
+            return Err("配置错误: 批量记录数不能为 0".to_string());
         }
-        if self.batch_size > 1024 * 1024 {
-            return Err("配置错误: 批量大小过大 (最大 1MB)".to_string());
+        if self.batch_size > Self::MAX_BATCH_SIZE {
+            return Err(format!("配置错误: 批量记录数过大 (最大 {})", Self::MAX_BATCH_SIZE));
         }
 
         // 验证批量间隔
@@ -93,81 +293,361 @@ impl BatchConfig {
             return Err("配置错误: 批量间隔过长 (最大 60秒)".to_string());
         }
 
-        // 验证缓冲区大小
+        // 验证强制写入合并窗口
+        if self.emergency_coalesce_ms > Self::MAX_EMERGENCY_COALESCE_MS {
+            return Err(format!("配置错误: 强制写入合并窗口过长 (最大 {}ms)", Self::MAX_EMERGENCY_COALESCE_MS));
+        }
+
+        // 验证缓冲区容量
         if self.buffer_size == 0 {
-            return Err("配置错误: 缓冲区大小不能为 0".to_string());
+            return Err("配置错误: 缓冲区容量不能为 0".to_string());
         }
-        if self.buffer_size > 10 * 1024 * 1024 {
-            return Err("配置错误: 缓冲区大小过大 (最大 10MB)".to_string());
+        if self.buffer_size > Self::MAX_BUFFER_SIZE {
+            return Err(format!("配置错误: 缓冲区容量过大 (最大 {})", Self::MAX_BUFFER_SIZE));
         }
 
-        // 验证缓冲区大小与批量大小的关系
+        // 验证缓冲区容量与批量记录数的关系
         if self.buffer_size < self.batch_size {
-            return Err(format!("配置错误: 缓冲区大小 ({}) 必须大于等于批量大小 ({})", self.buffer_size, self.batch_size));
+            return Err(format!("配置错误: 缓冲区容量 ({}) 必须大于等于批量记录数 ({})", self.buffer_size, self.batch_size));
         }
 
         Ok(())
     }
+
+    /// 超过建议上限的配置项（配置仍然有效，但可能占用大量内存或增加延迟）
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.batch_size > Self::SOFT_MAX_BATCH_SIZE {
+            warnings.push(format!("批量记录数 {} 超过建议上限 {}", self.batch_size, Self::SOFT_MAX_BATCH_SIZE));
+        }
+        if self.buffer_size > Self::SOFT_MAX_BUFFER_SIZE {
+            warnings.push(format!("缓冲区容量 {} 超过建议上限 {}", self.buffer_size, Self::SOFT_MAX_BUFFER_SIZE));
+        }
+        warnings
+    }
 }
 
 impl Default for BatchConfig {
     fn default() -> Self {
         Self {
-            batch_size: 2048,           // 2KB - 更保守的批量大小确保可靠输出
+            batch_size: 2048,           // 2048条 - 更保守的批量大小确保可靠输出
             batch_interval_ms: 25,       // 25ms - 更短的间隔确保及时输出
-            buffer_size: 16 * 1024,     // 16KB - 相应减小缓冲区大小
+            buffer_size: 16 * 1024,     // 16K条
+            emergency_coalesce_ms: 2,    // 2ms - 合并突发的强制写入
+        }
+    }
+}
+
+/// 处理器错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 数据无法反序列化（永久错误，数据被丢弃）
+    Decode,
+    /// I/O 错误
+    Io,
+    /// 下游无法及时接收数据
+    Backpressure,
+    /// 处理器或其后台任务已停止
+    Shutdown,
+    /// 其他错误
+    Other,
+}
+
+/// 处理器错误
+///
+/// 工作线程按退避重试 `retryable` 的错误，重试耗尽或不可重试的错误通过
+/// [`DiagnosticEvent::ProcessorFailed`](crate::diagnostics::DiagnosticEvent::ProcessorFailed) 上报一次。
+#[derive(Debug)]
+pub struct ProcessorError {
+    /// 错误类别
+    pub kind: ErrorKind,
+    /// 是否可以重试
+    pub retryable: bool,
+    /// 错误描述
+    pub message: String,
+    /// 底层错误
+    pub source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl ProcessorError {
+    /// 创建不可重试的错误
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, retryable: false, message: message.into(), source: None }
+    }
+
+    /// 反序列化错误
+    pub fn decode(error: bincode::error::DecodeError) -> Self {
+        Self {
+            source: Some(Box::new(error)),
+            ..Self::new(ErrorKind::Decode, "反序列化失败")
+        }
+    }
+
+    /// I/O 错误，`Interrupted`、`WouldBlock` 和 `TimedOut` 可以重试
+    pub fn io(message: impl Into<String>, error: std::io::Error) -> Self {
+        let retryable = matches!(
+            error.kind(),
+            std::io::ErrorKind::Interrupted | std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        );
+        Self {
+            retryable,
+            source: Some(Box::new(error)),
+            ..Self::new(ErrorKind::Io, message)
         }
     }
 }
 
+impl std::fmt::Display for ProcessorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "{}: {}", self.message, source),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for ProcessorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<String> for ProcessorError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorKind::Other, message)
+    }
+}
+
+/// 可重试错误的最大重试次数
+const MAX_RETRIES: u32 = 3;
+
+/// 第一次重试前的等待时间（之后每次翻倍）
+const RETRY_BACKOFF: Duration = Duration::from_millis(10);
+
 /// 处理器 trait - 各个处理器实现具体的处理逻辑
 pub trait LogProcessor: Send + 'static {
     /// 处理器名称
     fn name(&self) -> &'static str;
 
     /// 处理单个日志数据
-    fn process(&mut self, data: &[u8]) -> Result<(), String>;
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError>;
 
     /// 批量处理日志数据 - 保持原有优化逻辑
-    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), String> {
+    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
         // 默认实现：逐个处理
         for data in batch {
-            if let Err(e) = self.process(data) {
-                return Err(e);
-            }
+            self.process(data)?;
         }
         Ok(())
     }
 
+    /// 批量处理原始数据（每项为一行不含格式的文本，不经过记录编码和格式化）- 默认忽略
+    fn process_raw_batch(&mut self, _batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
+    /// 批量转发已编码的网络数据包（中继场景，见 `LoggerCore::forward_net_packet`）- 默认忽略（只有 UDP 处理器处理）
+    fn process_packet_batch(&mut self, _batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
     /// 处理文件轮转命令 - 默认忽略（只有文件处理器需要处理）
-    fn handle_rotate(&mut self) -> Result<(), String> {
+    fn handle_rotate(&mut self) -> Result<(), ProcessorError> {
         Ok(())
     }
 
     /// 处理文件压缩命令 - 默认忽略（只有文件处理器需要处理）
-    fn handle_compress(&mut self, _path: &std::path::Path) -> Result<(), String> {
+    fn handle_compress(&mut self, _path: &std::path::Path) -> Result<(), ProcessorError> {
         Ok(())
     }
 
+    /// 处理器内部缓冲数据的刷新截止时间 - 默认无内部缓冲
+    ///
+    /// 返回 `Some` 时，工作线程即使空闲也会在截止时间到达后调用 `flush()`
+    fn pending_deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    /// 是否接收强制写入 - 默认接收
+    ///
+    /// 返回 `false` 时，紧急日志和 Error 级别日志不再走强制写入路径：
+    /// 通过级别过滤的记录改为普通写入随批量处理，未通过的紧急日志不会发给该处理器
+    fn accepts_emergency(&self) -> bool {
+        true
+    }
+
+    /// 接收诊断上报句柄 - 默认忽略（需要上报错误或丢弃计数的处理器覆盖）
+    fn set_diagnostics(&mut self, _diagnostics: Diagnostics) {}
+
+    /// 工作线程启动后、开始处理命令前调用（如建立连接）- 默认不做任何事
+    ///
+    /// 返回后工作线程才视为就绪，耗时计入 [`ProcessorManager::readiness_report`]。
+    fn on_worker_start(&mut self) {}
+
     /// 刷新操作
-    fn flush(&mut self) -> Result<(), String>;
+    fn flush(&mut self) -> Result<(), ProcessorError>;
 
     /// 清理资源
-    fn cleanup(&mut self) -> Result<(), String>;
+    fn cleanup(&mut self) -> Result<(), ProcessorError>;
+}
+
+/// 批量缓冲区中数据的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    /// 编码记录
+    Record,
+    /// 原始数据（`WriteRaw`）
+    Raw,
+    /// 转发的网络数据包（`ForwardPacket`）
+    Packet,
+}
+
+/// 工作线程的批量缓冲区
+///
+/// 同一批次只包含一种类型的数据，类型切换时先处理已缓冲的批次，保持写入顺序。
+struct BatchBuffer {
+    entries: Vec<Vec<u8>>,
+    /// 缓冲的数据类型
+    kind: EntryKind,
+    /// 缓冲的字节数（处理后从 `in_flight_bytes` 中扣除）
+    bytes: u64,
+    /// 缓冲期间到达的管道探测标记的产生时间（批次处理完后记录延迟）
+    probes: Vec<Instant>,
+}
+
+impl BatchBuffer {
+    fn with_capacity(capacity: usize) -> Self {
+        Self { entries: Vec::with_capacity(capacity), kind: EntryKind::Record, bytes: 0, probes: Vec::new() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+thread_local! {
+    /// 当前线程是否为处理器工作线程
+    static WORKER_THREAD: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// 当前线程是否为处理器工作线程（工作线程中的诊断回调记录日志时不能等待管道腾出空间）
+pub(crate) fn on_worker_thread() -> bool {
+    WORKER_THREAD.with(|flag| flag.get())
+}
+
+/// 工作线程收到的命令来自哪个通道
+enum Lane {
+    Data(LogCommand),
+    Control(LogCommand),
+}
+
+/// 同时等待数据通道和控制通道，`deadline` 为 None 时一直等待
+///
+/// 任一通道断开视为断开（两个通道的发送端由 `ProcessorWorker` 同时持有）。
+fn next_command(
+    receiver: &Receiver<LogCommand>,
+    control: &Receiver<LogCommand>,
+    deadline: Option<Instant>,
+) -> Result<Lane, crossbeam_channel::RecvTimeoutError> {
+    let mut select = Select::new();
+    let control_index = select.recv(control);
+    select.recv(receiver);
+    let operation = match deadline {
+        None => select.select(),
+        Some(deadline) => select.select_deadline(deadline).map_err(|_| crossbeam_channel::RecvTimeoutError::Timeout)?,
+    };
+    let disconnected = |_| crossbeam_channel::RecvTimeoutError::Disconnected;
+    if operation.index() == control_index {
+        operation.recv(control).map(Lane::Control).map_err(disconnected)
+    } else {
+        operation.recv(receiver).map(Lane::Data).map_err(disconnected)
+    }
+}
+
+/// 命令携带的数据大小（计入 `in_flight_bytes` 的部分）
+fn payload_len(command: &LogCommand) -> usize {
+    match command {
+        LogCommand::Write(data) | LogCommand::WriteRaw(data) | LogCommand::ForwardPacket(data) | LogCommand::WriteForce(data) => {
+            data.len()
+        }
+        LogCommand::Enrich(record, _) => record.len(),
+        _ => 0,
+    }
+}
+
+/// 编码管道探测标记记录（见 [`ProbeMarker::Written`]）
+fn probe_record() -> Option<Vec<u8>> {
+    let record = crate::config::Record {
+        metadata: Arc::new(crate::config::Metadata {
+            level: Level::Info,
+            target: crate::config::PROBE_TARGET.to_string(),
+            auth_token: None,
+            app_id: None,
+        }),
+        args: "pipeline probe".into(),
+        timestamp: Some(std::time::SystemTime::now()),
+        record_id: Some(crate::config::next_record_id()),
+        ..Default::default()
+    };
+    crate::config::encode_record_v2(&record).ok()
 }
 
+/// 处理器类型名称是否匹配 `kind`（完整名称如 `"udp_processor"` 或简称如 `"udp"`）
+pub(crate) fn matches_kind(processor_type: &str, kind: &str) -> bool {
+    processor_type == kind || processor_type.strip_suffix("_processor") == Some(kind)
+}
+
+/// 控制通道的容量（控制命令很少，满时的刷新请求与已排队的刷新合并）
+const CONTROL_CAPACITY: usize = 64;
+
+/// 刷新前按顺序处理的积压命令数下限（批量记录数更大时为批量记录数），积压较少时刷新覆盖此前提交的全部记录
+const MIN_FLUSH_BACKLOG: usize = 1024;
+
 /// 单个处理器的工作线程
+///
+/// 数据和需要保持顺序的命令（写入、轮转、压缩、`sync_point` 的确认刷新、停止）经数据通道按顺序处理；
+/// 刷新、立即确认刷新和健康检查经控制通道发送，工作线程在处理每条数据命令前检查控制通道，
+/// 队列中积压再多，刷新也最多在一个批次（至少 1024 条命令）之后开始。
 pub struct ProcessorWorker {
     sender: Sender<LogCommand>,
+    /// 控制通道（刷新、立即确认刷新、健康检查）
+    control: Sender<LogCommand>,
     worker_thread: Option<thread::JoinHandle<()>>,
-    config: BatchConfig,
+    /// 当前的批量配置
+    config: Mutex<BatchConfig>,
     /// 处理器类型名称
-    processor_type: String,
+    processor_type: &'static str,
+    /// 处理器名称（由 `ProcessorManager` 分配，同一管理器中唯一，见 [`HandlerHandle`]）
+    name: String,
+    /// 是否接收强制写入
+    accept_emergency: bool,
+    /// 接收的级别集合（只作用于带级别广播的记录）
+    levels: LevelMask,
+    /// 当前操作的计时（卡住检测）
+    tracker: Arc<OpTracker>,
+    /// 工作线程就绪通知
+    ready: Arc<WorkerReady>,
+    /// 共享的运行统计（发送的数据计入 `in_flight_bytes`）
+    stats: Arc<LoggerStats>,
+    /// 管道探测延迟
+    probe_latency: Arc<ProbeLatency>,
 }
 
 impl ProcessorWorker {
     /// 创建新的处理器工作线程
-    pub fn new<P>(mut processor: P, config: BatchConfig) -> Self
+    pub fn new<P>(processor: P, config: BatchConfig) -> Self
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        Self::with_diagnostics(processor, config, Diagnostics::new())
+    }
+
+    /// 创建新的处理器工作线程，处理失败通过 `diagnostics` 上报
+    pub fn with_diagnostics<P>(processor: P, config: BatchConfig, diagnostics: Diagnostics) -> Self
     where
         P: LogProcessor + Send + 'static,
     {
@@ -175,279 +655,676 @@ impl ProcessorWorker {
         if let Err(e) = config.validate() {
             panic!("BatchConfig 验证失败: {}\n请检查您的配置并修复上述问题后再重试。", e);
         }
+        for warning in config.warnings() {
+            eprintln!("[batch] 警告: {}", warning);
+        }
 
         let (sender, receiver) = unbounded();
+        let (control, control_receiver) = bounded(CONTROL_CAPACITY);
         let config_clone = config.clone();
         let processor_name = processor.name();
+        let accept_emergency = processor.accepts_emergency();
+        let tracker = Arc::new(OpTracker::new(processor_name));
+        let worker_tracker = tracker.clone();
+        let ready = Arc::new(WorkerReady::new());
+        let worker_ready = ready.clone();
+        let stats = diagnostics.stats().clone();
+        let probe_latency = Arc::new(ProbeLatency::default());
+        let worker_probe_latency = probe_latency.clone();
 
         let worker_thread = thread::spawn(move || {
-            Self::worker_thread(processor, receiver, config_clone, processor_name);
+            WORKER_THREAD.with(|flag| flag.set(true));
+            let mut worker = Worker { processor, diagnostics, tracker: worker_tracker, probe_latency: worker_probe_latency };
+            worker.run_loop(receiver, control_receiver, config_clone, &worker_ready);
         });
 
         Self {
             sender,
+            control,
             worker_thread: Some(worker_thread),
-            config,
-            processor_type: processor_name.to_string(),
+            config: Mutex::new(config),
+            processor_type: processor_name,
+            name: processor_name.to_string(),
+            accept_emergency,
+            levels: LevelMask::ALL,
+            tracker,
+            ready,
+            stats,
+            probe_latency,
+        }
+    }
+
+    /// 发送写入命令
+    pub fn send_write(&self, data: Vec<u8>) -> Result<(), String> {
+        let len = data.len();
+        self.send_data(LogCommand::Write(data), len, "写入")
+    }
+
+    /// 发送轮转命令
+    pub fn send_rotate(&self) -> Result<(), String> {
+        let command = LogCommand::Rotate;
+        self.sender.send(command)
+            .map_err(|e| format!("发送轮转命令失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 发送压缩命令
+    pub fn send_compress(&self, path: std::path::PathBuf) -> Result<(), String> {
+        let command = LogCommand::Compress(path);
+        self.sender.send(command)
+            .map_err(|e| format!("发送压缩命令失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 发送刷新命令（经控制通道，不排在积压的写入之后）
+    pub fn send_flush(&self) -> Result<(), String> {
+        match self.control.try_send(LogCommand::Flush) {
+            // 控制通道已满：已排队的刷新命令会完成这次刷新
+            Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(e) => Err(format!("发送刷新命令失败: {}", e)),
+        }
+    }
+
+    /// 发送立即确认刷新命令（经控制通道）：工作线程最多再处理一个批次（至少 1024 条命令）后刷新并通过 `ack` 回复，
+    /// 不等待队列中其余的积压
+    pub fn send_priority_flush_ack(&self, ack: Sender<bool>) -> Result<(), String> {
+        self.control.send(LogCommand::FlushAck(ack))
+            .map_err(|e| format!("发送立即确认刷新命令失败: {}", e))
+    }
+
+    /// 发送带确认的刷新命令，工作线程处理完此前的所有数据并刷新后通过 `ack` 回复
+    pub fn send_flush_ack(&self, ack: Sender<bool>) -> Result<(), String> {
+        let command = LogCommand::FlushAck(ack);
+        self.sender.send(command)
+            .map_err(|e| format!("发送确认刷新命令失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 等待工作线程进入处理循环，超时返回 false
+    pub fn wait_ready(&self, timeout_ms: u64) -> bool {
+        self.ready.wait_until(Instant::now() + Duration::from_millis(timeout_ms))
+    }
+
+    /// 工作线程是否已经退出
+    pub fn is_finished(&self) -> bool {
+        self.worker_thread.as_ref().is_none_or(|t| t.is_finished())
+    }
+
+    /// 发送原始数据写入命令
+    pub fn send_write_raw(&self, data: Vec<u8>) -> Result<(), String> {
+        let len = data.len();
+        self.send_data(LogCommand::WriteRaw(data), len, "原始写入")
+    }
+
+    /// 发送转发数据包命令
+    pub fn send_forward_packet(&self, packet: Vec<u8>) -> Result<(), String> {
+        let len = packet.len();
+        self.send_data(LogCommand::ForwardPacket(packet), len, "转发")
+    }
+
+    /// 发送待富化的记录，`force` 为 true 时富化后按强制写入处理
+    pub fn send_enrich(&self, record: Arc<PendingEnrichment>, force: bool) -> Result<(), String> {
+        let len = record.len();
+        self.send_data(LogCommand::Enrich(record, force), len, "写入")
+    }
+
+    /// 发送强制写入命令（忽略批量限制）
+    pub fn send_write_force(&self, data: Vec<u8>) -> Result<(), String> {
+        let len = data.len();
+        self.send_data(LogCommand::WriteForce(data), len, "强制写入")
+    }
+
+    /// 发送携带数据的命令：数据大小在发送前计入 `in_flight_bytes`，工作线程处理完后扣除
+    fn send_data(&self, command: LogCommand, len: usize, what: &str) -> Result<(), String> {
+        self.stats.add_in_flight(len as u64);
+        self.sender.send(command).map_err(|e| {
+            self.stats.remove_in_flight(len as u64);
+            format!("发送{}命令失败: {}", what, e)
+        })
+    }
+
+    /// 发送管道探测标记（经数据通道，排在此前的记录之后）
+    pub fn send_probe(&self, captured: Instant) -> Result<(), String> {
+        self.sender.send(LogCommand::Probe(captured))
+            .map_err(|e| format!("发送探测命令失败: {}", e))
+    }
+
+    /// 管道探测延迟
+    pub fn probe_report(&self) -> ProbeReport {
+        self.probe_latency.report(self.processor_type)
+    }
+
+    /// 发送停止命令
+    pub fn send_shutdown(&self) -> Result<(), String> {
+        let command = LogCommand::Shutdown("ProcessorWorker::send_shutdown");
+        self.sender.send(command)
+            .map_err(|e| format!("发送停止命令失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 获取发送者（用于高级用法）
+    pub fn sender(&self) -> &Sender<LogCommand> {
+        &self.sender
+    }
+
+    /// 获取当前的批量配置
+    pub fn config(&self) -> BatchConfig {
+        self.config.lock().clone()
+    }
+
+    /// 发送新的批量配置（经控制通道），工作线程在下一次循环时按新配置批量处理
+    pub fn send_update_batch(&self, config: BatchConfig) -> Result<(), String> {
+        config.validate()?;
+        *self.config.lock() = config.clone();
+        self.control.send(LogCommand::UpdateBatch(config))
+            .map_err(|e| format!("发送批量配置更新命令失败: {}", e))
+    }
+
+    /// 数据通道中等待工作线程取出的命令数
+    pub fn queue_len(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// 获取处理器类型
+    pub fn get_processor_type(&self) -> &'static str {
+        self.processor_type
+    }
+
+    /// 处理器名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 设置处理器名称
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// 是否接收强制写入
+    pub fn accepts_emergency(&self) -> bool {
+        self.accept_emergency
+    }
+
+    /// 设置接收的级别集合
+    pub fn with_levels(mut self, levels: LevelMask) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// 是否接收该级别的记录
+    pub fn accepts_level(&self, level: Level) -> bool {
+        self.levels.contains(level)
+    }
+
+    /// 执行健康检查，验证工作线程是否正常运行
+    pub fn health_check(&self, timeout_ms: u64) -> Result<(), String> {
+        let (response_sender, response_receiver) = unbounded();
+
+        // 发送健康检查命令
+        let command = LogCommand::HealthCheck(response_sender);
+        self.control.send(command)
+            .map_err(|e| format!("发送健康检查命令失败: {}", e))?;
+
+        // 等待响应
+        let timeout = Duration::from_millis(timeout_ms);
+        match response_receiver.recv_timeout(timeout) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("工作线程响应异常".to_string()),
+            Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
+                Err(format!("健康检查超时（{}ms）", timeout_ms))
+            }
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
+                Err("工作线程已断开连接".to_string())
+            }
         }
     }
+}
 
+/// 工作线程持有的处理器及其诊断句柄
+struct Worker<P> {
+    processor: P,
+    diagnostics: Diagnostics,
+    tracker: Arc<OpTracker>,
+    probe_latency: Arc<ProbeLatency>,
+}
+
+impl<P: LogProcessor> Worker<P> {
     /// 工作线程实现 - 保持与原有文件处理器相同的批量处理逻辑
-    fn worker_thread<P>(
-        mut processor: P,
-        receiver: Receiver<LogCommand>,
-        config: BatchConfig,
-        processor_name: &'static str,
-    ) where
-        P: LogProcessor + Send + 'static,
-    {
+    fn run_loop(&mut self, receiver: Receiver<LogCommand>, control: Receiver<LogCommand>, mut config: BatchConfig, ready: &WorkerReady) {
+        self.processor.on_worker_start();
+
         // 发送就绪通知
         increment_ready_count();
-        let mut batch_buffer = Vec::with_capacity(config.buffer_size);
+        ready.mark_ready();
+        // 缓冲的记录数达到批量记录数就会处理，不需要预分配更多
+        let mut batch_buffer = BatchBuffer::with_capacity(config.buffer_size.min(config.batch_size));
         let mut last_flush = Instant::now();
-        let flush_interval = Duration::from_millis(config.batch_interval_ms);
 
-        // 核心优化：缓冲区为空时永久阻塞（0% CPU），有数据时用短超时保证及时刷新
+        // 核心优化：没有任何待刷新数据时永久阻塞（0% CPU），有数据时等到最近的刷新截止时间
         loop {
-            if batch_buffer.is_empty() {
-                // 空闲状态：永久阻塞，完全不消耗 CPU
-                match receiver.recv() {
-                    Ok(cmd) => {
-                        match cmd {
-                            LogCommand::Write(data) => {
-                                batch_buffer.push(data);
-                                // 检查是否需要批量刷新
-                                if batch_buffer.len() >= config.batch_size ||
-                                   last_flush.elapsed() >= flush_interval {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                    last_flush = Instant::now();
-                                }
-                            }
-                            LogCommand::WriteForce(data) => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.process(&data);
-                                let _ = processor.flush();
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Rotate => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.handle_rotate();
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Compress(path) => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.handle_compress(&path);
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Flush => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.flush();
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Shutdown(_) => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.flush();
-                                let _ = processor.cleanup();
-                                std::process::exit(0);
-                            }
-                            LogCommand::HealthCheck(tx) => {
-                                let _ = tx.send(true);
-                            }
-                        }
+            // 控制命令优先：每条数据命令之前先处理已到达的控制命令
+            let mut running = true;
+            while let (true, Ok(cmd)) = (running, control.try_recv()) {
+                running = self.handle_control(cmd, &receiver, &mut batch_buffer, &mut last_flush, &mut config);
+            }
+            if !running {
+                break;
+            }
+
+            // 批量配置可能被控制命令更新，每次循环重新读取
+            let flush_interval = Duration::from_millis(config.batch_interval_ms);
+            let batch_deadline = (!batch_buffer.is_empty()).then(|| last_flush + flush_interval);
+            let deadline = match (batch_deadline, self.processor.pending_deadline()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+
+            // 空闲状态：永久阻塞，完全不消耗 CPU；有数据待处理：等到截止时间确保及时刷新
+            let cmd = match next_command(&receiver, &control, deadline) {
+                Ok(Lane::Control(cmd)) => {
+                    if !self.handle_control(cmd, &receiver, &mut batch_buffer, &mut last_flush, &mut config) {
+                        break;
                     }
-                    Err(crossbeam_channel::RecvError) => break,
+                    continue;
                 }
-            } else {
-                // 有数据待处理：用短超时确保及时刷新
-                match receiver.recv_timeout(Duration::from_millis(config.batch_interval_ms)) {
-                    Ok(cmd) => {
-                        match cmd {
-                            LogCommand::Write(data) => {
-                                batch_buffer.push(data);
-                                if batch_buffer.len() >= config.batch_size ||
-                                   last_flush.elapsed() >= flush_interval {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                    last_flush = Instant::now();
-                                }
-                            }
-                            LogCommand::WriteForce(data) => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.process(&data);
-                                let _ = processor.flush();
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Rotate => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.handle_rotate();
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Compress(path) => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.handle_compress(&path);
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Flush => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.flush();
-                                last_flush = Instant::now();
-                            }
-                            LogCommand::Shutdown(_) => {
-                                if !batch_buffer.is_empty() {
-                                    let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                                }
-                                let _ = processor.flush();
-                                let _ = processor.cleanup();
-                                std::process::exit(0);
-                            }
-                            LogCommand::HealthCheck(tx) => {
-                                let _ = tx.send(true);
-                            }
-                        }
+                Ok(Lane::Data(cmd)) => cmd,
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
+                    // 超时：检查刷新
+                    if !batch_buffer.is_empty() && last_flush.elapsed() >= flush_interval {
+                        self.process_batch(&mut batch_buffer);
+                        last_flush = Instant::now();
                     }
-                    Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
-                        // 超时：检查刷新
-                        if last_flush.elapsed() >= flush_interval {
-                            let _ = Self::process_batch(&mut processor, &mut batch_buffer);
-                            last_flush = Instant::now();
-                        }
+                    if self.processor.pending_deadline().is_some_and(|d| Instant::now() >= d) {
+                        self.run(|p| p.flush());
                     }
-                    Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
+                    continue;
+                }
+                Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
+                    self.process_batch(&mut batch_buffer);
+                    self.run(|p| p.flush());
+                    break;
                 }
+            };
+
+            if !self.handle_command(cmd, &receiver, &mut batch_buffer, &mut last_flush, &mut config) {
+                break;
             }
         }
+
+        // 退出后不再处理的数据不计入待处理字节数
+        let pending: usize = receiver.try_iter().map(|cmd| payload_len(&cmd)).sum();
+        self.diagnostics.stats().remove_in_flight(pending as u64);
+    }
+
+    /// 处理控制命令：先按顺序处理数据通道中最多一个批次（至少 1024 条命令）的积压，再执行命令
+    ///
+    /// 返回 false 表示工作线程应当退出
+    fn handle_control(
+        &mut self,
+        cmd: LogCommand,
+        receiver: &Receiver<LogCommand>,
+        batch_buffer: &mut BatchBuffer,
+        last_flush: &mut Instant,
+        config: &mut BatchConfig,
+    ) -> bool {
+        if matches!(cmd, LogCommand::Flush | LogCommand::FlushAck(_)) {
+            for cmd in receiver.try_iter().take(config.batch_size.max(MIN_FLUSH_BACKLOG)) {
+                if !self.handle_command(cmd, receiver, batch_buffer, last_flush, config) {
+                    return false;
+                }
+            }
+        }
+        self.handle_command(cmd, receiver, batch_buffer, last_flush, config)
+    }
+
+    /// 处理单条命令，返回 false 表示工作线程应当退出
+    fn handle_command(
+        &mut self,
+        cmd: LogCommand,
+        receiver: &Receiver<LogCommand>,
+        batch_buffer: &mut BatchBuffer,
+        last_flush: &mut Instant,
+        config: &mut BatchConfig,
+    ) -> bool {
+        match cmd {
+            LogCommand::Write(data) => {
+                self.buffer_write(batch_buffer, data, EntryKind::Record, last_flush, config);
+            }
+            LogCommand::WriteRaw(data) => {
+                self.buffer_write(batch_buffer, data, EntryKind::Raw, last_flush, config);
+            }
+            LogCommand::ForwardPacket(packet) => {
+                self.buffer_write(batch_buffer, packet, EntryKind::Packet, last_flush, config);
+            }
+            LogCommand::Enrich(record, force) => {
+                let data = self.enrich(&record);
+                let cmd = if force { LogCommand::WriteForce(data) } else { LogCommand::Write(data) };
+                return self.handle_command(cmd, receiver, batch_buffer, last_flush, config);
+            }
+            LogCommand::WriteForce(data) => {
+                self.process_batch(batch_buffer);
+                let next = self.write_force(data, receiver, config);
+                *last_flush = Instant::now();
+                // 合并窗口内到达的其他命令在强制写入之后按顺序处理（不会是强制写入）
+                if let Some(cmd) = next {
+                    return self.handle_command(cmd, receiver, batch_buffer, last_flush, config);
+                }
+            }
+            LogCommand::Rotate => {
+                self.process_batch(batch_buffer);
+                self.run(|p| p.handle_rotate());
+                *last_flush = Instant::now();
+            }
+            LogCommand::Compress(path) => {
+                self.process_batch(batch_buffer);
+                self.run(|p| p.handle_compress(&path));
+                *last_flush = Instant::now();
+            }
+            LogCommand::Flush => {
+                self.process_batch(batch_buffer);
+                self.run(|p| p.flush());
+                *last_flush = Instant::now();
+            }
+            LogCommand::FlushAck(ack) => {
+                self.process_batch(batch_buffer);
+                let ok = self.run(|p| p.flush());
+                *last_flush = Instant::now();
+                let _ = ack.send(ok);
+            }
+            LogCommand::Shutdown(_) => {
+                // 只退出工作线程，不能结束整个进程
+                self.process_batch(batch_buffer);
+                self.run(|p| p.flush());
+                self.run(|p| p.cleanup());
+                return false;
+            }
+            LogCommand::HealthCheck(tx) => {
+                let _ = tx.send(true);
+            }
+            LogCommand::Probe(captured) => {
+                // 没有缓冲的记录时立即完成，否则等包含它的批次处理完
+                if batch_buffer.is_empty() {
+                    self.probe_latency.record(captured.elapsed());
+                } else {
+                    batch_buffer.probes.push(captured);
+                }
+            }
+            LogCommand::UpdateBatch(new_config) => {
+                // 已缓冲的记录达到新的批量记录数时立即处理；新的批量间隔从上次处理时开始计算
+                if batch_buffer.len() >= new_config.batch_size {
+                    self.process_batch(batch_buffer);
+                    *last_flush = Instant::now();
+                }
+                *config = new_config;
+            }
+        }
+        true
+    }
+
+    /// 处理强制写入：合并窗口内连续到达的强制写入作为一批处理，最后刷新一次
+    ///
+    /// 返回窗口内收到的第一条非强制写入命令，由调用方继续处理
+    fn write_force(&mut self, data: Vec<u8>, receiver: &Receiver<LogCommand>, config: &BatchConfig) -> Option<LogCommand> {
+        let mut forced = vec![data];
+        let mut next = None;
+        if config.emergency_coalesce_ms > 0 {
+            let deadline = Instant::now() + Duration::from_millis(config.emergency_coalesce_ms);
+            while forced.len() < config.batch_size {
+                match receiver.recv_deadline(deadline) {
+                    Ok(LogCommand::WriteForce(data)) => forced.push(data),
+                    Ok(LogCommand::Enrich(record, true)) => forced.push(self.enrich(&record)),
+                    Ok(cmd) => {
+                        next = Some(cmd);
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+
+        if forced.len() == 1 {
+            self.run(|p| p.process(&forced[0]));
+        } else {
+            self.run(|p| p.process_batch(&forced));
+        }
+        self.run(|p| p.flush());
+        self.diagnostics.stats().remove_in_flight(forced.iter().map(|data| data.len() as u64).sum());
+        next
+    }
+
+    /// 取得富化后的记录编码（同一条记录只富化一次），待处理字节数按富化后的大小计算
+    fn enrich(&self, record: &PendingEnrichment) -> Vec<u8> {
+        let data = record.resolve(&self.diagnostics);
+        let stats = self.diagnostics.stats();
+        stats.add_in_flight(data.len() as u64);
+        stats.remove_in_flight(record.len() as u64);
+        data
+    }
+
+    /// 缓冲写入数据，达到批量记录数或刷新间隔时处理
+    fn buffer_write(
+        &mut self,
+        batch_buffer: &mut BatchBuffer,
+        data: Vec<u8>,
+        kind: EntryKind,
+        last_flush: &mut Instant,
+        config: &BatchConfig,
+    ) {
+        // 数据类型切换：先处理已缓冲的批次
+        if batch_buffer.kind != kind {
+            self.process_batch(batch_buffer);
+            batch_buffer.kind = kind;
+        }
+        batch_buffer.bytes += data.len() as u64;
+        batch_buffer.entries.push(data);
+
+        // 检查是否需要批量刷新
+        if batch_buffer.len() >= config.batch_size ||
+           last_flush.elapsed() >= Duration::from_millis(config.batch_interval_ms) {
+            self.process_batch(batch_buffer);
+            *last_flush = Instant::now();
+        }
     }
 
     /// 处理批量数据
-    fn process_batch<P>(processor: &mut P, batch: &mut Vec<Vec<u8>>) -> Result<(), String>
-    where
-        P: LogProcessor,
-    {
+    fn process_batch(&mut self, batch: &mut BatchBuffer) {
         if batch.is_empty() {
-            return Ok(());
+            return;
         }
 
-        let result = processor.process_batch(batch);
-        batch.clear(); // 确保缓冲区被清空
-        result
+        let entries = std::mem::take(&mut batch.entries);
+        match batch.kind {
+            EntryKind::Record => self.run(|p| p.process_batch(&entries)),
+            EntryKind::Raw => self.run(|p| p.process_raw_batch(&entries)),
+            EntryKind::Packet => self.run(|p| p.process_packet_batch(&entries)),
+        };
+        self.diagnostics.stats().remove_in_flight(std::mem::take(&mut batch.bytes));
+        for captured in batch.probes.drain(..) {
+            self.probe_latency.record(captured.elapsed());
+        }
+        // 复用缓冲区的容量
+        batch.entries = entries;
+        batch.entries.clear();
     }
 
-    /// 发送写入命令
-    pub fn send_write(&self, data: Vec<u8>) -> Result<(), String> {
-        let command = LogCommand::Write(data);
-        self.sender.send(command)
-            .map_err(|e| format!("发送写入命令失败: {}", e))?;
-        Ok(())
+    /// 执行处理器操作：可重试的错误按退避重试，最终失败时上报一次，返回是否成功
+    fn run(&mut self, op: impl FnMut(&mut P) -> Result<(), ProcessorError>) -> bool {
+        // 计时覆盖包括重试在内的整个操作
+        self.tracker.begin();
+        let ok = self.run_with_retry(op);
+        self.tracker.end(&self.diagnostics);
+        ok
     }
 
-    /// 发送轮转命令
-    pub fn send_rotate(&self) -> Result<(), String> {
-        let command = LogCommand::Rotate;
-        self.sender.send(command)
-            .map_err(|e| format!("发送轮转命令失败: {}", e))?;
-        Ok(())
+    fn run_with_retry(&mut self, mut op: impl FnMut(&mut P) -> Result<(), ProcessorError>) -> bool {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match op(&mut self.processor) {
+                Ok(()) => return true,
+                Err(e) if e.retryable && attempts <= MAX_RETRIES => {
+                    thread::sleep(RETRY_BACKOFF * 2u32.pow(attempts - 1));
+                }
+                Err(e) => {
+                    self.diagnostics.emit(DiagnosticEvent::ProcessorFailed {
+                        processor: self.processor.name(),
+                        kind: e.kind,
+                        attempts,
+                        error: e.to_string(),
+                    });
+                    return false;
+                }
+            }
+        }
     }
+}
 
-    /// 发送压缩命令
-    pub fn send_compress(&self, path: std::path::PathBuf) -> Result<(), String> {
-        let command = LogCommand::Compress(path);
-        self.sender.send(command)
-            .map_err(|e| format!("发送压缩命令失败: {}", e))?;
-        Ok(())
+// ProcessorWorker 不应该实现 Clone，因为每个实例代表一个真实的工作线程
+// impl Clone for ProcessorWorker {
+//     fn clone(&self) -> Self {
+//         Self {
+//             sender: self.sender.clone(),
+//             worker_thread: None, // 不克隆工作线程，只克隆发送者
+//             config: self.config.clone(),
+//             processor_type: self.processor_type.clone(),
+//         }
+//     }
+// }
+
+/// 移除处理器错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoveError {
+    /// 没有匹配的处理器
+    NotFound(String),
+    /// 工作线程未在超时时间内退出（处理器已从广播集合中移除，线程被分离），或替换时新的工作线程未在超时时间内就绪
+    Timeout(String),
+    /// 替换时有多个匹配的处理器
+    Ambiguous(String),
+}
+
+impl std::fmt::Display for RemoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoveError::NotFound(name) => write!(f, "没有找到处理器: {}", name),
+            RemoveError::Timeout(name) => write!(f, "等待处理器 {} 退出超时", name),
+            RemoveError::Ambiguous(name) => write!(f, "有多个匹配的处理器，无法替换: {}", name),
+        }
     }
+}
+
+impl std::error::Error for RemoveError {}
+
+/// 同步点（带确认的刷新）错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlushError {
+    /// 全局日志器未初始化
+    NotInitialized,
+    /// 处理器未在超时时间内确认
+    Timeout(Duration),
+    /// 处理器刷新失败
+    Failed,
+    /// 工作线程已断开连接
+    Disconnected,
+}
+
+impl std::fmt::Display for FlushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlushError::NotInitialized => write!(f, "全局日志器未初始化"),
+            FlushError::Timeout(timeout) => write!(f, "确认刷新超时（{}ms）", timeout.as_millis()),
+            FlushError::Failed => write!(f, "处理器刷新失败"),
+            FlushError::Disconnected => write!(f, "工作线程已断开连接"),
+        }
+    }
+}
+
+impl std::error::Error for FlushError {}
+
+/// 单个处理器的句柄（见 `LoggerCore::handler`）
+///
+/// 句柄按名称查找处理器，不会延长日志器的生命周期；处理器被移除或日志器释放后，
+/// 刷新返回 `FlushError::Disconnected`，其余操作返回 `NotFound`。
+#[derive(Clone)]
+pub struct HandlerHandle {
+    manager: std::sync::Weak<ProcessorManager>,
+    name: String,
+}
 
-    /// 发送刷新命令
-    pub fn send_flush(&self) -> Result<(), String> {
-        let command = LogCommand::Flush;
-        self.sender.send(command)
-            .map_err(|e| format!("发送刷新命令失败: {}", e))?;
-        Ok(())
+impl HandlerHandle {
+    pub(crate) fn new(manager: &Arc<ProcessorManager>, name: &str) -> Self {
+        Self { manager: Arc::downgrade(manager), name: name.to_string() }
     }
 
-    /// 发送强制写入命令（忽略批量限制）
-    pub fn send_write_force(&self, data: Vec<u8>) -> Result<(), String> {
-        let command = LogCommand::WriteForce(data);
-        self.sender.send(command)
-            .map_err(|e| format!("发送强制写入命令失败: {}", e))?;
-        Ok(())
+    /// 处理器名称
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
-    /// 发送停止命令
-    pub fn send_shutdown(&self) -> Result<(), String> {
-        let command = LogCommand::Shutdown("ProcessorWorker::send_shutdown");
-        self.sender.send(command)
-            .map_err(|e| format!("发送停止命令失败: {}", e))?;
-        Ok(())
+    fn worker(&self) -> Option<Arc<ProcessorWorker>> {
+        self.manager.upgrade()?.worker(&self.name)
     }
 
-    /// 获取发送者（用于高级用法）
-    pub fn sender(&self) -> &Sender<LogCommand> {
-        &self.sender
+    /// 等待该处理器处理完此前收到的记录并刷新，其他处理器不受影响
+    pub fn flush_sync(&self, timeout: Duration) -> Result<(), FlushError> {
+        let worker = self.worker().ok_or(FlushError::Disconnected)?;
+        ProcessorManager::collect_acks_from(std::slice::from_ref(&worker), timeout, ProcessorWorker::send_flush_ack)
     }
 
-    /// 获取批量配置
-    pub fn config(&self) -> &BatchConfig {
-        &self.config
+    /// 数据通道中等待工作线程取出的命令数（处理器已移除时为 0）
+    pub fn queue_len(&self) -> usize {
+        self.worker().map_or(0, |worker| worker.queue_len())
     }
 
-    /// 获取处理器类型
-    pub fn get_processor_type(&self) -> &str {
-        &self.processor_type
+    /// 当前的批量配置
+    pub fn batch_config(&self) -> Result<BatchConfig, RemoveError> {
+        self.worker().map(|worker| worker.config()).ok_or_else(|| RemoveError::NotFound(self.name.clone()))
     }
 
-    /// 执行健康检查，验证工作线程是否正常运行
-    pub fn health_check(&self, timeout_ms: u64) -> Result<(), String> {
-        let (response_sender, response_receiver) = unbounded();
+    /// 在运行中修改批量配置，工作线程在下一次循环时生效（已缓冲的记录按新的间隔和记录数处理）
+    pub fn set_batch_config(&self, config: BatchConfig) -> Result<(), String> {
+        let worker = self.worker().ok_or_else(|| RemoveError::NotFound(self.name.clone()).to_string())?;
+        worker.send_update_batch(config)
+    }
 
-        // 发送健康检查命令
-        let command = LogCommand::HealthCheck(response_sender);
-        self.sender.send(command)
-            .map_err(|e| format!("发送健康检查命令失败: {}", e))?;
+    /// 移除该处理器（见 `LoggerCore::detach_handler`）
+    pub fn detach(self) -> Result<(), RemoveError> {
+        let manager = self.manager.upgrade().ok_or_else(|| RemoveError::NotFound(self.name.clone()))?;
+        manager.remove_named(&self.name)
+    }
+}
 
-        // 等待响应
-        let timeout = Duration::from_millis(timeout_ms);
-        match response_receiver.recv_timeout(timeout) {
-            Ok(true) => Ok(()),
-            Ok(false) => Err("工作线程响应异常".to_string()),
-            Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
-                Err(format!("健康检查超时（{}ms）", timeout_ms))
-            }
-            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
-                Err("工作线程已断开连接".to_string())
-            }
+/// 关闭后提交数据时返回的错误
+const CLOSED: &str = "日志器已关闭";
+
+/// 移除处理器时等待工作线程退出的时间
+const REMOVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 释放管理器时关闭闸门等待正在发送的广播的时间
+const GATE_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 等待 `done` 成立，到达 `deadline` 时放弃并返回 false
+///
+/// 先让出几次时间片（通常的等待只有几微秒），之后按指数退避休眠，最长 5 毫秒一次，
+/// 长时间等待时不会占满一个核心。
+fn wait_with_backoff(deadline: Instant, mut done: impl FnMut() -> bool) -> bool {
+    let (mut spins, mut delay) = (0, Duration::from_micros(50));
+    while !done() {
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        if spins < 16 {
+            spins += 1;
+            thread::yield_now();
+        } else {
+            thread::sleep(delay.min(deadline - now));
+            delay = (delay * 2).min(Duration::from_millis(5));
         }
     }
+    true
 }
 
-// ProcessorWorker 不应该实现 Clone，因为每个实例代表一个真实的工作线程
-// impl Clone for ProcessorWorker {
-//     fn clone(&self) -> Self {
-//         Self {
-//             sender: self.sender.clone(),
-//             worker_thread: None, // 不克隆工作线程，只克隆发送者
-//             config: self.config.clone(),
-//             processor_type: self.processor_type.clone(),
-//         }
-//     }
-// }
-
 impl Drop for ProcessorWorker {
     fn drop(&mut self) {
         // 发送停止命令
@@ -462,28 +1339,218 @@ impl Drop for ProcessorWorker {
 
 /// 处理器管理器 - 管理所有处理器的工作线程
 pub struct ProcessorManager {
-    workers: Vec<ProcessorWorker>,
+    /// 广播集合（广播时读取快照，添加和移除处理器时整体替换；管道探测线程共享）
+    workers: Arc<ArcSwap<Vec<Arc<ProcessorWorker>>>>,
     /// 已验证的处理器类型集合
     verified_types: std::collections::HashSet<String>,
+    /// 诊断上报句柄（所有处理器共享）
+    diagnostics: Diagnostics,
+    /// 所有工作线程的操作计时
+    trackers: TrackerRegistry,
+    /// 卡住检测线程（设置了卡住阈值时存在）
+    stall_supervisor: Option<StallSupervisor>,
+    /// 内存预算（设置了预算时存在）
+    memory: Option<MemoryGovernor>,
+    /// 关闭闸门（关闭后拒绝新的记录）
+    gate: ShutdownGate,
+    /// 关闭后被拒绝的记录是否写入标准错误
+    stderr_on_shutdown: bool,
+    /// 记录富化函数（设置时记录在工作线程中富化后再处理）
+    enricher: Option<Enricher>,
+    /// 统计导出线程（设置了导出回调时存在）
+    stats_exporter: Option<PeriodicTask>,
+    /// 管道探测线程（设置了探测间隔时存在）
+    pipeline_probe: Option<PeriodicTask>,
+    /// 各处理器类型下一个自动名称的序号
+    next_ids: Mutex<std::collections::HashMap<&'static str, usize>>,
+}
+
+/// 广播给处理器的一条记录
+enum Outgoing {
+    /// 已编码的记录，每个处理器收到一份副本
+    Encoded(Vec<u8>),
+    /// 等待在工作线程中富化的记录
+    Pending(Arc<PendingEnrichment>),
+}
+
+impl Outgoing {
+    fn send(&self, worker: &ProcessorWorker, force: bool) -> Result<(), String> {
+        match self {
+            Outgoing::Encoded(data) if force => worker.send_write_force(data.clone()),
+            Outgoing::Encoded(data) => worker.send_write(data.clone()),
+            Outgoing::Pending(record) => worker.send_enrich(record.clone(), force),
+        }
+    }
+}
+
+/// 关闭闸门：关闭后广播拒绝新的记录，关闭时等待已通过检查的广播发送完毕
+///
+/// 关闭在期限内返回后不会再有记录进入工作线程的队列，之后广播的停止命令一定排在所有记录之后；
+/// 超时（如某个广播阻塞在已满的队列上）时不再等待，仍在发送的记录可能排在停止命令之后被丢弃。
+#[derive(Default)]
+struct ShutdownGate {
+    closed: AtomicBool,
+    /// 已通过检查、正在发送的广播数
+    active: AtomicUsize,
+    /// 关闭后最后一个广播完成时通知等待的关闭方
+    drained_lock: Mutex<()>,
+    drained: Condvar,
+}
+
+impl ShutdownGate {
+    /// 进入闸门，已关闭时返回 None；守卫释放前关闭会一直等待
+    fn enter(&self) -> Option<GateGuard<'_>> {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        if self.closed.load(Ordering::SeqCst) {
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(GateGuard(self))
+    }
+
+    /// 关闭闸门并在 `timeout` 内等待正在发送的广播完成，已经关闭时返回 false
+    ///
+    /// 超时后同样返回 true，调用方照常进入第二阶段。
+    fn close(&self, timeout: Duration) -> bool {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.drained_lock.lock();
+        while self.active.load(Ordering::SeqCst) > 0 {
+            if self.drained.wait_until(&mut guard, deadline).timed_out() {
+                break;
+            }
+        }
+        true
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
+
+struct GateGuard<'a>(&'a ShutdownGate);
+
+impl Drop for GateGuard<'_> {
+    fn drop(&mut self) {
+        let gate = self.0;
+        // 关闭方检查计数时持有锁，加锁后再通知不会错过它的等待
+        if gate.active.fetch_sub(1, Ordering::SeqCst) == 1 && gate.closed.load(Ordering::SeqCst) {
+            let _guard = gate.drained_lock.lock();
+            gate.drained.notify_all();
+        }
+    }
 }
 
 impl ProcessorManager {
     /// 创建新的处理器管理器
     pub fn new() -> Self {
         Self {
-            workers: Vec::new(),
+            workers: Arc::new(ArcSwap::from_pointee(Vec::new())),
             verified_types: std::collections::HashSet::new(),
+            diagnostics: Diagnostics::new(),
+            trackers: TrackerRegistry::default(),
+            stall_supervisor: None,
+            memory: None,
+            gate: ShutdownGate::default(),
+            stderr_on_shutdown: false,
+            enricher: None,
+            stats_exporter: None,
+            pipeline_probe: None,
+            next_ids: Mutex::new(std::collections::HashMap::new()),
         }
     }
 
+    /// 设置卡住阈值：处理器的单次操作超过该时间仍未完成时上报 `ProcessorStalled`，
+    /// 完成后上报 `ProcessorRecovered`（只上报，不中断工作线程）
+    pub fn set_stall_threshold(&mut self, threshold: Duration) {
+        // 先停止旧的检测线程
+        self.stall_supervisor = None;
+        self.stall_supervisor = Some(StallSupervisor::spawn(self.trackers.clone(), threshold, self.diagnostics.clone()));
+    }
+
+    /// 设置内存预算：已提交但尚未处理完的数据超过预算时按 `MemoryBudget::action` 处理之后广播的记录
+    pub fn set_memory_budget(&mut self, config: MemoryBudget) {
+        self.memory = Some(MemoryGovernor::new(config, self.diagnostics.clone()));
+    }
+
+    /// 设置关闭后被拒绝的记录是否写入标准错误（默认只计入 `LoggerStats::shutdown_rejected`）
+    pub fn set_stderr_on_shutdown(&mut self, enabled: bool) {
+        self.stderr_on_shutdown = enabled;
+    }
+
+    /// 设置记录富化函数：之后广播的记录由第一个处理它的工作线程富化一次，所有处理器使用富化后的记录
+    pub fn set_enricher(&mut self, enricher: Option<Enricher>) {
+        self.enricher = enricher;
+    }
+
+    /// 设置统计导出：每隔 `interval` 以统计快照调用一次 `exporter`，处理器管理器释放时停止
+    pub fn set_stats_exporter(&mut self, interval: Duration, exporter: StatsExporterFn) {
+        // 先停止旧的导出线程
+        self.stats_exporter = None;
+        self.stats_exporter = Some(PeriodicTask::stats_exporter(self.diagnostics.stats().clone(), interval, exporter));
+    }
+
+    /// 设置管道探测：每隔 `interval` 向所有处理器发送一个探测标记，处理器处理完标记之前的记录后记录延迟
+    /// （见 [`probe_reports`](Self::probe_reports)），处理器管理器释放时停止
+    pub fn set_pipeline_probe(&mut self, interval: Duration, marker: ProbeMarker) {
+        // 先停止旧的探测线程
+        self.pipeline_probe = None;
+        let workers = self.workers.clone();
+        self.pipeline_probe = Some(PeriodicTask::spawn("rat-logger-probe", "管道探测", interval, move || {
+            let captured = Instant::now();
+            let record = match marker {
+                ProbeMarker::Written => probe_record(),
+                ProbeMarker::Suppressed => None,
+            };
+            for worker in workers.load().iter() {
+                if let Some(data) = record.as_ref().filter(|_| worker.accepts_level(Level::Info)) {
+                    let _ = worker.send_write(data.clone());
+                }
+                let _ = worker.send_probe(captured);
+            }
+        }));
+    }
+
+    /// 各处理器的管道探测延迟（按添加顺序，未设置探测时探测次数为 0）
+    pub fn probe_reports(&self) -> Vec<ProbeReport> {
+        self.workers.load().iter().map(|worker| worker.probe_report()).collect()
+    }
+
+    /// 内存预算配置（未配置时为 None）
+    pub(crate) fn memory_budget(&self) -> Option<&MemoryBudget> {
+        self.memory.as_ref().map(|memory| memory.config())
+    }
+
+    /// 获取诊断上报句柄
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
     /// 添加处理器
     pub fn add_processor<P>(&mut self, processor: P, config: BatchConfig) -> Result<(), String>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        self.add_processor_with_levels(processor, config, LevelMask::ALL)
+    }
+
+    /// 添加只接收 `levels` 中级别的记录的处理器
+    pub fn add_processor_with_levels<P>(&mut self, processor: P, config: BatchConfig, levels: LevelMask) -> Result<(), String>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        self.add_named_processor(processor, config, levels, None)
+    }
+
+    /// 添加名为 `name` 的处理器（None 时自动命名，见 [`attach_named_processor`](Self::attach_named_processor)）
+    pub fn add_named_processor<P>(&mut self, processor: P, config: BatchConfig, levels: LevelMask, name: Option<String>) -> Result<(), String>
     where
         P: LogProcessor + Send + 'static,
     {
         let processor_type = processor.name().to_string();
-        let worker = ProcessorWorker::new(processor, config);
-        self.workers.push(worker);
+        self.attach_named_processor(processor, config, levels, name)?;
 
         // 新增处理器类型，需要重新验证
         self.verified_types.remove(&processor_type);
@@ -491,10 +1558,210 @@ impl ProcessorManager {
         Ok(())
     }
 
+    /// 在运行中添加处理器，之后广播的记录会发送给它
+    pub fn attach_processor<P>(&self, processor: P, config: BatchConfig) -> Result<(), String>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        self.attach_processor_with_levels(processor, config, LevelMask::ALL)
+    }
+
+    /// 在运行中添加只接收 `levels` 中级别的记录的处理器
+    pub fn attach_processor_with_levels<P>(&self, processor: P, config: BatchConfig, levels: LevelMask) -> Result<(), String>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        self.attach_named_processor(processor, config, levels, None)
+    }
+
+    /// 在运行中添加名为 `name` 的处理器，名称在同一管理器中必须唯一
+    ///
+    /// `name` 为 None 时按处理器类型和添加顺序自动命名（`file_processor#0`、`file_processor#1` …），
+    /// 序号不会因处理器被移除而复用。
+    pub fn attach_named_processor<P>(&self, mut processor: P, config: BatchConfig, levels: LevelMask, name: Option<String>) -> Result<(), String>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        let taken = |name: &str| self.workers.load().iter().any(|worker| worker.name() == name);
+        let name = match name {
+            Some(name) if taken(&name) => return Err(format!("配置错误: 处理器名称重复: {}", name)),
+            Some(name) => name,
+            None => {
+                let mut next_ids = self.next_ids.lock();
+                let next_id = next_ids.entry(processor.name()).or_default();
+                loop {
+                    let name = format!("{}#{}", processor.name(), next_id);
+                    *next_id += 1;
+                    if !taken(&name) {
+                        break name;
+                    }
+                }
+            }
+        };
+        processor.set_diagnostics(self.diagnostics.clone());
+        let worker = ProcessorWorker::with_diagnostics(processor, config, self.diagnostics.clone())
+            .with_levels(levels)
+            .with_name(name);
+        let worker = Arc::new(worker);
+        self.trackers.register(&worker.tracker);
+        self.workers.rcu(|workers| {
+            let mut workers = Vec::clone(workers);
+            workers.push(worker.clone());
+            workers
+        });
+        Ok(())
+    }
+
+    /// 移除指定类型的所有处理器，`name` 可以是完整名称（`"udp_processor"`）或简称（`"udp"`）
+    ///
+    /// 处理器先从广播集合中移除，之后的记录不再发送给它；随后发送刷新和停止命令，
+    /// 等待工作线程处理完已收到的记录并退出。
+    pub fn remove_processor(&self, name: &str) -> Result<(), RemoveError> {
+        let matches = |worker: &ProcessorWorker| matches_kind(worker.get_processor_type(), name);
+
+        let previous = self.workers.rcu(|workers| {
+            workers.iter().filter(|worker| !matches(worker)).cloned().collect::<Vec<_>>()
+        });
+        let removed: Vec<Arc<ProcessorWorker>> = previous.iter().filter(|worker| matches(worker)).cloned().collect();
+        drop(previous);
+        if removed.is_empty() {
+            return Err(RemoveError::NotFound(name.to_string()));
+        }
+        Self::retire(removed, name)
+    }
+
+    /// 用新的处理器替换 `name` 类型的处理器（如修改了 `log_dir` 的文件处理器），切换期间不丢失也不重复记录
+    ///
+    /// 先启动新处理器的工作线程并等待其就绪，再在广播集合中原位替换（沿用旧处理器的级别集合），
+    /// 等待仍在向旧处理器发送的广播完成后刷新并停止旧的工作线程：切换点之前广播的记录全部由旧处理器写入，
+    /// 之后的全部由新处理器写入。`name` 必须恰好匹配一个处理器；新的工作线程未在超时时间内就绪时不做替换。
+    /// 新旧处理器短暂并存，两个文件处理器不能使用同一个日志目录（见 `SharedDirPolicy`）。
+    pub fn replace_processor<P>(&self, name: &str, mut processor: P, config: BatchConfig) -> Result<(), RemoveError>
+    where
+        P: LogProcessor + Send + 'static,
+    {
+        let matches = |worker: &ProcessorWorker| matches_kind(worker.get_processor_type(), name);
+        let old = {
+            let workers = self.workers.load();
+            let mut found = workers.iter().filter(|worker| matches(worker));
+            match (found.next(), found.next()) {
+                (Some(old), None) => old.clone(),
+                (None, _) => return Err(RemoveError::NotFound(name.to_string())),
+                (Some(_), Some(_)) => return Err(RemoveError::Ambiguous(name.to_string())),
+            }
+        };
+
+        processor.set_diagnostics(self.diagnostics.clone());
+        let worker = ProcessorWorker::with_diagnostics(processor, config, self.diagnostics.clone())
+            .with_levels(old.levels)
+            .with_name(old.name.clone());
+        if !worker.wait_ready(REMOVE_TIMEOUT.as_millis() as u64) {
+            return Err(RemoveError::Timeout(name.to_string()));
+        }
+        let worker = Arc::new(worker);
+        self.trackers.register(&worker.tracker);
+        let previous = self.workers.rcu(|workers| {
+            workers.iter().map(|current| if Arc::ptr_eq(current, &old) { worker.clone() } else { current.clone() }).collect::<Vec<_>>()
+        });
+        if !previous.iter().any(|current| Arc::ptr_eq(current, &old)) {
+            // 等待期间旧处理器已被移除
+            return Err(RemoveError::NotFound(name.to_string()));
+        }
+
+        // 替换时会为仍持有旧广播集合的读取方补上引用计数，计数回到 1 后旧处理器不会再收到记录
+        wait_with_backoff(Instant::now() + REMOVE_TIMEOUT, || Arc::strong_count(&previous) == 1);
+        drop(previous);
+        Self::retire(vec![old], name)
+    }
+
+    /// 移除名为 `name` 的处理器（见 [`remove_processor`](Self::remove_processor)）
+    pub fn remove_named(&self, name: &str) -> Result<(), RemoveError> {
+        let previous = self.workers.rcu(|workers| {
+            workers.iter().filter(|worker| worker.name() != name).cloned().collect::<Vec<_>>()
+        });
+        let removed: Vec<Arc<ProcessorWorker>> = previous.iter().filter(|worker| worker.name() == name).cloned().collect();
+        drop(previous);
+        if removed.is_empty() {
+            return Err(RemoveError::NotFound(name.to_string()));
+        }
+        Self::retire(removed, name)
+    }
+
+    /// 名为 `name` 的处理器的工作线程
+    pub fn worker(&self, name: &str) -> Option<Arc<ProcessorWorker>> {
+        self.workers.load().iter().find(|worker| worker.name() == name).cloned()
+    }
+
+    /// 所有处理器的名称（按添加顺序）
+    pub fn processor_names(&self) -> Vec<String> {
+        self.workers.load().iter().map(|worker| worker.name().to_string()).collect()
+    }
+
+    /// 刷新并停止已从广播集合中移除的处理器，等待工作线程处理完已收到的记录并退出
+    fn retire(removed: Vec<Arc<ProcessorWorker>>, name: &str) -> Result<(), RemoveError> {
+        for worker in &removed {
+            let _ = worker.send_flush();
+            let _ = worker.send_shutdown();
+        }
+
+        let deadline = Instant::now() + REMOVE_TIMEOUT;
+        while !removed.iter().all(|worker| worker.is_finished()) {
+            if Instant::now() >= deadline {
+                // 不再等待：分离仍在运行的工作线程，避免释放时阻塞
+                for mut worker in removed {
+                    if let Some(worker) = Arc::get_mut(&mut worker) {
+                        worker.worker_thread.take();
+                    }
+                }
+                return Err(RemoveError::Timeout(name.to_string()));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        Ok(())
+    }
+
     /// 广播写入命令给所有处理器
+    ///
+    /// 超出内存预算而被丢弃的记录和关闭后被拒绝的记录不视为错误
     pub fn broadcast_write(&self, data: Vec<u8>) -> Result<(), String> {
-        for worker in &self.workers {
-            if let Err(e) = worker.send_write(data.clone()) {
+        self.write_to(data, None, false)
+    }
+
+    /// 广播级别为 `level` 的记录，只发送给接收该级别的处理器（见 [`ProcessorWorker::accepts_level`]）；
+    /// Error 级别的记录走强制写入路径
+    pub fn broadcast_record(&self, data: Vec<u8>, level: Level) -> Result<(), String> {
+        if level == Level::Error {
+            self.emergency_to(data, Some(level), true, false)
+        } else {
+            self.write_to(data, Some(level), false)
+        }
+    }
+
+    /// 广播受保护目标的记录（见 [`LoggerBuilder::with_protected_targets`](crate::LoggerBuilder::with_protected_targets)）：
+    /// 与 [`broadcast_record`](Self::broadcast_record) 相同，但不会因超出内存预算而被丢弃
+    pub fn broadcast_protected_record(&self, data: Vec<u8>, level: Level) -> Result<(), String> {
+        if level == Level::Error {
+            self.emergency_to(data, Some(level), true, true)
+        } else {
+            self.write_to(data, Some(level), true)
+        }
+    }
+
+    /// 写入命令发送给所有处理器，或只发送给接收 `level` 的处理器
+    fn write_to(&self, data: Vec<u8>, level: Option<Level>, protected: bool) -> Result<(), String> {
+        let Some(_open) = self.gate.enter() else {
+            self.reject_record(&data);
+            return Ok(());
+        };
+        let workers = self.workers.load();
+        let accepts = |worker: &ProcessorWorker| level.is_none_or(|level| worker.accepts_level(level));
+        let targets = workers.iter().filter(|worker| accepts(worker)).count();
+        let Some(_reservation) = self.admit(data.len() * targets, false, protected) else {
+            return Ok(());
+        };
+        let outgoing = self.outgoing(data);
+        for worker in workers.iter().filter(|worker| accepts(worker)) {
+            if let Err(e) = outgoing.send(worker, false) {
                 return Err(e);
             }
         }
@@ -502,18 +1769,115 @@ impl ProcessorManager {
     }
 
     /// 广播强制写入命令给所有处理器（忽略批量限制）
+    ///
+    /// 不接收强制写入的处理器改为收到普通写入命令
     pub fn broadcast_write_force(&self, data: Vec<u8>) -> Result<(), String> {
-        for worker in &self.workers {
-            if let Err(e) = worker.send_write_force(data.clone()) {
+        self.broadcast_emergency(data, true)
+    }
+
+    /// 广播紧急日志：接收强制写入的处理器收到强制写入命令，
+    /// 其余处理器仅在记录通过级别过滤 (`level_allowed`) 时收到普通写入命令
+    pub fn broadcast_emergency(&self, data: Vec<u8>, level_allowed: bool) -> Result<(), String> {
+        self.emergency_to(data, None, level_allowed, false)
+    }
+
+    /// 广播级别为 `level` 的紧急日志，不接收该级别的处理器不会收到（见 [`broadcast_emergency`](Self::broadcast_emergency)）
+    pub fn broadcast_emergency_record(&self, data: Vec<u8>, level: Level, level_allowed: bool) -> Result<(), String> {
+        self.emergency_to(data, Some(level), level_allowed, false)
+    }
+
+    fn emergency_to(&self, data: Vec<u8>, level: Option<Level>, level_allowed: bool, protected: bool) -> Result<(), String> {
+        let Some(_open) = self.gate.enter() else {
+            self.reject_record(&data);
+            return Ok(());
+        };
+        let workers = self.workers.load();
+        let accepts = |worker: &ProcessorWorker| level.is_none_or(|level| worker.accepts_level(level));
+        let targets = workers.iter().filter(|worker| accepts(worker) && (worker.accepts_emergency() || level_allowed)).count();
+        let Some(_reservation) = self.admit(data.len() * targets, true, protected) else {
+            return Ok(());
+        };
+        let outgoing = self.outgoing(data);
+        for worker in workers.iter().filter(|worker| accepts(worker)) {
+            let result = if worker.accepts_emergency() {
+                outgoing.send(worker, true)
+            } else if level_allowed {
+                outgoing.send(worker, false)
+            } else {
+                continue;
+            };
+            if let Err(e) = result {
                 return Err(e);
             }
         }
         Ok(())
     }
 
+    /// 发给各处理器的记录：设置了富化函数时所有处理器共享同一条待富化记录
+    fn outgoing(&self, data: Vec<u8>) -> Outgoing {
+        match &self.enricher {
+            Some(enricher) => Outgoing::Pending(Arc::new(PendingEnrichment::new(data, enricher.clone()))),
+            None => Outgoing::Encoded(data),
+        }
+    }
+
+    /// 内存预算判定，`forced` 的记录可以使用预留，`protected` 的记录总是放行；没有设置预算时总是放行
+    ///
+    /// 放行时返回的预留在发送完成后释放，丢弃时返回 None。
+    fn admit(&self, bytes: usize, forced: bool, protected: bool) -> Option<Reservation<'_>> {
+        match &self.memory {
+            Some(memory) if protected => Some(memory.admit_protected(bytes as u64)),
+            Some(memory) => memory.admit(bytes as u64, forced),
+            None => Some(Reservation::none()),
+        }
+    }
+
+    /// 关闭后被拒绝的记录：计入统计，启用时写入标准错误
+    fn reject_record(&self, data: &[u8]) {
+        self.reject(|| {
+            let (record, _) = crate::config::decode_record_versioned(data).ok()?;
+            Some(format!("{} {} - {}", record.metadata.level, record.metadata.target, record.args))
+        });
+    }
+
+    fn reject(&self, line: impl FnOnce() -> Option<String>) {
+        self.diagnostics.stats().add_shutdown_rejected();
+        if self.stderr_on_shutdown && let Some(line) = line() {
+            eprintln!("[rat_logger] 日志器已关闭: {}", line);
+        }
+    }
+
+    /// 发送原始数据给指定类型的处理器，`kind` 可以是完整名称或简称，没有匹配的处理器或已关闭时返回错误
+    pub fn broadcast_raw(&self, kind: &str, data: Vec<u8>) -> Result<(), String> {
+        let Some(_open) = self.gate.enter() else {
+            self.reject(|| Some(String::from_utf8_lossy(&data).into_owned()));
+            return Err(CLOSED.to_string());
+        };
+        self.broadcast_to(kind, |worker| worker.send_write_raw(data.clone()))
+    }
+
+    /// 对指定类型的每个处理器执行 `send`，没有匹配的处理器时返回错误
+    fn broadcast_to(&self, kind: &str, send: impl Fn(&ProcessorWorker) -> Result<(), String>) -> Result<(), String> {
+        let workers = self.workers.load();
+        let mut targets = workers.iter().filter(|worker| matches_kind(worker.get_processor_type(), kind)).peekable();
+        if targets.peek().is_none() {
+            return Err(format!("没有找到处理器: {}", kind));
+        }
+        targets.try_for_each(|worker| send(worker))
+    }
+
+    /// 把已编码的网络数据包交给所有 UDP 处理器，没有 UDP 处理器或已关闭时返回错误
+    pub fn forward_packet(&self, packet: Vec<u8>) -> Result<(), String> {
+        let Some(_open) = self.gate.enter() else {
+            self.reject(|| None);
+            return Err(CLOSED.to_string());
+        };
+        self.broadcast_to(crate::core::processor_types::UDP, |worker| worker.send_forward_packet(packet.clone()))
+    }
+
     /// 广播轮转命令给所有处理器
     pub fn broadcast_rotate(&self) -> Result<(), String> {
-        for worker in &self.workers {
+        for worker in self.workers.load().iter() {
             if let Err(e) = worker.send_rotate() {
                 return Err(e);
             }
@@ -523,7 +1887,7 @@ impl ProcessorManager {
 
     /// 广播压缩命令给所有处理器
     pub fn broadcast_compress(&self, path: std::path::PathBuf) -> Result<(), String> {
-        for worker in &self.workers {
+        for worker in self.workers.load().iter() {
             if let Err(e) = worker.send_compress(path.clone()) {
                 return Err(e);
             }
@@ -533,7 +1897,7 @@ impl ProcessorManager {
 
     /// 广播刷新命令给所有处理器
     pub fn broadcast_flush(&self) -> Result<(), String> {
-        for worker in &self.workers {
+        for worker in self.workers.load().iter() {
             if let Err(e) = worker.send_flush() {
                 return Err(e);
             }
@@ -541,9 +1905,94 @@ impl ProcessorManager {
         Ok(())
     }
 
+    /// 广播带确认的刷新命令，等待所有处理器确认刷新完成
+    pub fn broadcast_flush_ack(&self, timeout_ms: u64) -> Result<(), String> {
+        self.sync_point(Duration::from_millis(timeout_ms)).map_err(|e| e.to_string())
+    }
+
+    /// 同步点：等待此前提交给处理器的所有命令处理完毕，且每个处理器都完成了刷新
+    ///
+    /// 确认命令与记录经同一队列按顺序处理，收到所有处理器的确认时，确认命令之前的队列已经排空：
+    /// 文件处理器已同步到磁盘，终端已刷新，UDP 处理器已发送完待发记录（或达到重试上限）。
+    pub fn sync_point(&self, timeout: Duration) -> Result<(), FlushError> {
+        self.collect_acks(timeout, ProcessorWorker::send_flush_ack)
+    }
+
+    /// 立即刷新：每个处理器最多再处理一个批次（至少 1024 条命令）的积压后刷新并确认，不等待队列中其余的记录
+    ///
+    /// 返回时已处理的记录已经刷新（文件已同步到磁盘），之后的积压仍按顺序写入。
+    pub fn flush_now(&self, timeout: Duration) -> Result<(), FlushError> {
+        self.collect_acks(timeout, ProcessorWorker::send_priority_flush_ack)
+    }
+
+    /// 向每个工作线程发送确认刷新命令并等待所有确认
+    fn collect_acks(
+        &self,
+        timeout: Duration,
+        send: impl Fn(&ProcessorWorker, Sender<bool>) -> Result<(), String>,
+    ) -> Result<(), FlushError> {
+        Self::collect_acks_from(&self.workers.load_full(), timeout, send)
+    }
+
+    /// 向 `workers` 中的每个工作线程发送确认刷新命令并等待所有确认
+    fn collect_acks_from(
+        workers: &[Arc<ProcessorWorker>],
+        timeout: Duration,
+        send: impl Fn(&ProcessorWorker, Sender<bool>) -> Result<(), String>,
+    ) -> Result<(), FlushError> {
+        let (ack_sender, ack_receiver) = unbounded();
+        for worker in workers.iter() {
+            send(worker, ack_sender.clone()).map_err(|_| FlushError::Disconnected)?;
+        }
+        drop(ack_sender);
+
+        let deadline = Instant::now() + timeout;
+        for _ in 0..workers.len() {
+            match ack_receiver.recv_deadline(deadline) {
+                Ok(true) => {}
+                Ok(false) => return Err(FlushError::Failed),
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => return Err(FlushError::Timeout(timeout)),
+                Err(crossbeam_channel::RecvTimeoutError::Disconnected) => return Err(FlushError::Disconnected),
+            }
+        }
+        Ok(())
+    }
+
+    /// 等待所有工作线程退出（需先广播停止命令）
+    pub fn wait_for_shutdown(&self, timeout_ms: u64) -> Result<(), String> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        while !self.workers.load().iter().all(|w| w.is_finished()) {
+            if Instant::now() >= deadline {
+                return Err(format!("等待工作线程退出超时（{}ms）", timeout_ms));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        Ok(())
+    }
+
+    /// 两阶段关闭：先关闭闸门并等待正在发送的广播完成，之后的记录被拒绝并计入统计；
+    /// 再广播刷新和停止命令，等待所有工作线程退出
+    ///
+    /// 停止命令之后不会再有记录进入队列，关闭前提交的记录全部被处理。第一阶段最多等待 `timeout`，
+    /// 超时后仍进入第二阶段，此时还在发送的记录可能被丢弃。重复调用只等待工作线程退出。
+    pub fn shutdown(&self, timeout: Duration) -> Result<(), String> {
+        if self.gate.close(timeout) {
+            let _ = self.broadcast_flush();
+            let _ = self.broadcast_shutdown("ProcessorManager::shutdown");
+        }
+        self.wait_for_shutdown(timeout.as_millis() as u64)
+    }
+
+    /// 是否已经开始关闭（见 [`shutdown`](Self::shutdown)）
+    pub fn is_closed(&self) -> bool {
+        self.gate.is_closed()
+    }
+
     /// 广播停止命令给所有处理器
+    ///
+    /// 不拒绝之后的记录，停止命令之后到达的记录会被丢弃；需要完整关闭时使用 [`shutdown`](Self::shutdown)。
     pub fn broadcast_shutdown(&self, source: &'static str) -> Result<(), String> {
-        for worker in &self.workers {
+        for worker in self.workers.load().iter() {
             if let Err(e) = worker.send_shutdown() {
                 return Err(e);
             }
@@ -553,7 +2002,19 @@ impl ProcessorManager {
 
     /// 获取处理器数量
     pub fn len(&self) -> usize {
-        self.workers.len()
+        self.workers.load().len()
+    }
+
+    /// 按添加顺序列出已注册处理器的类型名称（同类型处理器只出现一次）
+    pub fn processor_types(&self) -> Vec<&'static str> {
+        let mut types = Vec::with_capacity(self.len());
+        for worker in self.workers.load().iter() {
+            let processor_type = worker.get_processor_type();
+            if !types.contains(&processor_type) {
+                types.push(processor_type);
+            }
+        }
+        types
     }
 
     /// 智能健康检查：被动等待工作线程就绪通知
@@ -562,7 +2023,7 @@ impl ProcessorManager {
         let mut unverified_count = 0;
         let mut newly_verified = Vec::new();
 
-        for worker in &self.workers {
+        for worker in self.workers.load().iter() {
             let worker_type = worker.get_processor_type();
             if !self.verified_types.contains(worker_type) {
                 unverified_count += 1;
@@ -598,7 +2059,7 @@ impl ProcessorManager {
         let expected_set: std::collections::HashSet<&str> = expected_types.iter().map(|s| s.as_str()).collect();
         let mut expected_workers = Vec::new();
 
-        for worker in &self.workers {
+        for worker in self.workers.load().iter() {
             let worker_type = worker.get_processor_type();
             let is_expected = expected_set.contains(worker_type);
             let is_verified = self.verified_types.contains(worker_type);
@@ -630,9 +2091,36 @@ impl ProcessorManager {
         Ok(())
     }
 
+    /// 等待当前所有工作线程进入处理循环
+    ///
+    /// 只检查本管理器的工作线程，不使用全局就绪计数器，多个日志器同时创建时互不影响。
+    pub fn wait_ready(&self, timeout_ms: u64) -> Result<(), WorkerStartupTimeout> {
+        let started = Instant::now();
+        let deadline = started + Duration::from_millis(timeout_ms);
+        let workers = self.workers.load();
+        let pending: Vec<&Arc<ProcessorWorker>> = workers.iter().filter(|worker| !worker.ready.wait_until(deadline)).collect();
+        match pending.iter().min_by_key(|worker| worker.ready.spawned) {
+            None => Ok(()),
+            Some(slowest) => Err(WorkerStartupTimeout {
+                slowest: slowest.processor_type,
+                waited_ms: started.elapsed().as_millis() as u64,
+                ready: workers.len() - pending.len(),
+                total: workers.len(),
+            }),
+        }
+    }
+
+    /// 各工作线程从创建到就绪的耗时（按添加顺序）
+    pub fn readiness_report(&self) -> Vec<WorkerReadiness> {
+        self.workers.load()
+            .iter()
+            .map(|worker| WorkerReadiness { processor: worker.processor_type, startup: worker.ready.startup() })
+            .collect()
+    }
+
     /// 检查是否为空
     pub fn is_empty(&self) -> bool {
-        self.workers.is_empty()
+        self.workers.load().is_empty()
     }
 }
 
@@ -644,14 +2132,16 @@ impl Default for ProcessorManager {
 
 impl Drop for ProcessorManager {
     fn drop(&mut self) {
-        // 优雅地关闭所有工作线程
-        let _ = self.broadcast_shutdown("ProcessorManager::drop");
-
-        // 给每个工作线程一些时间来清理资源
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        // 先停止探测线程，之后只有这里持有工作线程
+        self.pipeline_probe = None;
+        // 两阶段关闭：此时已没有其他引用，关闭闸门后刷新并停止所有工作线程
+        if self.gate.close(GATE_CLOSE_TIMEOUT) {
+            let _ = self.broadcast_flush();
+            let _ = self.broadcast_shutdown("ProcessorManager::drop");
+        }
 
-        // 清理工作线程
-        self.workers.clear();
+        // 释放工作线程（等待每个线程处理完队列并退出）
+        self.workers.store(Arc::new(Vec::new()));
     }
 }
 
@@ -685,32 +2175,32 @@ mod tests {
             self.name
         }
 
-        fn process(&mut self, data: &[u8]) -> Result<(), String> {
+        fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
             self.processed_data.push(data.to_vec());
             Ok(())
         }
 
-        fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), String> {
+        fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
             self.processed_data.extend(batch.iter().cloned());
             Ok(())
         }
 
-        fn handle_rotate(&mut self) -> Result<(), String> {
+        fn handle_rotate(&mut self) -> Result<(), ProcessorError> {
             self.rotate_count += 1;
             Ok(())
         }
 
-        fn handle_compress(&mut self, _path: &std::path::Path) -> Result<(), String> {
+        fn handle_compress(&mut self, _path: &std::path::Path) -> Result<(), ProcessorError> {
             self.compress_count += 1;
             Ok(())
         }
 
-        fn flush(&mut self) -> Result<(), String> {
+        fn flush(&mut self) -> Result<(), ProcessorError> {
             self.flush_count += 1;
             Ok(())
         }
 
-        fn cleanup(&mut self) -> Result<(), String> {
+        fn cleanup(&mut self) -> Result<(), ProcessorError> {
             self.processed_data.clear();
             Ok(())
         }
@@ -723,6 +2213,7 @@ mod tests {
             batch_size: 2,
             batch_interval_ms: 10,
             buffer_size: 10,
+            ..Default::default()
         };
 
         let worker = ProcessorWorker::new(processor, config);
@@ -767,4 +2258,65 @@ mod tests {
 
         assert_eq!(manager.len(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_batch_config_presets_and_limits() {
+        for preset in [BatchConfig::low_latency(), BatchConfig::balanced(), BatchConfig::throughput()] {
+            assert!(preset.validate().is_ok(), "{:?}", preset);
+            assert!(preset.warnings().is_empty(), "{:?}", preset);
+        }
+
+        // 超过建议上限但未超过硬上限：有效，但给出警告
+        let large = BatchConfig {
+            batch_size: 4 * 1024 * 1024,
+            batch_interval_ms: 100,
+            buffer_size: 16 * 1024 * 1024,
+            ..Default::default()
+        };
+        assert!(large.validate().is_ok());
+        assert_eq!(large.warnings().len(), 2);
+
+        let too_large = BatchConfig {
+            batch_size: BatchConfig::MAX_BATCH_SIZE + 1,
+            buffer_size: BatchConfig::MAX_BUFFER_SIZE,
+            ..large.clone()
+        };
+        assert!(too_large.validate().is_err());
+
+        let small_buffer = BatchConfig { buffer_size: 1024, ..large };
+        assert!(small_buffer.validate().unwrap_err().contains("缓冲区容量"));
+    }
+
+    #[test]
+    fn test_shutdown_gate_close_waits_with_deadline() {
+        let gate = Arc::new(ShutdownGate::default());
+
+        // 正在发送的广播在关闭期间完成：关闭被唤醒后返回
+        let sender = {
+            let gate = gate.clone();
+            let (entered_tx, entered_rx) = bounded(0);
+            let handle = thread::spawn(move || {
+                let _open = gate.enter().unwrap();
+                entered_tx.send(()).unwrap();
+                thread::sleep(Duration::from_millis(50));
+            });
+            entered_rx.recv().unwrap();
+            handle
+        };
+        let start = Instant::now();
+        assert!(gate.close(Duration::from_secs(10)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(gate.active.load(Ordering::SeqCst), 0);
+        sender.join().unwrap();
+
+        assert!(gate.enter().is_none());
+        assert!(!gate.close(Duration::from_secs(10)));
+
+        // 广播一直不完成：到期后放弃等待
+        let stuck = ShutdownGate::default();
+        let _open = stuck.enter().unwrap();
+        let start = Instant::now();
+        assert!(stuck.close(Duration::from_millis(50)));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,161 @@
+//! 配置报告 - 日志器实际生效的配置快照，可序列化为 JSON 等格式供诊断接口输出
+//!
+//! ```rust,no_run
+//! use rat_logger::LoggerBuilder;
+//! use rat_logger::handler::term::TermConfig;
+//!
+//! let logger = LoggerBuilder::new().add_terminal_with_config(TermConfig::default()).build();
+//! let report = logger.config_report();
+//! println!("{}", serde_json::to_string_pretty(&report).unwrap());
+//! ```
+//!
+//! 报告由构建时保存的配置组装，级别为调用时的当前级别。UDP 认证令牌只报告是否设置，不会出现在报告中。
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::config::{BreakerConfig, ByteSize, LevelFilter, MemoryBudget, SamplingConfig};
+use crate::core::ProcessorConfig;
+use crate::producer_consumer::BatchConfig;
+
+/// 日志器实际生效的配置（见 [`LoggerCore::config_report`](crate::core::LoggerCore::config_report)）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ConfigReport {
+    /// rat_logger 版本
+    pub version: &'static str,
+    /// 当前日志级别
+    pub level: LevelFilter,
+    /// 处理器使用的批量配置
+    pub batch: BatchConfig,
+    /// 采样配置（未配置时为 None）
+    pub sampling: Option<SamplingConfig>,
+    /// 全局熔断配置（未配置时为 None）
+    pub breaker: Option<BreakerConfig>,
+    /// 内存预算（未配置时为 None）
+    pub memory_budget: Option<MemoryBudget>,
+    /// 构建时添加的处理器（按添加顺序）
+    pub handlers: Vec<HandlerReport>,
+    /// 编译时启用的特性
+    pub features: Vec<&'static str>,
+}
+
+/// 单个处理器的关键配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum HandlerReport {
+    /// 终端处理器
+    Terminal {
+        /// 是否启用颜色输出
+        color: bool,
+        /// 输出模板（未指定格式时为 None）
+        format: Option<String>,
+    },
+    /// 文件处理器
+    File {
+        /// 日志目录
+        log_dir: PathBuf,
+        /// 单个日志文件的大小上限（字节）
+        max_file_size: u64,
+        /// 保留的压缩文件数量上限（0 表示禁用压缩）
+        max_compressed_files: usize,
+        /// 日志目录的总大小上限（字节，0 表示不限制）
+        max_total_size: u64,
+        /// 压缩归档的保留期限（秒）
+        max_archive_age_secs: Option<u64>,
+        /// 日志文件名模板
+        file_name_template: Option<String>,
+        /// 是否为原始数据模式
+        raw: bool,
+        /// 输出模板（未指定格式时为 None）
+        format: Option<String>,
+    },
+    /// UDP处理器
+    Udp {
+        /// 服务器地址和端口
+        target: String,
+        /// 是否设置了认证令牌（令牌本身不报告）
+        auth_token_set: bool,
+        /// 每个数据报最多合并的记录数
+        batch_size: usize,
+        /// 单个批量数据报的字节上限
+        max_batch_bytes: usize,
+        /// 记录在发送端最长等待时间（毫秒）
+        max_wait_time_ms: u64,
+    },
+}
+
+impl From<&ProcessorConfig> for HandlerReport {
+    fn from(config: &ProcessorConfig) -> Self {
+        match config {
+            ProcessorConfig::Terminal(config) => HandlerReport::Terminal {
+                color: config.enable_color,
+                format: config.format.as_ref().map(|format| format.format_template.clone()),
+            },
+            ProcessorConfig::File(config) => HandlerReport::File {
+                log_dir: config.log_dir.clone(),
+                max_file_size: config.max_file_size,
+                max_compressed_files: config.max_compressed_files,
+                max_total_size: config.max_total_size,
+                max_archive_age_secs: config.max_archive_age.map(|age| age.as_secs()),
+                file_name_template: config.file_name_template.clone(),
+                raw: config.is_raw,
+                format: config.format.as_ref().map(|format| format.format_template.clone()),
+            },
+            ProcessorConfig::Udp(config) => {
+                let network = &config.network_config;
+                HandlerReport::Udp {
+                    target: network.endpoint(),
+                    auth_token_set: !network.auth_token.is_empty(),
+                    batch_size: config.batch_size,
+                    max_batch_bytes: config.max_batch_bytes,
+                    max_wait_time_ms: config.max_wait_time_ms,
+                }
+            }
+        }
+    }
+}
+
+impl HandlerReport {
+    /// 启动横幅中该处理器的一行（见 [`banner_lines`](crate::startup::banner_lines)）
+    pub fn summary(&self) -> String {
+        match self {
+            HandlerReport::Terminal { color, .. } => {
+                format!("handler terminal: color={}", if *color { "on" } else { "off" })
+            }
+            HandlerReport::File { log_dir, max_file_size, max_compressed_files, .. } => {
+                let compression = match max_compressed_files {
+                    0 => "off".to_string(),
+                    max => format!("keep {}", max),
+                };
+                format!(
+                    "handler file: log_dir={}, max_file_size={}, compression={}",
+                    log_dir.display(),
+                    ByteSize(*max_file_size),
+                    compression,
+                )
+            }
+            HandlerReport::Udp { target, auth_token_set, .. } => {
+                format!("handler udp: target={}, token={}", target, if *auth_token_set { "***" } else { "<none>" })
+            }
+        }
+    }
+}
+
+/// 编译时启用的特性
+pub(crate) fn enabled_features() -> Vec<&'static str> {
+    [
+        ("tokio-runtime", cfg!(feature = "tokio-runtime")),
+        ("smol-runtime", cfg!(feature = "smol-runtime")),
+        ("signal-flush", cfg!(feature = "signal-flush")),
+        ("bench-utils", cfg!(feature = "bench-utils")),
+        ("test-util", cfg!(feature = "test-util")),
+        ("ffi", cfg!(feature = "ffi")),
+        ("tools", cfg!(feature = "tools")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
@@ -0,0 +1,188 @@
+//! 采样模块 - 对 Debug/Trace 等高频级别做统计采样
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::config::{Level, SamplingConfig};
+
+/// 每线程保留随机状态的采样器数上限，超出时淘汰最早的状态（该采样器再次使用时重新播种）
+const MAX_THREAD_STATES: usize = 8;
+
+/// 下一个采样器标识
+static NEXT_SAMPLER_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// 每线程、每个采样器的 xorshift 状态：`(采样器标识, 状态)`
+    static RNG_STATES: RefCell<Vec<(u64, u64)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 采样器 - 无锁，每线程独立随机数
+///
+/// 随机状态按采样器分开保存，同一线程上的多个采样器（如重新构建的日志器）各自从自己的种子开始。
+#[derive(Debug)]
+pub struct Sampler {
+    config: SamplingConfig,
+    /// 线程本地随机状态的键
+    id: u64,
+}
+
+/// 采样结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleDecision {
+    /// 不在采样范围内，原样通过
+    Exempt,
+    /// 通过采样，附带生效的采样比例
+    Keep(f32),
+    /// 被采样丢弃
+    Drop,
+}
+
+impl Sampler {
+    /// 创建采样器
+    pub fn new(config: SamplingConfig) -> Self {
+        Self { config, id: NEXT_SAMPLER_ID.fetch_add(1, Ordering::Relaxed) }
+    }
+
+    /// 获取采样配置
+    pub fn config(&self) -> &SamplingConfig {
+        &self.config
+    }
+
+    /// 对一条记录做采样决策
+    pub fn decide(&self, level: Level, target: &str) -> SampleDecision {
+        // 只对阈值及更详细的级别采样
+        if (level.to_level_filter() as u8) < (self.config.levels as u8) {
+            return SampleDecision::Exempt;
+        }
+
+        let ratio = self.ratio_for(target);
+        if ratio >= 1.0 {
+            return SampleDecision::Keep(1.0);
+        }
+        if ratio <= 0.0 {
+            return SampleDecision::Drop;
+        }
+
+        // 取高 24 位映射到 [0, 1)
+        let roll = (self.next_random() >> 40) as f32 / (1u64 << 24) as f32;
+        if roll < ratio {
+            SampleDecision::Keep(ratio)
+        } else {
+            SampleDecision::Drop
+        }
+    }
+
+    /// 目标匹配的采样规则（最长前缀），没有匹配时为 `None`
+    pub fn matching_rule(&self, target: &str) -> Option<&(String, f32)> {
+        crate::config::longest_prefix_match(&self.config.per_target, target)
+    }
+
+    /// 按最长目标前缀查找采样比例
+    fn ratio_for(&self, target: &str) -> f32 {
+        self.matching_rule(target)
+            .map(|(_, ratio)| *ratio)
+            .unwrap_or(self.config.default_ratio)
+    }
+
+    fn next_random(&self) -> u64 {
+        RNG_STATES.with(|states| {
+            let mut states = states.borrow_mut();
+            let index = match states.iter().rposition(|(id, _)| *id == self.id) {
+                Some(index) => index,
+                None => {
+                    if states.len() >= MAX_THREAD_STATES {
+                        states.remove(0);
+                    }
+                    states.push((self.id, self.config.seed.unwrap_or_else(random_seed) | 1));
+                    states.len() - 1
+                }
+            };
+            // xorshift64
+            let mut x = states[index].1;
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            states[index].1 = x;
+            x
+        })
+    }
+}
+
+pub(crate) fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default());
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LevelFilter;
+
+    fn sampler() -> Sampler {
+        Sampler::new(SamplingConfig {
+            default_ratio: 0.01,
+            per_target: vec![("hot::path".to_string(), 1.0)],
+            levels: LevelFilter::Debug,
+            annotate: true,
+            seed: Some(42),
+        })
+    }
+
+    #[test]
+    fn test_default_ratio_is_approximate() {
+        // 新线程保证线程本地随机状态从种子开始
+        let kept = std::thread::spawn(|| {
+            let sampler = sampler();
+            (0..100_000)
+                .filter(|_| matches!(sampler.decide(Level::Debug, "app::db"), SampleDecision::Keep(_)))
+                .count()
+        }).join().unwrap();
+
+        assert!((700..=1300).contains(&kept), "1% 采样通过了 {} 条", kept);
+    }
+
+    #[test]
+    fn test_overridden_target_passes_everything() {
+        let sampler = sampler();
+        for _ in 0..100_000 {
+            assert_eq!(sampler.decide(Level::Trace, "hot::path::inner"), SampleDecision::Keep(1.0));
+        }
+    }
+
+    #[test]
+    fn test_levels_above_threshold_are_exempt() {
+        let sampler = sampler();
+        assert_eq!(sampler.decide(Level::Info, "app::db"), SampleDecision::Exempt);
+        assert_eq!(sampler.decide(Level::Error, "app::db"), SampleDecision::Exempt);
+    }
+
+    #[test]
+    fn test_each_sampler_starts_from_its_seed() {
+        let decisions = |sampler: &Sampler| -> Vec<SampleDecision> {
+            (0..1000).map(|_| sampler.decide(Level::Debug, "app::db")).collect()
+        };
+
+        // 同一线程上先用过的采样器不影响之后创建的采样器
+        let first = sampler();
+        let expected = decisions(&first);
+        let second = sampler();
+        assert_eq!(decisions(&second), expected);
+
+        // 交替使用时各自的序列不受干扰
+        let (a, b) = (sampler(), sampler());
+        let interleaved: Vec<_> = (0..1000)
+            .map(|_| (a.decide(Level::Debug, "app::db"), b.decide(Level::Debug, "app::db")))
+            .collect();
+        assert!(interleaved.iter().map(|(a, _)| *a).eq(expected.iter().copied()));
+        assert!(interleaved.iter().map(|(_, b)| *b).eq(expected.iter().copied()));
+
+        // 不同的种子得到不同的序列
+        let other = Sampler::new(SamplingConfig { seed: Some(7), ..first.config().clone() });
+        assert_ne!(decisions(&other), expected);
+    }
+}
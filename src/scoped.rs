@@ -0,0 +1,77 @@
+//! 作用域日志器 - 在一段代码的动态范围内让日志宏使用指定的日志器
+//!
+//! 库可以在内部使用 `info!` 等日志宏，同时不触碰宿主应用的全局日志器：
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use rat_logger::{info, LoggerBuilder};
+//! use rat_logger::handler::term::TermConfig;
+//!
+//! let logger = Arc::new(LoggerBuilder::new().add_terminal_with_config(TermConfig::default()).build());
+//! rat_logger::scoped::with_logger(logger, || {
+//!     info!("只写入作用域日志器");
+//! });
+//! ```
+//!
+//! 作用域内的日志宏按作用域日志器自己的级别过滤，不受全局级别（`max_level`）影响；
+//! 作用域外没有额外开销（只读取一次线程局部变量）。`flush_logs!` 始终作用于全局日志器。
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::thread::LocalKey;
+
+use crate::core::Logger;
+
+type Slot = RefCell<Option<Arc<dyn Logger>>>;
+
+thread_local! {
+    static SCOPED_LOGGER: Slot = const { RefCell::new(None) };
+    /// 正在轮询的 [`with_logger_async`] future 的日志器（只在轮询期间设置，与运行时无关）
+    static TASK_LOGGER: Slot = const { RefCell::new(None) };
+}
+
+/// 在 `slot` 中设置 `logger`，离开作用域（包括 panic 展开）时恢复之前的日志器
+struct Restore(&'static LocalKey<Slot>, Option<Arc<dyn Logger>>);
+
+impl Restore {
+    fn set(slot: &'static LocalKey<Slot>, logger: Arc<dyn Logger>) -> Self {
+        Self(slot, slot.with(|current| current.replace(Some(logger))))
+    }
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.1.take();
+        self.0.with(|slot| *slot.borrow_mut() = previous);
+    }
+}
+
+/// 在 `f` 执行期间让当前线程上的日志宏使用 `logger`
+///
+/// 可以嵌套，返回（包括 panic 展开）时恢复之前的作用域日志器。闭包中启动的其他线程不继承该日志器。
+pub fn with_logger<R>(logger: Arc<dyn Logger>, f: impl FnOnce() -> R) -> R {
+    let _restore = Restore::set(&SCOPED_LOGGER, logger);
+    f()
+}
+
+/// 在 `future` 执行期间让其中的日志宏使用 `logger`（跟随任务，在 `.await` 前后切换线程时仍然有效）
+///
+/// 同一线程上通过 [`with_logger`] 设置的日志器优先。不依赖特定的异步运行时；
+/// `future` 中新启动的任务（如 `tokio::spawn`、`smol::spawn`）不继承该日志器。
+pub async fn with_logger_async<F: Future>(logger: Arc<dyn Logger>, future: F) -> F::Output {
+    let mut future = pin!(future);
+    std::future::poll_fn(|cx| {
+        let _restore = Restore::set(&TASK_LOGGER, logger.clone());
+        future.as_mut().poll(cx)
+    })
+    .await
+}
+
+/// 当前生效的作用域日志器（没有时日志宏使用全局日志器）
+pub fn current() -> Option<Arc<dyn Logger>> {
+    SCOPED_LOGGER
+        .with(|slot| slot.borrow().clone())
+        .or_else(|| TASK_LOGGER.with(|slot| slot.borrow().clone()))
+}
@@ -0,0 +1,139 @@
+//! 终止信号刷新模块 - 进程收到终止信号时先刷新日志再按原语义退出
+//!
+//! Unix 上通过 signal-hook 在专用线程中处理信号（信号处理函数本身只负责唤醒，保证异步信号安全），
+//! Windows 上通过 `SetConsoleCtrlHandler` 注册控制台事件处理器。
+
+use std::sync::Weak;
+
+use crate::producer_consumer::ProcessorManager;
+
+/// 刷新与关闭的等待超时（毫秒）
+const SIGNAL_FLUSH_TIMEOUT_MS: u64 = 3000;
+
+/// 需要拦截的终止信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// SIGTERM（Windows: 关闭控制台 / 系统关机）
+    Term,
+    /// SIGINT（Windows: Ctrl+C）
+    Int,
+    /// SIGHUP（Windows: 用户注销）
+    Hup,
+    /// SIGQUIT（Windows: Ctrl+Break）
+    Quit,
+}
+
+impl Signal {
+    #[cfg(unix)]
+    fn as_raw(self) -> i32 {
+        match self {
+            Signal::Term => libc::SIGTERM,
+            Signal::Int => libc::SIGINT,
+            Signal::Hup => libc::SIGHUP,
+            Signal::Quit => libc::SIGQUIT,
+        }
+    }
+}
+
+/// 收到信号后的刷新动作：确认刷新，按需完整关闭
+fn flush_for_signal(manager: &ProcessorManager, shutdown: bool) {
+    if let Err(e) = manager.broadcast_flush_ack(SIGNAL_FLUSH_TIMEOUT_MS) {
+        eprintln!("[signal] 信号刷新失败: {}", e);
+    }
+    if shutdown && let Err(e) = manager.shutdown(std::time::Duration::from_millis(SIGNAL_FLUSH_TIMEOUT_MS)) {
+        eprintln!("[signal] 信号关闭失败: {}", e);
+    }
+}
+
+/// 安装信号处理器
+#[cfg(unix)]
+pub(crate) fn install(manager: Weak<ProcessorManager>, signals: &[Signal], shutdown: bool) -> Result<(), String> {
+    use signal_hook::iterator::Signals;
+
+    // 记录注册前是否为默认处理方式：默认则刷新后重新触发信号，否则交给之前的处理器（由 signal-hook 链式调用）
+    let raw: Vec<(i32, bool)> = signals.iter().map(|s| {
+        let raw = s.as_raw();
+        (raw, is_default_disposition(raw))
+    }).collect();
+
+    let mut handle = Signals::new(raw.iter().map(|(sig, _)| *sig))
+        .map_err(|e| format!("注册信号处理器失败: {}", e))?;
+
+    std::thread::Builder::new()
+        .name("rat_logger_signal".to_string())
+        .spawn(move || {
+            for sig in handle.forever() {
+                if let Some(manager) = manager.upgrade() {
+                    flush_for_signal(&manager, shutdown);
+                }
+
+                let was_default = raw.iter().any(|(s, default)| *s == sig && *default);
+                if was_default {
+                    // 恢复默认语义：以原信号结束进程
+                    let _ = signal_hook::low_level::emulate_default_handler(sig);
+                }
+            }
+        })
+        .map_err(|e| format!("创建信号线程失败: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_default_disposition(sig: i32) -> bool {
+    // SAFETY: 仅查询当前的处理方式，不修改
+    unsafe {
+        let mut old: libc::sigaction = std::mem::zeroed();
+        if libc::sigaction(sig, std::ptr::null(), &mut old) != 0 {
+            return true;
+        }
+        old.sa_sigaction == libc::SIG_DFL
+    }
+}
+
+#[cfg(windows)]
+static WINDOWS_TARGET: once_cell::sync::OnceCell<(Weak<ProcessorManager>, Vec<Signal>, bool)> =
+    once_cell::sync::OnceCell::new();
+
+/// 安装控制台事件处理器
+#[cfg(windows)]
+pub(crate) fn install(manager: Weak<ProcessorManager>, signals: &[Signal], shutdown: bool) -> Result<(), String> {
+    use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+
+    WINDOWS_TARGET
+        .set((manager, signals.to_vec(), shutdown))
+        .map_err(|_| "控制台事件处理器已经安装".to_string())?;
+
+    // SAFETY: 回调为 'static 的 extern "system" 函数
+    if unsafe { SetConsoleCtrlHandler(Some(console_handler), 1) } == 0 {
+        return Err(format!("注册控制台事件处理器失败: {}", std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// 控制台事件回调（系统在独立线程中调用，可以直接执行刷新）
+#[cfg(windows)]
+unsafe extern "system" fn console_handler(event: u32) -> windows_sys::Win32::Foundation::BOOL {
+    use windows_sys::Win32::System::Console::{
+        CTRL_BREAK_EVENT, CTRL_C_EVENT, CTRL_CLOSE_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT,
+    };
+
+    let signal = match event {
+        CTRL_C_EVENT => Signal::Int,
+        CTRL_BREAK_EVENT => Signal::Quit,
+        CTRL_CLOSE_EVENT | CTRL_SHUTDOWN_EVENT => Signal::Term,
+        CTRL_LOGOFF_EVENT => Signal::Hup,
+        _ => return 0,
+    };
+
+    if let Some((manager, signals, shutdown)) = WINDOWS_TARGET.get() {
+        if signals.contains(&signal) {
+            if let Some(manager) = manager.upgrade() {
+                flush_for_signal(&manager, *shutdown);
+            }
+        }
+    }
+
+    // 返回 FALSE，交给之前注册的处理器（默认行为为结束进程）
+    0
+}
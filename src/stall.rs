@@ -0,0 +1,130 @@
+//! 卡住检测 - 发现长时间没有完成的处理器操作
+//!
+//! 每个工作线程持有一个 `OpTracker`，执行处理器操作（批量处理、刷新等）前后记录开始和结束。
+//! 设置了卡住阈值时，`StallSupervisor` 线程在最早的操作到达阈值时检查所有工作线程，当前操作超过阈值时上报
+//! `ProcessorStalled`，操作完成后由工作线程上报 `ProcessorRecovered`。检测只上报，不会中断工作线程。
+
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use crossbeam_channel::{bounded, Sender, RecvTimeoutError};
+use parking_lot::Mutex;
+
+use crate::diagnostics::{DiagnosticEvent, Diagnostics};
+
+/// 正在执行的操作
+struct CurrentOp {
+    started: Instant,
+    /// 是否已上报卡住
+    stalled: bool,
+}
+
+/// 单个工作线程的操作计时
+pub(crate) struct OpTracker {
+    processor: &'static str,
+    current: Mutex<Option<CurrentOp>>,
+}
+
+impl OpTracker {
+    pub(crate) fn new(processor: &'static str) -> Self {
+        Self {
+            processor,
+            current: Mutex::new(None),
+        }
+    }
+
+    /// 操作开始
+    pub(crate) fn begin(&self) {
+        *self.current.lock() = Some(CurrentOp { started: Instant::now(), stalled: false });
+    }
+
+    /// 操作结束：曾被上报卡住时上报恢复
+    pub(crate) fn end(&self, diagnostics: &Diagnostics) {
+        let finished = self.current.lock().take();
+        if let Some(op) = finished.filter(|op| op.stalled) {
+            diagnostics.emit(DiagnosticEvent::ProcessorRecovered {
+                processor: self.processor,
+                duration: op.started.elapsed(),
+            });
+        }
+    }
+
+    /// 当前操作超过阈值且尚未上报时标记为卡住，返回 `Ok(已执行的时间)`；
+    /// 尚未超过阈值时返回 `Err(到达阈值的时间)`，没有需要检查的操作时返回 `Err(None)`
+    fn check(&self, threshold: Duration) -> Result<Duration, Option<Instant>> {
+        let mut current = self.current.lock();
+        let op = current.as_mut().filter(|op| !op.stalled).ok_or(None)?;
+        let elapsed = op.started.elapsed();
+        if elapsed < threshold {
+            return Err(Some(op.started + threshold));
+        }
+        op.stalled = true;
+        Ok(elapsed)
+    }
+}
+
+/// 所有工作线程的操作计时（工作线程退出后自动移除）
+#[derive(Clone, Default)]
+pub(crate) struct TrackerRegistry(Arc<Mutex<Vec<Weak<OpTracker>>>>);
+
+impl TrackerRegistry {
+    pub(crate) fn register(&self, tracker: &Arc<OpTracker>) {
+        self.0.lock().push(Arc::downgrade(tracker));
+    }
+
+    fn live(&self) -> Vec<Arc<OpTracker>> {
+        let mut trackers = self.0.lock();
+        trackers.retain(|tracker| tracker.strong_count() > 0);
+        trackers.iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+/// 卡住检测线程，丢弃时停止
+pub(crate) struct StallSupervisor {
+    stop: Option<Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl StallSupervisor {
+    /// 启动检测线程：有正在执行的操作时在最早的操作到达阈值时检查，否则每隔一个阈值（至少 10 毫秒）检查一次
+    ///
+    /// 两次检查之间开始的操作最早在下一次检查时到达阈值，因此空闲时不需要更频繁地唤醒。
+    pub(crate) fn spawn(registry: TrackerRegistry, threshold: Duration, diagnostics: Diagnostics) -> Self {
+        let (stop, stopped) = bounded::<()>(0);
+        let idle_interval = threshold.max(Duration::from_millis(10));
+        let thread = thread::Builder::new()
+            .name("rat-logger-stall".to_string())
+            .spawn(move || {
+                let mut next_check = Instant::now() + idle_interval;
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_deadline(next_check) {
+                    next_check = Instant::now() + idle_interval;
+                    for tracker in registry.live() {
+                        match tracker.check(threshold) {
+                            Ok(elapsed) => diagnostics.emit(DiagnosticEvent::ProcessorStalled {
+                                processor: tracker.processor,
+                                elapsed,
+                            }),
+                            Err(Some(deadline)) => next_check = next_check.min(deadline),
+                            Err(None) => {}
+                        }
+                    }
+                }
+            })
+            .map_err(|e| eprintln!("创建卡住检测线程失败: {}", e))
+            .ok();
+        Self {
+            stop: Some(stop),
+            thread,
+        }
+    }
+}
+
+impl Drop for StallSupervisor {
+    fn drop(&mut self) {
+        // 关闭停止通道，检测线程在下一次等待时退出
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! 启动横幅 - 在程序启动时输出版本、进程和日志配置摘要
+//!
+//! ```rust,no_run
+//! use rat_logger::startup::{log_banner, BannerInfo};
+//!
+//! log_banner(BannerInfo::new("my-service", env!("CARGO_PKG_VERSION"))
+//!     .with_extra("git_sha", "1a2b3c4")
+//!     .with_extra("config", "/etc/my-service.toml"));
+//! ```
+//!
+//! 横幅通过启动日志路径（与 `startup_log!` 相同）立即写入所有处理器，不受批量配置和级别过滤影响。
+//! 每行的顺序和格式固定：应用行、运行环境行、每个处理器一行、每个附加项一行。UDP 认证令牌不会输出。
+
+use crate::config::Level;
+use crate::core::{Logger, Priority};
+use crate::report::HandlerReport;
+
+/// 启动横幅的内容
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BannerInfo {
+    /// 应用名称
+    pub app_name: String,
+    /// 应用版本
+    pub version: String,
+    /// 附加项（如 git 提交、配置文件路径），按添加顺序输出
+    pub extra: Vec<(String, String)>,
+}
+
+impl BannerInfo {
+    /// 创建横幅信息
+    pub fn new(app_name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            app_name: app_name.into(),
+            version: version.into(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// 添加一个附加项
+    pub fn with_extra(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// 通过当前日志器（作用域日志器优先，否则为全局日志器）输出启动横幅，没有日志器时不做任何事
+pub fn log_banner(info: BannerInfo) {
+    let logger = crate::scoped::current().or_else(|| crate::core::lock_global_logger().clone());
+    if let Some(logger) = logger {
+        log_banner_to(logger.as_ref(), &info);
+    }
+}
+
+/// 通过指定的日志器输出启动横幅
+pub fn log_banner_to(logger: &dyn Logger, info: &BannerInfo) {
+    if !logger.emergency_enabled() {
+        return;
+    }
+    for line in banner_lines(logger, info) {
+        let record = crate::macro_record(logger, Level::Info, line, module_path!(), file!(), line!());
+        logger.log_with_priority(&record, Priority::Emergency);
+    }
+}
+
+/// 横幅的各行内容（不含时间戳等格式部分）
+pub fn banner_lines(logger: &dyn Logger, info: &BannerInfo) -> Vec<String> {
+    let mut lines = vec![
+        format!("{} {} 启动", info.app_name, info.version),
+        format!(
+            "rat_logger {} | pid {} | host {} | level {:?}",
+            env!("CARGO_PKG_VERSION"),
+            crate::process_info::pid(),
+            crate::process_info::hostname().unwrap_or("unknown"),
+            logger.level(),
+        ),
+    ];
+
+    let configs = logger.processor_configs();
+    if configs.is_empty() {
+        // 没有配置信息时只列出处理器类型
+        lines.extend(logger.handler_types().into_iter().map(|kind| format!("handler {}", kind)));
+    } else {
+        lines.extend(configs.iter().map(|config| HandlerReport::from(config).summary()));
+    }
+
+    lines.extend(info.extra.iter().map(|(key, value)| format!("{} = {}", key, value)));
+    lines
+}
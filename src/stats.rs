@@ -0,0 +1,478 @@
+//! 日志统计模块 - 日志器运行期间的计数器
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use crossbeam_channel::{bounded, RecvTimeoutError, Sender};
+use serde::Serialize;
+
+/// 日志器运行统计（除 `stalled`、`degraded`、`pending_compressions`、`in_flight_bytes`、熔断和内存预算状态外的计数器单调递增）
+#[derive(Debug, Default)]
+pub struct LoggerStats {
+    /// 被采样丢弃的记录数
+    pub(crate) sampled_out: AtomicU64,
+    /// 处理器发送失败后丢弃的记录数
+    pub(crate) dropped: AtomicU64,
+    /// 处理器操作最终失败的次数
+    pub(crate) processor_errors: AtomicU64,
+    /// 通过紧急路径提交的记录数
+    pub(crate) emergency: AtomicU64,
+    /// 当前被判定为卡住的处理器操作数
+    pub(crate) stalled: AtomicU64,
+    /// 当前处于降级状态的处理器数
+    pub(crate) degraded: AtomicU64,
+    /// 排队和正在执行的压缩任务数
+    pub(crate) pending_compressions: AtomicU64,
+    /// 因压缩任务数达到上限而未压缩的文件数
+    pub(crate) compressions_skipped: AtomicU64,
+    /// 因超过保留期限而删除的归档数
+    pub(crate) archives_expired: AtomicU64,
+    /// 当前是否处于熔断状态
+    pub(crate) breaker_open: AtomicBool,
+    /// 熔断次数
+    pub(crate) breaker_trips: AtomicU64,
+    /// 熔断期间丢弃的记录数
+    pub(crate) breaker_dropped: AtomicU64,
+    /// 已提交但处理器尚未处理完的字节数
+    pub(crate) in_flight_bytes: AtomicU64,
+    /// 等待 `in_flight_bytes` 减少的生产者数（`MemoryAction::BlockProducers`）
+    in_flight_waiters: AtomicUsize,
+    in_flight_lock: parking_lot::Mutex<()>,
+    /// 扣除 `in_flight_bytes` 时通知等待的生产者
+    in_flight_released: parking_lot::Condvar,
+    /// 当前是否超出内存预算
+    pub(crate) memory_exceeded: AtomicBool,
+    /// 因超出内存预算而丢弃的记录数
+    pub(crate) memory_dropped: AtomicU64,
+    /// 日志器关闭后被拒绝的记录数
+    pub(crate) shutdown_rejected: AtomicU64,
+    /// 受保护目标的记录数
+    pub(crate) protected_records: AtomicU64,
+    /// 用户回调中产生、未进入管道的记录数
+    pub(crate) reentrant_records: AtomicU64,
+    /// 通过过滤、提交给处理器的记录数
+    pub(crate) submitted: AtomicU64,
+    /// UDP 离线缓存超出总大小上限时丢弃的记录数
+    pub(crate) spool_dropped: AtomicU64,
+    /// 已打开的 UDP 离线缓存
+    spools: parking_lot::Mutex<Vec<Weak<SpoolGauge>>>,
+}
+
+impl LoggerStats {
+    /// 创建新的统计对象
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 被采样丢弃的记录数
+    pub fn sampled_out(&self) -> u64 {
+        self.sampled_out.load(Ordering::Relaxed)
+    }
+
+    /// 处理器发送失败后丢弃的记录数
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 处理器操作最终失败的次数（不含成功的重试）
+    pub fn processor_errors(&self) -> u64 {
+        self.processor_errors.load(Ordering::Relaxed)
+    }
+
+    /// 通过紧急路径提交的记录数（`emergency!`、`startup_log!` 和 `emergency_log()`）
+    pub fn emergency(&self) -> u64 {
+        self.emergency.load(Ordering::Relaxed)
+    }
+
+    /// 当前被判定为卡住的处理器操作数（操作完成后减少，见 `LoggerBuilder::with_stall_threshold`）
+    pub fn stalled(&self) -> u64 {
+        self.stalled.load(Ordering::Relaxed)
+    }
+
+    /// 当前因连通性探测连续失败而处于降级状态的处理器数（见 `UdpConfig::probe_interval_ms`）
+    pub fn degraded(&self) -> u64 {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// 排队和正在执行的压缩任务数（见 `FileConfig::max_pending_compressions`）
+    pub fn pending_compressions(&self) -> u64 {
+        self.pending_compressions.load(Ordering::Relaxed)
+    }
+
+    /// 因压缩任务数达到上限而保留为 `.log` 的轮转文件数
+    pub fn compressions_skipped(&self) -> u64 {
+        self.compressions_skipped.load(Ordering::Relaxed)
+    }
+
+    /// 因超过 `FileConfig::max_archive_age` 而删除的压缩归档数
+    pub fn archives_expired(&self) -> u64 {
+        self.archives_expired.load(Ordering::Relaxed)
+    }
+
+    /// 当前是否处于熔断状态（见 `LoggerBuilder::with_max_record_rate`）
+    pub fn breaker_open(&self) -> bool {
+        self.breaker_open.load(Ordering::Relaxed)
+    }
+
+    /// 熔断次数
+    pub fn breaker_trips(&self) -> u64 {
+        self.breaker_trips.load(Ordering::Relaxed)
+    }
+
+    /// 熔断期间丢弃的记录数
+    pub fn breaker_dropped(&self) -> u64 {
+        self.breaker_dropped.load(Ordering::Relaxed)
+    }
+
+    /// 已提交但处理器尚未处理完的字节数（每个处理器的副本分别计入，处理完后扣除）
+    pub fn in_flight_bytes(&self) -> u64 {
+        self.in_flight_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 当前是否超出内存预算（见 `LoggerBuilder::with_memory_budget`）
+    pub fn memory_exceeded(&self) -> bool {
+        self.memory_exceeded.load(Ordering::Relaxed)
+    }
+
+    /// 因超出内存预算而丢弃的记录数
+    pub fn memory_dropped(&self) -> u64 {
+        self.memory_dropped.load(Ordering::Relaxed)
+    }
+
+    /// 日志器开始关闭（见 `LoggerCore::shutdown`）后被拒绝的记录数
+    pub fn shutdown_rejected(&self) -> u64 {
+        self.shutdown_rejected.load(Ordering::Relaxed)
+    }
+
+    /// 通过级别过滤的受保护目标记录数（见 `LoggerBuilder::with_protected_targets`）
+    pub fn protected_records(&self) -> u64 {
+        self.protected_records.load(Ordering::Relaxed)
+    }
+
+    /// 格式化函数、富化函数等用户回调中产生的记录数（见 `LoggerBuilder::with_reentrant_policy`），
+    /// 这些记录被丢弃或写入标准错误，不计入 `submitted`
+    pub fn reentrant_records(&self) -> u64 {
+        self.reentrant_records.load(Ordering::Relaxed)
+    }
+
+    /// 通过级别过滤、采样和熔断后提交给处理器的记录数（包括紧急日志，不含 `log_raw` 的原始数据）
+    pub fn submitted(&self) -> u64 {
+        self.submitted.load(Ordering::Relaxed)
+    }
+
+    /// UDP 离线缓存超出总大小上限时丢弃的记录数
+    pub fn spool_dropped(&self) -> u64 {
+        self.spool_dropped.load(Ordering::Relaxed)
+    }
+
+    /// UDP 离线缓存的当前状态（多个处理器启用缓存时为合计，未启用时为 None）
+    pub fn spool(&self) -> Option<SpoolStats> {
+        let spools: Vec<_> = self.spools.lock().iter().filter_map(Weak::upgrade).collect();
+        if spools.is_empty() {
+            return None;
+        }
+        let mut stats = SpoolStats::default();
+        for gauge in spools {
+            stats.segments += gauge.segments.load(Ordering::Relaxed);
+            stats.bytes += gauge.bytes.load(Ordering::Relaxed);
+            let oldest = gauge.oldest_ms.load(Ordering::Relaxed);
+            if oldest > 0 {
+                stats.oldest_timestamp_ms = Some(stats.oldest_timestamp_ms.map_or(oldest, |current| current.min(oldest)));
+            }
+        }
+        Some(stats)
+    }
+
+    /// 所有单调递增计数器的快照
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            submitted: self.submitted(),
+            emergency: self.emergency(),
+            protected_records: self.protected_records(),
+            sampled_out: self.sampled_out(),
+            breaker_trips: self.breaker_trips(),
+            breaker_dropped: self.breaker_dropped(),
+            memory_dropped: self.memory_dropped(),
+            dropped: self.dropped(),
+            shutdown_rejected: self.shutdown_rejected(),
+            processor_errors: self.processor_errors(),
+            compressions_skipped: self.compressions_skipped(),
+            archives_expired: self.archives_expired(),
+            spool_dropped: self.spool_dropped(),
+            reentrant_records: self.reentrant_records(),
+        }
+    }
+
+    /// 全局压缩线程池当前的线程数（进程内所有日志器共享，尚未发生压缩时为 0）
+    pub fn compression_threads(&self) -> u64 {
+        crate::handler::file::compression_threads() as u64
+    }
+
+    pub(crate) fn add_dropped(&self, count: u64) {
+        self.dropped.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_processor_error(&self) {
+        self.processor_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_stalled(&self) {
+        self.stalled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn remove_stalled(&self) {
+        self.stalled.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_degraded(&self) {
+        self.degraded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn remove_degraded(&self) {
+        self.degraded.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_pending_compression(&self) {
+        self.pending_compressions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn remove_pending_compression(&self) {
+        self.pending_compressions.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_compression_skipped(&self) {
+        self.compressions_skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_archives_expired(&self, count: u64) {
+        self.archives_expired.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_breaker_open(&self, open: bool) {
+        self.breaker_open.store(open, Ordering::Relaxed);
+        if open {
+            self.breaker_trips.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn add_breaker_dropped(&self) {
+        self.breaker_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_in_flight(&self, bytes: u64) {
+        self.in_flight_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn remove_in_flight(&self, bytes: u64) {
+        // 通过 `ProcessorWorker::sender()` 直接发送的数据没有计入，不能减到 0 以下
+        let _ = self.in_flight_bytes.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| Some(n.saturating_sub(bytes)));
+        // 等待方检查字节数时持有锁，加锁后再通知不会错过它的等待
+        if self.in_flight_waiters.load(Ordering::SeqCst) > 0 {
+            let _guard = self.in_flight_lock.lock();
+            self.in_flight_released.notify_all();
+        }
+    }
+
+    /// `fits(当前字节数)` 成立时原子地在 `in_flight_bytes` 上预留 `bytes`，返回是否预留成功
+    pub(crate) fn try_reserve_in_flight(&self, bytes: u64, fits: impl Fn(u64) -> bool) -> bool {
+        self.in_flight_bytes
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| fits(n).then_some(n + bytes))
+            .is_ok()
+    }
+
+    /// 等到 `fits(当前字节数)` 成立后预留 `bytes`，处理器扣除字节数时被唤醒重新检查
+    pub(crate) fn reserve_in_flight_blocking(&self, bytes: u64, fits: impl Fn(u64) -> bool) {
+        self.in_flight_waiters.fetch_add(1, Ordering::SeqCst);
+        let mut guard = self.in_flight_lock.lock();
+        while !self.try_reserve_in_flight(bytes, &fits) {
+            self.in_flight_released.wait(&mut guard);
+        }
+        drop(guard);
+        self.in_flight_waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn set_memory_exceeded(&self, exceeded: bool) {
+        self.memory_exceeded.store(exceeded, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_memory_dropped(&self) {
+        self.memory_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_shutdown_rejected(&self) {
+        self.shutdown_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_submitted(&self) {
+        self.submitted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_spool_dropped(&self, count: u64) {
+        self.spool_dropped.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// 登记一个离线缓存，缓存释放后不再计入 [`spool`](Self::spool)
+    pub(crate) fn register_spool(&self) -> Arc<SpoolGauge> {
+        let gauge = Arc::new(SpoolGauge::default());
+        let mut spools = self.spools.lock();
+        spools.retain(|spool| spool.strong_count() > 0);
+        spools.push(Arc::downgrade(&gauge));
+        gauge
+    }
+}
+
+/// 统计快照：日志器创建以来的累计计数，只包含单调递增的计数器，适合导出为 Prometheus 的 counter
+///
+/// 字段名即导出的计数器名，之后的版本只会增加字段，不会改名或改变含义。
+/// 当前状态类的值（`in_flight_bytes`、`stalled` 等）不在快照中，需要时直接读取 [`LoggerStats`]。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct StatsSnapshot {
+    /// 通过过滤、提交给处理器的记录数
+    pub submitted: u64,
+    /// 通过紧急路径提交的记录数（已计入 `submitted`）
+    pub emergency: u64,
+    /// 受保护目标的记录数（已计入 `submitted`）
+    pub protected_records: u64,
+    /// 被采样丢弃的记录数
+    pub sampled_out: u64,
+    /// 熔断次数
+    pub breaker_trips: u64,
+    /// 熔断期间丢弃的记录数
+    pub breaker_dropped: u64,
+    /// 因超出内存预算而丢弃的记录数
+    pub memory_dropped: u64,
+    /// 处理器发送失败后丢弃的记录数
+    pub dropped: u64,
+    /// 日志器关闭后被拒绝的记录数
+    pub shutdown_rejected: u64,
+    /// 处理器操作最终失败的次数
+    pub processor_errors: u64,
+    /// 因压缩任务数达到上限而未压缩的文件数
+    pub compressions_skipped: u64,
+    /// 因超过保留期限而删除的归档数
+    pub archives_expired: u64,
+    /// UDP 离线缓存超出总大小上限时丢弃的记录数
+    pub spool_dropped: u64,
+    /// 用户回调中产生、未进入管道的记录数
+    pub reentrant_records: u64,
+}
+
+/// UDP 离线缓存的当前状态（见 [`LoggerStats::spool`]）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct SpoolStats {
+    /// 待重放的分段数
+    pub segments: u64,
+    /// 待重放的字节数
+    pub bytes: u64,
+    /// 最旧数据的写入时间（Unix 毫秒，缓存为空时为 None）
+    pub oldest_timestamp_ms: Option<u64>,
+}
+
+/// 单个离线缓存发布的状态（由 UDP 发送任务更新）
+#[derive(Debug, Default)]
+pub(crate) struct SpoolGauge {
+    segments: AtomicU64,
+    bytes: AtomicU64,
+    /// 最旧数据的写入时间（0 表示缓存为空）
+    oldest_ms: AtomicU64,
+}
+
+impl SpoolGauge {
+    pub(crate) fn set(&self, segments: u64, bytes: u64, oldest_ms: u64) {
+        self.segments.store(segments, Ordering::Relaxed);
+        self.bytes.store(bytes, Ordering::Relaxed);
+        self.oldest_ms.store(oldest_ms, Ordering::Relaxed);
+    }
+}
+
+/// 统计导出回调（见 `LoggerBuilder::with_stats_exporter`）
+pub type StatsExporterFn = Arc<dyn Fn(StatsSnapshot) + Send + Sync>;
+
+/// 定期执行任务的线程（统计导出、管道探测），丢弃时停止
+pub(crate) struct PeriodicTask {
+    stop: Option<Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl PeriodicTask {
+    /// 启动名为 `name` 的线程，每隔 `interval` 执行一次 `task`，`what` 用于线程创建失败时的提示
+    pub(crate) fn spawn(name: &str, what: &str, interval: Duration, mut task: impl FnMut() + Send + 'static) -> Self {
+        let (stop, stopped) = bounded::<()>(0);
+        let thread = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                // 按绝对时间计时，任务的耗时不会累积成漂移；耗时超过间隔时从当前时间重新计时
+                let mut next_tick = Instant::now() + interval;
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_deadline(next_tick) {
+                    task();
+                    next_tick = (next_tick + interval).max(Instant::now());
+                }
+            })
+            .map_err(|e| eprintln!("创建{}线程失败: {}", what, e))
+            .ok();
+        Self {
+            stop: Some(stop),
+            thread,
+        }
+    }
+
+    /// 启动统计导出线程，每隔 `interval` 以当前快照调用一次 `exporter`
+    pub(crate) fn stats_exporter(stats: Arc<LoggerStats>, interval: Duration, exporter: StatsExporterFn) -> Self {
+        Self::spawn("rat-logger-stats", "统计导出", interval, move || exporter(stats.snapshot()))
+    }
+}
+
+impl Drop for PeriodicTask {
+    fn drop(&mut self) {
+        // 关闭停止通道，线程在下一次等待时退出
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 单个处理器的管道探测延迟（见 `LoggerBuilder::with_pipeline_probe`），由工作线程写入
+#[derive(Debug, Default)]
+pub(crate) struct ProbeLatency {
+    probes: AtomicU64,
+    last_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl ProbeLatency {
+    /// 记录一次探测的延迟
+    pub(crate) fn record(&self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.last_us.store(micros, Ordering::Relaxed);
+        self.max_us.fetch_max(micros, Ordering::Relaxed);
+        self.probes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn report(&self, processor: &'static str) -> ProbeReport {
+        ProbeReport {
+            processor,
+            probes: self.probes.load(Ordering::Relaxed),
+            last_latency_us: self.last_us.load(Ordering::Relaxed),
+            max_latency_us: self.max_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 处理器的管道探测延迟：探测标记从产生到处理器处理完包含它的批次所用的时间，
+/// 包括在队列中等待和凑满批次的时间（见 `LoggerCore::probe_reports`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct ProbeReport {
+    /// 处理器名称
+    pub processor: &'static str,
+    /// 已完成的探测次数
+    pub probes: u64,
+    /// 最近一次探测的延迟（微秒）
+    pub last_latency_us: u64,
+    /// 探测延迟的最大值（微秒）
+    pub max_latency_us: u64,
+}
@@ -0,0 +1,209 @@
+//! 测试辅助工具（需要 `test-util` 特性）
+//!
+//! [`UdpCollector`] 在本地临时端口上接收 UDP 日志并解码为 `NetRecord`，
+//! 集成测试无需自己编写监听和解码代码。收集器默认应答连通性探测，可以临时关闭以模拟服务端失联。
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use parking_lot::{Condvar, Mutex};
+
+use crate::config::{NetRecord, NetworkConfig};
+use crate::udp_helper::{UdpPacketHelper, MAX_DATAGRAM_SIZE};
+
+/// 接收线程检查停止标志的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 收到的记录（可克隆，所有克隆共享同一份数据）
+#[derive(Clone, Default)]
+pub struct CollectorHandle {
+    records: Arc<Mutex<Vec<NetRecord>>>,
+    packets: Arc<Mutex<Vec<Vec<u8>>>>,
+    arrived: Arc<Condvar>,
+    invalid: Arc<AtomicUsize>,
+    probes: Arc<AtomicUsize>,
+    ignore_probes: Arc<AtomicBool>,
+}
+
+impl CollectorHandle {
+    /// 已收到的所有记录
+    pub fn records(&self) -> Vec<NetRecord> {
+        self.records.lock().clone()
+    }
+
+    /// 已收到的所有记录的消息
+    pub fn messages(&self) -> Vec<String> {
+        self.records.lock().iter().map(|record| record.message.clone()).collect()
+    }
+
+    /// 已收到的所有数据包的原始字节（批量数据报拆分为单个数据包），用于检查转发是否改动了数据包
+    pub fn packets(&self) -> Vec<Vec<u8>> {
+        self.packets.lock().clone()
+    }
+
+    /// 已收到的记录数
+    pub fn len(&self) -> usize {
+        self.records.lock().len()
+    }
+
+    /// 是否还没有收到记录
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 无法解码的数据报数
+    pub fn invalid(&self) -> usize {
+        self.invalid.load(Ordering::Relaxed)
+    }
+
+    /// 收到的连通性探测数
+    pub fn probes(&self) -> usize {
+        self.probes.load(Ordering::Relaxed)
+    }
+
+    /// 是否应答连通性探测（默认应答）
+    pub fn set_answer_probes(&self, answer: bool) {
+        self.ignore_probes.store(!answer, Ordering::Relaxed);
+    }
+
+    /// 等待至少收到 `n` 条记录，超时返回 false
+    pub fn wait_for(&self, n: usize, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut records = self.records.lock();
+        while records.len() < n {
+            if self.arrived.wait_until(&mut records, deadline).timed_out() {
+                return records.len() >= n;
+            }
+        }
+        true
+    }
+
+    /// 探测数据报返回应发回的应答（关闭应答时返回 None）
+    fn probe(&self, datagram: &[u8]) -> Option<Vec<u8>> {
+        self.probes.fetch_add(1, Ordering::Relaxed);
+        if self.ignore_probes.load(Ordering::Relaxed) {
+            return None;
+        }
+        UdpPacketHelper::probe_ack(datagram)
+    }
+
+    fn push(&self, datagram: &[u8]) {
+        match UdpPacketHelper::decode_datagram(datagram) {
+            Ok(decoded) => {
+                let packets = UdpPacketHelper::split_batch(datagram).unwrap_or_else(|_| vec![datagram]);
+                self.packets.lock().extend(packets.into_iter().map(<[u8]>::to_vec));
+                self.records.lock().extend(decoded);
+                self.arrived.notify_all();
+            }
+            Err(_) => {
+                self.invalid.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// 本地 UDP 日志收集器
+///
+/// 默认绑定 `127.0.0.1` 的临时端口，在后台线程中接收并解码单条和批量数据报，释放时停止接收线程。
+pub struct UdpCollector {
+    addr: SocketAddr,
+    handle: CollectorHandle,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl UdpCollector {
+    /// 绑定临时端口并开始接收
+    pub fn bind() -> io::Result<Self> {
+        Self::bind_to("127.0.0.1:0")
+    }
+
+    /// 绑定指定地址（如 IPv6 回环地址 `[::1]:0`）并开始接收
+    pub fn bind_to(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        let addr = socket.local_addr()?;
+        let handle = CollectorHandle::default();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let handle = handle.clone();
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("rat_logger-udp-collector".to_string())
+                .spawn(move || {
+                    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+                    while !stop.load(Ordering::Relaxed) {
+                        let Ok((len, from)) = socket.recv_from(&mut buf) else {
+                            continue;
+                        };
+                        if UdpPacketHelper::is_probe(&buf[..len]) {
+                            if let Some(ack) = handle.probe(&buf[..len]) {
+                                let _ = socket.send_to(&ack, from);
+                            }
+                        } else {
+                            handle.push(&buf[..len]);
+                        }
+                    }
+                })?
+        };
+
+        Ok(Self { addr, handle, stop, thread: Some(thread) })
+    }
+
+    /// 收集器监听的地址
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 指向收集器的网络配置（其余字段使用默认值）
+    pub fn network_config(&self) -> NetworkConfig {
+        NetworkConfig {
+            server_addr: self.addr.ip().to_string(),
+            server_port: self.addr.port(),
+            ..Default::default()
+        }
+    }
+
+    /// 收到的记录的句柄
+    pub fn handle(&self) -> CollectorHandle {
+        self.handle.clone()
+    }
+
+    /// 已收到的所有记录
+    pub fn records(&self) -> Vec<NetRecord> {
+        self.handle.records()
+    }
+
+    /// 已收到的所有记录的消息
+    pub fn messages(&self) -> Vec<String> {
+        self.handle.messages()
+    }
+
+    /// 已收到的所有数据包的原始字节
+    pub fn packets(&self) -> Vec<Vec<u8>> {
+        self.handle.packets()
+    }
+
+    /// 是否应答连通性探测（默认应答）
+    pub fn set_answer_probes(&self, answer: bool) {
+        self.handle.set_answer_probes(answer);
+    }
+
+    /// 等待至少收到 `n` 条记录，超时返回 false
+    pub fn wait_for(&self, n: usize, timeout: Duration) -> bool {
+        self.handle.wait_for(n, timeout)
+    }
+}
+
+impl Drop for UdpCollector {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! 离线日志工具：将已有日志文件按新的格式配置重新渲染
+//!
+//! 更换文件格式模板后，旧日志与新日志的样式不同。[`reformat`] 读取旧日志，还原出 [`Record`]，
+//! 再按给定的 [`FormatConfig`] 输出，渲染方式与文件处理器使用格式配置时相同（控制字符按
+//! `sanitize` 处理，未指定时转义）。`.lz4` 结尾的输入（轮转压缩后的日志）会先解压。
+//!
+//! 无法解析的行原样输出并加上 [`UNPARSED_MARKER`] 前缀，不会中断处理。
+//!
+//! [`LogTailer`] 跟踪文件处理器正在写入的日志文件，逐行返回新追加的内容，轮转后自动切换到新文件。
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use chrono::TimeZone;
+use serde_json::Value;
+
+use crate::config::{FormatConfig, Level, LineEnding, Metadata, Record, SanitizeMode};
+use crate::handler::file::{FileProcessor, HEADER_PREFIX};
+use crate::handler::sanitize_record;
+
+/// 无法解析的内容在输出中的前缀
+pub const UNPARSED_MARKER: &str = "[unparsed] ";
+
+/// 默认文本格式中的时间戳格式
+const TEXT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// 输入日志的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    /// 文件处理器的默认文本格式（`{timestamp} [{level}] {target} {file}:{line} - {message}`）
+    ///
+    /// 尽力解析：时间戳按本地时间解释，`unknown:0` 还原为没有文件和行号，多行消息的后续行视为无法解析。
+    /// 文件头部行（`# rat_logger ` 开头）会被跳过。
+    Text,
+    /// 每行一个 JSON 对象
+    ///
+    /// 必需键为 `level` 和 `message`；可选键为 `timestamp`（RFC 3339 字符串或 Unix 毫秒数）、`target`、
+    /// `module`、`file`、`line`、`app_id`、`seq`、`tid`/`tseq` 以及 `fields`（值为字符串、数字或布尔值的对象）。
+    /// `config::JsonRecord`（`Record` 的 serde 序列化）输出的行可以直接读取。
+    Ndjson,
+    /// 连续的带版本号的 bincode 编码记录（与处理器通道中的编码相同，见 `config::encode_record_v2`）
+    ///
+    /// 遇到无法解码的数据时无法重新定位到下一条记录，剩余数据以一行标记说明后结束。
+    Binary,
+}
+
+/// 重新渲染的结果统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReformatSummary {
+    /// 成功还原并重新渲染的记录数
+    pub records: usize,
+    /// 原样输出的无法解析的行（或二进制数据段）数
+    pub unparsed: usize,
+}
+
+/// 读取 `input` 中的日志，按 `format` 重新渲染后写入 `output`
+///
+/// 只有读取输入或写入输出失败时返回错误；无法解析的内容加上 [`UNPARSED_MARKER`] 前缀原样输出。
+pub fn reformat(input: &Path, input_kind: InputKind, format: &FormatConfig, output: &mut dyn Write) -> io::Result<ReformatSummary> {
+    format.validate().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let reader = open_input(input)?;
+    let mut renderer = Renderer { format, output, summary: ReformatSummary::default() };
+
+    match input_kind {
+        InputKind::Binary => {
+            let mut data = Vec::new();
+            BufReader::new(reader).read_to_end(&mut data)?;
+            reformat_binary(&data, &mut renderer)?;
+        }
+        InputKind::Text | InputKind::Ndjson => {
+            let parse = match input_kind {
+                InputKind::Text => parse_text_line,
+                _ => parse_json_line,
+            };
+            let mut reader = BufReader::new(reader);
+            let mut buf = Vec::new();
+            while reader.read_until(b'\n', &mut buf)? > 0 {
+                let line = String::from_utf8_lossy(&buf);
+                let line = line.trim_end_matches(['\n', '\r']);
+                if !(line.is_empty() || input_kind == InputKind::Text && line.starts_with(HEADER_PREFIX)) {
+                    match parse(line) {
+                        Some(record) => renderer.record(&record)?,
+                        None => renderer.unparsed(line)?,
+                    }
+                }
+                buf.clear();
+            }
+        }
+    }
+
+    renderer.output.flush()?;
+    Ok(renderer.summary)
+}
+
+/// 打开输入文件，`.lz4` 文件自动解压
+fn open_input(path: &Path) -> io::Result<Box<dyn Read>> {
+    let file = std::fs::File::open(path)?;
+    if path.extension().is_some_and(|ext| ext == "lz4") {
+        Ok(Box::new(lz4::Decoder::new(file)?))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+struct Renderer<'a> {
+    format: &'a FormatConfig,
+    output: &'a mut dyn Write,
+    summary: ReformatSummary,
+}
+
+impl Renderer<'_> {
+    fn record(&mut self, record: &Record) -> io::Result<()> {
+        let sanitize = self.format.sanitize.unwrap_or(SanitizeMode::Escape);
+        FileProcessor::format_with_config(self.output, &sanitize_record(record, sanitize), self.format, LineEnding::Lf)?;
+        self.summary.records += 1;
+        Ok(())
+    }
+
+    fn unparsed(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.output, "{}{}", UNPARSED_MARKER, line)?;
+        self.summary.unparsed += 1;
+        Ok(())
+    }
+}
+
+/// 日志文件跟踪器：读取文件处理器写入的活动日志文件中新追加的行
+///
+/// 按文件处理器的命名规则（`{prefix}{YYYYMMDD_HHMMSS}.log`，同一秒内重复时追加 `_N`）在目录中查找
+/// 最新的文件。轮转产生新文件时，先读完旧文件剩余的内容再按顺序切换到下一个文件，跨轮转的每一行只返回一次；
+/// 文件变短时视为被截断，从头重新读取。通过轮询检测变化，不依赖文件系统通知。
+///
+/// 作为迭代器使用时阻塞等待新行；[`LogTailer::poll`] 只读取当前可用的行，不等待。
+///
+/// ```rust,no_run
+/// use rat_logger::tools::LogTailer;
+/// use rat_logger::LevelFilter;
+///
+/// let tailer = LogTailer::new("./logs", "app_").with_level_filter(|level| level.should_log_at(LevelFilter::Warn));
+/// for line in tailer {
+///     println!("{}", line.unwrap());
+/// }
+/// ```
+pub struct LogTailer {
+    log_dir: PathBuf,
+    prefix: String,
+    poll_interval: Duration,
+    from_start: bool,
+    level_filter: Option<Box<dyn Fn(Level) -> bool + Send>>,
+    current: Option<TailedFile>,
+    /// 上一条可解析记录是否被保留，多行消息的后续行跟随它
+    keep_continuation: bool,
+    pending: std::collections::VecDeque<String>,
+}
+
+struct TailedFile {
+    path: PathBuf,
+    reader: BufReader<File>,
+    offset: u64,
+    /// 尚未以换行结束的行
+    partial: Vec<u8>,
+}
+
+impl LogTailer {
+    /// 默认轮询间隔
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+    /// 跟踪 `log_dir` 中以 `prefix` 开头的日志文件（默认前缀为 `app_`）
+    ///
+    /// 默认从当前活动文件的末尾开始，只返回之后追加的行；目录中还没有日志文件时等待其出现，并从头读取。
+    pub fn new(log_dir: impl Into<PathBuf>, prefix: impl Into<String>) -> Self {
+        Self {
+            log_dir: log_dir.into(),
+            prefix: prefix.into(),
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            from_start: false,
+            level_filter: None,
+            current: None,
+            keep_continuation: true,
+            pending: std::collections::VecDeque::new(),
+        }
+    }
+
+    /// 设置迭代器等待新内容时的轮询间隔
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 从当前活动文件的开头读取，而不是末尾
+    pub fn from_start(mut self) -> Self {
+        self.from_start = true;
+        self
+    }
+
+    /// 只返回级别满足 `filter` 的记录
+    ///
+    /// 级别按默认文本格式（`{timestamp} [{level}] ...`）解析；无法解析的行（如多行消息的后续行）
+    /// 跟随前一条记录，文件头部行始终跳过。
+    pub fn with_level_filter(mut self, filter: impl Fn(Level) -> bool + Send + 'static) -> Self {
+        self.level_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// 当前跟踪的文件
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|file| file.path.as_path())
+    }
+
+    /// 读取当前可用的所有完整行（不含换行符），没有新内容时返回空列表
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let mut lines: Vec<String> = self.pending.drain(..).collect();
+        if self.current.is_none() {
+            let Some(path) = self.log_files()?.pop() else {
+                // 第一次轮询时还没有文件，之后出现的文件从头读取
+                self.from_start = true;
+                return Ok(lines);
+            };
+            let at_end = !self.from_start;
+            self.open(path, at_end)?;
+        }
+
+        loop {
+            // 先列出目录再读完当前文件：看到新文件时旧文件已经写完，读到的就是全部内容
+            let next = self.next_file()?;
+            self.read_current(&mut lines)?;
+            match next {
+                Some(path) => {
+                    self.finish_current(&mut lines);
+                    self.open(path, false)?;
+                }
+                None => return Ok(lines),
+            }
+        }
+    }
+
+    /// 目录中匹配前缀的日志文件，按轮转顺序排列
+    fn log_files(&self) -> io::Result<Vec<PathBuf>> {
+        let entries = match std::fs::read_dir(&self.log_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut files: Vec<((String, u64), PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                Some((self.rotation_key(&path)?, path))
+            })
+            .collect();
+        files.sort();
+        Ok(files.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// 当前文件之后的下一个文件
+    fn next_file(&self) -> io::Result<Option<PathBuf>> {
+        let Some(current) = &self.current else {
+            return Ok(None);
+        };
+        let files = self.log_files()?;
+        Ok(match files.iter().position(|path| *path == current.path) {
+            Some(index) => files.get(index + 1).cloned(),
+            // 当前文件已被删除（如压缩归档），切换到比它新的第一个文件
+            None => {
+                let current_key = self.rotation_key(&current.path);
+                files.into_iter().find(|path| self.rotation_key(path) > current_key)
+            }
+        })
+    }
+
+    fn rotation_key(&self, path: &Path) -> Option<(String, u64)> {
+        let name = path.file_name()?.to_str()?;
+        rotation_key(name.strip_prefix(&self.prefix)?.strip_suffix(".log")?)
+    }
+
+    fn open(&mut self, path: PathBuf, at_end: bool) -> io::Result<()> {
+        let mut file = File::open(&path)?;
+        let offset = if at_end { file.seek(SeekFrom::End(0))? } else { 0 };
+        self.current = Some(TailedFile { path, reader: BufReader::new(file), offset, partial: Vec::new() });
+        Ok(())
+    }
+
+    /// 读取当前文件中新追加的完整行
+    fn read_current(&mut self, lines: &mut Vec<String>) -> io::Result<()> {
+        let Some(file) = self.current.as_mut() else {
+            return Ok(());
+        };
+        // 文件变短说明被截断，从头重新读取
+        let len = file.reader.get_ref().metadata()?.len();
+        if len < file.offset {
+            file.reader.seek(SeekFrom::Start(0))?;
+            file.offset = 0;
+            file.partial.clear();
+        }
+
+        let mut completed = Vec::new();
+        loop {
+            let read = file.reader.read_until(b'\n', &mut file.partial)?;
+            if read == 0 {
+                break;
+            }
+            file.offset += read as u64;
+            if file.partial.ends_with(b"\n") {
+                completed.push(std::mem::take(&mut file.partial));
+            }
+        }
+        for line in completed {
+            self.push_line(&line, lines);
+        }
+        Ok(())
+    }
+
+    /// 切换文件前输出旧文件末尾没有换行的内容
+    fn finish_current(&mut self, lines: &mut Vec<String>) {
+        if let Some(file) = self.current.take().filter(|file| !file.partial.is_empty()) {
+            self.push_line(&file.partial, lines);
+        }
+    }
+
+    fn push_line(&mut self, line: &[u8], lines: &mut Vec<String>) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line.starts_with(HEADER_PREFIX) {
+            return;
+        }
+        if let Some(filter) = &self.level_filter {
+            if let Some(level) = text_line_level(line) {
+                self.keep_continuation = filter(level);
+            }
+            if !self.keep_continuation {
+                return;
+            }
+        }
+        lines.push(line.to_string());
+    }
+}
+
+impl Iterator for LogTailer {
+    type Item = io::Result<String>;
+
+    /// 阻塞直到有新行；读取出错时返回错误，之后可以继续迭代
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.pending.pop_front() {
+                return Some(Ok(line));
+            }
+            match self.poll() {
+                Ok(lines) if lines.is_empty() => std::thread::sleep(self.poll_interval),
+                Ok(lines) => self.pending.extend(lines),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// 文件名中前缀之后的部分（`YYYYMMDD_HHMMSS` 或 `YYYYMMDD_HHMMSS_N`）转换为排序键
+fn rotation_key(stem: &str) -> Option<(String, u64)> {
+    let timestamp = stem.get(..15)?;
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d_%H%M%S").ok()?;
+    let suffix = match &stem[15..] {
+        "" => 0,
+        rest => rest.strip_prefix('_')?.parse().ok()?,
+    };
+    Some((timestamp.to_string(), suffix))
+}
+
+/// 默认文本格式中一行的级别
+fn text_line_level(line: &str) -> Option<Level> {
+    let (_, rest) = line.split_once(" [")?;
+    let (level, _) = rest.split_once("] ")?;
+    parse_level(level)
+}
+
+fn reformat_binary(mut data: &[u8], renderer: &mut Renderer) -> io::Result<()> {
+    while !data.is_empty() {
+        match crate::config::decode_record_versioned(data) {
+            Ok((record, consumed)) => {
+                renderer.record(&record)?;
+                data = &data[consumed..];
+            }
+            Err(e) => {
+                return renderer.unparsed(&format!("<{} 字节无法解码的二进制数据: {}>", data.len(), e));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_level(text: &str) -> Option<Level> {
+    match text.to_uppercase().as_str() {
+        "ERROR" => Some(Level::Error),
+        "WARN" | "WARNING" => Some(Level::Warn),
+        "INFO" => Some(Level::Info),
+        "DEBUG" => Some(Level::Debug),
+        "TRACE" => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+fn record(level: Level, target: String, message: String) -> Record {
+    Record {
+        metadata: Arc::new(Metadata { level, target, auth_token: None, app_id: None }),
+        args: message.into(),
+        ..Default::default()
+    }
+}
+
+/// 解析默认文本格式的一行
+fn parse_text_line(line: &str) -> Option<Record> {
+    let (timestamp, rest) = line.split_once(" [")?;
+    let (level, rest) = rest.split_once("] ")?;
+    let (location, message) = rest.split_once(" - ")?;
+    let (target, location) = location.split_once(' ')?;
+    let (file, line_number) = location.rsplit_once(':')?;
+
+    let timestamp = chrono::NaiveDateTime::parse_from_str(timestamp, TEXT_TIMESTAMP_FORMAT).ok()?;
+    let timestamp = chrono::Local.from_local_datetime(&timestamp).earliest()?;
+    let line_number: u32 = line_number.parse().ok()?;
+
+    let mut record = record(parse_level(level)?, target.to_string(), message.to_string());
+    record.timestamp = Some(timestamp.into());
+    if file != "unknown" {
+        record.file = Some(file.to_string());
+    }
+    record.line = (line_number != 0).then_some(line_number);
+    Some(record)
+}
+
+/// 解析一行 JSON 记录
+fn parse_json_line(line: &str) -> Option<Record> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    let object = value.as_object()?;
+    let text = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+    let number = |key: &str| object.get(key).and_then(Value::as_u64);
+
+    let mut record = record(
+        parse_level(object.get("level")?.as_str()?)?,
+        text("target").unwrap_or_default(),
+        text("message")?,
+    );
+    record.timestamp = match object.get("timestamp") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(timestamp)) => Some(chrono::DateTime::parse_from_rfc3339(timestamp).ok()?.into()),
+        Some(Value::Number(millis)) => Some(UNIX_EPOCH + Duration::from_millis(millis.as_u64()?)),
+        Some(_) => return None,
+    };
+    if let Some(app_id) = text("app_id") {
+        Arc::make_mut(&mut record.metadata).app_id = Some(app_id);
+    }
+    record.module_path = text("module");
+    record.file = text("file");
+    record.line = number("line").and_then(|line| u32::try_from(line).ok());
+    record.seq = number("seq");
+    if let (Some(id), Some(seq)) = (number("tid"), number("tseq")) {
+        record.thread = Some(crate::config::ThreadStamp { id, seq });
+    }
+    record.record_id = text("record_id").and_then(|id| u64::from_str_radix(&id, 16).ok());
+    if let Some(fields) = object.get("fields") {
+        for (key, value) in fields.as_object()? {
+            let value = match value {
+                Value::String(text) => text.clone(),
+                Value::Number(number) => number.to_string(),
+                Value::Bool(flag) => flag.to_string(),
+                _ => return None,
+            };
+            record.fields.push((key.clone(), value));
+        }
+    }
+    Some(record)
+}
@@ -3,6 +3,127 @@
 use crate::config::{Level, Record, NetRecord, Metadata};
 use bincode;
 use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 数据包头部魔数（v1 数据包没有头部，首字节为级别字符串长度，不会与魔数冲突）
+pub const PACKET_MAGIC: [u8; 2] = *b"RL";
+
+/// 当前数据包线格式版本
+///
+/// - v1: 无头部，级别以字符串编码，NetRecord 不含主机信息、序号和记录标识
+/// - v2: `魔数 + 版本号` 头部；级别、应用ID、目标、时间戳、序号、主机名、进程号等字段在消息体之前，
+///   支持只解析头部；级别为单字节编码值（`Level::wire_value`）；消息体之后为级别别名和记录标识
+pub const PACKET_VERSION: u8 = 2;
+
+/// 数据包头部长度
+pub const PACKET_HEADER_LEN: usize = PACKET_MAGIC.len() + 1;
+
+/// 批量数据报魔数
+pub const BATCH_MAGIC: [u8; 2] = *b"RB";
+
+/// 批量数据报帧格式版本
+pub const BATCH_VERSION: u8 = 1;
+
+/// 批量数据报头部长度：魔数 + 版本号 + 记录数(u16)
+pub const BATCH_HEADER_LEN: usize = BATCH_MAGIC.len() + 1 + 2;
+
+/// 批量数据报中每条记录的长度前缀字节数(u16)
+pub const BATCH_LEN_PREFIX: usize = 2;
+
+/// 连通性探测数据报魔数
+pub const PROBE_MAGIC: [u8; 2] = *b"RP";
+
+/// 探测数据报格式版本
+pub const PROBE_VERSION: u8 = 1;
+
+/// 探测数据报长度：魔数 + 版本号 + 类型 + 探测编号(u64)
+pub const PROBE_LEN: usize = PROBE_MAGIC.len() + 1 + 1 + 8;
+
+/// 探测数据报类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeKind {
+    /// 发送端发出的探测
+    Probe,
+    /// 服务端对探测的应答
+    Ack,
+}
+
+/// 单个UDP数据报的最大载荷
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// UDP 数据包中发送的字段集合（见 `UdpConfig::payload_fields`）
+///
+/// 级别和消息总是发送。省略的字段编码为空值（目标为空字符串，其余为 None 或 0），线格式版本不变，
+/// 服务端照常解码，转换后的记录中对应字段为空。集合可以用 `|` 组合，如 `PayloadFields::MESSAGE | PayloadFields::APP_ID`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PayloadFields(u8);
+
+impl PayloadFields {
+    /// 消息（总是发送）
+    pub const MESSAGE: PayloadFields = PayloadFields(1);
+    /// 目标
+    pub const TARGET: PayloadFields = PayloadFields(1 << 1);
+    /// 模块路径
+    pub const MODULE: PayloadFields = PayloadFields(1 << 2);
+    /// 文件名和行号
+    pub const FILE_LINE: PayloadFields = PayloadFields(1 << 3);
+    /// 应用ID
+    pub const APP_ID: PayloadFields = PayloadFields(1 << 4);
+    /// 发送端主机信息（主机名、进程号和可执行文件名）
+    pub const HOSTNAME: PayloadFields = PayloadFields(1 << 5);
+    /// 所有字段
+    pub const ALL: PayloadFields = PayloadFields(0b11_1111);
+
+    /// 是否包含 `other` 中的所有字段
+    pub fn contains(self, other: PayloadFields) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 去掉 `other` 中的字段（消息不会被去掉）
+    pub fn without(self, other: PayloadFields) -> Self {
+        PayloadFields(self.0 & !other.0) | Self::MESSAGE
+    }
+
+    /// 把集合外的字段置为空值
+    pub fn strip(self, net_record: &mut NetRecord) {
+        if !self.contains(Self::TARGET) {
+            net_record.target.clear();
+        }
+        if !self.contains(Self::MODULE) {
+            net_record.module_path = None;
+        }
+        if !self.contains(Self::FILE_LINE) {
+            net_record.file = None;
+            net_record.line = None;
+        }
+        if !self.contains(Self::APP_ID) {
+            net_record.app_id = None;
+        }
+        if !self.contains(Self::HOSTNAME) {
+            net_record.hostname = None;
+            net_record.pid = 0;
+            net_record.binary = None;
+        }
+    }
+}
+
+impl Default for PayloadFields {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl std::ops::BitOr for PayloadFields {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        PayloadFields(self.0 | other.0)
+    }
+}
+
+/// 本进程发出的数据包序号
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
 
 /// UDP封包解包工具
 pub struct UdpPacketHelper;
@@ -10,19 +131,196 @@ pub struct UdpPacketHelper;
 impl UdpPacketHelper {
     /// 将Record编码为UDP数据包
     pub fn encode_record(record: &Record, auth_token: Option<String>, app_id: Option<String>) -> io::Result<Vec<u8>> {
+        Self::encode_record_fields(record, auth_token, app_id, PayloadFields::ALL)
+    }
+
+    /// 将Record编码为UDP数据包，只发送 `fields` 中的字段
+    pub fn encode_record_fields(
+        record: &Record,
+        auth_token: Option<String>,
+        app_id: Option<String>,
+        fields: PayloadFields,
+    ) -> io::Result<Vec<u8>> {
         let mut net_record = NetRecord::from(record);
         net_record.auth_token = auth_token;
         net_record.app_id = app_id;
+        net_record.seq = NEXT_SEQ.fetch_add(1, Ordering::Relaxed);
+        fields.strip(&mut net_record);
+
+        Self::encode_net_record(&net_record)
+    }
 
-        bincode::encode_to_vec(&net_record, bincode::config::standard())
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    /// 将NetRecord编码为带头部的UDP数据包
+    pub fn encode_net_record(net_record: &NetRecord) -> io::Result<Vec<u8>> {
+        let mut packet = Vec::with_capacity(128);
+        packet.extend_from_slice(&PACKET_MAGIC);
+        packet.push(PACKET_VERSION);
+        bincode::encode_into_std_write(net_record, &mut packet, bincode::config::standard())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(packet)
     }
 
-    /// 将UDP数据包解码为NetRecord
+    /// 读取数据包的线格式版本（无头部的数据包视为 v1）
+    pub fn packet_version(data: &[u8]) -> u8 {
+        if data.len() >= PACKET_HEADER_LEN && data[..PACKET_MAGIC.len()] == PACKET_MAGIC {
+            data[PACKET_MAGIC.len()]
+        } else {
+            1
+        }
+    }
+
+    /// 将UDP数据包解码为NetRecord（兼容 v1 数据包）
     pub fn decode_packet(data: &[u8]) -> io::Result<NetRecord> {
-        bincode::decode_from_slice(data, bincode::config::standard())
-            .map(|(record, _)| record)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        match Self::packet_version(data) {
+            1 => {
+                let reader = bincode::de::read::SliceReader::new(data);
+                let mut decoder = bincode::de::DecoderImpl::new(reader, bincode::config::standard(), ());
+                NetRecord::decode_v1(&mut decoder)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            PACKET_VERSION => {
+                bincode::decode_from_slice(&data[PACKET_HEADER_LEN..], bincode::config::standard())
+                    .map(|(record, _)| record)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            version => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("不支持的数据包版本: {}", version),
+            )),
+        }
+    }
+
+    /// 将多个数据包封装为一个批量数据报
+    ///
+    /// 帧格式：`RB` + 版本号 + 记录数(u16 LE) + 每条记录的 `长度(u16 LE) + 数据包`
+    pub fn encode_batch(packets: &[Vec<u8>]) -> io::Result<Vec<u8>> {
+        if packets.len() > u16::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "批量数据报记录数过多"));
+        }
+
+        let total = BATCH_HEADER_LEN + packets.iter().map(|p| BATCH_LEN_PREFIX + p.len()).sum::<usize>();
+        let mut datagram = Vec::with_capacity(total);
+        datagram.extend_from_slice(&BATCH_MAGIC);
+        datagram.push(BATCH_VERSION);
+        datagram.extend_from_slice(&(packets.len() as u16).to_le_bytes());
+        for packet in packets {
+            if packet.len() > u16::MAX as usize {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "数据包过大，无法放入批量数据报"));
+            }
+            datagram.extend_from_slice(&(packet.len() as u16).to_le_bytes());
+            datagram.extend_from_slice(packet);
+        }
+        Ok(datagram)
+    }
+
+    /// 编码探测数据报
+    ///
+    /// 帧格式：`RP` + 版本号 + 类型（0 探测，1 应答）+ 探测编号(u64 LE)
+    pub fn encode_probe(kind: ProbeKind, id: u64) -> Vec<u8> {
+        let mut datagram = Vec::with_capacity(PROBE_LEN);
+        datagram.extend_from_slice(&PROBE_MAGIC);
+        datagram.push(PROBE_VERSION);
+        datagram.push(match kind {
+            ProbeKind::Probe => 0,
+            ProbeKind::Ack => 1,
+        });
+        datagram.extend_from_slice(&id.to_le_bytes());
+        datagram
+    }
+
+    /// 判断数据报是否为探测数据报（探测或应答）
+    pub fn is_probe(data: &[u8]) -> bool {
+        data.len() >= PROBE_MAGIC.len() && data[..PROBE_MAGIC.len()] == PROBE_MAGIC
+    }
+
+    /// 解析探测数据报，返回类型和探测编号
+    pub fn parse_probe(data: &[u8]) -> io::Result<(ProbeKind, u64)> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        if !Self::is_probe(data) || data.len() != PROBE_LEN {
+            return Err(invalid("不是探测数据报".to_string()));
+        }
+        if data[2] != PROBE_VERSION {
+            return Err(invalid(format!("不支持的探测数据报版本: {}", data[2])));
+        }
+        let kind = match data[3] {
+            0 => ProbeKind::Probe,
+            1 => ProbeKind::Ack,
+            kind => return Err(invalid(format!("未知的探测数据报类型: {}", kind))),
+        };
+        let id = u64::from_le_bytes(data[4..PROBE_LEN].try_into().unwrap());
+        Ok((kind, id))
+    }
+
+    /// 服务端对探测的应答：数据报是探测时返回应发回发送端的应答数据报
+    pub fn probe_ack(data: &[u8]) -> Option<Vec<u8>> {
+        match Self::parse_probe(data) {
+            Ok((ProbeKind::Probe, id)) => Some(Self::encode_probe(ProbeKind::Ack, id)),
+            _ => None,
+        }
+    }
+
+    /// 判断数据报是否为批量数据报
+    pub fn is_batch(data: &[u8]) -> bool {
+        data.len() >= BATCH_HEADER_LEN && data[..BATCH_MAGIC.len()] == BATCH_MAGIC
+    }
+
+    /// 数据报包含的记录数（不解码记录本身）
+    pub fn record_count(data: &[u8]) -> usize {
+        if Self::is_probe(data) {
+            0
+        } else if Self::is_batch(data) {
+            u16::from_le_bytes([data[3], data[4]]) as usize
+        } else {
+            1
+        }
+    }
+
+    /// 将批量数据报拆分为各条数据包
+    pub fn split_batch(data: &[u8]) -> io::Result<Vec<&[u8]>> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        if !Self::is_batch(data) {
+            return Err(invalid("不是批量数据报"));
+        }
+        let version = data[BATCH_MAGIC.len()];
+        if version != BATCH_VERSION {
+            return Err(invalid(&format!("不支持的批量数据报版本: {}", version)));
+        }
+
+        let count = u16::from_le_bytes([data[3], data[4]]) as usize;
+        let mut packets = Vec::with_capacity(count);
+        let mut offset = BATCH_HEADER_LEN;
+        for _ in 0..count {
+            if offset + BATCH_LEN_PREFIX > data.len() {
+                return Err(invalid("批量数据报被截断"));
+            }
+            let len = u16::from_le_bytes([data[offset], data[offset + 1]]) as usize;
+            offset += BATCH_LEN_PREFIX;
+            if offset + len > data.len() {
+                return Err(invalid("批量数据报被截断"));
+            }
+            packets.push(&data[offset..offset + len]);
+            offset += len;
+        }
+        if offset != data.len() {
+            return Err(invalid("批量数据报存在多余数据"));
+        }
+        Ok(packets)
+    }
+
+    /// 解码一个UDP数据报（单条数据包或批量数据报）为所有NetRecord，探测数据报不含记录
+    pub fn decode_datagram(data: &[u8]) -> io::Result<Vec<NetRecord>> {
+        if Self::is_probe(data) {
+            Self::parse_probe(data).map(|_| Vec::new())
+        } else if Self::is_batch(data) {
+            Self::split_batch(data)?
+                .into_iter()
+                .map(Self::decode_packet)
+                .collect()
+        } else {
+            Self::decode_packet(data).map(|record| vec![record])
+        }
     }
 
     /// 将NetRecord转换为Record
@@ -34,12 +332,30 @@ impl UdpPacketHelper {
             app_id: net_record.app_id.clone(),
         };
 
+        // 发送端主机名、进程号和可执行文件名放入结构化字段，供服务端格式模板使用 {hostname}/{pid}/{binary}
+        let mut fields = Vec::new();
+        if let Some(hostname) = &net_record.hostname {
+            fields.push(("hostname".to_string(), hostname.clone()));
+        }
+        if net_record.pid != 0 {
+            fields.push(("pid".to_string(), net_record.pid.to_string()));
+        }
+        if let Some(binary) = &net_record.binary {
+            fields.push(("binary".to_string(), binary.clone()));
+        }
+
         Record {
             metadata: std::sync::Arc::new(metadata),
-            args: net_record.message.clone(),
+            args: net_record.message.clone().into(),
             module_path: net_record.module_path.clone(),
             file: net_record.file.clone(),
             line: net_record.line,
+            fields,
+            timestamp: Some(net_record.system_time()),
+            seq: net_record.log_seq,
+            thread: None,
+            level_label: net_record.level_label.clone(),
+            record_id: net_record.record_id,
         }
     }
 
@@ -63,33 +379,69 @@ impl UdpPacketHelper {
         Self::decode_packet(data).is_ok()
     }
 
-    /// 获取数据包的元数据（不解码整个包）
+    /// 获取数据包的元数据（当前版本数据包只解析头部字段）
     pub fn get_packet_metadata(data: &[u8]) -> Option<PacketMetadata> {
-        if let Ok(net_record) = Self::decode_packet(data) {
-            Some(PacketMetadata {
-                level: net_record.level,
-                target: net_record.target,
-                app_id: net_record.app_id,
-                timestamp: net_record.timestamp,
-                message_length: net_record.message.len(),
-            })
-        } else {
-            None
-        }
+        PacketMetadata::parse_header(data).ok()
     }
 }
 
 /// UDP数据包的元数据信息
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PacketMetadata {
     pub level: Level,
     pub target: String,
     pub app_id: Option<String>,
     pub timestamp: u64,
     pub message_length: usize,
+    /// 发送端主机名（v1 数据包为 None）
+    pub hostname: Option<String>,
+    /// 发送端进程号（v1 数据包为 0）
+    pub pid: u32,
+    /// 发送端数据包序号（v1 数据包为 0）
+    pub seq: u64,
+    /// 日志器序号（v1 数据包为 None）
+    pub log_seq: Option<u64>,
+    /// 发送端可执行文件名（v1 数据包为 None）
+    pub binary: Option<String>,
 }
 
+/// 当前版本数据包消息体之前的头部字段：级别、应用ID、目标、时间戳、亚秒纳秒、序号、日志器序号、主机名、进程号、可执行文件名、消息长度
+type HeaderFields = (Level, Option<String>, String, u64, u32, u64, Option<u64>, Option<String>, u32, Option<String>, u64);
+
 impl PacketMetadata {
+    /// 只解析数据包头部字段，不解码也不分配消息体
+    ///
+    /// v1 数据包回退为完整解码。
+    pub fn parse_header(data: &[u8]) -> io::Result<Self> {
+        if UdpPacketHelper::packet_version(data) != PACKET_VERSION {
+            return UdpPacketHelper::decode_packet(data).map(|record| Self::from(&record));
+        }
+
+        let body = &data[PACKET_HEADER_LEN..];
+        let ((level, app_id, target, timestamp, _timestamp_nanos, seq, log_seq, hostname, pid, binary, message_length), consumed): (HeaderFields, usize) =
+            bincode::decode_from_slice(body, bincode::config::standard())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        // 字符串长度前缀之后必须有完整的消息体
+        let message_length = usize::try_from(message_length)
+            .ok()
+            .filter(|len| *len <= body.len() - consumed)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "数据包消息体被截断"))?;
+
+        Ok(Self {
+            level,
+            target,
+            app_id,
+            timestamp,
+            message_length,
+            hostname,
+            pid,
+            seq,
+            log_seq,
+            binary,
+        })
+    }
+
     /// 检查数据包是否来自指定的应用
     pub fn is_from_app(&self, app_id: &str) -> bool {
         self.app_id.as_deref() == Some(app_id)
@@ -105,10 +457,34 @@ impl PacketMetadata {
     }
 }
 
+impl From<&NetRecord> for PacketMetadata {
+    fn from(net_record: &NetRecord) -> Self {
+        Self {
+            level: net_record.level,
+            target: net_record.target.clone(),
+            app_id: net_record.app_id.clone(),
+            timestamp: net_record.timestamp,
+            message_length: net_record.message.len(),
+            hostname: net_record.hostname.clone(),
+            pid: net_record.pid,
+            seq: net_record.seq,
+            log_seq: net_record.log_seq,
+            binary: net_record.binary.clone(),
+        }
+    }
+}
+
 /// UDP数据包批处理器
+///
+/// 发送端用于累积编码后的数据包，在达到记录数、字节上限或最长等待时间时封装为一个批量数据报；
+/// 接收端用于解码数据报（自动展开批量数据报）。
 pub struct UdpBatchProcessor {
     batch_size: usize,
     max_wait_time_ms: u64,
+    max_batch_bytes: usize,
+    pending: Vec<Vec<u8>>,
+    pending_bytes: usize,
+    oldest: Option<Instant>,
 }
 
 impl UdpBatchProcessor {
@@ -117,16 +493,85 @@ impl UdpBatchProcessor {
         Self {
             batch_size,
             max_wait_time_ms,
+            max_batch_bytes: MAX_DATAGRAM_SIZE,
+            pending: Vec::new(),
+            pending_bytes: BATCH_HEADER_LEN,
+            oldest: None,
         }
     }
 
-    /// 处理一批UDP数据包
+    /// 设置单个批量数据报的字节上限
+    pub fn with_max_batch_bytes(mut self, max_batch_bytes: usize) -> Self {
+        self.max_batch_bytes = max_batch_bytes.min(MAX_DATAGRAM_SIZE);
+        self
+    }
+
+    /// 加入一个已编码的数据包，返回因达到上限而需要立即发送的数据报
+    pub fn push(&mut self, packet: Vec<u8>) -> Vec<Vec<u8>> {
+        let mut ready = Vec::new();
+        let framed_len = BATCH_LEN_PREFIX + packet.len();
+
+        // 加入后超过字节上限：先发出已累积的部分
+        if !self.pending.is_empty() && self.pending_bytes + framed_len > self.max_batch_bytes {
+            ready.extend(self.take_datagram());
+        }
+
+        if self.pending.is_empty() {
+            self.oldest = Some(Instant::now());
+        }
+        self.pending_bytes += framed_len;
+        self.pending.push(packet);
+
+        if self.pending.len() >= self.batch_size || self.pending_bytes >= self.max_batch_bytes {
+            ready.extend(self.take_datagram());
+        }
+        ready
+    }
+
+    /// 待发送的数据包数量
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 最早一条待发送数据包的发送截止时间
+    pub fn deadline(&self) -> Option<Instant> {
+        self.oldest.map(|t| t + Duration::from_millis(self.max_wait_time_ms))
+    }
+
+    /// 是否已达到最长等待时间
+    pub fn is_due(&self) -> bool {
+        self.deadline().is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// 取出所有待发送的数据包组成一个数据报，单条数据包不加批量帧直接发送
+    pub fn take_datagram(&mut self) -> Option<Vec<u8>> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let pending = std::mem::take(&mut self.pending);
+        self.pending_bytes = BATCH_HEADER_LEN;
+        self.oldest = None;
+
+        if pending.len() == 1 {
+            return pending.into_iter().next();
+        }
+        match UdpPacketHelper::encode_batch(&pending) {
+            Ok(datagram) => Some(datagram),
+            Err(e) => {
+                eprintln!("[udp] 批量数据报封装失败，丢弃{}条记录: {}", pending.len(), e);
+                None
+            }
+        }
+    }
+
+    /// 处理一批UDP数据报（批量数据报会展开为其中的所有记录）
     pub fn process_batch(&self, packets: &[Vec<u8>]) -> Vec<Record> {
         let mut records = Vec::new();
 
         for packet in packets {
-            if let Ok(record) = UdpPacketHelper::create_decoder()(packet) {
-                records.push(record);
+            if let Ok(net_records) = UdpPacketHelper::decode_datagram(packet) {
+                records.extend(net_records.iter().map(UdpPacketHelper::net_record_to_record));
             }
         }
 
@@ -138,10 +583,8 @@ impl UdpBatchProcessor {
         let mut filtered = Vec::new();
 
         for packet in packets {
-            if let Some(metadata) = UdpPacketHelper::get_packet_metadata(packet) {
-                if filter(&metadata) {
-                    filtered.push(packet.clone());
-                }
+            if let Ok(metadata) = PacketMetadata::parse_header(packet) && filter(&metadata) {
+                filtered.push(packet.clone());
             }
         }
 
@@ -169,10 +612,11 @@ mod tests {
                 auth_token: None,
                 app_id: None,
             }),
-            args: "test message".to_string(),
+            args: "test message".into(),
             module_path: Some("test::module".to_string()),
             file: Some("test.rs".to_string()),
             line: Some(42),
+            ..Default::default()
         };
 
         let encoded = UdpPacketHelper::encode_record(&record, Some("token".to_string()), Some("app".to_string())).unwrap();
@@ -194,10 +638,11 @@ mod tests {
                 auth_token: None,
                 app_id: Some("my_app".to_string()),
             }),
-            args: "error message".to_string(),
+            args: "error message".into(),
             module_path: None,
             file: None,
             line: None,
+            ..Default::default()
         };
 
         let encoded = UdpPacketHelper::encode_record(&record, None, Some("my_app".to_string())).unwrap();
@@ -225,10 +670,11 @@ mod tests {
                 auth_token: None,
                 app_id: None,
             }),
-            args: "debug message".to_string(),
+            args: "debug message".into(),
             module_path: None,
             file: None,
             line: None,
+            ..Default::default()
         };
 
         let encoded = UdpPacketHelper::encode_record(&debug_record, None, None).unwrap();
@@ -240,4 +686,231 @@ mod tests {
         assert!(metadata.level.should_log_at_level(Level::Debug));  // Debug日志应该在Debug级别下发送
         assert!(metadata.level.should_log_at_level(Level::Trace));  // Debug日志应该在Trace级别下发送
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_hostname_and_pid_roundtrip() {
+        let record = Record {
+            metadata: std::sync::Arc::new(Metadata {
+                level: Level::Warn,
+                target: "host_test".to_string(),
+                auth_token: None,
+                app_id: None,
+            }),
+            args: "from some instance".into(),
+            ..Default::default()
+        };
+
+        let encoded = UdpPacketHelper::encode_record(&record, None, Some("app".to_string())).unwrap();
+        assert_eq!(UdpPacketHelper::packet_version(&encoded), PACKET_VERSION);
+
+        let decoded = UdpPacketHelper::decode_packet(&encoded).unwrap();
+        assert_eq!(decoded.pid, std::process::id());
+        assert_eq!(decoded.hostname.as_deref(), crate::process_info::hostname());
+
+        let metadata = UdpPacketHelper::get_packet_metadata(&encoded).unwrap();
+        assert_eq!(metadata.pid, std::process::id());
+        assert_eq!(metadata.hostname, decoded.hostname);
+
+        // 服务端格式化输出可以渲染 {hostname} 和 {pid}
+        let mut net_record = decoded;
+        net_record.hostname = Some("edge-07".to_string());
+        let restored = UdpPacketHelper::net_record_to_record(&net_record);
+        let format_config = crate::config::FormatConfig {
+            format_template: "{hostname}/{pid} {message}".to_string(),
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        crate::handler::term::format_with_config(&mut buf, &restored, &format_config).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), format!("edge-07/{} from some instance\n", std::process::id()));
+    }
+
+    #[test]
+    fn test_decode_v1_packet() {
+        // v1 数据包：无头部，没有主机名和进程号
+        let v1 = bincode::encode_to_vec(
+            ("INFO".to_string(), "legacy".to_string(), "old message".to_string(), None::<String>,
+             None::<String>, Some(7u32), 1_700_000_000u64, None::<String>, Some("old_app".to_string())),
+            bincode::config::standard(),
+        ).unwrap();
+
+        assert_eq!(UdpPacketHelper::packet_version(&v1), 1);
+        let decoded = UdpPacketHelper::decode_packet(&v1).unwrap();
+        assert_eq!(decoded.message, "old message");
+        assert_eq!(decoded.app_id.as_deref(), Some("old_app"));
+        assert_eq!(decoded.hostname, None);
+        assert_eq!(decoded.pid, 0);
+        assert!(UdpPacketHelper::net_record_to_record(&decoded).fields.is_empty());
+    }
+
+    #[test]
+    fn test_probe_framing() {
+        let probe = UdpPacketHelper::encode_probe(ProbeKind::Probe, 42);
+        assert_eq!(probe.len(), PROBE_LEN);
+        assert_eq!(UdpPacketHelper::parse_probe(&probe).unwrap(), (ProbeKind::Probe, 42));
+        assert_eq!(UdpPacketHelper::record_count(&probe), 0);
+        // 探测数据报不含记录，不会被当作无效数据报
+        assert!(UdpPacketHelper::decode_datagram(&probe).unwrap().is_empty());
+
+        let ack = UdpPacketHelper::probe_ack(&probe).unwrap();
+        assert_eq!(UdpPacketHelper::parse_probe(&ack).unwrap(), (ProbeKind::Ack, 42));
+        // 应答不再被应答，普通数据包不是探测
+        assert!(UdpPacketHelper::probe_ack(&ack).is_none());
+        assert!(!UdpPacketHelper::is_probe(&UdpPacketHelper::encode_batch(&[]).unwrap()));
+        assert!(UdpPacketHelper::parse_probe(&probe[..PROBE_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn test_batch_framing() {
+        let packets: Vec<Vec<u8>> = (0..3)
+            .map(|i| {
+                let record = Record { args: format!("framed #{}", i).into(), ..Default::default() };
+                UdpPacketHelper::encode_record(&record, None, None).unwrap()
+            })
+            .collect();
+
+        let datagram = UdpPacketHelper::encode_batch(&packets).unwrap();
+        assert!(UdpPacketHelper::is_batch(&datagram));
+        assert!(!UdpPacketHelper::is_batch(&packets[0]));
+
+        let records = UdpPacketHelper::decode_datagram(&datagram).unwrap();
+        let messages: Vec<_> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, ["framed #0", "framed #1", "framed #2"]);
+
+        // 截断的批量数据报应当报错而不是返回部分记录
+        assert!(UdpPacketHelper::split_batch(&datagram[..datagram.len() - 1]).is_err());
+
+        // 批处理器按记录数封帧，单条剩余记录不加批量帧
+        let mut batcher = UdpBatchProcessor::new(2, 1000);
+        assert!(batcher.push(packets[0].clone()).is_empty());
+        let ready = batcher.push(packets[1].clone());
+        assert_eq!(ready.len(), 1);
+        assert!(UdpPacketHelper::is_batch(&ready[0]));
+        assert!(batcher.push(packets[2].clone()).is_empty());
+        assert!(batcher.deadline().is_some());
+        assert_eq!(batcher.take_datagram().as_deref(), Some(packets[2].as_slice()));
+        assert!(batcher.deadline().is_none());
+    }
+
+    #[test]
+    fn test_parse_header_matches_full_decode() {
+        let levels = Level::ALL;
+        let messages = ["", "short", "消息体包含多字节字符", &"x".repeat(4096)];
+
+        let mut corpus = Vec::new();
+        for (i, level) in levels.iter().enumerate() {
+            for message in messages {
+                let record = Record {
+                    metadata: std::sync::Arc::new(Metadata {
+                        level: *level,
+                        target: format!("corpus::target{}", i),
+                        auth_token: None,
+                        app_id: None,
+                    }),
+                    args: message.into(),
+                    line: Some(i as u32),
+                    ..Default::default()
+                };
+                let app_id = (i % 2 == 0).then(|| format!("app{}", i));
+                corpus.push(UdpPacketHelper::encode_record(&record, Some("token".to_string()), app_id).unwrap());
+            }
+        }
+
+        for packet in &corpus {
+            let header = PacketMetadata::parse_header(packet).unwrap();
+            let full = PacketMetadata::from(&UdpPacketHelper::decode_packet(packet).unwrap());
+            assert_eq!(header, full);
+            assert_ne!(header.seq, 0);
+        }
+
+        // 消息体被截断时只解析头部也能发现
+        let truncated = &corpus[3][..corpus[3].len() - 2048];
+        assert!(PacketMetadata::parse_header(truncated).is_err());
+    }
+
+    #[test]
+    fn test_level_wire_roundtrip_all_levels() {
+        let levels = [
+            (Level::Error, "ERROR"),
+            (Level::Warn, "WARN"),
+            (Level::Info, "INFO"),
+            (Level::Debug, "DEBUG"),
+            (Level::Trace, "TRACE"),
+        ];
+
+        for (level, legacy) in levels {
+            // 编码值与 Display 无关且可逆
+            assert_eq!(Level::from_wire_value(level.wire_value()), Some(level));
+
+            let record = Record {
+                metadata: std::sync::Arc::new(Metadata {
+                    level,
+                    target: "wire".to_string(),
+                    auth_token: None,
+                    app_id: None,
+                }),
+                args: "level wire format".into(),
+                ..Default::default()
+            };
+
+            // 新格式
+            let packet = UdpPacketHelper::encode_record(&record, None, Some("app".to_string())).unwrap();
+            let decoded = UdpPacketHelper::decode_packet(&packet).unwrap();
+            assert_eq!(decoded.level, level);
+            assert_eq!(PacketMetadata::parse_header(&packet).unwrap().level, level);
+
+            // v1 数据包中的字符串级别
+            let v1 = bincode::encode_to_vec(
+                (legacy.to_string(), "wire".to_string(), "level wire format".to_string(), None::<String>,
+                 None::<String>, None::<u32>, decoded.timestamp, None::<String>, None::<String>),
+                bincode::config::standard(),
+            ).unwrap();
+            assert_eq!(UdpPacketHelper::decode_packet(&v1).unwrap().level, level);
+            assert_eq!(PacketMetadata::parse_header(&v1).unwrap().level, level);
+        }
+
+        // 未知编码值解码失败
+        assert_eq!(Level::from_wire_value(0), None);
+        assert_eq!(Level::from_wire_value(6), None);
+    }
+
+    #[test]
+    fn test_level_label_roundtrip_and_unknown_version() {
+        let record = Record {
+            args: "labelled".into(),
+            level_label: Some("AUDIT".to_string()),
+            ..Default::default()
+        };
+        let packet = UdpPacketHelper::encode_record(&record, None, Some("app".to_string())).unwrap();
+        let decoded = UdpPacketHelper::decode_packet(&packet).unwrap();
+        assert_eq!(decoded.level_label.as_deref(), Some("AUDIT"));
+        assert_eq!(PacketMetadata::parse_header(&packet).unwrap(), PacketMetadata::from(&decoded));
+
+        // 未知版本号的数据包解码失败，不按其他版本猜测
+        let mut unknown = packet.clone();
+        unknown[PACKET_MAGIC.len()] = PACKET_VERSION + 1;
+        let err = UdpPacketHelper::decode_packet(&unknown).unwrap_err();
+        assert!(err.to_string().contains("不支持的数据包版本"), "{}", err);
+    }
+
+    #[test]
+    fn test_timestamp_precision_and_log_seq_roundtrip() {
+        let timestamp = std::time::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        let record = Record {
+            args: "precise".into(),
+            timestamp: Some(timestamp),
+            seq: Some(42),
+            ..Default::default()
+        };
+
+        let packet = UdpPacketHelper::encode_record(&record, None, None).unwrap();
+        let decoded = UdpPacketHelper::decode_packet(&packet).unwrap();
+        assert_eq!(decoded.timestamp, 1_700_000_000);
+        assert_eq!(decoded.timestamp_nanos, 123_456_789);
+        assert_eq!(decoded.log_seq, Some(42));
+        assert_eq!(PacketMetadata::parse_header(&packet).unwrap().log_seq, Some(42));
+
+        let restored = UdpPacketHelper::net_record_to_record(&decoded);
+        assert_eq!(restored.timestamp, Some(timestamp));
+        assert_eq!(restored.seq, Some(42));
+    }
+}
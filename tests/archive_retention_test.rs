@@ -0,0 +1,70 @@
+//! 归档保留期限测试：轮转后超过期限的压缩归档被删除并上报，未过期的归档和活动文件保留
+
+mod common;
+
+use rat_logger::config::{DurationMs, Record};
+use rat_logger::{DiagnosticEvent, FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+fn archive(dir: &Path, name: &str, age: Duration) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, b"archived").unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() - age).unwrap();
+    path
+}
+
+fn record(i: usize) -> Record {
+    common::record(Level::Info, "archive_retention", format!("record {:03} {}", i, "x".repeat(200)))
+}
+
+#[test]
+fn test_rotation_removes_expired_archives() {
+    let log_dir = common::test_dir("archive_retention", "rotation_removes");
+    std::fs::create_dir_all(&log_dir).unwrap();
+
+    let day = Duration::from_secs(24 * 60 * 60);
+    let expired = [
+        archive(&log_dir, "app_20000101_000000.log.lz4", 40 * day),
+        archive(&log_dir, "app_20000102_000000.log.lz4", 31 * day),
+    ];
+    let kept = archive(&log_dir, "app_20000103_000000.log.lz4", 29 * day);
+
+    let events: Arc<Mutex<Vec<usize>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            max_file_size: 1024,
+            ..Default::default()
+        }.with_max_archive_age(DurationMs::days(30)))
+        .with_error_callback(move |event| {
+            if let DiagnosticEvent::ArchivesExpired { count, .. } = event {
+                sink.lock().push(*count);
+            }
+        })
+        .build();
+
+    // 超过 max_file_size 触发一次轮转
+    for i in 0..8 {
+        logger.log(&record(i));
+    }
+    logger.flush_sync(5000).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while logger.stats().pending_compressions() > 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(expired.iter().all(|path| !path.exists()));
+    assert!(kept.exists());
+    assert_eq!(logger.stats().archives_expired(), 2);
+    assert_eq!(events.lock().iter().sum::<usize>(), 2);
+    assert!(std::fs::read_dir(&log_dir).unwrap().flatten().any(|entry| entry.path().extension().is_some_and(|ext| ext == "log")));
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,103 @@
+//! 整块写入批次测试：子进程在持续写入时被强制终止，离线读取工具只会缺少末尾的完整记录，不会遇到半行
+#![cfg(unix)]
+
+mod common;
+
+use rat_logger::tools::{reformat, InputKind};
+use rat_logger::{ByteSize, FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// 子进程通过该环境变量获得日志目录
+const LOG_DIR_ENV: &str = "RAT_LOGGER_ATOMIC_BATCH_DIR";
+
+fn log_files(log_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(log_dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "log")).collect())
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// 子进程：不停写入长度不一的记录，直到被终止
+#[test]
+#[ignore = "由 test_killed_writer_leaves_only_whole_records 在子进程中运行"]
+fn atomic_batch_child() {
+    let log_dir = std::env::var(LOG_DIR_ENV).expect("只在子进程中运行");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.into(),
+            max_compressed_files: 0,
+            compression_level: 0,
+            ..Default::default()
+        }.with_atomic_batches(ByteSize::kib(4)))
+        .build();
+
+    for i in 0.. {
+        logger.log(&common::record(Level::Info, "atomic_batch", format!("seq {} {}", i, "x".repeat(i % 700))));
+    }
+}
+
+#[test]
+fn test_killed_writer_leaves_only_whole_records() {
+    let log_dir = common::test_dir("atomic_batch", "killed_writer");
+
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["atomic_batch_child", "--exact", "--ignored", "--nocapture"])
+        .env(LOG_DIR_ENV, &log_dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // 文件写入足够多的数据后强制终止（SIGKILL），不给日志器刷新的机会
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while log_files(&log_dir).iter().map(|p| std::fs::metadata(p).map_or(0, |m| m.len())).sum::<u64>() < 2 * 1024 * 1024 {
+        assert!(Instant::now() < deadline, "子进程没有写入日志");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    let files = log_files(&log_dir);
+    assert!(!files.is_empty());
+    let format = FormatConfig { format_template: "{message}".to_string(), ..Default::default() };
+    let mut expected = 0;
+    for path in &files {
+        let mut output = Vec::new();
+        let summary = reformat(path, InputKind::Text, &format, &mut output).unwrap();
+        assert_eq!(summary.unparsed, 0, "{} 中有半条记录", path.display());
+
+        // 记录按顺序完整保留，只在末尾缺少
+        for line in String::from_utf8(output).unwrap().lines() {
+            let mut parts = line.splitn(3, ' ');
+            assert_eq!(parts.next(), Some("seq"));
+            let seq: usize = parts.next().unwrap().parse().unwrap();
+            assert_eq!(seq, expected);
+            assert_eq!(parts.next().unwrap_or("").len(), seq % 700, "第 {} 条记录不完整", seq);
+            expected += 1;
+        }
+    }
+    assert!(expected > 0);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_atomic_batches_config() {
+    let config: FileConfig = serde_json::from_str(r#"{
+        "log_dir": "./logs", "max_file_size": "10MiB", "max_compressed_files": 0, "compression_level": 0,
+        "min_compress_threads": 0, "skip_server_logs": false, "is_raw": false, "compress_on_drop": false,
+        "force_sync": false, "format": null, "check_path_interval_ms": 0, "max_total_size": 0,
+        "accept_emergency": true, "write_header": false, "line_ending": "Lf", "order_by_timestamp": false,
+        "max_pending_compressions": 0, "compression_overflow": "Skip", "shared_dir_policy": "Error",
+        "atomic_batches": true, "atomic_chunk_size": "16KiB"
+    }"#).unwrap();
+    assert!(config.atomic_batches);
+    assert_eq!(config.atomic_chunk_size, 16 * 1024);
+    assert_eq!(FileConfig::default().atomic_chunk_size, 64 * 1024);
+
+    let zero = FileConfig { atomic_batches: true, atomic_chunk_size: 0, ..Default::default() };
+    assert!(zero.validate().unwrap_err().contains("atomic_chunk_size"));
+}
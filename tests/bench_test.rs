@@ -0,0 +1,77 @@
+//! 负载生成器测试：小规模负载驱动捕获日志器，检查报告字段
+
+use rat_logger::bench::{LoadGenerator, LoadProfile};
+use rat_logger::{Level, LevelFilter, Logger, LoggerBuilder, SamplingConfig};
+use rat_logger::config::Record;
+use std::sync::{Arc, Mutex};
+
+/// 只在内存中记录级别和消息长度的日志器
+#[derive(Default)]
+struct CaptureLogger {
+    records: Mutex<Vec<(Level, usize)>>,
+}
+
+impl Logger for CaptureLogger {
+    fn log(&self, record: &Record) {
+        self.records.lock().unwrap().push((record.metadata.level, record.args.len()));
+    }
+    fn flush(&self) {}
+    fn set_level(&self, _level: LevelFilter) {}
+    fn level(&self) -> LevelFilter {
+        LevelFilter::Trace
+    }
+    fn force_flush(&self) {}
+    fn emergency_log(&self, record: &Record) {
+        self.log(record);
+    }
+}
+
+#[test]
+fn test_load_report_against_capture_logger() {
+    let logger = Arc::new(CaptureLogger::default());
+    let report = LoadGenerator::new(logger.clone(), LoadProfile {
+        threads: 3,
+        records: 1000,
+        message_size: 64,
+        level_mix: vec![(Level::Info, 3), (Level::Error, 1)],
+    })
+    .run();
+
+    assert_eq!(report.records, 1000);
+    assert_eq!(report.dropped, 0);
+    assert!(report.duration > std::time::Duration::ZERO);
+    assert!(report.throughput > 0.0);
+    assert!(report.p50_latency <= report.p95_latency);
+
+    let records = logger.records.lock().unwrap();
+    assert_eq!(records.len(), 1000);
+    assert!(records.iter().all(|(_, len)| *len == 64));
+    assert_eq!(records.iter().filter(|(level, _)| *level == Level::Error).count(), 250);
+}
+
+#[test]
+fn test_load_report_counts_sampled_out_records() {
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        .with_sampling(SamplingConfig {
+            default_ratio: 0.0,
+            per_target: Vec::new(),
+            levels: LevelFilter::Debug,
+            annotate: false,
+            seed: Some(3),
+        })
+        // 所有记录都会被采样丢弃，终端不会有输出
+        .add_terminal_with_config(rat_logger::handler::term::TermConfig::default())
+        .build();
+
+    let report = LoadGenerator::new(Arc::new(logger), LoadProfile {
+        threads: 2,
+        records: 200,
+        message_size: 16,
+        level_mix: vec![(Level::Debug, 1)],
+    })
+    .run();
+
+    assert_eq!(report.records, 200);
+    assert_eq!(report.dropped, 200, "全部 Debug 记录都应被采样丢弃");
+}
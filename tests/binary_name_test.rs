@@ -0,0 +1,70 @@
+//! 可执行文件名测试：`{binary}` 占位符与未配置应用ID时的 UDP 默认应用ID
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, FormatConfig};
+use rat_logger::test_util::UdpCollector;
+use std::time::Duration;
+
+fn current_stem() -> String {
+    std::env::current_exe().unwrap().file_stem().unwrap().to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_binary_placeholder_renders_executable_stem() {
+    let log_dir = common::test_dir("binary", "binary_placeholder");
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            format: Some(FormatConfig {
+                format_template: "{binary}|{message}".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .build();
+
+    logger.log(&common::record(Level::Info, "binary_name", "hello"));
+    logger.flush_sync(2000).unwrap();
+
+    let mut content = String::new();
+    for entry in std::fs::read_dir(&log_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "log") {
+            content.push_str(&std::fs::read_to_string(&path).unwrap());
+        }
+    }
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    assert_eq!(content, format!("{}|hello\n", current_stem()));
+    assert_eq!(logger.app_id(), Some(current_stem().as_str()), "未设置应用ID时默认为可执行文件名");
+}
+
+#[test]
+fn test_udp_uses_binary_as_default_app_id() {
+    let collector = UdpCollector::bind().unwrap();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp(collector.network_config())
+        .build();
+
+    logger.log(&common::record(Level::Info, "binary_name", "over udp"));
+
+    assert!(collector.wait_for(1, Duration::from_secs(5)));
+    let records = collector.records();
+    assert_eq!(records[0].message, "over udp");
+    assert_eq!(records[0].app_id.as_deref(), Some(current_stem().as_str()));
+    assert_eq!(records[0].binary.as_deref(), Some(current_stem().as_str()));
+}
+
+#[test]
+fn test_with_app_id_overrides_binary_default() {
+    let logger = LoggerBuilder::new()
+        .with_app_id("billing")
+        .add_terminal_with_config(rat_logger::handler::term::TermConfig::default())
+        .build();
+    assert_eq!(logger.app_id(), Some("billing"));
+}
@@ -0,0 +1,81 @@
+//! 标准输出管道断开测试：读取端关闭后终端输出被静默丢弃，进程继续运行，文件日志不受影响
+#![cfg(unix)]
+
+mod common;
+
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{DiagnosticEvent, FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 子进程通过该环境变量获得日志目录
+const LOG_DIR_ENV: &str = "RAT_LOGGER_BROKEN_PIPE_DIR";
+
+/// 子进程：持续写终端和文件，结束时以退出码报告是否只收到一次关闭事件且有记录被丢弃
+#[test]
+#[ignore = "由 test_logging_continues_after_stdout_closed 在子进程中运行"]
+fn broken_pipe_child() {
+    let log_dir = std::env::var(LOG_DIR_ENV).expect("只在子进程中运行");
+    let closed = Arc::new(AtomicUsize::new(0));
+    let closed_events = closed.clone();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            ..Default::default()
+        })
+        .add_file(FileConfig {
+            log_dir: log_dir.into(),
+            ..Default::default()
+        })
+        .with_error_callback(move |event| {
+            if matches!(event, DiagnosticEvent::OutputClosed { processor: "term_processor" }) {
+                closed_events.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .build();
+
+    for i in 0..200 {
+        logger.log(&common::record(Level::Info, "broken_pipe", format!("tick {}", i)));
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    logger.log(&common::record(Level::Info, "broken_pipe", "finished"));
+    logger.flush_sync(2000).unwrap();
+
+    let ok = closed.load(Ordering::SeqCst) == 1 && logger.stats().dropped() > 0;
+    // 直接退出，避免测试框架向已关闭的标准输出写入结果
+    std::process::exit(if ok { 0 } else { 1 });
+}
+
+#[test]
+fn test_logging_continues_after_stdout_closed() {
+    let log_dir = common::test_dir("broken_pipe", "logging_continues");
+
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["broken_pipe_child", "--exact", "--ignored", "--nocapture"])
+        .env(LOG_DIR_ENV, &log_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // 读到第一条终端输出后关闭读取端，模拟 `| head -n 1`
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    while stdout.read_line(&mut line).unwrap() > 0 && !line.contains("tick") {
+        line.clear();
+    }
+    assert!(line.contains("tick"), "子进程应当先输出到终端");
+    drop(stdout);
+
+    let status = child.wait().unwrap();
+    let content = common::read_logs(&log_dir);
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    assert!(status.success(), "子进程应当正常结束，且终端处理器只上报一次关闭: {:?}", status);
+    assert!(content.contains("tick 199") && content.contains("finished"), "文件日志应当完整");
+}
@@ -0,0 +1,92 @@
+//! 构建器级默认格式测试
+//!
+//! 终端输出写入标准输出，测试以子进程模式重新启动自身并捕获子进程的标准输出。
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, FileConfig, FormatConfig, Logger};
+use rat_logger::handler::term::TermConfig;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const CHILD_ENV: &str = "RAT_LOGGER_FORMAT_CHILD_DIR";
+
+fn format(template: &str) -> FormatConfig {
+    FormatConfig {
+        format_template: template.to_string(),
+        ..Default::default()
+    }
+}
+
+fn run_child(log_dir: &Path, override_file: bool) {
+    let file_format = override_file.then(|| format("FILE|{message}"));
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_format(format("SHARED|{level}|{message}"))
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            ..Default::default()
+        })
+        .add_file(FileConfig {
+            log_dir: log_dir.to_path_buf(),
+            format: file_format,
+            ..Default::default()
+        })
+        .build();
+
+    logger.log(&common::record(Level::Info, "format_test", "hello"));
+    logger.flush_sync(2000).unwrap();
+}
+
+/// 运行子进程，返回（终端输出中的日志行，文件中的日志行）
+fn run(test_name: &str) -> (Vec<String>, Vec<String>) {
+    let log_dir: PathBuf = std::env::temp_dir().join(format!("rat_logger_{}_{}", test_name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", test_name, "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, &log_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "子进程失败: {}", String::from_utf8_lossy(&output.stderr));
+
+    // 测试框架的 "test xxx ... " 前缀可能与日志位于同一行
+    let term_lines = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| l.find("SHARED|").map(|i| l[i..].to_string()))
+        .collect();
+
+    let mut file_lines = Vec::new();
+    for entry in std::fs::read_dir(&log_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "log") {
+            file_lines.extend(std::fs::read_to_string(&path).unwrap().lines().map(|l| l.to_string()));
+        }
+    }
+    let _ = std::fs::remove_dir_all(&log_dir);
+    (term_lines, file_lines)
+}
+
+#[test]
+fn test_builder_format_applies_to_all_handlers() {
+    if let Ok(dir) = std::env::var(CHILD_ENV) {
+        run_child(Path::new(&dir), false);
+        return;
+    }
+
+    let (term, file) = run("test_builder_format_applies_to_all_handlers");
+    assert_eq!(term, vec!["SHARED|INFO|hello".to_string()]);
+    assert_eq!(file, vec!["SHARED|INFO|hello".to_string()]);
+}
+
+#[test]
+fn test_handler_format_overrides_builder_format() {
+    if let Ok(dir) = std::env::var(CHILD_ENV) {
+        run_child(Path::new(&dir), true);
+        return;
+    }
+
+    let (term, file) = run("test_handler_format_overrides_builder_format");
+    assert_eq!(term, vec!["SHARED|INFO|hello".to_string()]);
+    assert_eq!(file, vec!["FILE|hello".to_string()]);
+}
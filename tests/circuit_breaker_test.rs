@@ -0,0 +1,99 @@
+//! 全局熔断测试：突发记录超过上限后熔断，Error 级别仍然通过，速率回落并经过冷却时间后恢复
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{BreakerAction, BreakerConfig, DiagnosticEvent, DurationMs, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+type Capture = Arc<Mutex<Vec<String>>>;
+
+fn breaker_logger(action: BreakerAction) -> (rat_logger::core::LoggerCore, Capture, Arc<Mutex<Vec<DiagnosticEvent>>>) {
+    let captured: Capture = Arc::new(Mutex::new(Vec::new()));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (sink, event_sink) = (captured.clone(), events.clone());
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.args.to_string()))),
+            ..Default::default()
+        })
+        .with_circuit_breaker(BreakerConfig::new(100, action).with_recovery(50, DurationMs::ms(200)))
+        .with_error_callback(move |event| {
+            if matches!(event, DiagnosticEvent::BreakerOpened { .. } | DiagnosticEvent::BreakerClosed { .. }) {
+                event_sink.lock().push(event.clone());
+            }
+        })
+        .build();
+    (logger, captured, events)
+}
+
+/// 突发 1000 条记录触发熔断，然后以约 10 条/秒的速率继续记录直到恢复
+fn burst_then_recover(logger: &rat_logger::core::LoggerCore) {
+    for _ in 0..1000 {
+        logger.log(&common::record(Level::Info, "breaker", "burst"));
+    }
+    assert!(logger.stats().breaker_open());
+    assert_eq!(logger.stats().breaker_trips(), 1);
+    logger.log(&common::record(Level::Error, "breaker", "error while open"));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().breaker_open() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(100));
+        logger.log(&common::record(Level::Info, "breaker", "trickle"));
+    }
+    assert!(!logger.stats().breaker_open());
+    logger.log(&common::record(Level::Info, "breaker", "after recovery"));
+    logger.flush_sync(2000).unwrap();
+}
+
+#[test]
+fn test_breaker_opens_keeps_errors_and_recovers() {
+    let (logger, captured, events) = breaker_logger(BreakerAction::DropWithSummary);
+    burst_then_recover(&logger);
+
+    let captured = captured.lock();
+    let count = |message: &str| captured.iter().filter(|m| m.as_str() == message).count();
+    assert_eq!(count("burst"), 100);
+    assert_eq!(count("error while open"), 1);
+    assert_eq!(captured.last().map(String::as_str), Some("after recovery"));
+    // 熔断期间每个窗口一条汇总，汇总的丢弃数合计为全部丢弃数
+    let dropped: Vec<u64> = captured.iter()
+        .filter_map(|m| m.strip_prefix("熔断中：上一秒丢弃 "))
+        .map(|rest| rest.split_once(' ').unwrap().0.parse().unwrap())
+        .collect();
+    assert!(dropped[0] >= 900, "{:?}", dropped);
+    assert_eq!(dropped.iter().sum::<u64>(), logger.stats().breaker_dropped());
+
+    let events = events.lock();
+    assert!(matches!(events[0], DiagnosticEvent::BreakerOpened { rate: 101, limit: 100 }));
+    match &events[1] {
+        DiagnosticEvent::BreakerClosed { dropped, .. } => assert_eq!(*dropped, logger.stats().breaker_dropped()),
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn test_degrade_to_error_only_emits_no_summary() {
+    let (logger, captured, events) = breaker_logger(BreakerAction::DegradeToErrorOnly);
+    burst_then_recover(&logger);
+
+    let captured = captured.lock();
+    assert!(captured.iter().all(|m| !m.starts_with("熔断中")));
+    assert!(captured.iter().any(|m| m == "error while open"));
+    assert_eq!(captured.last().map(String::as_str), Some("after recovery"));
+    assert_eq!(events.lock().len(), 2);
+}
+
+#[test]
+fn test_invalid_breaker_config_rejected() {
+    let builder = || LoggerBuilder::new().add_terminal_with_config(TermConfig::default());
+    assert!(builder().with_max_record_rate(0, BreakerAction::DropWithSummary).try_build().is_err());
+    let config = BreakerConfig::new(100, BreakerAction::DropWithSummary).with_recovery(200, DurationMs::secs(1));
+    assert!(builder().with_circuit_breaker(config).try_build().is_err());
+}
@@ -0,0 +1,90 @@
+//! 时钟测试：使用固定时钟和步进时钟时，格式化输出和 UDP 数据包的时间戳是确定的
+
+mod common;
+
+use rat_logger::clock::{FixedClock, StepClock};
+use rat_logger::test_util::UdpCollector;
+use rat_logger::{FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// 2026-01-02 03:04:05.678 UTC
+const FIXED_MILLIS: u64 = 1_767_323_045_678;
+
+/// 使用给定时钟写入两条日志并返回日志文件内容
+fn render(log_dir: &Path, clock: Arc<dyn rat_logger::Clock>) -> String {
+    let _ = std::fs::remove_dir_all(log_dir);
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_clock(clock)
+        .add_file(FileConfig {
+            log_dir: log_dir.to_path_buf(),
+            format: Some(FormatConfig {
+                timestamp_format: "%Y-%m-%d %H:%M:%S%.3f".to_string(),
+                format_template: "{timestamp} [{level}] {target} - {message}".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .build();
+
+    logger.log(&common::record(Level::Info, "clock", "first"));
+    logger.log(&common::record(Level::Warn, "clock", "second"));
+    logger.flush_sync(2000).unwrap();
+    drop(logger);
+
+    let mut content = String::new();
+    for entry in std::fs::read_dir(log_dir).unwrap() {
+        content.push_str(&std::fs::read_to_string(entry.unwrap().path()).unwrap());
+    }
+    let _ = std::fs::remove_dir_all(log_dir);
+    content
+}
+
+fn local_time(millis: u64) -> String {
+    chrono::DateTime::<chrono::Local>::from(UNIX_EPOCH + Duration::from_millis(millis))
+        .format("%Y-%m-%d %H:%M:%S%.3f")
+        .to_string()
+}
+
+#[test]
+fn test_fixed_clock_snapshot_is_byte_identical() {
+    let base = std::env::temp_dir().join(format!("rat_logger_clock_{}", std::process::id()));
+    let first = render(&base.join("a"), Arc::new(FixedClock::from_unix_millis(FIXED_MILLIS)));
+    let second = render(&base.join("b"), Arc::new(FixedClock::from_unix_millis(FIXED_MILLIS)));
+
+    let time = local_time(FIXED_MILLIS);
+    assert_eq!(first, format!("{time} [INFO] clock - first\n{time} [WARN] clock - second\n"));
+    assert_eq!(first.as_bytes(), second.as_bytes());
+}
+
+#[test]
+fn test_step_clock_advances_per_record() {
+    let log_dir = std::env::temp_dir().join(format!("rat_logger_step_clock_{}", std::process::id()));
+    let output = render(&log_dir, Arc::new(StepClock::from_unix_millis(FIXED_MILLIS, Duration::from_millis(250))));
+
+    assert_eq!(output, format!(
+        "{} [INFO] clock - first\n{} [WARN] clock - second\n",
+        local_time(FIXED_MILLIS),
+        local_time(FIXED_MILLIS + 250),
+    ));
+}
+
+#[test]
+fn test_udp_packets_use_clock_time() {
+    let collector = UdpCollector::bind().unwrap();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_clock(Arc::new(FixedClock::from_unix_millis(FIXED_MILLIS)))
+        .add_udp(collector.network_config())
+        .build();
+
+    logger.log(&common::record(Level::Info, "clock", "over udp"));
+
+    assert!(collector.wait_for(1, Duration::from_secs(5)));
+    let records = collector.records();
+    assert_eq!(records[0].timestamp, FIXED_MILLIS / 1000);
+    assert_eq!(records[0].timestamp_nanos, 678_000_000);
+}
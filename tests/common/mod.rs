@@ -0,0 +1,76 @@
+//! 集成测试共用的辅助函数：测试目录、文件处理器配置、读取日志文件、构造记录和等待条件
+//!
+//! 每个测试文件是独立的 crate，只用到其中一部分函数。
+#![allow(dead_code)]
+
+use rat_logger::config::{Metadata, Record, RecordArgs};
+use rat_logger::{FileConfig, FormatConfig, Level};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 本进程专用的临时测试目录 `rat_logger_<prefix>_<name>_<pid>`，已存在时先删除（不创建目录）
+pub fn test_dir(prefix: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rat_logger_{}_{}_{}", prefix, name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// 同 [`test_dir`]，但先创建好空目录
+pub fn created_test_dir(prefix: &str, name: &str) -> PathBuf {
+    let dir = test_dir(prefix, name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 写入 `log_dir` 的文件处理器配置，`template` 为 None 时使用默认格式
+pub fn file_config(log_dir: &Path, template: Option<&str>) -> FileConfig {
+    FileConfig {
+        log_dir: log_dir.to_path_buf(),
+        format: template.map(|template| FormatConfig { format_template: template.to_string(), ..Default::default() }),
+        ..Default::default()
+    }
+}
+
+/// 拼接日志目录中所有 `.log` 文件的内容（目录不存在或无法读取的文件按空内容处理）
+pub fn read_logs(log_dir: &Path) -> String {
+    let mut content = String::new();
+    for entry in std::fs::read_dir(log_dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "log") {
+            content.push_str(&std::fs::read_to_string(&path).unwrap_or_default());
+        }
+    }
+    content
+}
+
+/// 日志目录中所有 `.log` 文件里包含 `needle` 的行
+pub fn log_lines(log_dir: &Path, needle: &str) -> Vec<String> {
+    read_logs(log_dir).lines().filter(|line| line.contains(needle)).map(str::to_string).collect()
+}
+
+/// 指定级别、目标和消息的记录，其余字段为默认值
+pub fn record(level: Level, target: &str, message: impl Into<RecordArgs>) -> Record {
+    Record {
+        metadata: Arc::new(Metadata {
+            level,
+            target: target.to_string(),
+            auth_token: None,
+            app_id: None,
+        }),
+        args: message.into(),
+        ..Default::default()
+    }
+}
+
+/// 轮询直到条件成立，超时返回 false
+pub fn wait_until(timeout: Duration, condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    condition()
+}
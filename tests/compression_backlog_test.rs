@@ -0,0 +1,103 @@
+//! 压缩积压测试：极小的轮转大小下，排队的压缩任务数不超过上限，轮转出的每个文件都有去向
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::{DiagnosticEvent, FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const MAX_PENDING: usize = 2;
+
+fn record(i: usize) -> Record {
+    common::record(Level::Info, "compression_backlog", format!("record {:05} {}", i, "x".repeat(100)))
+}
+
+#[test]
+fn test_pending_compressions_stay_under_cap() {
+    let log_dir = common::test_dir("compression_backlog", "pending_compressions");
+
+    let compressed: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+    let skipped: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+    let (compressed_events, skipped_events) = (compressed.clone(), skipped.clone());
+
+    let logger = Arc::new(LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            max_file_size: 1024,
+            max_compressed_files: 1000,
+            max_pending_compressions: MAX_PENDING,
+            ..Default::default()
+        })
+        .with_error_callback(move |event| match event {
+            DiagnosticEvent::LogFileCompressed { path } => {
+                compressed_events.lock().insert(path.clone());
+            }
+            DiagnosticEvent::CompressionSkipped { path, .. } => {
+                skipped_events.lock().insert(path.clone());
+            }
+            _ => {}
+        })
+        .build());
+
+    // 写入期间持续采样排队的压缩任务数
+    let done = Arc::new(AtomicBool::new(false));
+    let peak = Arc::new(AtomicU64::new(0));
+    let sampler = {
+        let (logger, done, peak) = (logger.clone(), done.clone(), peak.clone());
+        std::thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                peak.fetch_max(logger.stats().pending_compressions(), Ordering::SeqCst);
+                std::thread::yield_now();
+            }
+        })
+    };
+
+    for i in 0..2000 {
+        logger.log(&record(i));
+    }
+    logger.flush_sync(5000).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while logger.stats().pending_compressions() > 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    done.store(true, Ordering::SeqCst);
+    sampler.join().unwrap();
+
+    assert!(peak.load(Ordering::SeqCst) <= MAX_PENDING as u64, "压缩任务数超过上限: {}", peak.load(Ordering::SeqCst));
+    assert_eq!(logger.stats().pending_compressions(), 0);
+    assert_eq!(logger.stats().compressions_skipped(), skipped.lock().len() as u64);
+
+    // 除目录锁文件外，目录中的文件要么是上报过的压缩文件，要么是上报过跳过压缩的 .log，要么是当前活动文件
+    let compressed = compressed.lock().clone();
+    let skipped = skipped.lock().clone();
+    let mut unaccounted: Vec<PathBuf> = std::fs::read_dir(&log_dir).unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| !path.ends_with(rat_logger::handler::file::LOCK_FILE_NAME))
+        .filter(|path| !compressed.contains(path) && !skipped.contains(path))
+        .collect();
+    assert_eq!(unaccounted.len(), 1, "只应剩下当前活动文件: {:?}", unaccounted);
+    let active = unaccounted.pop().unwrap();
+    assert!(active.extension().is_some_and(|ext| ext == "log"), "{:?}", active);
+    assert!(compressed.len() + skipped.len() > 50, "应当发生多次轮转");
+    assert!(compressed.iter().all(|path| path.exists()));
+
+    // 所有记录都在压缩文件、跳过压缩的文件或活动文件中
+    let mut content = String::new();
+    for path in compressed.iter() {
+        let mut decoder = lz4::Decoder::new(std::fs::File::open(path).unwrap()).unwrap();
+        std::io::Read::read_to_string(&mut decoder, &mut content).unwrap();
+    }
+    for path in skipped.iter().chain([&active]) {
+        content.push_str(&std::fs::read_to_string(path).unwrap());
+    }
+    assert_eq!(content.lines().count(), 2000);
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,64 @@
+//! 压缩线程池测试：没有轮转时不创建压缩线程，第一次压缩时按需创建，最后一个文件处理器释放后关闭
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::{FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::time::Duration;
+
+fn record(i: usize) -> Record {
+    common::record(Level::Info, "compression_pool", format!("message {:0>200}", i))
+}
+
+/// 进程中的压缩线程数（Linux 上按线程名统计，其他平台使用线程池报告的线程数）
+fn os_compression_threads() -> usize {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_dir("/proc/self/task").unwrap()
+            .flatten()
+            .filter_map(|task| std::fs::read_to_string(task.path().join("comm")).ok())
+            .filter(|name| name.trim_end() == "rat-compress")
+            .count()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        rat_logger::handler::file::compression_threads()
+    }
+}
+
+#[test]
+fn test_pool_spawned_on_first_compression_and_released() {
+    let log_dir = common::test_dir("compression_pool", "pool_spawned");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            max_file_size: 2048,
+            min_compress_threads: 2,
+            ..Default::default()
+        })
+        .build();
+
+    // 尚未轮转：没有任何压缩线程
+    logger.log(&record(0));
+    logger.flush_sync(5000).unwrap();
+    assert_eq!(logger.stats().compression_threads(), 0);
+    assert_eq!(os_compression_threads(), 0);
+
+    // 超过 max_file_size 触发轮转，压缩线程池按需创建
+    for i in 1..20 {
+        logger.log(&record(i));
+    }
+    logger.flush_sync(5000).unwrap();
+    assert!(common::wait_until(Duration::from_secs(10), || logger.stats().pending_compressions() == 0));
+    let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+    assert_eq!(logger.stats().compression_threads(), 2.min(cores) as u64);
+    assert!(std::fs::read_dir(&log_dir).unwrap().flatten().any(|entry| entry.file_name().to_string_lossy().ends_with(".log.lz4")));
+
+    // 最后一个文件处理器释放后线程池关闭，线程退出
+    drop(logger);
+    assert_eq!(rat_logger::handler::file::compression_threads(), 0);
+    assert!(common::wait_until(Duration::from_secs(10), || os_compression_threads() == 0));
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
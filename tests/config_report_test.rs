@@ -0,0 +1,70 @@
+//! 配置报告测试：多处理器日志器的报告序列化为 JSON 后包含关键配置，UDP 认证令牌不出现在报告中
+
+mod common;
+
+use rat_logger::producer_consumer::BatchConfig;
+use rat_logger::{
+    BreakerAction, BreakerConfig, ByteSize, FileConfig, HandlerReport, LevelFilter, Logger, LoggerBuilder, MemoryAction,
+    NetworkConfig,
+};
+use std::net::UdpSocket;
+
+#[test]
+fn test_report_serializes_key_settings_without_secrets() {
+    let log_dir = common::test_dir("config_report", "report_serializes");
+    let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = collector.local_addr().unwrap().port();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        .with_batch_config(BatchConfig::low_latency())
+        .with_circuit_breaker(BreakerConfig::new(5000, BreakerAction::DropWithSummary))
+        .with_memory_budget(ByteSize::mib(8), MemoryAction::DropNewest)
+        .add_terminal_with_config(rat_logger::handler::term::TermConfig { enable_color: false, ..Default::default() })
+        .add_file(FileConfig { log_dir: log_dir.clone(), max_compressed_files: 0, compression_level: 0, ..Default::default() }.with_max_file_size(ByteSize::mib(4)))
+        .add_udp(NetworkConfig {
+            server_addr: "127.0.0.1".to_string(),
+            server_port: port,
+            auth_token: "super-secret-token".to_string(),
+            app_id: "report_test".to_string(),
+        })
+        .build();
+
+    let report = logger.config_report();
+    assert_eq!(report.level, LevelFilter::Debug);
+    assert_eq!(report.batch, BatchConfig::low_latency());
+    assert_eq!(report.handlers.len(), 3);
+    assert!(matches!(&report.handlers[2], HandlerReport::Udp { auth_token_set: true, .. }));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(json["level"], "Debug");
+    assert_eq!(json["breaker"]["max_records_per_sec"], 5000);
+    assert_eq!(json["memory_budget"]["max_bytes"], 8 * 1024 * 1024);
+    assert!(json["sampling"].is_null());
+    let features = json["features"].as_array().unwrap();
+    assert_eq!(features.iter().any(|feature| feature == "test-util"), cfg!(feature = "test-util"));
+    assert_eq!(features.iter().any(|feature| feature == "smol-runtime"), cfg!(feature = "smol-runtime"));
+
+    let handlers = json["handlers"].as_array().unwrap();
+    assert_eq!(handlers[0]["kind"], "terminal");
+    assert_eq!(handlers[0]["color"], false);
+    assert_eq!(handlers[1]["kind"], "file");
+    assert_eq!(handlers[1]["log_dir"], log_dir.display().to_string());
+    assert_eq!(handlers[1]["max_file_size"], 4 * 1024 * 1024);
+    assert_eq!(handlers[1]["max_compressed_files"], 0);
+    assert_eq!(handlers[2]["kind"], "udp");
+    assert_eq!(handlers[2]["target"], format!("127.0.0.1:{}", port));
+
+    // 令牌不以任何形式出现
+    let text = serde_json::to_string(&report).unwrap();
+    assert!(!text.contains("super-secret-token"), "{}", text);
+    assert!(!format!("{:?}", report).contains("super-secret-token"));
+
+    // 级别变化反映在之后的报告中
+    logger.set_level(LevelFilter::Error);
+    assert_eq!(logger.config_report().level, LevelFilter::Error);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,47 @@
+//! 自定义格式化函数测试：通过构建器注册格式化函数，输出必须与格式化函数写出的内容完全一致
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, TermProcessor};
+use rat_logger::config::Record;
+use std::io::Write;
+
+fn record(message: &str) -> Record {
+    Record { line: Some(42), ..common::record(Level::Warn, "custom_formatter", message) }
+}
+
+#[test]
+fn test_file_formatter_registered_via_builder() {
+    let log_dir = common::test_dir("custom_fmt", "file_formatter");
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file_with_formatter(
+            FileConfig {
+                log_dir: log_dir.clone(),
+                ..Default::default()
+            },
+            |buf: &mut dyn Write, record: &Record| {
+                writeln!(buf, "{}|{}|{:?}|{}", record.metadata.level, record.metadata.target, record.line, record.args)
+            },
+        )
+        .build();
+
+    logger.log(&record("hello\tworld"));
+    logger.flush_sync(2000).unwrap();
+
+    let content = common::read_logs(&log_dir);
+    let _ = std::fs::remove_dir_all(&log_dir);
+    assert_eq!(content, "WARN|custom_formatter|Some(42)|hello\tworld\n");
+}
+
+#[test]
+fn test_formatter_handle_shared_between_clones() {
+    let processor = TermProcessor::new();
+    let handle = processor.formatter_handle();
+    processor.set_formatter(|buf: &mut dyn Write, record: &Record| write!(buf, "<{}>", record.args));
+
+    let mut buf = Vec::new();
+    handle.format(&mut buf, &record("shared")).unwrap();
+    assert_eq!(buf, b"<shared>");
+}
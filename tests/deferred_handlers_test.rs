@@ -0,0 +1,59 @@
+//! 延迟创建处理器测试：`add_*` 只记录配置，处理器创建失败在构建时报告并指明处理器序号和类型
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::core::SetLoggerError;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+#[test]
+fn test_invalid_file_config_reported_at_build() {
+    let log_dir = common::test_dir("deferred", "invalid_file");
+
+    // 构建器可以正常创建，此时还没有创建日志目录
+    let builder = LoggerBuilder::new()
+        .add_terminal_with_config(TermConfig::default())
+        .add_file(FileConfig { log_dir: log_dir.clone(), max_file_size: 0, ..Default::default() });
+    assert!(!log_dir.exists());
+
+    let error = builder.clone().try_build().err().unwrap();
+    assert!(error.starts_with("处理器 #1（file）创建失败"), "{}", error);
+    assert!(error.contains("最大文件大小不能为 0"), "{}", error);
+
+    match builder.try_init_global_logger() {
+        Err(SetLoggerError::Build(e)) => assert_eq!(e, error),
+        other => panic!("{:?}", other),
+    }
+    assert!(rat_logger::core::lock_global_logger().is_none());
+    assert!(!log_dir.exists());
+}
+
+#[test]
+fn test_cloned_builders_create_independent_processors() {
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let builder = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.args.to_string()))),
+            ..Default::default()
+        });
+
+    let first = builder.clone().build();
+    let second = builder.build();
+    first.log(&common::record(Level::Info, "deferred", "first"));
+    second.log(&common::record(Level::Info, "deferred", "second"));
+    first.flush_sync(2000).unwrap();
+    second.flush_sync(2000).unwrap();
+
+    // 两个日志器各自有一个终端处理器，共享同一个钩子
+    assert_eq!(first.processor_manager().len(), 1);
+    assert_eq!(second.processor_manager().len(), 1);
+    let mut messages = captured.lock().clone();
+    messages.sort();
+    assert_eq!(messages, ["first", "second"]);
+}
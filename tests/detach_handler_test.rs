@@ -0,0 +1,87 @@
+//! 动态移除/添加处理器测试：运行中移除 UDP 处理器后记录只到达其余处理器
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, TermProcessor};
+use rat_logger::config::Record;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::producer_consumer::{BatchConfig, RemoveError};
+use rat_logger::test_util::UdpCollector;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_detach_udp_mid_stream() {
+    let log_dir = common::test_dir("detach", "detach_udp");
+    let collector = UdpCollector::bind().unwrap();
+    let diagnostics = Arc::new(AtomicUsize::new(0));
+    let diagnostics_seen = diagnostics.clone();
+
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_error_callback(move |_| {
+            diagnostics_seen.fetch_add(1, Ordering::SeqCst);
+        })
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            ..Default::default()
+        })
+        .add_udp(collector.network_config())
+        .init_global_logger()
+        .unwrap();
+
+    rat_logger::info!("before detach");
+    assert!(collector.wait_for(1, Duration::from_secs(5)), "移除前 UDP 应当收到记录");
+    assert_eq!(collector.messages(), ["before detach"]);
+
+    rat_logger::detach_global_handler("udp").unwrap();
+    assert_eq!(rat_logger::global_handler_types(), vec!["file_processor"]);
+    assert_eq!(rat_logger::detach_global_handler("udp"), Err(RemoveError::NotFound("udp".to_string())));
+
+    for i in 0..20 {
+        rat_logger::info!("after detach {}", i);
+    }
+    rat_logger::flush_logs!();
+
+    let content = common::read_logs(&log_dir);
+    assert!(content.contains("before detach") && content.contains("after detach 19"), "{}", content);
+    assert!(!collector.wait_for(2, Duration::from_millis(300)), "移除后 UDP 不应再收到记录");
+    assert_eq!(diagnostics.load(Ordering::SeqCst), 0, "移除处理器不应产生发送错误");
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_attach_then_detach_processor() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let hook_calls = calls.clone();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            ..Default::default()
+        })
+        .build();
+
+    logger.log(&common::record(Level::Info, "detach", "before attach"));
+    logger.attach_processor(
+        TermProcessor::with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |_: &Record| {
+                hook_calls.fetch_add(1, Ordering::SeqCst);
+            })),
+            ..Default::default()
+        }),
+        BatchConfig::low_latency(),
+    ).unwrap();
+    logger.log(&common::record(Level::Info, "detach", "after attach"));
+    logger.flush_sync(2000).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1, "添加的处理器只收到之后的记录");
+    assert_eq!(logger.processor_manager().len(), 2);
+
+    // 同类型的处理器一起移除
+    logger.detach_handler("term_processor").unwrap();
+    assert!(logger.processor_manager().is_empty());
+    assert!(!logger.has_handler("term"));
+}
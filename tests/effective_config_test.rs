@@ -0,0 +1,96 @@
+//! 生效配置测试：处理器和日志器报告的配置与传入的配置相等，构建器补充的默认值体现在报告中
+
+use rat_logger::config::{Metadata, Record};
+use rat_logger::handler::term::{FlushPolicy, TermConfig};
+use rat_logger::producer_consumer::BatchConfig;
+use rat_logger::{ColorConfig, FileConfig, FileProcessor, FormatConfig, Level, LevelFilter, LoggerBuilder, ProcessorConfig, TermProcessor};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+fn file_config(name: &str) -> FileConfig {
+    FileConfig {
+        log_dir: std::env::temp_dir().join(format!("rat_logger_effective_config_{}_{}", name, std::process::id())),
+        max_compressed_files: 3,
+        write_header: true,
+        format: Some(FormatConfig {
+            format_template: "{level}|{message}".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_processors_report_their_config() {
+    let config = file_config("processor");
+    let processor = FileProcessor::new(config.clone());
+    assert_eq!(processor.config(), &config);
+    drop(processor);
+    let _ = std::fs::remove_dir_all(&config.log_dir);
+
+    let hook: rat_logger::handler::term::RecordHook = Arc::new(|_: &Record| {});
+    let config = TermConfig {
+        enable_color: true,
+        color: Some(ColorConfig::default()),
+        on_record: Some(hook.clone()),
+        max_line_bytes: Some(200),
+        flush_policy: Some(FlushPolicy::IntervalMs(50)),
+        ..Default::default()
+    };
+    let processor = TermProcessor::with_config(config.clone());
+    assert_eq!(processor.config(), &config);
+    // 钩子按是否为同一个 Arc 比较
+    let other_hook = TermConfig { on_record: Some(Arc::new(|_: &Record| {})), ..config.clone() };
+    assert_ne!(processor.config(), &other_hook);
+
+    assert_eq!(BatchConfig::default(), BatchConfig::default());
+    assert_ne!(BatchConfig::default(), BatchConfig::low_latency());
+}
+
+#[test]
+fn test_logger_reports_resolved_configs() {
+    let default_format = FormatConfig {
+        format_template: "{target} {message}".to_string(),
+        ..Default::default()
+    };
+    let file = file_config("logger");
+    let bare_file = FileConfig { format: None, ..file_config("logger_bare") };
+    let term = TermConfig { enable_color: false, ..Default::default() };
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_format(default_format.clone())
+        .add_terminal_with_config(term.clone())
+        .add_file(file.clone())
+        .add_file(bare_file.clone())
+        .build();
+
+    // 构建器为未指定的格式和刷新策略补充默认值，已指定的配置保持不变
+    assert_eq!(logger.processor_configs(), [
+        ProcessorConfig::Terminal(TermConfig {
+            format: Some(default_format.clone()),
+            flush_policy: Some(FlushPolicy::EveryRecord),
+            ..term
+        }),
+        ProcessorConfig::File(file.clone()),
+        ProcessorConfig::File(FileConfig { format: Some(default_format), ..bare_file.clone() }),
+    ]);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&file.log_dir);
+    let _ = std::fs::remove_dir_all(&bare_file.log_dir);
+}
+
+#[test]
+fn test_metadata_accessors_and_level_hash() {
+    let metadata = Metadata {
+        level: Level::Warn,
+        target: "db".to_string(),
+        auth_token: None,
+        app_id: Some("svc".to_string()),
+    };
+    assert_eq!((metadata.level(), metadata.target(), metadata.app_id()), (Level::Warn, "db", Some("svc")));
+
+    let levels: HashSet<Level> = [Level::Warn, Level::Error, Level::Warn].into_iter().collect();
+    assert_eq!(levels.len(), 2);
+}
@@ -0,0 +1,53 @@
+//! 时间间隔占位符测试：`{uptime}` 和 `{delta}` 按注入时钟给出的记录时间渲染，精度可配置
+
+mod common;
+
+use rat_logger::clock::Clock;
+use rat_logger::{FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 按顺序返回预设时间的时钟（第一次调用为日志器启动时间）
+struct ScriptedClock(Mutex<VecDeque<SystemTime>>);
+
+impl Clock for ScriptedClock {
+    fn now(&self) -> SystemTime {
+        self.0.lock().pop_front().expect("时钟调用次数超出预设")
+    }
+}
+
+fn format(uptime_precision: usize, delta_precision: usize) -> FormatConfig {
+    FormatConfig {
+        format_template: "{uptime} {delta} {message}".to_string(),
+        uptime_precision,
+        delta_precision,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_uptime_and_delta_follow_record_timestamps() {
+    let base = common::test_dir("elapsed", "uptime_and");
+    let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let offsets = [Duration::ZERO, Duration::from_micros(1_500_000), Duration::from_micros(1_500_800), Duration::from_millis(12_345)];
+    let clock = ScriptedClock(Mutex::new(offsets.iter().map(|offset| start + *offset).collect()));
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_clock(Arc::new(clock))
+        .add_file(FileConfig { log_dir: base.join("default"), format: Some(format(3, 1)), ..Default::default() })
+        .add_file(FileConfig { log_dir: base.join("precise"), format: Some(format(1, 3)), ..Default::default() })
+        .build();
+    for message in ["first", "second", "third"] {
+        logger.log(&common::record(Level::Info, "elapsed", message));
+    }
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    assert_eq!(common::read_logs(&base.join("default")), "+1.500s +0.0ms first\n+1.501s +0.8ms second\n+12.345s +10.8s third\n");
+    assert_eq!(common::read_logs(&base.join("precise")), "+1.5s +0.000ms first\n+1.5s +0.800ms second\n+12.3s +10.844s third\n");
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&base);
+}
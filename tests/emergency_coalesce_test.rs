@@ -0,0 +1,124 @@
+//! 强制写入合并测试：突发的强制写入在合并窗口内按原顺序成批处理，刷新次数受窗口限制，单独的强制写入不会被延迟超过窗口
+
+mod common;
+
+use rat_logger::config::{decode_record_versioned, encode_record_v2};
+use rat_logger::producer_consumer::{BatchConfig, LogProcessor, ProcessorError, ProcessorWorker};
+use rat_logger::{DurationMs, Level};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct Counts {
+    messages: Vec<String>,
+    flushes: usize,
+    last_processed: Option<Instant>,
+}
+
+/// 记录处理顺序和刷新次数的处理器
+struct CountingProcessor(Arc<Mutex<Counts>>);
+
+impl LogProcessor for CountingProcessor {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
+        let (record, _) = decode_record_versioned(data).map_err(ProcessorError::decode)?;
+        let mut counts = self.0.lock();
+        counts.messages.push(record.args.into());
+        counts.last_processed = Some(Instant::now());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        self.0.lock().flushes += 1;
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+fn encoded(message: String) -> Vec<u8> {
+    let record = common::record(Level::Error, "emergency_coalesce", message);
+    encode_record_v2(&record).unwrap()
+}
+
+fn flush(worker: &ProcessorWorker) {
+    let (ack_tx, ack_rx) = crossbeam_channel::bounded(1);
+    worker.send_flush_ack(ack_tx).unwrap();
+    assert!(ack_rx.recv_timeout(Duration::from_secs(2)).unwrap());
+}
+
+#[test]
+fn test_error_burst_flush_count_bounded_by_window() {
+    const WINDOW_MS: u64 = 2;
+    let counts = Arc::new(Mutex::new(Counts::default()));
+    let config = BatchConfig::default().with_emergency_coalesce(DurationMs::ms(WINDOW_MS));
+    let worker = ProcessorWorker::new(CountingProcessor(counts.clone()), config);
+
+    let start = Instant::now();
+    for i in 0..1000 {
+        worker.send_write_force(encoded(format!("error {}", i))).unwrap();
+    }
+    flush(&worker);
+    let elapsed = start.elapsed();
+
+    let counts = counts.lock();
+    let expected: Vec<String> = (0..1000).map(|i| format!("error {}", i)).collect();
+    assert_eq!(counts.messages, expected, "合并后仍按发送顺序处理");
+    // 每个窗口最多刷新一次，另加确认刷新和计时误差的余量
+    let bound = elapsed.as_millis() as usize / WINDOW_MS as usize + 2;
+    assert!(counts.flushes <= bound, "刷新 {} 次，耗时 {:?}，上限 {}", counts.flushes, elapsed, bound);
+    assert!(counts.flushes < 1000, "突发的强制写入不应逐条刷新");
+}
+
+#[test]
+fn test_isolated_emergency_not_delayed_beyond_window() {
+    let counts = Arc::new(Mutex::new(Counts::default()));
+    let config = BatchConfig::default().with_emergency_coalesce(DurationMs::ms(20));
+    let worker = ProcessorWorker::new(CountingProcessor(counts.clone()), config);
+
+    let sent = Instant::now();
+    worker.send_write_force(encoded("lone error".to_string())).unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+
+    let counts = counts.lock();
+    assert_eq!(counts.messages, ["lone error"]);
+    assert_eq!(counts.flushes, 1);
+    let delay = counts.last_processed.unwrap() - sent;
+    assert!(delay < Duration::from_millis(150), "单独的强制写入延迟 {:?}", delay);
+}
+
+#[test]
+fn test_zero_window_flushes_every_forced_write() {
+    let counts = Arc::new(Mutex::new(Counts::default()));
+    let worker = ProcessorWorker::new(CountingProcessor(counts.clone()), BatchConfig::low_latency());
+
+    for i in 0..10 {
+        worker.send_write_force(encoded(format!("error {}", i))).unwrap();
+    }
+    flush(&worker);
+
+    // 每条强制写入各刷新一次，另加确认刷新
+    assert_eq!(counts.lock().flushes, 11);
+}
+
+#[test]
+fn test_coalesce_window_config() {
+    assert_eq!(BatchConfig::default().emergency_coalesce_ms, 2);
+    assert_eq!(BatchConfig::low_latency().emergency_coalesce_ms, 0);
+
+    let too_long = BatchConfig::default().with_emergency_coalesce(DurationMs::ms(BatchConfig::MAX_EMERGENCY_COALESCE_MS + 1));
+    assert!(too_long.validate().is_err());
+
+    // 旧配置文件没有该字段时取默认值，也可写作时长字符串
+    let config: BatchConfig = serde_json::from_str(r#"{"batch_size":1,"batch_interval_ms":1,"buffer_size":1}"#).unwrap();
+    assert_eq!(config.emergency_coalesce_ms, 2);
+    let config: BatchConfig =
+        serde_json::from_str(r#"{"batch_size":1,"batch_interval_ms":1,"buffer_size":1,"emergency_coalesce_ms":"5ms"}"#).unwrap();
+    assert_eq!(config.emergency_coalesce_ms, 5);
+}
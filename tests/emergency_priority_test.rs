@@ -0,0 +1,58 @@
+//! 紧急日志宏测试：`emergency!`/`startup_log!` 经统一入口投递，照常清理内容并计入统计，同时不受批量配置限制
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, FileConfig};
+use rat_logger::producer_consumer::BatchConfig;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 轮询日志目录直到出现指定内容
+fn wait_for_log(log_dir: &Path, needle: &str, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if common::read_logs(log_dir).contains(needle) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn test_emergency_macros_share_log_path() {
+    let log_dir = common::test_dir("emergency_priority", "emergency_macros");
+
+    // 批量间隔足够长，普通路径的记录在测试期间不会被写出
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Warn)
+        .with_batch_config(BatchConfig {
+            batch_size: 1000,
+            batch_interval_ms: 60_000,
+            buffer_size: 4096,
+            ..Default::default()
+        })
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            ..Default::default()
+        })
+        .init_global_logger()
+        .unwrap();
+
+    rat_logger::emergency!("token=abc123\r\x1b[2Jfake line");
+    rat_logger::startup_log!("listening on :8080");
+    // Info 低于日志器级别，普通宏不产生记录
+    rat_logger::info!("filtered");
+
+    assert!(
+        wait_for_log(&log_dir, "token=abc123\\r\\x1b[2Jfake line", Duration::from_secs(1)),
+        "紧急日志应当立即写出，且控制字符经过清理"
+    );
+    assert!(wait_for_log(&log_dir, "listening on :8080", Duration::from_secs(1)), "启动日志不受级别过滤");
+
+    let stats = rat_logger::core::LOGGER.lock().unwrap().as_ref().and_then(|logger| logger.runtime_stats()).unwrap();
+    assert_eq!(stats.emergency(), 2);
+    assert!(!common::read_logs(&log_dir).contains("filtered"));
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,69 @@
+//! 紧急日志路由测试：`accept_emergency: false` 的处理器不走强制写入路径
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, UdpConfig};
+use rat_logger::test_util::UdpCollector;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 轮询日志目录直到出现指定内容
+fn wait_for_log(log_dir: &Path, needle: &str, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if common::read_logs(log_dir).contains(needle) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn test_emergency_skips_opted_out_udp() {
+    let log_dir = common::test_dir("emergency", "emergency_skips");
+    let collector = UdpCollector::bind().unwrap();
+
+    // 级别过滤为 Off（允许紧急日志）：紧急日志只能通过强制写入路径到达处理器
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Off)
+        .with_emergency_when_off(true)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            ..Default::default()
+        })
+        .add_udp_with_config(UdpConfig {
+            network_config: collector.network_config(),
+            accept_emergency: false,
+            ..Default::default()
+        })
+        .init_global_logger()
+        .unwrap();
+
+    rat_logger::emergency!("startup banner");
+
+    assert!(wait_for_log(&log_dir, "startup banner", Duration::from_secs(1)), "本地文件应当立即收到紧急日志");
+
+    assert!(!collector.wait_for(1, Duration::from_millis(300)), "不接收紧急日志的 UDP 处理器不应收到任何数据");
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_opted_out_handler_receives_allowed_levels_on_normal_path() {
+    let collector = UdpCollector::bind().unwrap();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(UdpConfig {
+            network_config: collector.network_config(),
+            accept_emergency: false,
+            ..Default::default()
+        })
+        .build();
+
+    logger.emergency_log(&common::record(Level::Error, "emergency_routing", "panic dump"));
+
+    assert!(collector.wait_for(1, Duration::from_secs(5)), "通过级别过滤的记录应当经普通路径发出");
+    assert_eq!(collector.messages(), ["panic dump"]);
+}
@@ -0,0 +1,97 @@
+//! 记录富化测试：富化结果出现在文件和 UDP 输出中，每条记录只富化一次；富化函数 panic 只影响该记录的富化
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::test_util::UdpCollector;
+use rat_logger::{DiagnosticEvent, FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn record(level: Level, message: &str, user_id: &str) -> Record {
+    Record {
+        fields: vec![("user_id".to_string(), user_id.to_string())],
+        ..common::record(level, "enricher", message)
+    }
+}
+
+/// 按用户ID查找用户名并附加到消息，ID 为 "bad" 时 panic
+fn enrich(record: &mut Record) {
+    let user_id = record.fields.iter().find(|(key, _)| key == "user_id").map(|(_, value)| value.clone());
+    match user_id.as_deref() {
+        Some("bad") => panic!("用户缓存损坏"),
+        Some(id) => record.args.to_mut().push_str(&format!(" user={}", if id == "42" { "alice" } else { "bob" })),
+        None => {}
+    }
+}
+
+#[test]
+fn test_enrichment_reaches_all_outputs_once() {
+    let (first_dir, second_dir) = (common::test_dir("enricher", "first"), common::test_dir("enricher", "second"));
+    let collector = UdpCollector::bind().unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let format = FormatConfig { format_template: "{level} {message}".to_string(), ..Default::default() };
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_enricher(move |record| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            enrich(record);
+        })
+        .add_file(FileConfig { log_dir: first_dir.clone(), format: Some(format.clone()), ..Default::default() })
+        .add_file(FileConfig { log_dir: second_dir.clone(), format: Some(format), ..Default::default() })
+        .add_udp(collector.network_config())
+        .build();
+    logger.log(&record(Level::Info, "login", "42"));
+    logger.log(&record(Level::Error, "denied", "7"));
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    let expected = "INFO login user=alice\nERROR denied user=bob\n";
+    assert_eq!(common::read_logs(&first_dir), expected);
+    assert_eq!(common::read_logs(&second_dir), expected);
+    assert!(collector.wait_for(2, Duration::from_secs(5)));
+    let mut messages = collector.messages();
+    messages.sort();
+    assert_eq!(messages, ["denied user=bob", "login user=alice"]);
+    // 三个处理器共享每条记录的富化结果
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&first_dir);
+    let _ = std::fs::remove_dir_all(&second_dir);
+}
+
+#[test]
+fn test_enricher_panic_keeps_record_and_worker() {
+    let log_dir = common::test_dir("enricher", "panic");
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seen = events.clone();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_error_callback(move |event| {
+            if let DiagnosticEvent::EnricherPanicked { target, message } = event {
+                seen.lock().push(format!("{}: {}", target, message));
+            }
+        })
+        .with_enricher(enrich)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            format: Some(FormatConfig { format_template: "{message}".to_string(), ..Default::default() }),
+            ..Default::default()
+        })
+        .build();
+    logger.log(&record(Level::Info, "before", "42"));
+    logger.log(&record(Level::Info, "broken", "bad"));
+    logger.log(&record(Level::Error, "after", "7"));
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    assert_eq!(common::read_logs(&log_dir), "before user=alice\nbroken\nafter user=bob\n");
+    assert_eq!(*events.lock(), ["enricher: 用户缓存损坏"]);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,78 @@
+//! env_logger 风格测试：使用固定时钟时，五个级别的输出与 env_logger 默认格式逐字节一致
+
+mod common;
+
+use rat_logger::clock::FixedClock;
+use rat_logger::config::Record;
+use rat_logger::handler::term::format_with_color;
+use rat_logger::{ColorConfig, FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// 2026-01-02 03:04:05.678 UTC
+const FIXED_MILLIS: u64 = 1_767_323_045_678;
+
+fn record(level: Level) -> Record {
+    common::record(level, "my_crate::db", format!("{:?} message", level).to_lowercase())
+}
+
+#[test]
+fn test_env_logger_style_file_snapshot() {
+    let log_dir = common::test_dir("env_style", "env_logger");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Trace)
+        .with_clock(Arc::new(FixedClock::from_unix_millis(FIXED_MILLIS)))
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            format: Some(FormatConfig::env_logger_style()),
+            ..Default::default()
+        })
+        .build();
+
+    for level in Level::iter() {
+        logger.log(&record(level));
+    }
+    logger.flush_sync(2000).unwrap();
+    drop(logger);
+
+    let mut content = String::new();
+    for entry in std::fs::read_dir(&log_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "log") {
+            content.push_str(&std::fs::read_to_string(path).unwrap());
+        }
+    }
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    // 时间戳与本地时区无关
+    assert_eq!(content, concat!(
+        "[2026-01-02T03:04:05Z ERROR my_crate::db] error message\n",
+        "[2026-01-02T03:04:05Z WARN  my_crate::db] warn message\n",
+        "[2026-01-02T03:04:05Z INFO  my_crate::db] info message\n",
+        "[2026-01-02T03:04:05Z DEBUG my_crate::db] debug message\n",
+        "[2026-01-02T03:04:05Z TRACE my_crate::db] trace message\n",
+    ));
+}
+
+#[test]
+fn test_env_logger_style_colors_only_level() {
+    let format = FormatConfig::env_logger_style();
+    let colors = ColorConfig::env_logger_style();
+    let codes = ["31", "33", "32", "34", "36"];
+
+    for (level, code) in Level::iter().zip(codes) {
+        let record = Record {
+            timestamp: Some(UNIX_EPOCH + Duration::from_millis(FIXED_MILLIS)),
+            ..record(level)
+        };
+        let mut buf = Vec::new();
+        format_with_color(&mut buf, &record, &format, &colors).unwrap();
+
+        let level_text = format!("{:<5}", format!("{:?}", level).to_uppercase());
+        let expected = format!(
+            "[2026-01-02T03:04:05Z\x1b[0m \x1b[{code}m{level_text}\x1b[0m my_crate::db\x1b[0m] {}\x1b[0m\n",
+            record.args
+        );
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+    }
+}
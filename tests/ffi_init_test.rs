@@ -0,0 +1,49 @@
+//! FFI 初始化测试：JSON 配置的错误码、文件输出以及关闭后重新初始化
+
+mod common;
+
+use rat_logger::ffi::*;
+use std::ffi::CString;
+
+fn init(json: &str) -> i32 {
+    let json = CString::new(json).unwrap();
+    unsafe { rat_logger_init_from_json(json.as_ptr()) }
+}
+
+#[test]
+fn test_init_from_json() {
+    // 配置错误不会初始化日志器
+    assert_eq!(unsafe { rat_logger_init_from_json(std::ptr::null()) }, RAT_LOGGER_ERR_NULL);
+    assert_eq!(unsafe { rat_logger_init_from_json(c"{\"level\": \"\xff\"}".as_ptr()) }, RAT_LOGGER_ERR_UTF8);
+    assert_eq!(init("not json"), RAT_LOGGER_ERR_CONFIG);
+    assert_eq!(init(r#"{"level": "loud", "terminal": {}}"#), RAT_LOGGER_ERR_CONFIG);
+    assert_eq!(init(r#"{"terminal": {}, "colour": true}"#), RAT_LOGGER_ERR_CONFIG);
+    // 没有任何处理器
+    assert_eq!(init(r#"{"level": "info"}"#), RAT_LOGGER_ERR_CONFIG);
+    assert_eq!(rat_logger_flush(100), RAT_LOGGER_ERR_NOT_INITIALIZED);
+
+    let log_dir = common::test_dir("ffi_init", "init_from");
+    let config = serde_json::json!({
+        "level": "debug",
+        "file": {
+            "log_dir": log_dir,
+            "format": { "format_template": "{target}|{message}" }
+        }
+    })
+    .to_string();
+
+    assert_eq!(init(&config), RAT_LOGGER_OK);
+    assert_eq!(init(&config), RAT_LOGGER_ERR_ALREADY_INITIALIZED);
+    let target = CString::new("c_app").unwrap();
+    let message = CString::new("started").unwrap();
+    assert_eq!(unsafe { rat_logger_log(RAT_LOGGER_LEVEL_DEBUG, target.as_ptr(), message.as_ptr()) }, RAT_LOGGER_OK);
+    assert_eq!(rat_logger_flush(2000), RAT_LOGGER_OK);
+    assert!(common::read_logs(&log_dir).contains("c_app|started"), "{}", common::read_logs(&log_dir));
+
+    // 关闭后可以重新初始化
+    assert_eq!(rat_logger_shutdown(), RAT_LOGGER_OK);
+    assert_eq!(init(&config), RAT_LOGGER_OK);
+    assert_eq!(rat_logger_shutdown(), RAT_LOGGER_OK);
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,67 @@
+//! FFI 写入测试：模拟 C 调用方写入全局日志器，检查级别、字段、非法 UTF-8 处理和错误码
+
+use rat_logger::config::Record;
+use rat_logger::ffi::*;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{Level, LevelFilter, LoggerBuilder};
+use parking_lot::Mutex;
+use std::ffi::c_char;
+use std::sync::Arc;
+
+#[test]
+fn test_ffi_calls_reach_global_logger() {
+    let captured: Arc<Mutex<Vec<Record>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.clone()))),
+            ..Default::default()
+        })
+        .init_global_logger()
+        .unwrap();
+
+    unsafe {
+        assert_eq!(rat_logger_log(RAT_LOGGER_LEVEL_WARN, c"c_service".as_ptr(), c"disk almost full".as_ptr()), RAT_LOGGER_OK);
+        // 非法 UTF-8 按替换字符处理；目标为空指针时使用默认目标
+        assert_eq!(rat_logger_log(RAT_LOGGER_LEVEL_INFO, std::ptr::null(), c"bad \xff byte".as_ptr()), RAT_LOGGER_OK);
+
+        let keys = [c"user".as_ptr(), c"latency_ms".as_ptr()];
+        let values = [c"bob".as_ptr(), c"12".as_ptr()];
+        assert_eq!(
+            rat_logger_log_kv(RAT_LOGGER_LEVEL_ERROR, c"c_service".as_ptr(), c"request failed".as_ptr(), keys.as_ptr(), values.as_ptr(), 2),
+            RAT_LOGGER_OK
+        );
+        // 被级别过滤的记录同样成功返回
+        assert_eq!(rat_logger_log(RAT_LOGGER_LEVEL_TRACE, c"c_service".as_ptr(), c"filtered".as_ptr()), RAT_LOGGER_OK);
+
+        // 参数错误返回错误码
+        assert_eq!(rat_logger_log(9, c"c_service".as_ptr(), c"bad level".as_ptr()), RAT_LOGGER_ERR_LEVEL);
+        assert_eq!(rat_logger_log(RAT_LOGGER_LEVEL_INFO, c"c_service".as_ptr(), std::ptr::null()), RAT_LOGGER_ERR_NULL);
+        let null_keys = [std::ptr::null::<c_char>()];
+        assert_eq!(
+            rat_logger_log_kv(RAT_LOGGER_LEVEL_INFO, std::ptr::null(), c"null key".as_ptr(), null_keys.as_ptr(), values.as_ptr(), 1),
+            RAT_LOGGER_ERR_NULL
+        );
+    }
+    assert_eq!(rat_logger_flush(2000), RAT_LOGGER_OK);
+
+    let records = captured.lock().clone();
+    let summary: Vec<(Level, &str, &str)> = records.iter()
+        .map(|record| (record.metadata.level, record.metadata.target.as_str(), record.args.as_str()))
+        .collect();
+    assert_eq!(summary, [
+        (Level::Warn, "c_service", "disk almost full"),
+        (Level::Info, "ffi", "bad \u{FFFD} byte"),
+        (Level::Error, "c_service", "request failed"),
+    ]);
+    assert_eq!(records[2].field("user"), Some("bob"));
+    assert_eq!(records[2].field("latency_ms"), Some("12"));
+
+    // 关闭后不再接收日志
+    assert_eq!(rat_logger_shutdown(), RAT_LOGGER_OK);
+    assert_eq!(unsafe { rat_logger_log(RAT_LOGGER_LEVEL_ERROR, c"c_service".as_ptr(), c"late".as_ptr()) }, RAT_LOGGER_ERR_NOT_INITIALIZED);
+    assert_eq!(rat_logger_flush(100), RAT_LOGGER_ERR_NOT_INITIALIZED);
+    assert_eq!(rat_logger_shutdown(), RAT_LOGGER_ERR_NOT_INITIALIZED);
+}
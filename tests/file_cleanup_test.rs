@@ -0,0 +1,69 @@
+//! 文件处理器清理测试：重复清理（显式清理后销毁、工作线程停止后销毁）不产生诊断事件，文件内容完整写出
+
+mod common;
+
+use rat_logger::diagnostics::Diagnostics;
+use rat_logger::producer_consumer::LogProcessor;
+use rat_logger::{FileConfig, FileProcessor, Level, LevelFilter, Logger, LoggerBuilder};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_cleanup_then_drop_is_idempotent() {
+    let log_dir = common::test_dir("file_cleanup", "direct");
+    let events = Arc::new(AtomicUsize::new(0));
+    let seen = events.clone();
+    let diagnostics = Diagnostics::new();
+    diagnostics.set_callback(Some(Arc::new(move |_: &rat_logger::DiagnosticEvent| {
+        seen.fetch_add(1, Ordering::SeqCst);
+    })));
+
+    let mut processor = FileProcessor::new(FileConfig { log_dir: log_dir.clone(), ..Default::default() });
+    processor.set_diagnostics(diagnostics);
+    for i in 0..100 {
+        processor.write_raw_line(&format!("line {}", i)).unwrap();
+    }
+    processor.cleanup().unwrap();
+    processor.cleanup().unwrap();
+
+    // 清理后的命令被忽略，不报错
+    processor.write_raw_line("after cleanup").unwrap();
+    processor.flush().unwrap();
+    processor.handle_rotate().unwrap();
+    drop(processor);
+
+    let content = common::read_logs(&log_dir);
+    assert_eq!(content.lines().count(), 100, "{}", content);
+    assert!(content.ends_with("line 99\n"));
+    assert_eq!(events.load(Ordering::SeqCst), 0);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_drop_after_worker_shutdown_reports_nothing() {
+    let log_dir = common::test_dir("file_cleanup", "worker");
+    let events = Arc::new(AtomicUsize::new(0));
+    let seen = events.clone();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_error_callback(move |_| {
+            seen.fetch_add(1, Ordering::SeqCst);
+        })
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .build();
+    for i in 0..500 {
+        logger.log(&common::record(Level::Info, "file_cleanup", format!("record {}", i)));
+    }
+    // 工作线程停止时清理一次，日志器销毁时处理器随工作线程释放
+    logger.shutdown(Duration::from_secs(5)).unwrap();
+    logger.flush();
+    drop(logger);
+
+    let content = common::read_logs(&log_dir);
+    assert_eq!(content.lines().count(), 500, "{}", content);
+    assert!(content.trim_end().ends_with("record 499"));
+    assert_eq!(events.load(Ordering::SeqCst), 0);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
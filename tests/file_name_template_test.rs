@@ -0,0 +1,130 @@
+//! 文件名模板测试：轮转后的文件名包含应用ID和序号，保留策略按模板推导出的模式只清理本应用的文件
+
+mod common;
+
+use rat_logger::{FileConfig, Level, LevelFilter, Logger, LoggerBuilder, SharedDirPolicy};
+use std::path::Path;
+
+/// 目录中的日志文件名（不含锁文件），已排序
+fn names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir).unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".log"))
+        .collect();
+    names.sort();
+    names
+}
+
+/// 启用轮转和按总大小清理、禁用压缩的配置
+fn rotating_config(dir: &Path, template: &str) -> FileConfig {
+    FileConfig {
+        log_dir: dir.to_path_buf(),
+        max_file_size: 1024,
+        max_compressed_files: 0,
+        compression_level: 0,
+        max_total_size: 2048,
+        file_name_template: Some(template.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_app_id_template_rotation_and_retention_per_app() {
+    let dir = common::test_dir("file_name_template", "app_id");
+    let billing = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig { app_id: Some("billing".to_string()), ..rotating_config(&dir, "{app_id}-{timestamp}") })
+        .build();
+    // 应用ID来自日志器；文件名以 `billing-` 开头，按前缀匹配会被 billing 的清理误删
+    let billing_eu = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_app_id("billing-eu")
+        .add_file(FileConfig { shared_dir_policy: SharedDirPolicy::Prefix, ..rotating_config(&dir, "{app_id}-{timestamp}") })
+        .build();
+
+    billing_eu.log(&common::record(Level::Info, "file_name_template", "eu first"));
+    billing_eu.flush_sync(2000).unwrap();
+    for i in 0..200 {
+        billing.log(&common::record(Level::Info, "file_name_template", format!("billing {:03} {}", i, "x".repeat(100))));
+    }
+    billing.flush_sync(5000).unwrap();
+    billing_eu.log(&common::record(Level::Info, "file_name_template", "eu last"));
+    billing_eu.flush_sync(2000).unwrap();
+
+    let names = names(&dir);
+    let (eu, own): (Vec<&String>, Vec<&String>) = names.iter().partition(|name| name.starts_with("billing-eu-"));
+    assert_eq!(eu.len(), 1, "{:?}", names);
+    let content = std::fs::read_to_string(dir.join(eu[0])).unwrap();
+    assert!(content.contains("eu first") && content.contains("eu last"), "{}", content);
+
+    // billing 轮转了多次，旧文件按总大小清理
+    assert!(own.len() >= 2, "{:?}", names);
+    for name in &own {
+        let timestamp = name.strip_prefix("billing-").unwrap().trim_end_matches(".log");
+        assert!(timestamp.len() >= 15 && timestamp.as_bytes()[8] == b'_', "{}", name);
+    }
+    let own_size: u64 = own.iter().map(|name| std::fs::metadata(dir.join(name)).unwrap().len()).sum();
+    assert!(own_size <= 2048 + 1024, "按总大小清理: {}", own_size);
+
+    drop((billing, billing_eu));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_seq_template_numbers_files_and_skips_existing() {
+    let dir = common::test_dir("file_name_template", "seq");
+    let config = FileConfig { app_id: Some("ingest".to_string()), ..rotating_config(&dir, "{app_id}.{seq}") };
+
+    let logger = LoggerBuilder::new().with_level(LevelFilter::Info).add_file(config.clone()).build();
+    for i in 0..100 {
+        logger.log(&common::record(Level::Info, "file_name_template", format!("ingest {:03} {}", i, "x".repeat(100))));
+    }
+    logger.flush_sync(5000).unwrap();
+    drop(logger);
+
+    let seqs = |names: &[String]| -> Vec<u64> {
+        let mut seqs: Vec<u64> = names.iter()
+            .map(|name| name.strip_prefix("ingest.").unwrap().strip_suffix(".log").unwrap().parse().unwrap())
+            .collect();
+        seqs.sort();
+        seqs
+    };
+    let first_run = seqs(&names(&dir));
+    let last = *first_run.last().unwrap();
+    assert!(last > 3, "应当轮转多次: {:?}", first_run);
+    assert!(!first_run.contains(&1), "最旧的文件应被清理: {:?}", first_run);
+    assert_eq!(first_run, (first_run[0]..=last).collect::<Vec<_>>());
+
+    // 重启后从下一个未使用的序号开始，不追加到已有文件
+    let logger = LoggerBuilder::new().with_level(LevelFilter::Info).add_file(config).build();
+    logger.log(&common::record(Level::Info, "file_name_template", "restarted"));
+    logger.flush_sync(2000).unwrap();
+    drop(logger);
+    let restarted = dir.join(format!("ingest.{}.log", last + 1));
+    assert!(std::fs::read_to_string(restarted).unwrap().contains("restarted"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_unresolvable_placeholders_rejected() {
+    let dir = common::test_dir("file_name_template", "invalid");
+    let config = |template: &str| FileConfig {
+        log_dir: dir.clone(),
+        file_name_template: Some(template.to_string()),
+        ..Default::default()
+    };
+
+    let err = LoggerBuilder::new().add_file(config("{app_id}-{timestamp}")).try_build().err().unwrap();
+    assert!(err.contains("{app_id}"), "{}", err);
+    let err = LoggerBuilder::new().add_file(config("{prefix}{date}")).try_build().err().unwrap();
+    assert!(err.contains("{date}"), "{}", err);
+    assert!(config("{prefix}fixed").validate().is_err());
+
+    // 日志器设置了应用ID时可以解析
+    let logger = LoggerBuilder::new().with_app_id("gateway").add_file(config("{app_id}-{timestamp}")).try_build().unwrap();
+    drop(logger);
+    assert!(names(&dir).iter().all(|name| name.starts_with("gateway-")), "{:?}", names(&dir));
+    let _ = std::fs::remove_dir_all(&dir);
+}
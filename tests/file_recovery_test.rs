@@ -0,0 +1,48 @@
+//! 日志目录恢复测试：运行期间删除日志目录后继续记录，日志应写入重新创建的目录
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, DiagnosticEvent};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+#[test]
+fn test_log_dir_recreated_after_removal() {
+    let log_dir = common::test_dir("recovery", "log_dir");
+
+    let (tx, rx) = channel();
+    let tx = std::sync::Mutex::new(tx);
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            check_path_interval_ms: 20,
+            ..Default::default()
+        })
+        .with_error_callback(move |event| {
+            let _ = tx.lock().unwrap().send(event.clone());
+        })
+        .build();
+
+    logger.log(&common::record(Level::Info, "file_recovery", "before removal"));
+    logger.flush_sync(2000).unwrap();
+    assert!(common::read_logs(&log_dir).contains("before removal"));
+
+    std::fs::remove_dir_all(&log_dir).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+
+    logger.log(&common::record(Level::Info, "file_recovery", "after removal"));
+    logger.flush_sync(2000).unwrap();
+
+    match rx.recv_timeout(Duration::from_secs(2)).expect("目录丢失应当触发诊断事件") {
+        DiagnosticEvent::LogFileRecreated { path, .. } => assert!(path.starts_with(&log_dir)),
+        other => panic!("意外的诊断事件: {:?}", other),
+    }
+
+    assert!(log_dir.exists(), "日志目录应当被重新创建");
+    let content = common::read_logs(&log_dir);
+    assert!(content.contains("after removal"), "新日志应写入重新创建的目录: {:?}", content);
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,74 @@
+//! 过滤追踪测试：被过滤的记录通过错误回调上报过滤它的环节，事件按秒限流
+
+mod common;
+
+use rat_logger::diagnostics::FILTER_TRACE_LIMIT;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{debug, DiagnosticEvent, FilterGate, Level, LevelFilter, Logger, LoggerBuilder, SamplingConfig};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+type Events = Arc<Mutex<Vec<(Level, String, FilterGate)>>>;
+
+/// 启用过滤追踪并收集 `RecordFiltered` 事件的构建器
+fn tracing_builder(level: LevelFilter) -> (LoggerBuilder, Events) {
+    let events: Events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let builder = LoggerBuilder::new()
+        .with_level(level)
+        .add_terminal_with_config(TermConfig::default())
+        .with_filter_tracing(true)
+        .with_error_callback(move |event| {
+            if let DiagnosticEvent::RecordFiltered { level, target, gate, .. } = event {
+                sink.lock().push((*level, target.clone(), gate.clone()));
+            }
+        });
+    (builder, events)
+}
+
+#[test]
+fn test_events_name_the_rejecting_gate() {
+    let (builder, events) = tracing_builder(LevelFilter::Debug);
+    let logger = builder
+        .with_sampling(SamplingConfig {
+            per_target: vec![("noisy".to_string(), 0.0)],
+            ..Default::default()
+        })
+        .build();
+
+    logger.log(&common::record(Level::Debug, "noisy::db", "message"));
+    logger.log(&common::record(Level::Trace, "app", "message"));
+    // 通过的记录和不在采样范围内的记录不产生事件
+    logger.log(&common::record(Level::Debug, "app", "message"));
+    logger.log(&common::record(Level::Info, "noisy::db", "message"));
+
+    assert_eq!(*events.lock(), [
+        (Level::Debug, "noisy::db".to_string(), FilterGate::Sampling { rule: Some("noisy".to_string()), ratio: 0.0 }),
+        (Level::Trace, "app".to_string(), FilterGate::LoggerLevel(LevelFilter::Debug)),
+    ]);
+}
+
+#[test]
+fn test_events_are_rate_limited() {
+    let (builder, events) = tracing_builder(LevelFilter::Info);
+    let logger = builder.build();
+
+    for _ in 0..500 {
+        logger.log(&common::record(Level::Debug, "app", "message"));
+    }
+
+    // 循环可能跨过一个窗口边界
+    let count = events.lock().len();
+    assert!(count >= FILTER_TRACE_LIMIT as usize && count <= 2 * FILTER_TRACE_LIMIT as usize, "{}", count);
+}
+
+#[test]
+fn test_macro_reports_global_max_level() {
+    let (builder, events) = tracing_builder(LevelFilter::Info);
+    builder.init_global_logger().unwrap();
+
+    debug!("filtered by max level");
+
+    let events = events.lock();
+    assert_eq!(*events, [(Level::Debug, module_path!().to_string(), FilterGate::MaxLevel(LevelFilter::Info))]);
+}
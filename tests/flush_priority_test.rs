@@ -0,0 +1,74 @@
+//! 刷新优先测试：处理器积压大量写入时立即刷新在一个批次内开始并确认，写入顺序不变
+
+mod common;
+
+use rat_logger::config::decode_record_versioned;
+use rat_logger::producer_consumer::{BatchConfig, LogProcessor, ProcessorError};
+use rat_logger::{FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const RECORDS: u64 = 10_000;
+
+/// 每条记录耗时 100µs 的处理器，按顺序保存记录序号和刷新时已处理的记录数
+struct SlowProcessor {
+    seen: Arc<Mutex<Vec<u64>>>,
+    flushes: Arc<Mutex<Vec<usize>>>,
+}
+
+impl LogProcessor for SlowProcessor {
+    fn name(&self) -> &'static str {
+        "slow"
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
+        std::thread::sleep(Duration::from_micros(100));
+        let (record, _) = decode_record_versioned(data).map_err(ProcessorError::decode)?;
+        self.seen.lock().push(record.args.parse().unwrap());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        self.flushes.lock().push(self.seen.lock().len());
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_flush_now_overtakes_backlog_without_reordering() {
+    let log_dir = std::env::temp_dir().join(format!("rat_logger_flush_priority_{}", std::process::id()));
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .build();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let flushes = Arc::new(Mutex::new(Vec::new()));
+    let batch = BatchConfig { batch_size: 100, batch_interval_ms: 50, ..BatchConfig::low_latency() };
+    logger.attach_processor(SlowProcessor { seen: seen.clone(), flushes: flushes.clone() }, batch).unwrap();
+    logger.detach_handler("file").unwrap();
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    for i in 0..RECORDS {
+        logger.log(&common::record(Level::Info, "flush_priority", i.to_string()));
+    }
+
+    // 积压约需 1 秒处理完，确认应在几个批次内到达
+    let start = Instant::now();
+    logger.flush_now(Duration::from_secs(5)).unwrap();
+    let waited = start.elapsed();
+    let processed = seen.lock().len() as u64;
+    assert!(waited < Duration::from_millis(300), "等待 {:?}", waited);
+    assert!(processed < RECORDS / 2, "确认时已处理 {} 条", processed);
+    assert!(flushes.lock().first().is_some_and(|&at| (at as u64) < RECORDS / 2), "{:?}", flushes.lock());
+
+    // 同步点仍等待全部积压，写入顺序不变
+    logger.sync_point(Duration::from_secs(10)).unwrap();
+    let seen = seen.lock();
+    assert_eq!(seen.len() as u64, RECORDS);
+    assert!(seen.iter().copied().eq(0..RECORDS));
+}
@@ -0,0 +1,93 @@
+//! 格式化分配测试：稳定状态下格式化和写出记录不产生额外的堆分配（只剩反序列化记录本身的分配）
+//!
+//! 使用计数全局分配器，因此单独放在一个测试文件中，且只包含一个测试。
+
+mod common;
+
+use rat_logger::config::{decode_record_versioned, encode_record_v2, Record};
+use rat_logger::producer_consumer::LogProcessor;
+use rat_logger::{FileConfig, FileProcessor, FormatConfig, Level};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const RECORDS: usize = 1000;
+
+/// 执行 `f` 期间的分配次数
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// 预热后处理同一批次的分配次数
+fn processing_allocations(processor: &mut FileProcessor, batch: &[Vec<u8>]) -> usize {
+    // 预热：输出缓冲和写入缓冲增长到稳定容量
+    for _ in 0..3 {
+        processor.process_batch(batch).unwrap();
+    }
+    allocations(|| processor.process_batch(batch).unwrap())
+}
+
+#[test]
+fn test_steady_state_formatting_does_not_allocate() {
+    let log_dir = common::test_dir("format_alloc", "steady_state");
+
+    let record = common::record(Level::Info, "alloc", "steady state message");
+    let encoded = encode_record_v2(&record).unwrap();
+    let batch = vec![encoded; RECORDS];
+
+    // 基准：只反序列化同一批次的分配次数
+    let decoding = allocations(|| {
+        let records: Vec<Record> = batch.iter()
+            .map(|data| decode_record_versioned(data).unwrap().0)
+            .collect();
+        drop(records);
+    });
+
+    // 自定义格式化函数本身不分配，测量的是处理器的缓冲管理
+    let mut processor = FileProcessor::new(FileConfig { log_dir: log_dir.join("custom"), ..Default::default() })
+        .with_formatter(|buf: &mut dyn Write, record: &Record| {
+            buf.write_all(record.args.as_bytes())?;
+            buf.write_all(b"\n")
+        });
+    let processing = processing_allocations(&mut processor, &batch);
+    drop(processor);
+    // 格式化和写出每条记录的额外分配接近于零（只允许每批次常数次）
+    assert!(processing <= decoding + 8, "处理 {} 次分配，反序列化 {} 次分配", processing, decoding);
+
+    // 默认格式模板（时间戳、级别、目标、位置和消息）直接展开到输出缓冲
+    let mut processor = FileProcessor::new(FileConfig {
+        log_dir: log_dir.join("template"),
+        format: Some(FormatConfig::default()),
+        ..Default::default()
+    });
+    let processing = processing_allocations(&mut processor, &batch);
+    drop(processor);
+    assert!(processing <= decoding + 8, "默认模板处理 {} 次分配，反序列化 {} 次分配", processing, decoding);
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,71 @@
+//! 格式模板检查测试：拼错的占位符、无法解析的时间戳格式和不配对的花括号在构建时报错，注册的自定义占位符可以使用
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder, TemplateError};
+use std::time::Duration;
+
+#[test]
+fn test_typo_placeholder_rejected() {
+    let error = FormatConfig::builder().template("{timestamp} [{level}] {mesage} {tagret}").build().unwrap_err();
+    assert_eq!(error, TemplateError::UnknownPlaceholders { unknown: vec!["mesage".to_string(), "tagret".to_string()] });
+    assert!(error.to_string().contains("{mesage}"), "{}", error);
+
+    // 终端和文件配置的验证执行同样的检查
+    let format = FormatConfig { format_template: "{level} {mesage}".to_string(), ..Default::default() };
+    let term = TermConfig { format: Some(format.clone()), ..Default::default() };
+    assert!(term.validate().unwrap_err().contains("{mesage}"));
+    let file = FileConfig { format: Some(format), ..Default::default() };
+    assert!(file.validate().unwrap_err().contains("{mesage}"));
+}
+
+#[test]
+fn test_bad_timestamp_and_braces_rejected() {
+    assert_eq!(
+        FormatConfig::builder().timestamp("%Y-%m-%d %Q").build().unwrap_err(),
+        TemplateError::InvalidTimestamp { format: "%Y-%m-%d %Q".to_string() }
+    );
+    assert!(matches!(
+        FormatConfig::builder().template("{level} {message").build(),
+        Err(TemplateError::UnbalancedBrace { position: 8, .. })
+    ));
+    assert!(matches!(FormatConfig::builder().template("{level}} {message}").build(), Err(TemplateError::UnbalancedBrace { .. })));
+    assert_eq!(FormatConfig::builder().template("").build().unwrap_err(), TemplateError::EmptyTemplate);
+}
+
+#[test]
+fn test_custom_placeholders_render() {
+    let format = FormatConfig::builder()
+        .template("{level} {request_id}{?tenant} tenant={tenant}{/tenant} {message}")
+        .timestamp("%H:%M:%S")
+        .placeholder("request_id")
+        .placeholder("tenant")
+        .build()
+        .unwrap();
+    assert_eq!(format.custom_placeholders, ["request_id", "tenant"]);
+    // 未注册时同样的模板被拒绝
+    let unregistered = FormatConfig { custom_placeholders: Vec::new(), ..format.clone() };
+    assert!(unregistered.validate().is_err());
+
+    let log_dir = common::test_dir("format_template", "custom_placeholders");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig { log_dir: log_dir.clone(), format: Some(format), ..Default::default() })
+        .build();
+    logger.log(&Record {
+        fields: vec![("request_id".to_string(), "req-42".to_string()), ("tenant".to_string(), "acme".to_string())],
+        ..common::record(Level::Info, "format_template", "served")
+    });
+    logger.sync_point(Duration::from_secs(2)).unwrap();
+
+    let content: String = std::fs::read_dir(&log_dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| std::fs::read_to_string(entry.path()).unwrap_or_default())
+        .collect();
+    assert!(content.contains("INFO req-42 tenant=acme served"), "{}", content);
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
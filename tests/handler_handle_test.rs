@@ -0,0 +1,107 @@
+//! 处理器句柄测试：按名称取得单个处理器，运行中修改批量间隔后刷新节奏随之改变；只刷新文件处理器时 UDP 处理器的缓冲不受影响
+
+mod common;
+
+use rat_logger::core::LoggerCore;
+use rat_logger::producer_consumer::BatchConfig;
+use rat_logger::test_util::UdpCollector;
+use rat_logger::{HandlerOptions, Level, LevelFilter, Logger, LoggerBuilder, ProbeMarker, UdpConfig};
+use std::time::Duration;
+
+fn log(logger: &LoggerCore, message: &str) {
+    logger.log(&common::record(Level::Info, "handler_handle", message));
+}
+
+#[test]
+fn test_batch_interval_changes_at_runtime() {
+    let log_dir = common::test_dir("handler_handle", "interval");
+    // 文件处理器的写入在缓冲区中，用管道探测的延迟观察批次的处理节奏
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_batch_config(BatchConfig { batch_size: 10_000, batch_interval_ms: 1000, ..Default::default() })
+        .with_pipeline_probe(Duration::from_millis(50), ProbeMarker::Suppressed)
+        .add_file(common::file_config(&log_dir, Some("{message}")))
+        .build();
+    assert_eq!(logger.handler_names(), vec!["file_processor#0".to_string()]);
+    let handle = logger.handler("file_processor#0").unwrap();
+    assert!(logger.handler("file_processor#1").is_none());
+
+    log(&logger, "slow");
+    std::thread::sleep(Duration::from_millis(1500));
+    let slow = logger.probe_reports()[0];
+    assert!(slow.max_latency_us >= 500_000, "{:?}", slow);
+
+    let fast = BatchConfig { batch_interval_ms: 10, ..handle.batch_config().unwrap() };
+    handle.set_batch_config(fast.clone()).unwrap();
+    assert_eq!(handle.batch_config().unwrap().batch_interval_ms, 10);
+    std::thread::sleep(Duration::from_millis(100));
+    // 新的间隔生效后，每次探测都在约 10 毫秒内处理
+    for _ in 0..5 {
+        std::thread::sleep(Duration::from_millis(60));
+        let report = logger.probe_reports()[0];
+        assert!(report.probes > slow.probes && report.last_latency_us < 100_000, "{:?}", report);
+    }
+
+    let invalid = BatchConfig { batch_size: 0, ..fast };
+    assert!(handle.set_batch_config(invalid).is_err());
+
+    logger.flush_sync(5000).unwrap();
+    assert_eq!(common::read_logs(&log_dir), "slow\n");
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_flush_single_handler_leaves_others_buffered() {
+    let log_dir = common::test_dir("handler_handle", "single");
+    let collector = UdpCollector::bind().unwrap();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_batch_config(BatchConfig { batch_size: 10_000, batch_interval_ms: 60_000, ..Default::default() })
+        .add_file_with_options(common::file_config(&log_dir, Some("{message}")), HandlerOptions::new().name("audit"))
+        .add_udp_with_options(UdpConfig { network_config: collector.network_config(), ..Default::default() }, HandlerOptions::new())
+        .build();
+    assert_eq!(logger.handler_names(), vec!["audit".to_string(), "udp_processor#0".to_string()]);
+
+    for i in 0..10 {
+        log(&logger, &format!("record {}", i));
+    }
+    let audit = logger.handler("audit").unwrap();
+    audit.flush_sync(Duration::from_secs(5)).unwrap();
+    assert_eq!(common::read_logs(&log_dir).lines().count(), 10);
+    assert_eq!(audit.queue_len(), 0);
+    // UDP 处理器的记录仍在批次缓冲中
+    assert!(!collector.wait_for(1, Duration::from_millis(200)));
+
+    logger.handler("udp_processor#0").unwrap().flush_sync(Duration::from_secs(5)).unwrap();
+    assert!(collector.wait_for(10, Duration::from_secs(5)));
+
+    // 移除后句柄失效，名称不会被复用
+    let udp = logger.handler("udp_processor#0").unwrap();
+    udp.clone().detach().unwrap();
+    assert_eq!(logger.handler_names(), vec!["audit".to_string()]);
+    assert!(udp.flush_sync(Duration::from_secs(1)).is_err());
+    assert!(udp.batch_config().is_err());
+
+    // 名称重复和空名称在构建时报错
+    let (dir_a, dir_b) = (common::test_dir("handler_handle", "duplicate_a"), common::test_dir("handler_handle", "duplicate_b"));
+    let duplicate = LoggerBuilder::new()
+        .add_file_with_options(common::file_config(&dir_a, Some("{message}")), HandlerOptions::new().name("audit"))
+        .add_file_with_options(common::file_config(&dir_b, Some("{message}")), HandlerOptions::new().name("audit"))
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(duplicate.contains("处理器名称重复"), "{}", duplicate);
+    let empty = LoggerBuilder::new()
+        .add_file_with_options(common::file_config(&dir_a, Some("{message}")), HandlerOptions::new().name(" "))
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(empty.contains("处理器名称不能为空"), "{}", empty);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+    let _ = std::fs::remove_dir_all(&dir_a);
+    let _ = std::fs::remove_dir_all(&dir_b);
+}
@@ -0,0 +1,44 @@
+//! 空闲 CPU 测试：挂接终端和文件处理器的日志器在没有记录时几乎不消耗 CPU 时间（读取 `/proc/self/stat`，仅 Linux）
+//!
+//! 测试独占一个测试进程，进程的 CPU 时间只来自日志器的工作线程和测试主线程。
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{FileConfig, HandlerOptions, Level, LevelFilter, Logger, LoggerBuilder};
+use std::time::Duration;
+
+/// 进程累计的用户态和内核态 CPU 时间（时钟节拍，通常每节拍 10 毫秒）
+fn cpu_ticks() -> u64 {
+    let stat = std::fs::read_to_string("/proc/self/stat").unwrap();
+    // 进程名可能包含空格，从最后一个右括号之后开始按字段解析：utime 和 stime 是第 14、15 个字段
+    let fields: Vec<&str> = stat[stat.rfind(')').unwrap() + 2..].split(' ').collect();
+    fields[11].parse::<u64>().unwrap() + fields[12].parse::<u64>().unwrap()
+}
+
+#[test]
+fn test_idle_logger_accrues_no_cpu_time() {
+    let log_dir = common::test_dir("idle_cpu", "idle_logger");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_stall_threshold(Duration::from_secs(1))
+        // 终端只接收 Error，测试不输出到标准输出
+        .add_terminal_with_options(TermConfig::default(), HandlerOptions::new().levels(&[Level::Error]))
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .build();
+
+    for i in 0..100 {
+        logger.log(&common::record(Level::Info, "idle_cpu", format!("record {}", i)));
+    }
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    let before = cpu_ticks();
+    std::thread::sleep(Duration::from_secs(2));
+    let spent = cpu_ticks() - before;
+    // 2 秒空闲最多允许约 50 毫秒的 CPU 时间
+    assert!(spent <= 5, "空闲 2 秒消耗了 {} 个时钟节拍", spent);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,60 @@
+//! 并发初始化测试：多个线程同时初始化全局日志器时只有一个成功，其余得到类型化的错误且不启动工作线程
+
+mod common;
+
+use rat_logger::core::{lock_global_logger, SetLoggerError};
+use rat_logger::{info, FileConfig, FormatConfig, LevelFilter, LoggerBuilder};
+use std::sync::{Arc, Barrier};
+
+const THREADS: usize = 8;
+
+#[test]
+fn test_concurrent_init_installs_exactly_one_logger() {
+    let base = common::test_dir("init_race", "concurrent_init");
+
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let barrier = barrier.clone();
+            let log_dir = base.join(i.to_string());
+            std::thread::spawn(move || {
+                let builder = LoggerBuilder::new()
+                    .with_level(LevelFilter::Info)
+                    .add_file(FileConfig {
+                        log_dir,
+                        format: Some(FormatConfig { format_template: "{message}".to_string(), ..Default::default() }),
+                        ..Default::default()
+                    });
+                barrier.wait();
+                (i, builder.init_global_logger())
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+
+    let winners: Vec<usize> = results.iter().filter(|(_, result)| result.is_ok()).map(|(i, _)| *i).collect();
+    assert_eq!(winners.len(), 1);
+    for (_, result) in results.iter().filter(|(_, result)| result.is_err()) {
+        assert_eq!(result, &Err(SetLoggerError::AlreadyInitialized));
+    }
+
+    // 落后的线程没有创建文件处理器，只有获胜者的日志目录存在
+    let dirs: Vec<_> = std::fs::read_dir(&base).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+    assert_eq!(dirs, [winners[0].to_string()]);
+
+    let logger = lock_global_logger().clone().unwrap();
+    assert_eq!(logger.handler_types(), ["file_processor"]);
+    assert_eq!(logger.processor_configs().len(), 1);
+
+    info!("after race");
+    logger.flush_sync(2000).unwrap();
+    let log_dir = base.join(winners[0].to_string());
+    let content: String = std::fs::read_dir(&log_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect();
+    assert_eq!(content, "after race\n");
+    let _ = std::fs::remove_dir_all(&base);
+}
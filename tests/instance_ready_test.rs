@@ -0,0 +1,51 @@
+//! 实例日志器就绪测试：不设为全局日志器时，构建后立即记录的日志也不会丢失
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+
+type Capture = Arc<Mutex<Vec<String>>>;
+
+fn capture_builder() -> (LoggerBuilder, Capture) {
+    let captured: Capture = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let builder = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.args.to_string()))),
+            ..Default::default()
+        });
+    (builder, captured)
+}
+
+#[test]
+fn test_instance_logger_is_ready_after_build() {
+    // 同时构建多个实例，就绪检查互不干扰
+    let loggers: Vec<_> = (0..4).map(|_| capture_builder()).map(|(builder, captured)| (builder.build(), captured)).collect();
+
+    for (logger, captured) in &loggers {
+        assert!(logger.processor_manager().wait_ready(0).is_ok());
+        for i in 0..100 {
+            logger.log(&common::record(Level::Info, "instance_ready", format!("message {}", i)));
+        }
+        logger.flush_sync(2000).unwrap();
+        assert_eq!(captured.lock().len(), 100);
+    }
+}
+
+#[test]
+fn test_ready_wait_can_be_deferred() {
+    let (builder, captured) = capture_builder();
+    let logger = builder.with_ready_timeout(Duration::ZERO).build();
+    logger.wait_for_workers_ready(5000).unwrap();
+
+    logger.log(&common::record(Level::Info, "instance_ready", "deferred"));
+    logger.flush_sync(2000).unwrap();
+    assert_eq!(*captured.lock(), ["deferred"]);
+}
@@ -0,0 +1,92 @@
+//! 内部记录测试：诊断回调把事件写回日志器时不会形成反馈循环，处理器可以不接收 rat_logger 自身产生的记录
+
+mod common;
+
+use rat_logger::config::{Metadata, Record, SamplingConfig};
+use rat_logger::core::Logger;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{scoped, warn, BreakerAction, BreakerConfig, DurationMs, Level, LevelFilter, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+type Capture = Arc<Mutex<Vec<(String, String)>>>;
+
+fn capture_terminal(include_internal: bool) -> (TermConfig, Capture) {
+    let captured: Capture = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let config = TermConfig {
+        enable_color: false,
+        include_internal,
+        on_record: Some(Arc::new(move |record: &Record| {
+            sink.lock().push((record.metadata.target.clone(), record.args.to_string()));
+        })),
+        ..Default::default()
+    };
+    (config, captured)
+}
+
+#[test]
+fn test_internal_metadata_detection() {
+    let metadata = |target: &str| Metadata { target: target.to_string(), ..Default::default() };
+    assert!(metadata("rat_logger").is_internal());
+    assert!(metadata("rat_logger::core").is_internal());
+    assert!(!metadata("rat_logger_server").is_internal());
+    assert!(!metadata("app::rat_logger").is_internal());
+}
+
+#[test]
+fn test_diagnostics_routed_back_do_not_loop() {
+    let (all, all_captured) = capture_terminal(true);
+    let (external, external_captured) = capture_terminal(false);
+    let callbacks = Arc::new(AtomicUsize::new(0));
+    let counter = callbacks.clone();
+
+    // 熔断、采样和过滤追踪同时启用，回调把每个诊断事件写回当前日志器
+    let logger: Arc<dyn Logger> = Arc::new(
+        LoggerBuilder::new()
+            .with_level(LevelFilter::Debug)
+            .add_terminal_with_config(all)
+            .add_terminal_with_config(external)
+            .with_circuit_breaker(BreakerConfig::new(100, BreakerAction::DropWithSummary).with_recovery(50, DurationMs::ms(200)))
+            .with_sampling(SamplingConfig { default_ratio: 0.5, seed: Some(7), ..Default::default() })
+            .with_filter_tracing(true)
+            .with_error_callback(move |event| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                warn!("diagnostic: {}", event);
+            })
+            .build(),
+    );
+
+    scoped::with_logger(logger.clone(), || {
+        for round in 0..3 {
+            for _ in 0..500 {
+                logger.log(&common::record(Level::Info, "app", "burst"));
+                logger.log(&common::record(Level::Debug, "app", "sampled"));
+            }
+            if round < 2 {
+                std::thread::sleep(Duration::from_millis(1100));
+            }
+        }
+    });
+    logger.flush_sync(2000).unwrap();
+
+    let stats = logger.runtime_stats().unwrap();
+    assert_eq!(stats.breaker_trips(), 1);
+
+    // 回调产生的记录不经过熔断和采样，也不再引发新的事件：每个事件恰好对应一条记录
+    let all_captured = all_captured.lock();
+    let routed = all_captured.iter().filter(|(_, message)| message.starts_with("diagnostic: ")).count();
+    let callbacks = callbacks.load(Ordering::Relaxed);
+    assert_eq!(routed, callbacks);
+    // 熔断事件加上每秒最多 20 个过滤追踪事件
+    assert!(callbacks <= 2 + 20 * 4, "诊断事件过多: {}", callbacks);
+
+    // 熔断汇总是 rat_logger 自身的记录，只有接收内部记录的处理器能看到
+    let summaries = all_captured.iter().filter(|(target, _)| target.starts_with("rat_logger::")).count();
+    assert!((1..=3).contains(&summaries), "熔断汇总数: {}", summaries);
+    let external_captured = external_captured.lock();
+    assert!(external_captured.iter().all(|(target, _)| !target.starts_with("rat_logger")));
+    assert_eq!(external_captured.len(), all_captured.len() - summaries);
+}
@@ -0,0 +1,86 @@
+//! 记录 JSON 结构测试：固定输出的键和顺序，认证令牌脱敏，空的可选字段不输出，离线工具可读回
+
+mod common;
+
+use rat_logger::config::{JsonRecord, Metadata, Record, ThreadStamp};
+use rat_logger::tools::{reformat, InputKind};
+use rat_logger::{FormatConfig, Level};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+fn full_record() -> Record {
+    Record {
+        metadata: Arc::new(Metadata {
+            level: Level::Error,
+            target: "app::db".to_string(),
+            auth_token: Some("secret-token".to_string()),
+            app_id: Some("svc".to_string()),
+        }),
+        args: "query \"users\" failed".into(),
+        module_path: Some("app::db".to_string()),
+        file: Some("src/db.rs".to_string()),
+        line: Some(42),
+        fields: vec![("user".to_string(), "42".to_string()), ("attempt".to_string(), "3".to_string())],
+        timestamp: Some(UNIX_EPOCH + Duration::new(1_767_323_045, 678_000_000)),
+        seq: Some(9),
+        thread: Some(ThreadStamp { id: 2, seq: 5 }),
+        level_label: Some("FATAL".to_string()),
+        record_id: Some(0x1f2e_0042_0000_0007),
+    }
+}
+
+fn minimal_record() -> Record {
+    common::record(Level::Info, "app", "ready")
+}
+
+#[test]
+fn test_json_schema_snapshot() {
+    assert_eq!(
+        serde_json::to_string(&JsonRecord::from(&full_record())).unwrap(),
+        concat!(
+            r#"{"timestamp":"2026-01-02T03:04:05.678Z","level":"ERROR","label":"FATAL","target":"app::db","#,
+            r#""message":"query \"users\" failed","module":"app::db","file":"src/db.rs","line":42,"app_id":"svc","#,
+            r#""auth_token":"***","seq":9,"tid":2,"tseq":5,"record_id":"1f2e004200000007","fields":{"user":"42","attempt":"3"}}"#,
+        )
+    );
+    // 空的可选字段和空的结构化字段不输出
+    assert_eq!(
+        serde_json::to_string(&JsonRecord::from(&minimal_record())).unwrap(),
+        r#"{"level":"INFO","target":"app","message":"ready"}"#
+    );
+}
+
+#[test]
+fn test_record_serialize_matches_json_record() {
+    for record in [full_record(), minimal_record()] {
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(json, serde_json::to_string(&JsonRecord::from(&record)).unwrap());
+        assert!(!json.contains("secret-token"));
+    }
+
+    let value = serde_json::to_value(JsonRecord::from(&minimal_record()).with_process_info()).unwrap();
+    assert_eq!(value["pid"], std::process::id());
+    assert_eq!(value["hostname"].as_str(), rat_logger::process_info::hostname());
+}
+
+#[test]
+fn test_tools_read_back_json_records() {
+    let path = std::env::temp_dir().join(format!("rat_logger_json_record_{}.ndjson", std::process::id()));
+    let lines: Vec<String> = [full_record(), minimal_record()].iter().map(|r| serde_json::to_string(r).unwrap()).collect();
+    std::fs::write(&path, lines.join("\n")).unwrap();
+
+    let format = FormatConfig {
+        format_template: "{level}|{target}|{message}|{file}:{line}|{app_id}|{seq}|{tid}/{tseq}|{record_id}|{fields}".to_string(),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    let summary = reformat(&path, InputKind::Ndjson, &format, &mut output).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!((summary.records, summary.unparsed), (2, 0));
+    let output = String::from_utf8(output).unwrap();
+    let mut rendered = output.lines();
+    // 离线工具按键名顺序读回结构化字段
+    assert_eq!(rendered.next(), Some("ERROR|app::db|query \"users\" failed|src/db.rs:42|svc|9|2/5|1f2e004200000007|attempt=3 user=42"));
+    assert!(rendered.next().unwrap().starts_with("INFO|app|ready|unknown:0||"));
+}
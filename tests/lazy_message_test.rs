@@ -0,0 +1,103 @@
+//! 延迟消息测试：消息闭包只在记录通过级别过滤和采样后调用，且只调用一次，在日志器的后台线程中执行
+
+mod common;
+
+use rat_logger::config::RecordArgs;
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, SamplingConfig};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 调用时计数并记下所在线程名的延迟消息
+fn counted(calls: &Arc<AtomicUsize>, threads: &Arc<Mutex<Vec<String>>>, text: &str) -> RecordArgs {
+    let (calls, threads, text) = (calls.clone(), threads.clone(), text.to_string());
+    RecordArgs::lazy(move || {
+        calls.fetch_add(1, Ordering::SeqCst);
+        threads.lock().unwrap().push(std::thread::current().name().unwrap_or_default().to_string());
+        text
+    })
+}
+
+#[test]
+fn test_lazy_message_runs_once_on_worker_for_surviving_records() {
+    let (dir_a, dir_b) = (common::test_dir("lazy", "surviving_a"), common::test_dir("lazy", "surviving_b"));
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        .with_sampling(SamplingConfig {
+            default_ratio: 0.0,
+            per_target: Vec::new(),
+            levels: LevelFilter::Debug,
+            annotate: false,
+            seed: Some(1),
+        })
+        .add_file(common::file_config(&dir_a, Some("{level} {message}")))
+        .add_file(common::file_config(&dir_b, Some("{level} {message}")))
+        .build();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let threads = Arc::new(Mutex::new(Vec::new()));
+
+    // 级别过滤丢弃
+    logger.log(&common::record(Level::Trace, "lazy", counted(&calls, &threads, "filtered")));
+    // 采样丢弃
+    logger.log(&common::record(Level::Debug, "lazy", counted(&calls, &threads, "sampled out")));
+    logger.log(&common::record(Level::Info, "lazy", counted(&calls, &threads, "kept")));
+
+    logger.sync_point(Duration::from_secs(2)).unwrap();
+    let (content_a, content_b) = (common::read_logs(&dir_a), common::read_logs(&dir_b));
+    let _ = std::fs::remove_dir_all(&dir_a);
+    let _ = std::fs::remove_dir_all(&dir_b);
+
+    // 两个处理器共用一次生成的消息，被过滤的记录不调用闭包
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(*threads.lock().unwrap(), vec!["rat-lazy-message".to_string()]);
+    assert_eq!(content_a.lines().collect::<Vec<_>>(), vec!["INFO kept"]);
+    assert_eq!(content_b.lines().collect::<Vec<_>>(), vec!["INFO kept"]);
+}
+
+#[test]
+fn test_lazy_message_keeps_thread_order() {
+    let log_dir = common::test_dir("lazy", "thread_order");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(common::file_config(&log_dir, Some("{message}")))
+        .build();
+
+    // 生成较慢的延迟消息之后的普通记录不会越过它
+    let slow = RecordArgs::lazy(|| {
+        std::thread::sleep(Duration::from_millis(100));
+        "first".to_string()
+    });
+    logger.log(&common::record(Level::Info, "lazy", slow));
+    logger.log(&common::record(Level::Info, "lazy", "second"));
+    logger.log(&common::record(Level::Info, "lazy", RecordArgs::lazy(|| "third".to_string())));
+
+    logger.sync_point(Duration::from_secs(2)).unwrap();
+    let lines = common::log_lines(&log_dir, "");
+    let _ = std::fs::remove_dir_all(&log_dir);
+    assert_eq!(lines, vec!["first", "second", "third"]);
+}
+
+#[test]
+fn test_lazy_macros_skip_filtered_levels() {
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(rat_logger::handler::term::TermConfig::default())
+        .init_global_logger()
+        .unwrap();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    rat_logger::debug_lazy!(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        "debug".to_string()
+    });
+    let counter = calls.clone();
+    rat_logger::info_lazy!(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        format!("info #{}", 1)
+    });
+    rat_logger::sync_point(Duration::from_secs(2)).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
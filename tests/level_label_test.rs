@@ -0,0 +1,101 @@
+//! 级别别名测试：`fatal!`/`audit!` 按所在级别过滤，输出时显示别名，别名经 UDP 数据包传递，颜色可按别名配置
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::core::Logger;
+use rat_logger::handler::term::{format_with_color, TermConfig};
+use rat_logger::udp_helper::UdpPacketHelper;
+use rat_logger::{audit, error, fatal, info, scoped, ColorConfig, FileConfig, FormatConfig, Level, LevelFilter, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+fn format(template: &str) -> FormatConfig {
+    FormatConfig {
+        format_template: template.to_string(),
+        ..Default::default()
+    }
+}
+
+/// 读取目录中所有日志文件的日志行（跳过文件头）
+fn file_lines(log_dir: &std::path::Path) -> Vec<String> {
+    let mut lines = Vec::new();
+    for entry in std::fs::read_dir(log_dir).unwrap() {
+        let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        lines.extend(content.lines().filter(|l| l.contains('|')).map(str::to_string));
+    }
+    lines
+}
+
+#[test]
+fn test_aliases_render_label_and_filter_by_level() {
+    let log_dir = common::test_dir("level_label", "aliases_render");
+
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let logger: Arc<dyn Logger> = Arc::new(
+        LoggerBuilder::new()
+            .with_level(LevelFilter::Warn)
+            .add_terminal_with_config(TermConfig {
+                enable_color: false,
+                on_record: Some(Arc::new(move |record: &Record| {
+                    sink.lock().push((record.metadata.level, record.level_label.clone()));
+                })),
+                ..Default::default()
+            })
+            .add_file(FileConfig {
+                log_dir: log_dir.clone(),
+                format: Some(format("{level}|{message}")),
+                ..Default::default()
+            })
+            .build(),
+    );
+
+    scoped::with_logger(logger.clone(), || {
+        fatal!("disk {} gone", "/dev/sda");
+        // Warn 级别下 audit! 与 info! 一样被过滤
+        audit!("user login");
+        info!("plain info");
+        error!("plain error");
+    });
+    logger.flush_sync(2000).unwrap();
+
+    assert_eq!(file_lines(&log_dir), ["FATAL|disk /dev/sda gone", "ERROR|plain error"]);
+    // 过滤和处理器看到的级别仍是 Error
+    assert_eq!(*captured.lock(), [(Level::Error, Some("FATAL".to_string())), (Level::Error, None)]);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+fn labeled(level: Level, label: &str) -> Record {
+    Record {
+        level_label: Some(label.to_string()),
+        ..common::record(level, "audit", "granted")
+    }
+}
+
+#[test]
+fn test_label_survives_udp_packet() {
+    let record = labeled(Level::Info, "AUDIT");
+    let packet = UdpPacketHelper::encode_record(&record, None, Some("app".to_string())).unwrap();
+    let restored = UdpPacketHelper::net_record_to_record(&UdpPacketHelper::decode_packet(&packet).unwrap());
+    assert_eq!(restored.metadata.level, Level::Info);
+    assert_eq!(restored.level_name(), "AUDIT");
+}
+
+#[test]
+fn test_label_color_defaults_to_level_color() {
+    let colors = ColorConfig {
+        label_colors: vec![("AUDIT".to_string(), "\x1b[35m".to_string())],
+        ..Default::default()
+    };
+    let render = |record: &Record| {
+        let mut buf = Vec::new();
+        format_with_color(&mut buf, record, &format("{level}"), &colors).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+
+    assert_eq!(render(&labeled(Level::Info, "AUDIT")), "\x1b[35mAUDIT\x1b[0m\n");
+    assert_eq!(render(&labeled(Level::Error, "FATAL")), format!("{}FATAL\x1b[0m\n", colors.error));
+}
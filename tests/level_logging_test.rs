@@ -109,10 +109,11 @@ fn log_with_logger(logger: &dyn Logger, level: Level, message: &str) {
             auth_token: None,
             app_id: Some("level_app".to_string()),
         }),
-        args: message.to_string(),
+        args: message.into(),
         module_path: Some("level_logging_example".to_string()),
         file: Some("level_logging_example.rs".to_string()),
         line: Some(140),
+        ..Default::default()
     };
     logger.log(&record);
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+//! 处理器级别集合测试：两个文件处理器使用不相交的级别集合，混合负载下每个文件只包含自己的级别
+
+mod common;
+
+use rat_logger::{FileConfig, HandlerOptions, Level, LevelFilter, LevelMask, LoggerBuilder};
+use std::time::Duration;
+
+/// 内容中带标记的记录行的级别标记
+fn tags(content: &str) -> Vec<&str> {
+    content.lines().filter_map(|line| line.split("mask-").nth(1)?.split('-').next()).collect()
+}
+
+#[test]
+fn test_disjoint_masks_split_levels_between_files() {
+    let base = common::test_dir("level_mask", "disjoint_masks");
+    let (alerts_dir, access_dir) = (base.join("alerts"), base.join("access"));
+
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        .add_file_with_options(
+            FileConfig { log_dir: alerts_dir.clone(), ..Default::default() },
+            HandlerOptions::new().level_mask(LevelMask::at_least(Level::Warn)),
+        )
+        .add_file_with_options(
+            FileConfig { log_dir: access_dir.clone(), ..Default::default() },
+            HandlerOptions::new().levels(&[Level::Info]),
+        )
+        .init_global_logger()
+        .unwrap();
+
+    for i in 0..50 {
+        rat_logger::error!("mask-error-{}", i);
+        rat_logger::warn!("mask-warn-{}", i);
+        rat_logger::info!("mask-info-{}", i);
+        rat_logger::debug!("mask-debug-{}", i);
+        rat_logger::trace!("mask-trace-{}", i);
+    }
+    rat_logger::sync_point(Duration::from_secs(5)).unwrap();
+
+    let alerts = common::read_logs(&alerts_dir);
+    let alert_tags = tags(&alerts);
+    assert_eq!(alert_tags.iter().filter(|tag| **tag == "error").count(), 50, "{}", alerts);
+    assert_eq!(alert_tags.iter().filter(|tag| **tag == "warn").count(), 50, "{}", alerts);
+    assert_eq!(alert_tags.len(), 100, "{}", alerts);
+
+    let access = common::read_logs(&access_dir);
+    let access_tags = tags(&access);
+    assert_eq!(access_tags.len(), 50, "{}", access);
+    assert!(access_tags.iter().all(|tag| *tag == "info"), "{}", access);
+
+    let _ = std::fs::remove_dir_all(&base);
+}
+
+#[test]
+fn test_mask_constructors_and_serde() {
+    assert_eq!(LevelMask::at_least(Level::Warn), LevelMask::only(&[Level::Error, Level::Warn]));
+    assert_eq!(LevelMask::at_least(Level::Trace), LevelMask::ALL);
+    assert!(!LevelMask::only(&[Level::Info]).contains(Level::Error));
+    assert_eq!(LevelMask::ALL.without(Level::Error).levels().count(), 4);
+    assert_eq!(LevelMask::only(&[Level::Info, Level::Error]).to_string(), "[ERROR, INFO]");
+
+    let options: HandlerOptions = serde_json::from_str(r#"{"levels": ["Warn", "Error"]}"#).unwrap();
+    assert_eq!(options.effective_levels(), LevelMask::at_least(Level::Warn));
+    assert_eq!(serde_json::to_string(&options).unwrap(), r#"{"levels":["Error","Warn"]}"#);
+    let default: HandlerOptions = serde_json::from_str("{}").unwrap();
+    assert_eq!(default.effective_levels(), LevelMask::ALL);
+
+    // 空集合的处理器使构建失败
+    let result = LoggerBuilder::new()
+        .add_file_with_options(FileConfig::default(), HandlerOptions::new().levels(&[]))
+        .try_build();
+    assert!(result.err().unwrap().contains("级别集合为空"));
+}
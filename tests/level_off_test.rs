@@ -0,0 +1,88 @@
+//! `LevelFilter::Off` 测试：所有日志宏（包括 `error!` 和 `emergency!`）都不产生处理器活动
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger};
+use rat_logger::config::Record;
+use rat_logger::handler::term::TermConfig;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 每写入一条记录计数一次的终端配置
+fn counting_terminal(calls: &Arc<AtomicUsize>) -> TermConfig {
+    let calls = calls.clone();
+    TermConfig {
+        enable_color: false,
+        on_record: Some(Arc::new(move |_: &Record| {
+            calls.fetch_add(1, Ordering::SeqCst);
+        })),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_off_silences_all_macros() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Off)
+        .add_terminal_with_config(counting_terminal(&calls))
+        .init_global_logger()
+        .unwrap();
+
+    let formatted = Arc::new(AtomicUsize::new(0));
+    let counter = formatted.clone();
+    let message = move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        "expensive".to_string()
+    };
+
+    rat_logger::error!("error {}", 1);
+    rat_logger::warn!("warn");
+    rat_logger::info!("info");
+    rat_logger::debug!("debug");
+    rat_logger::trace!("trace");
+    rat_logger::error_lazy!(message);
+    rat_logger::emergency!("emergency {}", 2);
+    rat_logger::startup_log!("startup");
+    rat_logger::flush_logs!();
+
+    assert_eq!(formatted.load(Ordering::SeqCst), 0, "被过滤的延迟消息不应生成");
+    assert_eq!(calls.load(Ordering::SeqCst), 0, "级别为 Off 时处理器不应收到任何记录");
+}
+
+#[test]
+fn test_set_level_off_stops_output_immediately() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(counting_terminal(&calls))
+        .build();
+
+    logger.log(&common::record(Level::Info, "level_off", "before"));
+    logger.flush_sync(2000).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    logger.set_level(LevelFilter::Off);
+    assert_eq!(logger.level(), LevelFilter::Off);
+    logger.log(&common::record(Level::Error, "level_off", "after"));
+    logger.emergency_log(&common::record(Level::Error, "level_off", "emergency after"));
+    logger.flush_sync(2000).unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1, "set_level(Off) 之后的记录不应输出");
+}
+
+#[test]
+fn test_emergency_when_off_opt_in() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Off)
+        .with_emergency_when_off(true)
+        .add_terminal_with_config(counting_terminal(&calls))
+        .build();
+
+    logger.log(&common::record(Level::Error, "level_off", "normal error"));
+    logger.emergency_log(&common::record(Level::Error, "level_off", "emergency"));
+    logger.flush_sync(2000).unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1, "只有紧急日志应当输出");
+}
@@ -0,0 +1,54 @@
+//! 级别枚举测试：`ALL`/`iter()` 按严重程度排列，级别访问器对每个级别返回配置的字符串
+
+use rat_logger::{ColorConfig, FormatConfig, Level, LevelFilter, LevelStyle};
+use rat_logger::config::LevelTemplates;
+
+#[test]
+fn test_iterators_follow_severity() {
+    assert_eq!(Level::iter().collect::<Vec<_>>(), Level::ALL);
+    assert_eq!(LevelFilter::iter().collect::<Vec<_>>(), LevelFilter::ALL);
+    // 级别越详细，放行它所需的过滤级别越高
+    assert!(LevelFilter::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    for (index, level) in Level::iter().enumerate() {
+        assert_eq!(level.to_level_filter(), LevelFilter::ALL[index + 1]);
+        assert_eq!(Level::from_usize(level.to_level_filter() as usize), Some(level));
+        assert_eq!(level.as_str(), level.to_string());
+    }
+    assert_eq!(Level::from_usize(0), None);
+    assert_eq!(Level::from_usize(6), None);
+}
+
+#[test]
+fn test_level_accessors_return_configured_strings() {
+    let style = LevelStyle {
+        error: "E".to_string(),
+        warn: "W".to_string(),
+        info: "I".to_string(),
+        debug: "D".to_string(),
+        trace: "T".to_string(),
+    };
+    let colors = ColorConfig {
+        error: "c1".to_string(),
+        warn: "c2".to_string(),
+        info: "c3".to_string(),
+        debug: "c4".to_string(),
+        trace: "c5".to_string(),
+        ..Default::default()
+    };
+    let templates = LevelTemplates {
+        error: Some("error {message}".to_string()),
+        trace: Some("+".to_string()),
+        ..Default::default()
+    };
+
+    let texts: Vec<&str> = Level::iter().map(|level| style.get(level)).collect();
+    assert_eq!(texts, ["E", "W", "I", "D", "T"]);
+    let codes: Vec<&str> = Level::iter().map(|level| colors.get(level)).collect();
+    assert_eq!(codes, ["c1", "c2", "c3", "c4", "c5"]);
+    let templates: Vec<Option<&str>> = Level::iter().map(|level| templates.get(level)).collect();
+    assert_eq!(templates, [Some("error {message}"), None, None, None, Some("+")]);
+
+    // 默认样式与级别名称一致
+    let default_style = FormatConfig::default().level_style;
+    assert!(Level::iter().all(|level| default_style.get(level) == level.as_str()));
+}
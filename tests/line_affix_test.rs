@@ -0,0 +1,87 @@
+//! 行前后缀测试：原始模式、模板模式和 JSON 模板的每行输出都带有处理器选项中的前缀和后缀
+
+mod common;
+
+use rat_logger::core::LoggerCore;
+use rat_logger::{FileConfig, FormatConfig, HandlerOptions, Level, LevelFilter, Logger, LoggerBuilder};
+use std::time::Duration;
+
+fn log(logger: &LoggerCore, message: &str) {
+    logger.log(&common::record(Level::Info, "line_affix", message));
+}
+
+#[test]
+fn test_raw_mode_lines_are_prefixed() {
+    let log_dir = common::test_dir("line_affix", "raw");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file_with_options(
+            FileConfig { log_dir: log_dir.clone(), is_raw: true, ..Default::default() },
+            HandlerOptions::new().line_prefix("[svc:payments] "),
+        )
+        .build();
+    log(&logger, "first");
+    logger.log_raw("file", b"forwarded line").unwrap();
+    log(&logger, "second");
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    assert_eq!(common::read_logs(&log_dir), "[svc:payments] first\n[svc:payments] forwarded line\n[svc:payments] second\n");
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_template_and_json_lines_get_prefix_and_suffix() {
+    let (text_dir, json_dir) = (common::test_dir("line_affix", "text"), common::test_dir("line_affix", "json"));
+    let options = HandlerOptions::new().line_prefix("[svc:payments] ").line_suffix(" #end");
+    let text = FormatConfig { format_template: "{level} {message}".to_string(), ..Default::default() };
+    // JSON 模板：前缀在 JSON 对象之前，后缀在其之后，由下游去掉前缀后再解析
+    let json = FormatConfig { format_template: r#"{"level":"{level}","msg":"{message}"}"#.to_string(), ..Default::default() };
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file_with_options(FileConfig { log_dir: text_dir.clone(), format: Some(text), ..Default::default() }, options.clone())
+        .add_file_with_options(FileConfig { log_dir: json_dir.clone(), format: Some(json), ..Default::default() }, options.clone().line_suffix(""))
+        .build();
+    log(&logger, "hello");
+    log(&logger, "world");
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    assert_eq!(common::read_logs(&text_dir), "[svc:payments] INFO hello #end\n[svc:payments] INFO world #end\n");
+    assert_eq!(
+        common::read_logs(&json_dir),
+        "[svc:payments] {\"level\":\"INFO\",\"msg\":\"hello\"}\n[svc:payments] {\"level\":\"INFO\",\"msg\":\"world\"}\n"
+    );
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&text_dir);
+    let _ = std::fs::remove_dir_all(&json_dir);
+}
+
+#[test]
+fn test_invalid_affixes_rejected() {
+    let error = LoggerBuilder::new()
+        .add_file_with_options(FileConfig::default(), HandlerOptions::new().line_prefix("[a]\n"))
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(error.contains("换行符"), "{}", error);
+
+    let collector = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let udp = rat_logger::UdpConfig {
+        network_config: rat_logger::NetworkConfig {
+            server_addr: "127.0.0.1".to_string(),
+            server_port: collector.local_addr().unwrap().port(),
+            auth_token: "token".to_string(),
+            app_id: "line_affix".to_string(),
+        },
+        ..Default::default()
+    };
+    let error = LoggerBuilder::new()
+        .add_udp_with_options(udp, HandlerOptions::new().line_prefix("[svc:payments] "))
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(error.contains("UDP 处理器不支持行前缀和后缀"), "{}", error);
+
+    let options: HandlerOptions = serde_json::from_str(r#"{"line_prefix": "[svc:payments] "}"#).unwrap();
+    assert_eq!(options, HandlerOptions::new().line_prefix("[svc:payments] "));
+}
@@ -0,0 +1,81 @@
+//! 日志跟踪测试：跨按大小轮转持续写入时，每一行恰好返回一次；级别过滤按默认文本格式解析
+
+mod common;
+
+use rat_logger::tools::LogTailer;
+use rat_logger::{FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn file_logger(log_dir: &Path) -> rat_logger::core::LoggerCore {
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Trace)
+        .add_file(FileConfig {
+            log_dir: log_dir.to_path_buf(),
+            max_file_size: 2048,
+            max_compressed_files: 100,
+            ..Default::default()
+        })
+        .build()
+}
+
+/// 轮询直到收到 `count` 行或超时
+fn collect(tailer: &mut LogTailer, lines: &mut Vec<String>, count: usize) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while lines.len() < count && Instant::now() < deadline {
+        lines.extend(tailer.poll().unwrap());
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_every_line_yielded_once_across_rotation() {
+    let log_dir = common::test_dir("tailer", "rotation");
+    let logger = file_logger(&log_dir);
+    let mut tailer = LogTailer::new(&log_dir, "app_");
+    assert!(tailer.poll().unwrap().is_empty());
+    let first_file = tailer.current_path().unwrap().to_path_buf();
+
+    let mut lines = Vec::new();
+    for batch in 0..10 {
+        for i in 0..20 {
+            logger.log(&common::record(Level::Info, "tailer", format!("message {:04}", batch * 20 + i)));
+        }
+        logger.flush_sync(5000).unwrap();
+        lines.extend(tailer.poll().unwrap());
+    }
+    collect(&mut tailer, &mut lines, 200);
+
+    // 发生过轮转，跟踪器已切换到新文件
+    assert_ne!(tailer.current_path().unwrap(), first_file);
+    let messages: Vec<&str> = lines.iter().map(|line| line.rsplit_once(" - ").unwrap().1).collect();
+    let expected: Vec<String> = (0..200).map(|i| format!("message {:04}", i)).collect();
+    assert_eq!(messages, expected);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_level_filter_and_iterator() {
+    let log_dir = common::test_dir("tailer", "filter");
+    let logger = file_logger(&log_dir);
+    let tailer = LogTailer::new(&log_dir, "app_")
+        .from_start()
+        .with_poll_interval(Duration::from_millis(5))
+        .with_level_filter(|level| level.should_log_at(LevelFilter::Warn));
+
+    logger.log(&common::record(Level::Info, "tailer", "skipped"));
+    logger.log(&common::record(Level::Warn, "tailer", "first line\ncontinued"));
+    logger.log(&common::record(Level::Debug, "tailer", "skipped too"));
+    logger.log(&common::record(Level::Error, "tailer", "failed"));
+    logger.flush_sync(5000).unwrap();
+
+    let lines: Vec<String> = tailer.take(3).map(Result::unwrap).collect();
+    assert!(lines[0].ends_with("[WARN] tailer unknown:0 - first line"));
+    assert_eq!(lines[1], "continued");
+    assert!(lines[2].ends_with("[ERROR] tailer unknown:0 - failed"));
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,263 @@
+//! 内存预算测试：处理器卡住时待处理字节数停在预算处并按配置的方式处理新记录，处理器恢复后待处理字节数回到 0
+
+mod common;
+
+use rat_logger::config::decode_record_versioned;
+use rat_logger::core::LoggerCore;
+use rat_logger::producer_consumer::{BatchConfig, LogProcessor, ProcessorError};
+use rat_logger::{ByteSize, DiagnosticEvent, FileConfig, Level, LevelFilter, Logger, LoggerBuilder, MemoryAction, MemoryBudget};
+use parking_lot::{Condvar, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const BUDGET: u64 = 1024 * 1024;
+
+/// 处理器放行开关：关闭时处理器卡在 `process` 中
+#[derive(Default)]
+struct Gate {
+    open: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl Gate {
+    fn open(&self) {
+        *self.open.lock() = true;
+        self.changed.notify_all();
+    }
+}
+
+/// 开关打开前不处理任何记录的处理器
+struct GatedProcessor {
+    gate: Arc<Gate>,
+    levels: Arc<Mutex<Vec<Level>>>,
+}
+
+impl LogProcessor for GatedProcessor {
+    fn name(&self) -> &'static str {
+        "gated"
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
+        let mut open = self.gate.open.lock();
+        while !*open {
+            self.gate.changed.wait(&mut open);
+        }
+        let (record, _) = decode_record_versioned(data).map_err(ProcessorError::decode)?;
+        self.levels.lock().push(record.metadata.level);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+struct Fixture {
+    logger: LoggerCore,
+    gate: Arc<Gate>,
+    levels: Arc<Mutex<Vec<Level>>>,
+    events: Arc<Mutex<Vec<DiagnosticEvent>>>,
+    log_dir: std::path::PathBuf,
+}
+
+impl Fixture {
+    /// 只有一个卡住的处理器的日志器
+    fn new(name: &str, budget: MemoryBudget) -> Self {
+        let log_dir = common::test_dir("memory_budget", name);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let logger = LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+            .with_memory_budget_config(budget)
+            .with_error_callback(move |event| sink.lock().push(event.clone()))
+            .build();
+
+        let gate = Arc::new(Gate::default());
+        let levels = Arc::new(Mutex::new(Vec::new()));
+        let processor = GatedProcessor { gate: gate.clone(), levels: levels.clone() };
+        logger.attach_processor(processor, BatchConfig::low_latency()).unwrap();
+        logger.detach_handler("file").unwrap();
+        Self { logger, gate, levels, events, log_dir }
+    }
+
+    fn log(&self, level: Level, i: usize) {
+        self.logger.log(&common::record(level, "memory_budget", format!("{:04} {}", i, "x".repeat(1000))));
+    }
+
+    fn in_flight(&self) -> u64 {
+        self.logger.runtime_stats().unwrap().in_flight_bytes()
+    }
+
+    fn count(&self, matches: impl Fn(&DiagnosticEvent) -> bool) -> usize {
+        self.events.lock().iter().filter(|event| matches(event)).count()
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        self.gate.open();
+        let _ = std::fs::remove_dir_all(&self.log_dir);
+    }
+}
+
+fn exceeded(event: &DiagnosticEvent) -> bool {
+    matches!(event, DiagnosticEvent::MemoryBudgetExceeded { budget: BUDGET, .. })
+}
+
+fn recovered(event: &DiagnosticEvent) -> bool {
+    matches!(event, DiagnosticEvent::MemoryBudgetRecovered { .. })
+}
+
+#[test]
+fn test_drop_newest_plateaus_at_budget() {
+    let fixture = Fixture::new("drop", MemoryBudget::new(ByteSize::mib(1), MemoryAction::DropNewest));
+    let stats = fixture.logger.runtime_stats().unwrap();
+
+    for i in 0..3000 {
+        fixture.log(Level::Info, i);
+    }
+    // 待处理字节数停在预算以内，差距不到两条记录
+    let plateau = fixture.in_flight();
+    assert!(plateau <= BUDGET && plateau > BUDGET - 2200, "待处理 {} 字节", plateau);
+    let dropped = stats.memory_dropped();
+    assert!(dropped > 1500, "丢弃 {} 条", dropped);
+    assert!(stats.memory_exceeded());
+    assert_eq!(fixture.count(exceeded), 1);
+
+    // 恢复处理后全部处理完，下一条记录使状态恢复
+    fixture.gate.open();
+    assert!(common::wait_until(Duration::from_secs(5), || fixture.in_flight() == 0), "待处理 {} 字节", fixture.in_flight());
+    assert_eq!(fixture.levels.lock().len() as u64, 3000 - dropped);
+    fixture.log(Level::Info, 3000);
+    assert!(!stats.memory_exceeded());
+    assert_eq!(fixture.count(recovered), 1);
+    assert!(matches!(
+        fixture.events.lock().last(),
+        Some(DiagnosticEvent::MemoryBudgetRecovered { dropped: d, .. }) if *d == dropped
+    ));
+    fixture.logger.flush_sync(2000).unwrap();
+    assert_eq!(fixture.in_flight(), 0);
+}
+
+#[test]
+fn test_block_producers_waits_for_processor() {
+    let fixture = Arc::new(Fixture::new("block", MemoryBudget::new(ByteSize::mib(1), MemoryAction::BlockProducers)));
+    let logged = Arc::new(AtomicUsize::new(0));
+
+    let producer = {
+        let (fixture, logged) = (fixture.clone(), logged.clone());
+        std::thread::spawn(move || {
+            for i in 0..2000 {
+                fixture.log(Level::Info, i);
+                logged.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+    };
+
+    // 生产线程停在预算处
+    assert!(common::wait_until(Duration::from_secs(5), || fixture.count(exceeded) == 1));
+    std::thread::sleep(Duration::from_millis(100));
+    let blocked_at = logged.load(Ordering::SeqCst);
+    assert!(blocked_at < 2000, "生产线程没有被阻塞");
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(logged.load(Ordering::SeqCst), blocked_at);
+    assert!(fixture.in_flight() <= BUDGET);
+
+    // 恢复处理后生产线程继续，不丢弃任何记录
+    fixture.gate.open();
+    producer.join().unwrap();
+    fixture.logger.flush_sync(2000).unwrap();
+    assert_eq!(fixture.levels.lock().len(), 2000);
+    assert_eq!(fixture.logger.runtime_stats().unwrap().memory_dropped(), 0);
+    assert_eq!(fixture.in_flight(), 0);
+}
+
+#[test]
+fn test_concurrent_producers_stay_within_budget() {
+    let fixture = Arc::new(Fixture::new("concurrent", MemoryBudget::new(ByteSize::mib(1), MemoryAction::DropNewest)));
+    let stats = fixture.logger.runtime_stats().unwrap();
+
+    // 判定和计入是同一次原子操作，并发的生产者不会一起越过预算
+    let producers: Vec<_> = (0..8)
+        .map(|t| {
+            let fixture = fixture.clone();
+            std::thread::spawn(move || {
+                for i in 0..500 {
+                    fixture.log(Level::Info, t * 500 + i);
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    assert!(fixture.in_flight() <= BUDGET, "待处理 {} 字节", fixture.in_flight());
+
+    fixture.gate.open();
+    fixture.logger.flush_sync(2000).unwrap();
+    assert_eq!(fixture.levels.lock().len() as u64 + stats.memory_dropped(), 4000);
+    assert_eq!(fixture.in_flight(), 0);
+}
+
+#[test]
+fn test_degrade_keeps_errors_until_drained() {
+    let fixture = Fixture::new("degrade", MemoryBudget::new(ByteSize::mib(1), MemoryAction::DegradeToErrorOnly));
+    let stats = fixture.logger.runtime_stats().unwrap();
+
+    for i in 0..1500 {
+        fixture.log(Level::Info, i);
+    }
+    assert_eq!(fixture.count(exceeded), 1);
+    let plateau = fixture.in_flight();
+    assert!(plateau <= BUDGET, "待处理 {} 字节", plateau);
+
+    // 超出后只接受 Error 级别的记录，Error 可以使用预留
+    let dropped = stats.memory_dropped();
+    fixture.log(Level::Warn, 1500);
+    assert_eq!(stats.memory_dropped(), dropped + 1);
+    for i in 0..10 {
+        fixture.log(Level::Error, i);
+    }
+    assert_eq!(stats.memory_dropped(), dropped + 1);
+    let with_errors = fixture.in_flight();
+    assert!(with_errors > plateau && with_errors <= BUDGET + BUDGET / 16, "待处理 {} 字节", with_errors);
+
+    // 处理完后恢复，Info 记录重新放行
+    fixture.gate.open();
+    assert!(common::wait_until(Duration::from_secs(5), || fixture.in_flight() == 0));
+    fixture.log(Level::Info, 1501);
+    fixture.logger.flush_sync(2000).unwrap();
+    assert_eq!(fixture.count(recovered), 1);
+    assert!(!stats.memory_exceeded());
+    let levels = fixture.levels.lock();
+    assert_eq!(levels.iter().filter(|level| **level == Level::Error).count(), 10);
+    assert_eq!(levels.last(), Some(&Level::Info));
+    assert_eq!(fixture.in_flight(), 0);
+}
+
+#[test]
+fn test_memory_budget_config() {
+    let budget = MemoryBudget::new(ByteSize::mib(16), MemoryAction::DropNewest);
+    assert_eq!(budget.emergency_reserve, 1024 * 1024);
+    assert!(budget.validate().is_ok());
+    assert!(MemoryBudget::new(ByteSize::b(0), MemoryAction::DropNewest).validate().is_err());
+
+    // 配置文件中可以写作字节大小字符串
+    let budget: MemoryBudget =
+        serde_json::from_str(r#"{"max_bytes":"64MiB","action":"BlockProducers","emergency_reserve":"1MiB"}"#).unwrap();
+    assert_eq!(budget, MemoryBudget::new(ByteSize::mib(64), MemoryAction::BlockProducers).with_emergency_reserve(ByteSize::mib(1)));
+
+    let error = LoggerBuilder::new()
+        .add_terminal_with_config(Default::default())
+        .with_memory_budget(ByteSize::b(0), MemoryAction::DropNewest)
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(error.contains("内存预算不能为 0"), "{}", error);
+}
@@ -0,0 +1,141 @@
+//! 网络配置验证测试：无效的地址、端口和令牌在构建时报告，而不是在发送时才失败
+
+mod common;
+
+use rat_logger::test_util::UdpCollector;
+use rat_logger::{Level, LevelFilter, Logger, LoggerBuilder, NetworkConfig, UdpConfig};
+use std::time::Duration;
+
+fn network(server_addr: &str, server_port: u16) -> NetworkConfig {
+    NetworkConfig {
+        server_addr: server_addr.to_string(),
+        server_port,
+        ..Default::default()
+    }
+}
+
+fn try_build_udp(config: UdpConfig) -> Result<rat_logger::core::LoggerCore, String> {
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(Default::default())
+        .add_udp_with_config(config)
+        .try_build()
+}
+
+#[test]
+fn test_invalid_network_fields_rejected() {
+    let cases = [
+        (network("", 5140), "服务器地址不能为空"),
+        (network("bad host!", 5140), "合法的主机名"),
+        (network("-leading.example.com", 5140), "合法的主机名"),
+        (network(&format!("{}.com", "a".repeat(64)), 5140), "合法的主机名"),
+        (network("127.0.0.1", 0), "端口不能为 0"),
+    ];
+    for (network_config, expected) in cases {
+        let error = network_config.validate().unwrap_err();
+        assert!(error.contains(expected), "{:?}: {}", network_config, error);
+
+        // 其余处理器有效时构建同样失败，而不是静默跳过 UDP 处理器
+        let error = try_build_udp(UdpConfig { network_config, ..Default::default() }).err().unwrap();
+        assert!(error.contains("UdpConfig 验证失败") && error.contains(expected), "{}", error);
+    }
+
+    for valid in ["127.0.0.1", "::1", "localhost", "logs.example.com", "log-server-01"] {
+        assert!(network(valid, 5140).validate().is_ok(), "{}", valid);
+    }
+}
+
+#[test]
+fn test_auth_token_policy() {
+    let tokenless = NetworkConfig { auth_token: String::new(), ..network("127.0.0.1", 5140) };
+    assert!(UdpConfig { network_config: tokenless.clone(), ..Default::default() }.validate().is_ok());
+
+    let required = UdpConfig { network_config: tokenless, require_auth_token: true, ..Default::default() };
+    assert!(required.validate().unwrap_err().contains("认证令牌不能为空"));
+}
+
+#[test]
+fn test_verify_connectivity_fails_fast_for_unresolvable_host() {
+    let config = UdpConfig {
+        network_config: network("rat-logger.invalid", 5140),
+        verify_connectivity: true,
+        ..Default::default()
+    };
+    assert!(config.validate().is_ok(), "地址格式合法，只有连接检查失败");
+    let error = try_build_udp(config).err().unwrap();
+    assert!(error.contains("无法解析服务器地址"), "{}", error);
+}
+
+#[test]
+fn test_verified_config_delivers_to_collector() {
+    let collector = UdpCollector::bind().unwrap();
+    let logger = try_build_udp(UdpConfig {
+        network_config: collector.network_config(),
+        require_auth_token: true,
+        verify_connectivity: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    logger.log(&common::record(Level::Info, "network_config", "verified"));
+
+    assert!(collector.wait_for(1, Duration::from_secs(5)));
+    assert_eq!(collector.messages(), ["verified"]);
+}
+
+#[test]
+fn test_from_endpoint_splits_host_and_port() {
+    let config = NetworkConfig::from_endpoint("collector.internal:5140", "token", "app").unwrap();
+    assert_eq!((config.server_addr.as_str(), config.server_port), ("collector.internal", 5140));
+    assert_eq!((config.auth_token.as_str(), config.app_id.as_str()), ("token", "app"));
+
+    let config = NetworkConfig::from_endpoint("[::1]:5140", "token", "").unwrap();
+    assert_eq!((config.host(), config.server_port), ("::1", 5140));
+    assert_eq!(config.endpoint(), "[::1]:5140");
+    assert_eq!(NetworkConfig::from_endpoint("10.0.0.7:9000", "token", "").unwrap().endpoint(), "10.0.0.7:9000");
+
+    for (endpoint, expected) in [
+        ("collector.internal", "缺少端口"),
+        ("[::1]", "缺少端口"),
+        ("::1:5140", "方括号"),
+        ("collector.internal:http", "端口无效"),
+        ("collector.internal:0", "端口不能为 0"),
+        ("bad host!:5140", "合法的主机名"),
+    ] {
+        let error = NetworkConfig::from_endpoint(endpoint, "token", "").unwrap_err();
+        assert!(error.contains(expected), "{}: {}", endpoint, error);
+    }
+}
+
+#[test]
+fn test_ipv6_addresses_with_and_without_brackets() {
+    for server_addr in ["::1", "[::1]", "0:0:0:0:0:0:0:1"] {
+        let config = network(server_addr, 5140);
+        assert!(config.validate().is_ok(), "{}", server_addr);
+        assert_eq!(config.endpoint(), "[::1]:5140");
+    }
+    assert!(network("[::1", 5140).validate().is_err());
+}
+
+#[test]
+fn test_ipv6_loopback_delivers_to_collector() {
+    // 没有 IPv6 回环地址的环境跳过
+    let Ok(collector) = UdpCollector::bind_to("[::1]:0") else {
+        eprintln!("IPv6 回环地址不可用，跳过");
+        return;
+    };
+    let port = collector.addr().port();
+    for network_config in [
+        collector.network_config(),
+        network("[::1]", port),
+        NetworkConfig::from_endpoint(&format!("[::1]:{}", port), "token", "").unwrap(),
+    ] {
+        assert_eq!(network_config.host(), "::1");
+        let logger = try_build_udp(UdpConfig { network_config, verify_connectivity: true, ..Default::default() }).unwrap();
+        logger.log(&common::record(Level::Info, "network_config", "over ipv6"));
+        drop(logger);
+    }
+
+    assert!(collector.wait_for(3, Duration::from_secs(5)));
+    assert_eq!(collector.messages(), ["over ipv6"; 3]);
+}
@@ -4,6 +4,7 @@
 //! 1. 纯终端输出
 //! 2. 纯文件输出
 //! 3. 终端+文件输出
+//! 4. 文件输出批量预设对比（均衡 / 高吞吐）
 
 use rat_logger::{LoggerBuilder, LevelFilter, Level, FileConfig, config::Record, Logger};
 use rat_logger::config::Metadata;
@@ -24,10 +25,11 @@ fn create_test_record(level: Level, message: &str) -> Record {
             auth_token: None,
             app_id: Some("test_app".to_string()),
         }),
-        args: message.to_string(),
+        args: message.into(),
         module_path: Some("performance_test".to_string()),
         file: Some("performance_test.rs".to_string()),
         line: Some(42),
+        ..Default::default()
     }
 }
 
@@ -41,6 +43,7 @@ fn benchmark_terminal_only() -> Result<(), Box<dyn std::error::Error>> {
             batch_size: 1000,     // 适合性能测试的批量大小
             batch_interval_ms: 100,  // 100ms间隔
             buffer_size: 10000,
+            ..Default::default()
         })
         .add_terminal_with_config(rat_logger::handler::term::TermConfig::default())
         .build();
@@ -87,6 +90,7 @@ fn benchmark_file_only() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步模式测试性能
         format: None,
+        ..Default::default()
     };
 
     let logger = LoggerBuilder::new()
@@ -96,6 +100,7 @@ fn benchmark_file_only() -> Result<(), Box<dyn std::error::Error>> {
             batch_size: 1000,     // 适合性能测试的批量大小
             batch_interval_ms: 100,  // 100ms间隔
             buffer_size: 10000,
+            ..Default::default()
         })
         .add_file(file_config)
         .build();
@@ -145,6 +150,7 @@ fn benchmark_terminal_and_file() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步模式测试性能
         format: None,
+        ..Default::default()
     };
 
     let logger = LoggerBuilder::new()
@@ -154,6 +160,7 @@ fn benchmark_terminal_and_file() -> Result<(), Box<dyn std::error::Error>> {
             batch_size: 1000,     // 适合性能测试的批量大小
             batch_interval_ms: 100,  // 100ms间隔
             buffer_size: 10000,
+            ..Default::default()
         })
         .add_terminal_with_config(rat_logger::handler::term::TermConfig::default())
         .add_file(file_config)
@@ -204,6 +211,7 @@ fn benchmark_multithreaded() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步模式测试性能
         format: None,
+        ..Default::default()
     };
 
     let logger = Arc::new(LoggerBuilder::new()
@@ -213,6 +221,7 @@ fn benchmark_multithreaded() -> Result<(), Box<dyn std::error::Error>> {
             batch_size: 1000,     // 适合性能测试的批量大小
             batch_interval_ms: 100,  // 100ms间隔
             buffer_size: 10000,
+            ..Default::default()
         })
         .add_file(file_config)
         .build());
@@ -275,6 +284,7 @@ fn benchmark_different_log_levels() -> Result<(), Box<dyn std::error::Error>> {
         compress_on_drop: false,
         force_sync: false, // 异步模式测试性能
         format: None,
+        ..Default::default()
     };
 
     let levels = vec![
@@ -293,6 +303,7 @@ fn benchmark_different_log_levels() -> Result<(), Box<dyn std::error::Error>> {
                 batch_size: 1000,     // 适合性能测试的批量大小
                 batch_interval_ms: 100,  // 100ms间隔
                 buffer_size: 10000,
+                ..Default::default()
             })
             .add_file(file_config.clone())
             .build();
@@ -320,6 +331,49 @@ fn benchmark_different_log_levels() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn benchmark_file_batch_presets() -> Result<(), Box<dyn std::error::Error>> {
+    println!("\n=== 文件输出批量预设对比 ===");
+
+    for (name, batch_config) in [("balanced", BatchConfig::balanced()), ("throughput", BatchConfig::throughput())] {
+        let test_dir = PathBuf::from(format!("./preset_{}_test_logs", name));
+        if test_dir.exists() {
+            fs::remove_dir_all(&test_dir)?;
+        }
+
+        let file_config = FileConfig {
+            log_dir: test_dir.clone(),
+            max_file_size: 1024 * 1024 * 100, // 100MB
+            max_compressed_files: 0, // 不压缩以测试纯写入性能
+            compression_level: 0,
+            ..Default::default()
+        };
+
+        let logger = LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .with_async_mode(true)
+            .with_batch_config(batch_config)
+            .add_file(file_config)
+            .build();
+
+        // 计时包含确认刷新，衡量的是记录真正写入文件的吞吐量
+        let start = Instant::now();
+        for i in 0..ITERATIONS {
+            logger.log(&create_test_record(Level::Info, &format!("预设日志消息 #{}", i)));
+        }
+        logger.flush_sync(10_000)?;
+        let duration = start.elapsed();
+
+        println!("预设: {}", name);
+        println!("总耗时: {:?}", duration);
+        println!("吞吐量: {:.0} 条/秒", ITERATIONS as f64 / duration.as_secs_f64());
+
+        drop(logger);
+        fs::remove_dir_all(&test_dir)?;
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_performance_comparison() {
     println!("开始rat_logger性能对比测试");
@@ -334,6 +388,7 @@ fn test_performance_comparison() {
     benchmark_terminal_and_file().unwrap();
     benchmark_multithreaded().unwrap();
     benchmark_different_log_levels().unwrap();
+    benchmark_file_batch_presets().unwrap();
 
     println!("\n================================");
     println!("性能测试完成！");
@@ -359,6 +414,7 @@ fn test_basic_functionality() {
         compress_on_drop: false,
         force_sync: false, // 异步模式测试性能
         format: None,
+        ..Default::default()
     };
 
     let logger = LoggerBuilder::new()
@@ -368,6 +424,7 @@ fn test_basic_functionality() {
             batch_size: 1000,     // 适合性能测试的批量大小
             batch_interval_ms: 100,  // 100ms间隔
             buffer_size: 10000,
+            ..Default::default()
         })
         .add_file(file_config)
         .build();
@@ -0,0 +1,79 @@
+//! 管道探测测试：批量间隔较长的处理器测得的探测延迟明显更大；默认不写入标记记录，`Written` 模式下标记记录出现在输出中
+
+mod common;
+
+use rat_logger::config::PROBE_TARGET;
+use rat_logger::core::LoggerCore;
+use rat_logger::producer_consumer::BatchConfig;
+use rat_logger::{FileProcessor, Level, LevelFilter, Logger, LoggerBuilder, ProbeMarker};
+use std::time::{Duration, Instant};
+
+/// 持续记录 `duration`，使批次缓冲中始终有记录
+fn log_for(logger: &LoggerCore, duration: Duration) {
+    let start = Instant::now();
+    let mut i = 0;
+    while start.elapsed() < duration {
+        logger.log(&common::record(Level::Info, "pipeline_probe", format!("record {}", i)));
+        i += 1;
+        std::thread::sleep(Duration::from_millis(2));
+    }
+}
+
+#[test]
+fn test_slow_batching_shows_higher_latency() {
+    let (fast_dir, slow_dir) = (common::test_dir("pipeline_probe", "fast"), common::test_dir("pipeline_probe", "slow"));
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_pipeline_probe(Duration::from_millis(50), ProbeMarker::Suppressed)
+        .add_file(common::file_config(&fast_dir, Some("{target} {message}")))
+        .build();
+    let slow = BatchConfig { batch_size: 10_000, batch_interval_ms: 400, ..Default::default() };
+    logger.attach_processor(FileProcessor::new(common::file_config(&slow_dir, Some("{target} {message}"))), slow).unwrap();
+
+    log_for(&logger, Duration::from_millis(1200));
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    let reports = logger.probe_reports();
+    assert_eq!(reports.len(), 2, "{:?}", reports);
+    let (fast, slow) = (reports[0], reports[1]);
+    assert!(fast.probes >= 5 && slow.probes >= 5, "{:?}", reports);
+    assert!(slow.max_latency_us >= 150_000, "{:?}", reports);
+    assert!(slow.max_latency_us > fast.max_latency_us, "{:?}", reports);
+
+    // 默认不写入标记记录
+    assert!(!common::read_logs(&fast_dir).contains(PROBE_TARGET));
+    assert!(!common::read_logs(&slow_dir).contains(PROBE_TARGET));
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&fast_dir);
+    let _ = std::fs::remove_dir_all(&slow_dir);
+}
+
+#[test]
+fn test_written_marker_appears_in_output() {
+    let log_dir = common::test_dir("pipeline_probe", "written");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_pipeline_probe(Duration::from_millis(20), ProbeMarker::Written)
+        .add_file(common::file_config(&log_dir, Some("{target} {message}")))
+        .build();
+
+    log_for(&logger, Duration::from_millis(200));
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    let content = common::read_logs(&log_dir);
+    let markers = content.lines().filter(|line| *line == format!("{} pipeline probe", PROBE_TARGET)).count();
+    assert!(markers >= 2, "{}", content);
+    assert!(logger.probe_reports()[0].probes >= 2);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    let error = LoggerBuilder::new()
+        .add_terminal_with_config(Default::default())
+        .with_pipeline_probe(Duration::ZERO, ProbeMarker::Suppressed)
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(error.contains("管道探测间隔不能为 0"), "{}", error);
+}
@@ -0,0 +1,52 @@
+//! 锁中毒恢复测试：持有全局日志器锁的线程 panic 后，之后的日志宏仍然正常工作
+
+use rat_logger::config::Record;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{flush_logs, info, warn, LevelFilter, LoggerBuilder};
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::Arc;
+
+/// `Display` 实现会 panic 的参数
+struct PanickingDisplay;
+
+impl fmt::Display for PanickingDisplay {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        panic!("Display 实现 panic");
+    }
+}
+
+#[test]
+fn test_macros_survive_panics_and_poisoned_lock() {
+    let captured: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.args.to_string()))),
+            ..Default::default()
+        })
+        .init_global_logger()
+        .unwrap();
+
+    // 格式化参数时 panic
+    let result = std::panic::catch_unwind(|| info!("{}", PanickingDisplay));
+    assert!(result.is_err());
+    info!("after display panic");
+
+    // 持有全局日志器锁时 panic，锁被标记为中毒
+    let result = std::thread::spawn(|| {
+        let _guard = rat_logger::core::LOGGER.lock();
+        panic!("持有锁时 panic");
+    })
+    .join();
+    assert!(result.is_err());
+    assert!(rat_logger::core::LOGGER.is_poisoned());
+
+    warn!("after poisoned lock");
+    flush_logs!();
+    rat_logger::core::lock_global_logger().as_ref().unwrap().flush_sync(2000).unwrap();
+
+    assert_eq!(*captured.lock(), ["after display panic", "after poisoned lock"]);
+}
@@ -0,0 +1,151 @@
+//! 轮转后命令测试：轮转（启用压缩时为压缩）完成后以文件路径为最后一个参数执行命令，超时和失败只上报诊断事件
+#![cfg(unix)]
+
+mod common;
+
+use rat_logger::handler::file::FileProcessor;
+use rat_logger::producer_consumer::LogProcessor;
+use rat_logger::diagnostics::Diagnostics;
+use rat_logger::{DiagnosticEvent, DurationMs, FileConfig};
+use parking_lot::Mutex;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 轮转后执行 `sh -c script`，文件路径为 `$1`
+fn config(dir: &Path, script: &str, compressed: bool, timeout_ms: u64) -> FileConfig {
+    let config = FileConfig {
+        log_dir: dir.join("logs"),
+        max_compressed_files: if compressed { 10 } else { 0 },
+        compression_level: if compressed { 4 } else { 0 },
+        ..Default::default()
+    };
+    config.with_post_rotate_command(["/bin/sh", "-c", script, "sh"], DurationMs::ms(timeout_ms))
+}
+
+fn file_processor(config: FileConfig) -> (FileProcessor, Arc<Mutex<Vec<DiagnosticEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let diagnostics = Diagnostics::new();
+    diagnostics.set_callback(Some(Arc::new(move |event: &DiagnosticEvent| sink.lock().push(event.clone()))));
+    let mut processor = FileProcessor::new(config);
+    processor.set_diagnostics(diagnostics);
+    (processor, events)
+}
+
+/// 等待标记文件中出现 `count` 行
+fn marker_lines(marker: &Path, count: usize) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let content = std::fs::read_to_string(marker).unwrap_or_default();
+        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+        if lines.len() >= count || Instant::now() >= deadline {
+            return lines;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_command_receives_rotated_path() {
+    let dir = common::created_test_dir("post_rotate", "rotated");
+    let marker = dir.join("marker");
+    let script = format!("echo \"$1\" >> {}", marker.display());
+    let (mut processor, events) = file_processor(config(&dir, &script, false, 5000));
+
+    processor.handle_rotate().unwrap();
+    processor.handle_rotate().unwrap();
+
+    let lines = marker_lines(&marker, 2);
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    for line in &lines {
+        let path = Path::new(line);
+        assert_eq!(path.parent(), Some(dir.join("logs").as_path()));
+        assert!(line.ends_with(".log") && path.exists(), "{}", line);
+    }
+    assert_ne!(lines[0], lines[1]);
+    assert!(events.lock().is_empty(), "{:?}", events.lock());
+    drop(processor);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_command_runs_after_compression() {
+    let dir = common::created_test_dir("post_rotate", "compressed");
+    let marker = dir.join("marker");
+    let script = format!("test -f \"$1\" && echo \"$1\" >> {}", marker.display());
+    let (mut processor, events) = file_processor(config(&dir, &script, true, 5000));
+
+    processor.handle_rotate().unwrap();
+
+    let lines = marker_lines(&marker, 1);
+    assert_eq!(lines.len(), 1, "{:?}", events.lock());
+    assert!(lines[0].ends_with(".log.lz4"), "{}", lines[0]);
+    drop(processor);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_timeout_and_failure_reported_without_blocking() {
+    let dir = common::created_test_dir("post_rotate", "timeout");
+    let (mut processor, events) = file_processor(config(&dir, "sleep 5", false, 100));
+
+    // 轮转不等待命令
+    let start = Instant::now();
+    processor.handle_rotate().unwrap();
+    processor.handle_rotate().unwrap();
+    assert!(start.elapsed() < Duration::from_millis(100), "轮转耗时 {:?}", start.elapsed());
+
+    // 两个命令依次执行，各自超时后被终止
+    let deadline = Instant::now() + Duration::from_secs(3);
+    while events.lock().len() < 2 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(200) && elapsed < Duration::from_secs(2), "耗时 {:?}", elapsed);
+    for event in events.lock().iter() {
+        assert!(
+            matches!(event, DiagnosticEvent::PostRotateCommandFailed { code: None, error, .. } if error.contains("超时")),
+            "{:?}",
+            event
+        );
+    }
+    drop(processor);
+
+    // 退出状态和标准错误输出
+    let (mut processor, events) = file_processor(config(&dir, "echo \"cannot upload $1\" >&2; exit 3", false, 5000));
+    processor.handle_rotate().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(3);
+    while events.lock().is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    match events.lock().as_slice() {
+        [DiagnosticEvent::PostRotateCommandFailed { path, code: Some(3), error }] => {
+            assert!(error.contains(&format!("cannot upload {}", path.display())), "{}", error);
+        }
+        other => panic!("{:?}", other),
+    }
+    drop(processor);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_post_rotate_config() {
+    let dir = common::created_test_dir("post_rotate", "config");
+    assert!(config(&dir, "true", false, 1000).validate().is_ok());
+    assert!(FileConfig { post_rotate_command: Some(Vec::new()), ..Default::default() }.validate().is_err());
+    assert!(FileConfig { post_rotate_timeout_ms: 0, ..config(&dir, "true", false, 1000) }.validate().is_err());
+
+    // 旧配置文件没有这两个字段时不执行命令；只指定命令时使用默认超时
+    let mut value = serde_json::to_value(FileConfig::default()).unwrap();
+    let fields = value.as_object_mut().unwrap();
+    fields.remove("post_rotate_command");
+    fields.remove("post_rotate_timeout_ms");
+    let config: FileConfig = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(config.post_rotate_command, None);
+    value["post_rotate_command"] = serde_json::json!(["/usr/bin/logger", "-t", "rotated"]);
+    let config: FileConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(config.post_rotate_command.unwrap(), ["/usr/bin/logger", "-t", "rotated"]);
+    assert_eq!(config.post_rotate_timeout_ms, 30_000);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,66 @@
+//! 初始化前输出测试：全局日志器安装之前的 Warn 及以上级别日志宏按策略写入标准错误，安装之后不再使用此路径
+
+mod common;
+
+use rat_logger::{FileConfig, LevelFilter, LoggerBuilder, PreInit};
+use std::process::Command;
+use std::time::Duration;
+
+/// 子进程通过这些环境变量获得策略和日志目录
+const POLICY_ENV: &str = "RAT_LOGGER_PREINIT_POLICY";
+const LOG_DIR_ENV: &str = "RAT_LOGGER_PREINIT_DIR";
+
+/// 子进程：初始化前后各记录一次日志
+#[test]
+#[ignore = "由 test_preinit_policies 在子进程中运行"]
+fn preinit_child() {
+    let policy = std::env::var(POLICY_ENV).expect("只在子进程中运行");
+    if policy == "stderr" {
+        rat_logger::set_preinit_policy(PreInit::StderrWarnAndAbove);
+    }
+    rat_logger::error!("config file missing: {}", "app.toml");
+    rat_logger::warn!("falling back to defaults");
+    rat_logger::info!("preinit info");
+
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig { log_dir: std::env::var(LOG_DIR_ENV).unwrap().into(), ..Default::default() })
+        .init_global_logger()
+        .unwrap();
+    rat_logger::error!("after init");
+    rat_logger::sync_point(Duration::from_secs(5)).unwrap();
+}
+
+/// 在子进程中运行 `preinit_child`，返回其标准错误和文件日志
+fn run_child(policy: &str) -> (String, String) {
+    let log_dir = common::test_dir("preinit", policy);
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["preinit_child", "--exact", "--ignored", "--nocapture"])
+        .env(POLICY_ENV, policy)
+        .env(LOG_DIR_ENV, &log_dir)
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let logs = common::read_logs(&log_dir);
+    let _ = std::fs::remove_dir_all(&log_dir);
+    (String::from_utf8(output.stderr).unwrap(), logs)
+}
+
+#[test]
+fn test_preinit_policies() {
+    let (stderr, logs) = run_child("stderr");
+    let error_line = stderr.lines().find(|line| line.contains("config file missing: app.toml")).expect(&stderr);
+    assert!(error_line.contains("[ERROR] preinit_test tests/preinit_test.rs:"), "{}", error_line);
+    assert!(stderr.contains("[WARN] preinit_test") && stderr.contains("falling back to defaults"), "{}", stderr);
+    assert!(!stderr.contains("preinit info"), "Info 级别不应输出: {}", stderr);
+    // 安装全局日志器之后的记录只进入日志器
+    assert!(!stderr.contains("after init"), "{}", stderr);
+    assert!(logs.contains("after init") && !logs.contains("config file missing"), "{}", logs);
+
+    // 默认策略保持原有行为：初始化之前的记录被丢弃
+    let (stderr, logs) = run_child("silent");
+    assert!(!stderr.contains("config file missing") && !stderr.contains("falling back"), "{}", stderr);
+    assert!(logs.contains("after init"), "{}", logs);
+    assert_eq!(rat_logger::core::preinit_policy(), PreInit::Silent);
+}
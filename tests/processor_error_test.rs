@@ -0,0 +1,140 @@
+//! 处理器错误测试：反序列化错误上报一次且不影响工作线程，可重试的 I/O 错误会被重试
+
+mod common;
+
+use rat_logger::config::{encode_record_v2, Record};
+use rat_logger::diagnostics::Diagnostics;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::producer_consumer::{BatchConfig, ErrorKind, LogProcessor, ProcessorError, ProcessorWorker};
+use rat_logger::{DiagnosticEvent, Level, TermProcessor};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 收集所有诊断事件的诊断句柄
+fn collecting_diagnostics() -> (Diagnostics, Arc<Mutex<Vec<DiagnosticEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let diagnostics = Diagnostics::new();
+    let sink = events.clone();
+    diagnostics.set_callback(Some(Arc::new(move |event: &DiagnosticEvent| sink.lock().push(event.clone()))));
+    (diagnostics, events)
+}
+
+fn encoded(message: &str) -> Vec<u8> {
+    let record = common::record(Level::Info, "processor_error", message);
+    encode_record_v2(&record).unwrap()
+}
+
+fn flush(worker: &ProcessorWorker) -> bool {
+    let (ack_tx, ack_rx) = crossbeam_channel::bounded(1);
+    worker.send_flush_ack(ack_tx).unwrap();
+    ack_rx.recv_timeout(Duration::from_secs(2)).unwrap()
+}
+
+#[test]
+fn test_decode_error_reported_without_killing_worker() {
+    let written = Arc::new(AtomicUsize::new(0));
+    let hook_written = written.clone();
+    let processor = TermProcessor::with_config(TermConfig {
+        enable_color: false,
+        on_record: Some(Arc::new(move |_: &Record| {
+            hook_written.fetch_add(1, Ordering::SeqCst);
+        })),
+        ..Default::default()
+    });
+    let (diagnostics, events) = collecting_diagnostics();
+    let config = BatchConfig { batch_size: 4, batch_interval_ms: 1000, buffer_size: 1024, ..Default::default() };
+    let worker = ProcessorWorker::with_diagnostics(processor, config, diagnostics.clone());
+
+    // 同一批次中的损坏数据只丢弃自身
+    worker.send_write(encoded("before")).unwrap();
+    worker.send_write(b"\xff\xff\xff not a record".to_vec()).unwrap();
+    worker.send_write(encoded("after")).unwrap();
+    assert!(flush(&worker));
+    // 工作线程仍在处理之后的记录
+    worker.send_write_force(encoded("forced")).unwrap();
+    assert!(flush(&worker));
+
+    assert_eq!(written.load(Ordering::SeqCst), 3);
+    let events = events.lock();
+    assert_eq!(events.len(), 1, "{:?}", events);
+    assert!(matches!(
+        &events[0],
+        DiagnosticEvent::ProcessorFailed { processor: "term_processor", kind: ErrorKind::Decode, attempts: 1, .. }
+    ));
+    assert_eq!(diagnostics.stats().processor_errors(), 1);
+}
+
+/// 前几次写入返回指定 I/O 错误的处理器
+struct FlakyProcessor {
+    failures: usize,
+    error_kind: std::io::ErrorKind,
+    attempts: Arc<AtomicUsize>,
+    written: Arc<AtomicUsize>,
+}
+
+impl LogProcessor for FlakyProcessor {
+    fn name(&self) -> &'static str {
+        "flaky_processor"
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
+        self.process_batch(&[data.to_vec()])
+    }
+
+    fn process_batch(&mut self, batch: &[Vec<u8>]) -> Result<(), ProcessorError> {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err(ProcessorError::io("写入失败", self.error_kind.into()));
+        }
+        self.written.fetch_add(batch.len(), Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+fn run_flaky(failures: usize, error_kind: std::io::ErrorKind) -> (usize, usize, Vec<DiagnosticEvent>) {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let written = Arc::new(AtomicUsize::new(0));
+    let processor = FlakyProcessor { failures, error_kind, attempts: attempts.clone(), written: written.clone() };
+    let (diagnostics, events) = collecting_diagnostics();
+    let worker = ProcessorWorker::with_diagnostics(processor, BatchConfig::default(), diagnostics);
+
+    worker.send_write(encoded("flaky")).unwrap();
+    assert!(flush(&worker));
+    let events = events.lock().clone();
+    (attempts.load(Ordering::SeqCst), written.load(Ordering::SeqCst), events)
+}
+
+#[test]
+fn test_interrupted_write_is_retried() {
+    let (attempts, written, events) = run_flaky(2, std::io::ErrorKind::Interrupted);
+    assert_eq!(attempts, 3);
+    assert_eq!(written, 1);
+    assert!(events.is_empty(), "重试成功不应上报: {:?}", events);
+}
+
+#[test]
+fn test_non_retryable_error_reported_once() {
+    let (attempts, written, events) = run_flaky(usize::MAX, std::io::ErrorKind::PermissionDenied);
+    assert_eq!(attempts, 1);
+    assert_eq!(written, 0);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        &events[0],
+        DiagnosticEvent::ProcessorFailed { processor: "flaky_processor", kind: ErrorKind::Io, attempts: 1, .. }
+    ));
+
+    // 可重试的错误重试耗尽后同样只上报一次
+    let (attempts, _, events) = run_flaky(usize::MAX, std::io::ErrorKind::Interrupted);
+    assert_eq!(attempts, 4);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], DiagnosticEvent::ProcessorFailed { attempts: 4, .. }));
+}
@@ -0,0 +1,141 @@
+//! 受保护目标测试：熔断、采样和内存预算丢弃普通记录时，`audit::` 目标的记录一条不少地输出
+
+mod common;
+
+use rat_logger::config::decode_record_versioned;
+use rat_logger::core::LoggerCore;
+use rat_logger::producer_consumer::{BatchConfig, LogProcessor, ProcessorError};
+use rat_logger::{
+    BreakerAction, ByteSize, FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder, MemoryAction, SamplingConfig,
+};
+use parking_lot::{Condvar, Mutex};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn log(logger: &LoggerCore, target: &str, i: usize) {
+    logger.log(&common::record(Level::Info, target, format!("{:04} {}", i, "x".repeat(1000))));
+}
+
+/// 第 i 条记录的目标：每 10 条中有一条审计记录
+fn target(i: usize) -> &'static str {
+    if i.is_multiple_of(10) { "audit::payment" } else { "app::noise" }
+}
+
+#[test]
+fn test_protected_targets_bypass_breaker_and_sampling() {
+    let log_dir = common::test_dir("protected", "breaker");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_max_record_rate(100, BreakerAction::DropWithSummary)
+        .with_sampling(SamplingConfig {
+            default_ratio: 0.5,
+            per_target: Vec::new(),
+            levels: LevelFilter::Info,
+            annotate: false,
+            seed: Some(7),
+        })
+        .with_protected_targets(vec!["audit::".to_string()])
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            format: Some(FormatConfig { format_template: "{target} {message}".to_string(), ..Default::default() }),
+            ..Default::default()
+        })
+        .build();
+
+    const TOTAL: usize = 2000;
+    for i in 0..TOTAL {
+        log(&logger, target(i), i);
+    }
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    let content = common::read_logs(&log_dir);
+    let audit: Vec<usize> = content
+        .lines()
+        .filter_map(|line| line.strip_prefix("audit::payment "))
+        .map(|message| message[..4].parse().unwrap())
+        .collect();
+    assert_eq!(audit, (0..TOTAL).step_by(10).collect::<Vec<_>>());
+    let noise = content.lines().filter(|line| line.starts_with("app::noise ")).count();
+    assert!(noise < 200, "普通记录应当被熔断和采样丢弃: {}", noise);
+
+    let stats = logger.runtime_stats().unwrap();
+    assert!(stats.breaker_open() && stats.breaker_dropped() > 0);
+    assert!(stats.sampled_out() > 0);
+    assert_eq!(stats.protected_records(), (TOTAL / 10) as u64);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+/// 开关打开前卡在 `process` 中的处理器，记录收到的目标
+struct GatedProcessor {
+    gate: Arc<(Mutex<bool>, Condvar)>,
+    targets: Arc<Mutex<Vec<String>>>,
+}
+
+impl LogProcessor for GatedProcessor {
+    fn name(&self) -> &'static str {
+        "gated"
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<(), ProcessorError> {
+        let (open, changed) = &*self.gate;
+        let mut open = open.lock();
+        while !*open {
+            changed.wait(&mut open);
+        }
+        let (record, _) = decode_record_versioned(data).map_err(ProcessorError::decode)?;
+        self.targets.lock().push(record.metadata.target.clone());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_protected_targets_exceed_memory_budget() {
+    let log_dir = common::test_dir("protected", "memory");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_memory_budget(ByteSize::kib(256), MemoryAction::DropNewest)
+        .with_protected_targets(vec!["audit::".to_string()])
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .build();
+    let gate = Arc::new((Mutex::new(false), Condvar::new()));
+    let targets = Arc::new(Mutex::new(Vec::new()));
+    logger
+        .attach_processor(GatedProcessor { gate: gate.clone(), targets: targets.clone() }, BatchConfig::low_latency())
+        .unwrap();
+    logger.detach_handler("file").unwrap();
+
+    // 处理器卡住时持续记录，预算（包括预留）很快用完
+    const TOTAL: usize = 3000;
+    for i in 0..TOTAL {
+        log(&logger, target(i), i);
+    }
+    let stats = logger.runtime_stats().unwrap();
+    assert!(stats.memory_exceeded());
+    assert!(stats.in_flight_bytes() > 256 * 1024 + 16 * 1024, "受保护的记录超出预算后仍然放行");
+
+    *gate.0.lock() = true;
+    gate.1.notify_all();
+    logger.flush_sync(5000).unwrap();
+
+    let targets = targets.lock();
+    let audit = targets.iter().filter(|target| *target == "audit::payment").count();
+    assert_eq!(audit, TOTAL / 10);
+    let noise = targets.len() - audit;
+    assert_eq!(noise as u64, (TOTAL - TOTAL / 10) as u64 - stats.memory_dropped());
+    assert!(stats.memory_dropped() > 1000, "丢弃 {} 条", stats.memory_dropped());
+    assert_eq!(stats.protected_records(), (TOTAL / 10) as u64);
+
+    drop(targets);
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
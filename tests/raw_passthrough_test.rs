@@ -0,0 +1,88 @@
+//! 原始数据快速路径测试：原始模式配置验证，原始行经过批量和轮转后原样写入文件
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, FormatConfig};
+use rat_logger::producer_consumer::BatchConfig;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn raw_config(log_dir: &Path) -> FileConfig {
+    FileConfig {
+        log_dir: log_dir.to_path_buf(),
+        is_raw: true,
+        max_compressed_files: 0,
+        compression_level: 0,
+        ..Default::default()
+    }
+}
+
+/// 按文件名（即创建时间）排序的非空日志文件内容
+fn file_contents(log_dir: &Path) -> Vec<String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(log_dir).unwrap().map(|e| e.unwrap().path()).collect();
+    paths.sort();
+    paths.iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .filter(|content| !content.is_empty())
+        .collect()
+}
+
+#[test]
+fn test_raw_mode_rejects_format() {
+    let config = FileConfig {
+        format: Some(FormatConfig::default()),
+        ..raw_config(Path::new("./unused"))
+    };
+    let error = config.validate().unwrap_err();
+    assert!(error.contains("is_raw"), "{}", error);
+}
+
+#[test]
+fn test_raw_lines_verbatim_across_rotation() {
+    let log_dir = common::test_dir("raw_passthrough", "raw_rotation");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_batch_config(BatchConfig::low_latency())
+        .add_file(FileConfig {
+            max_file_size: 64,
+            ..raw_config(&log_dir)
+        })
+        .build();
+
+    let rounds: Vec<Vec<String>> = (0..2)
+        .map(|round| (0..3).map(|i| format!("upstream {} line {} \t{{\"k\":1}}", round, i)).collect())
+        .collect();
+    for lines in &rounds {
+        // 日志文件名精确到秒，间隔一秒以上轮转才会产生新文件
+        std::thread::sleep(Duration::from_millis(1100));
+        for line in lines {
+            logger.log_raw("file", line.as_bytes()).unwrap();
+        }
+        logger.flush_sync(2000).unwrap();
+    }
+
+    let expected: Vec<String> = rounds.iter().map(|lines| lines.iter().map(|l| format!("{}\n", l)).collect()).collect();
+    assert_eq!(file_contents(&log_dir), expected);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_raw_lines_keep_order_with_records() {
+    let log_dir = common::test_dir("raw_passthrough", "raw_order");
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(raw_config(&log_dir))
+        .build();
+
+    let record = |message: &str| common::record(Level::Info, "raw", message);
+
+    logger.log(&record("first"));
+    logger.log_raw("file_processor", b"second").unwrap();
+    logger.log_raw("file", b"third\n").unwrap();
+    logger.log(&record("fourth"));
+    logger.flush_sync(2000).unwrap();
+
+    assert_eq!(file_contents(&log_dir).concat(), "first\nsecond\nthird\nfourth\n");
+    assert!(logger.log_raw("udp", b"nowhere").is_err(), "没有匹配的处理器时应当返回错误");
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,69 @@
+//! 工作线程就绪耗时测试：报告中每个处理器都有启动耗时，就绪超时的错误指出最慢的处理器
+
+use rat_logger::handler::term::TermConfig;
+use rat_logger::producer_consumer::{BatchConfig, LogProcessor, ProcessorError, ProcessorManager};
+use rat_logger::{FileConfig, LoggerBuilder};
+use std::time::Duration;
+
+/// 启动时需要一段时间初始化的处理器
+struct SlowStart {
+    name: &'static str,
+    delay: Duration,
+}
+
+impl LogProcessor for SlowStart {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn process(&mut self, _data: &[u8]) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
+    fn on_worker_start(&mut self) {
+        std::thread::sleep(self.delay);
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_report_has_entry_per_handler() {
+    let log_dir = std::env::temp_dir().join(format!("rat_logger_readiness_report_{}", std::process::id()));
+    let logger = LoggerBuilder::new()
+        .add_terminal_with_config(TermConfig::default())
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .build();
+
+    let report = logger.processor_manager().readiness_report();
+    let processors: Vec<&str> = report.iter().map(|entry| entry.processor).collect();
+    assert_eq!(processors, ["term_processor", "file_processor"]);
+    assert!(report.iter().all(|entry| entry.startup.is_some_and(|startup| startup > Duration::ZERO)), "{:?}", report);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_timeout_names_slowest_handler() {
+    let mut manager = ProcessorManager::new();
+    manager.add_processor(SlowStart { name: "fast_processor", delay: Duration::ZERO }, BatchConfig::low_latency()).unwrap();
+    manager.add_processor(SlowStart { name: "slow_processor", delay: Duration::from_millis(500) }, BatchConfig::low_latency()).unwrap();
+
+    let err = manager.wait_ready(50).unwrap_err();
+    assert_eq!((err.slowest, err.ready, err.total), ("slow_processor", 1, 2));
+    assert!(err.waited_ms >= 50);
+    assert!(err.to_string().contains("slow_processor"), "{}", err);
+    assert_eq!(manager.readiness_report()[1].startup, None);
+
+    manager.wait_ready(5000).unwrap();
+    let report = manager.readiness_report();
+    assert!(report[1].startup.unwrap() >= Duration::from_millis(500));
+    assert!(report[0].startup.unwrap() < report[1].startup.unwrap());
+}
@@ -0,0 +1,74 @@
+//! 记录标识测试：标识在进程内不重复，同一条记录在文件输出和 UDP 数据包中的标识相同，编码解码后保持不变
+
+mod common;
+
+use rat_logger::config::{next_record_id, record_id_text, Record};
+use rat_logger::test_util::UdpCollector;
+use rat_logger::udp_helper::UdpPacketHelper;
+use rat_logger::{FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder, UdpConfig};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+#[test]
+fn test_ids_are_unique_across_threads() {
+    let threads: Vec<_> = (0..4)
+        .map(|_| std::thread::spawn(|| (0..25_000).map(|_| next_record_id()).collect::<Vec<_>>()))
+        .collect();
+    let ids: Vec<u64> = threads.into_iter().flat_map(|thread| thread.join().unwrap()).collect();
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 100_000);
+
+    // 高 32 位（随机数和进程号）在进程内固定
+    assert!(ids.iter().all(|id| id >> 32 == ids[0] >> 32));
+    assert_eq!((ids[0] >> 32) & 0xffff, u64::from(std::process::id() & 0xffff));
+    assert_eq!(record_id_text(0x1f2e_0042_0000_0007), "1f2e004200000007");
+}
+
+#[test]
+fn test_file_line_and_udp_packet_share_id() {
+    let log_dir = common::test_dir("record_id", "file_line");
+    let collector = UdpCollector::bind().unwrap();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            format: Some(FormatConfig { format_template: "{record_id} {message}".to_string(), ..Default::default() }),
+            ..Default::default()
+        })
+        .add_udp_with_config(UdpConfig { network_config: collector.network_config(), ..Default::default() })
+        .build();
+
+    const TOTAL: usize = 50;
+    for i in 0..TOTAL {
+        logger.log(&common::record(Level::Info, "record_id", format!("record {}", i)));
+    }
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+    assert!(collector.wait_for(TOTAL, Duration::from_secs(5)));
+
+    let content = common::read_logs(&log_dir);
+    let file_ids: HashMap<&str, &str> = content
+        .lines()
+        .map(|line| line.split_once(' ').map(|(id, message)| (message, id)).unwrap())
+        .collect();
+    assert_eq!(file_ids.len(), TOTAL);
+    for received in collector.records() {
+        let id = received.record_id.expect("数据包应当带有记录标识");
+        assert_eq!(file_ids[received.message.as_str()], record_id_text(id), "{}", received.message);
+    }
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_id_survives_packet_round_trip() {
+    let record = Record { args: "dedupe".into(), record_id: Some(next_record_id()), ..Default::default() };
+    let packet = UdpPacketHelper::encode_record(&record, None, None).unwrap();
+    let decoded = UdpPacketHelper::decode_packet(&packet).unwrap();
+    assert_eq!(decoded.record_id, record.record_id);
+    assert_eq!(UdpPacketHelper::net_record_to_record(&decoded).record_id, record.record_id);
+
+    // 没有标识的记录（如直接调用处理器）编码为空标识
+    let packet = UdpPacketHelper::encode_record(&Record { record_id: None, ..record }, None, None).unwrap();
+    let decoded = UdpPacketHelper::decode_packet(&packet).unwrap();
+    assert_eq!((decoded.message.as_str(), decoded.record_id), ("dedupe", None));
+}
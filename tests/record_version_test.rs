@@ -0,0 +1,133 @@
+//! 记录线格式版本测试：引入版本号之前写入的 v1 记录和 v2 记录仍可解码（新增字段取默认值），当前版本记录往返不变
+
+use rat_logger::config::{
+    decode_record_legacy, decode_record_versioned, encode_record_v2, Metadata, Record, ThreadStamp, RECORD_VERSION,
+};
+use rat_logger::handler::term::TermConfig;
+use rat_logger::producer_consumer::LogProcessor;
+use rat_logger::{Level, TermProcessor};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// 旧版本写入的记录：WARN app::db，应用ID svc，消息 "slow query"，位置 app::db src/db.rs:42
+const V1_FULL: &[u8] = &[
+    4, 87, 65, 82, 78, 7, 97, 112, 112, 58, 58, 100, 98, 0, 1, 3, 115, 118, 99, 10, 115, 108, 111, 119, 32, 113,
+    117, 101, 114, 121, 1, 7, 97, 112, 112, 58, 58, 100, 98, 1, 9, 115, 114, 99, 47, 100, 98, 46, 114, 115, 1, 42,
+];
+
+/// 旧版本写入的记录：ERROR，空目标，认证令牌 tok，消息 "boom\n多行"，没有位置信息
+const V1_MINIMAL: &[u8] = &[
+    5, 69, 82, 82, 79, 82, 0, 1, 3, 116, 111, 107, 0, 11, 98, 111, 111, 109, 10, 229, 164, 154, 232, 161, 140, 0, 0, 0,
+];
+
+/// 元数据的各字段：级别、目标、认证令牌、应用ID
+fn metadata(record: &Record) -> (Level, &str, Option<&str>, Option<&str>) {
+    let metadata = &record.metadata;
+    (metadata.level, &metadata.target, metadata.auth_token.as_deref(), metadata.app_id.as_deref())
+}
+
+fn assert_v1_full(record: &Record) {
+    assert_eq!(metadata(record), (Level::Warn, "app::db", None, Some("svc")));
+    assert_eq!(record.args, "slow query");
+    assert_eq!(record.module_path.as_deref(), Some("app::db"));
+    assert_eq!(record.file.as_deref(), Some("src/db.rs"));
+    assert_eq!(record.line, Some(42));
+    // v1 中不存在的字段取默认值
+    assert!(record.fields.is_empty());
+    assert_eq!((record.timestamp, record.seq, record.thread), (None, None, None));
+}
+
+#[test]
+fn test_v1_fixtures_still_decode() {
+    let (record, consumed) = decode_record_legacy(V1_FULL).unwrap();
+    assert_eq!(consumed, V1_FULL.len());
+    assert_v1_full(&record);
+
+    let (record, consumed) = decode_record_legacy(V1_MINIMAL).unwrap();
+    assert_eq!(consumed, V1_MINIMAL.len());
+    assert_eq!(metadata(&record), (Level::Error, "", Some("tok"), None));
+    assert_eq!(record.args, "boom\n多行");
+    assert_eq!((record.module_path, record.file, record.line), (None, None, None));
+
+    // 带版本号 1 的 v1 记录按版本号分派
+    let versioned = [&[1][..], V1_FULL].concat();
+    let (record, consumed) = decode_record_versioned(&versioned).unwrap();
+    assert_eq!(consumed, versioned.len());
+    assert_v1_full(&record);
+}
+
+#[test]
+fn test_current_version_round_trip() {
+    let record = Record {
+        metadata: Arc::new(Metadata {
+            level: Level::Debug,
+            target: "app".to_string(),
+            auth_token: Some("secret".to_string()),
+            app_id: Some("svc".to_string()),
+        }),
+        args: "hello".into(),
+        module_path: Some("app::main".to_string()),
+        file: Some("src/main.rs".to_string()),
+        line: Some(7),
+        fields: vec![("user".to_string(), "42".to_string())],
+        timestamp: Some(UNIX_EPOCH + Duration::new(1_767_323_045, 678_000_000)),
+        seq: Some(9),
+        thread: Some(ThreadStamp { id: 3, seq: 11 }),
+        level_label: Some("AUDIT".to_string()),
+        record_id: Some(0x1f2e_0042_0000_0007),
+    };
+    let encoded = encode_record_v2(&record).unwrap();
+    assert_eq!(encoded[0], RECORD_VERSION);
+
+    // 连续的记录按消耗的字节数依次解码
+    let stream = [encoded.clone(), encoded].concat();
+    let (first, consumed) = decode_record_versioned(&stream).unwrap();
+    let (second, rest) = decode_record_versioned(&stream[consumed..]).unwrap();
+    assert_eq!(consumed + rest, stream.len());
+    for decoded in [first, second] {
+        assert_eq!(metadata(&decoded), metadata(&record));
+        assert_eq!(
+            (decoded.args, decoded.module_path, decoded.file, decoded.line),
+            (record.args.clone(), record.module_path.clone(), record.file.clone(), record.line)
+        );
+        assert_eq!(decoded.fields, record.fields);
+        assert_eq!((decoded.timestamp, decoded.seq, decoded.thread), (record.timestamp, record.seq, record.thread));
+        assert_eq!((decoded.level_label, decoded.record_id), (record.level_label.clone(), record.record_id));
+    }
+
+    // v3 记录没有记录标识：去掉末尾的空标识并改写版本号
+    let unidentified = Record { record_id: None, ..record };
+    let mut v3 = encode_record_v2(&unidentified).unwrap();
+    assert_eq!(v3.pop(), Some(0));
+    v3[0] = 3;
+    let (decoded, consumed) = decode_record_versioned(&v3).unwrap();
+    assert_eq!(consumed, v3.len());
+    assert_eq!((decoded.level_label, decoded.record_id), (unidentified.level_label.clone(), None));
+
+    // v2 记录还没有级别别名
+    let unlabeled = Record { level_label: None, ..unidentified };
+    let mut v2 = encode_record_v2(&unlabeled).unwrap();
+    assert_eq!((v2.pop(), v2.pop()), (Some(0), Some(0)));
+    v2[0] = 2;
+    let (decoded, consumed) = decode_record_versioned(&v2).unwrap();
+    assert_eq!(consumed, v2.len());
+    assert_eq!((decoded.args, decoded.thread, decoded.level_label), (unlabeled.args, unlabeled.thread, None));
+
+    assert!(decode_record_versioned(&[RECORD_VERSION + 1, 0]).is_err());
+    assert!(decode_record_versioned(&[]).is_err());
+}
+
+#[test]
+fn test_processor_accepts_v1_records() {
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let mut processor = TermProcessor::with_config(TermConfig {
+        enable_color: false,
+        on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.args.to_string()))),
+        ..Default::default()
+    });
+
+    processor.process(&[&[1][..], V1_MINIMAL].concat()).unwrap();
+    assert_eq!(*captured.lock(), ["boom\n多行"]);
+}
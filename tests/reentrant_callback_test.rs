@@ -0,0 +1,120 @@
+//! 回调重入测试：格式化函数、富化函数和错误回调中记录日志不会死锁或无限递归，这些记录按策略计数后丢弃或写入标准错误
+
+mod common;
+
+use rat_logger::config::{Metadata, Record};
+use rat_logger::core::LoggerCore;
+use rat_logger::{info, warn, Level, LevelFilter, Logger, LoggerBuilder, ReentrantPolicy};
+use std::io::Write;
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+
+#[test]
+fn test_macros_in_callbacks_are_dropped_and_counted() {
+    let log_dir = common::test_dir("reentrant", "global");
+    let logger = Arc::new(
+        LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .with_enricher(|record: &mut Record| {
+                warn!("enricher reentry");
+                record.fields.push(("enriched".to_string(), "yes".to_string()));
+            })
+            .add_file_with_formatter(common::file_config(&log_dir, None), |buf: &mut dyn Write, record: &Record| {
+                info!("formatter reentry for {}", record.args);
+                writeln!(buf, "{} {:?}", record.args, record.fields)
+            })
+            .build(),
+    );
+    rat_logger::set_max_level(LevelFilter::Info);
+    rat_logger::core::set_logger(logger.clone()).unwrap();
+
+    for i in 0..10 {
+        info!("record {}", i);
+    }
+    rat_logger::sync_point(Duration::from_secs(5)).unwrap();
+
+    let content = common::read_logs(&log_dir);
+    assert_eq!(content.lines().count(), 10, "{}", content);
+    assert!(!content.contains("reentry"), "{}", content);
+    assert!(content.contains("enriched"), "{}", content);
+
+    let stats = logger.runtime_stats().unwrap();
+    assert_eq!(stats.reentrant_records(), 20);
+    assert_eq!(stats.submitted(), 10);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_formatter_logging_into_own_logger_goes_to_stderr() {
+    let log_dir = common::test_dir("reentrant", "stderr");
+    let own: Arc<OnceLock<Weak<LoggerCore>>> = Arc::new(OnceLock::new());
+    let cell = own.clone();
+    let logger = Arc::new(
+        LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .with_reentrant_policy(ReentrantPolicy::Stderr)
+            .add_file_with_formatter(common::file_config(&log_dir, None), move |buf: &mut dyn Write, record: &Record| {
+                // 同一日志器的格式化函数中记录日志：没有保护时每条记录都会产生新的记录
+                if let Some(logger) = cell.get().and_then(Weak::upgrade) {
+                    let mut nested = record.clone();
+                    nested.args = format!("nested {}", record.args).into();
+                    logger.log(&nested);
+                    let mut debug = nested.clone();
+                    debug.metadata = Arc::new(Metadata { level: Level::Debug, ..(*nested.metadata).clone() });
+                    logger.log(&debug);
+                }
+                writeln!(buf, "{}", record.args)
+            })
+            .build(),
+    );
+    own.set(Arc::downgrade(&logger)).unwrap();
+
+    for i in 0..5 {
+        logger.log(&common::record(Level::Info, "reentrant", format!("record {}", i)));
+    }
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    let content = common::read_logs(&log_dir);
+    assert_eq!(content.lines().collect::<Vec<_>>(), (0..5).map(|i| format!("record {}", i)).collect::<Vec<_>>());
+    // 被级别过滤的记录不计数
+    let stats = logger.runtime_stats().unwrap();
+    assert_eq!((stats.reentrant_records(), stats.submitted()), (5, 5));
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_error_callback_logging_is_diverted() {
+    let log_dir = common::test_dir("reentrant", "error_callback");
+    let own: Arc<OnceLock<Weak<LoggerCore>>> = Arc::new(OnceLock::new());
+    let cell = own.clone();
+    let logger = Arc::new(
+        LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .with_enricher(|record: &mut Record| {
+                if record.args == "panic" {
+                    panic!("enricher failed");
+                }
+            })
+            .with_error_callback(move |event| {
+                if let Some(logger) = cell.get().and_then(Weak::upgrade) {
+                    logger.log(&common::record(Level::Warn, "reentrant", format!("diagnostic: {}", event)));
+                }
+            })
+            .add_file_with_formatter(common::file_config(&log_dir, None), |buf: &mut dyn Write, record: &Record| writeln!(buf, "{}", record.args))
+            .build(),
+    );
+    own.set(Arc::downgrade(&logger)).unwrap();
+
+    for message in ["before", "panic", "after"] {
+        logger.log(&common::record(Level::Info, "reentrant", message));
+    }
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    assert_eq!(common::read_logs(&log_dir), "before\npanic\nafter\n");
+    assert_eq!(logger.runtime_stats().unwrap().reentrant_records(), 1);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,130 @@
+//! 离线重新渲染测试：默认文本格式（lz4 压缩）和二进制格式的日志按同一模板输出
+
+mod common;
+
+use rat_logger::clock::FixedClock;
+use rat_logger::config::{encode_record_v2, Record};
+use rat_logger::tools::{reformat, InputKind, ReformatSummary, UNPARSED_MARKER};
+use rat_logger::{FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// 2026-01-02 03:04:05.678 UTC
+const FIXED_MILLIS: u64 = 1_767_323_045_678;
+
+fn record(level: Level, target: &str, message: &str, line: Option<u32>) -> Record {
+    Record {
+        file: line.map(|_| "src/main.rs".to_string()),
+        line,
+        ..common::record(level, target, message)
+    }
+}
+
+fn records() -> Vec<Record> {
+    vec![
+        record(Level::Info, "app", "service started", Some(12)),
+        record(Level::Warn, "app::db", "slow query - 3s", None),
+    ]
+}
+
+/// 统一模板：时间戳使用 Unix 秒数，与运行测试的时区无关
+fn unified_format() -> FormatConfig {
+    FormatConfig {
+        timestamp_format: "%s%.3f".to_string(),
+        format_template: "{timestamp} {level} {target}{?file} ({file}:{line}){/file}: {message}".to_string(),
+        ..Default::default()
+    }
+}
+
+fn find_log(log_dir: &Path) -> PathBuf {
+    std::fs::read_dir(log_dir).unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|e| e == "log"))
+        .unwrap()
+}
+
+fn render(input: &Path, kind: InputKind) -> (String, ReformatSummary) {
+    let mut output = Vec::new();
+    let summary = reformat(input, kind, &unified_format(), &mut output).unwrap();
+    (String::from_utf8(output).unwrap(), summary)
+}
+
+#[test]
+fn test_reformat_text_and_binary_logs_to_one_template() {
+    let dir = common::test_dir("reformat", "reformat_text");
+    let log_dir = dir.join("text");
+
+    // 默认文本格式，带文件头部，之后追加一行无法解析的内容并压缩为 .lz4
+    {
+        let logger = LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .with_clock(Arc::new(FixedClock::from_unix_millis(FIXED_MILLIS)))
+            .add_file(FileConfig {
+                log_dir: log_dir.clone(),
+                write_header: true,
+                ..Default::default()
+            })
+            .build();
+        for record in records() {
+            logger.log(&record);
+        }
+        logger.flush_sync(2000).unwrap();
+    }
+    let mut text = std::fs::read(find_log(&log_dir)).unwrap();
+    text.extend_from_slice(b"    at app::main\n");
+    let text_path = dir.join("old.log.lz4");
+    let mut encoder = lz4::EncoderBuilder::new().build(std::fs::File::create(&text_path).unwrap()).unwrap();
+    encoder.write_all(&text).unwrap();
+    encoder.finish().1.unwrap();
+
+    // 二进制格式：连续的 bincode 记录，末尾是损坏的数据
+    let mut binary = Vec::new();
+    for mut record in records() {
+        record.timestamp = Some(UNIX_EPOCH + Duration::from_millis(FIXED_MILLIS));
+        binary.extend(encode_record_v2(&record).unwrap());
+    }
+    binary.extend_from_slice(&[0xff, 0xff, 0xff]);
+    let binary_path = dir.join("old.bin");
+    std::fs::write(&binary_path, &binary).unwrap();
+
+    let expected_records = "\
+1767323045.678 INFO app (src/main.rs:12): service started
+1767323045.678 WARN app::db: slow query - 3s
+";
+
+    let (text_output, summary) = render(&text_path, InputKind::Text);
+    assert_eq!(text_output, format!("{}{}    at app::main\n", expected_records, UNPARSED_MARKER));
+    assert_eq!(summary, ReformatSummary { records: 2, unparsed: 1 });
+
+    let (binary_output, summary) = render(&binary_path, InputKind::Binary);
+    assert!(binary_output.starts_with(expected_records), "{}", binary_output);
+    let rest = &binary_output[expected_records.len()..];
+    assert!(rest.starts_with(UNPARSED_MARKER) && rest.contains("3 字节") && rest.ends_with(">\n"), "{}", rest);
+    assert_eq!(summary, ReformatSummary { records: 2, unparsed: 1 });
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_reformat_ndjson_log() {
+    let dir = std::env::temp_dir().join(format!("rat_logger_reformat_ndjson_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("old.ndjson");
+    std::fs::write(&path, concat!(
+        r#"{"timestamp": "2026-01-02T03:04:05.678Z", "level": "info", "target": "app", "file": "src/main.rs", "line": 12, "message": "service started"}"#, "\n",
+        "{\"level\": \"warn\", \"message\": \"truncated\n",
+        r#"{"timestamp": 1767323045678, "level": "WARN", "target": "app::db", "message": "slow query - 3s"}"#, "\n",
+    )).unwrap();
+
+    let (output, summary) = render(&path, InputKind::Ndjson);
+    assert_eq!(output, format!(
+        "1767323045.678 INFO app (src/main.rs:12): service started\n{}{{\"level\": \"warn\", \"message\": \"truncated\n1767323045.678 WARN app::db: slow query - 3s\n",
+        UNPARSED_MARKER,
+    ));
+    assert_eq!(summary, ReformatSummary { records: 2, unparsed: 1 });
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
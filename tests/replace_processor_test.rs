@@ -0,0 +1,75 @@
+//! 处理器替换测试：持续记录日志时把文件处理器替换为写入另一个目录的处理器，两个目录合起来的序号连续、不重复
+
+mod common;
+
+use rat_logger::producer_consumer::{BatchConfig, RemoveError};
+use rat_logger::{FileProcessor, Level, LevelFilter, Logger, LoggerBuilder};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn sequence(content: &str) -> Vec<usize> {
+    content.lines().map(|line| line.strip_prefix("seq ").unwrap().parse().unwrap()).collect()
+}
+
+#[test]
+fn test_replace_file_processor_without_gaps_or_duplicates() {
+    let base = common::test_dir("replace_processor", "replace_file");
+    let (old_dir, new_dir) = (base.join("old"), base.join("new"));
+
+    let logger = Arc::new(
+        LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .add_file(common::file_config(&old_dir, Some("{message}")))
+            .build(),
+    );
+    const TOTAL: usize = 20_000;
+    let logged = Arc::new(AtomicUsize::new(0));
+    let writer = {
+        let (logger, logged) = (logger.clone(), logged.clone());
+        std::thread::spawn(move || {
+            for i in 0..TOTAL {
+                // 部分记录为 Error 级别，经过强制写入路径
+                let level = if i % 50 == 0 { Level::Error } else { Level::Info };
+                logger.log(&common::record(level, "replace_processor", format!("seq {}", i)));
+                logged.store(i + 1, Ordering::SeqCst);
+            }
+        })
+    };
+
+    while logged.load(Ordering::SeqCst) < TOTAL / 4 {
+        std::thread::yield_now();
+    }
+    logger
+        .replace_processor("file", FileProcessor::new(common::file_config(&new_dir, Some("{message}"))), BatchConfig::default())
+        .unwrap();
+    writer.join().unwrap();
+    logger.sync_point(Duration::from_secs(5)).unwrap();
+
+    let (old, new) = (sequence(&common::read_logs(&old_dir)), sequence(&common::read_logs(&new_dir)));
+    assert!(!old.is_empty() && !new.is_empty(), "切换应当发生在记录过程中: {} / {}", old.len(), new.len());
+    // 切换点之前的记录全部在旧目录，之后的全部在新目录
+    assert_eq!(old, (0..old.len()).collect::<Vec<_>>());
+    assert_eq!(new, (old.len()..TOTAL).collect::<Vec<_>>());
+    assert_eq!(logger.handler_types(), ["file_processor"]);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&base);
+}
+
+#[test]
+fn test_replace_requires_single_match() {
+    let base = common::test_dir("replace_match", "replace_requires");
+    let logger = LoggerBuilder::new()
+        .add_file(common::file_config(&base.join("a"), Some("{message}")))
+        .add_file(common::file_config(&base.join("b"), Some("{message}")))
+        .build();
+
+    let result = logger.replace_processor("udp", FileProcessor::new(common::file_config(&base.join("c"), Some("{message}"))), BatchConfig::default());
+    assert_eq!(result, Err(RemoveError::NotFound("udp".to_string())));
+    let result = logger.replace_processor("file", FileProcessor::new(common::file_config(&base.join("d"), Some("{message}"))), BatchConfig::default());
+    assert_eq!(result, Err(RemoveError::Ambiguous("file".to_string())));
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&base);
+}
@@ -0,0 +1,31 @@
+//! 采样集成测试：被采样丢弃的记录计入统计，覆盖目标按比例 1.0 全部通过
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, SamplingConfig};
+
+#[test]
+fn test_sampled_out_records_are_counted() {
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Trace)
+        .add_terminal_with_config(rat_logger::handler::term::TermConfig::default())
+        .with_sampling(SamplingConfig {
+            default_ratio: 0.0,
+            per_target: vec![("investigate".to_string(), 1.0)],
+            levels: LevelFilter::Debug,
+            annotate: true,
+            seed: Some(7),
+        })
+        .build();
+
+    for _ in 0..100 {
+        logger.log(&common::record(Level::Debug, "app::noisy", "sampling"));
+    }
+    for _ in 0..3 {
+        logger.log(&common::record(Level::Debug, "investigate::cache", "sampling"));
+    }
+    // Info 不在采样阈值内
+    logger.log(&common::record(Level::Info, "app::noisy", "sampling"));
+
+    assert_eq!(logger.stats().sampled_out(), 100);
+}
@@ -0,0 +1,62 @@
+//! 日志注入防护测试：消息中的 `\r` 和 ANSI 转义序列不能在输出中伪造出新的日志行
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, FormatConfig, SanitizeMode};
+
+const INJECTION: &str = "login ok\r\x1b[2Jfake line";
+
+fn write_and_read(name: &str, format: Option<FormatConfig>, is_raw: bool) -> String {
+    let log_dir = common::test_dir("sanitize", name);
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            format,
+            is_raw,
+            ..Default::default()
+        })
+        .build();
+    logger.log(&common::record(Level::Info, "auth", INJECTION));
+    logger.flush_sync(2000).unwrap();
+
+    let content = common::read_logs(&log_dir);
+    let _ = std::fs::remove_dir_all(&log_dir);
+    content
+}
+
+/// 按任意行终止符拆分，模拟日志解析器和终端的行为
+fn records(content: &str) -> Vec<&str> {
+    content.split(['\n', '\r']).filter(|l| !l.is_empty()).collect()
+}
+
+#[test]
+fn test_file_output_escapes_control_characters_by_default() {
+    let content = write_and_read("default", None, false);
+    let lines = records(&content);
+    assert_eq!(lines.len(), 1, "注入内容不应产生新的记录: {:?}", content);
+    assert!(lines[0].ends_with("login ok\\r\\x1b[2Jfake line"));
+    assert!(!content.contains('\x1b'));
+}
+
+#[test]
+fn test_file_output_honours_configured_mode() {
+    let format = FormatConfig {
+        format_template: "{level} {target} {message}".to_string(),
+        sanitize: Some(SanitizeMode::Strip),
+        timestamp_utc: false,
+        custom_placeholders: Vec::new(),
+        uptime_precision: 3,
+        delta_precision: 1,
+        ..Default::default()
+    };
+    let content = write_and_read("strip", Some(format), false);
+    assert_eq!(records(&content), vec!["INFO auth login okfake line"]);
+}
+
+#[test]
+fn test_raw_mode_bypasses_sanitize() {
+    let content = write_and_read("raw", None, true);
+    assert_eq!(content, format!("{}\n", INJECTION));
+}
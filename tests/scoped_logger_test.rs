@@ -0,0 +1,103 @@
+//! 作用域日志器测试：作用域内的日志宏写入作用域日志器，作用域外写入全局日志器，嵌套和异步任务正确切换
+
+use rat_logger::config::Record;
+use rat_logger::core::Logger;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{debug, info, info_lazy, scoped, warn, LevelFilter, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::{Arc, Once};
+
+type Capture = Arc<Mutex<Vec<String>>>;
+
+/// 构建把消息收集到 `Capture` 的日志器
+fn capture_logger(level: LevelFilter) -> (LoggerBuilder, Capture) {
+    let captured: Capture = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let builder = LoggerBuilder::new()
+        .with_level(level)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.args.to_string()))),
+            ..Default::default()
+        });
+    (builder, captured)
+}
+
+fn scoped_logger(level: LevelFilter) -> (Arc<dyn Logger>, Capture) {
+    let (builder, captured) = capture_logger(level);
+    (Arc::new(builder.build()), captured)
+}
+
+/// 全局日志器只接收 Warn 及以上级别，收集的消息按测试前缀过滤
+fn global_messages(prefix: &str) -> Vec<String> {
+    static INIT: Once = Once::new();
+    static GLOBAL: Mutex<Option<Capture>> = Mutex::new(None);
+    INIT.call_once(|| {
+        let (builder, captured) = capture_logger(LevelFilter::Warn);
+        builder.init_global_logger().unwrap();
+        *GLOBAL.lock() = Some(captured);
+    });
+    rat_logger::core::LOGGER.lock().unwrap().as_ref().unwrap().flush_sync(2000).unwrap();
+    let captured = GLOBAL.lock().clone().unwrap();
+    captured.lock().iter().filter(|m| m.starts_with(prefix)).cloned().collect()
+}
+
+fn flushed(logger: &Arc<dyn Logger>, captured: &Capture) -> Vec<String> {
+    logger.flush_sync(2000).unwrap();
+    captured.lock().clone()
+}
+
+#[test]
+fn test_macros_use_scoped_logger_inside_scope() {
+    global_messages("sync");
+    let (outer, outer_captured) = scoped_logger(LevelFilter::Debug);
+    let (inner, inner_captured) = scoped_logger(LevelFilter::Info);
+
+    warn!("sync global before");
+    let result = scoped::with_logger(outer.clone(), || {
+        // 作用域日志器按自己的级别过滤：全局级别为 Warn，Debug 仍被记录
+        debug!("sync outer debug");
+        scoped::with_logger(inner.clone(), || {
+            debug!("sync inner filtered");
+            info_lazy!(|| "sync inner lazy".to_string());
+        });
+        // 嵌套作用域结束后恢复外层日志器
+        warn!("sync outer after nesting");
+        42
+    });
+    assert_eq!(result, 42);
+    warn!("sync global after");
+    info!("sync global filtered");
+
+    // panic 展开时同样恢复
+    let (panicking, panicking_captured) = scoped_logger(LevelFilter::Info);
+    let unwound = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        scoped::with_logger(panicking.clone(), || panic!("boom"))
+    }));
+    assert!(unwound.is_err());
+    assert!(scoped::current().is_none());
+    warn!("sync global after panic");
+
+    assert_eq!(flushed(&outer, &outer_captured), ["sync outer debug", "sync outer after nesting"]);
+    assert_eq!(flushed(&inner, &inner_captured), ["sync inner lazy"]);
+    assert!(flushed(&panicking, &panicking_captured).is_empty());
+    assert_eq!(global_messages("sync"), ["sync global before", "sync global after", "sync global after panic"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_scoped_logger_follows_async_task() {
+    global_messages("async");
+    let (logger, captured) = scoped_logger(LevelFilter::Info);
+
+    scoped::with_logger_async(logger.clone(), async {
+        info!("async before await");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        tokio::task::yield_now().await;
+        info!("async after await");
+    })
+    .await;
+    warn!("async global");
+
+    assert_eq!(flushed(&logger, &captured), ["async before await", "async after await"]);
+    assert_eq!(global_messages("async"), ["async global"]);
+}
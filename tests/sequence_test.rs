@@ -0,0 +1,61 @@
+//! 日志器序号与微秒时间戳测试：快速记录大量日志，文件中的 `{seq}` 必须严格递增
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, FileConfig, FormatConfig};
+
+const RECORD_COUNT: u64 = 10_000;
+
+#[test]
+fn test_seq_strictly_increasing_with_microsecond_timestamps() {
+    let log_dir = common::test_dir("seq", "seq_strictly");
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_sequence(true)
+        .add_file(FileConfig {
+            log_dir: log_dir.clone(),
+            max_file_size: 100 * 1024 * 1024,
+            format: Some(FormatConfig {
+                timestamp_format: "%Y-%m-%d %H:%M:%S%.6f".to_string(),
+                format_template: "{seq}|{timestamp}|{message}".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .build();
+
+    for i in 0..RECORD_COUNT {
+        logger.log(&common::record(Level::Info, "seq_test", format!("record {}", i)));
+    }
+    logger.flush_sync(5000).unwrap();
+
+    let mut content = String::new();
+    for entry in std::fs::read_dir(&log_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "log") {
+            content.push_str(&std::fs::read_to_string(&path).unwrap());
+        }
+    }
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    let mut last: Option<(u64, String)> = None;
+    let mut count = 0;
+    for line in content.lines() {
+        let mut parts = line.splitn(3, '|');
+        let seq: u64 = parts.next().unwrap().parse().expect("每行都应以序号开头");
+        let timestamp = parts.next().unwrap().to_string();
+        let fraction = timestamp.rsplit('.').next().unwrap();
+        assert_eq!(fraction.len(), 6, "时间戳应包含微秒: {}", timestamp);
+
+        if let Some((last_seq, last_timestamp)) = &last {
+            assert!(seq > *last_seq, "序号必须严格递增: {} 之后是 {}", last_seq, seq);
+            // 时间在 log() 中捕获，单线程记录时不会倒退
+            assert!(timestamp >= *last_timestamp, "时间戳倒退: {} 之后是 {}", last_timestamp, timestamp);
+        }
+        last = Some((seq, timestamp));
+        count += 1;
+    }
+    assert_eq!(count, RECORD_COUNT);
+    assert_eq!(last.unwrap().0, RECORD_COUNT - 1);
+}
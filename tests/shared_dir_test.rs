@@ -0,0 +1,78 @@
+//! 共享日志目录测试：同一目录的第二个文件处理器按 `shared_dir_policy` 报错或使用独立文件名前缀，
+//! 各自的轮转和清理不会删除对方的活动文件
+
+mod common;
+
+use rat_logger::handler::file::LOCK_FILE_NAME;
+use rat_logger::{FileConfig, FileProcessor, FileProcessorError, Level, LevelFilter, Logger, LoggerBuilder, SharedDirPolicy};
+use std::path::{Path, PathBuf};
+
+fn log_files(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    std::fs::read_dir(dir).unwrap()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .map(|entry| entry.path())
+        .collect()
+}
+
+#[test]
+fn test_second_processor_rejected_by_default() {
+    let dir = common::test_dir("shared_dir", "error");
+    let config = FileConfig { log_dir: dir.clone(), ..Default::default() };
+
+    let owner = FileProcessor::new(config.clone());
+    assert!(dir.join(LOCK_FILE_NAME).exists());
+    assert_eq!(FileProcessor::try_new(config.clone()).err(), Some(FileProcessorError::DirInUse(dir.clone())));
+
+    let err = LoggerBuilder::new().add_file(config.clone()).try_build().err().unwrap();
+    assert!(err.contains("日志目录已被另一个文件处理器使用"), "{}", err);
+
+    // 持有锁的处理器销毁后目录可以再次使用
+    drop(owner);
+    assert!(FileProcessor::try_new(config).is_ok());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_prefix_policy_isolates_rotation_and_cleanup() {
+    let dir = common::test_dir("shared_dir", "prefix");
+    let config = FileConfig {
+        log_dir: dir.clone(),
+        max_file_size: 1024,
+        max_compressed_files: 0,
+        compression_level: 0,
+        max_total_size: 2048,
+        ..Default::default()
+    };
+
+    let owner = LoggerBuilder::new().with_level(LevelFilter::Info).add_file(config.clone()).build();
+    let guest = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig { shared_dir_policy: SharedDirPolicy::Prefix, ..config })
+        .build();
+
+    guest.log(&common::record(Level::Info, "shared_dir", "guest first"));
+    guest.flush_sync(2000).unwrap();
+
+    // 持有锁的日志器多次轮转并按总大小清理旧文件
+    for i in 0..200 {
+        owner.log(&common::record(Level::Info, "shared_dir", format!("owner {:03} {}", i, "x".repeat(100))));
+    }
+    owner.flush_sync(5000).unwrap();
+    guest.log(&common::record(Level::Info, "shared_dir", "guest last"));
+    guest.flush_sync(2000).unwrap();
+
+    let guest_prefix = format!("app-{}-", std::process::id());
+    let guest_files = log_files(&dir, &guest_prefix);
+    assert_eq!(guest_files.len(), 1, "{:?}", guest_files);
+    let content = std::fs::read_to_string(&guest_files[0]).unwrap();
+    assert!(content.contains("guest first") && content.contains("guest last"), "{}", content);
+
+    let owner_files = log_files(&dir, "app_");
+    assert!(!owner_files.is_empty());
+    let owner_size: u64 = owner_files.iter().map(|path| std::fs::metadata(path).unwrap().len()).sum();
+    assert!(owner_size <= 2048 + 1024, "按总大小清理只针对自己的文件: {}", owner_size);
+
+    drop((owner, guest));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,104 @@
+//! 两阶段关闭测试：关闭期间仍在记录的线程提交的每条记录要么被写入，要么计入被拒绝数，不会无声丢失
+
+mod common;
+
+use rat_logger::core::LoggerCore;
+use rat_logger::producer_consumer::{BatchConfig, LogProcessor, ProcessorError};
+use rat_logger::{FileConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 只统计收到的记录数的处理器
+struct CountingProcessor {
+    written: Arc<AtomicU64>,
+}
+
+impl LogProcessor for CountingProcessor {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+
+    fn process(&mut self, _data: &[u8]) -> Result<(), ProcessorError> {
+        self.written.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+/// 只有一个计数处理器的日志器
+fn counting_logger(name: &str, batch: BatchConfig) -> (LoggerCore, Arc<AtomicU64>) {
+    let log_dir = std::env::temp_dir().join(format!("rat_logger_shutdown_{}_{}", name, std::process::id()));
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .build();
+    let written = Arc::new(AtomicU64::new(0));
+    logger.attach_processor(CountingProcessor { written: written.clone() }, batch).unwrap();
+    logger.detach_handler("file").unwrap();
+    let _ = std::fs::remove_dir_all(&log_dir);
+    (logger, written)
+}
+
+#[test]
+fn test_records_across_shutdown_are_written_or_rejected() {
+    // 大批量配置使记录在关闭时仍停留在批量缓冲区中
+    let (logger, written) = counting_logger("producers", BatchConfig::throughput());
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let producers: Vec<_> = (0..4)
+        .map(|t| {
+            let (logger, stop) = (logger.clone(), stop.clone());
+            std::thread::spawn(move || {
+                let mut sent = 0u64;
+                while !stop.load(Ordering::Relaxed) {
+                    // 每个线程也记录 Error 级别（强制写入路径）
+                    let level = if sent.is_multiple_of(50) { Level::Error } else { Level::Info };
+                    logger.log(&common::record(level, "shutdown", format!("record {}", t * 1_000_000 + sent)));
+                    sent += 1;
+                }
+                sent
+            })
+        })
+        .collect();
+
+    std::thread::sleep(Duration::from_millis(50));
+    logger.shutdown(Duration::from_secs(10)).unwrap();
+    // 关闭后继续记录一段时间
+    std::thread::sleep(Duration::from_millis(20));
+    stop.store(true, Ordering::Relaxed);
+    let sent: u64 = producers.into_iter().map(|producer| producer.join().unwrap()).sum();
+
+    let stats = logger.runtime_stats().unwrap();
+    let rejected = stats.shutdown_rejected();
+    assert!(rejected > 0, "关闭后没有拒绝任何记录");
+    assert_eq!(written.load(Ordering::SeqCst) + rejected, sent, "写入 {} 拒绝 {}", written.load(Ordering::SeqCst), rejected);
+    assert_eq!(stats.in_flight_bytes(), 0);
+}
+
+#[test]
+fn test_shutdown_is_idempotent_and_rejects_direct_paths() {
+    let (logger, written) = counting_logger("idempotent", BatchConfig::low_latency());
+    for i in 0..100 {
+        logger.log(&common::record(Level::Info, "shutdown", format!("record {}", i)));
+    }
+    logger.shutdown(Duration::from_secs(5)).unwrap();
+    assert_eq!(written.load(Ordering::SeqCst), 100);
+
+    // 重复关闭立即返回，之后的各种路径都不会 panic
+    logger.shutdown(Duration::from_secs(5)).unwrap();
+    logger.log(&common::record(Level::Info, "shutdown", "record 100"));
+    logger.emergency_log(&common::record(Level::Warn, "shutdown", "record 101"));
+    assert_eq!(logger.log_raw("counting", b"raw line").unwrap_err(), "日志器已关闭");
+    logger.flush();
+    assert!(logger.flush_sync(100).is_err());
+    assert_eq!(logger.runtime_stats().unwrap().shutdown_rejected(), 3);
+    assert_eq!(written.load(Ordering::SeqCst), 100);
+}
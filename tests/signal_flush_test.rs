@@ -0,0 +1,99 @@
+//! SIGTERM 刷新测试
+//!
+//! 测试进程以子进程模式重新启动自身：子进程持续写入日志（批量配置足够大，不刷新就不会落盘），
+//! 父进程在中途发送 SIGTERM，断言文件以信号前最后一条记录结尾。
+
+#![cfg(unix)]
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, FileConfig, Logger, Signal};
+use rat_logger::config::Record;
+use rat_logger::producer_consumer::BatchConfig;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const CHILD_ENV: &str = "RAT_LOGGER_SIGNAL_CHILD_DIR";
+
+fn run_child(log_dir: &Path) {
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_async_mode(true)
+        .with_batch_config(BatchConfig {
+            batch_size: 100_000,
+            batch_interval_ms: 60_000,
+            buffer_size: 1024 * 1024,
+            ..Default::default()
+        })
+        .add_file(FileConfig {
+            log_dir: log_dir.to_path_buf(),
+            ..Default::default()
+        })
+        .with_signal_flush(&[Signal::Term])
+        .build();
+
+    let stdout = std::io::stdout();
+    for i in 0.. {
+        let record = Record {
+            module_path: None,
+            file: None,
+            line: None,
+            ..common::record(Level::Info, "signal_test", format!("record #{}", i))
+        };
+        logger.log(&record);
+
+        let mut out = stdout.lock();
+        let _ = writeln!(out, "LOGGED {}", i);
+        let _ = out.flush();
+        drop(out);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+}
+
+#[test]
+fn sigterm_flushes_pending_records() {
+    if let Ok(dir) = std::env::var(CHILD_ENV) {
+        run_child(Path::new(&dir));
+        return;
+    }
+
+    let log_dir = common::test_dir("signal", "sigterm_flushes");
+
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "sigterm_flushes_pending_records", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, &log_dir)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let reader = BufReader::new(child.stdout.take().unwrap());
+    // 发送信号前最后确认写入的记录
+    let mut signalled_at = None;
+    for line in reader.lines() {
+        let line = line.unwrap();
+        if let Some(n) = line.strip_prefix("LOGGED ") {
+            let n: u64 = n.trim().parse().unwrap();
+            if n == 50 && signalled_at.is_none() {
+                Command::new("kill").args(["-TERM", &child.id().to_string()]).status().unwrap();
+                signalled_at = Some(n);
+            }
+        }
+    }
+
+    let status = child.wait().unwrap();
+    use std::os::unix::process::ExitStatusExt;
+    assert_eq!(status.signal(), Some(15), "子进程应当以 SIGTERM 结束");
+
+    let signalled_at = signalled_at.expect("子进程没有输出");
+    let lines = common::log_lines(&log_dir, "");
+    let _ = std::fs::remove_dir_all(&log_dir);
+
+    // 信号处理线程刷新期间子进程仍在记录：信号之后的记录可能写入也可能丢失，
+    // 信号之前的记录必须全部按顺序写入
+    let numbers: Vec<u64> = lines.iter().map(|line| line.rsplit('#').next().unwrap().parse().unwrap()).collect();
+    assert!(!numbers.is_empty(), "日志文件为空，信号刷新没有生效");
+    assert_eq!(numbers, (0..numbers.len() as u64).collect::<Vec<_>>(), "记录不连续");
+    let tail = *numbers.last().unwrap();
+    assert!(tail >= signalled_at, "文件末尾 {} 早于信号前最后一条记录 {}", tail, signalled_at);
+}
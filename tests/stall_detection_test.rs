@@ -0,0 +1,56 @@
+//! 卡住检测测试：处理器操作超过阈值时上报卡住，完成后上报恢复和实际耗时
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{DiagnosticEvent, Level, LevelFilter, Logger, LoggerBuilder};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_slow_processor_reports_stall_and_recovery() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+
+    // 每条记录输出前阻塞 500ms，模拟挂起的写入
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(|_: &Record| std::thread::sleep(Duration::from_millis(500)))),
+            ..Default::default()
+        })
+        .with_stall_threshold(Duration::from_millis(100))
+        .with_error_callback(move |event| sink.lock().push(event.clone()))
+        .build();
+
+    logger.log(&common::record(Level::Info, "stall", "slow write"));
+
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(logger.stats().stalled(), 1, "操作执行中应被判定为卡住: {:?}", events.lock());
+
+    logger.flush_sync(2000).unwrap();
+    assert_eq!(logger.stats().stalled(), 0);
+
+    let events = events.lock();
+    assert_eq!(events.len(), 2, "{:?}", events);
+    assert!(matches!(
+        &events[0],
+        DiagnosticEvent::ProcessorStalled { processor: "term_processor", elapsed } if *elapsed >= Duration::from_millis(100)
+    ));
+    assert!(matches!(
+        &events[1],
+        DiagnosticEvent::ProcessorRecovered { processor: "term_processor", duration } if *duration >= Duration::from_millis(500)
+    ));
+}
+
+#[test]
+fn test_zero_stall_threshold_rejected() {
+    let result = LoggerBuilder::new()
+        .add_terminal_with_config(TermConfig::default())
+        .with_stall_threshold(Duration::ZERO)
+        .try_build();
+    assert!(result.is_err_and(|e| e.contains("卡住阈值")));
+}
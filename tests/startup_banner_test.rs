@@ -0,0 +1,59 @@
+//! 启动横幅测试：横幅经启动日志路径立即写入文件，包含处理器配置摘要，UDP 认证令牌被隐藏
+
+mod common;
+
+use rat_logger::startup::{banner_lines, log_banner, BannerInfo};
+use rat_logger::{FileConfig, LevelFilter, LoggerBuilder, NetworkConfig};
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_banner_written_to_file_with_token_masked() {
+    let log_dir = common::test_dir("startup_banner", "banner_written");
+    let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Warn)
+        .add_terminal_with_config(rat_logger::handler::term::TermConfig::default())
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .add_udp(NetworkConfig {
+            server_addr: "127.0.0.1".to_string(),
+            server_port: collector.local_addr().unwrap().port(),
+            auth_token: "secret-token".to_string(),
+            app_id: "banner_test".to_string(),
+        })
+        .init_global_logger()
+        .unwrap();
+
+    let info = BannerInfo::new("banner-app", "1.2.3").with_extra("git_sha", "abc123");
+    log_banner(info.clone());
+
+    let logger = rat_logger::core::LOGGER.lock().unwrap().clone().unwrap();
+    let lines = banner_lines(logger.as_ref(), &info);
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "banner-app 1.2.3 启动");
+    assert!(lines[1].starts_with(&format!("rat_logger {} | pid {} | host ", env!("CARGO_PKG_VERSION"), std::process::id())));
+    assert!(lines[1].ends_with("| level Warn"));
+    assert_eq!(lines[2], "handler terminal: color=on");
+    assert_eq!(lines[3], format!("handler file: log_dir={}, max_file_size=10MiB, compression=keep 10", log_dir.display()));
+    assert_eq!(lines[4], format!("handler udp: target=127.0.0.1:{}, token=***", collector.local_addr().unwrap().port()));
+    assert_eq!(lines[5], "git_sha = abc123");
+
+    // 级别为 Warn 时 Info 级别的横幅仍然立即写入，不需要显式刷新
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let content = loop {
+        let content: String = std::fs::read_dir(&log_dir).into_iter().flatten().flatten()
+            .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+            .collect();
+        if content.lines().count() >= lines.len() || Instant::now() > deadline {
+            break content;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    for line in &lines {
+        assert!(content.contains(line.as_str()), "文件中缺少横幅行 {:?}:\n{}", line, content);
+    }
+    assert!(!content.contains("secret-token"));
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
@@ -0,0 +1,86 @@
+//! 统计导出测试：导出线程定期送出计数器单调递增的快照，日志器释放后停止；快照的序列化字段名保持稳定
+
+mod common;
+
+use rat_logger::core::LoggerCore;
+use rat_logger::stats::StatsSnapshot;
+use rat_logger::{FileConfig, Level, LevelFilter, Logger, LoggerBuilder, SamplingConfig};
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn log(logger: &LoggerCore, level: Level, i: usize) {
+    logger.log(&common::record(level, "stats_exporter", format!("record {}", i)));
+}
+
+#[test]
+fn test_exporter_delivers_increasing_snapshots() {
+    let log_dir = common::test_dir("stats_exporter", "increasing");
+    let (sender, receiver) = mpsc::channel();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Debug)
+        // Debug 记录全部被采样丢弃
+        .with_sampling(SamplingConfig {
+            default_ratio: 0.0,
+            per_target: Vec::new(),
+            levels: LevelFilter::Debug,
+            annotate: false,
+            seed: Some(1),
+        })
+        .with_stats_exporter(Duration::from_millis(50), move |snapshot| {
+            let _ = sender.send(snapshot);
+        })
+        .add_file(FileConfig { log_dir: log_dir.clone(), ..Default::default() })
+        .build();
+
+    let mut snapshots = vec![receiver.recv_timeout(Duration::from_secs(5)).unwrap()];
+    for round in 1..=3 {
+        for i in 0..10 {
+            log(&logger, Level::Info, i);
+            log(&logger, Level::Debug, i);
+        }
+        // 等到包含本轮记录的快照
+        loop {
+            let snapshot = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            snapshots.push(snapshot);
+            if snapshot.submitted == round * 10 {
+                break;
+            }
+        }
+    }
+
+    for pair in snapshots.windows(2) {
+        assert!(pair[1].submitted >= pair[0].submitted && pair[1].sampled_out >= pair[0].sampled_out, "{:?}", pair);
+    }
+    let last = snapshots.last().unwrap();
+    assert_eq!((last.submitted, last.sampled_out), (30, 30));
+    assert_eq!(*last, logger.stats_snapshot());
+
+    // 日志器释放后导出线程停止，回调（和其中的发送端）随之释放
+    drop(logger);
+    while receiver.recv_timeout(Duration::from_secs(5)).is_ok() {}
+    assert_eq!(receiver.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_snapshot_field_names_are_stable() {
+    let value = serde_json::to_value(StatsSnapshot::default()).unwrap();
+    let mut names: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "archives_expired", "breaker_dropped", "breaker_trips", "compressions_skipped", "dropped", "emergency",
+            "memory_dropped", "processor_errors", "protected_records", "reentrant_records", "sampled_out", "shutdown_rejected", "spool_dropped",
+            "submitted",
+        ]
+    );
+
+    let error = LoggerBuilder::new()
+        .add_terminal_with_config(Default::default())
+        .with_stats_exporter(Duration::ZERO, |_| {})
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(error.contains("统计导出间隔不能为 0"), "{}", error);
+}
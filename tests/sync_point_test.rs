@@ -0,0 +1,157 @@
+//! 同步点测试：`sync_point` 返回后此前记录的日志已全部写出，示例中日志后的 `sleep` 可以去掉
+
+mod common;
+
+use rat_logger::config::Record;
+use rat_logger::core::LoggerCore;
+use rat_logger::{FileConfig, FlushError, FormatConfig, Level, LevelFilter, LevelStyle, Logger, LoggerBuilder};
+use std::path::Path;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn file_logger(log_dir: &Path, format: Option<FormatConfig>, force_sync: bool, level: LevelFilter) -> LoggerCore {
+    LoggerBuilder::new()
+        .with_level(level)
+        .add_file(FileConfig {
+            log_dir: log_dir.to_path_buf(),
+            force_sync,
+            format,
+            ..Default::default()
+        })
+        .build()
+}
+
+fn record(level: Level, target: &str, message: String) -> Record {
+    Record {
+        file: Some("sync_point_test.rs".to_string()),
+        line: Some(42),
+        ..common::record(level, target, message)
+    }
+}
+
+/// 对应 `file_format_example`：四种格式的文件日志器各写一条错误和一条信息日志
+#[test]
+fn test_file_formats_visible_after_sync_point() {
+    let short_style = LevelStyle {
+        error: "E".to_string(),
+        warn: "W".to_string(),
+        info: "I".to_string(),
+        debug: "D".to_string(),
+        trace: "T".to_string(),
+    };
+    let formats = [
+        ("default", None),
+        ("simple", Some(FormatConfig {
+            timestamp_format: "%H:%M:%S".to_string(),
+            level_style: short_style,
+            format_template: "{level} {timestamp} {message}".to_string(),
+            ..Default::default()
+        })),
+        ("detailed", Some(FormatConfig {
+            format_template: "[{level}] {target}:{line} - {message}".to_string(),
+            ..Default::default()
+        })),
+        ("json", Some(FormatConfig {
+            format_template: r#"{"level":"{level}","target":"{target}","message":"{message}"}"#.to_string(),
+            ..Default::default()
+        })),
+    ];
+
+    let mut loggers = Vec::new();
+    for (name, format) in formats {
+        let dir = common::test_dir("sync_point", name);
+        let logger = file_logger(&dir, format, false, LevelFilter::Debug);
+        logger.log(&record(Level::Error, "format_test", format!("{} 错误消息", name)));
+        logger.log(&record(Level::Info, "format_test", format!("{} 信息消息", name)));
+        loggers.push((name, dir, logger));
+    }
+
+    for (name, dir, logger) in &loggers {
+        logger.sync_point(TIMEOUT).unwrap();
+        assert_eq!(common::log_lines(dir, name).len(), 2, "{} 格式的日志未全部写出", name);
+    }
+
+    let simple = common::log_lines(&loggers[1].1, "simple");
+    assert!(simple[0].starts_with("E ") && simple[0].ends_with("simple 错误消息"), "{:?}", simple);
+    assert!(simple[1].starts_with("I ") && simple[1].ends_with("simple 信息消息"), "{:?}", simple);
+    assert_eq!(common::log_lines(&loggers[2].1, "detailed"), ["[ERROR] format_test:42 - detailed 错误消息", "[INFO] format_test:42 - detailed 信息消息"]);
+    assert_eq!(common::log_lines(&loggers[3].1, "json")[0], r#"{"level":"ERROR","target":"format_test","message":"json 错误消息"}"#);
+
+    for (_, dir, logger) in loggers {
+        drop(logger);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+/// 对应 `sync_async_demo`：异步写入、同步写入和混合场景的日志在同步点之后全部可见
+#[test]
+fn test_async_and_sync_writes_complete_at_sync_point() {
+    for (name, force_sync) in [("async", false), ("sync", true)] {
+        let dir = common::test_dir("sync_point", name);
+        let logger = file_logger(&dir, None, force_sync, LevelFilter::Info);
+        for i in 0..100 {
+            logger.log(&record(Level::Info, name, format!("{} 日志消息 #{}", name, i)));
+        }
+        logger.sync_point(TIMEOUT).unwrap();
+
+        let lines = common::log_lines(&dir, "日志消息");
+        assert_eq!(lines.len(), 100, "{} 模式的日志未全部写出", name);
+        assert!(lines[99].ends_with(&format!("{} 日志消息 #99", name)));
+        drop(logger);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // 混合场景：业务日志异步写入，关键错误同步写入
+    let business_dir = common::test_dir("sync_point", "business");
+    let error_dir = common::test_dir("sync_point", "critical_error");
+    let business = file_logger(&business_dir, None, false, LevelFilter::Info);
+    let errors = file_logger(&error_dir, None, true, LevelFilter::Error);
+    for i in 0..50 {
+        if i % 10 == 0 {
+            errors.log(&record(Level::Error, "critical_error", format!("严重错误！处理失败，ID: {}", i)));
+        }
+        business.log(&record(Level::Info, "business", format!("用户操作日志 #{}", i)));
+    }
+    business.sync_point(TIMEOUT).unwrap();
+    errors.sync_point(TIMEOUT).unwrap();
+
+    assert_eq!(common::log_lines(&business_dir, "用户操作日志").len(), 50);
+    assert_eq!(common::log_lines(&error_dir, "严重错误").len(), 5);
+    drop((business, errors));
+    let _ = std::fs::remove_dir_all(&business_dir);
+    let _ = std::fs::remove_dir_all(&error_dir);
+}
+
+/// 全局日志器的同步点：批量间隔远大于测试时长，日志只能由同步点写出
+#[test]
+fn test_global_sync_point() {
+    assert_eq!(rat_logger::sync_point(TIMEOUT), Err(FlushError::NotInitialized));
+
+    let dir = common::test_dir("sync_point", "global");
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_batch_config(rat_logger::producer_consumer::BatchConfig {
+            batch_size: 1000,
+            batch_interval_ms: 60_000,
+            buffer_size: 4096,
+            ..Default::default()
+        })
+        .add_file(FileConfig {
+            log_dir: dir.clone(),
+            ..Default::default()
+        })
+        .init_global_logger()
+        .unwrap();
+
+    // 错误日志会先写出已缓冲的记录，放在信息日志之前
+    rat_logger::error!("全局错误");
+    for i in 0..10 {
+        rat_logger::info!("全局日志 #{}", i);
+    }
+    rat_logger::sync_point(TIMEOUT).unwrap();
+
+    assert_eq!(common::log_lines(&dir, "全局日志").len(), 10);
+    assert_eq!(common::log_lines(&dir, "全局错误").len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,89 @@
+//! 多线程记录顺序测试：线程标记（`{tid}`/`{tseq}`）和文件处理器的批次内按时间排序
+
+mod common;
+
+use rat_logger::config::ThreadStamp;
+use rat_logger::producer_consumer::BatchConfig;
+use rat_logger::{FileConfig, FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const THREADS: usize = 8;
+const PER_THREAD: usize = 100;
+const BATCH_SIZE: usize = 100;
+
+#[test]
+fn test_batches_sorted_by_timestamp_across_threads() {
+    let log_dir = common::test_dir("thread_order", "batches_sorted");
+
+    let logger = Arc::new(
+        LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .with_async_mode(true)
+            // 只按记录数分批，批次边界是确定的
+            .with_batch_config(BatchConfig { batch_size: BATCH_SIZE, batch_interval_ms: 60_000, buffer_size: 1024, ..Default::default() })
+            .add_file(FileConfig {
+                log_dir: log_dir.clone(),
+                format: Some(FormatConfig {
+                    timestamp_format: "%Y-%m-%dT%H:%M:%S%.9f".to_string(),
+                    format_template: "{timestamp} {tid} {tseq} {message}".to_string(),
+                    ..Default::default()
+                }),
+                order_by_timestamp: true,
+                ..Default::default()
+            })
+            .build(),
+    );
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let logger = logger.clone();
+            std::thread::spawn(move || {
+                for i in 0..PER_THREAD {
+                    logger.log(&common::record(Level::Info, "thread_order", format!("thread {} #{}", t, i)));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    logger.flush_sync(5000).unwrap();
+
+    let mut content = String::new();
+    for entry in std::fs::read_dir(&log_dir).unwrap().flatten() {
+        content.push_str(&std::fs::read_to_string(entry.path()).unwrap());
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), THREADS * PER_THREAD);
+
+    // 每个批次内的时间不减
+    for batch in lines.chunks(BATCH_SIZE) {
+        let timestamps: Vec<&str> = batch.iter().map(|line| line.split(' ').next().unwrap()).collect();
+        assert!(timestamps.windows(2).all(|w| w[0] <= w[1]), "批次内时间应当不减: {:?}", batch);
+    }
+
+    // 同一线程的线程内序号从 0 开始连续递增，可据此还原每个线程的产生顺序
+    let mut next_seq: HashMap<&str, u64> = HashMap::new();
+    for line in &lines {
+        let mut parts = line.split(' ');
+        let (tid, tseq) = (parts.nth(1).unwrap(), parts.next().unwrap().parse::<u64>().unwrap());
+        let expected = next_seq.entry(tid).or_insert(0);
+        assert_eq!(tseq, *expected, "{}", line);
+        *expected += 1;
+    }
+    assert_eq!(next_seq.len(), THREADS);
+
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[test]
+fn test_thread_stamp_per_thread_sequence() {
+    let first = ThreadStamp::next();
+    let second = ThreadStamp::next();
+    assert_eq!((second.id, second.seq), (first.id, first.seq + 1));
+
+    let other = std::thread::spawn(ThreadStamp::next).join().unwrap();
+    assert_ne!(other.id, first.id);
+    assert_eq!(other.seq, 0);
+}
@@ -0,0 +1,107 @@
+//! UDP批量发送回环测试：批量数据报可还原出全部记录，单条记录在最长等待时间内发出
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, NetworkConfig, UdpConfig, UdpProcessor};
+use rat_logger::config::encode_record_v2;
+use rat_logger::producer_consumer::{BatchConfig, LogProcessor};
+use rat_logger::udp_helper::UdpPacketHelper;
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+
+fn loopback_receiver() -> (UdpSocket, NetworkConfig) {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let config = NetworkConfig {
+        server_addr: "127.0.0.1".to_string(),
+        server_port: socket.local_addr().unwrap().port(),
+        ..Default::default()
+    };
+    (socket, config)
+}
+
+fn recv_messages(socket: &UdpSocket) -> (bool, Vec<String>) {
+    let mut buf = vec![0u8; 65536];
+    let len = socket.recv(&mut buf).unwrap();
+    let datagram = &buf[..len];
+    let records = UdpPacketHelper::decode_datagram(datagram).unwrap();
+    (UdpPacketHelper::is_batch(datagram), records.into_iter().map(|r| r.message).collect())
+}
+
+#[test]
+fn test_batched_datagram_decodes_all_records() {
+    let (socket, network_config) = loopback_receiver();
+    let mut processor = UdpProcessor::with_config(UdpConfig {
+        network_config,
+        batch_size: 4,
+        max_wait_time_ms: 10_000,
+        ..Default::default()
+    });
+
+    let batch: Vec<Vec<u8>> = (0..4)
+        .map(|i| encode_record_v2(&common::record(Level::Info, "udp_batch", format!("batched #{}", i))).unwrap())
+        .collect();
+    processor.process_batch(&batch).unwrap();
+
+    let (is_batch, messages) = recv_messages(&socket);
+    assert!(is_batch, "达到批量记录数后应当发送批量数据报");
+    assert_eq!(messages, (0..4).map(|i| format!("batched #{}", i)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_byte_cap_splits_batches() {
+    let (socket, network_config) = loopback_receiver();
+    let mut processor = UdpProcessor::with_config(UdpConfig {
+        network_config,
+        batch_size: 1000,
+        max_batch_bytes: 1024,
+        max_wait_time_ms: 10_000,
+        ..Default::default()
+    });
+
+    let batch: Vec<Vec<u8>> = (0..20)
+        .map(|i| encode_record_v2(&common::record(Level::Info, "udp_batch", format!("{:0>100}", i))).unwrap())
+        .collect();
+    processor.process_batch(&batch).unwrap();
+    processor.flush().unwrap();
+
+    let mut received = Vec::new();
+    while received.len() < 20 {
+        let (_, messages) = recv_messages(&socket);
+        assert!(!messages.is_empty());
+        received.extend(messages);
+    }
+    assert_eq!(received, (0..20).map(|i| format!("{:0>100}", i)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_lone_record_ships_within_wait_time() {
+    let (socket, network_config) = loopback_receiver();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .with_async_mode(true)
+        .with_batch_config(BatchConfig {
+            batch_size: 1,
+            batch_interval_ms: 1,
+            buffer_size: 1024,
+            ..Default::default()
+        })
+        .add_udp_with_config(UdpConfig {
+            network_config,
+            batch_size: 100,
+            max_wait_time_ms: 200,
+            ..Default::default()
+        })
+        .build();
+
+    let start = Instant::now();
+    logger.log(&common::record(Level::Info, "udp_batch", "lonely"));
+
+    // 没有后续记录，工作线程空闲时仍需在最长等待时间到达后发出
+    let (is_batch, messages) = recv_messages(&socket);
+    let elapsed = start.elapsed();
+    assert!(!is_batch, "单条记录不应加批量帧");
+    assert_eq!(messages, vec!["lonely".to_string()]);
+    assert!(elapsed >= Duration::from_millis(150), "记录过早发出: {:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(2), "记录没有在等待时间内发出: {:?}", elapsed);
+}
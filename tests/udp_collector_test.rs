@@ -0,0 +1,57 @@
+//! UDP 收集器测试：`UdpCollector` 接收日志宏经 UDP 发出的记录
+
+use rat_logger::test_util::UdpCollector;
+use rat_logger::{LoggerBuilder, LevelFilter, Logger, UdpConfig};
+use std::net::UdpSocket;
+use std::time::Duration;
+
+#[test]
+fn test_collector_receives_macro_records() {
+    let collector = UdpCollector::bind().unwrap();
+
+    LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp(collector.network_config())
+        .init_global_logger()
+        .unwrap();
+
+    rat_logger::info!("first");
+    rat_logger::warn!("second {}", 2);
+    rat_logger::error!("third");
+
+    assert!(collector.wait_for(3, Duration::from_secs(5)), "{:?}", collector.messages());
+    let mut messages = collector.messages();
+    messages.sort();
+    assert_eq!(messages, ["first", "second 2", "third"]);
+}
+
+#[test]
+fn test_collector_decodes_batches_and_counts_invalid_datagrams() {
+    let collector = UdpCollector::bind().unwrap();
+    let handle = collector.handle();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.send_to(b"not a packet", collector.addr()).unwrap();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(UdpConfig {
+            network_config: collector.network_config(),
+            batch_size: 4,
+            ..Default::default()
+        })
+        .build();
+    for i in 0..4 {
+        logger.log(&rat_logger::config::Record {
+            args: format!("batched {}", i).into(),
+            ..Default::default()
+        });
+    }
+
+    assert!(handle.wait_for(4, Duration::from_secs(5)), "{:?}", handle.messages());
+    assert_eq!(handle.messages(), ["batched 0", "batched 1", "batched 2", "batched 3"]);
+
+    drop(collector);
+    // 收集器释放后句柄仍可读取已收到的记录
+    assert_eq!(handle.len(), 4);
+    assert_eq!(handle.invalid(), 1);
+}
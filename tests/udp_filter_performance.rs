@@ -0,0 +1,73 @@
+//! UDP数据包预过滤性能对比
+//!
+//! 对比服务端按级别/应用预过滤时：
+//! 1. 完整解码 NetRecord 后过滤（旧方式）
+//! 2. 只解析头部字段后过滤（`PacketMetadata::parse_header`）
+
+mod common;
+
+use rat_logger::Level;
+use rat_logger::config::Record;
+use rat_logger::udp_helper::{UdpPacketHelper, UdpBatchProcessor, PacketMetadata};
+use std::time::Instant;
+
+const PACKET_COUNT: usize = 10000;
+const ROUNDS: usize = 10;
+
+fn create_packets() -> Vec<Vec<u8>> {
+    let levels = Level::ALL;
+    (0..PACKET_COUNT)
+        .map(|i| {
+            let record = Record {
+                module_path: Some("filter_bench".to_string()),
+                file: Some("filter_bench.rs".to_string()),
+                line: Some(42),
+                ..common::record(levels[i % levels.len()], "filter_bench", format!("{} {}", i, "payload ".repeat(128)))
+            };
+            let app_id = if i % 3 == 0 { "wanted_app" } else { "other_app" };
+            UdpPacketHelper::encode_record(&record, None, Some(app_id.to_string())).unwrap()
+        })
+        .collect()
+}
+
+fn accept(metadata: &PacketMetadata) -> bool {
+    matches!(metadata.level, Level::Error | Level::Warn | Level::Info) && metadata.is_from_app("wanted_app")
+}
+
+#[test]
+fn test_filter_performance_full_decode_vs_header() {
+    let packets = create_packets();
+    let processor = UdpBatchProcessor::default();
+
+    println!("\n=== UDP预过滤性能测试 ===");
+    println!("数据包数: {}，轮数: {}", PACKET_COUNT, ROUNDS);
+
+    let start = Instant::now();
+    let mut full_accepted = 0;
+    for _ in 0..ROUNDS {
+        full_accepted = packets
+            .iter()
+            .filter(|p| {
+                UdpPacketHelper::decode_packet(p)
+                    .map(|r| accept(&PacketMetadata::from(&r)))
+                    .unwrap_or(false)
+            })
+            .count();
+    }
+    let full_duration = start.elapsed();
+
+    let start = Instant::now();
+    let mut header_accepted = 0;
+    for _ in 0..ROUNDS {
+        header_accepted = processor.filter_packets(&packets, &accept).len();
+    }
+    let header_duration = start.elapsed();
+
+    let total = (PACKET_COUNT * ROUNDS) as f64;
+    println!("完整解码: {:?}，吞吐量: {:.0} 包/秒", full_duration, total / full_duration.as_secs_f64());
+    println!("头部解析: {:?}，吞吐量: {:.0} 包/秒", header_duration, total / header_duration.as_secs_f64());
+    println!("加速比: {:.2}x", full_duration.as_secs_f64() / header_duration.as_secs_f64());
+
+    assert_eq!(full_accepted, header_accepted);
+    assert!(header_accepted > 0);
+}
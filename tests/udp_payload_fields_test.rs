@@ -0,0 +1,84 @@
+//! UDP 发送字段测试：省略目标、模块、文件位置等字段后数据包变小，服务端解码出的记录中这些字段为空，级别和消息保留
+
+use rat_logger::config::{Level, Metadata, Record};
+use rat_logger::test_util::UdpCollector;
+use rat_logger::udp_helper::UdpPacketHelper;
+use rat_logger::{LevelFilter, Logger, LoggerBuilder, PayloadFields, UdpConfig};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn record() -> Record {
+    Record {
+        metadata: Arc::new(Metadata {
+            level: Level::Warn,
+            target: "edge_device::sensors::temperature".to_string(),
+            auth_token: None,
+            app_id: Some("edge".to_string()),
+        }),
+        args: "sensor offline".into(),
+        module_path: Some("edge_device::sensors::temperature::poller".to_string()),
+        file: Some("src/sensors/temperature/poller.rs".to_string()),
+        line: Some(128),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_minimal_payload_is_smaller_and_decodes() {
+    let token = Some("token".to_string());
+    let app_id = Some("edge".to_string());
+    let full = UdpPacketHelper::encode_record_fields(&record(), token.clone(), app_id.clone(), PayloadFields::ALL).unwrap();
+    let minimal = UdpPacketHelper::encode_record_fields(&record(), token, app_id, PayloadFields::MESSAGE).unwrap();
+    let stripped = "edge_device::sensors::temperature".len()
+        + "edge_device::sensors::temperature::poller".len()
+        + "src/sensors/temperature/poller.rs".len()
+        + "edge".len();
+    assert!(full.len() - minimal.len() >= stripped, "完整 {} 字节，精简 {} 字节", full.len(), minimal.len());
+
+    let decoded = UdpPacketHelper::decode_packet(&minimal).unwrap();
+    assert_eq!((decoded.level, decoded.message.as_str()), (Level::Warn, "sensor offline"));
+    assert_eq!(decoded.target, "");
+    assert_eq!((decoded.module_path, decoded.file, decoded.line), (None, None, None));
+    assert_eq!((decoded.app_id, decoded.hostname, decoded.pid, decoded.binary), (None, None, 0, None));
+    assert_eq!(decoded.auth_token.as_deref(), Some("token"));
+
+    // 服务端转换后的记录同样只有级别和消息
+    let restored = UdpPacketHelper::net_record_to_record(&UdpPacketHelper::decode_packet(&minimal).unwrap());
+    assert_eq!((restored.metadata.level, restored.args.as_str()), (Level::Warn, "sensor offline"));
+    assert!(restored.module_path.is_none() && restored.file.is_none() && restored.line.is_none());
+    assert!(restored.metadata.app_id.is_none() && restored.fields.is_empty());
+}
+
+#[test]
+fn test_payload_fields_set_operations() {
+    let fields = PayloadFields::MESSAGE | PayloadFields::TARGET | PayloadFields::APP_ID;
+    assert!(fields.contains(PayloadFields::TARGET) && !fields.contains(PayloadFields::FILE_LINE));
+    assert_eq!(PayloadFields::default(), PayloadFields::ALL);
+    // 消息不能被去掉
+    assert_eq!(PayloadFields::ALL.without(PayloadFields::ALL), PayloadFields::MESSAGE);
+    assert_eq!(
+        PayloadFields::ALL.without(PayloadFields::MODULE | PayloadFields::FILE_LINE | PayloadFields::HOSTNAME),
+        fields
+    );
+}
+
+#[test]
+fn test_processor_sends_only_selected_fields() {
+    let collector = UdpCollector::bind().unwrap();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(UdpConfig {
+            network_config: collector.network_config(),
+            payload_fields: PayloadFields::MESSAGE | PayloadFields::TARGET,
+            ..Default::default()
+        })
+        .build();
+    logger.log(&record());
+
+    assert!(collector.wait_for(1, Duration::from_secs(5)));
+    let received = collector.records().remove(0);
+    assert_eq!((received.level, received.message.as_str()), (Level::Warn, "sensor offline"));
+    assert_eq!(received.target, "edge_device::sensors::temperature");
+    assert_eq!((received.module_path, received.file, received.line), (None, None, None));
+    assert_eq!((received.app_id, received.hostname), (None, None));
+}
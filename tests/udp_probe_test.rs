@@ -0,0 +1,71 @@
+//! UDP 连通性探测测试：收集器停止应答探测后处理器降级，恢复应答后重新变为健康，探测数据报不计入记录
+
+mod common;
+
+use rat_logger::test_util::UdpCollector;
+use rat_logger::{DiagnosticEvent, LevelFilter, Logger, LoggerBuilder, UdpConfig};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_probe_status_follows_collector_answers() {
+    let collector = UdpCollector::bind().unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(UdpConfig {
+            network_config: collector.network_config(),
+            probe_interval_ms: 30,
+            expect_ack: true,
+            probe_timeout_ms: 200,
+            max_failed_probes: 2,
+            ..Default::default()
+        })
+        .with_error_callback(move |event| sink.lock().push(event.clone()))
+        .build();
+    let stats = logger.runtime_stats().unwrap();
+    let degraded_events = || events.lock().iter().filter(|e| matches!(e, DiagnosticEvent::ProcessorDegraded { .. })).count();
+    let healthy_events = || events.lock().iter().filter(|e| matches!(e, DiagnosticEvent::ProcessorHealthy { .. })).count();
+
+    // 收集器应答时保持健康
+    assert!(common::wait_until(Duration::from_secs(2), || collector.handle().probes() >= 5));
+    assert_eq!(degraded_events(), 0);
+    assert_eq!(stats.degraded(), 0);
+
+    // 停止应答：连续两次探测超时后降级
+    collector.set_answer_probes(false);
+    assert!(common::wait_until(Duration::from_secs(2), || degraded_events() == 1), "{:?}", events.lock());
+    assert_eq!(stats.degraded(), 1);
+    assert!(matches!(
+        events.lock()[0],
+        DiagnosticEvent::ProcessorDegraded { processor: "udp_processor", failed_probes: 2 }
+    ));
+
+    // 恢复应答：首次成功即恢复健康
+    collector.set_answer_probes(true);
+    assert!(common::wait_until(Duration::from_secs(2), || healthy_events() == 1), "{:?}", events.lock());
+    assert_eq!(stats.degraded(), 0);
+    assert_eq!(degraded_events(), 1);
+
+    // 探测数据报不会被当作记录或无效数据报
+    logger.log(&rat_logger::config::Record {
+        args: "after recovery".into(),
+        ..Default::default()
+    });
+    assert!(collector.wait_for(1, Duration::from_secs(2)));
+    assert_eq!(collector.messages(), ["after recovery"]);
+    assert_eq!(collector.handle().invalid(), 0);
+}
+
+#[test]
+fn test_probe_config_validation() {
+    let probing = UdpConfig { probe_interval_ms: 1000, ..Default::default() };
+    assert!(probing.validate().is_ok());
+    assert!(UdpConfig { probe_timeout_ms: 0, ..probing.clone() }.validate().is_err());
+    assert!(UdpConfig { max_failed_probes: 0, ..probing }.validate().is_err());
+    // 不探测时不检查探测参数
+    assert!(UdpConfig { probe_timeout_ms: 0, ..Default::default() }.validate().is_ok());
+}
@@ -0,0 +1,111 @@
+//! UDP 中继测试：中继收到的数据包经 `forward_net_packet` 原样转发到收集器，只交给 UDP 处理器
+
+mod common;
+
+use rat_logger::config::{NetworkConfig, Record};
+use rat_logger::handler::term::TermConfig;
+use rat_logger::test_util::UdpCollector;
+use rat_logger::udp_helper::{UdpPacketHelper, MAX_DATAGRAM_SIZE};
+use rat_logger::{Level, LevelFilter, Logger, LoggerBuilder, UdpConfig};
+use parking_lot::Mutex;
+use std::net::UdpSocket;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 从中继套接字接收数据报并拆分为数据包，直到收到 `n` 个数据包
+fn receive_packets(socket: &UdpSocket, n: usize) -> Vec<Vec<u8>> {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+    let mut packets = Vec::new();
+    while packets.len() < n && Instant::now() < deadline {
+        let Ok(len) = socket.recv(&mut buf) else {
+            continue;
+        };
+        let datagram = &buf[..len];
+        if UdpPacketHelper::is_batch(datagram) {
+            packets.extend(UdpPacketHelper::split_batch(datagram).unwrap().into_iter().map(<[u8]>::to_vec));
+        } else {
+            packets.push(datagram.to_vec());
+        }
+    }
+    packets
+}
+
+#[test]
+fn test_relay_forwards_packets_unchanged() {
+    let collector = UdpCollector::bind().unwrap();
+    let relay_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    relay_socket.set_read_timeout(Some(Duration::from_millis(20))).unwrap();
+    let relay_addr = relay_socket.local_addr().unwrap();
+
+    // 边缘节点按批量发送到中继
+    let edge = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(UdpConfig {
+            network_config: NetworkConfig {
+                server_addr: relay_addr.ip().to_string(),
+                server_port: relay_addr.port(),
+                auth_token: "edge-token".to_string(),
+                app_id: "edge-node".to_string(),
+            },
+            batch_size: 8,
+            ..Default::default()
+        })
+        .build();
+
+    // 中继的终端处理器不应收到转发的数据包
+    let terminal_records = Arc::new(Mutex::new(Vec::new()));
+    let sink = terminal_records.clone();
+    let relay = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            on_record: Some(Arc::new(move |record: &Record| sink.lock().push(record.args.to_string()))),
+            ..Default::default()
+        })
+        .add_udp_with_config(UdpConfig {
+            network_config: NetworkConfig { app_id: "relay".to_string(), ..collector.network_config() },
+            batch_size: 8,
+            ..Default::default()
+        })
+        .build();
+
+    for i in 0..20 {
+        edge.log(&common::record(Level::Info, "edge", format!("edge message {}", i)));
+    }
+    edge.flush_sync(2000).unwrap();
+
+    let packets = receive_packets(&relay_socket, 20);
+    assert_eq!(packets.len(), 20);
+    for packet in &packets {
+        relay.forward_net_packet(packet).unwrap();
+    }
+    relay.flush_sync(2000).unwrap();
+
+    assert!(collector.wait_for(20, Duration::from_secs(2)));
+    assert_eq!(collector.packets(), packets, "转发的数据包应与收到的逐字节一致");
+    let records = collector.records();
+    assert!(records.iter().all(|r| r.app_id.as_deref() == Some("edge-node") && r.auth_token.as_deref() == Some("edge-token")));
+    assert_eq!(records[19].message, "edge message 19");
+    assert!(terminal_records.lock().is_empty());
+}
+
+#[test]
+fn test_forward_rejects_invalid_packets_and_missing_udp() {
+    let collector = UdpCollector::bind().unwrap();
+    let relay = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(UdpConfig { network_config: collector.network_config(), ..Default::default() })
+        .build();
+
+    assert!(relay.forward_net_packet(b"not a packet").is_err());
+    // 只检查头部：消息体被截断的数据包能被发现
+    let mut truncated = UdpPacketHelper::encode_record(&common::record(Level::Info, "edge", "x".repeat(200)), None, None).unwrap();
+    truncated.truncate(truncated.len() - 150);
+    assert!(relay.forward_net_packet(&truncated).is_err());
+
+    // 没有 UDP 处理器时无处转发
+    let packet = UdpPacketHelper::encode_record(&common::record(Level::Info, "edge", "lost"), None, None).unwrap();
+    let terminal_only = LoggerBuilder::new().with_level(LevelFilter::Info).add_terminal_with_config(TermConfig::default()).build();
+    assert!(terminal_only.forward_net_packet(&packet).is_err());
+}
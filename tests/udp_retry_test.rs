@@ -0,0 +1,62 @@
+//! UDP重试耗尽测试：发往未监听端口的记录通过错误回调上报，并计入丢弃统计
+
+mod common;
+
+use rat_logger::{LoggerBuilder, LevelFilter, Level, Logger, NetworkConfig, UdpConfig, RetryPolicy, DiagnosticEvent};
+use std::net::UdpSocket;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+/// 获取一个当前没有监听者的本地端口
+fn unbound_port() -> u16 {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.local_addr().unwrap().port()
+}
+
+#[test]
+fn test_exhausted_sends_reach_error_callback() {
+    let (tx, rx) = channel();
+    let tx = std::sync::Mutex::new(tx);
+
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(UdpConfig {
+            network_config: NetworkConfig {
+                server_addr: "127.0.0.1".to_string(),
+                server_port: unbound_port(),
+                ..Default::default()
+            },
+            retry: RetryPolicy {
+                max_attempts: 1,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+                jitter: 0.0,
+            },
+            ..Default::default()
+        })
+        .with_error_callback(move |event| {
+            let _ = tx.lock().unwrap().send(event.clone());
+        })
+        .build();
+
+    // 端口不可达由后续发送报告（ICMP），持续发送直到出现失败
+    let mut failure = None;
+    for i in 0..50 {
+        logger.log(&common::record(Level::Info, "udp_retry", format!("lost #{}", i)));
+        logger.flush();
+        if let Ok(event) = rx.recv_timeout(Duration::from_millis(20)) {
+            failure = Some(event);
+            break;
+        }
+    }
+
+    match failure.expect("发送到未监听端口应当触发错误回调") {
+        DiagnosticEvent::SendFailed { processor, attempts, dropped, .. } => {
+            assert_eq!(processor, "udp_processor");
+            assert_eq!(attempts, 1);
+            assert_eq!(dropped, 1);
+        }
+        other => panic!("意外的诊断事件: {:?}", other),
+    }
+    assert!(logger.stats().dropped() >= 1);
+}
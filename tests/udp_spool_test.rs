@@ -0,0 +1,69 @@
+//! UDP 离线缓存测试：发送失败的记录写入缓存分段，重启后连接可用时按原顺序重放，之后的新记录排在重放记录之后
+
+mod common;
+
+use rat_logger::core::LoggerCore;
+use rat_logger::test_util::UdpCollector;
+use rat_logger::{Level, LevelFilter, Logger, LoggerBuilder, NetworkConfig, RetryPolicy, SpoolConfig, UdpConfig};
+use std::path::Path;
+use std::time::Duration;
+
+fn log(logger: &LoggerCore, i: usize) {
+    logger.log(&common::record(Level::Info, "udp_spool", format!("record {:02}", i)));
+}
+
+fn udp_config(network_config: NetworkConfig, spool_dir: &Path) -> UdpConfig {
+    UdpConfig {
+        network_config,
+        retry: RetryPolicy { max_attempts: 1, ..Default::default() },
+        spool: Some(SpoolConfig { dir: spool_dir.to_path_buf(), ..Default::default() }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_failed_sends_replay_in_order_after_restart() {
+    let spool_dir = common::test_dir("udp_spool", "failed_sends");
+
+    // 未开启广播的套接字向广播地址发送总是失败
+    let unreachable = NetworkConfig {
+        server_addr: "255.255.255.255".to_string(),
+        server_port: 9,
+        ..Default::default()
+    };
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(udp_config(unreachable, &spool_dir))
+        .build();
+    for i in 0..20 {
+        log(&logger, i);
+    }
+    logger.flush_sync(5000).unwrap();
+
+    let stats = logger.runtime_stats().unwrap();
+    let spool = stats.spool().unwrap();
+    assert!(spool.segments > 0 && spool.bytes > 0 && spool.oldest_timestamp_ms.is_some(), "{:?}", spool);
+    assert_eq!((stats.dropped(), stats.spool_dropped()), (0, 0));
+    drop(logger);
+
+    // 重启后连接可用：先重放缓存中的记录，再发送新记录
+    let collector = UdpCollector::bind().unwrap();
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_udp_with_config(udp_config(collector.network_config(), &spool_dir))
+        .build();
+    for i in 20..25 {
+        log(&logger, i);
+    }
+    logger.flush_sync(5000).unwrap();
+    assert!(collector.wait_for(25, Duration::from_secs(5)));
+
+    let messages: Vec<String> = collector.records().into_iter().map(|record| record.message).collect();
+    assert_eq!(messages, (0..25).map(|i| format!("record {:02}", i)).collect::<Vec<_>>());
+    let spool = logger.runtime_stats().unwrap().spool().unwrap();
+    assert_eq!((spool.segments, spool.bytes, spool.oldest_timestamp_ms), (0, 0, None));
+    assert_eq!(std::fs::read_dir(&spool_dir).unwrap().count(), 0);
+
+    drop(logger);
+    let _ = std::fs::remove_dir_all(&spool_dir);
+}
@@ -0,0 +1,50 @@
+//! Windows 控制台输出测试：标准输出被重定向时终端处理器仍写入原始 UTF-8 字节
+//!
+//! 测试以子进程模式重新启动自身，子进程的标准输出是管道而不是控制台。
+#![cfg(windows)]
+
+mod common;
+
+use rat_logger::handler::term::TermConfig;
+use rat_logger::{FormatConfig, Level, LevelFilter, Logger, LoggerBuilder};
+use std::process::Command;
+
+const CHILD_ENV: &str = "RAT_LOGGER_WINDOWS_CONSOLE_CHILD";
+const MESSAGE: &str = "启动完成 ok 🚀";
+
+fn run_child() {
+    let logger = LoggerBuilder::new()
+        .with_level(LevelFilter::Info)
+        .add_terminal_with_config(TermConfig {
+            enable_color: false,
+            format: Some(FormatConfig {
+                format_template: "CONSOLE|{level}|{message}".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .build();
+
+    logger.log(&common::record(Level::Info, "windows_console", MESSAGE));
+    logger.flush_sync(2000).unwrap();
+}
+
+#[test]
+fn test_redirected_output_keeps_utf8_bytes() {
+    if std::env::var(CHILD_ENV).is_ok() {
+        run_child();
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "test_redirected_output_keeps_utf8_bytes", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "子进程失败: {}", String::from_utf8_lossy(&output.stderr));
+
+    // 输出是有效的 UTF-8，不是按代码页转换后的字节
+    let stdout = String::from_utf8(output.stdout).expect("重定向的输出应为 UTF-8");
+    let expected = format!("CONSOLE|INFO|{}", MESSAGE);
+    assert!(stdout.lines().any(|line| line.ends_with(&expected)), "输出: {}", stdout);
+}